@@ -285,6 +285,9 @@ def external_crates_repository(name, static_openssl, cargo_lockfile, lockfile):
             "crossbeam-utils": crate.spec(
                 version = "^0.8.11",
             ),
+            "cryptoki": crate.spec(
+                version = "^0.4.1",
+            ),
             "csv": crate.spec(
                 version = "^1.1",
             ),
@@ -381,6 +384,9 @@ def external_crates_repository(name, static_openssl, cargo_lockfile, lockfile):
             "h2": crate.spec(
                 version = "^0.3.14",
             ),
+            "handlebars": crate.spec(
+                version = "^4.3.6",
+            ),
             "hashlink": crate.spec(
                 version = "^0.8.0",
             ),
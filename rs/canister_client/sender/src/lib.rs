@@ -8,7 +8,7 @@ use ic_crypto_internal_types::sign::eddsa::ed25519::SecretKey as Ed25519SecretKe
 use ic_crypto_utils_basic_sig::conversions::Ed25519PemParseError;
 use ic_crypto_utils_basic_sig::conversions::Ed25519SecretKeyConversions;
 use ic_types::crypto::DOMAIN_IC_REQUEST;
-use ic_types::messages::MessageId;
+use ic_types::messages::{MessageId, SignedDelegation};
 use rand::{CryptoRng, Rng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 use std::{error::Error, sync::Arc};
@@ -120,6 +120,16 @@ pub enum Sender {
         /// Function that signs the message id
         sign: SignMessageId,
     },
+    /// The sender is authenticated via a chain of delegations from its key to
+    /// a session key, which signs the messages. This allows the sender's key,
+    /// e.g. on an HSM, to be used only once to sign the delegation.
+    Delegation {
+        /// DER encoded public key of the sender
+        pub_key: Vec<u8>,
+        /// The delegations from `pub_key` to the public key of `session_key`
+        delegations: Vec<SignedDelegation>,
+        session_key: Ed25519KeyPair,
+    },
 }
 
 impl Sender {
@@ -131,6 +141,15 @@ impl Sender {
         Sender::SigKeys(SigKeys::Ed25519(keys))
     }
 
+    pub fn from_secp256k1_key_pair(keys: Secp256k1KeyPair) -> Self {
+        Sender::SigKeys(SigKeys::EcdsaSecp256k1(keys))
+    }
+
+    /// Parses the key pair of the sender from a PEM file, see [SigKeys::from_pem].
+    pub fn from_pem(pem: &str) -> Result<Self, &'static str> {
+        SigKeys::from_pem(pem).map(Sender::SigKeys)
+    }
+
     pub fn from_secp256k1_keys(
         sk_bytes: &[u8],
         pk_bytes: &[u8],
@@ -161,7 +180,9 @@ impl Sender {
                     PrincipalId::new_self_authenticating(&key_pair.pk.serialize_der())
                 }
             },
-            Self::ExternalHsm { pub_key, .. } => PrincipalId::new_self_authenticating(pub_key),
+            Self::ExternalHsm { pub_key, .. } | Self::Delegation { pub_key, .. } => {
+                PrincipalId::new_self_authenticating(pub_key)
+            }
             Self::Anonymous => PrincipalId::new_anonymous(),
             Self::PrincipalId(id) => *id,
             Self::Node { pub_key, .. } => {
@@ -191,6 +212,7 @@ impl Sender {
                 SigKeys::EcdsaSecp256k1(key_pair) => Ok(Some(key_pair.sk.sign_message(&msg))),
             },
             Self::ExternalHsm { sign, .. } => sign(&msg).map(Some),
+            Self::Delegation { session_key, .. } => Ok(Some(session_key.sign(&msg).to_vec())),
             Self::Anonymous => Ok(None),
             Self::PrincipalId(_) => Ok(None),
             Self::Node { .. } => unreachable!("Wrong case of agent.sign()"),
@@ -205,12 +227,22 @@ impl Sender {
                 }
                 SigKeys::EcdsaSecp256k1(key_pair) => Some(key_pair.pk.serialize_der()),
             },
-            Self::ExternalHsm { pub_key, .. } => Some(pub_key.clone()),
+            Self::ExternalHsm { pub_key, .. } | Self::Delegation { pub_key, .. } => {
+                Some(pub_key.clone())
+            }
             Self::Anonymous => None,
             Self::PrincipalId(_) => None,
             Self::Node { pub_key, .. } => Some(ed25519_public_key_to_der(pub_key.clone())),
         }
     }
+
+    /// The delegations to attach to the messages of the sender, if any.
+    pub fn sender_delegation(&self) -> Option<Vec<SignedDelegation>> {
+        match self {
+            Self::Delegation { delegations, .. } => Some(delegations.clone()),
+            _ => None,
+        }
+    }
 }
 
 /// This is a minimal implementation of DER-encoding for Ed25519, as the keys
@@ -3,15 +3,26 @@ use ic_crypto_secrets_containers::SecretVec;
 
 // Parses pem files to secp256k1 structs.
 impl Secp256k1KeyPair {
-    /// Parses a secp256k1 key pair from PEM.
+    /// Parses a secp256k1 key pair from PEM, either in RFC 5915 format, as
+    /// written by `openssl ecparam -genkey` and `dfx identity new`, or in
+    /// PKCS#8 format, as written by `openssl genpkey`.
     pub fn from_pem(pem: &str) -> Result<Self, String> {
+        use ic_crypto_utils_basic_sig::conversions::pem::pem_to_der;
         // Note: The SecretVec zeros the internal key after use.  DO NOT REMOVE.
-        let mut der =
-            ic_crypto_utils_basic_sig::conversions::pem::pem_to_der(pem, "EC PRIVATE KEY")
-                .map_err(|err| format!("Failed to convert PEM to DER: {err:?}"))?;
-
+        if let Ok(mut der) = pem_to_der(pem, "EC PRIVATE KEY") {
+            let der = SecretVec::new_and_zeroize_argument(&mut der);
+            return Self::from_der(der.expose_secret());
+        }
+        let mut der = pem_to_der(pem, "PRIVATE KEY")
+            .map_err(|err| format!("Failed to convert PEM to DER: {err:?}"))?;
         let der = SecretVec::new_and_zeroize_argument(&mut der);
-        Self::from_der(der.expose_secret())
+        Self::from_pkcs8_der(der.expose_secret())
+    }
+
+    /// Serializes the key pair to PEM in PKCS#8 format, which [Self::from_pem]
+    /// and standard tooling read.
+    pub fn to_pem(&self) -> String {
+        self.sk.serialize_pkcs8_pem()
     }
 
     /// Parses a secp256k1 key pair from DER in RFC 5915 format
@@ -23,6 +34,16 @@ impl Secp256k1KeyPair {
 
         Ok(Secp256k1KeyPair { sk, pk })
     }
+
+    /// Parses a secp256k1 key pair from DER in PKCS#8 format
+    pub fn from_pkcs8_der(der: &[u8]) -> Result<Self, String> {
+        let sk = ic_crypto_ecdsa_secp256k1::PrivateKey::deserialize_pkcs8_der(der)
+            .map_err(|e| format!("{:?}", e))?;
+
+        let pk = sk.public_key();
+
+        Ok(Secp256k1KeyPair { sk, pk })
+    }
 }
 
 #[cfg(test)]
@@ -36,6 +57,27 @@ mod tests {
             .expect("secp256k1 failed to read key");
     }
     #[test]
+    fn should_parse_key_from_pem_with_ec_parameters() {
+        Secp256k1KeyPair::from_pem(vectors::SAMPLE_SECP256K1_WITH_EC_PARAMETERS_PEM)
+            .expect("secp256k1 failed to read key following its EC parameters");
+    }
+    #[test]
+    fn should_parse_key_from_pkcs8_pem() {
+        let key_pair = Secp256k1KeyPair::from_pem(vectors::SAMPLE_SECP256K1_PKCS8_PEM)
+            .expect("secp256k1 failed to read PKCS#8 key");
+        assert_eq!(
+            key_pair.get_public_key().serialize_sec1(false)[..5],
+            [0x04, 0x5c, 0x1c, 0x98, 0x57]
+        );
+    }
+    #[test]
+    fn should_roundtrip_generated_key_through_pem() {
+        let key_pair = Secp256k1KeyPair::generate(&mut rand::thread_rng());
+        let parsed = Secp256k1KeyPair::from_pem(&key_pair.to_pem())
+            .expect("secp256k1 failed to read its own PEM");
+        assert_eq!(parsed.get_public_key(), key_pair.get_public_key());
+    }
+    #[test]
     fn should_fail_to_read_ed25519_key() {
         Secp256k1KeyPair::from_pem(vectors::SAMPLE_ED25519_PEM)
             .map(|_| ())
@@ -242,7 +242,7 @@ fn sign_submit(
         content,
         sender_pubkey: pub_key_der,
         sender_sig,
-        sender_delegation: None,
+        sender_delegation: sender.sender_delegation(),
     };
     Ok((envelope, message_id))
 }
@@ -264,7 +264,7 @@ fn sign_read_state(
         content,
         sender_pubkey: pub_key_der,
         sender_sig,
-        sender_delegation: None,
+        sender_delegation: sender.sender_delegation(),
     })
 }
 
@@ -285,7 +285,7 @@ fn sign_query(
         content,
         sender_pubkey: pub_key_der,
         sender_sig,
-        sender_delegation: None,
+        sender_delegation: sender.sender_delegation(),
     })
 }
 
@@ -132,7 +132,38 @@ pub const SAMPLE_CONFIG: &str = r#"
         //   CspVault is an internal structure of the replica process.
         // - EXAMPLE: csp_vault_type: { unix_socket: "/some/path/to/socket" },
         //   CspVault is run as a separate process, which can be reached via a Unix socket.
+        // - EXAMPLE: csp_vault_type: { pkcs11: { module: "/usr/lib/softhsm/libsofthsm2.so", slot: 0, pin_source: { file: "/run/ic-node/hsm-pin" } } },
+        //   CspVault is an internal structure of the replica process that keeps the node
+        //   secret keys on a PKCS#11 token (e.g., an HSM).
+        // - EXAMPLE: csp_vault_type: { tcp: { address: "vault.example.com:4321", tls: { certificate: "/run/ic-node/vault-client.pem", private_key: "/run/ic-node/vault-client.key", ca_certificate: "/run/ic-node/vault-ca.pem" } } },
+        //   CspVault is run as a separate process, e.g., in another VM, which is reached via TCP
+        //   with mutually authenticated TLS.
         csp_vault_type: { unix_socket: "/some/path/to/socket" },
+        // The path of a socket via which a CspVault of type unix_socket is reached if it
+        // cannot be reached via the socket above. Unset by default.
+        // - EXAMPLE: csp_vault_secondary_socket_path: "/some/other/path/to/socket",
+        // The entropy source of the CspVault.
+        // Alternatives:
+        // - EXAMPLE: csp_rng_source: "getrandom",
+        //   The operating system's RNG. This is the default.
+        // - EXAMPLE: csp_rng_source: { hw_rng: "/dev/hwrng" },
+        //   A hardware RNG device.
+        // - EXAMPLE: csp_rng_source: { seeded_drbg: 42 },
+        //   A deterministic RNG with a fixed seed. Only for reproducible test builds, and
+        //   only available if built with the `seeded_drbg_rng_source` feature.
+        csp_rng_source: "getrandom",
+        // The algorithms allowed in the TLS handshakes of the node, in the order of
+        // preference. By default, all supported algorithms are allowed.
+        // - EXAMPLE: tls_policy: { cipher_suites: ["aes_256_gcm_sha384"], key_exchange_groups: ["secp384r1"] },
+        tls_policy: { cipher_suites: ["aes_256_gcm_sha384", "aes_128_gcm_sha256"], key_exchange_groups: ["x25519", "secp256r1", "secp384r1"] },
+        // The recovery keys to which the CspVault escrows the node secret keys, as hex-encoded
+        // compressed secp256k1 points, and the number of their holders required for the
+        // recovery. Unset by default, in which case the vault refuses to escrow the keys.
+        // Optionally, the backup public key of the node operator to which the vault seals
+        // backups of the node keys, and the file with the backup private key, from which the
+        // vault reads the key to restore a backup on replacement hardware.
+        // - EXAMPLE: key_escrow: { recovery_public_keys: ["02...", "03...", "02..."], threshold: 2 },
+        // - EXAMPLE: key_escrow: { recovery_public_keys: ["02..."], threshold: 1, backup_public_key: "03...", backup_private_key_path: "/run/ic-node/backup-key" },
     },
     // ========================================
     // Configuration of the message scheduling.
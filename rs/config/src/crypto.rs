@@ -32,6 +32,30 @@ pub enum CspVaultType {
         )
     )]
     UnixSocket(PathBuf),
+    /// A vault running in the replica process that keeps the node secret keys
+    /// on the token in `slot` of the PKCS#11 `module`, instead of in
+    /// `sks_data.pb`. This only protects the keys at rest: they are read from
+    /// the token and used in the memory of the replica process.
+    Pkcs11 {
+        #[cfg_attr(
+            test,
+            proptest(strategy = "any::<String>().prop_map(|x| PathBuf::from(x))")
+        )]
+        module: PathBuf,
+        slot: u64,
+        pin_source: Pkcs11PinSource,
+    },
+    /// A vault running in a separate process, e.g., in another VM or in a
+    /// confidential-compute enclave, which is reached via TCP at `address`
+    /// (`host:port`) over mutually authenticated TLS. For the
+    /// `CspVault`-server, `address` is the address to listen at. The vault is
+    /// served via gRPC, whose `CspVaultTunnel` service carries the same
+    /// protocol as a Unix domain socket, so that both transports share one
+    /// RPC interface.
+    Tcp {
+        address: String,
+        tls: CspVaultTlsConfig,
+    },
 }
 
 impl Default for CspVaultType {
@@ -40,6 +64,181 @@ impl Default for CspVaultType {
     }
 }
 
+/// The source of the user PIN for logging in to a PKCS#11 token.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(test, derive(Arbitrary))]
+pub enum Pkcs11PinSource {
+    /// A file containing the PIN. A trailing newline is ignored.
+    #[cfg_attr(
+        test,
+        proptest(
+            strategy = "any::<String>().prop_map(|x| Pkcs11PinSource::File(PathBuf::from(x)))"
+        )
+    )]
+    File(PathBuf),
+    /// An environment variable containing the PIN.
+    EnvironmentVariable(String),
+}
+
+/// The TLS configuration of one end of a TCP connection to a `CspVault`.
+///
+/// Both ends authenticate with their `certificate` and `private_key`, and only
+/// accept a peer with a certificate issued by the CA with `ca_certificate`.
+/// All files are PEM-encoded.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub struct CspVaultTlsConfig {
+    #[cfg_attr(
+        test,
+        proptest(strategy = "any::<String>().prop_map(|x| PathBuf::from(x))")
+    )]
+    pub certificate: PathBuf,
+    #[cfg_attr(
+        test,
+        proptest(strategy = "any::<String>().prop_map(|x| PathBuf::from(x))")
+    )]
+    pub private_key: PathBuf,
+    #[cfg_attr(
+        test,
+        proptest(strategy = "any::<String>().prop_map(|x| PathBuf::from(x))")
+    )]
+    pub ca_certificate: PathBuf,
+}
+
+/// The entropy source of the CSPRNG used by the `CspVault`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(test, derive(Arbitrary))]
+#[non_exhaustive]
+pub enum CspRngSource {
+    /// The operating system's RNG, via the `getrandom` system call.
+    Getrandom,
+    /// A hardware RNG device, such as `/dev/hwrng`, which seeds a DRBG
+    /// together with the operating system's RNG. The DRBG is reseeded from
+    /// both after at most 64 KiB of output or one minute, whichever comes
+    /// first, so the device has to stay readable for the life of the vault.
+    #[cfg_attr(
+        test,
+        proptest(strategy = "any::<String>().prop_map(|x| CspRngSource::HwRng(PathBuf::from(x)))")
+    )]
+    HwRng(PathBuf),
+    /// A deterministic RNG seeded with the given seed, yielding the same keys
+    /// on every run. As anyone knowing the seed can derive the node's secret
+    /// keys, it is only available in builds with the `seeded_drbg_rng_source`
+    /// feature, which MUST only be enabled for reproducible test builds.
+    #[cfg(any(test, feature = "seeded_drbg_rng_source"))]
+    SeededDrbg(u64),
+}
+
+impl Default for CspRngSource {
+    fn default() -> Self {
+        CspRngSource::Getrandom
+    }
+}
+
+/// The recovery keys to which the `CspVault` escrows the node secret keys, and
+/// the backup key of the node operator to which it seals backups of the node
+/// keys.
+///
+/// The vault only ever encrypts the node secret keys to the keys configured
+/// here, never to keys given by its callers, so that a compromised replica
+/// cannot exfiltrate the keys by escrowing them to keys it controls. For the
+/// same reason, the vault reads the private backup key for restoring a backup
+/// itself, instead of being given the key.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub struct KeyEscrowConfig {
+    /// The hex-encoded recovery public keys, which are points on secp256k1
+    /// in compressed SEC1 encoding. The index of a key in the list is the
+    /// index of its holder for the recovery.
+    pub recovery_public_keys: Vec<String>,
+    /// The number of holders of recovery private keys required to recover the
+    /// node secret keys.
+    pub threshold: usize,
+    /// The hex-encoded backup public key of the node operator, a point on
+    /// secp256k1 in compressed SEC1 encoding. Without it, the vault refuses to
+    /// export sealed backups.
+    #[serde(default)]
+    pub backup_public_key: Option<String>,
+    /// The file with the hex-encoded backup private key, a big-endian
+    /// secp256k1 scalar, with which the vault unseals a backup to restore it,
+    /// e.g., on replacement hardware. It is only needed for the restore.
+    #[serde(default)]
+    #[cfg_attr(
+        test,
+        proptest(
+            strategy = "proptest::option::of(any::<String>().prop_map(|x| PathBuf::from(x)))"
+        )
+    )]
+    pub backup_private_key_path: Option<PathBuf>,
+}
+
+/// Restricts the algorithms used in the TLS handshakes of the node to a
+/// subset of the supported ones, e.g., to meet compliance requirements.
+///
+/// The algorithms are listed in the order of preference. The protocol version
+/// is not configurable: the handshakes always use TLS 1.3, the only version
+/// the IC supports.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub struct TlsPolicy {
+    pub cipher_suites: Vec<TlsCipherSuite>,
+    pub key_exchange_groups: Vec<TlsKeyExchangeGroup>,
+}
+
+impl Default for TlsPolicy {
+    /// Allows all supported algorithms.
+    fn default() -> Self {
+        Self {
+            cipher_suites: vec![
+                TlsCipherSuite::Aes256GcmSha384,
+                TlsCipherSuite::Aes128GcmSha256,
+            ],
+            key_exchange_groups: vec![
+                TlsKeyExchangeGroup::X25519,
+                TlsKeyExchangeGroup::Secp256r1,
+                TlsKeyExchangeGroup::Secp384r1,
+            ],
+        }
+    }
+}
+
+impl TlsPolicy {
+    /// Returns an error if the policy does not allow any cipher suite or any
+    /// key exchange group, so that no handshake could succeed.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.cipher_suites.is_empty() {
+            return Err("the TLS policy allows no cipher suites".to_string());
+        }
+        if self.key_exchange_groups.is_empty() {
+            return Err("the TLS policy allows no key exchange groups".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A TLS 1.3 cipher suite.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub enum TlsCipherSuite {
+    #[serde(rename = "aes_256_gcm_sha384")]
+    Aes256GcmSha384,
+    #[serde(rename = "aes_128_gcm_sha256")]
+    Aes128GcmSha256,
+}
+
+/// A group for the (EC)DHE key exchange in TLS handshakes.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(test, derive(Arbitrary))]
+pub enum TlsKeyExchangeGroup {
+    X25519,
+    Secp256r1,
+    Secp384r1,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default)]
 #[cfg_attr(test, derive(Arbitrary))]
@@ -59,6 +258,25 @@ pub struct CryptoConfig {
     )]
     pub crypto_root: PathBuf,
     pub csp_vault_type: CspVaultType,
+    /// The path of a socket via which the vault is reached if it cannot be
+    /// reached via the socket of a vault of type `UnixSocket`, e.g., because
+    /// the vault process is being restarted. Ignored for other vault types.
+    #[cfg_attr(
+        test,
+        proptest(
+            strategy = "proptest::option::of(any::<String>().prop_map(|x| PathBuf::from(x)))"
+        )
+    )]
+    pub csp_vault_secondary_socket_path: Option<PathBuf>,
+    /// The entropy source of the `CspVault`. For a vault of type `UnixSocket`
+    /// it is the one in the config of the `CspVault`-server that is used.
+    pub csp_rng_source: CspRngSource,
+    /// The algorithms allowed in the TLS handshakes of the node.
+    pub tls_policy: TlsPolicy,
+    /// The recovery keys of the `CspVault`, without which it refuses to escrow
+    /// the node secret keys. As for `csp_rng_source`, it is the one in the
+    /// config of the `CspVault`-server that is used for a remote vault.
+    pub key_escrow: Option<KeyEscrowConfig>,
 }
 
 impl Default for CryptoConfig {
@@ -66,6 +284,10 @@ impl Default for CryptoConfig {
         Self {
             crypto_root: PathBuf::from(CRYPTO_ROOT_DEFAULT_PATH),
             csp_vault_type: CspVaultType::InReplica,
+            csp_vault_secondary_socket_path: None,
+            csp_rng_source: CspRngSource::default(),
+            tls_policy: TlsPolicy::default(),
+            key_escrow: None,
         }
     }
 }
@@ -76,6 +298,10 @@ impl CryptoConfig {
         Self {
             crypto_root,
             csp_vault_type: CspVaultType::InReplica,
+            csp_vault_secondary_socket_path: None,
+            csp_rng_source: CspRngSource::default(),
+            tls_policy: TlsPolicy::default(),
+            key_escrow: None,
         }
     }
 
@@ -85,6 +311,10 @@ impl CryptoConfig {
         Self {
             crypto_root,
             csp_vault_type: CspVaultType::UnixSocket(socket_path),
+            csp_vault_secondary_socket_path: None,
+            csp_rng_source: CspRngSource::default(),
+            tls_policy: TlsPolicy::default(),
+            key_escrow: None,
         }
     }
 
@@ -204,6 +434,147 @@ mod tests {
         }
     }
 
+    #[test]
+    fn rng_source_deserializes_and_defaults_to_getrandom() {
+        let config: CryptoConfig = json5::from_str("{ crypto_root: '/tmp/ic_crypto' }").unwrap();
+        assert_eq!(config.csp_rng_source, CspRngSource::Getrandom);
+
+        for (source, expected) in [
+            ("'getrandom'", CspRngSource::Getrandom),
+            (
+                "{ hw_rng: '/dev/hwrng' }",
+                CspRngSource::HwRng(PathBuf::from("/dev/hwrng")),
+            ),
+            ("{ seeded_drbg: 42 }", CspRngSource::SeededDrbg(42)),
+        ] {
+            let config: CryptoConfig =
+                json5::from_str(&format!("{{ csp_rng_source: {} }}", source)).unwrap();
+            assert_eq!(config.csp_rng_source, expected);
+        }
+    }
+
+    #[test]
+    fn pkcs11_vault_type_deserializes() {
+        for (pin_source, expected_pin_source) in [
+            (
+                "{ file: '/run/hsm-pin' }",
+                Pkcs11PinSource::File(PathBuf::from("/run/hsm-pin")),
+            ),
+            (
+                "{ environment_variable: 'HSM_PIN' }",
+                Pkcs11PinSource::EnvironmentVariable("HSM_PIN".to_string()),
+            ),
+        ] {
+            let config: CryptoConfig = json5::from_str(&format!(
+                "{{ csp_vault_type: {{ pkcs11: {{ module: '/usr/lib/libpkcs11.so', slot: 1, pin_source: {} }} }} }}",
+                pin_source
+            ))
+            .unwrap();
+            assert_eq!(
+                config.csp_vault_type,
+                CspVaultType::Pkcs11 {
+                    module: PathBuf::from("/usr/lib/libpkcs11.so"),
+                    slot: 1,
+                    pin_source: expected_pin_source,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn tcp_vault_type_deserializes() {
+        let config: CryptoConfig = json5::from_str(
+            "{ csp_vault_type: { tcp: { address: 'vault.example.com:4321', tls: { \
+             certificate: '/run/ic-node/vault-client.pem', \
+             private_key: '/run/ic-node/vault-client.key', \
+             ca_certificate: '/run/ic-node/vault-ca.pem' } } } }",
+        )
+        .unwrap();
+        assert_eq!(
+            config.csp_vault_type,
+            CspVaultType::Tcp {
+                address: "vault.example.com:4321".to_string(),
+                tls: CspVaultTlsConfig {
+                    certificate: PathBuf::from("/run/ic-node/vault-client.pem"),
+                    private_key: PathBuf::from("/run/ic-node/vault-client.key"),
+                    ca_certificate: PathBuf::from("/run/ic-node/vault-ca.pem"),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn key_escrow_deserializes_and_defaults_to_none() {
+        let config: CryptoConfig = json5::from_str("{ crypto_root: '/tmp/ic_crypto' }").unwrap();
+        assert_eq!(config.key_escrow, None);
+
+        let config: CryptoConfig = json5::from_str(
+            "{ key_escrow: { recovery_public_keys: ['02aa', '03bb'], threshold: 2 } }",
+        )
+        .unwrap();
+        assert_eq!(
+            config.key_escrow,
+            Some(KeyEscrowConfig {
+                recovery_public_keys: vec!["02aa".to_string(), "03bb".to_string()],
+                threshold: 2,
+                backup_public_key: None,
+                backup_private_key_path: None,
+            })
+        );
+
+        let config: CryptoConfig = json5::from_str(
+            "{ key_escrow: { recovery_public_keys: ['02aa'], threshold: 1, \
+                backup_public_key: '03cc', backup_private_key_path: '/run/backup-key' } }",
+        )
+        .unwrap();
+        assert_eq!(
+            config.key_escrow,
+            Some(KeyEscrowConfig {
+                recovery_public_keys: vec!["02aa".to_string()],
+                threshold: 1,
+                backup_public_key: Some("03cc".to_string()),
+                backup_private_key_path: Some(PathBuf::from("/run/backup-key")),
+            })
+        );
+    }
+
+    #[test]
+    fn tls_policy_deserializes_and_defaults_to_all_algorithms() {
+        let config: CryptoConfig = json5::from_str("{ crypto_root: '/tmp/ic_crypto' }").unwrap();
+        assert_eq!(config.tls_policy, TlsPolicy::default());
+
+        let config: CryptoConfig = json5::from_str(
+            "{ tls_policy: { cipher_suites: ['aes_256_gcm_sha384'], \
+             key_exchange_groups: ['secp384r1', 'secp256r1'] } }",
+        )
+        .unwrap();
+        assert_eq!(
+            config.tls_policy,
+            TlsPolicy {
+                cipher_suites: vec![TlsCipherSuite::Aes256GcmSha384],
+                key_exchange_groups: vec![
+                    TlsKeyExchangeGroup::Secp384r1,
+                    TlsKeyExchangeGroup::Secp256r1
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn should_reject_tls_policy_without_cipher_suites_or_key_exchange_groups() {
+        assert_eq!(TlsPolicy::default().validate(), Ok(()));
+        let no_cipher_suites = TlsPolicy {
+            cipher_suites: vec![],
+            ..TlsPolicy::default()
+        };
+        assert!(no_cipher_suites.validate().is_err());
+        let no_key_exchange_groups = TlsPolicy {
+            key_exchange_groups: vec![],
+            ..TlsPolicy::default()
+        };
+        assert!(no_key_exchange_groups.validate().is_err());
+    }
+
     #[test]
     fn should_create_path_as_directory() {
         CryptoConfig::run_with_temp_config(|config| assert!(config.crypto_root.is_dir()));
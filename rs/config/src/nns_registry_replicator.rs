@@ -1,5 +1,11 @@
 use serde::{Deserialize, Serialize};
 
+/// The least number of versions the local store can retain when it is
+/// compacted. Readers of the local store poll it every few seconds and look up
+/// versions as old as the registry version of their subnet's latest catch-up
+/// package, which lags far fewer versions behind the latest one.
+pub const MIN_LOCAL_STORE_RETAINED_VERSIONS: u64 = 10_000;
+
 /// Configuration of the NNS Registry Replicator.
 ///
 /// This should eventually replace the registry client configuration. The path
@@ -12,12 +18,20 @@ use serde::{Deserialize, Serialize};
 pub struct Config {
     /// The duration to
     pub poll_delay_duration_ms: u64,
+    /// If set, the history of the local store below the latest version minus
+    /// this number of versions is compacted into a snapshot, once as many new
+    /// versions were replicated. Readers of the local store must not lag behind
+    /// by more versions, hence it must be at least
+    /// [MIN_LOCAL_STORE_RETAINED_VERSIONS].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_store_retained_versions: Option<u64>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             poll_delay_duration_ms: 5000,
+            local_store_retained_versions: None,
         }
     }
 }
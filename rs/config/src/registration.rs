@@ -30,6 +30,13 @@ pub struct Config {
 
     /// If this Sec256k1 PEM is available, use it instead of the HSM.
     pub node_operator_pem: Option<PathBuf>,
+
+    /// If set and no node operator PEM is available, the node is registered
+    /// without attaching the HSM: the orchestrator exports a registration
+    /// request to this directory and waits for the request signed offline by
+    /// the HSM to be imported into it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offline_registration_dir: Option<PathBuf>,
 }
 
 // We allow for the operator to only specify some of the fields while the others
@@ -57,6 +64,7 @@ impl Default for Config {
             nns_url: None,
             nns_pub_key_pem: None,
             node_operator_pem: None,
+            offline_registration_dir: None,
         }
     }
 }
@@ -1061,6 +1061,16 @@ impl BasicSigner<IDkgDealing> for EcdsaPreSignerImpl {
     ) -> CryptoResult<BasicSigOf<IDkgDealing>> {
         self.crypto.sign_basic(message, signer, registry_version)
     }
+
+    fn sign_basic_batch(
+        &self,
+        messages: &[IDkgDealing],
+        signer: NodeId,
+        registry_version: RegistryVersion,
+    ) -> CryptoResult<Vec<BasicSigOf<IDkgDealing>>> {
+        self.crypto
+            .sign_basic_batch(messages, signer, registry_version)
+    }
 }
 
 pub(crate) trait EcdsaTranscriptBuilder {
@@ -19,6 +19,7 @@ type CborMap = std::collections::BTreeMap<serde_cbor::Value, serde_cbor::Value>;
 enum CosePublicKey {
     EcdsaP256Sha256(Vec<u8>),
     RsaPkcs1v15Sha256(Vec<u8>),
+    RsaPssSha256(Vec<u8>),
 }
 
 // see https://tools.ietf.org/html/rfc8152 section 8.1
@@ -40,6 +41,9 @@ const COSE_EC2_CRV_P256: serde_cbor::Value = serde_cbor::Value::Integer(1);
 // https://datatracker.ietf.org/doc/html/rfc8812#section-2
 const COSE_ALG_RS256: serde_cbor::Value = serde_cbor::Value::Integer(-257);
 
+// https://datatracker.ietf.org/doc/html/rfc8230#section-2
+const COSE_ALG_PS256: serde_cbor::Value = serde_cbor::Value::Integer(-37);
+
 // https://datatracker.ietf.org/doc/html/rfc8230#section-4
 const COSE_KTY_RSA: serde_cbor::Value = serde_cbor::Value::Integer(3);
 const COSE_PARAM_RSA_N: serde_cbor::Value = serde_cbor::Value::Integer(-1);
@@ -85,7 +89,9 @@ impl CosePublicKey {
             if *kty == COSE_KTY_EC2 && *alg == COSE_ALG_ES256 {
                 Self::parse_ecdsa_p256(&fields)
             } else if *kty == COSE_KTY_RSA && *alg == COSE_ALG_RS256 {
-                Self::parse_rsa_pkcs1_sha256(&fields)
+                Self::parse_rsa(&fields, AlgorithmId::RsaSha256).map(Self::RsaPkcs1v15Sha256)
+            } else if *kty == COSE_KTY_RSA && *alg == COSE_ALG_PS256 {
+                Self::parse_rsa(&fields, AlgorithmId::RsaPssSha256).map(Self::RsaPssSha256)
             } else {
                 // Some other algorithm
                 Err(CosePublicKeyParseError::AlgorithmNotSupported)
@@ -156,33 +162,30 @@ impl CosePublicKey {
         }
     }
 
-    fn parse_rsa_pkcs1_sha256(fields: &CborMap) -> Result<Self, CosePublicKeyParseError> {
+    /// Parse a COSE RSA key, returning its SPKI encoding
+    ///
+    /// The key parameters are the same for all RSA signature schemes, the
+    /// `algorithm` is only used to report errors.
+    fn parse_rsa(
+        fields: &CborMap,
+        algorithm: AlgorithmId,
+    ) -> Result<Vec<u8>, CosePublicKeyParseError> {
         Self::verify_key_ops(fields)?;
 
-        let e =
-            fields
-                .get(&COSE_PARAM_RSA_E)
-                .ok_or(CosePublicKeyParseError::MalformedPublicKey(
-                    AlgorithmId::RsaSha256,
-                ))?;
-        let n =
-            fields
-                .get(&COSE_PARAM_RSA_N)
-                .ok_or(CosePublicKeyParseError::MalformedPublicKey(
-                    AlgorithmId::RsaSha256,
-                ))?;
+        let e = fields
+            .get(&COSE_PARAM_RSA_E)
+            .ok_or(CosePublicKeyParseError::MalformedPublicKey(algorithm))?;
+        let n = fields
+            .get(&COSE_PARAM_RSA_N)
+            .ok_or(CosePublicKeyParseError::MalformedPublicKey(algorithm))?;
 
         match (e, n) {
             (serde_cbor::Value::Bytes(e), serde_cbor::Value::Bytes(n)) => {
-                let key = RsaPublicKey::from_components(e, n).map_err(|_| {
-                    CosePublicKeyParseError::MalformedPublicKey(AlgorithmId::RsaSha256)
-                })?;
-                let der = key.as_der().to_vec();
-                Ok(Self::RsaPkcs1v15Sha256(der))
+                let key = RsaPublicKey::from_components(e, n)
+                    .map_err(|_| CosePublicKeyParseError::MalformedPublicKey(algorithm))?;
+                Ok(key.as_der().to_vec())
             }
-            (_, _) => Err(CosePublicKeyParseError::MalformedPublicKey(
-                AlgorithmId::RsaSha256,
-            )),
+            (_, _) => Err(CosePublicKeyParseError::MalformedPublicKey(algorithm)),
         }
     }
 
@@ -191,6 +194,7 @@ impl CosePublicKey {
         match self {
             Self::EcdsaP256Sha256(_) => AlgorithmId::EcdsaP256,
             Self::RsaPkcs1v15Sha256(_) => AlgorithmId::RsaSha256,
+            Self::RsaPssSha256(_) => AlgorithmId::RsaPssSha256,
         }
     }
 
@@ -199,6 +203,7 @@ impl CosePublicKey {
         match self {
             Self::EcdsaP256Sha256(der) => der.to_vec(),
             Self::RsaPkcs1v15Sha256(der) => der.to_vec(),
+            Self::RsaPssSha256(der) => der.to_vec(),
         }
     }
 }
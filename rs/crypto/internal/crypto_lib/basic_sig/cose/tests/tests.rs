@@ -25,6 +25,15 @@ fn should_correctly_parse_cose_encoded_rsa256_pk() {
     assert_eq!(pk.0, AlgorithmId::RsaSha256);
 }
 
+#[test]
+fn should_correctly_parse_cose_encoded_ps256_pk() {
+    let ps256_cose = hex::decode("a401030338242059010098194bcfd243773a9701dacad80d895225906826e74f34631a6f214374829bd407918e1a075aa14ba67898eb4d2c6feffb77d78b776ed60073a107d938318bf68289d1118cb555355bf7ecd4c94e8af560cd5069d35a947a454bdc312d228a0b6f749b560759295fa428b6596bd882d1f66b95db9217997a1fb1772ca13abda58178a44d1587eae516dbf0d66f29de377bfab06e174d3007262345b7624308091fcbabe0a9d7ae57a8daec5bc4c2a8876b0fd368269c850d0ae78980212c545be5b1803874d2cde67722d997ce6526a6430d5d3009452e00944e70be032c0bb33cad1d016ad6ba26c74e281514192a2bb2268062678aecb5ebc5061ee16ef3592143010001").unwrap();
+    let rsa_der = hex::decode("30820122300d06092a864886f70d01010105000382010f003082010a028201010098194bcfd243773a9701dacad80d895225906826e74f34631a6f214374829bd407918e1a075aa14ba67898eb4d2c6feffb77d78b776ed60073a107d938318bf68289d1118cb555355bf7ecd4c94e8af560cd5069d35a947a454bdc312d228a0b6f749b560759295fa428b6596bd882d1f66b95db9217997a1fb1772ca13abda58178a44d1587eae516dbf0d66f29de377bfab06e174d3007262345b7624308091fcbabe0a9d7ae57a8daec5bc4c2a8876b0fd368269c850d0ae78980212c545be5b1803874d2cde67722d997ce6526a6430d5d3009452e00944e70be032c0bb33cad1d016ad6ba26c74e281514192a2bb2268062678aecb5ebc5061ee16ef3590203010001").unwrap();
+
+    let pk = parse_cose_public_key(&ps256_cose).unwrap();
+    assert_eq!(pk, (AlgorithmId::RsaPssSha256, rsa_der));
+}
+
 #[test]
 fn should_reject_cose_encoded_rsa256_pk_with_unknown_alg() {
     // Here alg = 257 not the expected -257
@@ -60,6 +69,22 @@ fn should_reject_cose_encoded_rsa256_pk_with_missing_e() {
     );
 }
 
+#[test]
+fn should_reject_cose_encoded_ps256_pk_with_missing_e() {
+    let bad_ps256_cose = hex::decode("a301030338242059010098194bcfd243773a9701dacad80d895225906826e74f34631a6f214374829bd407918e1a075aa14ba67898eb4d2c6feffb77d78b776ed60073a107d938318bf68289d1118cb555355bf7ecd4c94e8af560cd5069d35a947a454bdc312d228a0b6f749b560759295fa428b6596bd882d1f66b95db9217997a1fb1772ca13abda58178a44d1587eae516dbf0d66f29de377bfab06e174d3007262345b7624308091fcbabe0a9d7ae57a8daec5bc4c2a8876b0fd368269c850d0ae78980212c545be5b1803874d2cde67722d997ce6526a6430d5d3009452e00944e70be032c0bb33cad1d016ad6ba26c74e281514192a2bb2268062678aecb5ebc5061ee16ef359").unwrap();
+
+    let result = parse_cose_public_key(&bad_ps256_cose);
+
+    assert_eq!(
+        result,
+        Err(CryptoError::MalformedPublicKey {
+            algorithm: AlgorithmId::RsaPssSha256,
+            key_bytes: Some(bad_ps256_cose),
+            internal_error: "Failed to parse COSE public key".to_string()
+        })
+    );
+}
+
 #[test]
 fn should_correctly_parse_webauthn_cose_encoded_pk() {
     let pk_cose = hex::decode(test_data::WEBAUTHN_ECDSA_P256_PK_COSE_HEX).unwrap();
@@ -19,6 +19,9 @@ pub enum PkixAlgorithmParameters {
     ObjectIdentifier(OID),
     /// An ASN.1 explicit NULL
     Null,
+    /// The DER encoding of an ASN.1 sequence, e.g., the RSASSA-PSS-params of
+    /// [RFC 4055](https://tools.ietf.org/html/rfc4055#section-3.1)
+    Sequence(Vec<u8>),
 }
 
 /// An AlgorithmIdentifier as described in RFC 5480
@@ -49,6 +52,13 @@ impl PkixAlgorithmIdentifier {
             params: Some(PkixAlgorithmParameters::ObjectIdentifier(algo_params)),
         }
     }
+
+    pub fn new_with_sequence_param(algo_oid: OID, algo_params_der: Vec<u8>) -> Self {
+        Self {
+            oid: algo_oid,
+            params: Some(PkixAlgorithmParameters::Sequence(algo_params_der)),
+        }
+    }
 }
 
 /// Encodes the given `key` according to
@@ -264,7 +274,8 @@ impl KeyDerParser {
     ) -> Result<PkixAlgorithmIdentifier, KeyDerParsingError> {
         // PkixAlgorithmIdentifier is a pair of an OID plus anything (or nothing)
         // whose type depends on the leading OID. However in our current usage
-        // the second parameter is always either absent or a second OID
+        // the second parameter is always either absent, NULL, a second OID, or
+        // a sequence (which is kept DER-encoded)
 
         if let ASN1Block::Sequence(_offset_oid, oid_parts) = oid_seq {
             if oid_parts.len() == 1 || oid_parts.len() == 2 {
@@ -284,6 +295,21 @@ impl KeyDerParser {
                         algo_oid.clone(),
                         algo_params.clone(),
                     )),
+                    (
+                        ASN1Block::ObjectIdentifier(_, algo_oid),
+                        Some(algo_params @ ASN1Block::Sequence(_, _)),
+                    ) => {
+                        let algo_params_der = simple_asn1::to_der(algo_params).map_err(|e| {
+                            Self::parsing_error(&format!(
+                                "Failed to encode algorithm parameters: {:?}",
+                                e
+                            ))
+                        })?;
+                        Ok(PkixAlgorithmIdentifier::new_with_sequence_param(
+                            algo_oid.clone(),
+                            algo_params_der,
+                        ))
+                    }
                     (ASN1Block::ObjectIdentifier(_, algo_oid), None) => Ok(
                         PkixAlgorithmIdentifier::new_with_empty_param(algo_oid.clone()),
                     ),
@@ -18,6 +18,31 @@ fn should_have_compatible_subject_public_key_info_der_encoder_and_decoder() {
     assert_eq!(pubkey_bytes, pubkey);
 }
 
+#[test]
+fn should_parse_subject_public_key_info_der_with_sequence_algorithm_parameters() {
+    let oid = oid!(1, 2, 3, 4, 5);
+    let params = ASN1Block::Sequence(0, vec![ASN1Block::Integer(0, BigInt::from(20))]);
+    let algorithm = ASN1Block::Sequence(
+        0,
+        vec![ASN1Block::ObjectIdentifier(0, oid.clone()), params.clone()],
+    );
+    let pubkey = b"subject public key".to_vec();
+    let subject_public_key = ASN1Block::BitString(0, pubkey.len() * 8, pubkey.clone());
+    let pubkey_der =
+        simple_asn1::to_der(&ASN1Block::Sequence(0, vec![algorithm, subject_public_key])).unwrap();
+
+    let (algo_id, pubkey_bytes) = algo_id_and_public_key_bytes_from_der(&pubkey_der).unwrap();
+
+    assert_eq!(
+        algo_id,
+        PkixAlgorithmIdentifier::new_with_sequence_param(
+            oid,
+            simple_asn1::to_der(&params).unwrap()
+        )
+    );
+    assert_eq!(pubkey_bytes, pubkey);
+}
+
 #[test]
 fn should_correctly_parse_cose_encoded_der_wrapped_ecdsa_p256_pk() {
     let pk_cose_der = hex::decode(test_data::ECDSA_P256_PK_3_COSE_DER_WRAPPED_HEX).unwrap();
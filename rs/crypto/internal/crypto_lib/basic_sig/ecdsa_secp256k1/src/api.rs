@@ -195,6 +195,68 @@ pub fn public_key_to_der(pk: &types::PublicKeyBytes) -> CryptoResult<Vec<u8>> {
         })
 }
 
+/// Parse a secp256k1 public key from the SEC1 encoding
+///
+/// # Arguments
+/// * `pk_sec1` is the SEC1 encoding of the public key point, which may be
+///   compressed or uncompressed
+/// # Errors
+/// * `AlgorithmNotSupported` if an error occurred while invoking OpenSSL
+/// * `MalformedPublicKey` if the public key could not be parsed, or is the
+///   point at infinity
+/// # Returns
+/// The decoded public key, in uncompressed format
+pub fn public_key_from_sec1(pk_sec1: &[u8]) -> CryptoResult<types::PublicKeyBytes> {
+    let group = EcGroup::from_curve_name(CURVE_NAME)
+        .map_err(|e| wrap_openssl_err(e, "unable to create EC group"))?;
+    let mut ctx =
+        BigNumContext::new().map_err(|e| wrap_openssl_err(e, "unable to create BigNumContext"))?;
+    let point = EcPoint::from_bytes(&group, pk_sec1, &mut ctx).map_err(|e| {
+        CryptoError::MalformedPublicKey {
+            algorithm: AlgorithmId::EcdsaSecp256k1,
+            key_bytes: Some(Vec::from(pk_sec1)),
+            internal_error: e.to_string(),
+        }
+    })?;
+    if point.is_infinity(&group) {
+        return Err(CryptoError::MalformedPublicKey {
+            algorithm: AlgorithmId::EcdsaSecp256k1,
+            key_bytes: Some(Vec::from(pk_sec1)),
+            internal_error: "point at infinity".to_string(),
+        });
+    }
+    let pk_bytes = point
+        .to_bytes(
+            &group,
+            openssl::ec::PointConversionForm::UNCOMPRESSED,
+            &mut ctx,
+        )
+        .map_err(|e| CryptoError::MalformedPublicKey {
+            algorithm: AlgorithmId::EcdsaSecp256k1,
+            key_bytes: Some(Vec::from(pk_sec1)),
+            internal_error: e.to_string(),
+        })?;
+    Ok(types::PublicKeyBytes::from(pk_bytes))
+}
+
+/// Decode a secp256k1 signature from the DER encoding
+///
+/// # Arguments
+/// `sig_der` the DER encoded signature, as a pair of integers (r,s)
+/// # Errors
+/// * `MalformedSignature` if the data could not be decoded as a DER ECDSA
+///   signature
+pub fn signature_from_der(sig_der: &[u8]) -> CryptoResult<types::SignatureBytes> {
+    let secp256k1_sig =
+        EcdsaSig::from_der(sig_der).map_err(|e| CryptoError::MalformedSignature {
+            algorithm: AlgorithmId::EcdsaSecp256k1,
+            sig_bytes: sig_der.to_vec(),
+            internal_error: format!("Error parsing DER signature: {}", e),
+        })?;
+    let sig_bytes = secp256k1_sig_to_bytes(secp256k1_sig)?;
+    Ok(types::SignatureBytes(sig_bytes))
+}
+
 // Returns `secp256k1_sig` as an array of exactly types::SignatureBytes::SIZE
 // bytes.
 fn secp256k1_sig_to_bytes(
@@ -66,6 +66,75 @@ mod keygen {
     }
 }
 
+mod sec1 {
+    use crate::{new_keypair, public_key_from_sec1, sign, signature_from_der, verify};
+    use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
+    use openssl::bn::{BigNum, BigNumContext};
+    use openssl::ec::{EcGroup, EcPoint, PointConversionForm};
+    use openssl::ecdsa::EcdsaSig;
+    use openssl::nid::Nid;
+
+    #[test]
+    fn should_correctly_parse_uncompressed_sec1_encoded_pk() {
+        let (_sk, pk) = new_keypair(&mut reproducible_rng()).unwrap();
+        let parsed_pk = public_key_from_sec1(&pk.0).unwrap();
+        assert_eq!(parsed_pk, pk);
+    }
+
+    #[test]
+    fn should_correctly_parse_compressed_sec1_encoded_pk() {
+        let (_sk, pk) = new_keypair(&mut reproducible_rng()).unwrap();
+        let group = EcGroup::from_curve_name(Nid::SECP256K1).unwrap();
+        let mut ctx = BigNumContext::new().unwrap();
+        let compressed = EcPoint::from_bytes(&group, &pk.0, &mut ctx)
+            .unwrap()
+            .to_bytes(&group, PointConversionForm::COMPRESSED, &mut ctx)
+            .unwrap();
+
+        let parsed_pk = public_key_from_sec1(&compressed).unwrap();
+
+        assert_eq!(parsed_pk, pk);
+    }
+
+    #[test]
+    fn should_fail_parsing_a_corrupted_sec1_encoded_pk() {
+        let (_sk, pk) = new_keypair(&mut reproducible_rng()).unwrap();
+        let pk_result = public_key_from_sec1(&pk.0[1..]);
+        assert!(pk_result.unwrap_err().is_malformed_public_key());
+    }
+
+    #[test]
+    fn should_fail_parsing_point_at_infinity() {
+        let pk_result = public_key_from_sec1(&[0x00]);
+        assert!(pk_result.unwrap_err().is_malformed_public_key());
+    }
+
+    #[test]
+    fn should_verify_signature_decoded_from_der() {
+        let msg = b"some message";
+        let (sk, pk) = new_keypair(&mut reproducible_rng()).unwrap();
+        let sig = sign(msg, &sk).unwrap();
+        let sig_der = EcdsaSig::from_private_components(
+            BigNum::from_slice(&sig.0[..32]).unwrap(),
+            BigNum::from_slice(&sig.0[32..]).unwrap(),
+        )
+        .unwrap()
+        .to_der()
+        .unwrap();
+
+        let decoded_sig = signature_from_der(&sig_der).unwrap();
+
+        assert_eq!(decoded_sig.0, sig.0);
+        assert!(verify(&decoded_sig, msg, &pk).is_ok());
+    }
+
+    #[test]
+    fn should_fail_decoding_a_corrupted_der_encoded_signature() {
+        let sig_result = signature_from_der(b"not a signature");
+        assert!(sig_result.unwrap_err().is_malformed_signature());
+    }
+}
+
 mod sign {
     use crate::{new_keypair, sign, types, verify};
     use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
@@ -1,17 +1,19 @@
 //! Verify RSA signatures
 //!
-//! See RFC 5280 (https://www.rfc-editor.org/rfc/rfc5280.txt)
-//! and RFC 3279 (https://www.rfc-editor.org/rfc/rfc3279.txt)
+//! See RFC 5280 (https://www.rfc-editor.org/rfc/rfc5280.txt),
+//! RFC 3279 (https://www.rfc-editor.org/rfc/rfc3279.txt)
+//! and RFC 4055 (https://www.rfc-editor.org/rfc/rfc4055.txt)
 //! for information about the SubjectPublicKeyInfo key encoding
 //!
 //! See RFC 8017 (https://www.rfc-editor.org/rfc/rfc8017.txt)
-//! for information about the signature format
+//! for information about the PKCS#1 v1.5 and PSS signature formats
 use ic_crypto_internal_basic_sig_der_utils as der_utils;
 use ic_crypto_sha::Sha256;
 use ic_types::crypto::{AlgorithmId, CryptoError, CryptoResult};
 use num_traits::{FromPrimitive, Zero};
 use rsa::{PublicKey, PublicKeyParts};
 use serde::{Deserialize, Deserializer, Serialize};
+use simple_asn1::{oid, ASN1Block, ASN1Class, BigInt, BigUint, OID};
 
 /// The object identifier for RSA public keys
 ///
@@ -22,6 +24,23 @@ pub fn algorithm_identifier() -> der_utils::PkixAlgorithmIdentifier {
     ))
 }
 
+/// The object identifier for RSASSA-PSS public keys (id-RSASSA-PSS)
+///
+/// See [RFC 4055](https://tools.ietf.org/html/rfc4055#section-3.1).
+pub fn pss_algorithm_oid() -> OID {
+    oid!(1, 2, 840, 113549, 1, 1, 10)
+}
+
+/// The object identifier for SHA-256 (id-sha256)
+fn sha256_oid() -> OID {
+    oid!(2, 16, 840, 1, 101, 3, 4, 2, 1)
+}
+
+/// The object identifier for the mask generation function MGF1 (id-mgf1)
+fn mgf1_oid() -> OID {
+    oid!(1, 2, 840, 113549, 1, 1, 8)
+}
+
 /// A RSA public key usable for signature verification
 #[derive(Debug, Clone, Eq, PartialEq, Serialize)]
 pub struct RsaPublicKey {
@@ -148,6 +167,49 @@ impl RsaPublicKey {
         })
     }
 
+    /// Create a RSA public key from the encoded X.509 SubjectPublicKeyInfo
+    /// with the algorithm id-RSASSA-PSS
+    ///
+    /// See https://datatracker.ietf.org/doc/html/rfc4055#section-3.1. If the
+    /// key restricts the RSASSA-PSS parameters, they must be SHA-256 and MGF1
+    /// with SHA-256, as used by `verify_pss_sha256`. The returned key is
+    /// encoded with the algorithm rsaEncryption, like the keys created with
+    /// `from_der_spki`.
+    ///
+    /// # Arguments
+    /// * `bytes` the DER encoded data
+    pub fn from_der_pss_spki(bytes: &[u8]) -> CryptoResult<Self> {
+        let malformed = |internal_error: String| CryptoError::MalformedPublicKey {
+            algorithm: AlgorithmId::RsaPssSha256,
+            key_bytes: Some(bytes.to_vec()),
+            internal_error,
+        };
+
+        let (algo_id, pk_bytes) = der_utils::algo_id_and_public_key_bytes_from_der(bytes)
+            .map_err(|e| malformed(e.internal_error))?;
+        if algo_id.oid != pss_algorithm_oid() {
+            return Err(malformed(format!(
+                "Expected RSASSA-PSS key, got algorithm {:?}",
+                algo_id.oid
+            )));
+        }
+        match &algo_id.params {
+            None => {}
+            Some(der_utils::PkixAlgorithmParameters::Sequence(params_der)) => {
+                ensure_pss_sha256_params(params_der).map_err(malformed)?
+            }
+            Some(params) => {
+                return Err(malformed(format!(
+                    "Unexpected RSASSA-PSS parameters {:?}",
+                    params
+                )))
+            }
+        }
+
+        let (n, e) = pkcs1_public_key_components(&pk_bytes).map_err(malformed)?;
+        Self::from_components(&e, &n)
+    }
+
     /// Return the DER encoding of the key
     pub fn as_der(&self) -> &[u8] {
         self.der.as_ref()
@@ -174,4 +236,124 @@ impl RsaPublicKey {
             }),
         }
     }
+
+    /// Verify a RSASSA-PSS signature with SHA-256 and MGF1 with SHA-256
+    ///
+    /// As specified in RFC 8017
+    /// (https://datatracker.ietf.org/doc/html/rfc8017#section-8.1) and used by
+    /// webauthn authenticators with the COSE algorithm PS256 (RFC 8230). The
+    /// salt length is recovered from the encoded message.
+    pub fn verify_pss_sha256(&self, message: &[u8], signature: &[u8]) -> CryptoResult<()> {
+        let digest = Sha256::hash(message);
+        // The random number generator is only used to generate the salt when
+        // signing, so it is never invoked here.
+        let padding = rsa::PaddingScheme::new_pss::<sha2::Sha256, _>(rand::rngs::OsRng);
+
+        match self.key.verify(padding, &digest, signature) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(CryptoError::SignatureVerification {
+                algorithm: AlgorithmId::RsaPssSha256,
+                public_key_bytes: self.as_der().to_vec(),
+                sig_bytes: signature.to_vec(),
+                internal_error: format!("{:?}", e),
+            }),
+        }
+    }
+}
+
+/// Returns the modulus and the public exponent, encoded in big-endian bytes,
+/// of the PKCS#1 RSAPublicKey `der`
+///
+/// See https://datatracker.ietf.org/doc/html/rfc8017#appendix-A.1.1
+fn pkcs1_public_key_components(der: &[u8]) -> Result<(Vec<u8>, Vec<u8>), String> {
+    use num_bigint::Sign;
+
+    let blocks =
+        simple_asn1::from_der(der).map_err(|e| format!("Parsing RSA public key failed {:?}", e))?;
+    match blocks.as_slice() {
+        [ASN1Block::Sequence(_, components)] => match components.as_slice() {
+            [ASN1Block::Integer(_, n), ASN1Block::Integer(_, e)]
+                if n.sign() == Sign::Plus && e.sign() == Sign::Plus =>
+            {
+                Ok((n.to_bytes_be().1, e.to_bytes_be().1))
+            }
+            _ => Err("RSA public key has unexpected components".to_string()),
+        },
+        _ => Err("Expected RSA public key sequence".to_string()),
+    }
+}
+
+/// Ensures that the RSASSA-PSS-params `der` of a public key allow signatures
+/// with SHA-256 and MGF1 with SHA-256
+///
+/// See https://datatracker.ietf.org/doc/html/rfc4055#section-3.1. The hash
+/// and mask generation function default to SHA-1, so they must be present.
+/// Any salt length is accepted, since `verify_pss_sha256` recovers the salt
+/// from the encoded message.
+fn ensure_pss_sha256_params(der: &[u8]) -> Result<(), String> {
+    let blocks = simple_asn1::from_der(der)
+        .map_err(|e| format!("Parsing RSASSA-PSS parameters failed {:?}", e))?;
+    let fields = match blocks.as_slice() {
+        [ASN1Block::Sequence(_, fields)] => fields,
+        _ => return Err("Expected RSASSA-PSS parameters sequence".to_string()),
+    };
+
+    let (mut hash_is_sha256, mut mgf_is_mgf1_sha256) = (false, false);
+    for field in fields {
+        match field {
+            ASN1Block::Explicit(ASN1Class::ContextSpecific, _, tag, value) => {
+                if *tag == BigUint::from(0u8) {
+                    hash_is_sha256 = is_sha256_algorithm_identifier(value);
+                } else if *tag == BigUint::from(1u8) {
+                    mgf_is_mgf1_sha256 = is_mgf1_sha256_algorithm_identifier(value);
+                } else if *tag == BigUint::from(2u8) {
+                    if !matches!(**value, ASN1Block::Integer(_, _)) {
+                        return Err("RSASSA-PSS salt length is not an integer".to_string());
+                    }
+                } else if *tag == BigUint::from(3u8) {
+                    let one = BigInt::from(1u8);
+                    if !matches!(&**value, ASN1Block::Integer(_, trailer) if *trailer == one) {
+                        return Err("RSASSA-PSS trailer field is not 1".to_string());
+                    }
+                } else {
+                    return Err(format!("Unexpected RSASSA-PSS parameter [{}]", tag));
+                }
+            }
+            _ => return Err(format!("Unexpected RSASSA-PSS parameter {:?}", field)),
+        }
+    }
+
+    if !hash_is_sha256 {
+        return Err("RSASSA-PSS hash algorithm is not SHA-256".to_string());
+    }
+    if !mgf_is_mgf1_sha256 {
+        return Err("RSASSA-PSS mask generation function is not MGF1 with SHA-256".to_string());
+    }
+    Ok(())
+}
+
+/// Whether `block` is the AlgorithmIdentifier of SHA-256, with absent or NULL
+/// parameters
+fn is_sha256_algorithm_identifier(block: &ASN1Block) -> bool {
+    match block {
+        ASN1Block::Sequence(_, parts) => match parts.as_slice() {
+            [ASN1Block::ObjectIdentifier(_, oid)]
+            | [ASN1Block::ObjectIdentifier(_, oid), ASN1Block::Null(_)] => *oid == sha256_oid(),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Whether `block` is the AlgorithmIdentifier of MGF1 with SHA-256
+fn is_mgf1_sha256_algorithm_identifier(block: &ASN1Block) -> bool {
+    match block {
+        ASN1Block::Sequence(_, parts) => match parts.as_slice() {
+            [ASN1Block::ObjectIdentifier(_, oid), hash] => {
+                *oid == mgf1_oid() && is_sha256_algorithm_identifier(hash)
+            }
+            _ => false,
+        },
+        _ => false,
+    }
 }
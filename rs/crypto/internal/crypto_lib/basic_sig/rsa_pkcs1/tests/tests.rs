@@ -1,4 +1,6 @@
+use ic_crypto_internal_basic_sig_der_utils as der_utils;
 use ic_crypto_internal_basic_sig_rsa_pkcs1::*;
+use simple_asn1::{oid, ASN1Block, ASN1Class, BigInt, BigUint};
 
 #[test]
 fn should_be_able_to_parse_rsa_pubkey() {
@@ -75,6 +77,35 @@ fn should_be_able_to_verify_rsa_signature() {
     assert!(key.verify_pkcs1_sha256(&msg, &sig).is_err());
 }
 
+#[test]
+fn should_be_able_to_verify_rsa_pss_signature() {
+    let der = hex::decode("30820122300D06092A864886F70D01010105000382010F003082010A028201010098194BCFD243773A9701DACAD80D895225906826E74F34631A6F214374829BD407918E1A075AA14BA67898EB4D2C6FEFFB77D78B776ED60073A107D938318BF68289D1118CB555355BF7ECD4C94E8AF560CD5069D35A947A454BDC312D228A0B6F749B560759295FA428B6596BD882D1F66B95DB9217997A1FB1772CA13ABDA58178A44D1587EAE516DBF0D66F29DE377BFAB06E174D3007262345B7624308091FCBABE0A9D7AE57A8DAEC5BC4C2A8876B0FD368269C850D0AE78980212C545BE5B1803874D2CDE67722D997CE6526A6430D5D3009452E00944E70BE032C0BB33CAD1D016AD6BA26C74E281514192A2BB2268062678AECB5EBC5061EE16EF3590203010001").unwrap();
+    let key = RsaPublicKey::from_der_spki(&der).unwrap();
+
+    let sig = hex::decode("886D52815D67C72DA48BC1D0EBA18C1C86B31263D5341A92562862ED5203A3740357EF32674D45DC61C6A3E8E216CDE9EFBBBB08BFAFD554BE9716AF78DFB6FF4B300F555B771DA33B739D30BB7A3B78FA96270B3E79E1CDD6FDD111F9C888E3254CC807E65033BCCD63335D45B7B09705BCF0140A1EA9DF458FC68B31121E0659EE7D0911301CCA3A60A7A964187DCBA25851946DB961342444D4F1902E77CB9A1092217FC75B55382A08CBE886735868C651BB7D8D5A7A8D8F72CBA6834E0F99CA6750E0345E3611867C512559793CC479E504494BEF4128611BE260E385E35A992D3405EF5258A6CC2A3A1C691AA0ACDA2743DD0EC7A1135C78609D64ECAA").unwrap();
+    let msg = hex::decode("616263").unwrap();
+
+    assert!(key.verify_pss_sha256(&msg, &sig).is_ok());
+
+    // A PSS signature is not a valid PKCS#1 v1.5 signature
+    assert!(key.verify_pkcs1_sha256(&msg, &sig).is_err());
+
+    // Wrong message fails to verify
+    let msg = hex::decode("6162").unwrap();
+    assert!(key.verify_pss_sha256(&msg, &sig).is_err());
+}
+
+#[test]
+fn should_not_verify_rsa_pkcs1_signature_as_pss_signature() {
+    let der = hex::decode("30820122300D06092A864886F70D01010105000382010F003082010A0282010100A7078A1A8FDE64C537AE5CA8D4B3A9139D68050CF76E45E77DBE47CECEB162F7095ADB6260998775203AA42A444F865DEB995C2B70B548ECEE01695DEB069ED18744C12FD24AEACDA4B2B7A5E97E7167CAF7D4B8904CE20CA9A8928978CA957FF2D9FCAE0859618B0AD74C164FAF5AB1DE7D7228A89BD3F8B497CEF9E45E1203CC40EE252140157C331A584F3916E569A8C39573D542A3577FB12332EBD3C9F421C9EF8A23D5ACF6BA439F7C3D6B73BA4E56B9B8EFBC42A2E5E734B99FDF7AB046813E43C65C926793919A7AE54F71AAF57C6876001A0558BC847D7555B1AE71F56A70272D786BE69A23A21A56C426371BD9882D40E7ECA6B7DA5D8169B7030F0203010001").unwrap();
+    let key = RsaPublicKey::from_der_spki(&der).unwrap();
+
+    let sig = hex::decode("7416E0A20E46CEF9FC09FA87D4C324502839EB8DEAFEF7CA5ADEC1044523232E66B32F4A497AA84FC4069182AD4A921B43DBCBD3ACCA870F887299692E23555086169F89EA1DD4856DC9FEB4E96B1661F803B784B4BE9A0E36B739A38126996912D92343688DB58F24CF8066250E2B04EE166A1C9C924D1AA9DED87D8A24E07CF35B02CA487B1632BA2508FF2B28F880983926A75D67EB83292BF77EE9B283337D841F04253C846BD66E63E50D8B326DCE1EC67A95A9D31DBDF3DCA5E8C09CA8CCE2026A3A5AE56250EC57CDE67A745FA1B1CC83473BA167AD1F8311A3D071184D03380B80C7921457CE282B9222FE805E506B53C5F798917B1A45044D2E896D").unwrap();
+    let msg = hex::decode("616263").unwrap();
+
+    assert!(key.verify_pss_sha256(&msg, &sig).is_err());
+}
+
 #[test]
 fn should_be_able_to_serialize_and_deserialize_rsa_pubkey() {
     let der = hex::decode("30820122300D06092A864886F70D01010105000382010F003082010A0282010100A7078A1A8FDE64C537AE5CA8D4B3A9139D68050CF76E45E77DBE47CECEB162F7095ADB6260998775203AA42A444F865DEB995C2B70B548ECEE01695DEB069ED18744C12FD24AEACDA4B2B7A5E97E7167CAF7D4B8904CE20CA9A8928978CA957FF2D9FCAE0859618B0AD74C164FAF5AB1DE7D7228A89BD3F8B497CEF9E45E1203CC40EE252140157C331A584F3916E569A8C39573D542A3577FB12332EBD3C9F421C9EF8A23D5ACF6BA439F7C3D6B73BA4E56B9B8EFBC42A2E5E734B99FDF7AB046813E43C65C926793919A7AE54F71AAF57C6876001A0558BC847D7555B1AE71F56A70272D786BE69A23A21A56C426371BD9882D40E7ECA6B7DA5D8169B7030F0203010001").unwrap();
@@ -86,3 +117,99 @@ fn should_be_able_to_serialize_and_deserialize_rsa_pubkey() {
 
     assert_eq!(key, deserialized);
 }
+
+#[test]
+fn should_be_able_to_parse_rsa_pss_pubkey_without_parameters() {
+    let der = rsa_pss_spki(None);
+
+    let key = RsaPublicKey::from_der_pss_spki(&der).unwrap();
+
+    assert_eq!(
+        key.as_der(),
+        hex::decode(RSA_PSS_TEST_KEY_RSA_ENCRYPTION_DER_HEX).unwrap()
+    );
+    let sig = hex::decode(RSA_PSS_TEST_SIGNATURE_HEX).unwrap();
+    assert!(key.verify_pss_sha256(b"abc", &sig).is_ok());
+}
+
+#[test]
+fn should_be_able_to_parse_rsa_pss_pubkey_with_sha256_parameters() {
+    let params = ASN1Block::Sequence(
+        0,
+        vec![
+            explicit(0, sha256_algorithm_identifier()),
+            explicit(
+                1,
+                ASN1Block::Sequence(
+                    0,
+                    vec![
+                        ASN1Block::ObjectIdentifier(0, oid!(1, 2, 840, 113549, 1, 1, 8)),
+                        sha256_algorithm_identifier(),
+                    ],
+                ),
+            ),
+            explicit(2, ASN1Block::Integer(0, BigInt::from(32))),
+        ],
+    );
+    let der = rsa_pss_spki(Some(params));
+
+    let key = RsaPublicKey::from_der_pss_spki(&der).unwrap();
+
+    let sig = hex::decode(RSA_PSS_TEST_SIGNATURE_HEX).unwrap();
+    assert!(key.verify_pss_sha256(b"abc", &sig).is_ok());
+}
+
+#[test]
+fn should_reject_rsa_pss_pubkey_with_default_sha1_parameters() {
+    let der = rsa_pss_spki(Some(ASN1Block::Sequence(0, vec![])));
+
+    assert!(RsaPublicKey::from_der_pss_spki(&der).is_err());
+}
+
+#[test]
+fn should_reject_rsa_encryption_pubkey_as_rsa_pss_pubkey() {
+    let der = hex::decode(RSA_PSS_TEST_KEY_RSA_ENCRYPTION_DER_HEX).unwrap();
+
+    assert!(RsaPublicKey::from_der_pss_spki(&der).is_err());
+}
+
+const RSA_PSS_TEST_KEY_RSA_ENCRYPTION_DER_HEX: &str = "30820122300D06092A864886F70D01010105000382010F003082010A028201010098194BCFD243773A9701DACAD80D895225906826E74F34631A6F214374829BD407918E1A075AA14BA67898EB4D2C6FEFFB77D78B776ED60073A107D938318BF68289D1118CB555355BF7ECD4C94E8AF560CD5069D35A947A454BDC312D228A0B6F749B560759295FA428B6596BD882D1F66B95DB9217997A1FB1772CA13ABDA58178A44D1587EAE516DBF0D66F29DE377BFAB06E174D3007262345B7624308091FCBABE0A9D7AE57A8DAEC5BC4C2A8876B0FD368269C850D0AE78980212C545BE5B1803874D2CDE67722D997CE6526A6430D5D3009452E00944E70BE032C0BB33CAD1D016AD6BA26C74E281514192A2BB2268062678AECB5EBC5061EE16EF3590203010001";
+
+const RSA_PSS_TEST_SIGNATURE_HEX: &str = "886D52815D67C72DA48BC1D0EBA18C1C86B31263D5341A92562862ED5203A3740357EF32674D45DC61C6A3E8E216CDE9EFBBBB08BFAFD554BE9716AF78DFB6FF4B300F555B771DA33B739D30BB7A3B78FA96270B3E79E1CDD6FDD111F9C888E3254CC807E65033BCCD63335D45B7B09705BCF0140A1EA9DF458FC68B31121E0659EE7D0911301CCA3A60A7A964187DCBA25851946DB961342444D4F1902E77CB9A1092217FC75B55382A08CBE886735868C651BB7D8D5A7A8D8F72CBA6834E0F99CA6750E0345E3611867C512559793CC479E504494BEF4128611BE260E385E35A992D3405EF5258A6CC2A3A1C691AA0ACDA2743DD0EC7A1135C78609D64ECAA";
+
+/// Encodes the RSA PSS test key as SubjectPublicKeyInfo with the algorithm
+/// id-RSASSA-PSS and the given parameters
+fn rsa_pss_spki(params: Option<ASN1Block>) -> Vec<u8> {
+    let rsa_encryption_der = hex::decode(RSA_PSS_TEST_KEY_RSA_ENCRYPTION_DER_HEX).unwrap();
+    let (_algo_id, pkcs1_key) =
+        der_utils::algo_id_and_public_key_bytes_from_der(&rsa_encryption_der).unwrap();
+    let mut algorithm = vec![ASN1Block::ObjectIdentifier(0, pss_algorithm_oid())];
+    algorithm.extend(params);
+    simple_asn1::to_der(&ASN1Block::Sequence(
+        0,
+        vec![
+            ASN1Block::Sequence(0, algorithm),
+            ASN1Block::BitString(0, pkcs1_key.len() * 8, pkcs1_key),
+        ],
+    ))
+    .unwrap()
+}
+
+fn sha256_algorithm_identifier() -> ASN1Block {
+    ASN1Block::Sequence(
+        0,
+        vec![
+            ASN1Block::ObjectIdentifier(0, oid!(2, 16, 840, 1, 101, 3, 4, 2, 1)),
+            ASN1Block::Null(0),
+        ],
+    )
+}
+
+fn explicit(tag: u8, block: ASN1Block) -> ASN1Block {
+    ASN1Block::Explicit(
+        ASN1Class::ContextSpecific,
+        0,
+        BigUint::from(tag),
+        Box::new(block),
+    )
+}
@@ -9,7 +9,9 @@
 #[cfg(test)]
 mod tests;
 
-use ic_bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use ic_bls12_381::hash_to_curve::{
+    ExpandMessageState, ExpandMsgXmd, HashToCurve, InitExpandMessage,
+};
 use pairing::group::{ff::Field, Group};
 use paste::paste;
 use rand::{CryptoRng, RngCore};
@@ -297,6 +299,32 @@ impl Scalar {
         Self::new(s)
     }
 
+    /// Hash into the scalar field
+    ///
+    /// This follows the hash_to_field of draft-irtf-cfrg-hash-to-curve-16
+    /// using expand_message_xmd with SHA-256, producing a single element.
+    ///
+    /// # Arguments
+    /// * `domain_sep` - some protocol specific domain seperator
+    /// * `input` - the input which will be hashed
+    pub fn hash(domain_sep: &[u8], input: &[u8]) -> Self {
+        // 255 bits for the group order plus 128 bits, so that the bias of the
+        // modular reduction is negligible
+        const HASH_BYTES: usize = 48;
+
+        let mut bytes = [0u8; HASH_BYTES];
+        ExpandMsgXmd::<sha2::Sha256>::init_expand(input, domain_sep, HASH_BYTES)
+            .read_into(&mut bytes);
+        bytes.reverse();
+        let mut le_bytes = [0u8; 64];
+        le_bytes[..HASH_BYTES].copy_from_slice(&bytes);
+
+        let s = ic_bls12_381::Scalar::from_bytes_wide(&le_bytes);
+        bytes.zeroize();
+        le_bytes.zeroize();
+        Self::new(s)
+    }
+
     /// Deserialize a scalar from a big-endian byte string
     pub fn deserialize<B: AsRef<[u8]>>(bytes: &B) -> Result<Self, PairingInvalidScalar> {
         let mut bytes: [u8; Self::BYTES] = bytes
@@ -692,6 +692,27 @@ fn test_verify_bls_signature() {
     assert!(!verify_bls_signature(&message, &pk, &signature));
 }
 
+#[test]
+fn test_hash_to_scalar_matches_expected_values() {
+    let dst = b"QUUX-V01-CS02-with-BLS12381SCALAR_XMD:SHA-256_";
+
+    assert_eq!(
+        hex::encode(Scalar::hash(&dst[..], b"").serialize()),
+        "2ca5350b81cb1af1cf6a217cb452e7f15f4eb1ef846b5f2c6f056f90c522d69b"
+    );
+
+    assert_eq!(
+        hex::encode(Scalar::hash(&dst[..], b"abc").serialize()),
+        "3ad6497e72bb13ddee5be905cc66643a8c4da3194b45351cb826ef922d8140b2"
+    );
+
+    // A domain separator longer than 255 bytes is hashed first
+    assert_eq!(
+        hex::encode(Scalar::hash("d".repeat(256).as_bytes(), b"abc").serialize()),
+        "4c18b5c58180f7f0e458a8c670c410ea35a54b9d9238707878b8b56338d84193"
+    );
+}
+
 #[test]
 fn test_hash_to_g1_matches_draft() {
     /*
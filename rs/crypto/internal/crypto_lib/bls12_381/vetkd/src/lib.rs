@@ -0,0 +1,506 @@
+//! Verifiable encrypted threshold key derivation (vetKD) over BLS12-381
+//!
+//! A subnet holding a threshold BLS key derives keys for a caller and a
+//! derivation path and ID, and encrypts them to a transport public key of the
+//! recipient, such that neither the nodes nor an observer learn the derived
+//! key. Each node creates an [`EncryptedKeyShare`] with its secret key share,
+//! and any `threshold` valid shares are combined into an [`EncryptedKey`],
+//! which the holder of the [`TransportSecretKey`] decrypts.
+//!
+//! The derived key is the BLS signature of the derivation ID under the
+//! [`DerivedPublicKey`], so it can be verified by anyone knowing the master
+//! public key of the subnet.
+//!
+//! # Examples
+//!
+//! ```
+//! use ic_crypto_internal_bls12_381_type::{G2Affine, Scalar};
+//! use ic_crypto_internal_bls12_381_vetkd::*;
+//!
+//! let rng = &mut rand::thread_rng();
+//! let master_sk = Scalar::random(rng);
+//! let master_pk = G2Affine::from(G2Affine::generator() * &master_sk);
+//! let tsk = TransportSecretKey::generate(rng);
+//! let path = DerivationPath::new(b"caller", &[b"path".to_vec()]);
+//!
+//! // A single node holding the whole key
+//! let share = EncryptedKeyShare::create(
+//!     rng, &master_pk, &master_sk, &tsk.public_key(), &path, b"id",
+//! );
+//! let shares = [(0, share)].into_iter().collect();
+//! let ek = EncryptedKey::combine_all(&shares, 1).expect("combination failed");
+//!
+//! let dpk = DerivedPublicKey::compute_derived_key(&master_pk, &path);
+//! assert!(ek.is_valid(&master_pk, &path, b"id", &tsk.public_key()));
+//! assert!(tsk.decrypt(&ek, &dpk, b"id").is_some());
+//! ```
+#![forbid(unsafe_code)]
+#![deny(clippy::unwrap_used)]
+
+use ic_crypto_internal_bls12_381_type::{
+    G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar,
+};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+#[cfg(test)]
+mod tests;
+
+/// The index of a node in the threshold key, see [`EncryptedKey::combine_all`]
+pub type NodeIndex = u32;
+
+const DERIVATION_PATH_DOMAIN_SEP: &[u8] = b"ic-crypto-vetkd-bls12-381-derivation-path";
+const DERIVED_KEY_DOMAIN_SEP: &[u8] = b"ic-crypto-vetkd-bls12-381-derived-key";
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum VetKdError {
+    /// A transport public key is not a valid G1 point.
+    InvalidTransportPublicKey,
+    /// A transport secret key is not a valid scalar.
+    InvalidTransportSecretKey,
+    /// A derived public key is not a valid G2 point.
+    InvalidDerivedPublicKey,
+    /// An encrypted key or key share is malformed.
+    InvalidEncryptedKey,
+    /// The threshold is zero.
+    InvalidThreshold,
+    /// Fewer shares than the threshold were provided.
+    InsufficientShares { threshold: usize, shares: usize },
+    /// Fewer valid shares than the threshold were provided.
+    InsufficientValidShares {
+        threshold: usize,
+        valid_shares: usize,
+    },
+}
+
+/// The caller and derivation path for which a key is derived
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DerivationPath {
+    delta: Scalar,
+}
+
+impl DerivationPath {
+    /// Creates the derivation path of `caller` with the components `path`.
+    pub fn new(caller: &[u8], path: &[Vec<u8>]) -> Self {
+        // Each component is prefixed with its length, such that different
+        // paths have different encodings.
+        let mut input = Vec::new();
+        for component in std::iter::once(caller).chain(path.iter().map(Vec::as_slice)) {
+            input.extend_from_slice(&(component.len() as u64).to_be_bytes());
+            input.extend_from_slice(component);
+        }
+        Self {
+            delta: Scalar::hash(DERIVATION_PATH_DOMAIN_SEP, &input),
+        }
+    }
+
+    fn delta(&self) -> &Scalar {
+        &self.delta
+    }
+}
+
+/// The public key of a [`DerivationPath`], under which the derived keys are
+/// BLS signatures of their derivation ID
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DerivedPublicKey {
+    pt: G2Affine,
+}
+
+impl DerivedPublicKey {
+    /// The size of the serialized key in bytes
+    pub const BYTES: usize = G2Affine::BYTES;
+
+    /// Derives the public key for `derivation_path` from the master public
+    /// key of the subnet.
+    pub fn compute_derived_key(master_pk: &G2Affine, derivation_path: &DerivationPath) -> Self {
+        Self {
+            pt: derive_public_key(master_pk, derivation_path),
+        }
+    }
+
+    pub fn serialize(&self) -> [u8; Self::BYTES] {
+        self.pt.serialize()
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, VetKdError> {
+        G2Affine::deserialize(&bytes)
+            .map(|pt| Self { pt })
+            .map_err(|_| VetKdError::InvalidDerivedPublicKey)
+    }
+}
+
+/// The secret key to which an [`EncryptedKey`] is encrypted
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct TransportSecretKey {
+    secret_key: Scalar,
+}
+
+impl fmt::Debug for TransportSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TransportSecretKey {{ secret_key: REDACTED }}")
+    }
+}
+
+impl TransportSecretKey {
+    /// The size of the serialized key in bytes
+    pub const BYTES: usize = Scalar::BYTES;
+
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self {
+            secret_key: Scalar::random(rng),
+        }
+    }
+
+    pub fn public_key(&self) -> TransportPublicKey {
+        TransportPublicKey {
+            pt: G1Affine::from(G1Affine::generator() * &self.secret_key),
+        }
+    }
+
+    pub fn serialize(&self) -> [u8; Self::BYTES] {
+        self.secret_key.serialize()
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, VetKdError> {
+        Scalar::deserialize(&bytes)
+            .map(|secret_key| Self { secret_key })
+            .map_err(|_| VetKdError::InvalidTransportSecretKey)
+    }
+
+    /// Decrypts `encrypted_key` and returns the derived key, if it is the BLS
+    /// signature of `derivation_id` under `derived_public_key`.
+    pub fn decrypt(
+        &self,
+        encrypted_key: &EncryptedKey,
+        derived_public_key: &DerivedPublicKey,
+        derivation_id: &[u8],
+    ) -> Option<G1Affine> {
+        let key = G1Affine::from(
+            G1Projective::from(&encrypted_key.c3) - &encrypted_key.c1 * &self.secret_key,
+        );
+        let msg = hash_derivation_id(&derived_public_key.pt, derivation_id);
+        let dpk_prepared = G2Prepared::from(&derived_public_key.pt);
+        let is_valid =
+            Gt::multipairing(&[(&key, G2Prepared::neg_generator()), (&msg, &dpk_prepared)])
+                .is_identity();
+        is_valid.then_some(key)
+    }
+}
+
+/// The public key to which an [`EncryptedKey`] is encrypted
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TransportPublicKey {
+    pt: G1Affine,
+}
+
+impl TransportPublicKey {
+    /// The size of the serialized key in bytes
+    pub const BYTES: usize = G1Affine::BYTES;
+
+    pub fn serialize(&self) -> [u8; Self::BYTES] {
+        self.pt.serialize()
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, VetKdError> {
+        G1Affine::deserialize(&bytes)
+            .map(|pt| Self { pt })
+            .map_err(|_| VetKdError::InvalidTransportPublicKey)
+    }
+}
+
+/// A node's share of an [`EncryptedKey`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EncryptedKeyShare {
+    c1: G1Affine,
+    c2: G2Affine,
+    c3: G1Affine,
+}
+
+impl EncryptedKeyShare {
+    /// The size of the serialized share in bytes
+    pub const BYTES: usize = 2 * G1Affine::BYTES + G2Affine::BYTES;
+
+    /// Creates the share of the node with the secret key share `node_sk` of
+    /// the key with public key `master_pk`.
+    pub fn create<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        master_pk: &G2Affine,
+        node_sk: &Scalar,
+        transport_public_key: &TransportPublicKey,
+        derivation_path: &DerivationPath,
+        derivation_id: &[u8],
+    ) -> Self {
+        let delta = derivation_path.delta();
+        let dpk = derive_public_key(master_pk, derivation_path);
+        let msg = hash_derivation_id(&dpk, derivation_id);
+
+        let r = Scalar::random(rng);
+        let mut derived_sk = node_sk + delta;
+        let c1 = G1Affine::from(G1Affine::generator() * &r);
+        let c2 = G2Affine::from(G2Affine::generator() * &r);
+        let c3 = G1Affine::from(&transport_public_key.pt * &r + &msg * &derived_sk);
+        derived_sk.zeroize();
+
+        Self { c1, c2, c3 }
+    }
+
+    /// Checks that the share was created for the given inputs by the node with
+    /// the public key share `master_pk_share` of the key `master_pk`.
+    pub fn is_valid(
+        &self,
+        master_pk: &G2Affine,
+        master_pk_share: &G2Affine,
+        derivation_path: &DerivationPath,
+        derivation_id: &[u8],
+        transport_public_key: &TransportPublicKey,
+    ) -> bool {
+        let dpk = derive_public_key(master_pk, derivation_path);
+        let dpk_share = derive_public_key(master_pk_share, derivation_path);
+        is_valid_ciphertext(
+            &self.c1,
+            &self.c2,
+            &self.c3,
+            &dpk,
+            &dpk_share,
+            derivation_id,
+            transport_public_key,
+        )
+    }
+
+    pub fn serialize(&self) -> [u8; Self::BYTES] {
+        serialize_ciphertext(&self.c1, &self.c2, &self.c3)
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, VetKdError> {
+        let (c1, c2, c3) = deserialize_ciphertext(bytes)?;
+        Ok(Self { c1, c2, c3 })
+    }
+}
+
+/// A derived key encrypted to a [`TransportPublicKey`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EncryptedKey {
+    c1: G1Affine,
+    c2: G2Affine,
+    c3: G1Affine,
+}
+
+impl EncryptedKey {
+    /// The size of the serialized key in bytes
+    pub const BYTES: usize = EncryptedKeyShare::BYTES;
+
+    /// Combines the first `threshold` of `shares`, indexed by the index of
+    /// the node that created them, without checking their validity.
+    ///
+    /// The result is only valid if all of these shares are.
+    pub fn combine_all(
+        shares: &BTreeMap<NodeIndex, EncryptedKeyShare>,
+        threshold: usize,
+    ) -> Result<Self, VetKdError> {
+        if threshold == 0 {
+            return Err(VetKdError::InvalidThreshold);
+        }
+        if shares.len() < threshold {
+            return Err(VetKdError::InsufficientShares {
+                threshold,
+                shares: shares.len(),
+            });
+        }
+        let (indexes, shares): (Vec<NodeIndex>, Vec<&EncryptedKeyShare>) = shares
+            .iter()
+            .take(threshold)
+            .map(|(index, share)| (*index, share))
+            .unzip();
+        let coefficients = lagrange_coefficients_at_zero(&indexes);
+
+        let combine_g1 = |points: Vec<G1Affine>| {
+            G1Affine::from(G1Projective::muln_affine_vartime(&points, &coefficients))
+        };
+        let c1 = combine_g1(shares.iter().map(|share| share.c1.clone()).collect());
+        let c3 = combine_g1(shares.iter().map(|share| share.c3.clone()).collect());
+        let c2_points: Vec<G2Affine> = shares.iter().map(|share| share.c2.clone()).collect();
+        let c2 = G2Affine::from(G2Projective::muln_affine_vartime(&c2_points, &coefficients));
+        Ok(Self { c1, c2, c3 })
+    }
+
+    /// Combines the first `threshold` of the valid `shares`, indexed by the
+    /// index of the node that created them and accompanied by its public key
+    /// share.
+    ///
+    /// As checking the shares is expensive, they are only checked if the
+    /// combination of all of them is invalid.
+    pub fn combine_valid_shares(
+        shares: &BTreeMap<NodeIndex, (G2Affine, EncryptedKeyShare)>,
+        threshold: usize,
+        master_pk: &G2Affine,
+        derivation_path: &DerivationPath,
+        derivation_id: &[u8],
+        transport_public_key: &TransportPublicKey,
+    ) -> Result<Self, VetKdError> {
+        let all_shares = shares
+            .iter()
+            .map(|(index, (_, share))| (*index, share.clone()))
+            .collect();
+        let combined = Self::combine_all(&all_shares, threshold)?;
+        if combined.is_valid(
+            master_pk,
+            derivation_path,
+            derivation_id,
+            transport_public_key,
+        ) {
+            return Ok(combined);
+        }
+
+        let valid_shares: BTreeMap<NodeIndex, EncryptedKeyShare> = shares
+            .iter()
+            .filter(|(_, (pk_share, share))| {
+                share.is_valid(
+                    master_pk,
+                    pk_share,
+                    derivation_path,
+                    derivation_id,
+                    transport_public_key,
+                )
+            })
+            .map(|(index, (_, share))| (*index, share.clone()))
+            .collect();
+        if valid_shares.len() < threshold {
+            return Err(VetKdError::InsufficientValidShares {
+                threshold,
+                valid_shares: valid_shares.len(),
+            });
+        }
+        Self::combine_all(&valid_shares, threshold)
+    }
+
+    /// Checks that the key is the encryption of the key derived for the given
+    /// inputs from the key `master_pk`.
+    pub fn is_valid(
+        &self,
+        master_pk: &G2Affine,
+        derivation_path: &DerivationPath,
+        derivation_id: &[u8],
+        transport_public_key: &TransportPublicKey,
+    ) -> bool {
+        let dpk = derive_public_key(master_pk, derivation_path);
+        is_valid_ciphertext(
+            &self.c1,
+            &self.c2,
+            &self.c3,
+            &dpk,
+            &dpk,
+            derivation_id,
+            transport_public_key,
+        )
+    }
+
+    pub fn serialize(&self) -> [u8; Self::BYTES] {
+        serialize_ciphertext(&self.c1, &self.c2, &self.c3)
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, VetKdError> {
+        let (c1, c2, c3) = deserialize_ciphertext(bytes)?;
+        Ok(Self { c1, c2, c3 })
+    }
+}
+
+fn derive_public_key(master_pk: &G2Affine, derivation_path: &DerivationPath) -> G2Affine {
+    G2Affine::from(G2Affine::generator() * derivation_path.delta() + master_pk)
+}
+
+/// The message of which a derived key is the BLS signature
+fn hash_derivation_id(derived_public_key: &G2Affine, derivation_id: &[u8]) -> G1Affine {
+    let mut input = derived_public_key.serialize().to_vec();
+    input.extend_from_slice(derivation_id);
+    G1Affine::hash(DERIVED_KEY_DOMAIN_SEP, &input)
+}
+
+/// Checks that `(c1, c2, c3)` is the encryption to `transport_public_key` of
+/// the signature of the derivation ID under `signing_key`, which is either a
+/// derived public key or a share of it.
+fn is_valid_ciphertext(
+    c1: &G1Affine,
+    c2: &G2Affine,
+    c3: &G1Affine,
+    derived_public_key: &G2Affine,
+    signing_key: &G2Affine,
+    derivation_id: &[u8],
+    transport_public_key: &TransportPublicKey,
+) -> bool {
+    let msg = hash_derivation_id(derived_public_key, derivation_id);
+    let c2_prepared = G2Prepared::from(c2);
+    let signing_key_prepared = G2Prepared::from(signing_key);
+
+    // e(c1, g2) == e(g1, c2)
+    let c1_c2_match = Gt::multipairing(&[
+        (c1, G2Prepared::generator()),
+        (&G1Affine::generator().neg(), &c2_prepared),
+    ])
+    .is_identity();
+    // e(c3, g2) == e(tpk, c2) * e(msg, signing_key)
+    let c3_matches = Gt::multipairing(&[
+        (c3, G2Prepared::neg_generator()),
+        (&transport_public_key.pt, &c2_prepared),
+        (&msg, &signing_key_prepared),
+    ])
+    .is_identity();
+    c1_c2_match && c3_matches
+}
+
+fn serialize_ciphertext(
+    c1: &G1Affine,
+    c2: &G2Affine,
+    c3: &G1Affine,
+) -> [u8; EncryptedKeyShare::BYTES] {
+    let mut bytes = [0u8; EncryptedKeyShare::BYTES];
+    let (c1_bytes, rest) = bytes.split_at_mut(G1Affine::BYTES);
+    let (c2_bytes, c3_bytes) = rest.split_at_mut(G2Affine::BYTES);
+    c1_bytes.copy_from_slice(&c1.serialize());
+    c2_bytes.copy_from_slice(&c2.serialize());
+    c3_bytes.copy_from_slice(&c3.serialize());
+    bytes
+}
+
+fn deserialize_ciphertext(bytes: &[u8]) -> Result<(G1Affine, G2Affine, G1Affine), VetKdError> {
+    if bytes.len() != EncryptedKeyShare::BYTES {
+        return Err(VetKdError::InvalidEncryptedKey);
+    }
+    let (c1_bytes, rest) = bytes.split_at(G1Affine::BYTES);
+    let (c2_bytes, c3_bytes) = rest.split_at(G2Affine::BYTES);
+    let invalid = |_| VetKdError::InvalidEncryptedKey;
+    Ok((
+        G1Affine::deserialize(&c1_bytes).map_err(invalid)?,
+        G2Affine::deserialize(&c2_bytes).map_err(invalid)?,
+        G1Affine::deserialize(&c3_bytes).map_err(invalid)?,
+    ))
+}
+
+/// The Lagrange coefficients for interpolating at zero the polynomial of the
+/// threshold key from the shares at the distinct `indexes`, where the share of
+/// index `i` is the evaluation at `i + 1`.
+fn lagrange_coefficients_at_zero(indexes: &[NodeIndex]) -> Vec<Scalar> {
+    let xs: Vec<Scalar> = indexes
+        .iter()
+        .map(|index| Scalar::from_u64(u64::from(*index) + 1))
+        .collect();
+    xs.iter()
+        .enumerate()
+        .map(|(i, x_i)| {
+            let mut numerator = Scalar::one();
+            let mut denominator = Scalar::one();
+            for (j, x_j) in xs.iter().enumerate() {
+                if i != j {
+                    numerator *= x_j;
+                    denominator *= x_j - x_i;
+                }
+            }
+            numerator
+                * denominator
+                    .inverse()
+                    .expect("the indexes are distinct, so the denominator is non-zero")
+        })
+        .collect()
+}
@@ -0,0 +1,279 @@
+use super::*;
+use assert_matches::assert_matches;
+use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
+
+const DERIVATION_ID: &[u8] = b"message";
+
+struct ThresholdKey {
+    master_pk: G2Affine,
+    secret_key_shares: Vec<Scalar>,
+    public_key_shares: Vec<G2Affine>,
+}
+
+impl ThresholdKey {
+    /// Shares a random key among `nodes` nodes, such that the node with index
+    /// `i` holds the evaluation of the polynomial at `i + 1`
+    fn generate<R: RngCore + CryptoRng>(threshold: usize, nodes: usize, rng: &mut R) -> Self {
+        let coefficients: Vec<Scalar> = (0..threshold).map(|_| Scalar::random(rng)).collect();
+        let evaluate = |x: u64| {
+            let x = Scalar::from_u64(x);
+            coefficients
+                .iter()
+                .rev()
+                .fold(Scalar::zero(), |acc, coefficient| acc * &x + coefficient)
+        };
+        let secret_key_shares: Vec<Scalar> = (1..=nodes as u64).map(evaluate).collect();
+        let public_key_shares = secret_key_shares
+            .iter()
+            .map(|sk| G2Affine::from(G2Affine::generator() * sk))
+            .collect();
+        Self {
+            master_pk: G2Affine::from(G2Affine::generator() * &coefficients[0]),
+            secret_key_shares,
+            public_key_shares,
+        }
+    }
+
+    fn create_shares<R: RngCore + CryptoRng>(
+        &self,
+        tpk: &TransportPublicKey,
+        path: &DerivationPath,
+        rng: &mut R,
+    ) -> BTreeMap<NodeIndex, EncryptedKeyShare> {
+        self.secret_key_shares
+            .iter()
+            .enumerate()
+            .map(|(index, sk)| {
+                let share =
+                    EncryptedKeyShare::create(rng, &self.master_pk, sk, tpk, path, DERIVATION_ID);
+                (index as NodeIndex, share)
+            })
+            .collect()
+    }
+}
+
+fn subset(
+    shares: &BTreeMap<NodeIndex, EncryptedKeyShare>,
+    indexes: &[NodeIndex],
+) -> BTreeMap<NodeIndex, EncryptedKeyShare> {
+    indexes
+        .iter()
+        .map(|index| (*index, shares[index].clone()))
+        .collect()
+}
+
+#[test]
+fn should_derive_same_key_from_any_threshold_of_shares() {
+    let rng = &mut reproducible_rng();
+    let key = ThresholdKey::generate(3, 5, rng);
+    let tsk = TransportSecretKey::generate(rng);
+    let tpk = tsk.public_key();
+    let path = DerivationPath::new(b"canister", &[b"a".to_vec(), b"b".to_vec()]);
+    let dpk = DerivedPublicKey::compute_derived_key(&key.master_pk, &path);
+
+    let shares = key.create_shares(&tpk, &path, rng);
+    for (index, share) in &shares {
+        assert!(share.is_valid(
+            &key.master_pk,
+            &key.public_key_shares[*index as usize],
+            &path,
+            DERIVATION_ID,
+            &tpk
+        ));
+    }
+
+    let derived_keys: Vec<G1Affine> = [[0, 1, 2], [4, 2, 0], [1, 3, 4]]
+        .iter()
+        .map(|indexes| {
+            let ek = EncryptedKey::combine_all(&subset(&shares, indexes), 3).unwrap();
+            assert!(ek.is_valid(&key.master_pk, &path, DERIVATION_ID, &tpk));
+            tsk.decrypt(&ek, &dpk, DERIVATION_ID).unwrap()
+        })
+        .collect();
+    assert_eq!(derived_keys[0], derived_keys[1]);
+    assert_eq!(derived_keys[0], derived_keys[2]);
+
+    let other_tsk = TransportSecretKey::generate(rng);
+    let other_shares = key.create_shares(&other_tsk.public_key(), &path, rng);
+    let other_ek = EncryptedKey::combine_all(&other_shares, 3).unwrap();
+    assert_eq!(
+        other_tsk.decrypt(&other_ek, &dpk, DERIVATION_ID),
+        Some(derived_keys[0].clone())
+    );
+    assert_eq!(tsk.decrypt(&other_ek, &dpk, DERIVATION_ID), None);
+}
+
+#[test]
+fn should_reject_shares_for_other_inputs() {
+    let rng = &mut reproducible_rng();
+    let key = ThresholdKey::generate(2, 3, rng);
+    let tpk = TransportSecretKey::generate(rng).public_key();
+    let path = DerivationPath::new(b"canister", &[]);
+    let shares = key.create_shares(&tpk, &path, rng);
+    let share = &shares[&0];
+
+    let is_valid =
+        |pk_share: &G2Affine, path: &DerivationPath, id: &[u8], tpk: &TransportPublicKey| {
+            share.is_valid(&key.master_pk, pk_share, path, id, tpk)
+        };
+    assert!(is_valid(
+        &key.public_key_shares[0],
+        &path,
+        DERIVATION_ID,
+        &tpk
+    ));
+    assert!(!is_valid(
+        &key.public_key_shares[1],
+        &path,
+        DERIVATION_ID,
+        &tpk
+    ));
+    assert!(!is_valid(
+        &key.public_key_shares[0],
+        &DerivationPath::new(b"other canister", &[]),
+        DERIVATION_ID,
+        &tpk
+    ));
+    assert!(!is_valid(&key.public_key_shares[0], &path, b"other", &tpk));
+    assert!(!is_valid(
+        &key.public_key_shares[0],
+        &path,
+        DERIVATION_ID,
+        &TransportSecretKey::generate(rng).public_key()
+    ));
+}
+
+#[test]
+fn should_combine_only_valid_shares() {
+    let rng = &mut reproducible_rng();
+    let key = ThresholdKey::generate(2, 4, rng);
+    let tpk = TransportSecretKey::generate(rng).public_key();
+    let path = DerivationPath::new(b"canister", &[]);
+    let shares = key.create_shares(&tpk, &path, rng);
+    let other_path_shares = key.create_shares(&tpk, &DerivationPath::new(b"other", &[]), rng);
+
+    let with_public_key_shares = |shares: BTreeMap<NodeIndex, EncryptedKeyShare>| {
+        shares
+            .into_iter()
+            .map(|(index, share)| {
+                let pk_share = key.public_key_shares[index as usize].clone();
+                (index, (pk_share, share))
+            })
+            .collect::<BTreeMap<_, _>>()
+    };
+    let combine_valid_shares = |shares| {
+        EncryptedKey::combine_valid_shares(
+            &with_public_key_shares(shares),
+            2,
+            &key.master_pk,
+            &path,
+            DERIVATION_ID,
+            &tpk,
+        )
+    };
+
+    let mut mixed_shares = subset(&other_path_shares, &[0, 1]);
+    mixed_shares.extend(subset(&shares, &[2, 3]));
+    let ek = combine_valid_shares(mixed_shares.clone()).unwrap();
+    assert!(ek.is_valid(&key.master_pk, &path, DERIVATION_ID, &tpk));
+    assert!(!EncryptedKey::combine_all(&mixed_shares, 2)
+        .unwrap()
+        .is_valid(&key.master_pk, &path, DERIVATION_ID, &tpk));
+
+    let mut too_few_valid_shares = subset(&other_path_shares, &[0, 1, 2]);
+    too_few_valid_shares.extend(subset(&shares, &[3]));
+    assert_eq!(
+        combine_valid_shares(too_few_valid_shares),
+        Err(VetKdError::InsufficientValidShares {
+            threshold: 2,
+            valid_shares: 1
+        })
+    );
+}
+
+#[test]
+fn should_fail_to_combine_with_invalid_threshold_or_insufficient_shares() {
+    let rng = &mut reproducible_rng();
+    let key = ThresholdKey::generate(3, 3, rng);
+    let tpk = TransportSecretKey::generate(rng).public_key();
+    let shares = key.create_shares(&tpk, &DerivationPath::new(b"canister", &[]), rng);
+
+    assert_eq!(
+        EncryptedKey::combine_all(&shares, 0),
+        Err(VetKdError::InvalidThreshold)
+    );
+    assert_eq!(
+        EncryptedKey::combine_all(&shares, 4),
+        Err(VetKdError::InsufficientShares {
+            threshold: 4,
+            shares: 3
+        })
+    );
+}
+
+#[test]
+fn should_distinguish_derivation_paths_by_their_components() {
+    let paths = [
+        DerivationPath::new(b"ab", &[]),
+        DerivationPath::new(b"a", &[b"b".to_vec()]),
+        DerivationPath::new(b"a", &[b"b".to_vec(), vec![]]),
+        DerivationPath::new(b"", &[b"ab".to_vec()]),
+    ];
+    for (i, path) in paths.iter().enumerate() {
+        for other in &paths[i + 1..] {
+            assert_ne!(path, other);
+        }
+    }
+}
+
+#[test]
+fn should_serialize_and_deserialize() {
+    let rng = &mut reproducible_rng();
+    let key = ThresholdKey::generate(1, 1, rng);
+    let tsk = TransportSecretKey::generate(rng);
+    let path = DerivationPath::new(b"canister", &[]);
+    let share = key.create_shares(&tsk.public_key(), &path, rng)[&0].clone();
+    let ek = EncryptedKey::combine_all(&[(0, share.clone())].into(), 1).unwrap();
+    let dpk = DerivedPublicKey::compute_derived_key(&key.master_pk, &path);
+
+    assert_eq!(
+        TransportSecretKey::deserialize(&tsk.serialize())
+            .unwrap()
+            .public_key(),
+        tsk.public_key()
+    );
+    assert_eq!(
+        TransportPublicKey::deserialize(&tsk.public_key().serialize()),
+        Ok(tsk.public_key())
+    );
+    assert_eq!(
+        DerivedPublicKey::deserialize(&dpk.serialize()),
+        Ok(dpk.clone())
+    );
+    assert_eq!(
+        EncryptedKeyShare::deserialize(&share.serialize()),
+        Ok(share.clone())
+    );
+    assert_eq!(EncryptedKey::deserialize(&ek.serialize()), Ok(ek.clone()));
+
+    assert_matches!(
+        EncryptedKey::deserialize(&ek.serialize()[1..]),
+        Err(VetKdError::InvalidEncryptedKey)
+    );
+    assert_matches!(
+        EncryptedKeyShare::deserialize(&[0xff; EncryptedKeyShare::BYTES]),
+        Err(VetKdError::InvalidEncryptedKey)
+    );
+    assert_matches!(
+        TransportPublicKey::deserialize(&dpk.serialize()),
+        Err(VetKdError::InvalidTransportPublicKey)
+    );
+    assert_matches!(
+        DerivedPublicKey::deserialize(&[0xff; DerivedPublicKey::BYTES]),
+        Err(VetKdError::InvalidDerivedPublicKey)
+    );
+    assert_matches!(
+        TransportSecretKey::deserialize(&[0xff; TransportSecretKey::BYTES]),
+        Err(VetKdError::InvalidTransportSecretKey)
+    );
+}
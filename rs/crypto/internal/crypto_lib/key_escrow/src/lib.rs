@@ -0,0 +1,307 @@
+//! Threshold-encrypted escrow of secrets
+//!
+//! A secret is escrowed with `n` recovery public keys and a threshold `m`, such
+//! that any `m` holders of the corresponding recovery private keys can jointly
+//! recover it, whereas fewer than `m` of them learn nothing about it.
+//!
+//! The secret is encrypted with ChaCha20Poly1305 under a key derived from a
+//! random scalar. The scalar is Shamir-shared with threshold `m`, and the share
+//! of each recipient is MEGa-encrypted to their recovery public key. To recover
+//! the secret, `m` holders each decrypt their share with
+//! [`EscrowedSecret::decrypt_share`], and the shares are combined with
+//! [`EscrowedSecret::combine`].
+//!
+//! The associated data binds the escrowed secret to its context, e.g., the ID
+//! of the node whose keys are escrowed, and must be the same for escrow and
+//! recovery.
+//!
+//! # Examples
+//!
+//! ```
+//! use ic_crypto_internal_key_escrow::{EscrowedSecret, RECOVERY_KEY_CURVE};
+//! use ic_crypto_internal_threshold_sig_ecdsa::MEGaPrivateKey;
+//! use ic_crypto_secrets_containers::SecretBytes;
+//!
+//! let rng = &mut rand::thread_rng();
+//! let private_keys: Vec<_> = (0..3)
+//!     .map(|_| MEGaPrivateKey::generate(RECOVERY_KEY_CURVE, rng))
+//!     .collect();
+//! let public_keys: Vec<_> = private_keys
+//!     .iter()
+//!     .map(|sk| sk.public_key().expect("valid private key"))
+//!     .collect();
+//!
+//! let secret = SecretBytes::new(b"secret".to_vec());
+//! let escrowed = EscrowedSecret::encrypt(&secret, &public_keys, 2, b"context", rng)
+//!     .expect("escrow failed");
+//!
+//! let shares = [
+//!     escrowed.decrypt_share(b"context", 0, &private_keys[0]).expect("decryption failed"),
+//!     escrowed.decrypt_share(b"context", 2, &private_keys[2]).expect("decryption failed"),
+//! ];
+//! let recovered = escrowed.combine(b"context", &shares).expect("recovery failed");
+//! assert_eq!(recovered, secret);
+//! ```
+#![forbid(unsafe_code)]
+#![deny(clippy::unwrap_used)]
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use ic_crypto_internal_seed::Seed;
+use ic_crypto_internal_threshold_sig_ecdsa::{
+    EccCurveType, EccScalar, EccScalarBytes, LagrangeCoefficients, MEGaCiphertextSingle,
+    MEGaPrivateKey, MEGaPublicKey, NodeIndex, Polynomial, ThresholdEcdsaError,
+};
+use ic_crypto_secrets_containers::SecretBytes;
+use ic_crypto_sha::Sha256;
+use rand::{CryptoRng, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+#[cfg(test)]
+mod tests;
+
+/// The curve of the recovery key pairs
+pub const RECOVERY_KEY_CURVE: EccCurveType = EccCurveType::K256;
+
+const AEAD_KEY_DOMAIN_SEP: &str = "ic-crypto-key-escrow-aead-key";
+const MEGA_SEED_DOMAIN_SEP: &str = "ic-crypto-key-escrow-share-encryption";
+const NONCE_LEN: usize = 12;
+
+/// MEGa ciphertexts are bound to the index of their dealer, of which there is
+/// only one when escrowing a secret.
+const DEALER_INDEX: NodeIndex = 0;
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum KeyEscrowError {
+    /// The threshold is zero or exceeds the number of recovery public keys.
+    InvalidThreshold { threshold: usize, recipients: usize },
+    /// A recovery public key is not on [`RECOVERY_KEY_CURVE`].
+    InvalidRecoveryKey(String),
+    /// The index is not the one of a recipient, or the recovery private key
+    /// does not belong to the recipient with the index.
+    InvalidRecipient { index: NodeIndex },
+    /// Fewer shares than the threshold were provided.
+    InsufficientShares { threshold: usize, shares: usize },
+    /// The shares are malformed or have duplicate indexes.
+    InvalidShares(String),
+    /// A share or the secret could not be decrypted, e.g., because a share is
+    /// wrong or the associated data differs from the one used for escrow.
+    DecryptionFailed,
+    /// The secret could not be encrypted.
+    EncryptionFailed(String),
+}
+
+/// A secret encrypted such that a threshold of recovery key holders can
+/// recover it.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EscrowedSecret {
+    threshold: usize,
+    recovery_public_keys: Vec<MEGaPublicKey>,
+    encrypted_shares: MEGaCiphertextSingle,
+    nonce: [u8; NONCE_LEN],
+    #[serde(with = "serde_bytes")]
+    ciphertext: Vec<u8>,
+}
+
+/// A recipient's share of the key that encrypts an [`EscrowedSecret`].
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct RecoveryShare {
+    index: NodeIndex,
+    share: EccScalarBytes,
+}
+
+impl RecoveryShare {
+    /// The index of the recipient the share belongs to
+    pub fn index(&self) -> NodeIndex {
+        self.index
+    }
+}
+
+impl fmt::Debug for RecoveryShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RecoveryShare {{ index: {}, share: REDACTED }}",
+            self.index
+        )
+    }
+}
+
+impl EscrowedSecret {
+    /// Encrypts `secret` such that any `threshold` holders of the private keys
+    /// of `recovery_public_keys` can recover it. The index of a recipient is
+    /// the position of their public key in `recovery_public_keys`.
+    pub fn encrypt<R: RngCore + CryptoRng>(
+        secret: &SecretBytes,
+        recovery_public_keys: &[MEGaPublicKey],
+        threshold: usize,
+        associated_data: &[u8],
+        rng: &mut R,
+    ) -> Result<Self, KeyEscrowError> {
+        if threshold == 0 || threshold > recovery_public_keys.len() {
+            return Err(KeyEscrowError::InvalidThreshold {
+                threshold,
+                recipients: recovery_public_keys.len(),
+            });
+        }
+        if let Some(key) = recovery_public_keys
+            .iter()
+            .find(|key| key.curve_type() != RECOVERY_KEY_CURVE)
+        {
+            return Err(KeyEscrowError::InvalidRecoveryKey(format!(
+                "expected a key on curve {:?} but got one on {:?}",
+                RECOVERY_KEY_CURVE,
+                key.curve_type()
+            )));
+        }
+        let encryption_error =
+            |e: ThresholdEcdsaError| KeyEscrowError::EncryptionFailed(format!("{:?}", e));
+
+        let key_scalar = EccScalar::random(RECOVERY_KEY_CURVE, rng);
+        let polynomial = Polynomial::random_with_constant(&key_scalar, threshold, rng)
+            .map_err(encryption_error)?;
+        let shares = (0..recovery_public_keys.len())
+            .map(|index| {
+                polynomial.evaluate_at(&EccScalar::from_node_index(
+                    RECOVERY_KEY_CURVE,
+                    index as NodeIndex,
+                ))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(encryption_error)?;
+        let encrypted_shares = MEGaCiphertextSingle::encrypt(
+            Seed::from_rng(rng).derive(MEGA_SEED_DOMAIN_SEP),
+            &shares,
+            recovery_public_keys,
+            DEALER_INDEX,
+            associated_data,
+        )
+        .map_err(encryption_error)?;
+
+        let nonce: [u8; NONCE_LEN] = rng.gen();
+        let ciphertext = aead_cipher(&key_scalar)
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: secret.expose_secret(),
+                    aad: associated_data,
+                },
+            )
+            .map_err(|e| KeyEscrowError::EncryptionFailed(e.to_string()))?;
+
+        Ok(Self {
+            threshold,
+            recovery_public_keys: recovery_public_keys.to_vec(),
+            encrypted_shares,
+            nonce,
+            ciphertext,
+        })
+    }
+
+    /// The number of shares required to recover the secret
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    pub fn recovery_public_keys(&self) -> &[MEGaPublicKey] {
+        &self.recovery_public_keys
+    }
+
+    /// The index of the recipient with the given recovery public key, if any
+    pub fn recipient_index(&self, recovery_public_key: &MEGaPublicKey) -> Option<NodeIndex> {
+        self.recovery_public_keys
+            .iter()
+            .position(|key| key == recovery_public_key)
+            .map(|index| index as NodeIndex)
+    }
+
+    /// Decrypts the share of the recipient with the given `index`.
+    pub fn decrypt_share(
+        &self,
+        associated_data: &[u8],
+        index: NodeIndex,
+        recovery_private_key: &MEGaPrivateKey,
+    ) -> Result<RecoveryShare, KeyEscrowError> {
+        let public_key = self
+            .recovery_public_keys
+            .get(index as usize)
+            .ok_or(KeyEscrowError::InvalidRecipient { index })?;
+        if recovery_private_key.public_key().ok().as_ref() != Some(public_key) {
+            return Err(KeyEscrowError::InvalidRecipient { index });
+        }
+        let share = self
+            .encrypted_shares
+            .decrypt(
+                associated_data,
+                DEALER_INDEX,
+                index,
+                recovery_private_key,
+                public_key,
+            )
+            .map_err(|_| KeyEscrowError::DecryptionFailed)?;
+        Ok(RecoveryShare {
+            index,
+            share: EccScalarBytes::try_from(&share)
+                .map_err(|e| KeyEscrowError::InvalidShares(format!("{:?}", e)))?,
+        })
+    }
+
+    /// Recovers the secret from the shares of at least [`Self::threshold`]
+    /// distinct recipients. Only the first [`Self::threshold`] shares are used.
+    pub fn combine(
+        &self,
+        associated_data: &[u8],
+        shares: &[RecoveryShare],
+    ) -> Result<SecretBytes, KeyEscrowError> {
+        if shares.len() < self.threshold {
+            return Err(KeyEscrowError::InsufficientShares {
+                threshold: self.threshold,
+                shares: shares.len(),
+            });
+        }
+        let shares = &shares[..self.threshold];
+        if let Some(share) = shares
+            .iter()
+            .find(|share| share.index as usize >= self.recovery_public_keys.len())
+        {
+            return Err(KeyEscrowError::InvalidRecipient { index: share.index });
+        }
+        let invalid_shares =
+            |e: ThresholdEcdsaError| KeyEscrowError::InvalidShares(format!("{:?}", e));
+
+        let indexes: Vec<NodeIndex> = shares.iter().map(|share| share.index).collect();
+        let values = shares
+            .iter()
+            .map(|share| EccScalar::try_from(&share.share))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(invalid_shares)?;
+        let key_scalar = LagrangeCoefficients::at_zero(RECOVERY_KEY_CURVE, &indexes)
+            .and_then(|coefficients| coefficients.interpolate_scalar(&values))
+            .map_err(invalid_shares)?;
+
+        let secret = aead_cipher(&key_scalar)
+            .decrypt(
+                Nonce::from_slice(&self.nonce),
+                Payload {
+                    msg: &self.ciphertext,
+                    aad: associated_data,
+                },
+            )
+            .map_err(|_| KeyEscrowError::DecryptionFailed)?;
+        Ok(SecretBytes::new(secret))
+    }
+}
+
+fn aead_cipher(key_scalar: &EccScalar) -> ChaCha20Poly1305 {
+    let mut key_scalar_bytes = key_scalar.serialize();
+    let mut hash = Sha256::new();
+    hash.write(AEAD_KEY_DOMAIN_SEP.as_bytes());
+    hash.write(&key_scalar_bytes);
+    let mut key = hash.finish();
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+    key_scalar_bytes.zeroize();
+    key.zeroize();
+    cipher
+}
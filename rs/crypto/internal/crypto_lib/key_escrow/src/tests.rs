@@ -0,0 +1,129 @@
+use super::*;
+use assert_matches::assert_matches;
+use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
+
+const ASSOCIATED_DATA: &[u8] = b"node-1";
+
+fn recovery_key_pairs<R: RngCore + CryptoRng>(
+    n: usize,
+    rng: &mut R,
+) -> (Vec<MEGaPrivateKey>, Vec<MEGaPublicKey>) {
+    let private_keys: Vec<_> = (0..n)
+        .map(|_| MEGaPrivateKey::generate(RECOVERY_KEY_CURVE, rng))
+        .collect();
+    let public_keys = private_keys
+        .iter()
+        .map(|private_key| private_key.public_key().expect("invalid private key"))
+        .collect();
+    (private_keys, public_keys)
+}
+
+fn shares(
+    escrowed: &EscrowedSecret,
+    private_keys: &[MEGaPrivateKey],
+    indexes: &[NodeIndex],
+) -> Vec<RecoveryShare> {
+    indexes
+        .iter()
+        .map(|&index| {
+            escrowed
+                .decrypt_share(ASSOCIATED_DATA, index, &private_keys[index as usize])
+                .expect("failed to decrypt share")
+        })
+        .collect()
+}
+
+#[test]
+fn should_recover_secret_from_any_threshold_of_shares() {
+    let rng = &mut reproducible_rng();
+    let (private_keys, public_keys) = recovery_key_pairs(5, rng);
+    let secret = SecretBytes::new(b"node secret keys".to_vec());
+
+    let escrowed =
+        EscrowedSecret::encrypt(&secret, &public_keys, 3, ASSOCIATED_DATA, rng).expect("escrow");
+
+    assert_eq!(escrowed.threshold(), 3);
+    assert_eq!(escrowed.recipient_index(&public_keys[4]), Some(4));
+    for indexes in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+        let shares = shares(&escrowed, &private_keys, &indexes);
+        assert_eq!(
+            escrowed.combine(ASSOCIATED_DATA, &shares),
+            Ok(secret.clone())
+        );
+    }
+}
+
+#[test]
+fn should_not_recover_secret_from_insufficient_or_wrong_shares() {
+    let rng = &mut reproducible_rng();
+    let (private_keys, public_keys) = recovery_key_pairs(3, rng);
+    let secret = SecretBytes::new(b"node secret keys".to_vec());
+    let escrowed =
+        EscrowedSecret::encrypt(&secret, &public_keys, 2, ASSOCIATED_DATA, rng).expect("escrow");
+    let other_escrowed =
+        EscrowedSecret::encrypt(&secret, &public_keys, 2, ASSOCIATED_DATA, rng).expect("escrow");
+
+    assert_matches!(
+        escrowed.combine(ASSOCIATED_DATA, &shares(&escrowed, &private_keys, &[1])),
+        Err(KeyEscrowError::InsufficientShares {
+            threshold: 2,
+            shares: 1
+        })
+    );
+    assert_matches!(
+        escrowed.combine(ASSOCIATED_DATA, &shares(&escrowed, &private_keys, &[1, 1])),
+        Err(KeyEscrowError::InvalidShares(_))
+    );
+
+    let mut mixed_shares = shares(&escrowed, &private_keys, &[0]);
+    mixed_shares.extend(shares(&other_escrowed, &private_keys, &[1]));
+    assert_matches!(
+        escrowed.combine(ASSOCIATED_DATA, &mixed_shares),
+        Err(KeyEscrowError::DecryptionFailed)
+    );
+
+    let valid_shares = shares(&escrowed, &private_keys, &[0, 2]);
+    assert_matches!(
+        escrowed.combine(b"node-2", &valid_shares),
+        Err(KeyEscrowError::DecryptionFailed)
+    );
+}
+
+#[test]
+fn should_only_decrypt_share_of_recipient() {
+    let rng = &mut reproducible_rng();
+    let (private_keys, public_keys) = recovery_key_pairs(3, rng);
+    let secret = SecretBytes::new(b"node secret keys".to_vec());
+    let escrowed =
+        EscrowedSecret::encrypt(&secret, &public_keys, 2, ASSOCIATED_DATA, rng).expect("escrow");
+
+    assert_matches!(
+        escrowed.decrypt_share(ASSOCIATED_DATA, 1, &private_keys[0]),
+        Err(KeyEscrowError::InvalidRecipient { index: 1 })
+    );
+    assert_matches!(
+        escrowed.decrypt_share(ASSOCIATED_DATA, 3, &private_keys[0]),
+        Err(KeyEscrowError::InvalidRecipient { index: 3 })
+    );
+    assert_matches!(
+        escrowed.decrypt_share(b"node-2", 0, &private_keys[0]),
+        Err(KeyEscrowError::DecryptionFailed)
+    );
+}
+
+#[test]
+fn should_fail_to_escrow_with_invalid_threshold() {
+    let rng = &mut reproducible_rng();
+    let (_, public_keys) = recovery_key_pairs(3, rng);
+    let secret = SecretBytes::new(b"node secret keys".to_vec());
+
+    for threshold in [0, 4] {
+        assert_eq!(
+            EscrowedSecret::encrypt(&secret, &public_keys, threshold, ASSOCIATED_DATA, rng),
+            Err(KeyEscrowError::InvalidThreshold {
+                threshold,
+                recipients: 3
+            })
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use sha3::Digest;
+
+/// Hasher with fixed algorithm that is guaranteed not to change in the future
+/// or across registry versions. The algorithm used to generate the hash is
+/// Keccak-256, i.e., SHA3-256 with the padding of the original Keccak
+/// submission, and therefore has constant output size of 32 bytes.
+///
+/// This is the hash used by Ethereum, e.g., to derive addresses from public
+/// keys.
+#[derive(Default)]
+pub struct Keccak256 {
+    state: sha3::Keccak256,
+}
+
+impl Keccak256 {
+    /// Returns a new Keccak256 object.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hashes some data and returns the digest
+    pub fn hash(data: &[u8]) -> [u8; 32] {
+        let mut hasher = Self::new();
+        hasher.write(data);
+        hasher.finish()
+    }
+
+    /// Incrementally update the current hash
+    pub fn write(&mut self, data: &[u8]) {
+        self.state.update(data);
+    }
+
+    /// Finishes computing a hash, returning the digest
+    pub fn finish(self) -> [u8; 32] {
+        self.state.finalize().into()
+    }
+}
+
+impl std::io::Write for Keccak256 {
+    /// Update an incremental hash
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.write(buf);
+        Ok(buf.len())
+    }
+
+    /// This is a no-op
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl std::hash::Hasher for Keccak256 {
+    /// This function will panic; use finish() -> [u8; 32] instead
+    fn finish(&self) -> u64 {
+        panic!(
+            "not supported because the hash values produced by this hasher \
+             contain more than just the 64 bits returned by this method"
+        )
+    }
+
+    /// Update an incremental hash
+    fn write(&mut self, bytes: &[u8]) {
+        self.write(bytes)
+    }
+}
@@ -0,0 +1,17 @@
+//! Hashing utilities based on the SHA-3 standard, and on the original Keccak
+//! submission that Ethereum uses.
+//!
+//! Unlike SHA-2, there is no architecture-dependent implementation: the pure
+//! Rust implementation of the sha3 package is used both on Wasm and native.
+//!
+//! Clients should not use this crate directly, but rather the crate
+//! ic_crypto_sha, which is a thin layer on top of this internal crate.
+
+#![forbid(unsafe_code)]
+#![deny(clippy::unwrap_used)]
+
+mod keccak256;
+pub use keccak256::Keccak256;
+
+mod sha3_256;
+pub use sha3_256::Sha3_256;
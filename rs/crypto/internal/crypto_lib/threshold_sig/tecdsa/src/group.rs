@@ -1,3 +1,4 @@
+use crate::timing::{timed, TimedOperation};
 use crate::*;
 use fe::EccFieldElement;
 use hex_literal::hex;
@@ -543,11 +544,11 @@ impl EccPoint {
 
     /// Perform point*scalar multiplication
     pub fn scalar_mul(&self, scalar: &EccScalar) -> ThresholdEcdsaResult<Self> {
-        match (&self.point, scalar) {
+        timed(TimedOperation::ScalarMul, || match (&self.point, scalar) {
             (EccPointInternal::K256(pt), EccScalar::K256(s)) => Ok(pt.mul(s).into()),
             (EccPointInternal::P256(pt), EccScalar::P256(s)) => Ok(pt.mul(s).into()),
             _ => Err(ThresholdEcdsaError::CurveMismatch),
-        }
+        })
     }
 
     /// Perform point doubling
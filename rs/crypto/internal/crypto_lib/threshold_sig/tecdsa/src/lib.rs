@@ -195,6 +195,14 @@
 //! hashes them using XMD to produce outputs which can be scalars, points,
 //! or bytestrings.
 //!
+//! ## Utility Functions: Timing Instrumentation
+//!
+//! File: `timing.rs`
+//!
+//! With the `timing-instrumentation` feature, records the timings of
+//! secret-dependent operations, such that constant-time audits can compare
+//! their distributions across classes of secret inputs.
+//!
 //! ## Utility Functions: Testing
 //!
 //! File: `test_utils.rs`
@@ -257,6 +265,7 @@ mod poly;
 pub mod ro;
 pub mod sign;
 pub mod test_utils;
+pub mod timing;
 mod transcript;
 pub mod zk;
 
@@ -1,4 +1,5 @@
 use crate::group::*;
+use crate::timing::{timed, TimedOperation};
 use crate::*;
 use core::fmt::{self, Debug};
 use ic_crypto_secrets_containers::SecretArray;
@@ -572,21 +573,23 @@ impl MEGaCiphertextSingle {
         our_private_key: &MEGaPrivateKey,
         recipient_public_key: &MEGaPublicKey,
     ) -> ThresholdEcdsaResult<EccScalar> {
-        // Since we only decrypt verified dealings, and the PoP is already
-        // verified during dealing verification, this check should never
-        // fail. However it is retained as it is not too expensive and more
-        // closely matches the description in the paper.
-        self.verify_pop(associated_data, dealer_index)?;
+        timed(TimedOperation::MEGaDecryptSingle, || {
+            // Since we only decrypt verified dealings, and the PoP is already
+            // verified during dealing verification, this check should never
+            // fail. However it is retained as it is not too expensive and more
+            // closely matches the description in the paper.
+            self.verify_pop(associated_data, dealer_index)?;
 
-        let ubeta = self.ephemeral_key.scalar_mul(&our_private_key.secret)?;
+            let ubeta = self.ephemeral_key.scalar_mul(&our_private_key.secret)?;
 
-        self.decrypt_from_shared_secret(
-            associated_data,
-            dealer_index,
-            recipient_index,
-            recipient_public_key,
-            &ubeta,
-        )
+            self.decrypt_from_shared_secret(
+                associated_data,
+                dealer_index,
+                recipient_index,
+                recipient_public_key,
+                &ubeta,
+            )
+        })
     }
 }
 
@@ -687,21 +690,23 @@ impl MEGaCiphertextPair {
         our_private_key: &MEGaPrivateKey,
         recipient_public_key: &MEGaPublicKey,
     ) -> ThresholdEcdsaResult<(EccScalar, EccScalar)> {
-        // Since we only decrypt verified dealings, and the PoP is already
-        // verified during dealing verification, this check should never
-        // fail. However it is retained as it is not too expensive and more
-        // closely matches the description in the paper.
-        self.verify_pop(associated_data, dealer_index)?;
+        timed(TimedOperation::MEGaDecryptPair, || {
+            // Since we only decrypt verified dealings, and the PoP is already
+            // verified during dealing verification, this check should never
+            // fail. However it is retained as it is not too expensive and more
+            // closely matches the description in the paper.
+            self.verify_pop(associated_data, dealer_index)?;
 
-        let ubeta = self.ephemeral_key.scalar_mul(&our_private_key.secret)?;
+            let ubeta = self.ephemeral_key.scalar_mul(&our_private_key.secret)?;
 
-        self.decrypt_from_shared_secret(
-            associated_data,
-            dealer_index,
-            recipient_index,
-            recipient_public_key,
-            &ubeta,
-        )
+            self.decrypt_from_shared_secret(
+                associated_data,
+                dealer_index,
+                recipient_index,
+                recipient_public_key,
+                &ubeta,
+            )
+        })
     }
 }
 
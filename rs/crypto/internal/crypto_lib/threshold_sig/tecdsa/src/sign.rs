@@ -1,3 +1,4 @@
+use crate::timing::{timed, TimedOperation};
 use crate::DerivationPath;
 use crate::*;
 use ic_types::crypto::canister_threshold_sig::MasterEcdsaPublicKey;
@@ -82,51 +83,55 @@ impl ThresholdEcdsaSigShareInternal {
         key_times_lambda: &CommitmentOpening,
         curve_type: EccCurveType,
     ) -> ThresholdEcdsaResult<Self> {
-        let (rho, key_tweak, randomizer, _presig) = derive_rho(
-            curve_type,
-            hashed_message,
-            &randomness,
-            derivation_path,
-            key_transcript,
-            presig_transcript,
-        )?;
-
-        // Compute the message representative from the hash, which may require
-        // a reduction if int(hashed_message) >= group_order
-        let e = convert_hash_to_integer(hashed_message, curve_type)?;
-
-        let theta = e.add(&rho.mul(&key_tweak)?)?;
-
-        let (lambda_value, lambda_mask) = match lambda {
-            CommitmentOpening::Pedersen(lambda_value, lambda_mask) => (lambda_value, lambda_mask),
-            _ => return Err(ThresholdEcdsaError::UnexpectedCommitmentType),
-        };
-
-        // Compute shares of sigma's numerator, i.e. openings of
-        // [nu] = theta*[lambda] + rho*[key_times_lambda]
-        let nu = match key_times_lambda {
-            CommitmentOpening::Pedersen(value, mask) => {
-                let nu_value = theta.mul(lambda_value)?.add(&rho.mul(value)?)?;
-                let nu_mask = theta.mul(lambda_mask)?.add(&rho.mul(mask)?)?;
-                CommitmentOpening::Pedersen(nu_value, nu_mask)
-            }
-            _ => return Err(ThresholdEcdsaError::UnexpectedCommitmentType),
-        };
-
-        // Compute shares of sigma's denominator, i.e. openings of
-        // [mu] = randomizer*[lambda] + [kappa_times_lambda]
-        let mu = match kappa_times_lambda {
-            CommitmentOpening::Pedersen(value, mask) => {
-                let mu_value = randomizer.mul(lambda_value)?.add(value)?;
-                let mu_mask = randomizer.mul(lambda_mask)?.add(mask)?;
-                CommitmentOpening::Pedersen(mu_value, mu_mask)
-            }
-            _ => return Err(ThresholdEcdsaError::UnexpectedCommitmentType),
-        };
+        timed(TimedOperation::SignShare, || {
+            let (rho, key_tweak, randomizer, _presig) = derive_rho(
+                curve_type,
+                hashed_message,
+                &randomness,
+                derivation_path,
+                key_transcript,
+                presig_transcript,
+            )?;
+
+            // Compute the message representative from the hash, which may require
+            // a reduction if int(hashed_message) >= group_order
+            let e = convert_hash_to_integer(hashed_message, curve_type)?;
+
+            let theta = e.add(&rho.mul(&key_tweak)?)?;
+
+            let (lambda_value, lambda_mask) = match lambda {
+                CommitmentOpening::Pedersen(lambda_value, lambda_mask) => {
+                    (lambda_value, lambda_mask)
+                }
+                _ => return Err(ThresholdEcdsaError::UnexpectedCommitmentType),
+            };
+
+            // Compute shares of sigma's numerator, i.e. openings of
+            // [nu] = theta*[lambda] + rho*[key_times_lambda]
+            let nu = match key_times_lambda {
+                CommitmentOpening::Pedersen(value, mask) => {
+                    let nu_value = theta.mul(lambda_value)?.add(&rho.mul(value)?)?;
+                    let nu_mask = theta.mul(lambda_mask)?.add(&rho.mul(mask)?)?;
+                    CommitmentOpening::Pedersen(nu_value, nu_mask)
+                }
+                _ => return Err(ThresholdEcdsaError::UnexpectedCommitmentType),
+            };
+
+            // Compute shares of sigma's denominator, i.e. openings of
+            // [mu] = randomizer*[lambda] + [kappa_times_lambda]
+            let mu = match kappa_times_lambda {
+                CommitmentOpening::Pedersen(value, mask) => {
+                    let mu_value = randomizer.mul(lambda_value)?.add(value)?;
+                    let mu_mask = randomizer.mul(lambda_mask)?.add(mask)?;
+                    CommitmentOpening::Pedersen(mu_value, mu_mask)
+                }
+                _ => return Err(ThresholdEcdsaError::UnexpectedCommitmentType),
+            };
 
-        Ok(Self {
-            sigma_numerator: nu,
-            sigma_denominator: mu,
+            Ok(Self {
+                sigma_numerator: nu,
+                sigma_denominator: mu,
+            })
         })
     }
 
@@ -0,0 +1,256 @@
+//! Timing instrumentation for constant-time audits
+//!
+//! With the `timing-instrumentation` feature, the secret-dependent operations
+//! listed in [`TimedOperation`] record how long each invocation took. The
+//! recorded samples can be drained as distributions with
+//! [`take_distributions`] and exported as JSON with [`export_json`]. At most
+//! [`MAX_SAMPLES`] samples are kept per operation and input class until they
+//! are drained.
+//!
+//! To detect timing leaks, a harness runs an operation on two classes of
+//! secret inputs (e.g. a fixed secret and random secrets, interleaved in
+//! random order), setting the class of the current thread with
+//! [`set_input_class`] before each invocation. If the timings of the two
+//! classes differ significantly, as determined by Welch's t-test in
+//! [`detect_leaks`], the operation likely leaks its secret input.
+//!
+//! Without the feature, the instrumentation compiles to nothing.
+
+#[cfg(feature = "timing-instrumentation")]
+pub use instrumented::*;
+
+/// Runs `f`, recording its duration under `operation` if the
+/// `timing-instrumentation` feature is enabled.
+#[cfg(feature = "timing-instrumentation")]
+pub(crate) fn timed<T>(operation: TimedOperation, f: impl FnOnce() -> T) -> T {
+    let start = std::time::Instant::now();
+    let result = f();
+    instrumented::record(operation, start.elapsed());
+    result
+}
+
+#[cfg(not(feature = "timing-instrumentation"))]
+#[inline(always)]
+pub(crate) fn timed<T>(_operation: TimedOperation, f: impl FnOnce() -> T) -> T {
+    f()
+}
+
+/// An operation whose running time must not depend on secret inputs
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, serde::Serialize)]
+pub enum TimedOperation {
+    /// [`crate::EccPoint::scalar_mul`], including `mul_by_g`
+    ScalarMul,
+    /// Decryption of a [`crate::MEGaCiphertextSingle`]
+    MEGaDecryptSingle,
+    /// Decryption of a [`crate::MEGaCiphertextPair`]
+    MEGaDecryptPair,
+    /// Creation of a [`crate::ThresholdEcdsaSigShareInternal`]
+    SignShare,
+}
+
+#[cfg(feature = "timing-instrumentation")]
+mod instrumented {
+    use super::TimedOperation;
+    use serde::Serialize;
+    use std::cell::Cell;
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// The absolute t-statistic above which two timing distributions are
+    /// considered different, as commonly used by dudect
+    pub const LEAKAGE_THRESHOLD: f64 = 4.5;
+
+    /// The number of samples kept per operation and input class, such that
+    /// the samples of a harness that does not drain them use bounded memory.
+    /// Further samples are dropped.
+    pub const MAX_SAMPLES: usize = 1 << 20;
+
+    /// The class of secret inputs an operation is run on
+    #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize)]
+    pub enum InputClass {
+        Fixed,
+        Random,
+    }
+
+    type SampleKey = (TimedOperation, Option<InputClass>);
+
+    lazy_static::lazy_static! {
+        static ref SAMPLES: Mutex<BTreeMap<SampleKey, Vec<u64>>> = Mutex::new(BTreeMap::new());
+    }
+
+    thread_local! {
+        static INPUT_CLASS: Cell<Option<InputClass>> = Cell::new(None);
+    }
+
+    /// Tags the samples subsequently recorded on this thread with `class`
+    pub fn set_input_class(class: Option<InputClass>) {
+        INPUT_CLASS.with(|c| c.set(class));
+    }
+
+    pub(super) fn record(operation: TimedOperation, elapsed: Duration) {
+        let class = INPUT_CLASS.with(|c| c.get());
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        let mut samples = SAMPLES.lock().expect("timing samples lock poisoned");
+        let samples = samples.entry((operation, class)).or_default();
+        if samples.len() < MAX_SAMPLES {
+            samples.push(nanos);
+        }
+    }
+
+    /// Summary of the durations recorded for an operation and input class
+    #[derive(Clone, Debug, PartialEq, Serialize)]
+    pub struct TimingDistribution {
+        pub operation: TimedOperation,
+        pub class: Option<InputClass>,
+        pub count: usize,
+        pub min_ns: u64,
+        pub max_ns: u64,
+        pub mean_ns: f64,
+        pub variance_ns2: f64,
+        pub p50_ns: u64,
+        pub p90_ns: u64,
+        pub p99_ns: u64,
+    }
+
+    impl TimingDistribution {
+        /// Summarizes `samples`, returning `None` if there are none
+        pub fn from_samples(
+            operation: TimedOperation,
+            class: Option<InputClass>,
+            mut samples: Vec<u64>,
+        ) -> Option<Self> {
+            if samples.is_empty() {
+                return None;
+            }
+            samples.sort_unstable();
+            let count = samples.len();
+            let mean = samples.iter().map(|&s| s as f64).sum::<f64>() / count as f64;
+            let variance = if count > 1 {
+                samples
+                    .iter()
+                    .map(|&s| (s as f64 - mean).powi(2))
+                    .sum::<f64>()
+                    / (count - 1) as f64
+            } else {
+                0.0
+            };
+            let percentile = |p: usize| samples[((count * p + 99) / 100).clamp(1, count) - 1];
+            Some(Self {
+                operation,
+                class,
+                count,
+                min_ns: samples[0],
+                max_ns: samples[count - 1],
+                mean_ns: mean,
+                variance_ns2: variance,
+                p50_ns: percentile(50),
+                p90_ns: percentile(90),
+                p99_ns: percentile(99),
+            })
+        }
+    }
+
+    /// Drains the recorded samples, returning one distribution per operation
+    /// and input class
+    pub fn take_distributions() -> Vec<TimingDistribution> {
+        let samples = std::mem::take(&mut *SAMPLES.lock().expect("timing samples lock poisoned"));
+        samples
+            .into_iter()
+            .filter_map(|((operation, class), samples)| {
+                TimingDistribution::from_samples(operation, class, samples)
+            })
+            .collect()
+    }
+
+    /// Serializes the distributions, e.g. for archiving them as artifacts of
+    /// a CI run
+    pub fn export_json(distributions: &[TimingDistribution]) -> String {
+        serde_json::to_string_pretty(distributions).expect("failed to serialize distributions")
+    }
+
+    /// Welch's t-statistic of the means of two distributions
+    pub fn welch_t_statistic(a: &TimingDistribution, b: &TimingDistribution) -> f64 {
+        let se = (a.variance_ns2 / a.count as f64 + b.variance_ns2 / b.count as f64).sqrt();
+        if se == 0.0 {
+            return 0.0;
+        }
+        (a.mean_ns - b.mean_ns) / se
+    }
+
+    /// Returns the operations whose timings differ between the fixed and the
+    /// random input class by more than [`LEAKAGE_THRESHOLD`], along with the
+    /// t-statistic
+    pub fn detect_leaks(distributions: &[TimingDistribution]) -> Vec<(TimedOperation, f64)> {
+        let of_class = |operation, class| {
+            distributions
+                .iter()
+                .find(|d| d.operation == operation && d.class == Some(class))
+        };
+        let mut operations: Vec<TimedOperation> =
+            distributions.iter().map(|d| d.operation).collect();
+        operations.sort();
+        operations.dedup();
+        operations
+            .into_iter()
+            .filter_map(|operation| {
+                let fixed = of_class(operation, InputClass::Fixed)?;
+                let random = of_class(operation, InputClass::Random)?;
+                let t = welch_t_statistic(fixed, random);
+                (t.abs() > LEAKAGE_THRESHOLD).then_some((operation, t))
+            })
+            .collect()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn distribution(class: InputClass, samples: Vec<u64>) -> TimingDistribution {
+            TimingDistribution::from_samples(TimedOperation::ScalarMul, Some(class), samples)
+                .expect("samples are not empty")
+        }
+
+        #[test]
+        fn should_summarize_samples() {
+            let d = distribution(InputClass::Fixed, (1..=100).rev().collect());
+            assert_eq!(d.count, 100);
+            assert_eq!((d.min_ns, d.max_ns), (1, 100));
+            assert_eq!(d.mean_ns, 50.5);
+            assert_eq!((d.p50_ns, d.p90_ns, d.p99_ns), (50, 90, 99));
+            assert!(
+                TimingDistribution::from_samples(TimedOperation::SignShare, None, vec![]).is_none()
+            );
+        }
+
+        #[test]
+        fn should_cap_recorded_samples() {
+            set_input_class(Some(InputClass::Random));
+            for _ in 0..MAX_SAMPLES + 10 {
+                record(TimedOperation::SignShare, Duration::from_nanos(1));
+            }
+            set_input_class(None);
+
+            let distribution = take_distributions()
+                .into_iter()
+                .find(|d| {
+                    d.operation == TimedOperation::SignShare && d.class == Some(InputClass::Random)
+                })
+                .expect("samples were recorded");
+            assert_eq!(distribution.count, MAX_SAMPLES);
+        }
+
+        #[test]
+        fn should_detect_differing_timings_only() {
+            let fixed = distribution(InputClass::Fixed, vec![100, 101, 99, 100, 100, 101, 99]);
+            let same = distribution(InputClass::Random, vec![101, 99, 100, 100, 99, 101, 100]);
+            assert!(detect_leaks(&[fixed.clone(), same]).is_empty());
+
+            let slower = distribution(InputClass::Random, vec![120, 121, 119, 120, 120, 121, 119]);
+            let leaks = detect_leaks(&[fixed, slower]);
+            assert_eq!(leaks.len(), 1);
+            assert_eq!(leaks[0].0, TimedOperation::ScalarMul);
+            assert!(leaks[0].1 < -LEAKAGE_THRESHOLD);
+        }
+    }
+}
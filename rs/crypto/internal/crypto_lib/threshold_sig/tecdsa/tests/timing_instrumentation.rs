@@ -0,0 +1,52 @@
+//! Checks that the secret-dependent operations record their timings with the
+//! `timing-instrumentation` feature.
+#![cfg(feature = "timing-instrumentation")]
+
+use ic_crypto_internal_threshold_sig_ecdsa::timing::{
+    set_input_class, take_distributions, InputClass, TimedOperation,
+};
+use ic_crypto_internal_threshold_sig_ecdsa::*;
+use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
+use ic_types::*;
+use rand::Rng;
+
+mod test_utils;
+
+use crate::test_utils::*;
+
+#[test]
+fn should_record_timings_of_instrumented_operations() -> Result<(), ThresholdEcdsaError> {
+    let nodes = 4;
+    let threshold = nodes / 3;
+    let mut rng = reproducible_rng();
+
+    set_input_class(Some(InputClass::Fixed));
+    let setup = SignatureProtocolSetup::new(
+        EccCurveType::K256,
+        nodes,
+        threshold,
+        0,
+        Seed::from_rng(&mut rng),
+    )?;
+    let proto = SignatureProtocolExecution::new(
+        setup,
+        rng.gen::<[u8; 32]>().to_vec(),
+        Randomness::from(rng.gen::<[u8; 32]>()),
+        DerivationPath::new_bip32(&[1, 2, 3]),
+    );
+    let shares = proto.generate_shares()?;
+    set_input_class(None);
+
+    let distributions = take_distributions();
+    let count_of = |operation| {
+        distributions
+            .iter()
+            .find(|d| d.operation == operation && d.class == Some(InputClass::Fixed))
+            .map_or(0, |d| d.count)
+    };
+    assert!(count_of(TimedOperation::ScalarMul) > 0);
+    assert!(count_of(TimedOperation::MEGaDecryptSingle) > 0);
+    assert!(count_of(TimedOperation::MEGaDecryptPair) > 0);
+    assert_eq!(count_of(TimedOperation::SignShare), shares.len());
+    Ok(())
+}
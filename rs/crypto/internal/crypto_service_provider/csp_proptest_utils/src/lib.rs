@@ -24,7 +24,7 @@ mod common {
     use super::*;
     use proptest::array::uniform24;
 
-    pub(crate) const MAX_ALGORITHM_ID_INDEX: i32 = 16;
+    pub(crate) const MAX_ALGORITHM_ID_INDEX: i32 = 17;
 
     prop_compose! {
         pub fn arb_key_id()(id in uniform32(any::<u8>())) -> KeyId {
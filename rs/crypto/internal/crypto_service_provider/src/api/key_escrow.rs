@@ -0,0 +1,21 @@
+use crate::vault::api::{CspEscrowNodeSecretKeysError, CspSealedBackupError};
+use ic_crypto_internal_key_escrow::EscrowedSecret;
+
+/// A trait that allows escrowing the node secret keys, and backing up and
+/// restoring the node keys, for disaster recovery.
+pub trait CspKeyEscrow {
+    /// See documentation in [`crate::vault::api::KeyEscrowCspVault::escrow_node_secret_keys`].
+    fn escrow_node_secret_keys(
+        &self,
+        associated_data: Vec<u8>,
+    ) -> Result<EscrowedSecret, CspEscrowNodeSecretKeysError>;
+
+    /// See documentation in [`crate::vault::api::KeyEscrowCspVault::export_sealed_backup`].
+    fn export_sealed_backup(&self) -> Result<EscrowedSecret, CspSealedBackupError>;
+
+    /// See documentation in [`crate::vault::api::KeyEscrowCspVault::restore_sealed_backup`].
+    fn restore_sealed_backup(
+        &self,
+        sealed_backup: EscrowedSecret,
+    ) -> Result<(), CspSealedBackupError>;
+}
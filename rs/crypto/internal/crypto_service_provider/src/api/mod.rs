@@ -1,14 +1,17 @@
 //! Top level traits for interacting with the crypto service provider
 
 mod canister_threshold;
+mod key_escrow;
 mod keygen;
 mod sign;
 mod threshold;
 mod tls;
+mod vetkd;
 
 pub use canister_threshold::{
     CspCreateMEGaKeyError, CspIDkgProtocol, CspThresholdEcdsaSigVerifier, CspThresholdEcdsaSigner,
 };
+pub use key_escrow::CspKeyEscrow;
 pub use keygen::{
     CspKeyGenerator, CspPublicAndSecretKeyStoreChecker, CspPublicKeyStore, NodePublicKeyDataError,
 };
@@ -17,3 +20,4 @@ pub use threshold::{
     threshold_sign_error::CspThresholdSignError, NiDkgCspClient, ThresholdSignatureCspClient,
 };
 pub use tls::CspTlsHandshakeSignerProvider;
+pub use vetkd::CspVetKdProtocol;
@@ -27,6 +27,29 @@ pub trait CspSigner {
         key_id: KeyId,
     ) -> CryptoResult<CspSignature>;
 
+    /// Sign each of the given messages using the specified algorithm and key
+    /// IDs, with a single call to the CSP vault.
+    ///
+    /// # Arguments
+    /// * `algorithm_id` specifies the signature algorithm
+    /// * `msgs` are the message data to be signed
+    /// * `key_id` specifies the private key to sign with
+    /// # Errors
+    /// * `CryptoError::SecretKeyNotFound` if the key ID could not be located in
+    ///   the secret key store.
+    /// * `CryptoError::MalformedSecretKey` if the key data could be loaded from
+    ///   the secret key store, but the key type does not match algorithm_id
+    /// * `CryptoError::InvalidArgument` if the algorithm is not supported by
+    ///   the trait implementation. Only basic signatures are supported.
+    /// # Returns
+    /// The generated signatures, in the order of `msgs`
+    fn sign_batch(
+        &self,
+        algorithm_id: AlgorithmId,
+        msgs: &[Vec<u8>],
+        key_id: KeyId,
+    ) -> CryptoResult<Vec<CspSignature>>;
+
     /// Verify a public key signature.
     ///
     /// # Arguments
@@ -0,0 +1,21 @@
+use crate::types::CspPublicCoefficients;
+use crate::vault::api::CspVetKdEncryptedKeyShareCreationError;
+use ic_types::crypto::canister_threshold_sig::ExtendedDerivationPath;
+use ic_types::crypto::vetkd::VetKdEncryptedKeyShare;
+
+/// A trait that allows creating vetKD encrypted key shares.
+pub trait CspVetKdProtocol {
+    /// Creates an encrypted key share with the threshold secret key share
+    /// that belongs to the given public coefficients.
+    ///
+    /// The constant term of the public coefficients is used as the master
+    /// public key. See also the documentation in
+    /// [`crate::vault::api::VetKdCspVault::create_encrypted_vetkd_key_share`].
+    fn create_encrypted_vetkd_key_share(
+        &self,
+        public_coefficients: CspPublicCoefficients,
+        encryption_public_key: Vec<u8>,
+        derivation_path: ExtendedDerivationPath,
+        derivation_id: Vec<u8>,
+    ) -> Result<VetKdEncryptedKeyShare, CspVetKdEncryptedKeyShareCreationError>;
+}
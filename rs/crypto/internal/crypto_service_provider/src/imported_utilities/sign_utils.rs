@@ -1,5 +1,6 @@
 //! Signature utilities
 use crate::types::CspSignature;
+use ic_certification::CertificateValidationError;
 use ic_crypto_internal_basic_sig_cose as cose;
 use ic_crypto_internal_basic_sig_der_utils as der_utils;
 use ic_crypto_internal_basic_sig_ecdsa_secp256k1 as ecdsa_secp256k1;
@@ -11,9 +12,11 @@ use ic_crypto_internal_threshold_sig_bls12381 as bls12_381;
 use ic_crypto_internal_types::sign::threshold_sig::public_key::bls12_381::PublicKeyBytes as BlsPublicKeyBytes;
 use ic_types::crypto::threshold_sig::ThresholdSigPublicKey;
 use ic_types::crypto::{
-    AlgorithmId, BasicSig, CombinedThresholdSigOf, CryptoError, CryptoResult, Signable,
-    UserPublicKey,
+    AlgorithmId, BasicSig, CanisterSigOf, CombinedThresholdSigOf, CryptoError, CryptoResult,
+    Signable, UserPublicKey,
 };
+use ic_types::messages::Certificate;
+use ic_types::CanisterId;
 use std::convert::{TryFrom, TryInto};
 
 #[cfg(test)]
@@ -25,9 +28,12 @@ pub enum KeyBytesContentType {
     Ed25519PublicKeyDer,
     EcdsaP256PublicKeyDer,
     EcdsaSecp256k1PublicKeyDer,
+    EcdsaSecp256k1PublicKeySec1,
     RsaSha256PublicKeyDer,
+    RsaPssSha256PublicKeyDer,
     EcdsaP256PublicKeyDerWrappedCose,
     RsaSha256PublicKeyDerWrappedCose,
+    RsaPssSha256PublicKeyDerWrappedCose,
     IcCanisterSignatureAlgPublicKeyDer,
 }
 
@@ -36,6 +42,8 @@ fn cose_key_bytes_content_type(alg_id: AlgorithmId) -> CryptoResult<KeyBytesCont
         Ok(KeyBytesContentType::EcdsaP256PublicKeyDerWrappedCose)
     } else if alg_id == AlgorithmId::RsaSha256 {
         Ok(KeyBytesContentType::RsaSha256PublicKeyDerWrappedCose)
+    } else if alg_id == AlgorithmId::RsaPssSha256 {
+        Ok(KeyBytesContentType::RsaPssSha256PublicKeyDerWrappedCose)
     } else {
         Err(CryptoError::AlgorithmNotSupported {
             algorithm: alg_id,
@@ -95,6 +103,14 @@ pub fn user_public_key_from_bytes(
             AlgorithmId::RsaSha256,
             KeyBytesContentType::RsaSha256PublicKeyDer,
         )
+    } else if pkix_algo_id.oid == rsa::pss_algorithm_oid() {
+        (
+            rsa::RsaPublicKey::from_der_pss_spki(bytes)?
+                .as_der()
+                .to_vec(),
+            AlgorithmId::RsaPssSha256,
+            KeyBytesContentType::RsaPssSha256PublicKeyDer,
+        )
     } else {
         return Err(CryptoError::MalformedPublicKey {
             algorithm: AlgorithmId::Placeholder,
@@ -115,6 +131,35 @@ pub fn ecdsa_p256_signature_from_der_bytes(bytes: &[u8]) -> CryptoResult<BasicSi
     Ok(BasicSig(ecdsa_sig.0.to_vec()))
 }
 
+/// Parses the given `bytes` as a SEC1-encoded ECDSA secp256k1 public key, i.e.,
+/// as a compressed or uncompressed curve point without any algorithm
+/// identifier, and returns, if the parsing is successful, the key as
+/// `UserPublicKey`-struct and `KeyBytesContentType::EcdsaSecp256k1PublicKeySec1`.
+///
+/// # Errors
+/// * `CryptoError::MalformedPublicKey`: if the public key cannot be parsed.
+pub fn ecdsa_secp256k1_public_key_from_sec1_bytes(
+    bytes: &[u8],
+) -> CryptoResult<(UserPublicKey, KeyBytesContentType)> {
+    let pk = ecdsa_secp256k1::api::public_key_from_sec1(bytes)?;
+    Ok((
+        UserPublicKey {
+            key: pk.0,
+            algorithm_id: AlgorithmId::EcdsaSecp256k1,
+        },
+        KeyBytesContentType::EcdsaSecp256k1PublicKeySec1,
+    ))
+}
+
+/// Decodes an ECDSA secp256k1 signature from DER.
+///
+/// # Errors
+/// * `CryptoError::MalformedSignature`: if the signature cannot be DER decoded.
+pub fn ecdsa_secp256k1_signature_from_der_bytes(bytes: &[u8]) -> CryptoResult<BasicSig> {
+    let ecdsa_sig = ecdsa_secp256k1::api::signature_from_der(bytes)?;
+    Ok(BasicSig(ecdsa_sig.0.to_vec()))
+}
+
 /// Encodes a threshold signature public key into DER.
 ///
 /// # Errors
@@ -157,6 +202,9 @@ pub fn ed25519_public_key_to_der(raw_key: Vec<u8>) -> CryptoResult<Vec<u8>> {
 }
 
 /// Decodes an RSA signature from binary data.
+///
+/// The encoding is the same for PKCS#1 v1.5 and PSS signatures, so the
+/// signature scheme is determined by the algorithm of the public key.
 pub fn rsa_signature_from_bytes(bytes: &[u8]) -> BasicSig {
     BasicSig(bytes.to_vec())
 }
@@ -181,3 +229,89 @@ pub fn verify_combined_threshold_sig<T: Signable>(
     let bls_sig = bls12_381::types::CombinedSignatureBytes::try_from(csp_sig)?;
     bls12_381::api::verify_combined_signature(&msg.as_signed_bytes(), bls_sig, bls_pk)
 }
+
+/// Verifies a certificate of the IC state tree, as contained e.g. in the
+/// response to a `read_state` request, w.r.t. the public key `root_of_trust`
+/// of the IC's root subnet.
+///
+/// If the certificate was issued by a subnet other than the root subnet, it
+/// must contain a delegation from the root subnet. The delegation is only
+/// accepted if its certificate is signed directly by `root_of_trust` (i.e.,
+/// delegations are not nested) and the canister ranges it certifies for the
+/// delegated subnet contain `effective_canister_id`. The certificate's
+/// signature is then verified w.r.t. the delegated subnet's public key.
+///
+/// Returns the verified certificate, whose tree can be used to look up the
+/// certified values.
+///
+/// # Errors
+/// * `CryptoError::MalformedSignature` if the certificate, its delegation, or
+///   the tree of either is malformed.
+/// * `CryptoError::SignatureVerification` if a signature is invalid, the
+///   delegation is nested, or `effective_canister_id` is not in the delegated
+///   subnet's canister ranges.
+pub fn verify_certified_response(
+    certificate: &[u8],
+    effective_canister_id: &CanisterId,
+    root_of_trust: &ThresholdSigPublicKey,
+) -> CryptoResult<Certificate> {
+    ic_certification::verify_certificate(certificate, effective_canister_id, root_of_trust)
+        .map_err(|err| certificate_validation_error_to_crypto_error(err, root_of_trust))
+}
+
+fn certificate_validation_error_to_crypto_error(
+    err: CertificateValidationError,
+    root_of_trust: &ThresholdSigPublicKey,
+) -> CryptoError {
+    match err {
+        CertificateValidationError::DeserError(_)
+        | CertificateValidationError::MalformedHashTree(_) => CryptoError::MalformedSignature {
+            algorithm: AlgorithmId::ThresBls12_381,
+            sig_bytes: vec![],
+            internal_error: format!("malformed certificate: {}", err),
+        },
+        CertificateValidationError::InvalidSignature(_)
+        | CertificateValidationError::CertifiedDataMismatch { .. }
+        | CertificateValidationError::MultipleSubnetDelegationsNotAllowed
+        | CertificateValidationError::CanisterIdOutOfRange => CryptoError::SignatureVerification {
+            algorithm: AlgorithmId::ThresBls12_381,
+            public_key_bytes: root_of_trust.into_bytes().to_vec(),
+            sig_bytes: vec![],
+            internal_error: format!("certificate verification failed: {}", err),
+        },
+    }
+}
+
+/// Verifies a canister signature on `message` by the canister with public key
+/// `canister_sig_pubkey`, where `root_of_trust` is the public key of the IC's
+/// root subnet that the signature's certificate must be signed with.
+///
+/// In contrast to `CanisterSigVerifier::verify_canister_sig`, this requires
+/// neither a crypto component nor a registry, since the caller supplies the
+/// root of trust.
+///
+/// # Errors
+/// * `CryptoError::AlgorithmNotSupported` if the algorithm of
+///   `canister_sig_pubkey` is not `AlgorithmId::IcCanisterSignature`.
+/// * `CryptoError::MalformedPublicKey` if `canister_sig_pubkey` is malformed.
+/// * `CryptoError::MalformedSignature` if `signature` is malformed.
+/// * `CryptoError::SignatureVerification` if the signature is invalid.
+pub fn verify_canister_sig<T: Signable>(
+    message: &T,
+    signature: &CanisterSigOf<T>,
+    canister_sig_pubkey: &UserPublicKey,
+    root_of_trust: &ThresholdSigPublicKey,
+) -> CryptoResult<()> {
+    if canister_sig_pubkey.algorithm_id != AlgorithmId::IcCanisterSignature {
+        return Err(CryptoError::AlgorithmNotSupported {
+            algorithm: canister_sig_pubkey.algorithm_id,
+            reason: format!("Expected {:?}", AlgorithmId::IcCanisterSignature),
+        });
+    }
+    iccsa::verify(
+        &message.as_signed_bytes(),
+        iccsa::types::SignatureBytes(signature.get_ref().0.clone()),
+        iccsa::types::PublicKeyBytes(canister_sig_pubkey.key.clone()),
+        root_of_trust,
+    )
+}
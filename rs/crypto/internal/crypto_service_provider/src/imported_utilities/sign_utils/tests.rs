@@ -1,6 +1,8 @@
 #![allow(clippy::unwrap_used)]
 use crate::imported_utilities::sign_utils as utils;
-use ic_crypto_internal_basic_sig_der_utils::subject_public_key_info_der;
+use ic_crypto_internal_basic_sig_der_utils::{
+    algo_id_and_public_key_bytes_from_der, subject_public_key_info_der,
+};
 use ic_crypto_internal_test_vectors::test_data;
 use simple_asn1::oid;
 
@@ -39,6 +41,39 @@ fn should_correctly_parse_der_encoded_iccsa_pubkey() {
     );
 }
 
+#[test]
+fn should_correctly_parse_der_wrapped_cose_encoded_rsa_pss_pk() {
+    let pk_cose = hex::decode("a401030338242059010098194bcfd243773a9701dacad80d895225906826e74f34631a6f214374829bd407918e1a075aa14ba67898eb4d2c6feffb77d78b776ed60073a107d938318bf68289d1118cb555355bf7ecd4c94e8af560cd5069d35a947a454bdc312d228a0b6f749b560759295fa428b6596bd882d1f66b95db9217997a1fb1772ca13abda58178a44d1587eae516dbf0d66f29de377bfab06e174d3007262345b7624308091fcbabe0a9d7ae57a8daec5bc4c2a8876b0fd368269c850d0ae78980212c545be5b1803874d2cde67722d997ce6526a6430d5d3009452e00944e70be032c0bb33cad1d016ad6ba26c74e281514192a2bb2268062678aecb5ebc5061ee16ef3592143010001").unwrap();
+    let pk_der =
+        subject_public_key_info_der(oid!(1, 3, 6, 1, 4, 1, 56387, 1, 1), &pk_cose).unwrap();
+
+    let (pk, bytes_type) = utils::user_public_key_from_bytes(&pk_der).unwrap();
+
+    assert_eq!(pk.algorithm_id, AlgorithmId::RsaPssSha256);
+    assert_eq!(pk.key, hex::decode("30820122300d06092a864886f70d01010105000382010f003082010a028201010098194bcfd243773a9701dacad80d895225906826e74f34631a6f214374829bd407918e1a075aa14ba67898eb4d2c6feffb77d78b776ed60073a107d938318bf68289d1118cb555355bf7ecd4c94e8af560cd5069d35a947a454bdc312d228a0b6f749b560759295fa428b6596bd882d1f66b95db9217997a1fb1772ca13abda58178a44d1587eae516dbf0d66f29de377bfab06e174d3007262345b7624308091fcbabe0a9d7ae57a8daec5bc4c2a8876b0fd368269c850d0ae78980212c545be5b1803874d2cde67722d997ce6526a6430d5d3009452e00944e70be032c0bb33cad1d016ad6ba26c74e281514192a2bb2268062678aecb5ebc5061ee16ef3590203010001").unwrap());
+    assert_eq!(
+        bytes_type,
+        utils::KeyBytesContentType::RsaPssSha256PublicKeyDerWrappedCose
+    );
+}
+
+#[test]
+fn should_correctly_parse_der_encoded_rsa_pss_pk() {
+    let pk_rsa_encryption_der = hex::decode("30820122300d06092a864886f70d01010105000382010f003082010a028201010098194bcfd243773a9701dacad80d895225906826e74f34631a6f214374829bd407918e1a075aa14ba67898eb4d2c6feffb77d78b776ed60073a107d938318bf68289d1118cb555355bf7ecd4c94e8af560cd5069d35a947a454bdc312d228a0b6f749b560759295fa428b6596bd882d1f66b95db9217997a1fb1772ca13abda58178a44d1587eae516dbf0d66f29de377bfab06e174d3007262345b7624308091fcbabe0a9d7ae57a8daec5bc4c2a8876b0fd368269c850d0ae78980212c545be5b1803874d2cde67722d997ce6526a6430d5d3009452e00944e70be032c0bb33cad1d016ad6ba26c74e281514192a2bb2268062678aecb5ebc5061ee16ef3590203010001").unwrap();
+    let (_algo_id, pk_pkcs1) =
+        algo_id_and_public_key_bytes_from_der(&pk_rsa_encryption_der).unwrap();
+    let pk_der = subject_public_key_info_der(oid!(1, 2, 840, 113549, 1, 1, 10), &pk_pkcs1).unwrap();
+
+    let (pk, bytes_type) = utils::user_public_key_from_bytes(&pk_der).unwrap();
+
+    assert_eq!(pk.algorithm_id, AlgorithmId::RsaPssSha256);
+    assert_eq!(pk.key, pk_rsa_encryption_der);
+    assert_eq!(
+        bytes_type,
+        utils::KeyBytesContentType::RsaPssSha256PublicKeyDer
+    );
+}
+
 #[test]
 fn should_correctly_parse_der_encoded_safari_ecdsa_p256_pk() {
     let pk_der = hex::decode(test_data::SAFARI_ECDSA_P256_PK_DER_HEX).unwrap();
@@ -83,6 +118,27 @@ fn should_correctly_parse_der_encoded_ecdsa_secp256k1_pk() {
     );
 }
 
+#[test]
+fn should_correctly_parse_sec1_encoded_ecdsa_secp256k1_pk() {
+    let pk_der = hex::decode(test_data::ECDSA_SECP256K1_PK_DER_HEX).unwrap();
+    let (der_pk, _) = utils::user_public_key_from_bytes(&pk_der).unwrap();
+
+    let (pk, bytes_type) = utils::ecdsa_secp256k1_public_key_from_sec1_bytes(&der_pk.key).unwrap();
+
+    assert_eq!(pk, der_pk);
+    assert_eq!(
+        bytes_type,
+        utils::KeyBytesContentType::EcdsaSecp256k1PublicKeySec1
+    );
+}
+
+#[test]
+fn should_correctly_parse_der_encoded_ecdsa_secp256k1_sig() {
+    let (_, sig_der) = new_pk_and_sig_der(Nid::SECP256K1);
+    let sig = utils::ecdsa_secp256k1_signature_from_der_bytes(&sig_der).unwrap();
+    assert_eq!(sig.0.len(), 64);
+}
+
 #[test]
 fn should_fail_parse_raw_ed25519_pk() {
     let pk_raw = hex::decode(test_data::ED25519_PK_1_RFC8032_HEX).unwrap();
@@ -20,24 +20,29 @@ pub mod vault;
 
 pub use crate::vault::api::TlsHandshakeCspVault;
 pub use crate::vault::local_csp_vault::LocalCspVault;
-pub use crate::vault::remote_csp_vault::run_csp_vault_server;
-use crate::vault::remote_csp_vault::RemoteCspVault;
+pub use crate::vault::remote_csp_vault::{run_csp_vault_server, run_csp_vault_server_over_tcp};
+use crate::vault::remote_csp_vault::{tls_connector, RemoteCspVault};
 
 use crate::api::{
-    CspIDkgProtocol, CspKeyGenerator, CspPublicAndSecretKeyStoreChecker, CspPublicKeyStore,
-    CspSigVerifier, CspSigner, CspThresholdEcdsaSigVerifier, CspThresholdEcdsaSigner,
-    CspTlsHandshakeSignerProvider, NiDkgCspClient, NodePublicKeyDataError,
-    ThresholdSignatureCspClient,
+    CspIDkgProtocol, CspKeyEscrow, CspKeyGenerator, CspPublicAndSecretKeyStoreChecker,
+    CspPublicKeyStore, CspSigVerifier, CspSigner, CspThresholdEcdsaSigVerifier,
+    CspThresholdEcdsaSigner, CspTlsHandshakeSignerProvider, CspVetKdProtocol, NiDkgCspClient,
+    NodePublicKeyDataError, ThresholdSignatureCspClient,
 };
+use crate::secret_key_store::pkcs11_store::Pkcs11SecretKeyStore;
 use crate::secret_key_store::SecretKeyStore;
-use crate::types::{CspPublicKey, ExternalPublicKeys};
+use crate::types::{CspPublicCoefficients, CspPublicKey, ExternalPublicKeys};
 use crate::vault::api::{
-    CspPublicKeyStoreError, CspVault, PksAndSksContainsErrors, ValidatePksAndSksError,
+    CspEscrowNodeSecretKeysError, CspPublicKeyStoreError, CspSealedBackupError, CspVault,
+    CspVetKdEncryptedKeyShareCreationError, PksAndSksContainsErrors, ValidatePksAndSksError,
 };
-use ic_config::crypto::{CryptoConfig, CspVaultType};
+use ic_config::crypto::{CryptoConfig, CspVaultTlsConfig, CspVaultType, Pkcs11PinSource};
+use ic_crypto_internal_key_escrow::EscrowedSecret;
 use ic_crypto_internal_logmon::metrics::CryptoMetrics;
 use ic_crypto_node_key_validation::ValidNodePublicKeys;
 use ic_logger::{info, new_logger, replica_logger::no_op_logger, ReplicaLogger};
+use ic_types::crypto::canister_threshold_sig::ExtendedDerivationPath;
+use ic_types::crypto::vetkd::VetKdEncryptedKeyShare;
 use ic_types::crypto::CurrentNodePublicKeys;
 use key_id::KeyId;
 use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
@@ -63,6 +68,8 @@ pub trait CryptoServiceProvider:
     + CspPublicAndSecretKeyStoreChecker
     + CspTlsHandshakeSignerProvider
     + CspPublicKeyStore
+    + CspKeyEscrow
+    + CspVetKdProtocol
 {
 }
 
@@ -79,11 +86,14 @@ impl<T> CryptoServiceProvider for T where
         + CspPublicAndSecretKeyStoreChecker
         + CspTlsHandshakeSignerProvider
         + CspPublicKeyStore
+        + CspKeyEscrow
+        + CspVetKdProtocol
 {
 }
 
 /// Implements `CryptoServiceProvider` that uses a `CspVault` for
 /// storing and managing secret keys.
+#[derive(Clone)]
 pub struct Csp {
     csp_vault: Arc<dyn CspVault>,
     logger: ReplicaLogger,
@@ -152,13 +162,19 @@ impl<T> CspRwLock<T> {
 impl Csp {
     /// Creates a production-grade crypto service provider.
     ///
-    /// If the `config`'s vault type is `UnixSocket`, a `tokio_runtime_handle`
-    /// must be provided, which is then used for the `async`hronous
-    /// communication with the vault via RPC.
+    /// If the `config`'s vault type is `UnixSocket` or `Tcp`, a
+    /// `tokio_runtime_handle` must be provided, which is then used for the
+    /// `async`hronous communication with the vault via RPC.
+    ///
+    /// If the `config`'s vault type is `Pkcs11`, the node secret keys are
+    /// stored on the configured PKCS#11 token instead of in the crypto root.
     ///
     /// # Panics
-    /// Panics if the `config`'s vault type is `UnixSocket` and
-    /// `tokio_runtime_handle` is `None`.
+    /// Panics if the `config`'s vault type is `UnixSocket` or `Tcp` and
+    /// `tokio_runtime_handle` is `None`, if the vault type is `Pkcs11` and
+    /// the token cannot be opened, if the vault type is `Tcp` and the TLS
+    /// configuration is invalid, or if the vault is in the replica and the
+    /// CSPRNG cannot be created from the configured `csp_rng_source`.
     pub fn new(
         config: &CryptoConfig,
         tokio_runtime_handle: Option<tokio::runtime::Handle>,
@@ -174,6 +190,19 @@ impl Csp {
                 logger,
                 metrics,
             ),
+            CspVaultType::Pkcs11 {
+                module,
+                slot,
+                pin_source,
+            } => Self::new_with_pkcs11_vault(module, *slot, pin_source, config, logger, metrics),
+            CspVaultType::Tcp { address, tls } => Self::new_with_tcp_vault(
+                address,
+                tls,
+                tokio_runtime_handle.expect("missing tokio runtime handle"),
+                config,
+                logger,
+                metrics,
+            ),
         }
     }
 
@@ -187,11 +216,50 @@ impl Csp {
             logger,
             "Proceeding with an in-replica csp_vault, CryptoConfig: {:?}", config
         );
-        let csp_vault = Arc::new(LocalCspVault::new_in_dir(
-            &config.crypto_root,
-            metrics.clone(),
-            new_logger!(&logger),
-        ));
+        let csp_vault = Arc::new(
+            LocalCspVault::new_in_dir(
+                &config.crypto_root,
+                &config.csp_rng_source,
+                metrics.clone(),
+                new_logger!(&logger),
+            )
+            .unwrap_or_else(|e| panic!("{}", e))
+            .with_key_escrow_config(config.key_escrow.as_ref()),
+        );
+        Csp {
+            csp_vault,
+            logger,
+            metrics,
+        }
+    }
+
+    fn new_with_pkcs11_vault(
+        module: &Path,
+        slot: u64,
+        pin_source: &Pkcs11PinSource,
+        config: &CryptoConfig,
+        logger: Option<ReplicaLogger>,
+        metrics: Arc<CryptoMetrics>,
+    ) -> Self {
+        let logger = logger.unwrap_or_else(no_op_logger);
+        info!(
+            logger,
+            "Proceeding with an in-replica csp_vault with node secret keys on a PKCS#11 token, CryptoConfig: {:?}",
+            config
+        );
+        let node_secret_key_store =
+            Pkcs11SecretKeyStore::open(module, slot, pin_source, Some(new_logger!(&logger)));
+        let csp_vault = Arc::new(
+            LocalCspVault::new_in_dir_with_node_secret_key_store(
+                &config.crypto_root,
+                node_secret_key_store,
+                &config.csp_rng_source,
+                metrics.clone(),
+                new_logger!(&logger),
+            )
+            .unwrap_or_else(|e| panic!("{}", e))
+            .with_key_escrow_config(config.key_escrow.as_ref()),
+        );
         Csp {
             csp_vault,
             logger,
@@ -211,13 +279,13 @@ impl Csp {
             logger,
             "Proceeding with a remote csp_vault, CryptoConfig: {:?}", config
         );
-        let csp_vault = RemoteCspVault::new(
-            socket_path,
-            rt_handle,
-            new_logger!(&logger),
-            metrics.clone(),
-        )
-        .unwrap_or_else(|e| {
+        let mut vault_builder = RemoteCspVault::builder(socket_path.to_path_buf(), rt_handle)
+            .with_logger(new_logger!(&logger))
+            .with_metrics(metrics.clone());
+        if let Some(secondary_socket_path) = &config.csp_vault_secondary_socket_path {
+            vault_builder = vault_builder.with_secondary_socket_path(secondary_socket_path.clone());
+        }
+        let csp_vault = vault_builder.build().unwrap_or_else(|e| {
             panic!(
                 "Could not connect to CspVault at socket {:?}: {:?}",
                 socket_path, e
@@ -229,6 +297,43 @@ impl Csp {
             metrics,
         }
     }
+
+    fn new_with_tcp_vault(
+        address: &str,
+        tls_config: &CspVaultTlsConfig,
+        rt_handle: tokio::runtime::Handle,
+        config: &CryptoConfig,
+        logger: Option<ReplicaLogger>,
+        metrics: Arc<CryptoMetrics>,
+    ) -> Self {
+        let logger = logger.unwrap_or_else(no_op_logger);
+        info!(
+            logger,
+            "Proceeding with a remote csp_vault via TCP, CryptoConfig: {:?}", config
+        );
+        let tls_connector = tls_connector(tls_config).unwrap_or_else(|e| {
+            panic!(
+                "Error setting up TLS for CspVault at address {}: {}",
+                address, e
+            )
+        });
+        let csp_vault =
+            RemoteCspVault::builder_for_tcp(address.to_string(), tls_connector, rt_handle)
+                .with_logger(new_logger!(&logger))
+                .with_metrics(metrics.clone())
+                .build()
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "Could not connect to CspVault at address {}: {:?}",
+                        address, e
+                    )
+                });
+        Csp {
+            csp_vault: Arc::new(csp_vault),
+            logger,
+            metrics,
+        }
+    }
 }
 
 impl CspPublicKeyStore for Csp {
@@ -263,6 +368,52 @@ impl CspPublicAndSecretKeyStoreChecker for Csp {
     }
 }
 
+impl CspKeyEscrow for Csp {
+    fn escrow_node_secret_keys(
+        &self,
+        associated_data: Vec<u8>,
+    ) -> Result<EscrowedSecret, CspEscrowNodeSecretKeysError> {
+        self.csp_vault.escrow_node_secret_keys(associated_data)
+    }
+
+    fn export_sealed_backup(&self) -> Result<EscrowedSecret, CspSealedBackupError> {
+        self.csp_vault.export_sealed_backup()
+    }
+
+    fn restore_sealed_backup(
+        &self,
+        sealed_backup: EscrowedSecret,
+    ) -> Result<(), CspSealedBackupError> {
+        self.csp_vault.restore_sealed_backup(sealed_backup)
+    }
+}
+
+impl CspVetKdProtocol for Csp {
+    fn create_encrypted_vetkd_key_share(
+        &self,
+        public_coefficients: CspPublicCoefficients,
+        encryption_public_key: Vec<u8>,
+        derivation_path: ExtendedDerivationPath,
+        derivation_id: Vec<u8>,
+    ) -> Result<VetKdEncryptedKeyShare, CspVetKdEncryptedKeyShareCreationError> {
+        let key_id = KeyId::from(&public_coefficients);
+        let CspPublicCoefficients::Bls12_381(public_coefficients_bytes) = public_coefficients;
+        let master_public_key = public_coefficients_bytes
+            .coefficients
+            .first()
+            .ok_or(CspVetKdEncryptedKeyShareCreationError::InvalidArgumentMasterPublicKey)?
+            .0
+            .to_vec();
+        self.csp_vault.create_encrypted_vetkd_key_share(
+            key_id,
+            master_public_key,
+            encryption_public_key,
+            derivation_path,
+            derivation_id,
+        )
+    }
+}
+
 #[cfg(test)]
 pub mod builder {
     use super::*;
@@ -3,6 +3,7 @@ use crate::public_key_store::PublicKeyGenerationTimestamps;
 use crate::public_key_store::PublicKeyRetainError;
 use crate::public_key_store::PublicKeySetOnceError;
 use crate::public_key_store::PublicKeyStore;
+use ic_protobuf::crypto::v1::NodePublicKeys;
 use ic_protobuf::registry::crypto::v1::PublicKey;
 use ic_protobuf::registry::crypto::v1::X509PublicKeyCert;
 use mockall::predicate::*;
@@ -43,6 +44,8 @@ mock! {
 
         fn add_idkg_dealing_encryption_pubkey(&mut self, key: PublicKey) -> Result<(), PublicKeyAddError>;
 
+        fn set_once_all_node_public_keys(&mut self, keys: NodePublicKeys) -> Result<(), PublicKeySetOnceError>;
+
         fn retain_most_recent_idkg_public_keys_up_to_inclusive(&mut self, oldest_public_key_to_keep: &PublicKey) -> Result<bool, PublicKeyRetainError>;
 
         fn idkg_dealing_encryption_pubkeys(&self) -> Vec<PublicKey>;
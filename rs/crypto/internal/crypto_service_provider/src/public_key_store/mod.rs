@@ -1,4 +1,5 @@
 //! Interfaces for saving and retrieving public keys
+use ic_protobuf::crypto::v1::NodePublicKeys;
 use ic_protobuf::registry::crypto::v1::{PublicKey as PublicKeyProto, X509PublicKeyCert};
 use ic_types::Time;
 
@@ -88,6 +89,16 @@ pub trait PublicKeyStore: Send + Sync {
         key: PublicKeyProto,
     ) -> Result<(), PublicKeyAddError>;
 
+    /// Sets all node public keys at once, e.g., when restoring them from a
+    /// backup. The `version` of `keys` is ignored.
+    ///
+    /// Returns an error if any public key is already set, or if writing to
+    /// disk fails, in which case the store is left unchanged.
+    fn set_once_all_node_public_keys(
+        &mut self,
+        keys: NodePublicKeys,
+    ) -> Result<(), PublicKeySetOnceError>;
+
     /// Retain only the most recent iDKG dealing encryption public keys.
     /// Returns `Ok(true)` iff this operation modified the public key store.
     ///
@@ -155,6 +155,25 @@ impl PublicKeyStore for ProtoPublicKeyStore {
             .map_err(PublicKeyAddError::Io)
     }
 
+    fn set_once_all_node_public_keys(
+        &mut self,
+        keys: NodePublicKeys,
+    ) -> Result<(), PublicKeySetOnceError> {
+        if self.keys.node_signing_pk.is_some()
+            || self.keys.committee_signing_pk.is_some()
+            || self.keys.tls_certificate.is_some()
+            || self.keys.dkg_dealing_encryption_pk.is_some()
+            || !self.keys.idkg_dealing_encryption_pks.is_empty()
+        {
+            return Err(PublicKeySetOnceError::AlreadySet);
+        }
+        let previous_keys = std::mem::replace(&mut self.keys, keys);
+        self.write_node_public_keys_proto_to_disk().map_err(|e| {
+            self.keys = previous_keys;
+            PublicKeySetOnceError::Io(e)
+        })
+    }
+
     fn retain_most_recent_idkg_public_keys_up_to_inclusive(
         &mut self,
         oldest_public_key_to_keep: &PublicKeyProto,
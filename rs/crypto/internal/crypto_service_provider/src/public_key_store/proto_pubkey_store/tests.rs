@@ -238,6 +238,78 @@ fn should_persist_pubkeys_to_disk_when_setting_them() {
     ));
 }
 
+#[test]
+fn should_set_all_node_public_keys_once_and_persist_them() {
+    let temp_dir = temp_dir();
+    let mut store = public_key_store(&temp_dir);
+    let (generated_keys, _temp_dir) = generate_node_keys_in_temp_dir();
+
+    assert!(store
+        .set_once_all_node_public_keys(generated_keys.clone())
+        .is_ok());
+
+    let reopened_store = public_key_store(&temp_dir);
+    assert_eq!(
+        reopened_store.node_signing_pubkey(),
+        generated_keys.node_signing_pk
+    );
+    assert_eq!(
+        reopened_store.committee_signing_pubkey(),
+        generated_keys.committee_signing_pk
+    );
+    assert_eq!(
+        reopened_store.ni_dkg_dealing_encryption_pubkey(),
+        generated_keys.dkg_dealing_encryption_pk
+    );
+    assert_eq!(
+        reopened_store.tls_certificate(),
+        generated_keys.tls_certificate
+    );
+    assert!(equal_ignoring_timestamp(
+        &reopened_store.idkg_dealing_encryption_pubkeys(),
+        &generated_keys.idkg_dealing_encryption_pks
+    ));
+    assert_matches!(
+        store.set_once_all_node_public_keys(generated_keys),
+        Err(PublicKeySetOnceError::AlreadySet)
+    );
+}
+
+#[test]
+fn should_not_set_all_node_public_keys_if_one_is_already_set() {
+    let temp_dir = temp_dir();
+    let mut store = public_key_store(&temp_dir);
+    let (generated_keys, _temp_dir) = generate_node_keys_in_temp_dir();
+    add_idkg_dealing_encryption_public_keys(&mut store, vec![public_key_with_key_value(42)]);
+
+    assert_matches!(
+        store.set_once_all_node_public_keys(generated_keys),
+        Err(PublicKeySetOnceError::AlreadySet)
+    );
+    assert!(store.node_signing_pubkey().is_none());
+}
+
+#[test]
+fn should_leave_store_unchanged_if_writing_all_node_public_keys_fails() {
+    let temp_dir = mk_temp_dir_with_permissions(0o700);
+    let mut pubkey_store =
+        ProtoPublicKeyStore::open(temp_dir.path(), PUBLIC_KEYS_FILE, no_op_logger());
+    let (generated_keys, _generated_keys_dir) = generate_node_keys_in_temp_dir();
+    fs::set_permissions(temp_dir.path(), fs::Permissions::from_mode(0o400))
+        .expect("failed to set read-only permissions");
+
+    let result = pubkey_store.set_once_all_node_public_keys(generated_keys);
+
+    assert_matches!(result, Err(PublicKeySetOnceError::Io(io_error)) if io_error.kind() == std::io::ErrorKind::PermissionDenied);
+    assert!(pubkey_store.node_signing_pubkey().is_none());
+    assert!(pubkey_store.idkg_dealing_encryption_pubkeys().is_empty());
+
+    fs::set_permissions(temp_dir.path(), fs::Permissions::from_mode(0o700)).expect(
+        "failed to change permissions of temp_dir so that writing is possible \
+               again, so that the directory can automatically be cleaned up",
+    );
+}
+
 #[test]
 fn should_preserve_order_of_rotating_pubkeys() {
     let temp_dir = temp_dir();
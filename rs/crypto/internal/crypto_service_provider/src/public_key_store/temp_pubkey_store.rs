@@ -4,6 +4,7 @@ use crate::public_key_store::{
     PublicKeyAddError, PublicKeyRetainError, PublicKeySetOnceError, PublicKeyStore,
 };
 use ic_logger::replica_logger::no_op_logger;
+use ic_protobuf::crypto::v1::NodePublicKeys;
 use ic_protobuf::registry::crypto::v1::{PublicKey, X509PublicKeyCert};
 use std::fs;
 use std::fs::Permissions;
@@ -94,6 +95,13 @@ impl PublicKeyStore for TempPublicKeyStore {
         self.store.add_idkg_dealing_encryption_pubkey(key)
     }
 
+    fn set_once_all_node_public_keys(
+        &mut self,
+        keys: NodePublicKeys,
+    ) -> Result<(), PublicKeySetOnceError> {
+        self.store.set_once_all_node_public_keys(keys)
+    }
+
     fn retain_most_recent_idkg_public_keys_up_to_inclusive(
         &mut self,
         oldest_public_key_to_keep: &PublicKey,
@@ -0,0 +1,309 @@
+//! PKCS#11-backed secret key store
+use crate::key_id::KeyId;
+use crate::secret_key_store::proto_store::pb;
+use crate::secret_key_store::{
+    Scope, SecretKeyStore, SecretKeyStoreError, SecretKeyStorePersistenceError,
+};
+use crate::types::CspSecretKey;
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::object::{Attribute, AttributeType, ObjectClass};
+use cryptoki::session::{Session, UserType};
+use hex::{FromHex, ToHex};
+use ic_config::crypto::Pkcs11PinSource;
+use ic_logger::{info, replica_logger::no_op_logger, ReplicaLogger};
+use parking_lot::Mutex;
+use prost::Message;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+#[cfg(test)]
+mod tests;
+
+/// Prefix of the labels of the token objects holding the keys of this store,
+/// which distinguishes them from other data objects on the same token.
+const KEY_LABEL_PREFIX: &str = "ic-csp-sks-";
+
+type SecretKeys = HashMap<KeyId, (CspSecretKey, Option<Scope>)>;
+
+/// A secret key store that persists data as private data objects on a PKCS#11
+/// token, such as an HSM.
+///
+/// Each key is stored in its own object, labelled with the key ID and holding
+/// the key and its scope encoded in the same way as an entry of the
+/// [`ProtoSecretKeyStore`](crate::secret_key_store::proto_store::ProtoSecretKeyStore).
+/// The keys are read from the token when the store is opened and are afterwards
+/// served from memory, while every modification is written through to the
+/// token.
+///
+/// The store only protects the keys at rest: the CSP computes with the keys in
+/// software, so the token holds them as extractable data objects rather than
+/// as non-extractable keys, and anyone logged in to the token can read them.
+/// In particular, the keys are in the memory of the vault process, exactly as
+/// with the [`ProtoSecretKeyStore`](crate::secret_key_store::proto_store::ProtoSecretKeyStore).
+pub struct Pkcs11SecretKeyStore {
+    // The store is only ever modified via `&mut self`, so the mutex is never
+    // contended and only makes the (`Send` but not `Sync`) session `Sync`.
+    session: Mutex<Session>,
+    keys: SecretKeys,
+    logger: ReplicaLogger,
+}
+
+impl Pkcs11SecretKeyStore {
+    /// Opens the store on the token in `slot` of the PKCS#11 `module`, logging
+    /// in as normal user with the PIN read from `pin_source`.
+    ///
+    /// # Panics
+    /// If the module cannot be loaded, there is no token in `slot`, the PIN
+    /// cannot be read, logging in to the token fails, or the keys stored on the
+    /// token cannot be read or parsed.
+    pub fn open(
+        module: &Path,
+        slot: u64,
+        pin_source: &Pkcs11PinSource,
+        logger: Option<ReplicaLogger>,
+    ) -> Self {
+        let logger = logger.unwrap_or_else(no_op_logger);
+        let pkcs11 = Pkcs11::new(module)
+            .unwrap_or_else(|e| panic!("Error loading PKCS#11 module {}: {}", module.display(), e));
+        pkcs11
+            .initialize(CInitializeArgs::OsThreads)
+            .unwrap_or_else(|e| {
+                panic!(
+                    "Error initializing PKCS#11 module {}: {}",
+                    module.display(),
+                    e
+                )
+            });
+        let token_slot = pkcs11
+            .get_slots_with_token()
+            .unwrap_or_else(|e| panic!("Error listing PKCS#11 slots: {}", e))
+            .into_iter()
+            .find(|token_slot| token_slot.id() == slot)
+            .unwrap_or_else(|| panic!("No PKCS#11 token in slot {}", slot));
+        let session = pkcs11
+            .open_rw_session(token_slot)
+            .unwrap_or_else(|e| panic!("Error opening PKCS#11 session on slot {}: {}", slot, e));
+        let pin = read_pin(pin_source);
+        session
+            .login(UserType::User, Some(pin.as_str()))
+            .unwrap_or_else(|e| {
+                panic!("Error logging in to PKCS#11 token in slot {}: {}", slot, e)
+            });
+        drop(pin);
+        let keys = read_secret_keys_from_token(&session);
+        info!(
+            logger,
+            "Opened PKCS#11 secret key store with {} keys on slot {} of {}",
+            keys.len(),
+            slot,
+            module.display()
+        );
+        Pkcs11SecretKeyStore {
+            session: Mutex::new(session),
+            keys,
+            logger,
+        }
+    }
+
+    fn write_secret_key_to_token(
+        &mut self,
+        key_id: &KeyId,
+        csp_key: &CspSecretKey,
+        scope: Option<Scope>,
+    ) -> Result<(), SecretKeyStorePersistenceError> {
+        let value = encode_secret_key(key_id, csp_key, scope)?;
+        self.session
+            .get_mut()
+            .create_object(&[
+                Attribute::Class(ObjectClass::DATA),
+                Attribute::Token(true),
+                Attribute::Private(true),
+                Attribute::Label(label_from_key_id(key_id).into_bytes()),
+                Attribute::Value(value),
+            ])
+            .map_err(|e| {
+                SecretKeyStorePersistenceError::IoError(format!(
+                    "Error creating PKCS#11 object for key with ID {}: {}",
+                    key_id, e
+                ))
+            })?;
+        Ok(())
+    }
+
+    fn delete_secret_key_from_token(
+        &mut self,
+        key_id: &KeyId,
+    ) -> Result<(), SecretKeyStorePersistenceError> {
+        let persistence_error = |e: cryptoki::error::Error| {
+            SecretKeyStorePersistenceError::IoError(format!(
+                "Error deleting PKCS#11 object for key with ID {}: {}",
+                key_id, e
+            ))
+        };
+        let session = self.session.get_mut();
+        let objects = session
+            .find_objects(&[
+                Attribute::Class(ObjectClass::DATA),
+                Attribute::Token(true),
+                Attribute::Label(label_from_key_id(key_id).into_bytes()),
+            ])
+            .map_err(persistence_error)?;
+        for object in objects {
+            session.destroy_object(object).map_err(persistence_error)?;
+        }
+        Ok(())
+    }
+}
+
+impl SecretKeyStore for Pkcs11SecretKeyStore {
+    fn insert(
+        &mut self,
+        id: KeyId,
+        key: CspSecretKey,
+        scope: Option<Scope>,
+    ) -> Result<(), SecretKeyStoreError> {
+        if self.keys.contains_key(&id) {
+            return Err(SecretKeyStoreError::DuplicateKeyId(id));
+        }
+        self.write_secret_key_to_token(&id, &key, scope)
+            .map_err(SecretKeyStoreError::PersistenceError)?;
+        self.keys.insert(id, (key, scope));
+        Ok(())
+    }
+
+    fn get(&self, id: &KeyId) -> Option<CspSecretKey> {
+        self.keys.get(id).map(|(csp_key, _)| csp_key.to_owned())
+    }
+
+    fn contains(&self, id: &KeyId) -> bool {
+        self.keys.contains_key(id)
+    }
+
+    fn remove(&mut self, id: &KeyId) -> Result<bool, SecretKeyStorePersistenceError> {
+        if !self.keys.contains_key(id) {
+            return Ok(false);
+        }
+        self.delete_secret_key_from_token(id)?;
+        self.keys.remove(id);
+        Ok(true)
+    }
+
+    fn retain<F>(&mut self, filter: F, scope: Scope) -> Result<(), SecretKeyStorePersistenceError>
+    where
+        F: Fn(&KeyId, &CspSecretKey) -> bool,
+    {
+        let key_ids_to_delete: Vec<KeyId> = self
+            .keys
+            .iter()
+            .filter(|(key_id, (csp_key, maybe_scope))| {
+                *maybe_scope == Some(scope) && !filter(key_id, csp_key)
+            })
+            .map(|(key_id, _)| *key_id)
+            .collect();
+        for key_id in key_ids_to_delete {
+            info!(
+                self.logger,
+                "Deleting key with ID {} with scope {}", key_id, scope
+            );
+            self.delete_secret_key_from_token(&key_id)?;
+            self.keys.remove(&key_id);
+        }
+        Ok(())
+    }
+}
+
+/// Reads the PIN into memory that is zeroized when the PIN is dropped.
+fn read_pin(pin_source: &Pkcs11PinSource) -> Zeroizing<String> {
+    let mut pin = Zeroizing::new(match pin_source {
+        Pkcs11PinSource::File(path) => fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("Error reading PKCS#11 PIN from {}: {}", path.display(), e)),
+        Pkcs11PinSource::EnvironmentVariable(name) => std::env::var(name).unwrap_or_else(|e| {
+            panic!(
+                "Error reading PKCS#11 PIN from environment variable {}: {}",
+                name, e
+            )
+        }),
+    });
+    // Truncating in place does not leave a copy of the PIN behind.
+    let len = pin.trim_end_matches(&['\r', '\n'][..]).len();
+    pin.truncate(len);
+    pin
+}
+
+fn read_secret_keys_from_token(session: &Session) -> SecretKeys {
+    let objects = session
+        .find_objects(&[Attribute::Class(ObjectClass::DATA), Attribute::Token(true)])
+        .unwrap_or_else(|e| panic!("Error listing PKCS#11 data objects: {}", e));
+    let mut secret_keys = SecretKeys::new();
+    for object in objects {
+        let attributes = session
+            .get_attributes(object, &[AttributeType::Label, AttributeType::Value])
+            .unwrap_or_else(|e| panic!("Error reading PKCS#11 data object: {}", e));
+        let (mut label, mut value) = (None, None);
+        for attribute in attributes {
+            match attribute {
+                Attribute::Label(bytes) => label = Some(bytes),
+                Attribute::Value(bytes) => value = Some(bytes),
+                _ => {}
+            }
+        }
+        // Data objects that do not belong to this store are ignored.
+        if let Some(key_id) = label.as_deref().and_then(key_id_from_label) {
+            let value = value.unwrap_or_else(|| panic!("Missing value of key with ID {}", key_id));
+            secret_keys.insert(key_id, decode_secret_key(&value, &key_id));
+        }
+    }
+    secret_keys
+}
+
+fn label_from_key_id(key_id: &KeyId) -> String {
+    let key_id_hex: String = key_id.encode_hex();
+    format!("{}{}", KEY_LABEL_PREFIX, key_id_hex)
+}
+
+fn key_id_from_label(label: &[u8]) -> Option<KeyId> {
+    let key_id_hex = std::str::from_utf8(label)
+        .ok()?
+        .strip_prefix(KEY_LABEL_PREFIX)?;
+    Some(
+        KeyId::from_hex(key_id_hex)
+            .unwrap_or_else(|_| panic!("Error parsing hex KeyId {}", key_id_hex)),
+    )
+}
+
+fn encode_secret_key(
+    key_id: &KeyId,
+    csp_key: &CspSecretKey,
+    scope: Option<Scope>,
+) -> Result<Vec<u8>, SecretKeyStorePersistenceError> {
+    let key_as_cbor = serde_cbor::to_vec(csp_key).map_err(|_| {
+        SecretKeyStorePersistenceError::SerializationError(format!(
+            "Error serializing key with ID {}",
+            key_id
+        ))
+    })?;
+    let sk_pb = pb::SecretKeyV1 {
+        csp_secret_key: key_as_cbor,
+        scope: scope.map(|scope| String::from(&scope)).unwrap_or_default(),
+    };
+    Ok(sk_pb.encode_to_vec())
+}
+
+fn decode_secret_key(value: &[u8], key_id: &KeyId) -> (CspSecretKey, Option<Scope>) {
+    let sk_pb = pb::SecretKeyV1::decode(value)
+        .unwrap_or_else(|e| panic!("Error parsing key with ID {}: {}", key_id, e));
+    let csp_key = serde_cbor::from_slice(&sk_pb.csp_secret_key)
+        .unwrap_or_else(|e| panic!("Error deserializing key with ID {}: {}", key_id, e));
+    let scope = if sk_pb.scope.is_empty() {
+        None
+    } else {
+        Some(
+            Scope::from_str(&sk_pb.scope)
+                .unwrap_or_else(|_| panic!("Unknown scope: {}", sk_pb.scope)),
+        )
+    };
+    (csp_key, scope)
+}
@@ -0,0 +1,153 @@
+#![allow(clippy::unwrap_used)]
+use super::*;
+use crate::secret_key_store::scope::ConstScope;
+use crate::secret_key_store::test_utils::{make_key_id, make_secret_key};
+use std::io::Write;
+
+#[test]
+fn should_decode_encoded_secret_key_with_and_without_scope() {
+    let key_id = make_key_id(42);
+    let csp_key = make_secret_key(42);
+
+    for scope in [None, Some(Scope::Const(ConstScope::Test0))] {
+        let value = encode_secret_key(&key_id, &csp_key, scope).unwrap();
+
+        assert_eq!(decode_secret_key(&value, &key_id), (csp_key.clone(), scope));
+    }
+}
+
+#[test]
+fn should_parse_key_id_from_label() {
+    let key_id = make_key_id(42);
+
+    assert_eq!(
+        key_id_from_label(label_from_key_id(&key_id).as_bytes()),
+        Some(key_id)
+    );
+}
+
+#[test]
+fn should_ignore_labels_of_other_data_objects() {
+    assert_eq!(key_id_from_label(b"some other object"), None);
+    assert_eq!(key_id_from_label(&[0xff, 0xfe]), None);
+}
+
+#[test]
+fn should_read_pin_from_file_without_trailing_newline() {
+    let mut pin_file = tempfile::NamedTempFile::new().unwrap();
+    pin_file.write_all(b"358138\n").unwrap();
+
+    let pin = read_pin(&Pkcs11PinSource::File(pin_file.path().to_path_buf()));
+
+    assert_eq!(pin.as_str(), "358138");
+}
+
+mod softhsm {
+    use super::*;
+    use std::path::PathBuf;
+    use std::process::Command;
+    use tempfile::{NamedTempFile, TempDir};
+
+    /// The SoftHSM module, which may be overridden with the environment
+    /// variable `SOFTHSM2_MODULE`.
+    const DEFAULT_SOFTHSM_MODULE: &str = "/usr/lib/softhsm/libsofthsm2.so";
+    const PIN: &str = "358138";
+
+    /// A freshly initialized token of SoftHSM, whose files are deleted on drop.
+    struct SoftHsmToken {
+        module: PathBuf,
+        slot: u64,
+        pin_file: NamedTempFile,
+        _token_dir: TempDir,
+    }
+
+    impl SoftHsmToken {
+        /// Initializes a token in a temporary directory with `softhsm2-util`.
+        ///
+        /// As SoftHSM reads its config from the environment variable
+        /// `SOFTHSM2_CONF`, which is shared by the whole process, there must be
+        /// only one token per test binary.
+        fn new() -> Self {
+            let token_dir = tempfile::tempdir().unwrap();
+            let conf_path = token_dir.path().join("softhsm2.conf");
+            std::fs::write(
+                &conf_path,
+                format!("directories.tokendir = {}\n", token_dir.path().display()),
+            )
+            .unwrap();
+            std::env::set_var("SOFTHSM2_CONF", &conf_path);
+            let output = Command::new("softhsm2-util")
+                .args(["--init-token", "--free", "--label", "ic-csp-sks-test"])
+                .args(["--pin", PIN, "--so-pin", PIN])
+                .output()
+                .expect("softhsm2-util must be installed");
+            assert!(output.status.success(), "{:?}", output);
+            // The output ends with "... is reassigned to slot <slot>".
+            let slot = String::from_utf8(output.stdout)
+                .unwrap()
+                .split_whitespace()
+                .last()
+                .unwrap()
+                .parse()
+                .unwrap();
+            let mut pin_file = NamedTempFile::new().unwrap();
+            pin_file.write_all(PIN.as_bytes()).unwrap();
+            let module = std::env::var("SOFTHSM2_MODULE")
+                .unwrap_or_else(|_| DEFAULT_SOFTHSM_MODULE.to_string())
+                .into();
+            SoftHsmToken {
+                module,
+                slot,
+                pin_file,
+                _token_dir: token_dir,
+            }
+        }
+
+        fn open_store(&self) -> Pkcs11SecretKeyStore {
+            Pkcs11SecretKeyStore::open(
+                &self.module,
+                self.slot,
+                &Pkcs11PinSource::File(self.pin_file.path().to_path_buf()),
+                None,
+            )
+        }
+    }
+
+    // All operations are covered by a single test, see `SoftHsmToken::new`.
+    #[test]
+    #[ignore] // The test is ignored because it requires SoftHSM to be installed.
+    fn should_insert_remove_and_retain_keys_on_token() {
+        let token = SoftHsmToken::new();
+        let scope = Some(Scope::Const(ConstScope::Test0));
+        let mut store = token.open_store();
+
+        for seed in 1..=4 {
+            store
+                .insert(make_key_id(seed), make_secret_key(seed), scope)
+                .unwrap();
+        }
+        assert!(matches!(
+            store.insert(make_key_id(1), make_secret_key(1), None),
+            Err(SecretKeyStoreError::DuplicateKeyId(_))
+        ));
+        assert!(store.remove(&make_key_id(1)).unwrap());
+        assert!(!store.remove(&make_key_id(1)).unwrap());
+        store
+            .retain(
+                |key_id, _| *key_id == make_key_id(2),
+                Scope::Const(ConstScope::Test0),
+            )
+            .unwrap();
+        store
+            .insert(make_key_id(5), make_secret_key(5), None)
+            .unwrap();
+        drop(store);
+
+        let store = token.open_store();
+        assert!(!store.contains(&make_key_id(1)));
+        assert_eq!(store.get(&make_key_id(2)), Some(make_secret_key(2)));
+        assert!(!store.contains(&make_key_id(3)));
+        assert!(!store.contains(&make_key_id(4)));
+        assert_eq!(store.get(&make_key_id(5)), Some(make_secret_key(5)));
+    }
+}
@@ -59,6 +59,36 @@ impl CspSigner for Csp {
         }
     }
 
+    fn sign_batch(
+        &self,
+        algorithm_id: AlgorithmId,
+        messages: &[Vec<u8>],
+        key_id: KeyId,
+    ) -> CryptoResult<Vec<CspSignature>> {
+        match algorithm_id {
+            AlgorithmId::Ed25519 => {
+                let result = self
+                    .csp_vault
+                    .sign_batch(algorithm_id, messages, key_id)
+                    .map_err(CspBasicSignatureError::into);
+                self.metrics.observe_parameter_size(
+                    MetricsDomain::BasicSignature,
+                    "sign_basic_batch",
+                    "messages",
+                    messages.iter().map(Vec::len).sum(),
+                    MetricsResult::from(&result),
+                );
+                result
+            }
+            _ => Err(CryptoError::InvalidArgument {
+                message: format!(
+                    "Cannot sign a batch with unsupported algorithm: {:?}",
+                    algorithm_id
+                ),
+            }),
+        }
+    }
+
     fn verify(
         &self,
         sig: &CspSignature,
@@ -108,6 +138,11 @@ impl CspSigner for Csp {
             {
                 public_key.verify_pkcs1_sha256(msg, signature)
             }
+            (
+                AlgorithmId::RsaPssSha256,
+                CspSignature::RsaSha256(signature),
+                CspPublicKey::RsaSha256(public_key),
+            ) => public_key.verify_pss_sha256(msg, signature),
             (
                 AlgorithmId::MultiBls12_381,
                 CspSignature::MultiBls12_381(MultiBls12_381_Signature::Individual(signature)),
@@ -240,7 +240,9 @@ impl TryFrom<&UserPublicKey> for CspPublicKey {
             AlgorithmId::EcdsaSecp256k1 => Ok(CspPublicKey::EcdsaSecp256k1(
                 ecdsa_secp256k1_types::PublicKeyBytes(user_public_key.key.to_owned()),
             )),
-            AlgorithmId::RsaSha256 => Ok(CspPublicKey::RsaSha256(
+            // RSA keys are the same for PKCS#1 v1.5 and PSS signatures, the
+            // padding is chosen by the algorithm ID passed to the verifier
+            AlgorithmId::RsaSha256 | AlgorithmId::RsaPssSha256 => Ok(CspPublicKey::RsaSha256(
                 rsa::RsaPublicKey::from_der_spki(&user_public_key.key)?,
             )),
             algorithm => Err(CryptoError::AlgorithmNotSupported {
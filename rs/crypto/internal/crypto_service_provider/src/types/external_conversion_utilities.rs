@@ -213,7 +213,7 @@ impl SigConverter {
                     secp256k1_types::SignatureBytes(bytes),
                 ))
             }
-            AlgorithmId::RsaSha256 => {
+            AlgorithmId::RsaSha256 | AlgorithmId::RsaPssSha256 => {
                 let sig_bytes = &signature.get_ref().0;
                 Ok(CspSignature::RsaSha256(sig_bytes.clone()))
             }
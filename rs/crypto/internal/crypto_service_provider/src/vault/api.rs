@@ -1,8 +1,9 @@
 use crate::api::{CspCreateMEGaKeyError, CspThresholdSignError};
 use crate::key_id::{KeyId, KeyIdInstantiationError};
 use crate::types::CspPublicCoefficients;
-use crate::types::{CspPop, CspPublicKey, CspSignature};
+use crate::types::{CspPop, CspPublicKey, CspSecretKey, CspSignature};
 use crate::ExternalPublicKeys;
+use ic_crypto_internal_key_escrow::{EscrowedSecret, KeyEscrowError};
 use ic_crypto_internal_logmon::metrics::KeyCounts;
 use ic_crypto_internal_seed::Seed;
 use ic_crypto_internal_threshold_sig_bls12381::api::ni_dkg_errors;
@@ -17,12 +18,14 @@ use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::{
     CspNiDkgDealing, CspNiDkgTranscript, Epoch,
 };
 use ic_crypto_node_key_validation::ValidNodePublicKeys;
+use ic_crypto_secrets_containers::SecretBytes;
 use ic_crypto_tls_interfaces::TlsPublicKeyCert;
 use ic_types::crypto::canister_threshold_sig::error::{
     IDkgCreateDealingError, IDkgLoadTranscriptError, IDkgOpenTranscriptError, IDkgRetainKeysError,
     IDkgVerifyDealingPrivateError, ThresholdEcdsaSignShareError,
 };
 use ic_types::crypto::canister_threshold_sig::ExtendedDerivationPath;
+use ic_types::crypto::vetkd::VetKdEncryptedKeyShare;
 use ic_types::crypto::{AlgorithmId, CryptoError, CurrentNodePublicKeys};
 use ic_types::{NodeId, NodeIndex, NumberOfNodes, Randomness};
 use serde::{Deserialize, Serialize};
@@ -378,6 +381,8 @@ pub trait CspVault:
     + PublicRandomSeedGenerator
     + PublicAndSecretKeyStoreCspVault
     + PublicKeyStoreCspVault
+    + KeyEscrowCspVault
+    + VetKdCspVault
 {
 }
 
@@ -395,6 +400,8 @@ impl<T> CspVault for T where
         + PublicRandomSeedGenerator
         + PublicAndSecretKeyStoreCspVault
         + PublicKeyStoreCspVault
+        + KeyEscrowCspVault
+        + VetKdCspVault
 {
 }
 
@@ -424,6 +431,26 @@ pub trait BasicSignatureCspVault {
         key_id: KeyId,
     ) -> Result<CspSignature, CspBasicSignatureError>;
 
+    /// Signs each of the given messages using the specified algorithm and key
+    /// ID.
+    ///
+    /// Computes the same signatures as calling `sign` for each message, but
+    /// the secret key is only looked up once and, for a remote vault, all
+    /// messages are signed in a single call.
+    ///
+    /// # Arguments
+    /// * `algorithm_id` specifies the signature algorithm
+    /// * `messages` are the messages to be signed
+    /// * `key_id` determines the private key to sign with
+    /// # Returns
+    /// The computed signatures, in the order of `messages`.
+    fn sign_batch(
+        &self,
+        algorithm_id: AlgorithmId,
+        messages: &[Vec<u8>],
+        key_id: KeyId,
+    ) -> Result<Vec<CspSignature>, CspBasicSignatureError>;
+
     /// Generates a node signing public/private key pair.
     ///
     /// # Returns
@@ -878,3 +905,241 @@ pub trait PublicRandomSeedGenerator {
     /// generation of cryptographic keys.
     fn new_public_seed(&self) -> Result<Seed, PublicRandomSeedGeneratorError>;
 }
+
+/// An error returned by failing to escrow the node secret keys.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CspEscrowNodeSecretKeysError {
+    /// The public keys of the node are missing or malformed.
+    NodeKeysError(ValidatePksAndSksError),
+    /// The secret key of one of the node's public keys is missing.
+    SecretKeyNotFound { key_id: String },
+    /// The secret keys could not be escrowed, e.g., because the threshold is
+    /// invalid.
+    EscrowError(KeyEscrowError),
+    /// The vault has no valid recovery public keys configured.
+    KeyEscrowNotConfigured { reason: String },
+    /// Internal error, e.g., the secret keys could not be serialized.
+    InternalError { internal_error: String },
+    /// Transient internal error, e.g., an RPC error.
+    TransientInternalError { internal_error: String },
+}
+
+impl From<CspEscrowNodeSecretKeysError> for CryptoError {
+    fn from(e: CspEscrowNodeSecretKeysError) -> CryptoError {
+        match e {
+            CspEscrowNodeSecretKeysError::EscrowError(error) => CryptoError::InvalidArgument {
+                message: format!("Failed to escrow the node secret keys: {:?}", error),
+            },
+            CspEscrowNodeSecretKeysError::NodeKeysError(_)
+            | CspEscrowNodeSecretKeysError::SecretKeyNotFound { .. }
+            | CspEscrowNodeSecretKeysError::KeyEscrowNotConfigured { .. } => {
+                CryptoError::InternalError {
+                    internal_error: format!("Failed to escrow the node secret keys: {:?}", e),
+                }
+            }
+            CspEscrowNodeSecretKeysError::InternalError { internal_error } => {
+                CryptoError::InternalError { internal_error }
+            }
+            CspEscrowNodeSecretKeysError::TransientInternalError { internal_error } => {
+                CryptoError::TransientInternalError { internal_error }
+            }
+        }
+    }
+}
+
+/// An error returned by failing to export or restore a sealed backup of the
+/// node keys.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CspSealedBackupError {
+    /// The public keys of the node are missing or malformed.
+    NodeKeysError(ValidatePksAndSksError),
+    /// The secret key of one of the node's public keys is missing.
+    SecretKeyNotFound { key_id: String },
+    /// The backup could not be sealed or unsealed, e.g., because the backup
+    /// private key does not match the backup public key it was sealed to.
+    EscrowError(KeyEscrowError),
+    /// The vault has no valid backup key configured.
+    SealedBackupNotConfigured { reason: String },
+    /// The key stores into which a backup is to be restored are not empty.
+    KeyStoresNotEmpty,
+    /// Internal error, e.g., the backup could not be (de)serialized or the
+    /// restored keys could not be persisted.
+    InternalError { internal_error: String },
+    /// Transient internal error, e.g., an RPC error.
+    TransientInternalError { internal_error: String },
+}
+
+impl From<CspSealedBackupError> for CryptoError {
+    fn from(e: CspSealedBackupError) -> CryptoError {
+        match e {
+            CspSealedBackupError::EscrowError(error) => CryptoError::InvalidArgument {
+                message: format!("Failed to seal or unseal the node key backup: {:?}", error),
+            },
+            CspSealedBackupError::NodeKeysError(_)
+            | CspSealedBackupError::SecretKeyNotFound { .. }
+            | CspSealedBackupError::SealedBackupNotConfigured { .. }
+            | CspSealedBackupError::KeyStoresNotEmpty => CryptoError::InternalError {
+                internal_error: format!("Failed to back up or restore the node keys: {:?}", e),
+            },
+            CspSealedBackupError::InternalError { internal_error } => {
+                CryptoError::InternalError { internal_error }
+            }
+            CspSealedBackupError::TransientInternalError { internal_error } => {
+                CryptoError::TransientInternalError { internal_error }
+            }
+        }
+    }
+}
+
+/// The secret keys contained in an escrow created with
+/// [`KeyEscrowCspVault::escrow_node_secret_keys`], indexed by their key ID.
+#[derive(Serialize, Deserialize)]
+pub struct EscrowedNodeSecretKeys {
+    pub secret_keys: BTreeMap<KeyId, CspSecretKey>,
+}
+
+impl EscrowedNodeSecretKeys {
+    /// Serializes the secret keys as CBOR, which is what gets escrowed.
+    pub fn to_secret_bytes(&self) -> Result<SecretBytes, String> {
+        serde_cbor::to_vec(self)
+            .map(SecretBytes::new)
+            .map_err(|e| format!("Failed to serialize the node secret keys: {}", e))
+    }
+
+    /// Deserializes the secret keys recovered from an escrow.
+    pub fn from_secret_bytes(bytes: &SecretBytes) -> Result<Self, String> {
+        serde_cbor::from_slice(bytes.expose_secret())
+            .map_err(|e| format!("Failed to deserialize the node secret keys: {}", e))
+    }
+}
+
+/// Operations of [`CspVault`] for recovering the node secret keys after a
+/// disaster, e.g., the loss of the secret key store.
+pub trait KeyEscrowCspVault {
+    /// Exports the secret keys of all node public keys in the public key
+    /// store, including the rotated iDKG dealing encryption keys, as
+    /// [`EscrowedNodeSecretKeys`] encrypted such that the configured
+    /// threshold of holders of the private keys of the recovery public keys
+    /// configured in the vault can recover them. The escrow is bound to
+    /// `associated_data`, which must be specified again for the recovery.
+    ///
+    /// The recovery public keys and the threshold are pinned in the vault's
+    /// own config (see `ic_config::crypto::KeyEscrowConfig`), so that callers
+    /// cannot have the keys escrowed to keys they control. The keys never
+    /// leave the vault unencrypted. See [`ic_crypto_internal_key_escrow`] for
+    /// the recovery.
+    ///
+    /// # Errors
+    /// * [`CspEscrowNodeSecretKeysError::KeyEscrowNotConfigured`] if the vault
+    ///   has no valid recovery public keys configured
+    /// * [`CspEscrowNodeSecretKeysError::NodeKeysError`] if the public keys
+    ///   required for a node are missing or malformed
+    /// * [`CspEscrowNodeSecretKeysError::SecretKeyNotFound`] if the secret key
+    ///   of a public key is missing
+    /// * [`CspEscrowNodeSecretKeysError::TransientInternalError`] if a
+    ///   transient internal error, e.g., an RPC error, occurred
+    fn escrow_node_secret_keys(
+        &self,
+        associated_data: Vec<u8>,
+    ) -> Result<EscrowedSecret, CspEscrowNodeSecretKeysError>;
+
+    /// Exports the node public keys in the public key store, with their
+    /// generation timestamps, together with the corresponding secret keys as
+    /// a backup sealed to the backup public key configured in the vault, see
+    /// [`ic_config::crypto::KeyEscrowConfig`]. The backup contains the secret
+    /// keys of all IDKG dealing encryption public keys in the store, not only
+    /// of the current one.
+    ///
+    /// The keys never leave the vault unencrypted: the backup can only be
+    /// restored with [`Self::restore_sealed_backup`] by a vault that is
+    /// configured with the corresponding backup private key.
+    ///
+    /// # Errors
+    /// * [`CspSealedBackupError::SealedBackupNotConfigured`] if the vault has
+    ///   no valid backup public key configured
+    /// * [`CspSealedBackupError::NodeKeysError`] if the public keys required
+    ///   for a node are missing or malformed
+    /// * [`CspSealedBackupError::SecretKeyNotFound`] if the secret key of a
+    ///   public key, including any of the IDKG dealing encryption public
+    ///   keys, is missing
+    /// * [`CspSealedBackupError::EscrowError`] if the backup cannot be sealed
+    /// * [`CspSealedBackupError::TransientInternalError`] if a transient
+    ///   internal error, e.g., an RPC error, occurred
+    fn export_sealed_backup(&self) -> Result<EscrowedSecret, CspSealedBackupError>;
+
+    /// Unseals a backup created with [`Self::export_sealed_backup`] with the
+    /// backup private key, which the vault reads from the file configured in
+    /// the vault, and restores the contained node keys into the key stores of
+    /// this vault, which must not contain any node public keys yet.
+    ///
+    /// The backup is fully validated before the key stores are modified. If
+    /// persisting the restored keys fails, the restored secret keys are
+    /// removed again, so that the key stores are left unchanged.
+    ///
+    /// # Errors
+    /// * [`CspSealedBackupError::SealedBackupNotConfigured`] if the vault has
+    ///   no backup private key configured, or it cannot be read
+    /// * [`CspSealedBackupError::EscrowError`] if the backup cannot be
+    ///   unsealed with the backup private key
+    /// * [`CspSealedBackupError::NodeKeysError`] if the public keys in the
+    ///   backup are missing or malformed
+    /// * [`CspSealedBackupError::SecretKeyNotFound`] if the backup lacks the
+    ///   secret key of one of its public keys
+    /// * [`CspSealedBackupError::KeyStoresNotEmpty`] if the public key store
+    ///   already contains node public keys, or the secret key store already
+    ///   contains one of the restored secret keys
+    /// * [`CspSealedBackupError::InternalError`] if the backup is malformed or
+    ///   the restored keys cannot be persisted
+    /// * [`CspSealedBackupError::TransientInternalError`] if a transient
+    ///   internal error, e.g., an RPC error, occurred
+    fn restore_sealed_backup(
+        &self,
+        sealed_backup: EscrowedSecret,
+    ) -> Result<(), CspSealedBackupError>;
+}
+
+/// An error returned by failing to create a vetKD encrypted key share.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CspVetKdEncryptedKeyShareCreationError {
+    /// The secret key share of the threshold key is missing.
+    SecretKeyNotFound { key_id: KeyId },
+    /// The secret key with the key ID is not a threshold BLS12-381 key.
+    WrongSecretKeyType { key_id: KeyId },
+    /// The master public key is malformed.
+    InvalidArgumentMasterPublicKey,
+    /// The transport public key is malformed.
+    InvalidArgumentEncryptionPublicKey,
+    /// Internal error, e.g., the secret key share is malformed.
+    InternalError { internal_error: String },
+    /// Transient internal error, e.g., an RPC error.
+    TransientInternalError { internal_error: String },
+}
+
+/// Operations of [`CspVault`] for vetKD (verifiable encrypted threshold key
+/// derivation).
+pub trait VetKdCspVault {
+    /// Creates the share of the key derived from the threshold BLS12-381 key
+    /// with public key `master_public_key` for `derivation_path` and
+    /// `derivation_id`, encrypted to `encryption_public_key`. The share is
+    /// created with the secret key share with ID `key_id`.
+    ///
+    /// # Errors
+    /// * [`CspVetKdEncryptedKeyShareCreationError::SecretKeyNotFound`] if the
+    ///   secret key share is missing
+    /// * [`CspVetKdEncryptedKeyShareCreationError::WrongSecretKeyType`] if
+    ///   the secret key is not a threshold BLS12-381 key
+    /// * [`CspVetKdEncryptedKeyShareCreationError::InvalidArgumentMasterPublicKey`]
+    ///   if the master public key is malformed
+    /// * [`CspVetKdEncryptedKeyShareCreationError::InvalidArgumentEncryptionPublicKey`]
+    ///   if the transport public key is malformed
+    /// * [`CspVetKdEncryptedKeyShareCreationError::TransientInternalError`] if
+    ///   a transient internal error, e.g., an RPC error, occurred
+    fn create_encrypted_vetkd_key_share(
+        &self,
+        key_id: KeyId,
+        master_public_key: Vec<u8>,
+        encryption_public_key: Vec<u8>,
+        derivation_path: ExtendedDerivationPath,
+        derivation_id: Vec<u8>,
+    ) -> Result<VetKdEncryptedKeyShare, CspVetKdEncryptedKeyShareCreationError>;
+}
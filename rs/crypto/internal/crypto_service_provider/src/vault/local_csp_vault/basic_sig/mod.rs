@@ -41,6 +41,24 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         result
     }
 
+    fn sign_batch(
+        &self,
+        algorithm_id: AlgorithmId,
+        messages: &[Vec<u8>],
+        key_id: KeyId,
+    ) -> Result<Vec<CspSignature>, CspBasicSignatureError> {
+        let start_time = self.metrics.now();
+        let result = self.sign_batch_internal(algorithm_id, messages, key_id);
+        self.metrics.observe_duration_seconds(
+            MetricsDomain::BasicSignature,
+            MetricsScope::Local,
+            "sign_batch",
+            MetricsResult::from(&result),
+            start_time,
+        );
+        result
+    }
+
     fn gen_node_signing_key_pair(&self) -> Result<CspPublicKey, CspBasicSignatureKeygenError> {
         let start_time = self.metrics.now();
         let result = self.gen_node_signing_key_pair_internal();
@@ -132,32 +150,59 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
         message: &[u8],
         key_id: KeyId,
     ) -> Result<CspSignature, CspBasicSignatureError> {
+        let secret_key = self.basic_sig_secret_key(algorithm_id, key_id)?;
+        sign_with_secret_key(algorithm_id, message, &secret_key)
+    }
+
+    fn sign_batch_internal(
+        &self,
+        algorithm_id: AlgorithmId,
+        messages: &[Vec<u8>],
+        key_id: KeyId,
+    ) -> Result<Vec<CspSignature>, CspBasicSignatureError> {
+        let secret_key = self.basic_sig_secret_key(algorithm_id, key_id)?;
+        messages
+            .iter()
+            .map(|message| sign_with_secret_key(algorithm_id, message, &secret_key))
+            .collect()
+    }
+
+    fn basic_sig_secret_key(
+        &self,
+        algorithm_id: AlgorithmId,
+        key_id: KeyId,
+    ) -> Result<CspSecretKey, CspBasicSignatureError> {
         let maybe_secret_key = self.sks_read_lock().get(&key_id);
-        let secret_key: CspSecretKey =
-            maybe_secret_key.ok_or(CspBasicSignatureError::SecretKeyNotFound {
-                algorithm: algorithm_id,
-                key_id,
-            })?;
-        let result = match algorithm_id {
-            AlgorithmId::Ed25519 => match &secret_key {
-                CspSecretKey::Ed25519(secret_key) => {
-                    let sig_bytes = ed25519::sign(message, secret_key).map_err(|_e| {
-                        CspBasicSignatureError::MalformedSecretKey {
-                            algorithm: AlgorithmId::Ed25519,
-                        }
-                    })?;
-                    Ok(CspSignature::Ed25519(sig_bytes))
-                }
-                _ => Err(CspBasicSignatureError::WrongSecretKeyType {
-                    algorithm: algorithm_id,
-                    secret_key_variant: secret_key.enum_variant().to_string(),
-                }),
-            },
-            _ => Err(CspBasicSignatureError::UnsupportedAlgorithm {
+        maybe_secret_key.ok_or(CspBasicSignatureError::SecretKeyNotFound {
+            algorithm: algorithm_id,
+            key_id,
+        })
+    }
+}
+
+fn sign_with_secret_key(
+    algorithm_id: AlgorithmId,
+    message: &[u8],
+    secret_key: &CspSecretKey,
+) -> Result<CspSignature, CspBasicSignatureError> {
+    match algorithm_id {
+        AlgorithmId::Ed25519 => match secret_key {
+            CspSecretKey::Ed25519(secret_key) => {
+                let sig_bytes = ed25519::sign(message, secret_key).map_err(|_e| {
+                    CspBasicSignatureError::MalformedSecretKey {
+                        algorithm: AlgorithmId::Ed25519,
+                    }
+                })?;
+                Ok(CspSignature::Ed25519(sig_bytes))
+            }
+            _ => Err(CspBasicSignatureError::WrongSecretKeyType {
                 algorithm: algorithm_id,
+                secret_key_variant: secret_key.enum_variant().to_string(),
             }),
-        };
-        result
+        },
+        _ => Err(CspBasicSignatureError::UnsupportedAlgorithm {
+            algorithm: algorithm_id,
+        }),
     }
 }
 
@@ -284,6 +284,74 @@ fn should_fail_to_sign_if_secret_key_in_store_has_wrong_type() {
     );
 }
 
+#[test]
+fn should_sign_batch_with_same_signatures_as_sign() {
+    let csp_vault = LocalCspVault::builder().build_into_arc();
+    let public_key = csp_vault
+        .gen_node_signing_key_pair()
+        .expect("failed to generate keys");
+    let key_id = KeyId::try_from(&public_key).unwrap();
+    let mut rng = reproducible_rng();
+    let messages: Vec<Vec<u8>> = (0..5)
+        .map(|_| {
+            let msg_len_in_bytes = rng.gen_range(0..1024);
+            random_message(&mut rng, msg_len_in_bytes)
+        })
+        .collect();
+
+    let signatures = csp_vault
+        .sign_batch(AlgorithmId::Ed25519, &messages, key_id)
+        .expect("failed to sign batch");
+
+    assert_eq!(signatures.len(), messages.len());
+    for (message, signature) in messages.iter().zip(signatures) {
+        assert_eq!(
+            signature,
+            csp_vault
+                .sign(AlgorithmId::Ed25519, message, key_id)
+                .expect("failed to sign")
+        );
+    }
+}
+
+#[test]
+fn should_sign_empty_batch() {
+    let csp_vault = LocalCspVault::builder().build_into_arc();
+    let public_key = csp_vault
+        .gen_node_signing_key_pair()
+        .expect("failed to generate keys");
+
+    let result = csp_vault.sign_batch(
+        AlgorithmId::Ed25519,
+        &[],
+        KeyId::try_from(&public_key).unwrap(),
+    );
+
+    assert_eq!(result, Ok(vec![]));
+}
+
+#[test]
+fn should_fail_to_sign_batch_with_non_existent_key() {
+    let csp_vault = LocalCspVault::builder().build_into_arc();
+    let mut rng = thread_rng();
+    let (_, pk_bytes) = ed25519::keypair_from_rng(&mut rng);
+    let key_id = KeyId::try_from(&CspPublicKey::Ed25519(pk_bytes)).unwrap();
+
+    let result = csp_vault.sign_batch(
+        AlgorithmId::Ed25519,
+        &[b"some message".to_vec(), b"another message".to_vec()],
+        key_id,
+    );
+
+    assert_eq!(
+        result,
+        Err(CspBasicSignatureError::SecretKeyNotFound {
+            algorithm: AlgorithmId::Ed25519,
+            key_id
+        })
+    );
+}
+
 pub fn generate_key_pair_and_sign_and_verify_message(csp_vault: Arc<dyn CspVault>, message: &[u8]) {
     let (pk_bytes, sign_result) = generate_key_pair_and_sign_message(csp_vault, message);
     assert!(sign_result.is_ok());
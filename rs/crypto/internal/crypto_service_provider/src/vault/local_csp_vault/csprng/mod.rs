@@ -0,0 +1,225 @@
+//! The CSPRNG of the [`ProdLocalCspVault`](super::ProdLocalCspVault), whose
+//! entropy source is selected with [`CspRngSource`].
+//!
+//! The sources are variants of a single enum rather than a type parameter,
+//! such that the production vault has one type regardless of the config.
+use ic_config::crypto::CspRngSource;
+use ic_crypto_internal_logmon::metrics::CsprngSource;
+use ic_crypto_sha::{Context, DomainSeparationContext, Sha256};
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::fmt;
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+mod tests;
+
+const HW_RNG_SEED_DOMAIN: &str = "ic-crypto-csp-hw-rng-seed";
+
+pub enum CspRng {
+    Getrandom(OsRng),
+    HwRng(HwRng),
+    #[cfg(feature = "seeded_drbg_rng_source")]
+    SeededDrbg(Box<ChaCha20Rng>),
+}
+
+/// The entropy source of a [`CspRng`] is unavailable, e.g., because a
+/// hardware RNG device cannot be opened.
+#[derive(Debug)]
+pub struct CspRngSourceError {
+    pub rng_source: CspRngSource,
+    pub error: Error,
+}
+
+impl fmt::Display for CspRngSourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to create the CSPRNG from {:?}: {}",
+            self.rng_source, self.error
+        )
+    }
+}
+
+impl CspRng {
+    /// Creates a CSPRNG using the entropy `source`.
+    ///
+    /// # Errors
+    /// If the source is a hardware RNG device that cannot be read, or if the
+    /// source is not supported by this build.
+    pub fn new(source: &CspRngSource) -> Result<Self, CspRngSourceError> {
+        match source {
+            CspRngSource::Getrandom => Ok(CspRng::Getrandom(OsRng)),
+            CspRngSource::HwRng(path) => HwRng::open(path).map(CspRng::HwRng),
+            #[cfg(feature = "seeded_drbg_rng_source")]
+            CspRngSource::SeededDrbg(seed) => Ok(CspRng::SeededDrbg(Box::new(
+                ChaCha20Rng::seed_from_u64(*seed),
+            ))),
+            _ => Err(Error::new(
+                ErrorKind::Unsupported,
+                "the RNG source is not supported by this build",
+            )),
+        }
+        .map_err(|error| CspRngSourceError {
+            rng_source: source.clone(),
+            error,
+        })
+    }
+
+    pub fn source(&self) -> CsprngSource {
+        match self {
+            CspRng::Getrandom(_) => CsprngSource::Getrandom,
+            CspRng::HwRng(_) => CsprngSource::HwRng,
+            #[cfg(feature = "seeded_drbg_rng_source")]
+            CspRng::SeededDrbg(_) => CsprngSource::SeededDrbg,
+        }
+    }
+}
+
+impl RngCore for CspRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            CspRng::Getrandom(rng) => rng.next_u32(),
+            CspRng::HwRng(rng) => rng.next_u32(),
+            #[cfg(feature = "seeded_drbg_rng_source")]
+            CspRng::SeededDrbg(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            CspRng::Getrandom(rng) => rng.next_u64(),
+            CspRng::HwRng(rng) => rng.next_u64(),
+            #[cfg(feature = "seeded_drbg_rng_source")]
+            CspRng::SeededDrbg(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            CspRng::Getrandom(rng) => rng.fill_bytes(dest),
+            CspRng::HwRng(rng) => rng.fill_bytes(dest),
+            #[cfg(feature = "seeded_drbg_rng_source")]
+            CspRng::SeededDrbg(rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        match self {
+            CspRng::Getrandom(rng) => rng.try_fill_bytes(dest),
+            CspRng::HwRng(rng) => rng.try_fill_bytes(dest),
+            #[cfg(feature = "seeded_drbg_rng_source")]
+            CspRng::SeededDrbg(rng) => rng.try_fill_bytes(dest),
+        }
+    }
+}
+
+impl CryptoRng for CspRng {}
+
+/// A ChaCha20 DRBG seeded from both a hardware RNG device, such as
+/// `/dev/hwrng`, and [`OsRng`].
+///
+/// The device output is never used as key material directly, so a faulty or
+/// compromised device alone does not determine the generated randomness. The
+/// DRBG is reseeded from the device and [`OsRng`] once it generated
+/// [`HW_RNG_RESEED_INTERVAL_BYTES`] bytes or [`HW_RNG_RESEED_INTERVAL`] passed
+/// since the last seeding, whichever comes first. Each new seed also hashes
+/// output of the previous DRBG, such that a reseed never loses entropy.
+pub struct HwRng {
+    device: File,
+    drbg: ChaCha20Rng,
+    bytes_since_reseed: usize,
+    last_reseed: Instant,
+}
+
+/// How many bytes an [`HwRng`] generates at most before it is reseeded.
+pub const HW_RNG_RESEED_INTERVAL_BYTES: usize = 1 << 16;
+
+/// How long an [`HwRng`] is used at most before it is reseeded.
+pub const HW_RNG_RESEED_INTERVAL: Duration = Duration::from_secs(60);
+
+impl HwRng {
+    /// Seeds the DRBG with the hash of 32 bytes read from the device at `path`
+    /// and 32 bytes from [`OsRng`]. The device is kept open for reseeding.
+    ///
+    /// # Errors
+    /// If the device cannot be opened or read, or if [`OsRng`] fails.
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let mut device = File::open(path)?;
+        let seed = Self::seed(&mut device, &[])?;
+        Ok(Self {
+            device,
+            drbg: ChaCha20Rng::from_seed(seed),
+            bytes_since_reseed: 0,
+            last_reseed: Instant::now(),
+        })
+    }
+
+    /// Hashes 32 bytes of the device, 32 bytes of [`OsRng`] and the output of
+    /// the previous DRBG, if any, into a seed.
+    fn seed(device: &mut File, previous_output: &[u8]) -> std::io::Result<[u8; 32]> {
+        let mut device_entropy = [0; 32];
+        device.read_exact(&mut device_entropy)?;
+        let mut os_entropy = [0; 32];
+        OsRng
+            .try_fill_bytes(&mut os_entropy)
+            .map_err(|e| Error::new(ErrorKind::Other, e))?;
+        let mut hash = Sha256::new_with_context(&DomainSeparationContext::new(HW_RNG_SEED_DOMAIN));
+        hash.write(&device_entropy);
+        hash.write(&os_entropy);
+        hash.write(previous_output);
+        Ok(hash.finish())
+    }
+
+    fn reseed_if_due(&mut self) -> std::io::Result<()> {
+        if self.bytes_since_reseed < HW_RNG_RESEED_INTERVAL_BYTES
+            && self.last_reseed.elapsed() < HW_RNG_RESEED_INTERVAL
+        {
+            return Ok(());
+        }
+        let mut previous_output = [0; 32];
+        self.drbg.fill_bytes(&mut previous_output);
+        self.drbg = ChaCha20Rng::from_seed(Self::seed(&mut self.device, &previous_output)?);
+        self.bytes_since_reseed = 0;
+        self.last_reseed = Instant::now();
+        Ok(())
+    }
+}
+
+impl RngCore for HwRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// # Panics
+    /// If the DRBG is due for a reseed and the device cannot be read, like
+    /// [`OsRng`] does if it fails.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(e) = self.try_fill_bytes(dest) {
+            panic!("Failed to reseed the hardware RNG: {}", e)
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        for chunk in dest.chunks_mut(HW_RNG_RESEED_INTERVAL_BYTES) {
+            self.reseed_if_due().map_err(rand::Error::new)?;
+            self.drbg.fill_bytes(chunk);
+            self.bytes_since_reseed += chunk.len();
+        }
+        Ok(())
+    }
+}
+
+impl CryptoRng for HwRng {}
@@ -0,0 +1,69 @@
+#![allow(clippy::unwrap_used)]
+use super::*;
+use rand::Rng;
+
+#[cfg(feature = "seeded_drbg_rng_source")]
+#[test]
+fn should_generate_same_bytes_with_same_seed() {
+    let bytes = |seed| {
+        let mut rng = CspRng::new(&CspRngSource::SeededDrbg(seed)).unwrap();
+        assert_eq!(rng.source(), CsprngSource::SeededDrbg);
+        rng.gen::<[u8; 32]>()
+    };
+
+    assert_eq!(bytes(42), bytes(42));
+    assert_ne!(bytes(42), bytes(43));
+}
+
+#[test]
+fn should_seed_drbg_from_hardware_rng_device_and_os_rng() {
+    let device = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(device.path(), [1; 64]).unwrap();
+    let bytes = || {
+        let mut rng = CspRng::new(&CspRngSource::HwRng(device.path().to_path_buf())).unwrap();
+        assert_eq!(rng.source(), CsprngSource::HwRng);
+        rng.gen::<[u8; 32]>()
+    };
+
+    let first = bytes();
+    assert_ne!(first, [1; 32]);
+    assert_ne!(first, bytes());
+}
+
+#[test]
+fn should_reseed_drbg_from_hardware_rng_device_after_reseed_interval_bytes() {
+    let device = tempfile::NamedTempFile::new().unwrap();
+    // Enough for the initial seed and one reseed.
+    std::fs::write(device.path(), [1; 64]).unwrap();
+    let mut rng = HwRng::open(device.path()).unwrap();
+    let mut bytes = vec![0; HW_RNG_RESEED_INTERVAL_BYTES];
+
+    assert!(rng.try_fill_bytes(&mut bytes).is_ok());
+    assert!(rng.try_fill_bytes(&mut bytes).is_ok());
+    assert!(rng.try_fill_bytes(&mut [0; 1]).is_err());
+}
+
+#[test]
+fn should_fail_to_seed_drbg_from_too_short_hardware_rng_device() {
+    let device = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(device.path(), [1; 31]).unwrap();
+
+    let result = CspRng::new(&CspRngSource::HwRng(device.path().to_path_buf()));
+
+    assert_eq!(
+        result.err().map(|e| e.error.kind()),
+        Some(ErrorKind::UnexpectedEof)
+    );
+}
+
+#[test]
+fn should_fail_to_open_missing_hardware_rng_device() {
+    let dir = tempfile::tempdir().unwrap();
+    let rng_source = CspRngSource::HwRng(dir.path().join("hwrng"));
+
+    let result = CspRng::new(&rng_source);
+
+    let error = result.err().unwrap();
+    assert_eq!(error.rng_source, rng_source);
+    assert_eq!(error.error.kind(), ErrorKind::NotFound);
+}
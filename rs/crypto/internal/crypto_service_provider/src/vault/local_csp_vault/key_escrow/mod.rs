@@ -0,0 +1,513 @@
+//! Key escrow operations provided by the CSP vault
+use crate::canister_threshold::IDKG_MEGA_SCOPE;
+use crate::key_id::KeyId;
+use crate::public_key_store::PublicKeyStore;
+use crate::secret_key_store::{Scope, SecretKeyStore};
+use crate::threshold::ni_dkg::NIDKG_FS_SCOPE;
+use crate::types::CspSecretKey;
+use crate::vault::api::{
+    CspEscrowNodeSecretKeysError, CspSealedBackupError, EscrowedNodeSecretKeys, KeyEscrowCspVault,
+};
+use crate::vault::local_csp_vault::public_and_secret_key_store::{
+    LocalNodePublicKeys, RequiredKeyIds, RequiredNodePublicKeys,
+};
+use crate::vault::local_csp_vault::LocalCspVault;
+use ic_config::crypto::KeyEscrowConfig;
+use ic_crypto_internal_key_escrow::{EscrowedSecret, RECOVERY_KEY_CURVE};
+use ic_crypto_internal_logmon::metrics::{MetricsDomain, MetricsResult, MetricsScope};
+use ic_crypto_internal_threshold_sig_ecdsa::{MEGaPrivateKey, MEGaPublicKey};
+use ic_crypto_secrets_containers::SecretBytes;
+use ic_logger::error;
+use ic_protobuf::crypto::v1::NodePublicKeys;
+use ic_protobuf::registry::crypto::v1::{PublicKey as PublicKeyProto, X509PublicKeyCert};
+use ic_types::Time;
+use rand::{CryptoRng, Rng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use zeroize::Zeroize;
+
+#[cfg(test)]
+mod tests;
+
+/// The recovery keys to which the vault escrows the node secret keys, as
+/// configured with [`LocalCspVault::with_key_escrow_config`].
+pub(crate) struct KeyEscrowPolicy {
+    recovery_public_keys: Vec<MEGaPublicKey>,
+    threshold: usize,
+}
+
+impl TryFrom<&KeyEscrowConfig> for KeyEscrowPolicy {
+    type Error = String;
+
+    fn try_from(config: &KeyEscrowConfig) -> Result<Self, Self::Error> {
+        let recovery_public_keys = config
+            .recovery_public_keys
+            .iter()
+            .enumerate()
+            .map(|(index, key)| {
+                public_key_from_hex(key)
+                    .map_err(|e| format!("invalid recovery public key at index {}: {}", index, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if config.threshold == 0 || config.threshold > recovery_public_keys.len() {
+            return Err(format!(
+                "the threshold {} is not between 1 and the number of recovery public keys {}",
+                config.threshold,
+                recovery_public_keys.len()
+            ));
+        }
+        Ok(KeyEscrowPolicy {
+            recovery_public_keys,
+            threshold: config.threshold,
+        })
+    }
+}
+
+/// The backup key of the node operator, with which the vault seals and
+/// unseals backups of the node keys, as configured with
+/// [`LocalCspVault::with_key_escrow_config`].
+pub(crate) struct SealedBackupPolicy {
+    /// The backup public key, or why it is not available.
+    public_key: Result<MEGaPublicKey, String>,
+    /// The file from which the backup private key is read for a restore.
+    private_key_path: Option<PathBuf>,
+}
+
+impl SealedBackupPolicy {
+    pub(crate) fn not_configured() -> Self {
+        SealedBackupPolicy {
+            public_key: Err("no backup public key is configured".to_string()),
+            private_key_path: None,
+        }
+    }
+
+    /// Reads the backup private key, which is only ever held in memory for the
+    /// duration of a restore.
+    fn read_private_key(&self) -> Result<MEGaPrivateKey, String> {
+        let path = self
+            .private_key_path
+            .as_ref()
+            .ok_or_else(|| "no backup private key is configured".to_string())?;
+        let mut key_hex = fs::read_to_string(path).map_err(|e| {
+            format!(
+                "the backup private key cannot be read from {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+        // The errors must not contain any part of the key.
+        let private_key = hex::decode(key_hex.trim())
+            .map(SecretBytes::new)
+            .map_err(|_| "it is not hex-encoded".to_string())
+            .and_then(|bytes| {
+                MEGaPrivateKey::deserialize(RECOVERY_KEY_CURVE, bytes.expose_secret())
+                    .map_err(|_| "it is not a secp256k1 scalar".to_string())
+            })
+            .map_err(|e| {
+                format!(
+                    "the backup private key in {} is invalid: {}",
+                    path.display(),
+                    e
+                )
+            });
+        key_hex.zeroize();
+        private_key
+    }
+}
+
+impl From<&KeyEscrowConfig> for SealedBackupPolicy {
+    fn from(config: &KeyEscrowConfig) -> Self {
+        let public_key = match &config.backup_public_key {
+            None => Err("no backup public key is configured".to_string()),
+            Some(key) => public_key_from_hex(key)
+                .map_err(|e| format!("the backup public key is invalid: {}", e)),
+        };
+        SealedBackupPolicy {
+            public_key,
+            private_key_path: config.backup_private_key_path.clone(),
+        }
+    }
+}
+
+fn public_key_from_hex(key: &str) -> Result<MEGaPublicKey, String> {
+    hex::decode(key)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| {
+            MEGaPublicKey::deserialize(RECOVERY_KEY_CURVE, &bytes).map_err(|e| format!("{:?}", e))
+        })
+}
+
+impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore>
+    LocalCspVault<R, S, C, P>
+{
+    /// Makes the vault escrow the node secret keys to the recovery keys in
+    /// `config`, and seal and unseal backups of the node keys with the backup
+    /// key in `config`. Without a config, or with an invalid one, which is
+    /// logged, the vault refuses to escrow the node secret keys, or to export
+    /// sealed backups, respectively.
+    pub fn with_key_escrow_config(mut self, config: Option<&KeyEscrowConfig>) -> Self {
+        self.key_escrow_policy = match config {
+            None => Err("no recovery public keys are configured".to_string()),
+            Some(config) => KeyEscrowPolicy::try_from(config).map_err(|e| {
+                error!(
+                    self.logger,
+                    "Invalid key escrow config, the node secret keys cannot be escrowed: {}", e
+                );
+                format!("the key escrow config is invalid: {}", e)
+            }),
+        };
+        self.sealed_backup_policy = match config {
+            None => SealedBackupPolicy::not_configured(),
+            Some(config) => {
+                let policy = SealedBackupPolicy::from(config);
+                if let (Some(_), Err(e)) = (&config.backup_public_key, &policy.public_key) {
+                    error!(
+                        self.logger,
+                        "Invalid key escrow config, sealed backups cannot be exported: {}", e
+                    );
+                }
+                policy
+            }
+        };
+        self
+    }
+}
+
+impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore> KeyEscrowCspVault
+    for LocalCspVault<R, S, C, P>
+{
+    fn escrow_node_secret_keys(
+        &self,
+        associated_data: Vec<u8>,
+    ) -> Result<EscrowedSecret, CspEscrowNodeSecretKeysError> {
+        let start_time = self.metrics.now();
+        let result = self.escrow_node_secret_keys_internal(associated_data);
+        self.metrics.observe_duration_seconds(
+            MetricsDomain::KeyManagement,
+            MetricsScope::Local,
+            "escrow_node_secret_keys",
+            MetricsResult::from(&result),
+            start_time,
+        );
+        result
+    }
+
+    fn export_sealed_backup(&self) -> Result<EscrowedSecret, CspSealedBackupError> {
+        let start_time = self.metrics.now();
+        let result = self.export_sealed_backup_internal();
+        self.metrics.observe_duration_seconds(
+            MetricsDomain::KeyManagement,
+            MetricsScope::Local,
+            "export_sealed_backup",
+            MetricsResult::from(&result),
+            start_time,
+        );
+        result
+    }
+
+    fn restore_sealed_backup(
+        &self,
+        sealed_backup: EscrowedSecret,
+    ) -> Result<(), CspSealedBackupError> {
+        let start_time = self.metrics.now();
+        let result = self.restore_sealed_backup_internal(sealed_backup);
+        self.metrics.observe_duration_seconds(
+            MetricsDomain::KeyManagement,
+            MetricsScope::Local,
+            "restore_sealed_backup",
+            MetricsResult::from(&result),
+            start_time,
+        );
+        result
+    }
+}
+
+impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore>
+    LocalCspVault<R, S, C, P>
+{
+    fn escrow_node_secret_keys_internal(
+        &self,
+        associated_data: Vec<u8>,
+    ) -> Result<EscrowedSecret, CspEscrowNodeSecretKeysError> {
+        let policy = self.key_escrow_policy.as_ref().map_err(|reason| {
+            CspEscrowNodeSecretKeysError::KeyEscrowNotConfigured {
+                reason: reason.clone(),
+            }
+        })?;
+        let node_secret_keys = {
+            let (sks_read_lock, pks_read_lock) = self.sks_and_pks_read_locks();
+            let key_ids = RequiredNodePublicKeys::try_from(
+                LocalNodePublicKeys::from_public_key_store(pks_read_lock),
+            )
+            .and_then(|public_keys| public_keys.compute_key_ids())
+            .map_err(CspEscrowNodeSecretKeysError::NodeKeysError)?;
+            let secret_keys = secret_keys_of(&*sks_read_lock, &key_ids).map_err(|key_id| {
+                CspEscrowNodeSecretKeysError::SecretKeyNotFound {
+                    key_id: key_id.to_string(),
+                }
+            })?;
+            EscrowedNodeSecretKeys { secret_keys }
+        }; // drop read locks on SKS and PKS
+
+        let plaintext = node_secret_keys
+            .to_secret_bytes()
+            .map_err(
+                |internal_error| CspEscrowNodeSecretKeysError::InternalError { internal_error },
+            )?;
+        // Encrypting the shares is comparatively expensive, so the RNG lock is
+        // only held to derive a dedicated RNG.
+        let mut rng = self.generate_seed().into_rng();
+        EscrowedSecret::encrypt(
+            &plaintext,
+            &policy.recovery_public_keys,
+            policy.threshold,
+            &associated_data,
+            &mut rng,
+        )
+        .map_err(CspEscrowNodeSecretKeysError::EscrowError)
+    }
+
+    fn export_sealed_backup_internal(&self) -> Result<EscrowedSecret, CspSealedBackupError> {
+        let backup_public_key =
+            self.sealed_backup_policy
+                .public_key
+                .as_ref()
+                .map_err(|reason| CspSealedBackupError::SealedBackupNotConfigured {
+                    reason: reason.clone(),
+                })?;
+        let sealed_node_keys = {
+            let (sks_read_lock, pks_read_lock) = self.sks_and_pks_read_locks();
+            let timestamps = pks_read_lock.generation_timestamps();
+            let mut public_keys = LocalNodePublicKeys::from_public_key_store(pks_read_lock);
+            let key_ids = RequiredNodePublicKeys::try_from(public_keys.clone())
+                .and_then(|required_public_keys| required_public_keys.compute_key_ids())
+                .map_err(CspSealedBackupError::NodeKeysError)?;
+            // The key IDs include those of all IDKG dealing encryption public
+            // keys in the store, not only of the current one, since dealings
+            // encrypted to a previous key may still have to be opened after a
+            // restore. The export fails if any of their secret keys is missing.
+            let secret_keys = secret_keys_of(&*sks_read_lock, &key_ids).map_err(|key_id| {
+                CspSealedBackupError::SecretKeyNotFound {
+                    key_id: key_id.to_string(),
+                }
+            })?;
+            set_timestamp(
+                &mut public_keys.node_signing_public_key,
+                timestamps.node_signing_public_key,
+            );
+            set_timestamp(
+                &mut public_keys.committee_signing_public_key,
+                timestamps.committee_signing_public_key,
+            );
+            set_timestamp(
+                &mut public_keys.dkg_dealing_encryption_public_key,
+                timestamps.dkg_dealing_encryption_public_key,
+            );
+            // The public key store only keeps the timestamp of the current
+            // IDKG dealing encryption public key.
+            if let Some(idkg_public_key) =
+                public_keys.idkg_dealing_encryption_public_keys.last_mut()
+            {
+                idkg_public_key.timestamp = timestamps
+                    .last_idkg_dealing_encryption_public_key
+                    .map(Time::as_millis_since_unix_epoch);
+            }
+            SealedNodeKeys {
+                node_signing_public_key: public_keys.node_signing_public_key,
+                committee_signing_public_key: public_keys.committee_signing_public_key,
+                tls_certificate: public_keys.tls_certificate,
+                dkg_dealing_encryption_public_key: public_keys.dkg_dealing_encryption_public_key,
+                idkg_dealing_encryption_public_keys: public_keys
+                    .idkg_dealing_encryption_public_keys,
+                secret_keys,
+            }
+        }; // drop read locks on SKS and PKS
+
+        let plaintext = serde_cbor::to_vec(&sealed_node_keys)
+            .map(SecretBytes::new)
+            .map_err(|e| CspSealedBackupError::InternalError {
+                internal_error: format!("Failed to serialize the node keys: {}", e),
+            })?;
+        let mut rng = self.generate_seed().into_rng();
+        EscrowedSecret::encrypt(
+            &plaintext,
+            &[backup_public_key.clone()],
+            1,
+            SEALED_BACKUP_ASSOCIATED_DATA,
+            &mut rng,
+        )
+        .map_err(CspSealedBackupError::EscrowError)
+    }
+
+    fn restore_sealed_backup_internal(
+        &self,
+        sealed_backup: EscrowedSecret,
+    ) -> Result<(), CspSealedBackupError> {
+        let decryption_key = self
+            .sealed_backup_policy
+            .read_private_key()
+            .map_err(|reason| CspSealedBackupError::SealedBackupNotConfigured { reason })?;
+        let plaintext = sealed_backup
+            .decrypt_share(SEALED_BACKUP_ASSOCIATED_DATA, 0, &decryption_key)
+            .and_then(|share| sealed_backup.combine(SEALED_BACKUP_ASSOCIATED_DATA, &[share]))
+            .map_err(CspSealedBackupError::EscrowError)?;
+        let sealed_node_keys: SealedNodeKeys = serde_cbor::from_slice(plaintext.expose_secret())
+            .map_err(|e| CspSealedBackupError::InternalError {
+                internal_error: format!("Failed to deserialize the node keys: {}", e),
+            })?;
+
+        // Validate the restored keys before modifying any key store.
+        let required_public_keys = RequiredNodePublicKeys::try_from(LocalNodePublicKeys {
+            node_signing_public_key: sealed_node_keys.node_signing_public_key.clone(),
+            committee_signing_public_key: sealed_node_keys.committee_signing_public_key.clone(),
+            tls_certificate: sealed_node_keys.tls_certificate.clone(),
+            dkg_dealing_encryption_public_key: sealed_node_keys
+                .dkg_dealing_encryption_public_key
+                .clone(),
+            idkg_dealing_encryption_public_keys: sealed_node_keys
+                .idkg_dealing_encryption_public_keys
+                .clone(),
+        })
+        .map_err(CspSealedBackupError::NodeKeysError)?;
+        let key_ids = required_public_keys
+            .compute_key_ids()
+            .map_err(CspSealedBackupError::NodeKeysError)?;
+        required_public_keys
+            .validate()
+            .map_err(CspSealedBackupError::NodeKeysError)?;
+        if let Some(key_id) = key_ids
+            .iter()
+            .find(|key_id| !sealed_node_keys.secret_keys.contains_key(key_id))
+        {
+            return Err(CspSealedBackupError::SecretKeyNotFound {
+                key_id: key_id.to_string(),
+            });
+        }
+
+        let (mut sks_write_lock, mut pks_write_lock) = self.sks_and_pks_write_locks();
+        let pks_is_empty = pks_write_lock.node_signing_pubkey().is_none()
+            && pks_write_lock.committee_signing_pubkey().is_none()
+            && pks_write_lock.tls_certificate().is_none()
+            && pks_write_lock.ni_dkg_dealing_encryption_pubkey().is_none()
+            && pks_write_lock.idkg_dealing_encryption_pubkeys().is_empty();
+        let sks_contains_restored_key = sealed_node_keys
+            .secret_keys
+            .keys()
+            .any(|key_id| sks_write_lock.contains(key_id));
+        if !pks_is_empty || sks_contains_restored_key {
+            return Err(CspSealedBackupError::KeyStoresNotEmpty);
+        }
+
+        // Commit the secret keys first, as their insertion can be rolled back,
+        // and then all public keys at once.
+        let mut inserted_key_ids = Vec::with_capacity(sealed_node_keys.secret_keys.len());
+        for (key_id, secret_key) in &sealed_node_keys.secret_keys {
+            if let Err(e) = sks_write_lock.insert(*key_id, secret_key.clone(), scope_of(secret_key))
+            {
+                return Err(roll_back_secret_keys(
+                    &mut *sks_write_lock,
+                    &inserted_key_ids,
+                    format!("{:?}", e),
+                ));
+            }
+            inserted_key_ids.push(*key_id);
+        }
+        if let Err(e) = pks_write_lock.set_once_all_node_public_keys(NodePublicKeys {
+            version: 0,
+            node_signing_pk: sealed_node_keys.node_signing_public_key,
+            committee_signing_pk: sealed_node_keys.committee_signing_public_key,
+            tls_certificate: sealed_node_keys.tls_certificate,
+            dkg_dealing_encryption_pk: sealed_node_keys.dkg_dealing_encryption_public_key,
+            idkg_dealing_encryption_pks: sealed_node_keys.idkg_dealing_encryption_public_keys,
+        }) {
+            return Err(roll_back_secret_keys(
+                &mut *sks_write_lock,
+                &inserted_key_ids,
+                format!("{:?}", e),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Removes the secret keys inserted by a failed restore of a sealed backup,
+/// and returns the error of the restore, which also reports whether the
+/// removal failed.
+fn roll_back_secret_keys<S: SecretKeyStore>(
+    sks: &mut S,
+    inserted_key_ids: &[KeyId],
+    persistence_error: String,
+) -> CspSealedBackupError {
+    let removal_errors: Vec<String> = inserted_key_ids
+        .iter()
+        .filter_map(|key_id| {
+            sks.remove(key_id)
+                .err()
+                .map(|e| format!("key with ID {}: {:?}", key_id, e))
+        })
+        .collect();
+    let internal_error = if removal_errors.is_empty() {
+        format!(
+            "Failed to persist the restored node keys: {}",
+            persistence_error
+        )
+    } else {
+        format!(
+            "Failed to persist the restored node keys: {}, and failed to remove the \
+            already restored secret keys: {}",
+            persistence_error,
+            removal_errors.join(", ")
+        )
+    };
+    CspSealedBackupError::InternalError { internal_error }
+}
+
+/// Binds sealed backups to their purpose, so that they cannot be confused with
+/// other secrets encrypted to the same key.
+const SEALED_BACKUP_ASSOCIATED_DATA: &[u8] = b"ic-crypto-sealed-node-key-backup";
+
+/// The contents of a sealed backup: the node public keys, as stored in the
+/// public key store but with their generation timestamps, and the
+/// corresponding secret keys, including those of all IDKG dealing encryption
+/// public keys.
+#[derive(Serialize, Deserialize)]
+struct SealedNodeKeys {
+    node_signing_public_key: Option<PublicKeyProto>,
+    committee_signing_public_key: Option<PublicKeyProto>,
+    tls_certificate: Option<X509PublicKeyCert>,
+    dkg_dealing_encryption_public_key: Option<PublicKeyProto>,
+    idkg_dealing_encryption_public_keys: Vec<PublicKeyProto>,
+    secret_keys: BTreeMap<KeyId, CspSecretKey>,
+}
+
+/// Returns the secret keys with the given IDs, or the ID of the first missing
+/// key.
+fn secret_keys_of<S: SecretKeyStore>(
+    sks: &S,
+    key_ids: &RequiredKeyIds,
+) -> Result<BTreeMap<KeyId, CspSecretKey>, KeyId> {
+    let mut secret_keys = BTreeMap::new();
+    for key_id in key_ids.iter() {
+        let secret_key = sks.get(key_id).ok_or(*key_id)?;
+        secret_keys.insert(*key_id, secret_key);
+    }
+    Ok(secret_keys)
+}
+
+fn set_timestamp(public_key: &mut Option<PublicKeyProto>, timestamp: Option<Time>) {
+    if let Some(public_key) = public_key {
+        public_key.timestamp = timestamp.map(Time::as_millis_since_unix_epoch);
+    }
+}
+
+/// The scope with which a node secret key is stored, which is not part of a
+/// sealed backup since it is determined by the type of the key.
+fn scope_of(secret_key: &CspSecretKey) -> Option<Scope> {
+    match secret_key {
+        CspSecretKey::MEGaEncryptionK256(_) => Some(IDKG_MEGA_SCOPE),
+        CspSecretKey::FsEncryption(_) => Some(NIDKG_FS_SCOPE),
+        _ => None,
+    }
+}
@@ -0,0 +1,417 @@
+#![allow(clippy::unwrap_used)]
+use super::{SealedNodeKeys, SEALED_BACKUP_ASSOCIATED_DATA};
+use crate::key_id::KeyId;
+use crate::public_key_store::mock_pubkey_store::MockPublicKeyStore;
+use crate::public_key_store::PublicKeySetOnceError;
+use crate::secret_key_store::SecretKeyStore;
+use crate::vault::api::{
+    CspEscrowNodeSecretKeysError, CspSealedBackupError, CspVault, EscrowedNodeSecretKeys,
+    ValidatePksAndSksError,
+};
+use crate::vault::test_utils::public_key_store::{
+    generate_all_keys, generate_idkg_dealing_encryption_key_pair,
+};
+use crate::LocalCspVault;
+use assert_matches::assert_matches;
+use ic_config::crypto::KeyEscrowConfig;
+use ic_crypto_internal_key_escrow::{EscrowedSecret, KeyEscrowError, RECOVERY_KEY_CURVE};
+use ic_crypto_internal_threshold_sig_ecdsa::{MEGaPrivateKey, MEGaPublicKey};
+use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
+use ic_protobuf::registry::crypto::v1::PublicKey;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const ASSOCIATED_DATA: &[u8] = b"node-1";
+
+fn recovery_key_pairs(n: usize) -> (Vec<MEGaPrivateKey>, Vec<MEGaPublicKey>) {
+    let rng = &mut reproducible_rng();
+    let private_keys: Vec<_> = (0..n)
+        .map(|_| MEGaPrivateKey::generate(RECOVERY_KEY_CURVE, rng))
+        .collect();
+    let public_keys = private_keys
+        .iter()
+        .map(|private_key| private_key.public_key().unwrap())
+        .collect();
+    (private_keys, public_keys)
+}
+
+fn key_escrow_config(public_keys: &[MEGaPublicKey], threshold: usize) -> KeyEscrowConfig {
+    KeyEscrowConfig {
+        recovery_public_keys: public_keys
+            .iter()
+            .map(|public_key| hex::encode(public_key.serialize()))
+            .collect(),
+        threshold,
+        backup_public_key: None,
+        backup_private_key_path: None,
+    }
+}
+
+/// Returns a vault that seals backups to the public key of `backup_key_pair`,
+/// together with a vault that restores them with its private key, which is
+/// stored in a file in `dir`.
+fn sealed_backup_vaults(
+    backup_key_pair: (&MEGaPrivateKey, &MEGaPublicKey),
+    dir: &TempDir,
+) -> (Arc<dyn CspVault>, Arc<dyn CspVault>) {
+    let (backup_private_key, backup_public_key) = backup_key_pair;
+    let exporting_config = KeyEscrowConfig {
+        backup_public_key: Some(hex::encode(backup_public_key.serialize())),
+        ..key_escrow_config(&[backup_public_key.clone()], 1)
+    };
+    let restoring_config = KeyEscrowConfig {
+        backup_private_key_path: Some(backup_private_key_file(backup_private_key, dir)),
+        ..key_escrow_config(&[backup_public_key.clone()], 1)
+    };
+    (
+        vault_with_key_escrow_config(Some(&exporting_config)),
+        vault_with_key_escrow_config(Some(&restoring_config)),
+    )
+}
+
+fn backup_private_key_file(backup_private_key: &MEGaPrivateKey, dir: &TempDir) -> PathBuf {
+    let path = dir.path().join("backup-key");
+    fs::write(&path, hex::encode(backup_private_key.serialize()) + "\n").unwrap();
+    path
+}
+
+/// Returns the IDs of the secret keys in `sealed_backup`.
+fn key_ids_in(sealed_backup: &EscrowedSecret, backup_private_key: &MEGaPrivateKey) -> Vec<KeyId> {
+    let share = sealed_backup
+        .decrypt_share(SEALED_BACKUP_ASSOCIATED_DATA, 0, backup_private_key)
+        .unwrap();
+    let plaintext = sealed_backup
+        .combine(SEALED_BACKUP_ASSOCIATED_DATA, &[share])
+        .unwrap();
+    let sealed_node_keys: SealedNodeKeys =
+        serde_cbor::from_slice(plaintext.expose_secret()).unwrap();
+    sealed_node_keys.secret_keys.into_keys().collect()
+}
+
+fn vault_with_key_escrow_config(config: Option<&KeyEscrowConfig>) -> Arc<dyn CspVault> {
+    Arc::new(
+        LocalCspVault::builder()
+            .build()
+            .with_key_escrow_config(config),
+    )
+}
+
+#[test]
+fn should_recover_all_node_secret_keys_from_escrow() {
+    let (private_keys, public_keys) = recovery_key_pairs(3);
+    let csp_vault = vault_with_key_escrow_config(Some(&key_escrow_config(&public_keys, 2)));
+    let _current_node_public_keys = generate_all_keys(&csp_vault);
+    let _rotated_idkg_public_key = generate_idkg_dealing_encryption_key_pair(&csp_vault);
+
+    let escrowed = csp_vault
+        .escrow_node_secret_keys(ASSOCIATED_DATA.to_vec())
+        .unwrap();
+
+    let shares = [
+        escrowed
+            .decrypt_share(ASSOCIATED_DATA, 2, &private_keys[2])
+            .unwrap(),
+        escrowed
+            .decrypt_share(ASSOCIATED_DATA, 0, &private_keys[0])
+            .unwrap(),
+    ];
+    let recovered = EscrowedNodeSecretKeys::from_secret_bytes(
+        &escrowed.combine(ASSOCIATED_DATA, &shares).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(recovered.secret_keys.len(), 6);
+    for key_id in recovered.secret_keys.keys() {
+        assert!(csp_vault.sks_contains(key_id).unwrap());
+    }
+}
+
+#[test]
+fn should_fail_to_escrow_without_node_keys() {
+    let (_, public_keys) = recovery_key_pairs(3);
+    let csp_vault = vault_with_key_escrow_config(Some(&key_escrow_config(&public_keys, 2)));
+
+    assert_matches!(
+        csp_vault.escrow_node_secret_keys(ASSOCIATED_DATA.to_vec()),
+        Err(CspEscrowNodeSecretKeysError::NodeKeysError(
+            ValidatePksAndSksError::EmptyPublicKeyStore
+        ))
+    );
+}
+
+#[test]
+fn should_fail_to_escrow_without_key_escrow_config() {
+    let csp_vault = vault_with_key_escrow_config(None);
+    let _current_node_public_keys = generate_all_keys(&csp_vault);
+
+    assert_matches!(
+        csp_vault.escrow_node_secret_keys(ASSOCIATED_DATA.to_vec()),
+        Err(CspEscrowNodeSecretKeysError::KeyEscrowNotConfigured { .. })
+    );
+}
+
+#[test]
+fn should_fail_to_escrow_with_invalid_threshold() {
+    let (_, public_keys) = recovery_key_pairs(3);
+    let csp_vault = vault_with_key_escrow_config(Some(&key_escrow_config(&public_keys, 4)));
+    let _current_node_public_keys = generate_all_keys(&csp_vault);
+
+    assert_matches!(
+        csp_vault.escrow_node_secret_keys(ASSOCIATED_DATA.to_vec()),
+        Err(CspEscrowNodeSecretKeysError::KeyEscrowNotConfigured { reason })
+            if reason.contains("threshold 4")
+    );
+}
+
+#[test]
+fn should_fail_to_escrow_with_malformed_recovery_public_key() {
+    let (_, public_keys) = recovery_key_pairs(3);
+    let mut config = key_escrow_config(&public_keys, 2);
+    config.recovery_public_keys[1] = "not-hex".to_string();
+    let csp_vault = vault_with_key_escrow_config(Some(&config));
+    let _current_node_public_keys = generate_all_keys(&csp_vault);
+
+    assert_matches!(
+        csp_vault.escrow_node_secret_keys(ASSOCIATED_DATA.to_vec()),
+        Err(CspEscrowNodeSecretKeysError::KeyEscrowNotConfigured { reason })
+            if reason.contains("index 1")
+    );
+}
+
+#[test]
+fn should_restore_sealed_backup_into_empty_vault() {
+    let (private_keys, public_keys) = recovery_key_pairs(1);
+    let dir = tempfile::tempdir().unwrap();
+    let (csp_vault, restored_vault) =
+        sealed_backup_vaults((&private_keys[0], &public_keys[0]), &dir);
+    let _current_node_public_keys = generate_all_keys(&csp_vault);
+    let _rotated_idkg_public_key = generate_idkg_dealing_encryption_key_pair(&csp_vault);
+    let sealed_backup = csp_vault.export_sealed_backup().unwrap();
+
+    assert_eq!(restored_vault.restore_sealed_backup(sealed_backup), Ok(()));
+
+    assert_eq!(
+        restored_vault.current_node_public_keys_with_timestamps(),
+        csp_vault.current_node_public_keys_with_timestamps()
+    );
+    assert_eq!(
+        restored_vault.idkg_dealing_encryption_pubkeys_count(),
+        Ok(2)
+    );
+    assert!(restored_vault.validate_pks_and_sks().is_ok());
+}
+
+#[test]
+fn should_restore_secret_keys_of_all_idkg_public_keys() {
+    let (private_keys, public_keys) = recovery_key_pairs(1);
+    let dir = tempfile::tempdir().unwrap();
+    let (csp_vault, restored_vault) =
+        sealed_backup_vaults((&private_keys[0], &public_keys[0]), &dir);
+    let _current_node_public_keys = generate_all_keys(&csp_vault);
+    let rotated_idkg_key_ids: Vec<KeyId> = (0..2)
+        .map(|_| KeyId::try_from(&generate_idkg_dealing_encryption_key_pair(&csp_vault)).unwrap())
+        .collect();
+    let sealed_backup = csp_vault.export_sealed_backup().unwrap();
+    let backed_up_key_ids = key_ids_in(&sealed_backup, &private_keys[0]);
+
+    assert_eq!(restored_vault.restore_sealed_backup(sealed_backup), Ok(()));
+
+    assert_eq!(backed_up_key_ids.len(), 7);
+    assert_eq!(
+        restored_vault.idkg_dealing_encryption_pubkeys_count(),
+        Ok(3)
+    );
+    for key_id in &rotated_idkg_key_ids {
+        assert!(backed_up_key_ids.contains(key_id));
+    }
+    for key_id in &backed_up_key_ids {
+        assert!(restored_vault.sks_contains(key_id).unwrap());
+    }
+    assert!(restored_vault.validate_pks_and_sks().is_ok());
+}
+
+#[test]
+fn should_fail_to_export_sealed_backup_without_secret_key_of_previous_idkg_public_key() {
+    let (_, public_keys) = recovery_key_pairs(1);
+    let config = KeyEscrowConfig {
+        backup_public_key: Some(hex::encode(public_keys[0].serialize())),
+        ..key_escrow_config(&public_keys, 1)
+    };
+    let local_vault = Arc::new(
+        LocalCspVault::builder()
+            .build()
+            .with_key_escrow_config(Some(&config)),
+    );
+    let csp_vault: Arc<dyn CspVault> = local_vault.clone();
+    let _current_node_public_keys = generate_all_keys(&csp_vault);
+    let previous_idkg_key_id =
+        KeyId::try_from(&generate_idkg_dealing_encryption_key_pair(&csp_vault)).unwrap();
+    let _current_idkg_public_key = generate_idkg_dealing_encryption_key_pair(&csp_vault);
+    assert!(local_vault
+        .sks_write_lock()
+        .remove(&previous_idkg_key_id)
+        .unwrap());
+
+    assert_matches!(
+        csp_vault.export_sealed_backup(),
+        Err(CspSealedBackupError::SecretKeyNotFound { key_id })
+            if key_id == previous_idkg_key_id.to_string()
+    );
+}
+
+#[test]
+fn should_fail_to_export_sealed_backup_without_backup_public_key() {
+    let (_, public_keys) = recovery_key_pairs(1);
+    let csp_vault = vault_with_key_escrow_config(Some(&key_escrow_config(&public_keys, 1)));
+    let _current_node_public_keys = generate_all_keys(&csp_vault);
+
+    assert_matches!(
+        csp_vault.export_sealed_backup(),
+        Err(CspSealedBackupError::SealedBackupNotConfigured { reason })
+            if reason.contains("no backup public key")
+    );
+}
+
+#[test]
+fn should_fail_to_export_sealed_backup_with_malformed_backup_public_key() {
+    let (_, public_keys) = recovery_key_pairs(1);
+    let config = KeyEscrowConfig {
+        backup_public_key: Some("not-hex".to_string()),
+        ..key_escrow_config(&public_keys, 1)
+    };
+    let csp_vault = vault_with_key_escrow_config(Some(&config));
+    let _current_node_public_keys = generate_all_keys(&csp_vault);
+
+    assert_matches!(
+        csp_vault.export_sealed_backup(),
+        Err(CspSealedBackupError::SealedBackupNotConfigured { reason })
+            if reason.contains("backup public key is invalid")
+    );
+}
+
+#[test]
+fn should_fail_to_restore_sealed_backup_without_backup_private_key() {
+    let (private_keys, public_keys) = recovery_key_pairs(1);
+    let dir = tempfile::tempdir().unwrap();
+    let (csp_vault, _) = sealed_backup_vaults((&private_keys[0], &public_keys[0]), &dir);
+    let _current_node_public_keys = generate_all_keys(&csp_vault);
+    let sealed_backup = csp_vault.export_sealed_backup().unwrap();
+
+    let restored_vault = vault_with_key_escrow_config(None);
+    assert_matches!(
+        restored_vault.restore_sealed_backup(sealed_backup),
+        Err(CspSealedBackupError::SealedBackupNotConfigured { reason })
+            if reason.contains("no backup private key")
+    );
+}
+
+#[test]
+fn should_fail_to_restore_sealed_backup_with_malformed_backup_private_key() {
+    let (private_keys, public_keys) = recovery_key_pairs(1);
+    let dir = tempfile::tempdir().unwrap();
+    let (csp_vault, restored_vault) =
+        sealed_backup_vaults((&private_keys[0], &public_keys[0]), &dir);
+    let _current_node_public_keys = generate_all_keys(&csp_vault);
+    let sealed_backup = csp_vault.export_sealed_backup().unwrap();
+    fs::write(dir.path().join("backup-key"), "secret-but-not-hex").unwrap();
+
+    assert_matches!(
+        restored_vault.restore_sealed_backup(sealed_backup),
+        Err(CspSealedBackupError::SealedBackupNotConfigured { reason })
+            if reason.contains("is invalid") && !reason.contains("secret")
+    );
+}
+
+#[test]
+fn should_fail_to_restore_sealed_backup_with_other_backup_private_key() {
+    let (private_keys, public_keys) = recovery_key_pairs(2);
+    let dir = tempfile::tempdir().unwrap();
+    let (csp_vault, _) = sealed_backup_vaults((&private_keys[0], &public_keys[0]), &dir);
+    let (_, restored_vault) = sealed_backup_vaults((&private_keys[1], &public_keys[1]), &dir);
+    let _current_node_public_keys = generate_all_keys(&csp_vault);
+    let sealed_backup = csp_vault.export_sealed_backup().unwrap();
+
+    assert_eq!(
+        restored_vault.restore_sealed_backup(sealed_backup),
+        Err(CspSealedBackupError::EscrowError(
+            KeyEscrowError::InvalidRecipient { index: 0 }
+        ))
+    );
+    assert_matches!(
+        restored_vault.validate_pks_and_sks(),
+        Err(ValidatePksAndSksError::EmptyPublicKeyStore)
+    );
+}
+
+#[test]
+fn should_fail_to_restore_sealed_backup_into_vault_with_node_keys() {
+    let (private_keys, public_keys) = recovery_key_pairs(1);
+    let dir = tempfile::tempdir().unwrap();
+    let (csp_vault, restored_vault) =
+        sealed_backup_vaults((&private_keys[0], &public_keys[0]), &dir);
+    let _current_node_public_keys = generate_all_keys(&csp_vault);
+    let _other_node_public_keys = generate_all_keys(&restored_vault);
+    let sealed_backup = csp_vault.export_sealed_backup().unwrap();
+
+    assert_eq!(
+        restored_vault.restore_sealed_backup(sealed_backup),
+        Err(CspSealedBackupError::KeyStoresNotEmpty)
+    );
+}
+
+#[test]
+fn should_remove_restored_secret_keys_if_persisting_public_keys_fails() {
+    let (private_keys, public_keys) = recovery_key_pairs(1);
+    let dir = tempfile::tempdir().unwrap();
+    let (csp_vault, _) = sealed_backup_vaults((&private_keys[0], &public_keys[0]), &dir);
+    let _current_node_public_keys = generate_all_keys(&csp_vault);
+    let sealed_backup = csp_vault.export_sealed_backup().unwrap();
+    let restored_key_ids = key_ids_in(&sealed_backup, &private_keys[0]);
+    let restored_vault: Arc<dyn CspVault> = {
+        let mut empty_pks_failing_to_persist = MockPublicKeyStore::new();
+        empty_pks_failing_to_persist
+            .expect_node_signing_pubkey()
+            .return_const(None);
+        empty_pks_failing_to_persist
+            .expect_committee_signing_pubkey()
+            .return_const(None);
+        empty_pks_failing_to_persist
+            .expect_tls_certificate()
+            .return_const(None);
+        empty_pks_failing_to_persist
+            .expect_ni_dkg_dealing_encryption_pubkey()
+            .return_const(None);
+        empty_pks_failing_to_persist
+            .expect_idkg_dealing_encryption_pubkeys()
+            .return_const(Vec::<PublicKey>::new());
+        empty_pks_failing_to_persist
+            .expect_set_once_all_node_public_keys()
+            .return_once(|_keys| {
+                Err(PublicKeySetOnceError::Io(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    "oh no!",
+                )))
+            });
+        let restoring_config = KeyEscrowConfig {
+            backup_private_key_path: Some(backup_private_key_file(&private_keys[0], &dir)),
+            ..key_escrow_config(&public_keys, 1)
+        };
+        Arc::new(
+            LocalCspVault::builder()
+                .with_public_key_store(empty_pks_failing_to_persist)
+                .build()
+                .with_key_escrow_config(Some(&restoring_config)),
+        )
+    };
+
+    assert_matches!(
+        restored_vault.restore_sealed_backup(sealed_backup),
+        Err(CspSealedBackupError::InternalError { internal_error })
+            if internal_error.contains("oh no!")
+    );
+    assert_eq!(restored_key_ids.len(), 5);
+    for key_id in restored_key_ids {
+        assert!(!restored_vault.sks_contains(&key_id).unwrap());
+    }
+}
@@ -1,5 +1,7 @@
 mod basic_sig;
+pub mod csprng;
 mod idkg;
+mod key_escrow;
 mod multi_sig;
 mod ni_dkg;
 mod public_and_secret_key_store;
@@ -11,20 +13,24 @@ mod tecdsa;
 mod tests;
 mod threshold_sig;
 mod tls;
+mod vetkd;
 
 use crate::public_key_store::proto_pubkey_store::ProtoPublicKeyStore;
 use crate::public_key_store::PublicKeyStore;
+use crate::secret_key_store::pkcs11_store::Pkcs11SecretKeyStore;
 use crate::secret_key_store::proto_store::ProtoSecretKeyStore;
 use crate::secret_key_store::SecretKeyStore;
 use crate::CspRwLock;
-use ic_crypto_internal_logmon::metrics::CryptoMetrics;
+use csprng::{CspRng, CspRngSourceError};
+use ic_config::crypto::CspRngSource;
+use ic_crypto_internal_logmon::metrics::{CryptoMetrics, CsprngSource};
 use ic_crypto_internal_seed::Seed;
 use ic_crypto_utils_time::CurrentSystemTimeSource;
 use ic_interfaces::time_source::TimeSource;
-use ic_logger::{new_logger, ReplicaLogger};
+use ic_logger::{info, new_logger, warn, ReplicaLogger};
 use ic_protobuf::registry::crypto::v1::PublicKey;
+use key_escrow::{KeyEscrowPolicy, SealedBackupPolicy};
 use parking_lot::{RwLockReadGuard, RwLockWriteGuard};
-use rand::rngs::OsRng;
 use rand::{CryptoRng, Rng};
 use std::collections::HashSet;
 use std::path::Path;
@@ -80,16 +86,37 @@ pub struct LocalCspVault<
     time_source: Arc<dyn TimeSource>,
     logger: ReplicaLogger,
     metrics: Arc<CryptoMetrics>,
+    /// The recovery keys for escrowing the node secret keys, or why they are
+    /// not available.
+    key_escrow_policy: Result<KeyEscrowPolicy, String>,
+    /// The backup key for sealed backups of the node keys.
+    sealed_backup_policy: SealedBackupPolicy,
 }
 
 pub type ProdLocalCspVault =
-    LocalCspVault<OsRng, ProtoSecretKeyStore, ProtoSecretKeyStore, ProtoPublicKeyStore>;
+    LocalCspVault<CspRng, ProtoSecretKeyStore, ProtoSecretKeyStore, ProtoPublicKeyStore>;
+
+/// A production-grade local CSP vault whose node secret keys are stored on a
+/// PKCS#11 token.
+pub type Pkcs11LocalCspVault =
+    LocalCspVault<CspRng, Pkcs11SecretKeyStore, ProtoSecretKeyStore, ProtoPublicKeyStore>;
+
+const SKS_DATA_FILENAME: &str = "sks_data.pb";
+const PUBLIC_KEY_STORE_DATA_FILENAME: &str = "public_keys.pb";
+const CANISTER_SKS_DATA_FILENAME: &str = "canister_sks_data.pb";
 
 impl ProdLocalCspVault {
     /// Creates a production-grade local CSP vault.
     ///
     /// For test purposes, it might be more appropriate to use the provided builder.
     ///
+    /// The CSPRNG draws its entropy from `rng_source`, which is logged and
+    /// reported in the metrics.
+    ///
+    /// # Errors
+    /// If the CSPRNG cannot be created from `rng_source`, e.g., because it is a
+    /// hardware RNG device that cannot be read.
+    ///
     /// # Panics
     /// If the key stores (`node_secret_key_store`,`canister_secret_key_store` or `public_key_store`)
     /// do not use distinct files.
@@ -97,34 +124,34 @@ impl ProdLocalCspVault {
         node_secret_key_store: ProtoSecretKeyStore,
         canister_secret_key_store: ProtoSecretKeyStore,
         public_key_store: ProtoPublicKeyStore,
+        rng_source: &CspRngSource,
         metrics: Arc<CryptoMetrics>,
         logger: ReplicaLogger,
-    ) -> Self {
+    ) -> Result<Self, CspRngSourceError> {
         ensure_unique_paths(&[
             node_secret_key_store.proto_file_path(),
             canister_secret_key_store.proto_file_path(),
             public_key_store.proto_file_path(),
         ]);
-        LocalCspVault::new_internal(
-            OsRng,
+        Ok(LocalCspVault::new_internal(
+            new_csprng(rng_source, &metrics, &logger)?,
             node_secret_key_store,
             canister_secret_key_store,
             public_key_store,
             Arc::new(CurrentSystemTimeSource::new(new_logger!(&logger))),
             metrics,
             logger,
-        )
+        ))
     }
 
+    /// Creates a production-grade local CSP vault that keeps its key stores in
+    /// `key_store_dir`, see [`Self::new`].
     pub fn new_in_dir(
         key_store_dir: &Path,
+        rng_source: &CspRngSource,
         metrics: Arc<CryptoMetrics>,
         logger: ReplicaLogger,
-    ) -> Self {
-        const SKS_DATA_FILENAME: &str = "sks_data.pb";
-        const PUBLIC_KEY_STORE_DATA_FILENAME: &str = "public_keys.pb";
-        const CANISTER_SKS_DATA_FILENAME: &str = "canister_sks_data.pb";
-
+    ) -> Result<Self, CspRngSourceError> {
         let node_secret_key_store =
             ProtoSecretKeyStore::open(key_store_dir, SKS_DATA_FILENAME, Some(new_logger!(logger)));
         let canister_secret_key_store = ProtoSecretKeyStore::open(
@@ -141,12 +168,67 @@ impl ProdLocalCspVault {
             node_secret_key_store,
             canister_secret_key_store,
             public_key_store,
+            rng_source,
             metrics,
             logger,
         )
     }
 }
 
+impl Pkcs11LocalCspVault {
+    /// Creates a production-grade local CSP vault that keeps the node secret
+    /// keys in `node_secret_key_store`, and the canister secret keys and the
+    /// public keys in `key_store_dir`.
+    ///
+    /// # Errors
+    /// If the CSPRNG cannot be created from `rng_source`, e.g., because it is a
+    /// hardware RNG device that cannot be read.
+    pub fn new_in_dir_with_node_secret_key_store(
+        key_store_dir: &Path,
+        node_secret_key_store: Pkcs11SecretKeyStore,
+        rng_source: &CspRngSource,
+        metrics: Arc<CryptoMetrics>,
+        logger: ReplicaLogger,
+    ) -> Result<Self, CspRngSourceError> {
+        let canister_secret_key_store = ProtoSecretKeyStore::open(
+            key_store_dir,
+            CANISTER_SKS_DATA_FILENAME,
+            Some(new_logger!(logger)),
+        );
+        let public_key_store = ProtoPublicKeyStore::open(
+            key_store_dir,
+            PUBLIC_KEY_STORE_DATA_FILENAME,
+            new_logger!(logger),
+        );
+        Ok(LocalCspVault::new_internal(
+            new_csprng(rng_source, &metrics, &logger)?,
+            node_secret_key_store,
+            canister_secret_key_store,
+            public_key_store,
+            Arc::new(CurrentSystemTimeSource::new(new_logger!(&logger))),
+            metrics,
+            logger,
+        ))
+    }
+}
+
+fn new_csprng(
+    rng_source: &CspRngSource,
+    metrics: &CryptoMetrics,
+    logger: &ReplicaLogger,
+) -> Result<CspRng, CspRngSourceError> {
+    let csprng = CspRng::new(rng_source)?;
+    info!(logger, "Using CSPRNG entropy source {:?}", rng_source);
+    if csprng.source() == CsprngSource::SeededDrbg {
+        warn!(
+            logger,
+            "The CSPRNG is seeded deterministically, which is insecure outside of tests"
+        );
+    }
+    metrics.observe_csprng_source(csprng.source());
+    Ok(csprng)
+}
+
 impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore>
     LocalCspVault<R, S, C, P>
 {
@@ -176,6 +258,8 @@ impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore
             time_source,
             logger,
             metrics,
+            key_escrow_policy: Err("no recovery public keys are configured".to_string()),
+            sealed_backup_policy: SealedBackupPolicy::not_configured(),
         }
     }
 
@@ -252,7 +252,7 @@ fn compute_tls_certificate_key_id(
 }
 
 #[derive(Debug, Clone)]
-struct LocalNodePublicKeys {
+pub(super) struct LocalNodePublicKeys {
     pub node_signing_public_key: Option<PublicKeyProto>,
     pub committee_signing_public_key: Option<PublicKeyProto>,
     pub tls_certificate: Option<X509PublicKeyCert>,
@@ -261,7 +261,9 @@ struct LocalNodePublicKeys {
 }
 
 impl LocalNodePublicKeys {
-    fn from_public_key_store<P: PublicKeyStore>(pks_read_lock: RwLockReadGuard<'_, P>) -> Self {
+    pub(super) fn from_public_key_store<P: PublicKeyStore>(
+        pks_read_lock: RwLockReadGuard<'_, P>,
+    ) -> Self {
         LocalNodePublicKeys {
             node_signing_public_key: pks_read_lock.node_signing_pubkey(),
             committee_signing_public_key: pks_read_lock.committee_signing_pubkey(),
@@ -281,7 +283,7 @@ impl LocalNodePublicKeys {
 }
 
 #[derive(Debug)]
-struct RequiredNodePublicKeys {
+pub(super) struct RequiredNodePublicKeys {
     node_signing_public_key: PublicKeyProto,
     committee_signing_public_key: PublicKeyProto,
     tls_certificate: X509PublicKeyCert,
@@ -328,7 +330,7 @@ impl TryFrom<LocalNodePublicKeys> for RequiredNodePublicKeys {
 }
 
 impl RequiredNodePublicKeys {
-    fn compute_key_ids(&self) -> Result<RequiredKeyIds, ValidatePksAndSksError> {
+    pub(super) fn compute_key_ids(&self) -> Result<RequiredKeyIds, ValidatePksAndSksError> {
         let node_signing_key_id = compute_node_signing_key_id(&self.node_signing_public_key)
             .map_err(|error| {
                 ValidatePksAndSksError::NodeSigningKeyError(PublicKeyInvalid(error.0.to_string()))
@@ -370,7 +372,7 @@ impl RequiredNodePublicKeys {
         })
     }
 
-    fn validate(self) -> Result<ValidNodePublicKeys, ValidatePksAndSksError> {
+    pub(super) fn validate(self) -> Result<ValidNodePublicKeys, ValidatePksAndSksError> {
         let node_signing_public_key =
             ValidNodeSigningPublicKey::try_from(self.node_signing_public_key).map_err(|e| {
                 ValidatePksAndSksError::NodeSigningKeyError(PublicKeyInvalid(e.error))
@@ -413,7 +415,7 @@ impl RequiredNodePublicKeys {
 }
 
 #[derive(Debug)]
-struct RequiredKeyIds {
+pub(super) struct RequiredKeyIds {
     node_signing_key_id: KeyId,
     committee_signing_key_id: KeyId,
     tls_secret_key_id: KeyId,
@@ -422,6 +424,17 @@ struct RequiredKeyIds {
 }
 
 impl RequiredKeyIds {
+    pub(super) fn iter(&self) -> impl Iterator<Item = &KeyId> {
+        [
+            &self.node_signing_key_id,
+            &self.committee_signing_key_id,
+            &self.tls_secret_key_id,
+            &self.dkg_dealing_encryption_key_id,
+        ]
+        .into_iter()
+        .chain(self.idkg_dealing_encryption_key_ids.iter())
+    }
+
     fn verify_contained_in_sks<S: SecretKeyStore>(
         &self,
         sks_read_lock: RwLockReadGuard<'_, S>,
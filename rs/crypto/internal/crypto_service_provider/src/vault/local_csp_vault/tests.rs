@@ -5,6 +5,7 @@ use crate::secret_key_store::test_utils::{make_key_id, make_secret_key};
 use crate::secret_key_store::SecretKeyStore;
 use crate::vault::local_csp_vault::ProtoSecretKeyStore;
 use crate::LocalCspVault;
+use ic_config::crypto::CspRngSource;
 use ic_crypto_internal_csp_test_utils::files::mk_temp_dir_with_permissions;
 use ic_crypto_internal_logmon::metrics::CryptoMetrics;
 use ic_logger::replica_logger::no_op_logger;
@@ -31,6 +32,7 @@ mod csp_new {
             node_sks,
             canister_sks,
             node_pks,
+            &CspRngSource::default(),
             Arc::new(CryptoMetrics::none()),
             no_op_logger(),
         );
@@ -53,6 +55,7 @@ mod csp_new {
             node_sks,
             canister_sks,
             node_pks,
+            &CspRngSource::default(),
             Arc::new(CryptoMetrics::none()),
             no_op_logger(),
         );
@@ -75,6 +78,7 @@ mod csp_new {
             node_sks,
             canister_sks,
             node_pks,
+            &CspRngSource::default(),
             Arc::new(CryptoMetrics::none()),
             no_op_logger(),
         );
@@ -96,6 +100,7 @@ mod csp_new {
             node_sks,
             canister_sks,
             node_pks,
+            &CspRngSource::default(),
             Arc::new(CryptoMetrics::none()),
             no_op_logger(),
         );
@@ -0,0 +1,101 @@
+//! vetKD operations provided by the CSP vault
+use crate::key_id::KeyId;
+use crate::public_key_store::PublicKeyStore;
+use crate::secret_key_store::SecretKeyStore;
+use crate::types::CspSecretKey;
+use crate::vault::api::{CspVetKdEncryptedKeyShareCreationError, VetKdCspVault};
+use crate::vault::local_csp_vault::LocalCspVault;
+use ic_crypto_internal_bls12_381_type::{G2Affine, Scalar};
+use ic_crypto_internal_bls12_381_vetkd::{DerivationPath, EncryptedKeyShare, TransportPublicKey};
+use ic_crypto_internal_logmon::metrics::{MetricsDomain, MetricsResult, MetricsScope};
+use ic_types::crypto::canister_threshold_sig::ExtendedDerivationPath;
+use ic_types::crypto::vetkd::VetKdEncryptedKeyShare;
+use rand::{CryptoRng, Rng};
+
+#[cfg(test)]
+mod tests;
+
+impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore> VetKdCspVault
+    for LocalCspVault<R, S, C, P>
+{
+    fn create_encrypted_vetkd_key_share(
+        &self,
+        key_id: KeyId,
+        master_public_key: Vec<u8>,
+        encryption_public_key: Vec<u8>,
+        derivation_path: ExtendedDerivationPath,
+        derivation_id: Vec<u8>,
+    ) -> Result<VetKdEncryptedKeyShare, CspVetKdEncryptedKeyShareCreationError> {
+        let start_time = self.metrics.now();
+        let result = self.create_encrypted_vetkd_key_share_internal(
+            key_id,
+            master_public_key,
+            encryption_public_key,
+            derivation_path,
+            derivation_id,
+        );
+        self.metrics.observe_duration_seconds(
+            MetricsDomain::VetKd,
+            MetricsScope::Local,
+            "create_encrypted_vetkd_key_share",
+            MetricsResult::from(&result),
+            start_time,
+        );
+        result
+    }
+}
+
+impl<R: Rng + CryptoRng, S: SecretKeyStore, C: SecretKeyStore, P: PublicKeyStore>
+    LocalCspVault<R, S, C, P>
+{
+    fn create_encrypted_vetkd_key_share_internal(
+        &self,
+        key_id: KeyId,
+        master_public_key: Vec<u8>,
+        encryption_public_key: Vec<u8>,
+        derivation_path: ExtendedDerivationPath,
+        derivation_id: Vec<u8>,
+    ) -> Result<VetKdEncryptedKeyShare, CspVetKdEncryptedKeyShareCreationError> {
+        let master_public_key = G2Affine::deserialize(&master_public_key)
+            .map_err(|_| CspVetKdEncryptedKeyShareCreationError::InvalidArgumentMasterPublicKey)?;
+        let transport_public_key = TransportPublicKey::deserialize(&encryption_public_key)
+            .map_err(|_| {
+                CspVetKdEncryptedKeyShareCreationError::InvalidArgumentEncryptionPublicKey
+            })?;
+
+        let secret_key = match self.sks_read_lock().get(&key_id) {
+            Some(CspSecretKey::ThresBls12_381(secret_key_bytes)) => {
+                Scalar::try_from(&secret_key_bytes).map_err(|e| {
+                    CspVetKdEncryptedKeyShareCreationError::InternalError {
+                        internal_error: format!("Malformed secret key share: {:?}", e),
+                    }
+                })?
+            }
+            Some(_) => {
+                return Err(CspVetKdEncryptedKeyShareCreationError::WrongSecretKeyType { key_id })
+            }
+            None => {
+                return Err(CspVetKdEncryptedKeyShareCreationError::SecretKeyNotFound { key_id })
+            }
+        };
+
+        let derivation_path = DerivationPath::new(
+            derivation_path.caller.as_slice(),
+            &derivation_path.derivation_path,
+        );
+        // Only the randomization of the ciphertext comes from the RNG, so the
+        // RNG lock is only held to derive a dedicated RNG.
+        let mut rng = self.generate_seed().into_rng();
+        let encrypted_key_share = EncryptedKeyShare::create(
+            &mut rng,
+            &master_public_key,
+            &secret_key,
+            &transport_public_key,
+            &derivation_path,
+            &derivation_id,
+        );
+        Ok(VetKdEncryptedKeyShare {
+            encrypted_key_share: encrypted_key_share.serialize().to_vec(),
+        })
+    }
+}
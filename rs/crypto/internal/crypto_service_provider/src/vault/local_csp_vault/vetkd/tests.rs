@@ -0,0 +1,130 @@
+#![allow(clippy::unwrap_used)]
+use super::*;
+use crate::types::CspPublicCoefficients;
+use crate::vault::api::{BasicSignatureCspVault, ThresholdSignatureCspVault};
+use assert_matches::assert_matches;
+use ic_crypto_internal_bls12_381_vetkd::TransportSecretKey;
+use ic_crypto_internal_threshold_sig_bls12381::api::individual_public_key;
+use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
+use ic_types::crypto::AlgorithmId;
+use ic_types::{NodeIndex, NumberOfNodes, PrincipalId};
+
+const DERIVATION_ID: &[u8] = b"message";
+
+fn derivation_path() -> ExtendedDerivationPath {
+    ExtendedDerivationPath {
+        caller: PrincipalId::new_user_test_id(1),
+        derivation_path: vec![b"path".to_vec()],
+    }
+}
+
+fn transport_public_key() -> TransportPublicKey {
+    TransportSecretKey::generate(&mut reproducible_rng()).public_key()
+}
+
+#[test]
+fn should_create_valid_encrypted_key_share() {
+    let csp_vault = LocalCspVault::builder().build();
+    let (public_coefficients, key_ids) = csp_vault
+        .threshold_keygen_for_test(
+            AlgorithmId::ThresBls12_381,
+            NumberOfNodes::new(2),
+            NumberOfNodes::new(3),
+        )
+        .unwrap();
+    let CspPublicCoefficients::Bls12_381(public_coefficients) = public_coefficients;
+    let master_public_key = public_coefficients.coefficients[0].0.to_vec();
+    let tpk = transport_public_key();
+
+    for (node_index, key_id) in key_ids.into_iter().enumerate() {
+        let key_share = csp_vault
+            .create_encrypted_vetkd_key_share(
+                key_id,
+                master_public_key.clone(),
+                tpk.serialize().to_vec(),
+                derivation_path(),
+                DERIVATION_ID.to_vec(),
+            )
+            .unwrap();
+
+        let public_key_share = G2Affine::deserialize(
+            &individual_public_key(&public_coefficients, node_index as NodeIndex)
+                .unwrap()
+                .0,
+        )
+        .unwrap();
+        let key_share = EncryptedKeyShare::deserialize(&key_share.encrypted_key_share).unwrap();
+        assert!(key_share.is_valid(
+            &G2Affine::deserialize(&master_public_key).unwrap(),
+            &public_key_share,
+            &DerivationPath::new(
+                derivation_path().caller.as_slice(),
+                &derivation_path().derivation_path
+            ),
+            DERIVATION_ID,
+            &tpk,
+        ));
+    }
+}
+
+#[test]
+fn should_fail_to_create_key_share_without_secret_key() {
+    let csp_vault = LocalCspVault::builder().build();
+    let key_id = KeyId::from([42; 32]);
+
+    let result = csp_vault.create_encrypted_vetkd_key_share(
+        key_id,
+        G2Affine::generator().serialize().to_vec(),
+        transport_public_key().serialize().to_vec(),
+        derivation_path(),
+        DERIVATION_ID.to_vec(),
+    );
+
+    assert_eq!(
+        result,
+        Err(CspVetKdEncryptedKeyShareCreationError::SecretKeyNotFound { key_id })
+    );
+}
+
+#[test]
+fn should_fail_to_create_key_share_with_secret_key_of_wrong_type() {
+    let csp_vault = LocalCspVault::builder().build();
+    let public_key = csp_vault.gen_node_signing_key_pair().unwrap();
+    let key_id = KeyId::try_from(&public_key).unwrap();
+
+    let result = csp_vault.create_encrypted_vetkd_key_share(
+        key_id,
+        G2Affine::generator().serialize().to_vec(),
+        transport_public_key().serialize().to_vec(),
+        derivation_path(),
+        DERIVATION_ID.to_vec(),
+    );
+
+    assert_eq!(
+        result,
+        Err(CspVetKdEncryptedKeyShareCreationError::WrongSecretKeyType { key_id })
+    );
+}
+
+#[test]
+fn should_fail_to_create_key_share_with_invalid_public_keys() {
+    let csp_vault = LocalCspVault::builder().build();
+    let create_key_share = |master_public_key: Vec<u8>, encryption_public_key: Vec<u8>| {
+        csp_vault.create_encrypted_vetkd_key_share(
+            KeyId::from([42; 32]),
+            master_public_key,
+            encryption_public_key,
+            derivation_path(),
+            DERIVATION_ID.to_vec(),
+        )
+    };
+
+    assert_matches!(
+        create_key_share(vec![0xff; 96], transport_public_key().serialize().to_vec()),
+        Err(CspVetKdEncryptedKeyShareCreationError::InvalidArgumentMasterPublicKey)
+    );
+    assert_matches!(
+        create_key_share(G2Affine::generator().serialize().to_vec(), vec![0xff; 48]),
+        Err(CspVetKdEncryptedKeyShareCreationError::InvalidArgumentEncryptionPublicKey)
+    );
+}
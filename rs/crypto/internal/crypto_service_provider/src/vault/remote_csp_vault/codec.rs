@@ -135,6 +135,7 @@ impl DeserializationObserver<Response<TarpcCspVaultResponse>> for CspVaultClient
 
 enum CspVaultMethod {
     Sign,
+    SignBatch,
     GenNodeSigningKeyPair,
     MultiSign,
     GenCommitteeSigningKeyPair,
@@ -162,12 +163,17 @@ enum CspVaultMethod {
     IdkgOpenDealing,
     EcdsaSignShare,
     NewPublicSeed,
+    EscrowNodeSecretKeys,
+    ExportSealedBackup,
+    RestoreSealedBackup,
+    CreateEncryptedVetKdKeyShare,
 }
 
 impl CspVaultMethod {
     fn detail(&self) -> (MetricsDomain, &str) {
         match self {
             CspVaultMethod::Sign => (MetricsDomain::BasicSignature, "sign"),
+            CspVaultMethod::SignBatch => (MetricsDomain::BasicSignature, "sign_batch"),
             CspVaultMethod::GenNodeSigningKeyPair => {
                 (MetricsDomain::BasicSignature, "gen_node_signing_key_pair")
             }
@@ -236,6 +242,18 @@ impl CspVaultMethod {
             CspVaultMethod::IdkgOpenDealing => (MetricsDomain::IdkgProtocol, "idkg_open_dealing"),
             CspVaultMethod::EcdsaSignShare => (MetricsDomain::ThresholdEcdsa, "ecdsa_sign_share"),
             CspVaultMethod::NewPublicSeed => (MetricsDomain::PublicSeed, "new_public_seed"),
+            CspVaultMethod::EscrowNodeSecretKeys => {
+                (MetricsDomain::KeyManagement, "escrow_node_secret_keys")
+            }
+            CspVaultMethod::ExportSealedBackup => {
+                (MetricsDomain::KeyManagement, "export_sealed_backup")
+            }
+            CspVaultMethod::RestoreSealedBackup => {
+                (MetricsDomain::KeyManagement, "restore_sealed_backup")
+            }
+            CspVaultMethod::CreateEncryptedVetKdKeyShare => {
+                (MetricsDomain::VetKd, "create_encrypted_vetkd_key_share")
+            }
         }
     }
 }
@@ -246,6 +264,7 @@ impl From<&TarpcCspVaultRequest> for CspVaultMethod {
         type Method = CspVaultMethod;
         match request {
             Req::Sign { .. } => Method::Sign,
+            Req::SignBatch { .. } => Method::SignBatch,
             Req::GenNodeSigningKeyPair { .. } => Method::GenNodeSigningKeyPair,
             Req::MultiSign { .. } => Method::MultiSign,
             Req::GenCommitteeSigningKeyPair { .. } => Method::GenCommitteeSigningKeyPair,
@@ -275,6 +294,10 @@ impl From<&TarpcCspVaultRequest> for CspVaultMethod {
             Req::IdkgOpenDealing { .. } => Method::IdkgOpenDealing,
             Req::EcdsaSignShare { .. } => Method::EcdsaSignShare,
             Req::NewPublicSeed { .. } => Method::NewPublicSeed,
+            Req::EscrowNodeSecretKeys { .. } => Method::EscrowNodeSecretKeys,
+            Req::ExportSealedBackup { .. } => Method::ExportSealedBackup,
+            Req::RestoreSealedBackup { .. } => Method::RestoreSealedBackup,
+            Req::CreateEncryptedVetKdKeyShare { .. } => Method::CreateEncryptedVetKdKeyShare,
         }
     }
 }
@@ -285,6 +308,7 @@ impl From<&TarpcCspVaultResponse> for CspVaultMethod {
         type Method = CspVaultMethod;
         match response {
             Resp::Sign { .. } => Method::Sign,
+            Resp::SignBatch { .. } => Method::SignBatch,
             Resp::GenNodeSigningKeyPair { .. } => Method::GenNodeSigningKeyPair,
             Resp::MultiSign { .. } => Method::MultiSign,
             Resp::GenCommitteeSigningKeyPair { .. } => Method::GenCommitteeSigningKeyPair,
@@ -314,6 +338,10 @@ impl From<&TarpcCspVaultResponse> for CspVaultMethod {
             Resp::IdkgOpenDealing { .. } => Method::IdkgOpenDealing,
             Resp::EcdsaSignShare { .. } => Method::EcdsaSignShare,
             Resp::NewPublicSeed { .. } => Method::NewPublicSeed,
+            Resp::EscrowNodeSecretKeys { .. } => Method::EscrowNodeSecretKeys,
+            Resp::ExportSealedBackup { .. } => Method::ExportSealedBackup,
+            Resp::RestoreSealedBackup { .. } => Method::RestoreSealedBackup,
+            Resp::CreateEncryptedVetKdKeyShare { .. } => Method::CreateEncryptedVetKdKeyShare,
         }
     }
 }
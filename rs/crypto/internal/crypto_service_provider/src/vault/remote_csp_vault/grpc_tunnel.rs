@@ -0,0 +1,166 @@
+//! gRPC transport for connections to a remote CSP vault via TCP
+//!
+//! The vault protocol (i.e., the length-delimited tarpc requests and responses)
+//! is tunneled through the bidirectional stream of the `CspVaultTunnel.Open`
+//! RPC, which is served over the mutually authenticated TLS connection.
+use futures::{Stream, StreamExt};
+use ic_crypto_internal_csp_vault_service::csp_vault_tunnel_client::CspVaultTunnelClient;
+use ic_crypto_internal_csp_vault_service::csp_vault_tunnel_server::{
+    CspVaultTunnel, CspVaultTunnelServer,
+};
+use ic_crypto_internal_csp_vault_service::Frame;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream, ReadBuf};
+use tokio::net::TcpStream;
+use tokio_openssl::SslStream;
+use tonic::transport::server::Connected;
+use tonic::transport::{Endpoint, Server, Uri};
+use tonic::{Request, Response, Status, Streaming};
+use tower::service_fn;
+
+/// The size of the buffer between a tunnel and the connection to the vault,
+/// which is also the maximum size of the data of a frame.
+const TUNNEL_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Opens a tunnel to the vault server via `tls_stream`, which is connected to
+/// `address` (`host:port`), and returns the client end of the tunnel.
+pub(super) async fn open_tunnel(
+    address: &str,
+    tls_stream: SslStream<TcpStream>,
+) -> Result<DuplexStream, String> {
+    let mut tls_stream = Some(tls_stream);
+    let channel = Endpoint::from_shared(format!("http://{}", address))
+        .map_err(|e| format!("invalid vault address: {}", e))?
+        .connect_with_connector(service_fn(move |_: Uri| {
+            let tls_stream = tls_stream.take();
+            async move {
+                tls_stream.ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::NotConnected,
+                        "TLS connection to the vault already used",
+                    )
+                })
+            }
+        }))
+        .await
+        .map_err(|e| format!("error establishing gRPC channel: {}", e))?;
+    let mut client = CspVaultTunnelClient::new(channel);
+    let (client_end, tunnel_end) = tokio::io::duplex(TUNNEL_BUFFER_SIZE);
+    let (reader, writer) = tokio::io::split(tunnel_end);
+    let frames = client
+        .open(frames_read_from(reader))
+        .await
+        .map_err(|status| format!("error opening tunnel: {}", status))?
+        .into_inner();
+    tokio::spawn(async move {
+        // The client is kept until the tunnel is closed, since dropping it
+        // could close the connection to the vault.
+        let _client = client;
+        write_frames_to(frames, writer).await;
+    });
+    Ok(client_end)
+}
+
+/// Serves tunnels to the vault over `tls_stream` of a vault client, and passes
+/// the server end of each tunnel to `serve`.
+pub(super) async fn serve_tunnels<F>(
+    tls_stream: SslStream<TcpStream>,
+    serve: F,
+) -> Result<(), tonic::transport::Error>
+where
+    F: Fn(DuplexStream) + Send + Sync + 'static,
+{
+    Server::builder()
+        .add_service(CspVaultTunnelServer::new(TunnelService { serve }))
+        .serve_with_incoming(futures::stream::iter(vec![Ok::<_, std::io::Error>(
+            TlsConnection(tls_stream),
+        )]))
+        .await
+}
+
+struct TunnelService<F> {
+    serve: F,
+}
+
+#[tonic::async_trait]
+impl<F> CspVaultTunnel for TunnelService<F>
+where
+    F: Fn(DuplexStream) + Send + Sync + 'static,
+{
+    type OpenStream = Pin<Box<dyn Stream<Item = Result<Frame, Status>> + Send>>;
+
+    async fn open(
+        &self,
+        request: Request<Streaming<Frame>>,
+    ) -> Result<Response<Self::OpenStream>, Status> {
+        let (server_end, tunnel_end) = tokio::io::duplex(TUNNEL_BUFFER_SIZE);
+        (self.serve)(server_end);
+        let (reader, writer) = tokio::io::split(tunnel_end);
+        tokio::spawn(write_frames_to(request.into_inner(), writer));
+        Ok(Response::new(Box::pin(frames_read_from(reader).map(Ok))))
+    }
+}
+
+/// Streams the bytes read from `reader` as frames, until the end of `reader`
+/// or an error is reached.
+fn frames_read_from<R>(reader: R) -> impl Stream<Item = Frame> + Send + 'static
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    futures::stream::unfold(reader, |mut reader| async move {
+        let mut data = Vec::with_capacity(TUNNEL_BUFFER_SIZE);
+        match reader.read_buf(&mut data).await {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some((Frame { data }, reader)),
+        }
+    })
+}
+
+/// Writes the data of `frames` to `writer` and shuts `writer` down at the end
+/// of `frames` or on an error.
+async fn write_frames_to<W: AsyncWrite + Unpin>(mut frames: Streaming<Frame>, mut writer: W) {
+    while let Ok(Some(frame)) = frames.message().await {
+        if writer.write_all(&frame.data).await.is_err() {
+            return;
+        }
+    }
+    let _ = writer.shutdown().await;
+}
+
+/// A TLS connection of a vault client, which can be served by `tonic`.
+struct TlsConnection(SslStream<TcpStream>);
+
+impl Connected for TlsConnection {
+    type ConnectInfo = ();
+
+    fn connect_info(&self) -> Self::ConnectInfo {}
+}
+
+impl AsyncRead for TlsConnection {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for TlsConnection {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
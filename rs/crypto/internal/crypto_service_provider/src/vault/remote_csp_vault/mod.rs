@@ -1,11 +1,14 @@
 use crate::api::{CspCreateMEGaKeyError, CspThresholdSignError};
 use crate::types::{CspPop, CspPublicCoefficients, CspPublicKey, CspSignature};
 use crate::vault::api::{
-    CspBasicSignatureError, CspBasicSignatureKeygenError, CspMultiSignatureError,
-    CspMultiSignatureKeygenError, CspPublicKeyStoreError, CspSecretKeyStoreContainsError,
-    CspThresholdSignatureKeygenError, CspTlsKeygenError, CspTlsSignError, PksAndSksContainsErrors,
-    ValidatePksAndSksError,
+    CspBasicSignatureError, CspBasicSignatureKeygenError, CspEscrowNodeSecretKeysError,
+    CspMultiSignatureError, CspMultiSignatureKeygenError, CspPublicKeyStoreError,
+    CspSealedBackupError, CspSecretKeyStoreContainsError, CspThresholdSignatureKeygenError,
+    CspTlsKeygenError, CspTlsSignError, CspVetKdEncryptedKeyShareCreationError,
+    PksAndSksContainsErrors, ValidatePksAndSksError,
 };
+use ic_config::crypto::{CspRngSource, CspVaultTlsConfig, KeyEscrowConfig};
+use ic_crypto_internal_key_escrow::EscrowedSecret;
 use ic_crypto_internal_seed::Seed;
 use ic_crypto_internal_threshold_sig_bls12381::api::ni_dkg_errors;
 use ic_crypto_internal_threshold_sig_ecdsa::{
@@ -19,24 +22,28 @@ use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::{
     CspNiDkgDealing, CspNiDkgTranscript, Epoch,
 };
 use ic_crypto_tls_interfaces::TlsPublicKeyCert;
-use ic_logger::ReplicaLogger;
+use ic_logger::{new_logger, ReplicaLogger};
 use ic_types::crypto::canister_threshold_sig::error::{
     IDkgCreateDealingError, IDkgLoadTranscriptError, IDkgOpenTranscriptError, IDkgRetainKeysError,
     IDkgVerifyDealingPrivateError, ThresholdEcdsaSignShareError,
 };
 use ic_types::crypto::canister_threshold_sig::ExtendedDerivationPath;
+use ic_types::crypto::vetkd::VetKdEncryptedKeyShare;
 use ic_types::crypto::{AlgorithmId, CurrentNodePublicKeys};
 use ic_types::{NodeId, NodeIndex, NumberOfNodes, Randomness};
 use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
-use tokio::net::UnixListener;
+use tokio::net::{TcpListener, UnixListener};
 
 const FOUR_GIGA_BYTES: usize = 4 * 1024 * 1024 * 1024;
 mod codec;
+mod grpc_tunnel;
 mod tarpc_csp_vault_client;
 mod tarpc_csp_vault_server;
+mod tls;
 
 use crate::key_id::KeyId;
+use crate::vault::local_csp_vault::csprng::CspRngSourceError;
 pub use crate::vault::local_csp_vault::ProdLocalCspVault;
 use crate::ExternalPublicKeys;
 use ic_crypto_internal_logmon::metrics::CryptoMetrics;
@@ -44,6 +51,7 @@ use ic_crypto_node_key_validation::ValidNodePublicKeys;
 use std::sync::Arc;
 pub use tarpc_csp_vault_client::{RemoteCspVault, RemoteCspVaultBuilder};
 pub use tarpc_csp_vault_server::{TarpcCspVaultServerImpl, TarpcCspVaultServerImplBuilder};
+pub use tls::{tls_acceptor, tls_connector};
 use tokio_util::codec::length_delimited::Builder;
 use tokio_util::codec::LengthDelimitedCodec;
 
@@ -66,6 +74,13 @@ pub trait TarpcCspVault {
         key_id: KeyId,
     ) -> Result<CspSignature, CspBasicSignatureError>;
 
+    // Corresponds to `BasicSignatureCspVault.sign_batch()`.
+    async fn sign_batch(
+        algorithm_id: AlgorithmId,
+        messages: Vec<Vec<u8>>,
+        key_id: KeyId,
+    ) -> Result<Vec<CspSignature>, CspBasicSignatureError>;
+
     // Corresponds to `BasicSignatureCspVault.gen_node_signing_key_pair()`.
     async fn gen_node_signing_key_pair() -> Result<CspPublicKey, CspBasicSignatureKeygenError>;
 
@@ -235,19 +250,90 @@ pub trait TarpcCspVault {
     ) -> Result<ThresholdEcdsaSigShareInternal, ThresholdEcdsaSignShareError>;
 
     async fn new_public_seed() -> Result<Seed, PublicRandomSeedGeneratorError>;
+
+    // Corresponds to `KeyEscrowCspVault.escrow_node_secret_keys()`.
+    async fn escrow_node_secret_keys(
+        associated_data: Vec<u8>,
+    ) -> Result<EscrowedSecret, CspEscrowNodeSecretKeysError>;
+
+    // Corresponds to `KeyEscrowCspVault.export_sealed_backup()`.
+    async fn export_sealed_backup() -> Result<EscrowedSecret, CspSealedBackupError>;
+
+    // Corresponds to `KeyEscrowCspVault.restore_sealed_backup()`.
+    async fn restore_sealed_backup(
+        sealed_backup: EscrowedSecret,
+    ) -> Result<(), CspSealedBackupError>;
+
+    // Corresponds to `VetKdCspVault.create_encrypted_vetkd_key_share()`.
+    async fn create_encrypted_vetkd_key_share(
+        key_id: KeyId,
+        master_public_key: Vec<u8>,
+        encryption_public_key: Vec<u8>,
+        derivation_path: ExtendedDerivationPath,
+        derivation_id: Vec<u8>,
+    ) -> Result<VetKdEncryptedKeyShare, CspVetKdEncryptedKeyShareCreationError>;
 }
 
+/// Runs a vault server accepting clients via the Unix domain socket
+/// `listener`.
+///
+/// # Errors
+/// If the CSPRNG of the vault cannot be created from `rng_source`.
 pub async fn run_csp_vault_server(
     sks_dir: &Path,
+    rng_source: &CspRngSource,
+    key_escrow: Option<&KeyEscrowConfig>,
     listener: UnixListener,
     logger: ReplicaLogger,
     metrics: CryptoMetrics,
-) {
-    let server = TarpcCspVaultServerImpl::builder(sks_dir)
+) -> Result<(), CspRngSourceError> {
+    let local_csp_vault = prod_local_csp_vault(sks_dir, rng_source, key_escrow, &logger, metrics)?;
+    let server = TarpcCspVaultServerImplBuilder::new_with_local_csp_vault(local_csp_vault)
         .with_logger(logger)
-        .with_metrics(Arc::new(metrics))
         .build(listener);
-    server.run().await
+    server.run().await;
+    Ok(())
+}
+
+/// Runs a vault server accepting clients via TCP at `listener`, which must
+/// authenticate via TLS as configured by `tls_config`.
+///
+/// # Errors
+/// If the CSPRNG of the vault cannot be created from `rng_source`.
+///
+/// # Panics
+/// If the TLS configuration is invalid, e.g., because a certificate or the
+/// private key cannot be read.
+pub async fn run_csp_vault_server_over_tcp(
+    sks_dir: &Path,
+    rng_source: &CspRngSource,
+    key_escrow: Option<&KeyEscrowConfig>,
+    listener: TcpListener,
+    tls_config: &CspVaultTlsConfig,
+    logger: ReplicaLogger,
+    metrics: CryptoMetrics,
+) -> Result<(), CspRngSourceError> {
+    let tls_acceptor = tls_acceptor(tls_config)
+        .unwrap_or_else(|e| panic!("Error setting up TLS for the CspVault server: {}", e));
+    let local_csp_vault = prod_local_csp_vault(sks_dir, rng_source, key_escrow, &logger, metrics)?;
+    let server = TarpcCspVaultServerImplBuilder::new_with_local_csp_vault(local_csp_vault)
+        .with_logger(logger)
+        .build_for_tcp(listener, tls_acceptor);
+    server.run().await;
+    Ok(())
+}
+
+fn prod_local_csp_vault(
+    sks_dir: &Path,
+    rng_source: &CspRngSource,
+    key_escrow: Option<&KeyEscrowConfig>,
+    logger: &ReplicaLogger,
+    metrics: CryptoMetrics,
+) -> Result<Arc<ProdLocalCspVault>, CspRngSourceError> {
+    let local_csp_vault =
+        ProdLocalCspVault::new_in_dir(sks_dir, rng_source, Arc::new(metrics), new_logger!(logger))?
+            .with_key_escrow_config(key_escrow);
+    Ok(Arc::new(local_csp_vault))
 }
 
 pub fn remote_vault_codec_builder() -> Builder {
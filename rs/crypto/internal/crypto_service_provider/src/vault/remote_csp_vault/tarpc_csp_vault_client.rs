@@ -3,19 +3,24 @@ use crate::key_id::KeyId;
 use crate::types::{CspPop, CspPublicCoefficients, CspPublicKey, CspSignature};
 use crate::vault::api::{
     BasicSignatureCspVault, CspBasicSignatureError, CspBasicSignatureKeygenError,
-    CspMultiSignatureError, CspMultiSignatureKeygenError, CspPublicKeyStoreError,
-    CspSecretKeyStoreContainsError, CspThresholdSignatureKeygenError, CspTlsKeygenError,
-    CspTlsSignError, IDkgProtocolCspVault, MultiSignatureCspVault, NiDkgCspVault,
-    PksAndSksContainsErrors, PublicAndSecretKeyStoreCspVault, PublicKeyStoreCspVault,
-    PublicRandomSeedGenerator, PublicRandomSeedGeneratorError, SecretKeyStoreCspVault,
-    ThresholdEcdsaSignerCspVault, ThresholdSignatureCspVault, ValidatePksAndSksError,
+    CspEscrowNodeSecretKeysError, CspMultiSignatureError, CspMultiSignatureKeygenError,
+    CspPublicKeyStoreError, CspSealedBackupError, CspSecretKeyStoreContainsError,
+    CspThresholdSignatureKeygenError, CspTlsKeygenError, CspTlsSignError,
+    CspVetKdEncryptedKeyShareCreationError, IDkgProtocolCspVault, KeyEscrowCspVault,
+    MultiSignatureCspVault, NiDkgCspVault, PksAndSksContainsErrors,
+    PublicAndSecretKeyStoreCspVault, PublicKeyStoreCspVault, PublicRandomSeedGenerator,
+    PublicRandomSeedGeneratorError, SecretKeyStoreCspVault, ThresholdEcdsaSignerCspVault,
+    ThresholdSignatureCspVault, ValidatePksAndSksError, VetKdCspVault,
 };
 use crate::vault::remote_csp_vault::codec::{CspVaultClientObserver, ObservableCodec};
+use crate::vault::remote_csp_vault::grpc_tunnel::open_tunnel;
+use crate::vault::remote_csp_vault::tls::connect_tls;
 use crate::vault::remote_csp_vault::{
     remote_vault_codec_builder, TarpcCspVaultClient, FOUR_GIGA_BYTES,
 };
 use crate::{ExternalPublicKeys, TlsHandshakeCspVault};
 use core::future::Future;
+use ic_crypto_internal_key_escrow::EscrowedSecret;
 use ic_crypto_internal_seed::Seed;
 use ic_crypto_internal_threshold_sig_bls12381::api::dkg_errors::InternalError;
 use ic_crypto_internal_threshold_sig_bls12381::api::ni_dkg_errors::{
@@ -34,21 +39,27 @@ use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::{
 };
 use ic_crypto_internal_types::NodeIndex;
 use ic_crypto_tls_interfaces::TlsPublicKeyCert;
-use ic_logger::{debug, new_logger, ReplicaLogger};
+use ic_logger::{debug, info, new_logger, warn, ReplicaLogger};
 use ic_types::crypto::canister_threshold_sig::error::{
     IDkgCreateDealingError, IDkgLoadTranscriptError, IDkgOpenTranscriptError, IDkgRetainKeysError,
     IDkgVerifyDealingPrivateError, ThresholdEcdsaSignShareError,
 };
 use ic_types::crypto::canister_threshold_sig::ExtendedDerivationPath;
+use ic_types::crypto::vetkd::VetKdEncryptedKeyShare;
 use ic_types::crypto::{AlgorithmId, CurrentNodePublicKeys};
 use ic_types::{NodeId, NumberOfNodes, Randomness};
+use openssl::ssl::SslConnector;
+use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
+use tarpc::client::RpcError;
 use tarpc::serde_transport;
 use tarpc::tokio_serde::formats::Bincode;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::UnixStream;
 
 #[cfg(test)]
@@ -62,9 +73,25 @@ use ic_logger::replica_logger::no_op_logger;
 use slog_async::AsyncGuard;
 
 /// An implementation of `CspVault`-trait that talks to a remote CSP vault.
+///
+/// If an RPC fails because the client got disconnected from the vault, e.g.,
+/// because the vault process was restarted, the client reconnects to the vault
+/// (falling back to the secondary socket, if any), so that subsequent RPCs
+/// succeed again. The failed RPC itself is not retried, since it may have been
+/// executed by the vault already. While the vault cannot be reached, at most
+/// one reconnection attempt is made per backoff window, which doubles with
+/// every failed attempt; RPCs in between fail without waiting.
 #[allow(dead_code)]
 pub struct RemoteCspVault {
-    tarpc_csp_client: TarpcCspVaultClient,
+    tarpc_csp_client: RwLock<TarpcCspVaultClient>,
+    // the endpoints to (re)connect to, in order of preference.
+    endpoints: Vec<VaultEndpoint>,
+    max_frame_length: usize,
+    // whether the connection broke and could not be re-established so far.
+    connection_broken: AtomicBool,
+    // when to reconnect next; held while reconnecting, so that concurrent RPCs
+    // do not reconnect as well.
+    reconnect_backoff: Mutex<ReconnectBackoff>,
     // default timeout for RPC calls that can timeout.
     rpc_timeout: Duration,
     // special, long timeout for RPC calls that should not really timeout.
@@ -76,6 +103,27 @@ pub struct RemoteCspVault {
     _logger_guard: Option<AsyncGuard>,
 }
 
+/// An endpoint via which the vault server can be reached.
+enum VaultEndpoint {
+    UnixSocket(PathBuf),
+    /// The vault is reached via TCP at `address` (`host:port`), and is
+    /// authenticated via TLS as required by `tls_connector`. The vault
+    /// protocol is tunneled through gRPC (see `grpc_tunnel`).
+    Tcp {
+        address: String,
+        tls_connector: SslConnector,
+    },
+}
+
+impl VaultEndpoint {
+    fn address(&self) -> String {
+        match self {
+            VaultEndpoint::UnixSocket(socket_path) => socket_path.to_string_lossy().to_string(),
+            VaultEndpoint::Tcp { address, .. } => address.clone(),
+        }
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum RemoteCspVaultError {
@@ -86,13 +134,94 @@ pub enum RemoteCspVaultError {
 }
 
 impl RemoteCspVault {
-    fn tokio_block_on<T: Future>(&self, task: T) -> T::Output {
-        self.tokio_runtime_handle.block_on(task)
+    fn tokio_block_on<T, F: Future<Output = Result<T, RpcError>>>(
+        &self,
+        task: F,
+    ) -> Result<T, RpcError> {
+        let result = self.tokio_runtime_handle.block_on(task);
+        // Neither an exceeded deadline nor an error of the server indicates a
+        // broken connection.
+        if let Err(RpcError::Disconnected) = &result {
+            warn!(
+                self.logger,
+                "Disconnected from remote CSP vault, reconnecting"
+            );
+            self.connection_broken.store(true, Ordering::SeqCst);
+            self.reconnect_if_due();
+        }
+        result
+    }
+
+    /// Returns the client for the current connection to the vault, after
+    /// trying to reconnect if the connection broke and could not be
+    /// re-established so far.
+    fn tarpc_csp_client(&self) -> TarpcCspVaultClient {
+        if self.connection_broken.load(Ordering::SeqCst) {
+            self.reconnect_if_due();
+        }
+        self.tarpc_csp_client.read().clone()
+    }
+
+    /// Tries to reconnect to the vault once, unless another thread is already
+    /// reconnecting or the backoff window after the last failed attempt has
+    /// not passed yet.
+    fn reconnect_if_due(&self) {
+        let mut backoff = match self.reconnect_backoff.try_lock() {
+            Some(backoff) => backoff,
+            // another thread is already reconnecting
+            None => return,
+        };
+        let now = Instant::now();
+        if now < backoff.next_attempt {
+            return;
+        }
+        match connect(
+            &self.endpoints,
+            self.max_frame_length,
+            &self.tokio_runtime_handle,
+            &self.logger,
+            &self.metrics,
+        ) {
+            Ok(client) => {
+                *self.tarpc_csp_client.write() = client;
+                self.connection_broken.store(false, Ordering::SeqCst);
+                *backoff = ReconnectBackoff::new(now);
+                info!(self.logger, "Reconnected to remote CSP vault");
+            }
+            Err(e) => {
+                warn!(
+                    self.logger,
+                    "Failed to reconnect to remote CSP vault, next attempt in {:?}: {:?}",
+                    backoff.window,
+                    e
+                );
+                backoff.next_attempt = now + backoff.window;
+                backoff.window = std::cmp::min(backoff.window * 2, MAX_RECONNECT_BACKOFF);
+            }
+        }
+    }
+}
+
+/// When the client may next try to reconnect to the vault.
+struct ReconnectBackoff {
+    next_attempt: Instant,
+    // the time to wait after the next attempt, should it fail.
+    window: Duration,
+}
+
+impl ReconnectBackoff {
+    fn new(now: Instant) -> Self {
+        ReconnectBackoff {
+            next_attempt: now,
+            window: INITIAL_RECONNECT_BACKOFF,
+        }
     }
 }
 
 const DEFAULT_RPC_TIMEOUT: Duration = Duration::from_secs(300); // 5 minutes
 const LONG_RPC_TIMEOUT: Duration = Duration::from_secs(3600 * 24 * 100); // 100 days
+const INITIAL_RECONNECT_BACKOFF: Duration = Duration::from_millis(50);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(5);
 
 #[allow(dead_code)]
 impl RemoteCspVault {
@@ -118,10 +247,22 @@ impl RemoteCspVault {
     ) -> RemoteCspVaultBuilder {
         RemoteCspVaultBuilder::new(socket_path, rt_handle)
     }
+
+    /// Creates a builder for a `RemoteCspVault`-object that communicates
+    /// with a server via TCP at `address` (`host:port`), authenticating the
+    /// server (and itself) via TLS as configured in `tls_connector`.
+    pub fn builder_for_tcp(
+        address: String,
+        tls_connector: SslConnector,
+        rt_handle: tokio::runtime::Handle,
+    ) -> RemoteCspVaultBuilder {
+        RemoteCspVaultBuilder::new_for_tcp(address, tls_connector, rt_handle)
+    }
 }
 
 pub struct RemoteCspVaultBuilder {
-    socket_path: PathBuf,
+    endpoint: VaultEndpoint,
+    secondary_socket_path: Option<PathBuf>,
     rt_handle: tokio::runtime::Handle,
     max_frame_length: usize,
     rpc_timeout: Duration,
@@ -134,8 +275,27 @@ pub struct RemoteCspVaultBuilder {
 
 impl RemoteCspVaultBuilder {
     pub fn new(socket_path: PathBuf, rt_handle: tokio::runtime::Handle) -> Self {
+        Self::new_for_endpoint(VaultEndpoint::UnixSocket(socket_path), rt_handle)
+    }
+
+    pub fn new_for_tcp(
+        address: String,
+        tls_connector: SslConnector,
+        rt_handle: tokio::runtime::Handle,
+    ) -> Self {
+        Self::new_for_endpoint(
+            VaultEndpoint::Tcp {
+                address,
+                tls_connector,
+            },
+            rt_handle,
+        )
+    }
+
+    fn new_for_endpoint(endpoint: VaultEndpoint, rt_handle: tokio::runtime::Handle) -> Self {
         RemoteCspVaultBuilder {
-            socket_path,
+            endpoint,
+            secondary_socket_path: None,
             rt_handle,
             max_frame_length: FOUR_GIGA_BYTES,
             rpc_timeout: DEFAULT_RPC_TIMEOUT,
@@ -156,6 +316,13 @@ impl RemoteCspVaultBuilder {
         builder
     }
 
+    /// Sets the path of a socket to connect to if the vault cannot be reached
+    /// via the primary socket.
+    pub fn with_secondary_socket_path(mut self, socket_path: PathBuf) -> Self {
+        self.secondary_socket_path = Some(socket_path);
+        self
+    }
+
     pub fn with_rpc_timeout(mut self, timeout: Duration) -> Self {
         self.rpc_timeout = timeout;
         self
@@ -182,29 +349,23 @@ impl RemoteCspVaultBuilder {
     }
 
     pub fn build(self) -> Result<RemoteCspVault, RemoteCspVaultError> {
-        let conn = self
-            .rt_handle
-            .block_on(UnixStream::connect(&self.socket_path))
-            .map_err(|e| RemoteCspVaultError::TransportError {
-                server_address: self.socket_path.to_string_lossy().to_string(),
-                message: e.to_string(),
-            })?;
-        let transport = serde_transport::new(
-            remote_vault_codec_builder()
-                .max_frame_length(self.max_frame_length)
-                .new_framed(conn),
-            ObservableCodec::new(
-                Bincode::default(),
-                CspVaultClientObserver::new(new_logger!(&self.logger), self.metrics.clone()),
-            ),
-        );
-        let client = {
-            let _enter_guard = self.rt_handle.enter();
-            TarpcCspVaultClient::new(Default::default(), transport).spawn()
-        };
+        let endpoints: Vec<VaultEndpoint> = std::iter::once(self.endpoint)
+            .chain(self.secondary_socket_path.map(VaultEndpoint::UnixSocket))
+            .collect();
+        let client = connect(
+            &endpoints,
+            self.max_frame_length,
+            &self.rt_handle,
+            &self.logger,
+            &self.metrics,
+        )?;
         debug!(self.logger, "Instantiated remote CSP vault client");
         Ok(RemoteCspVault {
-            tarpc_csp_client: client,
+            tarpc_csp_client: RwLock::new(client),
+            endpoints,
+            max_frame_length: self.max_frame_length,
+            connection_broken: AtomicBool::new(false),
+            reconnect_backoff: Mutex::new(ReconnectBackoff::new(Instant::now())),
             rpc_timeout: self.rpc_timeout,
             long_rpc_timeout: self.long_rpc_timeout,
             tokio_runtime_handle: self.rt_handle,
@@ -220,6 +381,68 @@ impl RemoteCspVaultBuilder {
     }
 }
 
+/// Connects to the vault via the first of the (non-empty) `endpoints` that
+/// can be connected to, or returns the error for the last one.
+fn connect(
+    endpoints: &[VaultEndpoint],
+    max_frame_length: usize,
+    rt_handle: &tokio::runtime::Handle,
+    logger: &ReplicaLogger,
+    metrics: &Arc<CryptoMetrics>,
+) -> Result<TarpcCspVaultClient, RemoteCspVaultError> {
+    let mut last_error = None;
+    for endpoint in endpoints {
+        let result = match endpoint {
+            VaultEndpoint::UnixSocket(socket_path) => rt_handle
+                .block_on(UnixStream::connect(socket_path))
+                .map(|conn| spawn_client(conn, max_frame_length, rt_handle, logger, metrics))
+                .map_err(|e| e.to_string()),
+            VaultEndpoint::Tcp {
+                address,
+                tls_connector,
+            } => rt_handle
+                .block_on(async {
+                    let tls_stream = connect_tls(tls_connector, address).await?;
+                    open_tunnel(address, tls_stream).await
+                })
+                .map(|conn| spawn_client(conn, max_frame_length, rt_handle, logger, metrics)),
+        };
+        match result {
+            Ok(client) => return Ok(client),
+            Err(message) => {
+                last_error = Some(RemoteCspVaultError::TransportError {
+                    server_address: endpoint.address(),
+                    message,
+                })
+            }
+        }
+    }
+    Err(last_error.expect("no endpoint to connect to"))
+}
+
+fn spawn_client<IO>(
+    conn: IO,
+    max_frame_length: usize,
+    rt_handle: &tokio::runtime::Handle,
+    logger: &ReplicaLogger,
+    metrics: &Arc<CryptoMetrics>,
+) -> TarpcCspVaultClient
+where
+    IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let transport = serde_transport::new(
+        remote_vault_codec_builder()
+            .max_frame_length(max_frame_length)
+            .new_framed(conn),
+        ObservableCodec::new(
+            Bincode::default(),
+            CspVaultClientObserver::new(new_logger!(logger), metrics.clone()),
+        ),
+    );
+    let _enter_guard = rt_handle.enter();
+    TarpcCspVaultClient::new(Default::default(), transport).spawn()
+}
+
 fn deadline_from_now(timeout: Duration) -> SystemTime {
     SystemTime::now() + timeout
 }
@@ -240,7 +463,7 @@ impl BasicSignatureCspVault for RemoteCspVault {
         message: &[u8],
         key_id: KeyId,
     ) -> Result<CspSignature, CspBasicSignatureError> {
-        self.tokio_block_on(self.tarpc_csp_client.sign(
+        self.tokio_block_on(self.tarpc_csp_client().sign(
             context_with_timeout(self.rpc_timeout),
             algorithm_id,
             message.to_vec(),
@@ -253,9 +476,28 @@ impl BasicSignatureCspVault for RemoteCspVault {
         })
     }
 
+    fn sign_batch(
+        &self,
+        algorithm_id: AlgorithmId,
+        messages: &[Vec<u8>],
+        key_id: KeyId,
+    ) -> Result<Vec<CspSignature>, CspBasicSignatureError> {
+        self.tokio_block_on(self.tarpc_csp_client().sign_batch(
+            context_with_timeout(self.rpc_timeout),
+            algorithm_id,
+            messages.to_vec(),
+            key_id,
+        ))
+        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
+            Err(CspBasicSignatureError::InternalError {
+                internal_error: rpc_error.to_string(),
+            })
+        })
+    }
+
     fn gen_node_signing_key_pair(&self) -> Result<CspPublicKey, CspBasicSignatureKeygenError> {
         self.tokio_block_on(
-            self.tarpc_csp_client
+            self.tarpc_csp_client()
                 .gen_node_signing_key_pair(context_with_timeout(self.rpc_timeout)),
         )
         .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
@@ -273,7 +515,7 @@ impl MultiSignatureCspVault for RemoteCspVault {
         message: &[u8],
         key_id: KeyId,
     ) -> Result<CspSignature, CspMultiSignatureError> {
-        self.tokio_block_on(self.tarpc_csp_client.multi_sign(
+        self.tokio_block_on(self.tarpc_csp_client().multi_sign(
             context_with_timeout(self.rpc_timeout),
             algorithm_id,
             message.to_vec(),
@@ -290,7 +532,7 @@ impl MultiSignatureCspVault for RemoteCspVault {
         &self,
     ) -> Result<(CspPublicKey, CspPop), CspMultiSignatureKeygenError> {
         self.tokio_block_on(
-            self.tarpc_csp_client
+            self.tarpc_csp_client()
                 .gen_committee_signing_key_pair(context_with_timeout(self.rpc_timeout)),
         )
         .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
@@ -308,7 +550,7 @@ impl ThresholdSignatureCspVault for RemoteCspVault {
         threshold: NumberOfNodes,
         receivers: NumberOfNodes,
     ) -> Result<(CspPublicCoefficients, Vec<KeyId>), CspThresholdSignatureKeygenError> {
-        self.tokio_block_on(self.tarpc_csp_client.threshold_keygen_for_test(
+        self.tokio_block_on(self.tarpc_csp_client().threshold_keygen_for_test(
             context_with_timeout(self.rpc_timeout),
             algorithm_id,
             threshold,
@@ -327,7 +569,7 @@ impl ThresholdSignatureCspVault for RemoteCspVault {
         message: &[u8],
         key_id: KeyId,
     ) -> Result<CspSignature, CspThresholdSignError> {
-        self.tokio_block_on(self.tarpc_csp_client.threshold_sign(
+        self.tokio_block_on(self.tarpc_csp_client().threshold_sign(
             context_with_timeout(self.rpc_timeout),
             algorithm_id,
             message.to_vec(),
@@ -344,7 +586,7 @@ impl ThresholdSignatureCspVault for RemoteCspVault {
 impl SecretKeyStoreCspVault for RemoteCspVault {
     fn sks_contains(&self, key_id: &KeyId) -> Result<bool, CspSecretKeyStoreContainsError> {
         self.tokio_block_on(
-            self.tarpc_csp_client
+            self.tarpc_csp_client()
                 .sks_contains(context_with_timeout(self.rpc_timeout), *key_id),
         )
         .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
@@ -358,7 +600,7 @@ impl SecretKeyStoreCspVault for RemoteCspVault {
 impl PublicKeyStoreCspVault for RemoteCspVault {
     fn current_node_public_keys(&self) -> Result<CurrentNodePublicKeys, CspPublicKeyStoreError> {
         self.tokio_block_on(
-            self.tarpc_csp_client
+            self.tarpc_csp_client()
                 .current_node_public_keys(context_with_timeout(self.rpc_timeout)),
         )
         .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
@@ -372,7 +614,7 @@ impl PublicKeyStoreCspVault for RemoteCspVault {
         &self,
     ) -> Result<CurrentNodePublicKeys, CspPublicKeyStoreError> {
         self.tokio_block_on(
-            self.tarpc_csp_client
+            self.tarpc_csp_client()
                 .current_node_public_keys_with_timestamps(context_with_timeout(self.rpc_timeout)),
         )
         .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
@@ -384,7 +626,7 @@ impl PublicKeyStoreCspVault for RemoteCspVault {
 
     fn idkg_dealing_encryption_pubkeys_count(&self) -> Result<usize, CspPublicKeyStoreError> {
         self.tokio_block_on(
-            self.tarpc_csp_client
+            self.tarpc_csp_client()
                 .idkg_key_count(context_with_timeout(self.rpc_timeout)),
         )
         .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
@@ -401,7 +643,7 @@ impl PublicAndSecretKeyStoreCspVault for RemoteCspVault {
         external_public_keys: ExternalPublicKeys,
     ) -> Result<(), PksAndSksContainsErrors> {
         self.tokio_block_on(
-            self.tarpc_csp_client
+            self.tarpc_csp_client()
                 .pks_and_sks_contains(context_with_timeout(self.rpc_timeout), external_public_keys),
         )
         .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
@@ -413,7 +655,7 @@ impl PublicAndSecretKeyStoreCspVault for RemoteCspVault {
 
     fn validate_pks_and_sks(&self) -> Result<ValidNodePublicKeys, ValidatePksAndSksError> {
         self.tokio_block_on(
-            self.tarpc_csp_client
+            self.tarpc_csp_client()
                 .validate_pks_and_sks(context_with_timeout(self.rpc_timeout)),
         )
         .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
@@ -430,7 +672,7 @@ impl NiDkgCspVault for RemoteCspVault {
         node_id: NodeId,
     ) -> Result<(CspFsEncryptionPublicKey, CspFsEncryptionPop), CspDkgCreateFsKeyError> {
         self.tokio_block_on(
-            self.tarpc_csp_client
+            self.tarpc_csp_client()
                 .gen_dealing_encryption_key_pair(context_with_timeout(self.rpc_timeout), node_id),
         )
         .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
@@ -446,7 +688,7 @@ impl NiDkgCspVault for RemoteCspVault {
         key_id: KeyId,
         epoch: Epoch,
     ) -> Result<(), CspDkgUpdateFsEpochError> {
-        self.tokio_block_on(self.tarpc_csp_client.update_forward_secure_epoch(
+        self.tokio_block_on(self.tarpc_csp_client().update_forward_secure_epoch(
             context_with_timeout(self.rpc_timeout),
             algorithm_id,
             key_id,
@@ -470,7 +712,7 @@ impl NiDkgCspVault for RemoteCspVault {
         receiver_keys: &BTreeMap<NodeIndex, CspFsEncryptionPublicKey>,
         maybe_resharing_secret: Option<KeyId>,
     ) -> Result<CspNiDkgDealing, CspDkgCreateReshareDealingError> {
-        self.tokio_block_on(self.tarpc_csp_client.create_dealing(
+        self.tokio_block_on(self.tarpc_csp_client().create_dealing(
             context_with_timeout(self.rpc_timeout),
             algorithm_id,
             dealer_index,
@@ -496,7 +738,7 @@ impl NiDkgCspVault for RemoteCspVault {
         fs_key_id: KeyId,
         receiver_index: NodeIndex,
     ) -> Result<(), CspDkgLoadPrivateKeyError> {
-        self.tokio_block_on(self.tarpc_csp_client.load_threshold_signing_key(
+        self.tokio_block_on(self.tarpc_csp_client().load_threshold_signing_key(
             context_with_timeout(self.long_rpc_timeout),
             algorithm_id,
             epoch,
@@ -517,7 +759,7 @@ impl NiDkgCspVault for RemoteCspVault {
         &self,
         active_key_ids: BTreeSet<KeyId>,
     ) -> Result<(), CspDkgRetainThresholdKeysError> {
-        self.tokio_block_on(self.tarpc_csp_client.retain_threshold_keys_if_present(
+        self.tokio_block_on(self.tarpc_csp_client().retain_threshold_keys_if_present(
             context_with_timeout(self.rpc_timeout),
             active_key_ids,
         ))
@@ -537,7 +779,7 @@ impl TlsHandshakeCspVault for RemoteCspVault {
         node: NodeId,
         not_after: &str,
     ) -> Result<TlsPublicKeyCert, CspTlsKeygenError> {
-        self.tokio_block_on(self.tarpc_csp_client.gen_tls_key_pair(
+        self.tokio_block_on(self.tarpc_csp_client().gen_tls_key_pair(
             context_with_timeout(self.rpc_timeout),
             node,
             not_after.to_string(),
@@ -558,7 +800,7 @@ impl TlsHandshakeCspVault for RemoteCspVault {
         // `TlsHandshake::perform_tls_server_handshake`.
         #[allow(clippy::disallowed_methods)]
         tokio::task::block_in_place(|| {
-            self.tokio_block_on(self.tarpc_csp_client.tls_sign(
+            self.tokio_block_on(self.tarpc_csp_client().tls_sign(
                 context_with_timeout(self.rpc_timeout),
                 message.to_vec(),
                 *key_id,
@@ -582,7 +824,7 @@ impl IDkgProtocolCspVault for RemoteCspVault {
         receiver_keys: &[MEGaPublicKey],
         transcript_operation: &IDkgTranscriptOperationInternal,
     ) -> Result<IDkgDealingInternal, IDkgCreateDealingError> {
-        self.tokio_block_on(self.tarpc_csp_client.idkg_create_dealing(
+        self.tokio_block_on(self.tarpc_csp_client().idkg_create_dealing(
             context_with_timeout(self.rpc_timeout),
             algorithm_id,
             context_data.to_vec(),
@@ -607,7 +849,7 @@ impl IDkgProtocolCspVault for RemoteCspVault {
         receiver_key_id: KeyId,
         context_data: &[u8],
     ) -> Result<(), IDkgVerifyDealingPrivateError> {
-        self.tokio_block_on(self.tarpc_csp_client.idkg_verify_dealing_private(
+        self.tokio_block_on(self.tarpc_csp_client().idkg_verify_dealing_private(
             context_with_timeout(self.rpc_timeout),
             algorithm_id,
             dealing.clone(),
@@ -631,7 +873,7 @@ impl IDkgProtocolCspVault for RemoteCspVault {
         key_id: &KeyId,
         transcript: &IDkgTranscriptInternal,
     ) -> Result<BTreeMap<NodeIndex, IDkgComplaintInternal>, IDkgLoadTranscriptError> {
-        self.tokio_block_on(self.tarpc_csp_client.idkg_load_transcript(
+        self.tokio_block_on(self.tarpc_csp_client().idkg_load_transcript(
             context_with_timeout(self.rpc_timeout),
             dealings.clone(),
             context_data.to_vec(),
@@ -655,7 +897,7 @@ impl IDkgProtocolCspVault for RemoteCspVault {
         key_id: &KeyId,
         transcript: &IDkgTranscriptInternal,
     ) -> Result<(), IDkgLoadTranscriptError> {
-        self.tokio_block_on(self.tarpc_csp_client.idkg_load_transcript_with_openings(
+        self.tokio_block_on(self.tarpc_csp_client().idkg_load_transcript_with_openings(
             context_with_timeout(self.rpc_timeout),
             dealings.clone(),
             openings.clone(),
@@ -676,7 +918,7 @@ impl IDkgProtocolCspVault for RemoteCspVault {
         active_key_ids: BTreeSet<KeyId>,
         oldest_public_key: MEGaPublicKey,
     ) -> Result<(), IDkgRetainKeysError> {
-        self.tokio_block_on(self.tarpc_csp_client.idkg_retain_active_keys(
+        self.tokio_block_on(self.tarpc_csp_client().idkg_retain_active_keys(
             context_with_timeout(self.rpc_timeout),
             active_key_ids,
             oldest_public_key,
@@ -690,7 +932,7 @@ impl IDkgProtocolCspVault for RemoteCspVault {
 
     fn idkg_gen_dealing_encryption_key_pair(&self) -> Result<MEGaPublicKey, CspCreateMEGaKeyError> {
         self.tokio_block_on(
-            self.tarpc_csp_client
+            self.tarpc_csp_client()
                 .idkg_gen_dealing_encryption_key_pair(context_with_timeout(self.rpc_timeout)),
         )
         .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
@@ -708,7 +950,7 @@ impl IDkgProtocolCspVault for RemoteCspVault {
         opener_index: NodeIndex,
         opener_key_id: &KeyId,
     ) -> Result<CommitmentOpening, IDkgOpenTranscriptError> {
-        self.tokio_block_on(self.tarpc_csp_client.idkg_open_dealing(
+        self.tokio_block_on(self.tarpc_csp_client().idkg_open_dealing(
             context_with_timeout(self.rpc_timeout),
             dealing,
             dealer_index,
@@ -737,7 +979,7 @@ impl ThresholdEcdsaSignerCspVault for RemoteCspVault {
         key_times_lambda: &IDkgTranscriptInternal,
         algorithm_id: AlgorithmId,
     ) -> Result<ThresholdEcdsaSigShareInternal, ThresholdEcdsaSignShareError> {
-        self.tokio_block_on(self.tarpc_csp_client.ecdsa_sign_share(
+        self.tokio_block_on(self.tarpc_csp_client().ecdsa_sign_share(
             context_with_timeout(self.rpc_timeout),
             derivation_path.clone(),
             hashed_message.to_vec(),
@@ -760,7 +1002,7 @@ impl ThresholdEcdsaSignerCspVault for RemoteCspVault {
 impl PublicRandomSeedGenerator for RemoteCspVault {
     fn new_public_seed(&self) -> Result<Seed, PublicRandomSeedGeneratorError> {
         self.tokio_block_on(
-            self.tarpc_csp_client
+            self.tarpc_csp_client()
                 .new_public_seed(context_with_timeout(self.rpc_timeout)),
         )
         .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
@@ -770,3 +1012,74 @@ impl PublicRandomSeedGenerator for RemoteCspVault {
         })
     }
 }
+
+impl KeyEscrowCspVault for RemoteCspVault {
+    fn escrow_node_secret_keys(
+        &self,
+        associated_data: Vec<u8>,
+    ) -> Result<EscrowedSecret, CspEscrowNodeSecretKeysError> {
+        self.tokio_block_on(
+            self.tarpc_csp_client()
+                .escrow_node_secret_keys(context_with_timeout(self.rpc_timeout), associated_data),
+        )
+        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
+            Err(CspEscrowNodeSecretKeysError::TransientInternalError {
+                internal_error: rpc_error.to_string(),
+            })
+        })
+    }
+
+    fn export_sealed_backup(&self) -> Result<EscrowedSecret, CspSealedBackupError> {
+        self.tokio_block_on(
+            self.tarpc_csp_client()
+                .export_sealed_backup(context_with_timeout(self.rpc_timeout)),
+        )
+        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
+            Err(CspSealedBackupError::TransientInternalError {
+                internal_error: rpc_error.to_string(),
+            })
+        })
+    }
+
+    fn restore_sealed_backup(
+        &self,
+        sealed_backup: EscrowedSecret,
+    ) -> Result<(), CspSealedBackupError> {
+        self.tokio_block_on(
+            self.tarpc_csp_client()
+                .restore_sealed_backup(context_with_timeout(self.rpc_timeout), sealed_backup),
+        )
+        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
+            Err(CspSealedBackupError::TransientInternalError {
+                internal_error: rpc_error.to_string(),
+            })
+        })
+    }
+}
+
+impl VetKdCspVault for RemoteCspVault {
+    fn create_encrypted_vetkd_key_share(
+        &self,
+        key_id: KeyId,
+        master_public_key: Vec<u8>,
+        encryption_public_key: Vec<u8>,
+        derivation_path: ExtendedDerivationPath,
+        derivation_id: Vec<u8>,
+    ) -> Result<VetKdEncryptedKeyShare, CspVetKdEncryptedKeyShareCreationError> {
+        self.tokio_block_on(self.tarpc_csp_client().create_encrypted_vetkd_key_share(
+            context_with_timeout(self.rpc_timeout),
+            key_id,
+            master_public_key,
+            encryption_public_key,
+            derivation_path,
+            derivation_id,
+        ))
+        .unwrap_or_else(|rpc_error: tarpc::client::RpcError| {
+            Err(
+                CspVetKdEncryptedKeyShareCreationError::TransientInternalError {
+                    internal_error: rpc_error.to_string(),
+                },
+            )
+        })
+    }
+}
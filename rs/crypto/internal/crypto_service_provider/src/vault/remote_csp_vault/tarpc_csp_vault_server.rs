@@ -2,15 +2,21 @@ use crate::api::{CspCreateMEGaKeyError, CspThresholdSignError};
 use crate::key_id::KeyId;
 use crate::types::{CspPop, CspPublicCoefficients, CspPublicKey, CspSignature};
 use crate::vault::api::{
-    CspBasicSignatureError, CspBasicSignatureKeygenError, CspMultiSignatureError,
-    CspMultiSignatureKeygenError, CspSecretKeyStoreContainsError, CspThresholdSignatureKeygenError,
-    CspTlsKeygenError, CspTlsSignError, PublicRandomSeedGeneratorError, ValidatePksAndSksError,
+    CspBasicSignatureError, CspBasicSignatureKeygenError, CspEscrowNodeSecretKeysError,
+    CspMultiSignatureError, CspMultiSignatureKeygenError, CspSealedBackupError,
+    CspSecretKeyStoreContainsError, CspThresholdSignatureKeygenError, CspTlsKeygenError,
+    CspTlsSignError, CspVetKdEncryptedKeyShareCreationError, PublicRandomSeedGeneratorError,
+    ValidatePksAndSksError,
 };
 use crate::vault::api::{CspPublicKeyStoreError, CspVault};
 use crate::vault::local_csp_vault::{LocalCspVault, ProdLocalCspVault};
+use crate::vault::remote_csp_vault::grpc_tunnel::serve_tunnels;
+use crate::vault::remote_csp_vault::tls::accept_tls;
 use crate::vault::remote_csp_vault::{remote_vault_codec_builder, TarpcCspVault};
 use crate::vault::remote_csp_vault::{PksAndSksContainsErrors, FOUR_GIGA_BYTES};
 use crate::ExternalPublicKeys;
+use ic_config::crypto::CspRngSource;
+use ic_crypto_internal_key_escrow::EscrowedSecret;
 use ic_crypto_internal_logmon::metrics::CryptoMetrics;
 use ic_crypto_internal_seed::Seed;
 use ic_crypto_internal_threshold_sig_bls12381::api::ni_dkg_errors::{
@@ -31,35 +37,54 @@ use ic_crypto_internal_types::NodeIndex;
 use ic_crypto_node_key_validation::ValidNodePublicKeys;
 use ic_crypto_tls_interfaces::TlsPublicKeyCert;
 use ic_logger::replica_logger::no_op_logger;
-use ic_logger::{new_logger, ReplicaLogger};
+use ic_logger::{new_logger, warn, ReplicaLogger};
 use ic_types::crypto::canister_threshold_sig::error::{
     IDkgCreateDealingError, IDkgLoadTranscriptError, IDkgOpenTranscriptError, IDkgRetainKeysError,
     IDkgVerifyDealingPrivateError, ThresholdEcdsaSignShareError,
 };
 use ic_types::crypto::canister_threshold_sig::ExtendedDerivationPath;
+use ic_types::crypto::vetkd::VetKdEncryptedKeyShare;
 use ic_types::crypto::{AlgorithmId, CurrentNodePublicKeys};
 use ic_types::{NodeId, NumberOfNodes, Randomness};
+use openssl::ssl::SslAcceptor;
+use parking_lot::Mutex;
 use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Debug;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use tarpc::server::BaseChannel;
 #[allow(unused_imports)]
 use tarpc::server::Serve;
 use tarpc::tokio_serde::formats::Bincode;
 use tarpc::{context, serde_transport, server::Channel};
 use threadpool::ThreadPool;
-use tokio::net::UnixListener;
+use tokio::io::{AsyncRead, AsyncWrite, DuplexStream};
+use tokio::net::{TcpListener, UnixListener};
+use tokio_util::codec::length_delimited::Builder;
+
+/// How long the server waits before accepting clients again after an error.
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
 
 /// Crypto service provider (CSP) vault server based on the tarpc RPC framework.
 pub struct TarpcCspVaultServerImpl<C: CspVault> {
     local_csp_vault: Arc<C>,
-    listener: UnixListener,
+    listener: Listener,
     thread_pool: ThreadPool,
     max_frame_length: usize,
-    #[allow(unused)]
     logger: ReplicaLogger,
 }
 
+enum Listener {
+    Unix(UnixListener),
+    /// Clients connect via TCP and must authenticate via TLS, and the vault
+    /// protocol is tunneled through gRPC (see `grpc_tunnel`).
+    Tcp {
+        listener: TcpListener,
+        tls_acceptor: SslAcceptor,
+    },
+}
+
 /// A worker of the tarpc CSP vault server responsible for a single service request.
 ///
 /// For each service request (i.e., remote procedure call), a new worker is
@@ -119,6 +144,18 @@ impl<C: CspVault + 'static> TarpcCspVault for TarpcCspVaultServerWorker<C> {
         execute_on_thread_pool(self.thread_pool_handle, job).await
     }
 
+    async fn sign_batch(
+        self,
+        _: context::Context,
+        algorithm_id: AlgorithmId,
+        messages: Vec<Vec<u8>>,
+        key_id: KeyId,
+    ) -> Result<Vec<CspSignature>, CspBasicSignatureError> {
+        let vault = self.local_csp_vault;
+        let job = move || vault.sign_batch(algorithm_id, &messages, key_id);
+        execute_on_thread_pool(self.thread_pool_handle, job).await
+    }
+
     async fn gen_node_signing_key_pair(
         self,
         _: context::Context,
@@ -509,6 +546,59 @@ impl<C: CspVault + 'static> TarpcCspVault for TarpcCspVaultServerWorker<C> {
         let job = move || vault.new_public_seed();
         execute_on_thread_pool(self.thread_pool_handle, job).await
     }
+
+    // `KeyEscrowCspVault`-methods.
+    async fn escrow_node_secret_keys(
+        self,
+        _: context::Context,
+        associated_data: Vec<u8>,
+    ) -> Result<EscrowedSecret, CspEscrowNodeSecretKeysError> {
+        let vault = self.local_csp_vault;
+        let job = move || vault.escrow_node_secret_keys(associated_data);
+        execute_on_thread_pool(self.thread_pool_handle, job).await
+    }
+
+    async fn export_sealed_backup(
+        self,
+        _: context::Context,
+    ) -> Result<EscrowedSecret, CspSealedBackupError> {
+        let vault = self.local_csp_vault;
+        let job = move || vault.export_sealed_backup();
+        execute_on_thread_pool(self.thread_pool_handle, job).await
+    }
+
+    async fn restore_sealed_backup(
+        self,
+        _: context::Context,
+        sealed_backup: EscrowedSecret,
+    ) -> Result<(), CspSealedBackupError> {
+        let vault = self.local_csp_vault;
+        let job = move || vault.restore_sealed_backup(sealed_backup);
+        execute_on_thread_pool(self.thread_pool_handle, job).await
+    }
+
+    // `VetKdCspVault`-methods.
+    async fn create_encrypted_vetkd_key_share(
+        self,
+        _: context::Context,
+        key_id: KeyId,
+        master_public_key: Vec<u8>,
+        encryption_public_key: Vec<u8>,
+        derivation_path: ExtendedDerivationPath,
+        derivation_id: Vec<u8>,
+    ) -> Result<VetKdEncryptedKeyShare, CspVetKdEncryptedKeyShareCreationError> {
+        let vault = self.local_csp_vault;
+        let job = move || {
+            vault.create_encrypted_vetkd_key_share(
+                key_id,
+                master_public_key,
+                encryption_public_key,
+                derivation_path,
+                derivation_id,
+            )
+        };
+        execute_on_thread_pool(self.thread_pool_handle, job).await
+    }
 }
 
 type VaultFactory<C> = dyn Fn(&ReplicaLogger, Arc<CryptoMetrics>) -> Arc<C> + Send + Sync;
@@ -525,11 +615,15 @@ impl TarpcCspVaultServerImplBuilder<ProdLocalCspVault> {
     pub fn new(key_store_dir: &Path) -> Self {
         let key_store_path = key_store_dir.to_path_buf();
         let local_csp_vault_factory = Box::new(move |logger: &ReplicaLogger, metrics| {
-            Arc::new(LocalCspVault::new_in_dir(
-                &key_store_path,
-                metrics,
-                new_logger!(logger),
-            ))
+            Arc::new(
+                LocalCspVault::new_in_dir(
+                    &key_store_path,
+                    &CspRngSource::Getrandom,
+                    metrics,
+                    new_logger!(logger),
+                )
+                .unwrap_or_else(|e| panic!("{}", e)),
+            )
         });
         Self::new_internal(local_csp_vault_factory)
     }
@@ -573,6 +667,23 @@ impl<C> TarpcCspVaultServerImplBuilder<C> {
 
 impl<C: CspVault> TarpcCspVaultServerImplBuilder<C> {
     pub fn build(&self, listener: UnixListener) -> TarpcCspVaultServerImpl<C> {
+        self.build_with_listener(Listener::Unix(listener))
+    }
+
+    /// Builds a server accepting clients via TCP at `listener`, which must
+    /// authenticate via TLS as required by `tls_acceptor`.
+    pub fn build_for_tcp(
+        &self,
+        listener: TcpListener,
+        tls_acceptor: SslAcceptor,
+    ) -> TarpcCspVaultServerImpl<C> {
+        self.build_with_listener(Listener::Tcp {
+            listener,
+            tls_acceptor,
+        })
+    }
+
+    fn build_with_listener(&self, listener: Listener) -> TarpcCspVaultServerImpl<C> {
         let local_csp_vault: Arc<C> =
             (self.local_csp_vault_factory)(&self.logger, Arc::clone(&self.metrics));
         TarpcCspVaultServerImpl {
@@ -609,26 +720,99 @@ impl<C: CspVault + 'static> TarpcCspVaultServerImpl<C> {
 
         // Listen for connections; spawns one `tokio` task per client.
         loop {
-            let (conn, _addr) = self.listener.accept().await.unwrap_or_else(|e| {
-                panic!(
-                    "Error listening at socket {:?}: {}",
-                    &self.listener.local_addr(),
-                    e
-                )
-            });
             let local_csp_vault = Arc::clone(&self.local_csp_vault);
             let thread_pool_handle = self.thread_pool.clone(); // creates a pool handle similar to Arc
-            tokio::spawn(async move {
-                let framed = codec_builder.new_framed(conn);
-                let transport = serde_transport::new(framed, Bincode::default());
-                let worker = TarpcCspVaultServerWorker {
-                    local_csp_vault,
-                    thread_pool_handle,
-                };
-                let channel_executor =
-                    BaseChannel::with_defaults(transport).execute(worker.serve());
-                channel_executor.await;
-            });
+            match &self.listener {
+                Listener::Unix(listener) => {
+                    let conn = match listener.accept().await {
+                        Ok((conn, _addr)) => conn,
+                        Err(e) => {
+                            back_off_after_accept_error(&self.logger, &listener.local_addr(), e)
+                                .await;
+                            continue;
+                        }
+                    };
+                    tokio::spawn(serve_connection(
+                        conn,
+                        codec_builder,
+                        local_csp_vault,
+                        thread_pool_handle,
+                    ));
+                }
+                Listener::Tcp {
+                    listener,
+                    tls_acceptor,
+                } => {
+                    let (conn, addr) = match listener.accept().await {
+                        Ok((conn, addr)) => (conn, addr),
+                        Err(e) => {
+                            back_off_after_accept_error(&self.logger, &listener.local_addr(), e)
+                                .await;
+                            continue;
+                        }
+                    };
+                    let tls_acceptor = tls_acceptor.clone();
+                    let logger = new_logger!(&self.logger);
+                    // The TLS handshake is done in the spawned task, so that
+                    // a slow client cannot block accepting other clients.
+                    tokio::spawn(async move {
+                        let tls_stream = match accept_tls(&tls_acceptor, conn).await {
+                            Ok(tls_stream) => tls_stream,
+                            Err(e) => {
+                                warn!(logger, "Rejected CSP vault client at {}: {}", addr, e);
+                                return;
+                            }
+                        };
+                        // `serve_tunnels` requires `Sync`, which a `ThreadPool` is not.
+                        let thread_pool_handle = Mutex::new(thread_pool_handle);
+                        let serve = move |tunnel: DuplexStream| {
+                            tokio::spawn(serve_connection(
+                                tunnel,
+                                codec_builder,
+                                Arc::clone(&local_csp_vault),
+                                thread_pool_handle.lock().clone(),
+                            ));
+                        };
+                        if let Err(e) = serve_tunnels(tls_stream, serve).await {
+                            warn!(logger, "Error serving CSP vault client at {}: {}", addr, e);
+                        }
+                    });
+                }
+            }
         }
     }
 }
+
+/// Logs an error on accepting a client and waits briefly before accepting
+/// again, so that a persistent error (e.g., too many open files) does not make
+/// the server spin.
+async fn back_off_after_accept_error<A: Debug>(
+    logger: &ReplicaLogger,
+    local_addr: &A,
+    error: std::io::Error,
+) {
+    warn!(
+        logger,
+        "Error accepting CSP vault client at {:?}: {}", local_addr, error
+    );
+    tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+}
+
+async fn serve_connection<C, IO>(
+    conn: IO,
+    codec_builder: Builder,
+    local_csp_vault: Arc<C>,
+    thread_pool_handle: ThreadPool,
+) where
+    C: CspVault + 'static,
+    IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let framed = codec_builder.new_framed(conn);
+    let transport = serde_transport::new(framed, Bincode::default());
+    let worker = TarpcCspVaultServerWorker {
+        local_csp_vault,
+        thread_pool_handle,
+    };
+    let channel_executor = BaseChannel::with_defaults(transport).execute(worker.serve());
+    channel_executor.await;
+}
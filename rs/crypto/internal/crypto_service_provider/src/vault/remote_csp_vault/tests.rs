@@ -90,6 +90,37 @@ mod timeout {
     }
 }
 
+mod basic_sig {
+    use super::*;
+    use crate::KeyId;
+    use ic_types::crypto::AlgorithmId;
+
+    #[test]
+    fn should_sign_batch_with_same_signatures_as_sign() {
+        let tokio_rt = new_tokio_runtime();
+        let csp_vault = new_remote_csp_vault(tokio_rt.handle());
+        let public_key = csp_vault
+            .gen_node_signing_key_pair()
+            .expect("failed to generate keys");
+        let key_id = KeyId::try_from(&public_key).unwrap();
+        let messages = vec![b"first message".to_vec(), b"second message".to_vec()];
+
+        let signatures = csp_vault
+            .sign_batch(AlgorithmId::Ed25519, &messages, key_id)
+            .expect("failed to sign batch");
+
+        assert_eq!(signatures.len(), messages.len());
+        for (message, signature) in messages.iter().zip(signatures) {
+            assert_eq!(
+                signature,
+                csp_vault
+                    .sign(AlgorithmId::Ed25519, message, key_id)
+                    .expect("failed to sign")
+            );
+        }
+    }
+}
+
 mod threshold_sig {
     use super::*;
     use ic_crypto_internal_seed::Seed;
@@ -760,6 +791,38 @@ mod public_seed {
     }
 }
 
+mod reconnect {
+    use super::*;
+    use crate::vault::api::CspBasicSignatureKeygenError;
+
+    #[test]
+    fn should_fail_over_to_secondary_socket_if_disconnected_from_primary_socket() {
+        let tokio_rt = new_tokio_runtime();
+        let secondary_socket_path = start_new_remote_csp_vault_server_for_test(tokio_rt.handle());
+        let (primary_socket_path, _primary_dir, primary_listener) =
+            setup_listener(tokio_rt.handle());
+        // The primary vault goes away right after the client connected to it.
+        let removed_socket_path = primary_socket_path.clone();
+        tokio_rt.spawn(async move {
+            let (connection, _) = primary_listener.accept().await.unwrap();
+            drop(primary_listener);
+            std::fs::remove_file(removed_socket_path).unwrap();
+            drop(connection);
+        });
+        let csp_vault = RemoteCspVault::builder(primary_socket_path, tokio_rt.handle().clone())
+            .with_secondary_socket_path(secondary_socket_path)
+            .build()
+            .expect("Could not create RemoteCspVault");
+
+        assert_matches!(
+            csp_vault.gen_node_signing_key_pair(),
+            Err(CspBasicSignatureKeygenError::TransientInternalError { internal_error })
+                if internal_error.contains("disconnected")
+        );
+        assert!(csp_vault.gen_node_signing_key_pair().is_ok());
+    }
+}
+
 mod logging {
     use super::*;
     use crate::CryptoMetrics;
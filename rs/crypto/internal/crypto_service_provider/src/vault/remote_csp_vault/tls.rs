@@ -0,0 +1,93 @@
+//! Mutually authenticated TLS for connections to a remote CSP vault via TCP
+use ic_config::crypto::CspVaultTlsConfig;
+use openssl::error::ErrorStack;
+use openssl::ssl::{SslAcceptor, SslConnector, SslFiletype, SslMethod, SslVerifyMode, SslVersion};
+use std::pin::Pin;
+use tokio::net::TcpStream;
+use tokio_openssl::SslStream;
+
+/// Creates the TLS connector of a vault client, which authenticates with the
+/// client certificate of `config`, and only accepts a vault server with a
+/// certificate for the connected host issued by the CA of `config`.
+pub fn tls_connector(config: &CspVaultTlsConfig) -> Result<SslConnector, ErrorStack> {
+    let mut builder = SslConnector::builder(SslMethod::tls_client())?;
+    builder.set_min_proto_version(Some(SslVersion::TLS1_3))?;
+    builder.set_ca_file(&config.ca_certificate)?;
+    builder.set_certificate_chain_file(&config.certificate)?;
+    builder.set_private_key_file(&config.private_key, SslFiletype::PEM)?;
+    builder.check_private_key()?;
+    builder.set_verify(SslVerifyMode::PEER);
+    Ok(builder.build())
+}
+
+/// Creates the TLS acceptor of a vault server, which authenticates with the
+/// server certificate of `config`, and only accepts vault clients with a
+/// certificate issued by the CA of `config`.
+pub fn tls_acceptor(config: &CspVaultTlsConfig) -> Result<SslAcceptor, ErrorStack> {
+    let mut builder = SslAcceptor::mozilla_modern_v5(SslMethod::tls_server())?;
+    builder.set_ca_file(&config.ca_certificate)?;
+    builder.set_certificate_chain_file(&config.certificate)?;
+    builder.set_private_key_file(&config.private_key, SslFiletype::PEM)?;
+    builder.check_private_key()?;
+    builder.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+    Ok(builder.build())
+}
+
+/// Connects to the vault server at `address` (`host:port`) and performs the
+/// TLS handshake, verifying the server certificate against `host`.
+pub(super) async fn connect_tls(
+    connector: &SslConnector,
+    address: &str,
+) -> Result<SslStream<TcpStream>, String> {
+    let host = host_of(address);
+    let ssl = connector
+        .configure()
+        .and_then(|configuration| configuration.into_ssl(host))
+        .map_err(|e| format!("error configuring TLS: {}", e))?;
+    let tcp_stream = TcpStream::connect(address)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut tls_stream = SslStream::new(ssl, tcp_stream).map_err(|e| e.to_string())?;
+    Pin::new(&mut tls_stream)
+        .connect()
+        .await
+        .map_err(|e| format!("TLS handshake failed: {}", e))?;
+    Ok(tls_stream)
+}
+
+/// Performs the TLS handshake with a vault client connected via `tcp_stream`.
+pub(super) async fn accept_tls(
+    acceptor: &SslAcceptor,
+    tcp_stream: TcpStream,
+) -> Result<SslStream<TcpStream>, String> {
+    let ssl = openssl::ssl::Ssl::new(acceptor.context()).map_err(|e| e.to_string())?;
+    let mut tls_stream = SslStream::new(ssl, tcp_stream).map_err(|e| e.to_string())?;
+    Pin::new(&mut tls_stream)
+        .accept()
+        .await
+        .map_err(|e| format!("TLS handshake failed: {}", e))?;
+    Ok(tls_stream)
+}
+
+/// Returns the host of an address of the form `host:port`, where an IPv6
+/// host is enclosed in square brackets.
+fn host_of(address: &str) -> &str {
+    let host = address
+        .rsplit_once(':')
+        .map_or(address, |(host, _port)| host);
+    host.strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_return_host_of_address() {
+        assert_eq!(host_of("vault.example.com:4321"), "vault.example.com");
+        assert_eq!(host_of("10.0.0.1:4321"), "10.0.0.1");
+        assert_eq!(host_of("[2001:db8::1]:4321"), "2001:db8::1");
+    }
+}
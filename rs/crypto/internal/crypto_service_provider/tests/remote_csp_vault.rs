@@ -3,6 +3,7 @@ use ic_crypto_internal_basic_sig_ed25519 as ed25519;
 use ic_crypto_internal_csp::key_id::KeyId;
 use ic_crypto_internal_csp::public_key_store::proto_pubkey_store::ProtoPublicKeyStore;
 use ic_crypto_internal_csp::secret_key_store::proto_store::ProtoSecretKeyStore;
+use ic_crypto_internal_csp::vault::local_csp_vault::csprng::CspRng;
 use ic_crypto_internal_csp::LocalCspVault;
 use ic_crypto_internal_logmon::metrics::CryptoMetrics;
 use ic_crypto_temp_crypto_vault::RemoteVaultEnvironment;
@@ -14,14 +15,12 @@ use proptest::prelude::any;
 use proptest::prelude::ProptestConfig;
 use proptest::result::maybe_err;
 use proptest::{prop_assert_eq, proptest};
-use rand::rngs::OsRng;
 use std::sync::Arc;
 use tempfile::TempDir;
 
 mod rpc_connection {
     use super::*;
     use crate::rpc_connection::MessageLength::{Small, TooLarge};
-    use ic_crypto_internal_csp::api::CspCreateMEGaKeyError;
     use ic_crypto_internal_csp::types::CspSignature;
     use ic_crypto_internal_csp::vault::api::CspBasicSignatureError::InternalError;
     use ic_crypto_internal_csp::vault::api::{
@@ -77,8 +76,7 @@ mod rpc_connection {
         assert_matches!(signature, Err(InternalError {internal_error}) if internal_error.contains("the connection to the server was already shutdown"));
 
         let signature = sign_message(Small, key_id, &client);
-        // TODO CRP-1822: with reconnection feature this should now be an `Ok(_)` result
-        assert_matches!(signature, Err(InternalError {internal_error}) if internal_error.contains("the connection to the server was already shutdown"));
+        assert_matches!(signature, Ok(_));
     }
 
     #[test]
@@ -97,9 +95,7 @@ mod rpc_connection {
         let keys = client.current_node_public_keys_with_timestamps(); //encoded response from server has 93 bytes
         assert_matches!(keys, Err(CspPublicKeyStoreError::TransientInternalError(msg)) if msg.contains("an error occurred while waiting for the server response"));
 
-        // TODO CRP-1822: with reconnection feature this should now be an `Ok(_)` result
-        assert_matches!(client.idkg_gen_dealing_encryption_key_pair(),
-        Err(CspCreateMEGaKeyError::TransientInternalError {internal_error}) if internal_error.contains("the connection to the server was already shutdown"));
+        assert_matches!(client.idkg_gen_dealing_encryption_key_pair(), Ok(_));
     }
 
     #[test]
@@ -116,9 +112,7 @@ mod rpc_connection {
         let keys = &client.current_node_public_keys_with_timestamps(); //encoded response from server has 93 bytes
         assert_matches!(keys, Err(CspPublicKeyStoreError::TransientInternalError(msg)) if msg.contains("the connection to the server was already shutdown"));
 
-        // TODO CRP-1822: with reconnection feature this should now be an `Ok(_)` result
-        assert_matches!(&client.idkg_gen_dealing_encryption_key_pair(),
-        Err(CspCreateMEGaKeyError::TransientInternalError {internal_error}) if internal_error.contains("the connection to the server was already shutdown"));
+        assert_matches!(&client.idkg_gen_dealing_encryption_key_pair(), Ok(_));
     }
 
     #[test]
@@ -141,8 +135,7 @@ mod rpc_connection {
 
         env.restart_server();
         let signature = sign_message(Small, key_id, &client);
-        // TODO CRP-1822: with reconnection feature this should now be an `Ok(_)` result
-        assert_matches!(signature, Err(InternalError {internal_error}) if internal_error.contains("the connection to the server was already shutdown"));
+        assert_matches!(signature, Ok(_));
     }
 
     #[test]
@@ -164,6 +157,21 @@ mod rpc_connection {
         assert_matches!(signature, Ok(_));
     }
 
+    #[test]
+    fn should_connect_via_secondary_socket_if_primary_socket_unavailable() {
+        let (vault, temp_dir) = local_vault_in_temp_dir();
+        let env = RemoteVaultEnvironment::start_server_with_local_csp_vault(Arc::new(vault));
+        let client = RemoteCspVault::builder(
+            temp_dir.path().join("missing.socket"),
+            env.vault_client_runtime.handle().clone(),
+        )
+        .with_secondary_socket_path(env.vault_server.vault_socket_path())
+        .with_rpc_timeout(Duration::from_millis(1000))
+        .build_expecting_ok();
+
+        assert_matches!(client.gen_node_signing_key_pair(), Ok(_));
+    }
+
     fn vault_client_with_short_timeouts<B>(
         env: &RemoteVaultEnvironment<B>,
     ) -> RemoteCspVaultBuilder {
@@ -203,6 +211,146 @@ mod rpc_connection {
     }
 }
 
+mod tcp_connection {
+    use super::*;
+    use ic_config::crypto::CspVaultTlsConfig;
+    use ic_crypto_internal_csp::vault::api::BasicSignatureCspVault;
+    use ic_crypto_internal_csp::vault::remote_csp_vault::{
+        tls_acceptor, tls_connector, RemoteCspVault, TarpcCspVaultServerImplBuilder,
+    };
+    use ic_crypto_test_utils::tls::x509_certificates::CertWithPrivateKey;
+    use std::path::Path;
+
+    const CA_CN: &str = "CSP vault CA";
+
+    #[test]
+    fn should_connect_via_tcp_with_mutual_tls() {
+        let temp_dir = tempfile::tempdir().expect("failed to create temporary directory");
+        let ca = certificate_authority(CA_CN);
+        let server_tls_config = tls_config_issued_by(&ca, "localhost", "server", temp_dir.path());
+        let client_tls_config = tls_config_issued_by(&ca, "client", "client", temp_dir.path());
+        let (_server_runtime, address, _vault_dir) = start_tcp_server(&server_tls_config);
+        let client_runtime = tokio::runtime::Runtime::new().expect("failed to create runtime");
+
+        let client = RemoteCspVault::builder_for_tcp(
+            address,
+            tls_connector(&client_tls_config).expect("invalid TLS config"),
+            client_runtime.handle().clone(),
+        )
+        .build_expecting_ok();
+
+        assert_matches!(client.gen_node_signing_key_pair(), Ok(_));
+    }
+
+    #[test]
+    fn should_sign_message_spanning_several_tunnel_frames_via_tcp() {
+        let temp_dir = tempfile::tempdir().expect("failed to create temporary directory");
+        let ca = certificate_authority(CA_CN);
+        let server_tls_config = tls_config_issued_by(&ca, "localhost", "server", temp_dir.path());
+        let client_tls_config = tls_config_issued_by(&ca, "client", "client", temp_dir.path());
+        let (_server_runtime, address, _vault_dir) = start_tcp_server(&server_tls_config);
+        let client_runtime = tokio::runtime::Runtime::new().expect("failed to create runtime");
+        let client = RemoteCspVault::builder_for_tcp(
+            address,
+            tls_connector(&client_tls_config).expect("invalid TLS config"),
+            client_runtime.handle().clone(),
+        )
+        .build_expecting_ok();
+        let node_signing_public_key = client
+            .gen_node_signing_key_pair()
+            .expect("failed generating node signing key pair");
+        let key_id = KeyId::try_from(&node_signing_public_key).unwrap();
+
+        let signature = client.sign(AlgorithmId::Ed25519, &vec![0_u8; 1024 * 1024], key_id);
+
+        assert_matches!(signature, Ok(_));
+    }
+
+    #[test]
+    fn should_fail_to_connect_if_server_certificate_issued_by_untrusted_ca() {
+        let temp_dir = tempfile::tempdir().expect("failed to create temporary directory");
+        let server_tls_config = tls_config_issued_by(
+            &certificate_authority(CA_CN),
+            "localhost",
+            "server",
+            temp_dir.path(),
+        );
+        let client_tls_config = tls_config_issued_by(
+            &certificate_authority("other CA"),
+            "client",
+            "client",
+            temp_dir.path(),
+        );
+        let (_server_runtime, address, _vault_dir) = start_tcp_server(&server_tls_config);
+        let client_runtime = tokio::runtime::Runtime::new().expect("failed to create runtime");
+
+        let result = RemoteCspVault::builder_for_tcp(
+            address,
+            tls_connector(&client_tls_config).expect("invalid TLS config"),
+            client_runtime.handle().clone(),
+        )
+        .build();
+
+        assert_matches!(result.err(), Some(error) if format!("{:?}", error).contains("TLS handshake failed"));
+    }
+
+    struct CertificateAuthority {
+        cn: String,
+        cert: CertWithPrivateKey,
+    }
+
+    fn certificate_authority(cn: &str) -> CertificateAuthority {
+        CertificateAuthority {
+            cn: cn.to_string(),
+            cert: CertWithPrivateKey::builder()
+                .cn(cn.to_string())
+                .set_ca_key_usage_extension()
+                .build_prime256v1(),
+        }
+    }
+
+    /// Writes a certificate for `cn` issued by `ca`, its private key, and the
+    /// certificate of `ca` to files in `dir` named after `name`.
+    fn tls_config_issued_by(
+        ca: &CertificateAuthority,
+        cn: &str,
+        name: &str,
+        dir: &Path,
+    ) -> CspVaultTlsConfig {
+        let cert = CertWithPrivateKey::builder()
+            .cn(cn.to_string())
+            .with_ca_signing(ca.cert.key_pair(), ca.cn.clone())
+            .build_prime256v1();
+        let config = CspVaultTlsConfig {
+            certificate: dir.join(format!("{}.pem", name)),
+            private_key: dir.join(format!("{}.key", name)),
+            ca_certificate: dir.join(format!("{}-ca.pem", name)),
+        };
+        std::fs::write(&config.certificate, cert.cert_pem()).expect("failed to write");
+        std::fs::write(&config.private_key, cert.key_pair_pem()).expect("failed to write");
+        std::fs::write(&config.ca_certificate, ca.cert.cert_pem()).expect("failed to write");
+        config
+    }
+
+    fn start_tcp_server(
+        tls_config: &CspVaultTlsConfig,
+    ) -> (tokio::runtime::Runtime, String, TempDir) {
+        let (vault, vault_dir) = local_vault_in_temp_dir();
+        let runtime = tokio::runtime::Runtime::new().expect("failed to create runtime");
+        let listener = runtime
+            .block_on(tokio::net::TcpListener::bind("127.0.0.1:0"))
+            .expect("failed to bind");
+        let port = listener.local_addr().expect("missing address").port();
+        let server = TarpcCspVaultServerImplBuilder::new_with_local_csp_vault(Arc::new(vault))
+            .build_for_tcp(
+                listener,
+                tls_acceptor(tls_config).expect("invalid TLS config"),
+            );
+        runtime.spawn(server.run());
+        (runtime, format!("localhost:{}", port), vault_dir)
+    }
+}
+
 mod basic_signature_csp_vault {
     use super::*;
     use ic_crypto_internal_csp::types::{CspPublicKey, CspSignature};
@@ -345,7 +493,7 @@ mod multi_signature_csp_vault {
 }
 
 fn local_vault_in_temp_dir() -> (
-    LocalCspVault<OsRng, ProtoSecretKeyStore, ProtoSecretKeyStore, ProtoPublicKeyStore>,
+    LocalCspVault<CspRng, ProtoSecretKeyStore, ProtoSecretKeyStore, ProtoPublicKeyStore>,
     TempDir,
 ) {
     use ic_config::crypto::CryptoConfig;
@@ -353,9 +501,11 @@ fn local_vault_in_temp_dir() -> (
     let (config, _temp_dir) = CryptoConfig::new_in_temp_dir();
     let local_vault = LocalCspVault::new_in_dir(
         &config.crypto_root,
+        &config.csp_rng_source,
         Arc::new(CryptoMetrics::none()),
         no_op_logger(),
-    );
+    )
+    .expect("failed to create the local vault");
     (local_vault, _temp_dir)
 }
 
@@ -0,0 +1,2 @@
+// Include the generated CSP vault proto files.
+include!(concat!(env!("OUT_DIR"), "/csp_vault.v1.rs"));
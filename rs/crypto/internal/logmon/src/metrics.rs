@@ -266,6 +266,19 @@ impl CryptoMetrics {
                 .inc();
         }
     }
+
+    /// Observes the entropy source of the CSPRNG. The gauge of the given
+    /// `source` is set to 1, and the ones of all other sources to 0.
+    pub fn observe_csprng_source(&self, source: CsprngSource) {
+        if let Some(metrics) = &self.metrics {
+            for other in CsprngSource::iter() {
+                metrics
+                    .crypto_csprng_source
+                    .with_label_values(&[&format!("{}", other)])
+                    .set(i64::from(other == source));
+            }
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, Eq, IntoStaticStr, PartialOrd, Ord, PartialEq)]
@@ -287,6 +300,7 @@ pub enum MetricsDomain {
     ThresholdEcdsa,
     PublicSeed,
     KeyManagement,
+    VetKd,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, Eq, IntoStaticStr, PartialOrd, Ord, PartialEq)]
@@ -323,6 +337,13 @@ pub enum KeyRotationResult {
     PublicKeyNotFound,
 }
 
+#[derive(Copy, Clone, Debug, EnumIter, Eq, IntoStaticStr, PartialOrd, Ord, PartialEq)]
+pub enum CsprngSource {
+    Getrandom,
+    HwRng,
+    SeededDrbg,
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, Eq, IntoStaticStr, PartialOrd, Ord, PartialEq)]
 pub enum ServiceType {
     Client,
@@ -496,6 +517,10 @@ struct Metrics {
 
     /// Counter for iDKG dealing encryption public key too old, but not in registry.
     crypto_latest_idkg_dealing_encryption_public_key_too_old_but_not_in_registry: IntCounter,
+
+    /// A gauge vector that is 1 for the entropy source of the CSPRNG, indicated by the 'source'
+    /// label, and 0 for all others.
+    crypto_csprng_source: IntGaugeVec,
 }
 
 impl Display for MetricsDomain {
@@ -533,6 +558,13 @@ impl Display for KeyType {
     }
 }
 
+impl Display for CsprngSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let value: &'static str = self.into();
+        write!(f, "{}", value.to_case(Case::Snake))
+    }
+}
+
 impl Display for ServiceType {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let value: &'static str = self.into();
@@ -592,6 +624,14 @@ impl Metrics {
         for result in KeyRotationResult::iter() {
             rotation_results.with_label_values(&[&format!("{}", result)]);
         }
+        let csprng_source = r.int_gauge_vec(
+            "crypto_csprng_source",
+            "Entropy source of the CSPRNG, 1 for the source in use and 0 otherwise",
+            &["source"],
+        );
+        for source in CsprngSource::iter() {
+            csprng_source.with_label_values(&[&format!("{}", source)]);
+        }
         Self {
             crypto_lock_acquisition_duration_seconds: r.histogram_vec(
                 "crypto_lock_acquisition_duration_seconds",
@@ -665,6 +705,7 @@ impl Metrics {
                 "crypto_latest_idkg_dealing_encryption_public_key_too_old_but_not_in_registry", 
                 "latest iDKG dealing encryption public key too old, but not in registry"
             ),
+            crypto_csprng_source: csprng_source,
         }
     }
 }
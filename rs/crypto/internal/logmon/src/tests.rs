@@ -1,4 +1,4 @@
-use crate::metrics::{BooleanOperation, KeyType, MetricsDomain};
+use crate::metrics::{BooleanOperation, CsprngSource, KeyType, MetricsDomain};
 
 #[test]
 fn shall_convert_enum_variants_to_snake_case_correctly() {
@@ -8,4 +8,5 @@ fn shall_convert_enum_variants_to_snake_case_correctly() {
     );
     assert_eq!("secret_sks", format!("{}", KeyType::SecretSKS));
     assert_eq!("idkg_protocol", format!("{}", MetricsDomain::IdkgProtocol));
+    assert_eq!("hw_rng", format!("{}", CsprngSource::HwRng));
 }
@@ -1,9 +1,10 @@
 //! Static crypto utility methods.
 use ic_config::crypto::CryptoConfig;
 use ic_crypto_internal_csp::api::CspCreateMEGaKeyError;
-use ic_crypto_internal_csp::vault::api::ValidatePksAndSksError;
+use ic_crypto_internal_csp::vault::api::{CspSealedBackupError, ValidatePksAndSksError};
 use ic_crypto_internal_csp::CryptoServiceProvider;
 use ic_crypto_internal_csp::Csp;
+use ic_crypto_internal_key_escrow::EscrowedSecret;
 use ic_crypto_internal_logmon::metrics::CryptoMetrics;
 use ic_crypto_node_key_validation::ValidNodePublicKeys;
 use ic_crypto_tls_interfaces::TlsPublicKeyCert;
@@ -186,6 +187,70 @@ impl ErrorReproducibility for NodeKeyGenerationError {
     }
 }
 
+/// Restores the node keys from a sealed backup on a node with empty key stores,
+/// e.g., on replacement hardware, and returns the validated node public keys.
+///
+/// The `sealed_backup` is created with `CryptoComponentImpl::export_sealed_backup`
+/// and is only unsealed inside the CSP vault created according to the given
+/// `config`, using the backup private key that the vault reads from the file
+/// configured in its `KeyEscrowConfig`.
+///
+/// # Errors
+/// * [`NodeKeysRestoreError::SealedBackupError`] if the vault has no backup
+///   private key configured, the backup cannot be unsealed with it, or if the
+///   key stores already contain node keys.
+/// * [`NodeKeysRestoreError::InconsistentKeyMaterial`] if the restored keys are
+///   not a consistent set of node keys.
+/// * [`NodeKeysRestoreError::TransientInternalError`] if a transient internal error
+///   occurs, e.g., an RPC error communicating with the remote vault.
+pub fn restore_node_keys_from_sealed_backup(
+    config: &CryptoConfig,
+    tokio_runtime_handle: Option<tokio::runtime::Handle>,
+    sealed_backup: EscrowedSecret,
+) -> Result<ValidNodePublicKeys, NodeKeysRestoreError> {
+    let csp = csp_for_config(config, tokio_runtime_handle);
+    restore_node_keys_from_sealed_backup_internal(&csp, sealed_backup)
+}
+
+fn restore_node_keys_from_sealed_backup_internal<T: CryptoServiceProvider>(
+    csp: &T,
+    sealed_backup: EscrowedSecret,
+) -> Result<ValidNodePublicKeys, NodeKeysRestoreError> {
+    csp.restore_sealed_backup(sealed_backup)
+        .map_err(|error| match error {
+            CspSealedBackupError::TransientInternalError { internal_error } => {
+                NodeKeysRestoreError::TransientInternalError(internal_error)
+            }
+            _ => NodeKeysRestoreError::SealedBackupError(error),
+        })?;
+    csp.validate_pks_and_sks().map_err(|error| match error {
+        ValidatePksAndSksError::TransientInternalError(transient_error) => {
+            NodeKeysRestoreError::TransientInternalError(transient_error)
+        }
+        _ => NodeKeysRestoreError::InconsistentKeyMaterial(error),
+    })
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NodeKeysRestoreError {
+    /// If the sealed backup cannot be unsealed or restored into the key stores
+    SealedBackupError(CspSealedBackupError),
+    /// If the restored keys are not a consistent set of node keys
+    InconsistentKeyMaterial(ValidatePksAndSksError),
+    /// If a transient internal error occurs, e.g., an RPC error communicating with the remote vault
+    TransientInternalError(String),
+}
+
+impl ErrorReproducibility for NodeKeysRestoreError {
+    fn is_reproducible(&self) -> bool {
+        match self {
+            NodeKeysRestoreError::SealedBackupError(_) => true,
+            NodeKeysRestoreError::InconsistentKeyMaterial(_) => true,
+            NodeKeysRestoreError::TransientInternalError(_) => false,
+        }
+    }
+}
+
 fn csp_for_config(
     config: &CryptoConfig,
     tokio_runtime_handle: Option<tokio::runtime::Handle>,
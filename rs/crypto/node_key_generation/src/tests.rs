@@ -4,15 +4,17 @@ use super::*;
 use assert_matches::assert_matches;
 use ic_crypto_internal_csp::types::CspPop;
 use ic_crypto_internal_csp::types::CspPublicKey;
-use ic_crypto_internal_threshold_sig_ecdsa::{EccCurveType, MEGaPublicKey};
+use ic_crypto_internal_threshold_sig_ecdsa::{EccCurveType, MEGaPrivateKey, MEGaPublicKey};
 use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::CspFsEncryptionPop;
 use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::CspFsEncryptionPublicKey;
 use ic_crypto_node_key_validation::ValidNodeSigningPublicKey;
+use ic_crypto_secrets_containers::SecretBytes;
 use ic_crypto_test_utils_csp::MockAllCryptoServiceProvider;
 use ic_crypto_test_utils_keys::public_keys::{
     valid_committee_signing_public_key, valid_dkg_dealing_encryption_public_key,
     valid_idkg_dealing_encryption_public_key, valid_node_signing_public_key,
 };
+use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
 use ic_protobuf::registry::crypto::v1::PublicKey;
 use ic_types::crypto::CurrentNodePublicKeys;
 use ic_types_test_utils::ids::node_test_id;
@@ -252,6 +254,97 @@ mod generate_required_node_keys_once_internal {
     }
 }
 
+mod restore_node_keys_from_sealed_backup_internal {
+    use super::*;
+
+    #[test]
+    fn should_restore_sealed_backup_and_return_validated_keys() {
+        let sealed_backup = sealed_backup();
+        let expected_keys = valid_node_public_keys();
+        let mut csp = MockAllCryptoServiceProvider::new();
+        let expected_backup = sealed_backup.clone();
+        csp.expect_restore_sealed_backup()
+            .withf(move |backup| *backup == expected_backup)
+            .times(1)
+            .return_const(Ok(()));
+        csp.expect_validate_pks_and_sks()
+            .times(1)
+            .return_const(Ok(expected_keys.clone()));
+
+        let result = restore_node_keys_from_sealed_backup_internal(&csp, sealed_backup);
+
+        assert_eq!(result, Ok(expected_keys));
+    }
+
+    #[test]
+    fn should_return_error_if_restoring_sealed_backup_fails() {
+        let sealed_backup = sealed_backup();
+        let mut csp = MockAllCryptoServiceProvider::new();
+        csp.expect_restore_sealed_backup()
+            .times(1)
+            .return_const(Err(CspSealedBackupError::KeyStoresNotEmpty));
+        csp.expect_validate_pks_and_sks().never();
+
+        let result = restore_node_keys_from_sealed_backup_internal(&csp, sealed_backup);
+
+        assert_eq!(
+            result,
+            Err(NodeKeysRestoreError::SealedBackupError(
+                CspSealedBackupError::KeyStoresNotEmpty
+            ))
+        );
+    }
+
+    #[test]
+    fn should_return_transient_error() {
+        let sealed_backup = sealed_backup();
+        let mut csp = MockAllCryptoServiceProvider::new();
+        csp.expect_restore_sealed_backup()
+            .times(1)
+            .return_const(Err(CspSealedBackupError::TransientInternalError {
+                internal_error: "RPC fails".to_string(),
+            }));
+
+        let result = restore_node_keys_from_sealed_backup_internal(&csp, sealed_backup);
+
+        assert_matches!(result, Err(NodeKeysRestoreError::TransientInternalError(e)) if e == "RPC fails");
+    }
+
+    #[test]
+    fn should_return_error_if_restored_keys_are_inconsistent() {
+        let sealed_backup = sealed_backup();
+        let mut csp = MockAllCryptoServiceProvider::new();
+        csp.expect_restore_sealed_backup()
+            .times(1)
+            .return_const(Ok(()));
+        csp.expect_validate_pks_and_sks()
+            .times(1)
+            .return_const(Err(ValidatePksAndSksError::EmptyPublicKeyStore));
+
+        let result = restore_node_keys_from_sealed_backup_internal(&csp, sealed_backup);
+
+        assert_eq!(
+            result,
+            Err(NodeKeysRestoreError::InconsistentKeyMaterial(
+                ValidatePksAndSksError::EmptyPublicKeyStore
+            ))
+        );
+    }
+
+    fn sealed_backup() -> EscrowedSecret {
+        let rng = &mut reproducible_rng();
+        let decryption_key = MEGaPrivateKey::generate(EccCurveType::K256, rng);
+        EscrowedSecret::encrypt(
+            &SecretBytes::new(b"node keys".to_vec()),
+            &[decryption_key.public_key().unwrap()],
+            1,
+            b"associated data",
+            rng,
+        )
+        .unwrap()
+    }
+}
+
 fn with_validate_pks_and_sks_returning(
     csp: &mut MockAllCryptoServiceProvider,
     result_on_first_call: Result<ValidNodePublicKeys, ValidatePksAndSksError>,
@@ -25,6 +25,13 @@
 //! The algorithm used by `ic_crypto_sha::Sha224` is SHA224 and
 //! has constant output size of 28 bytes.
 //!
+//! The algorithms used by `ic_crypto_sha::Sha3_256` and
+//! `ic_crypto_sha::Keccak256` are SHA3-256 and Keccak-256, respectively, and
+//! have constant output size of 32 bytes. Keccak-256 is used by Ethereum, e.g.,
+//! to derive addresses from public keys. As the digests have 32 bytes, they can
+//! be signed with threshold ECDSA as the `message_hash` of `sign_with_ecdsa`.
+//! These hashers use a pure Rust implementation on all architectures.
+//!
 //! These hashers can be used, e.g., for creating fingerprints of files that are
 //! persisted on disk.
 //!
@@ -89,6 +96,16 @@
 //!
 //! std::io::copy(&mut reader, &mut hasher).unwrap();
 //! ```
+//!
+//! # Example for `Keccak256` (using state implicitly with the convenience
+//! function)
+//!
+//! ```
+//! use ic_crypto_sha::Keccak256;
+//!
+//! let digest: [u8; 32] = Keccak256::hash(b"some data!");
+//! ```
 
 #![forbid(unsafe_code)]
 pub use ic_crypto_internal_sha2::{Context, DomainSeparationContext, Sha224, Sha256, Sha512};
+pub use ic_crypto_internal_sha3::{Keccak256, Sha3_256};
@@ -0,0 +1,67 @@
+#![allow(clippy::unwrap_used)]
+use ic_crypto_sha::{Keccak256, Sha3_256};
+
+const SHA3_256_DIGEST_OF_DATA: [u8; 32] = [
+    0xef, 0xda, 0x89, 0x3a, 0xa8, 0x50, 0xb0, 0xc0, 0xe6, 0x1f, 0x33, 0x32, 0x56, 0x15, 0xb9, 0xd9,
+    0x3b, 0xcf, 0x6b, 0x42, 0xd6, 0x0d, 0x8f, 0x5d, 0x37, 0xeb, 0xc7, 0x20, 0xfd, 0x4e, 0x3d, 0xaf,
+];
+
+const KECCAK256_DIGEST_OF_DATA: [u8; 32] = [
+    0x8f, 0x54, 0xf1, 0xc2, 0xd0, 0xeb, 0x57, 0x71, 0xcd, 0x5b, 0xf6, 0x7a, 0x66, 0x89, 0xfc, 0xd6,
+    0xee, 0xd9, 0x44, 0x4d, 0x91, 0xa3, 0x9e, 0x5e, 0xf3, 0x2a, 0x9b, 0x4a, 0xe5, 0xca, 0x14, 0xff,
+];
+
+#[test]
+fn should_return_correct_sha3_256_output_with_multiple_calls_to_write() {
+    let mut state = Sha3_256::new();
+    state.write(b"da");
+    state.write(b"ta");
+
+    assert_eq!(state.finish(), SHA3_256_DIGEST_OF_DATA);
+}
+
+#[test]
+fn should_return_correct_sha3_256_output_with_convenience_function() {
+    assert_eq!(Sha3_256::hash(b"data"), SHA3_256_DIGEST_OF_DATA);
+}
+
+#[test]
+fn should_return_correct_keccak256_output_with_multiple_calls_to_write() {
+    let mut state = Keccak256::new();
+    state.write(b"da");
+    state.write(b"ta");
+
+    assert_eq!(state.finish(), KECCAK256_DIGEST_OF_DATA);
+}
+
+#[test]
+fn should_return_correct_keccak256_output_with_convenience_function() {
+    assert_eq!(Keccak256::hash(b"data"), KECCAK256_DIGEST_OF_DATA);
+}
+
+#[test]
+fn should_return_standard_digests_of_empty_input() {
+    // The digests of the empty input differ only in the padding.
+    assert_eq!(
+        hex(&Sha3_256::hash(b"")),
+        "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+    );
+    assert_eq!(
+        hex(&Keccak256::hash(b"")),
+        "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+    );
+}
+
+#[test]
+fn should_act_as_writer() {
+    let mut reader: &[u8] = b"data";
+    let mut hasher = Keccak256::new();
+
+    std::io::copy(&mut reader, &mut hasher).unwrap();
+
+    assert_eq!(hasher.finish(), KECCAK256_DIGEST_OF_DATA);
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -1,5 +1,7 @@
 use clap::Parser;
+use ic_config::crypto::CspVaultType;
 use ic_config::{Config, ConfigSource};
+use ic_crypto_internal_csp::vault::local_csp_vault::csprng::CspRngSourceError;
 use ic_crypto_internal_logmon::metrics::CryptoMetrics;
 use ic_logger::{info, new_replica_logger_from_config};
 use ic_metrics::MetricsRegistry;
@@ -14,7 +16,9 @@ const IC_CRYPTO_CSP_SOCKET_NAME: &str = "ic-crypto-csp.socket";
     version = "0.1",
     author = "Internet Computer Developers",
     about = "NOTE: This binary is intended to be started as socket-activated \
-               systemd service with a single socket named ic-crypto-csp.socket"
+               systemd service with a single socket named ic-crypto-csp.socket, \
+               unless the vault type in the replica configuration is tcp, in which \
+               case it listens at the configured address"
 )]
 struct Opts {
     /// Sets the replica configuration file
@@ -23,18 +27,44 @@ struct Opts {
 }
 
 #[tokio::main]
-async fn main() {
+async fn main() -> Result<(), CspRngSourceError> {
     let opts = Opts::parse();
     let ic_config = get_ic_config(opts.config);
 
     let sks_dir = ic_config.crypto.crypto_root.as_path();
 
-    ensure_single_named_systemd_socket(IC_CRYPTO_CSP_SOCKET_NAME);
-    let systemd_socket_listener = listener_from_first_systemd_socket();
-
     // The `AsyncGuard` must be kept in scope for asynchronously logged messages to appear in the logs.
     let (logger, _async_log_guard) = new_replica_logger_from_config(&ic_config.csp_vault_logger);
 
+    if let CspVaultType::Tcp { address, tls } = &ic_config.crypto.csp_vault_type {
+        let tcp_listener = tokio::net::TcpListener::bind(address)
+            .await
+            .unwrap_or_else(|e| panic!("Failed to listen at address {}: {}", address, e));
+        info!(logger;
+            crypto.method_name => "main",
+            crypto.description => format!(
+                "Starting CspVault server listening at address '{}' via TLS, with SKS-data in '{}' ...",
+                address,
+                sks_dir.display()
+            )
+        );
+        abort_on_panic();
+        let metrics = CryptoMetrics::new(Some(&MetricsRegistry::global()));
+        return ic_crypto_internal_csp::run_csp_vault_server_over_tcp(
+            sks_dir,
+            &ic_config.crypto.csp_rng_source,
+            ic_config.crypto.key_escrow.as_ref(),
+            tcp_listener,
+            tls,
+            logger,
+            metrics,
+        )
+        .await;
+    }
+
+    ensure_single_named_systemd_socket(IC_CRYPTO_CSP_SOCKET_NAME);
+    let systemd_socket_listener = listener_from_first_systemd_socket();
+
     info!(logger;
         crypto.method_name => "main",
         crypto.description => format!(
@@ -48,8 +78,15 @@ async fn main() {
     // This way we can capture all the context if a critical error happens.
     abort_on_panic();
     let metrics = CryptoMetrics::new(Some(&MetricsRegistry::global()));
-    ic_crypto_internal_csp::run_csp_vault_server(sks_dir, systemd_socket_listener, logger, metrics)
-        .await;
+    ic_crypto_internal_csp::run_csp_vault_server(
+        sks_dir,
+        &ic_config.crypto.csp_rng_source,
+        ic_config.crypto.key_escrow.as_ref(),
+        systemd_socket_listener,
+        logger,
+        metrics,
+    )
+    .await
 }
 
 /// Aborts the whole program with a core dump if a single thread panics.
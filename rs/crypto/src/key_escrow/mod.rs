@@ -0,0 +1,100 @@
+//! Escrow of the node secret keys
+//!
+//! A node escrows its secret keys with [`CryptoComponentImpl::escrow_node_secret_keys`]
+//! such that a threshold of the holders of the recovery keys configured in
+//! the vault (see `ic_config::crypto::KeyEscrowConfig`) can jointly recover
+//! them, e.g., to restore a node whose secret key store was lost. The escrowed
+//! keys are bound to the ID of the node.
+//!
+//! For recovery, each holder decrypts their share with
+//! [`decrypt_recovery_share`], and the shares are combined with
+//! [`recover_node_secret_keys`].
+//!
+//! Alternatively, a node operator takes a sealed backup of all node keys with
+//! [`CryptoComponentImpl::export_sealed_backup`], which is restored on
+//! replacement hardware with `ic_crypto_node_key_generation::restore_node_keys_from_sealed_backup`.
+//! The backup is sealed to the backup public key configured in the vault, and
+//! is only ever unsealed inside the vault of the restoring node, which reads
+//! the backup private key itself.
+use crate::CryptoComponentImpl;
+use ic_crypto_internal_csp::api::CspKeyEscrow;
+use ic_crypto_internal_csp::CryptoServiceProvider;
+use ic_crypto_internal_threshold_sig_ecdsa::{MEGaPrivateKey, NodeIndex};
+use ic_logger::info;
+use ic_types::crypto::{CryptoError, CryptoResult};
+use ic_types::NodeId;
+
+pub use ic_crypto_internal_csp::vault::api::EscrowedNodeSecretKeys;
+pub use ic_crypto_internal_key_escrow::{
+    EscrowedSecret, KeyEscrowError, RecoveryShare, RECOVERY_KEY_CURVE,
+};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum KeyRecoveryError {
+    EscrowError(KeyEscrowError),
+    /// The recovered secret is not a valid set of node secret keys.
+    MalformedSecretKeys(String),
+}
+
+impl<C: CryptoServiceProvider> CryptoComponentImpl<C> {
+    /// Escrows the secret keys of this node such that the configured
+    /// threshold of holders of the private keys of the recovery public keys
+    /// configured in the vault can recover them.
+    ///
+    /// Fails if the vault has no valid key escrow config.
+    pub fn escrow_node_secret_keys(&self) -> CryptoResult<EscrowedSecret> {
+        info!(self.logger, "Escrowing the node secret keys");
+        self.csp
+            .escrow_node_secret_keys(associated_data(self.node_id))
+            .map_err(CryptoError::from)
+    }
+
+    /// Exports the node public keys and the corresponding secret keys of this
+    /// node as a backup sealed to the backup public key configured in the
+    /// vault.
+    ///
+    /// The backup public key is deliberately not a parameter: if callers could
+    /// choose it, anyone able to call into the crypto component could obtain
+    /// the node secret keys by sealing them to a key of their own. Node
+    /// operators configure it in the vault instead, together with the backup
+    /// private key used for restoring, see
+    /// `ic_config::crypto::KeyEscrowConfig`.
+    ///
+    /// Fails if the vault has no valid backup public key configured.
+    pub fn export_sealed_backup(&self) -> CryptoResult<EscrowedSecret> {
+        info!(self.logger, "Exporting a sealed backup of the node keys");
+        self.csp.export_sealed_backup().map_err(CryptoError::from)
+    }
+}
+
+/// Decrypts the share of the recipient with the given `index` of the secret
+/// keys escrowed by the node with `node_id`.
+pub fn decrypt_recovery_share(
+    node_id: NodeId,
+    escrowed: &EscrowedSecret,
+    index: NodeIndex,
+    recovery_private_key: &MEGaPrivateKey,
+) -> Result<RecoveryShare, KeyEscrowError> {
+    escrowed.decrypt_share(&associated_data(node_id), index, recovery_private_key)
+}
+
+/// Recovers the secret keys escrowed by the node with `node_id` from the
+/// shares of at least [`EscrowedSecret::threshold`] recipients.
+pub fn recover_node_secret_keys(
+    node_id: NodeId,
+    escrowed: &EscrowedSecret,
+    shares: &[RecoveryShare],
+) -> Result<EscrowedNodeSecretKeys, KeyRecoveryError> {
+    let secret = escrowed
+        .combine(&associated_data(node_id), shares)
+        .map_err(KeyRecoveryError::EscrowError)?;
+    EscrowedNodeSecretKeys::from_secret_bytes(&secret)
+        .map_err(KeyRecoveryError::MalformedSecretKeys)
+}
+
+fn associated_data(node_id: NodeId) -> Vec<u8> {
+    node_id.get().as_slice().to_vec()
+}
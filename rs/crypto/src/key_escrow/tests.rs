@@ -0,0 +1,120 @@
+#![allow(clippy::unwrap_used)]
+
+use super::*;
+use assert_matches::assert_matches;
+use ic_base_types::PrincipalId;
+use ic_config::crypto::KeyEscrowConfig;
+use ic_crypto_temp_crypto::{NodeKeysToGenerate, TempCryptoComponent};
+use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
+
+const NUM_NODE_SECRET_KEYS: usize = 5;
+
+fn node_id(id: u64) -> NodeId {
+    NodeId::from(PrincipalId::new_node_test_id(id))
+}
+
+#[test]
+fn should_recover_escrowed_node_secret_keys_only_for_escrowing_node() {
+    let rng = &mut reproducible_rng();
+    let private_keys: Vec<_> = (0..3)
+        .map(|_| MEGaPrivateKey::generate(RECOVERY_KEY_CURVE, rng))
+        .collect();
+    let crypto_component = TempCryptoComponent::builder()
+        .with_keys(NodeKeysToGenerate::all())
+        .with_node_id(node_id(1))
+        .with_key_escrow_config(KeyEscrowConfig {
+            recovery_public_keys: private_keys
+                .iter()
+                .map(|private_key| hex::encode(private_key.public_key().unwrap().serialize()))
+                .collect(),
+            threshold: 2,
+            backup_public_key: None,
+            backup_private_key_path: None,
+        })
+        .build();
+
+    let escrowed = crypto_component
+        .escrow_node_secret_keys()
+        .expect("failed to escrow node secret keys");
+
+    let shares: Vec<_> = [0, 2]
+        .iter()
+        .map(|&index| {
+            decrypt_recovery_share(node_id(1), &escrowed, index, &private_keys[index as usize])
+                .unwrap()
+        })
+        .collect();
+    let recovered = recover_node_secret_keys(node_id(1), &escrowed, &shares);
+    assert_eq!(
+        recovered.map(|keys| keys.secret_keys.len()),
+        Ok(NUM_NODE_SECRET_KEYS)
+    );
+
+    assert_matches!(
+        recover_node_secret_keys(node_id(2), &escrowed, &shares).err(),
+        Some(KeyRecoveryError::EscrowError(
+            KeyEscrowError::DecryptionFailed
+        ))
+    );
+}
+
+#[test]
+fn should_fail_to_escrow_without_key_escrow_config() {
+    let crypto_component = TempCryptoComponent::builder()
+        .with_keys(NodeKeysToGenerate::all())
+        .with_node_id(node_id(1))
+        .build();
+
+    assert_matches!(
+        crypto_component.escrow_node_secret_keys(),
+        Err(CryptoError::InternalError { internal_error })
+            if internal_error.contains("no recovery public keys are configured")
+    );
+}
+
+#[test]
+fn should_fail_to_export_sealed_backup_without_backup_public_key() {
+    let crypto_component = TempCryptoComponent::builder()
+        .with_keys(NodeKeysToGenerate::all())
+        .with_node_id(node_id(1))
+        .build();
+
+    assert_matches!(
+        crypto_component.export_sealed_backup(),
+        Err(CryptoError::InternalError { internal_error })
+            if internal_error.contains("no backup public key is configured")
+    );
+}
+
+#[test]
+fn should_export_sealed_backup_decryptable_only_with_backup_private_key() {
+    let rng = &mut reproducible_rng();
+    let private_key = MEGaPrivateKey::generate(RECOVERY_KEY_CURVE, rng);
+    let other_private_key = MEGaPrivateKey::generate(RECOVERY_KEY_CURVE, rng);
+    let public_key_hex = hex::encode(private_key.public_key().unwrap().serialize());
+    let crypto_component = TempCryptoComponent::builder()
+        .with_keys(NodeKeysToGenerate::all())
+        .with_node_id(node_id(1))
+        .with_key_escrow_config(KeyEscrowConfig {
+            recovery_public_keys: vec![public_key_hex.clone()],
+            threshold: 1,
+            backup_public_key: Some(public_key_hex),
+            backup_private_key_path: None,
+        })
+        .build();
+
+    let sealed_backup = crypto_component
+        .export_sealed_backup()
+        .expect("failed to export sealed backup");
+
+    assert_eq!(sealed_backup.threshold(), 1);
+    let associated_data = b"ic-crypto-sealed-node-key-backup";
+    assert_matches!(
+        sealed_backup.decrypt_share(associated_data, 0, &private_key),
+        Ok(_)
+    );
+    assert_matches!(
+        sealed_backup.decrypt_share(associated_data, 0, &other_private_key),
+        Err(KeyEscrowError::InvalidRecipient { index: 0 })
+    );
+}
@@ -13,19 +13,28 @@
 #![deny(clippy::unwrap_used)]
 
 mod common;
+mod key_escrow;
 mod keygen;
 mod sign;
 mod tls;
+mod vetkd;
 
+pub use key_escrow::{
+    decrypt_recovery_share, recover_node_secret_keys, EscrowedNodeSecretKeys, EscrowedSecret,
+    KeyEscrowError, KeyRecoveryError, RecoveryShare, RECOVERY_KEY_CURVE,
+};
 pub use sign::utils::{
-    ecdsa_p256_signature_from_der_bytes, ed25519_public_key_to_der, rsa_signature_from_bytes,
+    ecdsa_p256_signature_from_der_bytes, ecdsa_secp256k1_public_key_from_sec1_bytes,
+    ecdsa_secp256k1_signature_from_der_bytes, ed25519_public_key_to_der, rsa_signature_from_bytes,
     threshold_sig_public_key_from_der, threshold_sig_public_key_to_der, user_public_key_from_bytes,
-    verify_combined_threshold_sig, KeyBytesContentType,
+    verify_canister_sig, verify_certified_response, verify_combined_threshold_sig,
+    KeyBytesContentType,
 };
 pub use sign::{get_mega_pubkey, get_tecdsa_master_public_key, MegaKeyFromRegistryError};
 
 use crate::sign::ThresholdSigDataStoreImpl;
-use ic_config::crypto::CryptoConfig;
+use crate::tls::{validated_tls_policy, TlsSessionCache};
+use ic_config::crypto::{CryptoConfig, TlsPolicy};
 use ic_crypto_internal_csp::api::CspPublicKeyStore;
 use ic_crypto_internal_csp::{CryptoServiceProvider, Csp};
 use ic_crypto_internal_logmon::metrics::CryptoMetrics;
@@ -95,6 +104,9 @@ impl<T> CryptoComponentForNonReplicaProcess for T where
 /// handshakes.
 pub struct CryptoComponentImpl<C: CryptoServiceProvider> {
     lockable_threshold_sig_data_store: LockableThresholdSigDataStore,
+    // The TLS sessions of this node's handshakes, which can be resumed.
+    tls_session_cache: TlsSessionCache,
+    tls_policy: TlsPolicy,
     csp: C,
     registry_client: Arc<dyn RegistryClient>,
     // The node id of the node that instantiated this crypto component.
@@ -144,6 +156,8 @@ impl<C: CryptoServiceProvider> CryptoComponentImpl<C> {
     ) -> Self {
         CryptoComponentImpl {
             lockable_threshold_sig_data_store: LockableThresholdSigDataStore::new(),
+            tls_session_cache: TlsSessionCache::new(),
+            tls_policy: TlsPolicy::default(),
             csp,
             registry_client,
             node_id,
@@ -180,20 +194,22 @@ impl CryptoComponentImpl<Csp> {
     /// as this will lead to concurrency issues e.g. when the components
     /// access the secret key store simultaneously.
     ///
-    /// If the `config`'s vault type is `UnixSocket`, a `tokio_runtime_handle`
-    /// must be provided, which is then used for the `async`hronous
-    /// communication with the vault via RPC for secret key operations. In most
-    /// cases, this is done by calling `tokio::runtime::Handle::block_on` and
-    /// it is the caller's responsibility to ensure that these calls to
-    /// `block_on` do not panic. This can be achieved, for example, by ensuring
-    /// that the crypto component's methods are not themselves called from
-    /// within a call to `block_on` (because calls to `block_on` cannot be
-    /// nested), or by wrapping them with `tokio::task::block_in_place`
-    /// and accepting the performance implications.
+    /// If the `config`'s vault type is `UnixSocket` or `Tcp`, a
+    /// `tokio_runtime_handle` must be provided, which is then used for the
+    /// `async`hronous communication with the vault via RPC for secret key
+    /// operations. In most cases, this is done by calling
+    /// `tokio::runtime::Handle::block_on` and it is the caller's
+    /// responsibility to ensure that these calls to `block_on` do not panic.
+    /// This can be achieved, for example, by ensuring that the crypto
+    /// component's methods are not themselves called from within a call to
+    /// `block_on` (because calls to `block_on` cannot be nested), or by
+    /// wrapping them with `tokio::task::block_in_place` and accepting the
+    /// performance implications.
     ///
     /// # Panics
-    /// Panics if the `config`'s vault type is `UnixSocket` and
-    /// `tokio_runtime_handle` is `None`.
+    /// Panics if the `config`'s vault type is `UnixSocket` or `Tcp` and
+    /// `tokio_runtime_handle` is `None`, or if the `config`'s TLS policy is
+    /// invalid.
     ///
     /// ```
     /// use ic_config::crypto::CryptoConfig;
@@ -243,6 +259,8 @@ impl CryptoComponentImpl<Csp> {
         let latest_registry_version = registry_client.get_latest_version();
         let crypto_component = CryptoComponentImpl {
             lockable_threshold_sig_data_store: LockableThresholdSigDataStore::new(),
+            tls_session_cache: TlsSessionCache::new(),
+            tls_policy: validated_tls_policy(config),
             csp,
             registry_client,
             node_id,
@@ -257,8 +275,9 @@ impl CryptoComponentImpl<Csp> {
     /// Creates a crypto component using a fake `node_id`.
     ///
     /// # Panics
-    /// Panics if the `config`'s vault type is `UnixSocket` and
-    /// `tokio_runtime_handle` is `None`.
+    /// Panics if the `config`'s vault type is `UnixSocket` or `Tcp` and
+    /// `tokio_runtime_handle` is `None`, or if the `config`'s TLS policy is
+    /// invalid.
     pub fn new_with_fake_node_id(
         config: &CryptoConfig,
         tokio_runtime_handle: Option<tokio::runtime::Handle>,
@@ -270,6 +289,8 @@ impl CryptoComponentImpl<Csp> {
         let metrics = Arc::new(CryptoMetrics::none());
         CryptoComponentImpl {
             lockable_threshold_sig_data_store: LockableThresholdSigDataStore::new(),
+            tls_session_cache: TlsSessionCache::new(),
+            tls_policy: validated_tls_policy(config),
             csp: Csp::new(
                 config,
                 tokio_runtime_handle,
@@ -290,16 +311,17 @@ impl CryptoComponentImpl<Csp> {
     /// Please refer to the trait documentation of
     /// `CryptoComponentForNonReplicaProcess` for more details.
     ///
-    /// If the `config`'s vault type is `UnixSocket`, a `tokio_runtime_handle`
-    /// must be provided, which is then used for the `async`hronous
-    /// communication with the vault via RPC for secret key operations. In most
-    /// cases, this is done by calling `tokio::runtime::Handle::block_on` and
-    /// it is the caller's responsibility to ensure that these calls to
-    /// `block_on` do not panic. This can be achieved, for example, by ensuring
-    /// that the crypto component's methods are not themselves called from
-    /// within a call to `block_on` (because calls to `block_on` cannot be
-    /// nested), or by wrapping them with `tokio::task::block_in_place`
-    /// and accepting the performance implications.
+    /// If the `config`'s vault type is `UnixSocket` or `Tcp`, a
+    /// `tokio_runtime_handle` must be provided, which is then used for the
+    /// `async`hronous communication with the vault via RPC for secret key
+    /// operations. In most cases, this is done by calling
+    /// `tokio::runtime::Handle::block_on` and it is the caller's
+    /// responsibility to ensure that these calls to `block_on` do not panic.
+    /// This can be achieved, for example, by ensuring that the crypto
+    /// component's methods are not themselves called from within a call to
+    /// `block_on` (because calls to `block_on` cannot be nested), or by
+    /// wrapping them with `tokio::task::block_in_place` and accepting the
+    /// performance implications.
     /// Because the asynchronous communication with the vault happens only for
     /// secret key operations, for the `CryptoComponentImpl` the concerned
     /// methods are
@@ -314,8 +336,9 @@ impl CryptoComponentImpl<Csp> {
     /// by calling `new` instead of `new_for_non_replica_process`.
     ///
     /// # Panics
-    /// Panics if the `config`'s vault type is `UnixSocket` and
-    /// `tokio_runtime_handle` is `None`.
+    /// Panics if the `config`'s vault type is `UnixSocket` or `Tcp` and
+    /// `tokio_runtime_handle` is `None`, or if the `config`'s TLS policy is
+    /// invalid.
     pub fn new_for_non_replica_process(
         config: &CryptoConfig,
         tokio_runtime_handle: Option<tokio::runtime::Handle>,
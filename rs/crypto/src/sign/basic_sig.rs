@@ -108,14 +108,65 @@ impl BasicSignerInternal {
         signer: NodeId,
         registry_version: RegistryVersion,
     ) -> CryptoResult<BasicSigOf<H>> {
+        let (algorithm_id, key_id) = Self::signing_key(registry, signer, registry_version)?;
+        let csp_sig = csp_signer.sign(algorithm_id, &message.as_signed_bytes(), key_id)?;
+
+        Ok(BasicSigOf::new(BasicSig(csp_sig.as_ref().to_vec())))
+    }
+
+    pub fn sign_basic_batch<S: CspSigner, H: Signable>(
+        csp_signer: &S,
+        registry: &dyn RegistryClient,
+        messages: &[H],
+        signer: NodeId,
+        registry_version: RegistryVersion,
+    ) -> CryptoResult<Vec<BasicSigOf<H>>> {
+        let (algorithm_id, key_id) = Self::signing_key(registry, signer, registry_version)?;
+        let messages: Vec<Vec<u8>> = messages
+            .iter()
+            .map(|message| message.as_signed_bytes())
+            .collect();
+        let csp_sigs = csp_signer.sign_batch(algorithm_id, &messages, key_id)?;
+
+        Ok(csp_sigs
+            .into_iter()
+            .map(|csp_sig| BasicSigOf::new(BasicSig(csp_sig.as_ref().to_vec())))
+            .collect())
+    }
+
+    /// Like `sign_basic`, but signs on a blocking thread of the Tokio runtime
+    /// with a clone of `csp_signer`.
+    pub async fn sign_basic_async<S: CspSigner + Clone + Send + 'static, H: Signable>(
+        csp_signer: &S,
+        registry: &dyn RegistryClient,
+        message: &H,
+        signer: NodeId,
+        registry_version: RegistryVersion,
+    ) -> CryptoResult<BasicSigOf<H>> {
+        let (algorithm_id, key_id) = Self::signing_key(registry, signer, registry_version)?;
+        let csp_signer = csp_signer.clone();
+        let message = message.as_signed_bytes();
+        let csp_sig =
+            tokio::task::spawn_blocking(move || csp_signer.sign(algorithm_id, &message, key_id))
+                .await
+                .map_err(|e| CryptoError::InternalError {
+                    internal_error: format!("failed to sign on a blocking thread: {}", e),
+                })??;
+
+        Ok(BasicSigOf::new(BasicSig(csp_sig.as_ref().to_vec())))
+    }
+
+    fn signing_key(
+        registry: &dyn RegistryClient,
+        signer: NodeId,
+        registry_version: RegistryVersion,
+    ) -> CryptoResult<(AlgorithmId, KeyId)> {
         let pk_proto =
             key_from_registry(registry, signer, KeyPurpose::NodeSigning, registry_version)?;
         let algorithm_id = AlgorithmId::from(pk_proto.algorithm);
         let csp_pk = CspPublicKey::try_from(pk_proto)?;
         let key_id = KeyId::try_from(&csp_pk)?;
-        let csp_sig = csp_signer.sign(algorithm_id, &message.as_signed_bytes(), key_id)?;
-
-        Ok(BasicSigOf::new(BasicSig(csp_sig.as_ref().to_vec())))
+        Ok((algorithm_id, key_id))
     }
 }
 
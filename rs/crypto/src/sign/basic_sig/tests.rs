@@ -16,6 +16,8 @@ mod sign_basic {
     use super::*;
     use crate::common::test_utils::basic_sig;
     use crate::common::test_utils::basic_sig::TestVector::ED25519_STABILITY_1;
+    use ic_crypto_temp_crypto::NodeKeysToGenerate;
+    use ic_crypto_temp_crypto::TempCryptoComponent;
 
     #[test]
     fn should_fail_with_key_not_found_if_public_key_not_found_in_registry() {
@@ -80,6 +82,107 @@ mod sign_basic {
         assert_matches!(result, Ok(signature)
             if signature == BasicSigOf::new(BasicSig(expected_signature.as_ref().to_vec())));
     }
+
+    #[test]
+    fn should_sign_asynchronously_with_remote_vault_within_tokio_runtime() {
+        let crypto_component = TempCryptoComponent::builder()
+            .with_keys_in_registry_version(NodeKeysToGenerate::only_node_signing_key(), REG_V2)
+            .with_remote_vault()
+            .with_node_id(NODE_1)
+            .build();
+        let msg = SignableMock::new(b"message".to_vec());
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let result = rt.block_on(crypto_component.sign_basic_async(&msg, NODE_1, REG_V2));
+
+        assert_matches!(result, Ok(signature)
+            if crypto_component.verify_basic_sig(&signature, &msg, NODE_1, REG_V2).is_ok());
+    }
+}
+
+mod sign_basic_batch {
+    use super::*;
+    use crate::common::test_utils::basic_sig;
+    use crate::common::test_utils::basic_sig::TestVector::ED25519_STABILITY_1;
+    use ic_crypto_temp_crypto::NodeKeysToGenerate;
+    use ic_crypto_temp_crypto::TempCryptoComponent;
+
+    #[test]
+    fn should_fail_with_key_not_found_if_public_key_not_found_in_registry() {
+        let crypto = crypto_component_with_csp(
+            MockAllCryptoServiceProvider::new(),
+            registry_returning_none(),
+        );
+
+        let result = crypto.sign_basic_batch(&[SignableMock::new(vec![])], NODE_1, REG_V1);
+
+        assert_matches!(result, Err(CryptoError::PublicKeyNotFound { .. }));
+    }
+
+    #[test]
+    fn should_delegate_to_csp_to_sign_all_messages_at_once() {
+        use ic_crypto_internal_basic_sig_ed25519::types as ed25519_types;
+        let (_, pk, _, _) = basic_sig::testvec(ED25519_STABILITY_1);
+        let key_record =
+            node_signing_record_with(NODE_1, pk.ed25519_bytes().unwrap().to_vec(), REG_V2);
+        let mut csp = MockAllCryptoServiceProvider::new();
+        let expected_signatures = vec![
+            CspSignature::Ed25519(ed25519_types::SignatureBytes([42; 64])),
+            CspSignature::Ed25519(ed25519_types::SignatureBytes([43; 64])),
+        ];
+        let messages = [
+            SignableMock::new(b"first".to_vec()),
+            SignableMock::new(b"second".to_vec()),
+        ];
+        let expected_msgs: Vec<Vec<u8>> = messages
+            .iter()
+            .map(|message| message.as_signed_bytes())
+            .collect();
+        csp.expect_sign_batch()
+            .withf(move |algorithm_id, msgs, _key_id| {
+                *algorithm_id == AlgorithmId::Ed25519 && msgs == expected_msgs.as_slice()
+            })
+            .times(1)
+            .return_const(Ok(expected_signatures.clone()));
+        let crypto = crypto_component_with_csp(csp, registry_with(key_record));
+
+        let result = crypto.sign_basic_batch(&messages, NODE_1, REG_V2);
+
+        assert_eq!(
+            result.unwrap(),
+            expected_signatures
+                .iter()
+                .map(|signature| BasicSigOf::new(BasicSig(signature.as_ref().to_vec())))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_create_same_signatures_as_sign_basic_with_remote_vault() {
+        let crypto_component = TempCryptoComponent::builder()
+            .with_keys_in_registry_version(NodeKeysToGenerate::only_node_signing_key(), REG_V2)
+            .with_remote_vault()
+            .with_node_id(NODE_1)
+            .build();
+        let messages = [
+            SignableMock::new(b"first message".to_vec()),
+            SignableMock::new(b"second message".to_vec()),
+        ];
+
+        let signatures = crypto_component
+            .sign_basic_batch(&messages, NODE_1, REG_V2)
+            .expect("failed to sign batch");
+
+        assert_eq!(signatures.len(), messages.len());
+        for (message, signature) in messages.iter().zip(signatures) {
+            assert_eq!(
+                signature,
+                crypto_component
+                    .sign_basic(message, NODE_1, REG_V2)
+                    .expect("failed to sign")
+            );
+        }
+    }
 }
 
 mod verify_basic_sig {
@@ -1,7 +1,4 @@
 use super::*;
-use ic_crypto_internal_basic_sig_iccsa as iccsa;
-use ic_crypto_internal_basic_sig_iccsa::types::PublicKeyBytes;
-use ic_crypto_internal_basic_sig_iccsa::types::SignatureBytes;
 use ic_registry_client_helpers::{crypto::CryptoRegistry, subnet::SubnetRegistry};
 use ic_types::crypto::threshold_sig::ThresholdSigPublicKey;
 
@@ -14,12 +11,7 @@ pub fn verify_canister_sig<S: Signable>(
 ) -> CryptoResult<()> {
     ensure_correct_algorithm_id(user_public_key.algorithm_id)?;
     let root_subnet_pubkey = get_root_subnet_pubkey(registry, registry_version)?;
-    iccsa::verify(
-        &message.as_signed_bytes(),
-        SignatureBytes(signature.get_ref().0.clone()),
-        PublicKeyBytes(user_public_key.key.clone()),
-        &root_subnet_pubkey,
-    )
+    utils::verify_canister_sig(message, signature, user_public_key, &root_subnet_pubkey)
 }
 
 fn ensure_correct_algorithm_id(algorithm_id: AlgorithmId) -> CryptoResult<()> {
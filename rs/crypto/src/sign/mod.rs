@@ -5,14 +5,15 @@ use crate::sign::basic_sig::{BasicSignVerifierByPublicKeyInternal, BasicSignerIn
 use crate::sign::multi_sig::MultiSigVerifierInternal;
 use crate::sign::multi_sig::MultiSignerInternal;
 use crate::sign::threshold_sig::{ThresholdSigVerifierInternal, ThresholdSignerInternal};
+use async_trait::async_trait;
 pub use canister_threshold_sig::ecdsa::get_tecdsa_master_public_key;
 use ic_crypto_internal_csp::types::{CspPublicKey, CspSignature};
 use ic_crypto_internal_csp::CryptoServiceProvider;
 use ic_crypto_internal_threshold_sig_bls12381::api::bls_signature_cache_statistics;
 use ic_interfaces::crypto::{
-    BasicSigVerifier, BasicSigVerifierByPublicKey, BasicSigner, CanisterSigVerifier,
-    MultiSigVerifier, MultiSigner, ThresholdEcdsaSigVerifier, ThresholdEcdsaSigner,
-    ThresholdSigVerifier, ThresholdSigVerifierByPublicKey, ThresholdSigner,
+    AsyncBasicSigner, BasicSigVerifier, BasicSigVerifierByPublicKey, BasicSigner,
+    CanisterSigVerifier, MultiSigVerifier, MultiSigner, ThresholdEcdsaSigVerifier,
+    ThresholdEcdsaSigner, ThresholdSigVerifier, ThresholdSigVerifierByPublicKey, ThresholdSigner,
 };
 use ic_logger::{debug, new_logger};
 use ic_types::crypto::canister_threshold_sig::error::{
@@ -96,6 +97,96 @@ impl<C: CryptoServiceProvider, H: Signable> BasicSigner<H> for CryptoComponentIm
         );
         result
     }
+
+    fn sign_basic_batch(
+        &self,
+        messages: &[H],
+        signer: NodeId,
+        registry_version: RegistryVersion,
+    ) -> CryptoResult<Vec<BasicSigOf<H>>> {
+        let log_id = get_log_id(&self.logger, module_path!());
+        let logger = new_logger!(&self.logger;
+            crypto.log_id => log_id,
+            crypto.trait_name => "BasicSigner",
+            crypto.method_name => "sign_basic_batch",
+        );
+        debug!(logger;
+            crypto.description => "start",
+            crypto.registry_version => registry_version.get(),
+            crypto.signer => format!("{:?}", signer),
+        );
+        let start_time = self.metrics.now();
+        let result = BasicSignerInternal::sign_basic_batch(
+            &self.csp,
+            self.registry_client.as_ref(),
+            messages,
+            signer,
+            registry_version,
+        );
+        self.metrics.observe_duration_seconds(
+            MetricsDomain::BasicSignature,
+            MetricsScope::Full,
+            "sign_basic_batch",
+            MetricsResult::from(&result),
+            start_time,
+        );
+        debug!(logger;
+            crypto.description => "end",
+            crypto.is_ok => result.is_ok(),
+            crypto.error => log_err(result.as_ref().err()),
+        );
+        result
+    }
+}
+
+#[async_trait]
+impl<C, H> AsyncBasicSigner<H> for CryptoComponentImpl<C>
+where
+    C: CryptoServiceProvider + Clone + Send + Sync + 'static,
+    H: Signable + Sync,
+{
+    async fn sign_basic_async(
+        &self,
+        message: &H,
+        signer: NodeId,
+        registry_version: RegistryVersion,
+    ) -> CryptoResult<BasicSigOf<H>> {
+        let log_id = get_log_id(&self.logger, module_path!());
+        let logger = new_logger!(&self.logger;
+            crypto.log_id => log_id,
+            crypto.trait_name => "AsyncBasicSigner",
+            crypto.method_name => "sign_basic_async",
+        );
+        debug!(logger;
+            crypto.description => "start",
+            crypto.registry_version => registry_version.get(),
+            crypto.signed_bytes => format!("0x{}", hex::encode(message.as_signed_bytes())),
+            crypto.signer => format!("{:?}", signer),
+        );
+        let start_time = self.metrics.now();
+        let result = BasicSignerInternal::sign_basic_async(
+            &self.csp,
+            self.registry_client.as_ref(),
+            message,
+            signer,
+            registry_version,
+        )
+        .await;
+        self.metrics.observe_duration_seconds(
+            MetricsDomain::BasicSignature,
+            MetricsScope::Full,
+            "sign_basic_async",
+            MetricsResult::from(&result),
+            start_time,
+        );
+        debug!(logger;
+            crypto.description => "end",
+            crypto.is_ok => result.is_ok(),
+            crypto.error => log_err(result.as_ref().err()),
+            crypto.signature => log_ok_content(&result),
+        );
+        result
+    }
 }
 
 impl<C: CryptoServiceProvider, H: Signable> BasicSigVerifier<H> for CryptoComponentImpl<C> {
@@ -841,7 +932,7 @@ impl<C: CryptoServiceProvider> ThresholdEcdsaSigVerifier for CryptoComponentImpl
     }
 }
 
-fn log_err<T: fmt::Display>(error_option: Option<&T>) -> String {
+pub fn log_err<T: fmt::Display>(error_option: Option<&T>) -> String {
     if let Some(error) = error_option {
         return format!("{}", error);
     }
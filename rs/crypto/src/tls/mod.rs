@@ -15,6 +15,7 @@ use std::str::FromStr;
 use tokio::net::TcpStream;
 
 mod rustls;
+pub(crate) use rustls::session_cache::TlsSessionCache;
 #[cfg(test)]
 mod tests;
 
@@ -28,6 +29,7 @@ where
         tcp_stream: TcpStream,
         allowed_clients: AllowedClients,
         registry_version: RegistryVersion,
+        alpn_protocols: Vec<Vec<u8>>,
     ) -> Result<(Box<dyn TlsStream>, AuthenticatedPeer), TlsServerHandshakeError> {
         let log_id = get_log_id(&self.logger, module_path!());
         let logger = new_logger!(&self.logger;
@@ -45,9 +47,12 @@ where
             &self.csp,
             self.node_id,
             Arc::clone(&self.registry_client),
+            &self.tls_session_cache,
+            &self.tls_policy,
             tcp_stream,
             allowed_clients,
             registry_version,
+            alpn_protocols,
         )
         .await;
         self.metrics.observe_duration_seconds(
@@ -86,6 +91,7 @@ where
             &self.csp,
             self.node_id,
             self.registry_client.as_ref(),
+            &self.tls_policy,
             tcp_stream,
             registry_version,
         )
@@ -110,6 +116,7 @@ where
         tcp_stream: TcpStream,
         server: NodeId,
         registry_version: RegistryVersion,
+        alpn_protocols: Vec<Vec<u8>>,
     ) -> Result<Box<dyn TlsStream>, TlsClientHandshakeError> {
         let log_id = get_log_id(&self.logger, module_path!());
         let logger = new_logger!(&self.logger;
@@ -127,9 +134,12 @@ where
             &self.csp,
             self.node_id,
             Arc::clone(&self.registry_client),
+            &self.tls_session_cache,
+            &self.tls_policy,
             tcp_stream,
             server,
             registry_version,
+            alpn_protocols,
         )
         .await;
         self.metrics.observe_duration_seconds(
@@ -146,6 +156,55 @@ where
         );
         result
     }
+
+    async fn perform_tls_client_handshake_with_pinned_cert(
+        &self,
+        tcp_stream: TcpStream,
+        server_cert: TlsPublicKeyCert,
+    ) -> Result<Box<dyn TlsStream>, TlsClientHandshakeError> {
+        let log_id = get_log_id(&self.logger, module_path!());
+        let logger = new_logger!(&self.logger;
+            crypto.log_id => log_id,
+            crypto.trait_name => "TlsHandshake",
+            crypto.method_name => "perform_tls_client_handshake_with_pinned_cert",
+        );
+        debug!(logger;
+            crypto.description => "start",
+        );
+        let start_time = self.metrics.now();
+        let result = rustls::client_handshake::perform_tls_client_handshake_with_pinned_cert(
+            &self.csp,
+            &self.tls_policy,
+            tcp_stream,
+            server_cert,
+        )
+        .await;
+        self.metrics.observe_duration_seconds(
+            MetricsDomain::TlsHandshake,
+            MetricsScope::Full,
+            "perform_tls_client_handshake_with_pinned_cert",
+            MetricsResult::from(&result),
+            start_time,
+        );
+        debug!(logger;
+            crypto.description => "end",
+            crypto.is_ok => result.is_ok(),
+            crypto.error => log_err(result.as_ref().err()),
+        );
+        result
+    }
+}
+
+/// Returns the TLS policy of `config`.
+///
+/// # Panics
+/// If the policy is invalid, since no TLS handshake could succeed.
+pub(crate) fn validated_tls_policy(config: &CryptoConfig) -> TlsPolicy {
+    config
+        .tls_policy
+        .validate()
+        .unwrap_or_else(|e| panic!("Invalid TLS policy: {}", e));
+    config.tls_policy.clone()
 }
 
 fn node_id_from_cert_subject_common_name(
@@ -13,7 +13,9 @@ mod cert_resolver;
 pub mod client_handshake;
 mod csp_server_signing_key;
 mod node_cert_verifier;
+mod policy;
 pub mod server_handshake;
+pub mod session_cache;
 
 fn certified_key(
     self_tls_cert: TlsPublicKeyCert,
@@ -38,7 +40,11 @@ impl RustlsTlsStream {
     }
 }
 
-impl TlsStream for RustlsTlsStream {}
+impl TlsStream for RustlsTlsStream {
+    fn negotiated_alpn_protocol(&self) -> Option<&[u8]> {
+        self.tls_stream.get_ref().1.alpn_protocol()
+    }
+}
 
 impl AsyncRead for RustlsTlsStream {
     fn poll_read(
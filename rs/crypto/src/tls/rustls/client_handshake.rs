@@ -1,21 +1,24 @@
 use crate::tls::rustls::cert_resolver::StaticCertResolver;
 use crate::tls::rustls::csp_server_signing_key::CspServerEd25519SigningKey;
-use crate::tls::rustls::node_cert_verifier::NodeServerCertVerifier;
+use crate::tls::rustls::node_cert_verifier::{NodeServerCertVerifier, PinnedServerCertVerifier};
+use crate::tls::rustls::policy::{cipher_suites, kx_groups, protocol_versions};
+use crate::tls::rustls::session_cache::{session_cache_server_name, TlsSessionCache};
 use crate::tls::rustls::{certified_key, RustlsTlsStream};
 use crate::tls::{tls_cert_from_registry, TlsCertFromRegistryError};
-use ic_crypto_internal_csp::api::CspTlsHandshakeSignerProvider;
+use ic_config::crypto::TlsPolicy;
+use ic_crypto_internal_csp::api::{CspPublicKeyStore, CspTlsHandshakeSignerProvider};
 use ic_crypto_internal_csp::key_id::KeyId;
-use ic_crypto_tls_interfaces::{SomeOrAllNodes, TlsClientHandshakeError, TlsStream};
+use ic_crypto_tls_interfaces::{
+    SomeOrAllNodes, TlsClientHandshakeError, TlsPublicKeyCert, TlsStream,
+};
 use ic_interfaces_registry::RegistryClient;
 use ic_types::{NodeId, RegistryVersion};
 use std::sync::Arc;
 use tokio::net::TcpStream;
 use tokio_rustls::{
     rustls::{
-        cipher_suite::{TLS13_AES_128_GCM_SHA256, TLS13_AES_256_GCM_SHA384},
-        client::ResolvesClientCert,
+        client::{ResolvesClientCert, ServerCertVerifier},
         sign::CertifiedKey,
-        version::TLS13,
         ClientConfig, ServerName, SignatureScheme,
     },
     TlsConnector,
@@ -25,9 +28,12 @@ pub async fn perform_tls_client_handshake<P: CspTlsHandshakeSignerProvider>(
     signer_provider: &P,
     self_node_id: NodeId,
     registry_client: Arc<dyn RegistryClient>,
+    session_cache: &TlsSessionCache,
+    tls_policy: &TlsPolicy,
     tcp_stream: TcpStream,
     server: NodeId,
     registry_version: RegistryVersion,
+    alpn_protocols: Vec<Vec<u8>>,
 ) -> Result<Box<dyn TlsStream>, TlsClientHandshakeError> {
     let self_tls_cert =
         tls_cert_from_registry(registry_client.as_ref(), self_node_id, registry_version)?;
@@ -43,18 +49,92 @@ pub async fn perform_tls_client_handshake<P: CspTlsHandshakeSignerProvider>(
         registry_client,
         registry_version,
     );
-    let config = ClientConfig::builder()
-        .with_cipher_suites(&[TLS13_AES_256_GCM_SHA384, TLS13_AES_128_GCM_SHA256])
-        .with_safe_default_kx_groups()
-        .with_protocol_versions(&[&TLS13])
+    let mut config = client_config(
+        tls_policy,
+        Arc::new(server_cert_verifier),
+        self_tls_cert,
+        ed25519_signing_key,
+    );
+    config.resumption = session_cache.client_resumption();
+    config.alpn_protocols = alpn_protocols;
+
+    connect(
+        tcp_stream,
+        config,
+        session_cache_server_name(server, registry_version),
+    )
+    .await
+}
+
+pub async fn perform_tls_client_handshake_with_pinned_cert<
+    P: CspTlsHandshakeSignerProvider + CspPublicKeyStore,
+>(
+    csp: &P,
+    tls_policy: &TlsPolicy,
+    tcp_stream: TcpStream,
+    server_cert: TlsPublicKeyCert,
+) -> Result<Box<dyn TlsStream>, TlsClientHandshakeError> {
+    let self_tls_cert = tls_cert_from_public_key_store(csp)?;
+    let self_tls_cert_key_id = KeyId::try_from(&self_tls_cert).map_err(|error| {
+        TlsClientHandshakeError::MalformedSelfCertificate {
+            internal_error: format!("Cannot instantiate KeyId: {:?}", error),
+        }
+    })?;
+    let ed25519_signing_key =
+        CspServerEd25519SigningKey::new(self_tls_cert_key_id, csp.handshake_signer());
+    let config = client_config(
+        tls_policy,
+        Arc::new(PinnedServerCertVerifier::new(server_cert)),
+        self_tls_cert,
+        ed25519_signing_key,
+    );
+
+    connect(tcp_stream, config, pinned_cert_server_name()).await
+}
+
+fn client_config(
+    tls_policy: &TlsPolicy,
+    server_cert_verifier: Arc<dyn ServerCertVerifier>,
+    self_tls_cert: TlsPublicKeyCert,
+    ed25519_signing_key: CspServerEd25519SigningKey,
+) -> ClientConfig {
+    ClientConfig::builder()
+        .with_cipher_suites(&cipher_suites(tls_policy))
+        .with_kx_groups(&kx_groups(tls_policy))
+        .with_protocol_versions(&protocol_versions())
         .expect("Valid rustls client config.")
-        .with_custom_certificate_verifier(Arc::new(server_cert_verifier))
+        .with_custom_certificate_verifier(server_cert_verifier)
         .with_client_cert_resolver(static_cert_resolver(
             certified_key(self_tls_cert, ed25519_signing_key),
             SignatureScheme::ED25519,
-        ));
+        ))
+}
+
+fn tls_cert_from_public_key_store<P: CspPublicKeyStore>(
+    csp: &P,
+) -> Result<TlsPublicKeyCert, TlsClientHandshakeError> {
+    let raw_cert = csp
+        .current_node_public_keys()
+        .map_err(|e| TlsClientHandshakeError::SelfCertificateUnavailable {
+            internal_error: format!("Failed to read the public key store: {:?}", e),
+        })?
+        .tls_certificate
+        .ok_or_else(|| TlsClientHandshakeError::SelfCertificateUnavailable {
+            internal_error: "The public key store contains no TLS certificate".to_string(),
+        })?;
+    TlsPublicKeyCert::try_from(raw_cert).map_err(|e| {
+        TlsClientHandshakeError::MalformedSelfCertificate {
+            internal_error: e.internal_error,
+        }
+    })
+}
 
-    connect(tcp_stream, config).await
+/// Returns the server name of handshakes with a pinned certificate. The name
+/// is not used for verification, and since the name does not identify the
+/// server, the sessions of such handshakes are not cached in the shared
+/// session cache.
+fn pinned_cert_server_name() -> ServerName {
+    ServerName::try_from("pinned-cert.invalid").expect("failed to create server name")
 }
 
 fn static_cert_resolver(key: CertifiedKey, scheme: SignatureScheme) -> Arc<dyn ResolvesClientCert> {
@@ -67,12 +147,12 @@ fn static_cert_resolver(key: CertifiedKey, scheme: SignatureScheme) -> Arc<dyn R
 async fn connect(
     tcp_stream: TcpStream,
     config: ClientConfig,
+    // Hostname verification is disabled, so the server name only determines
+    // which cached sessions may be resumed.
+    server_name: ServerName,
 ) -> Result<Box<dyn TlsStream>, TlsClientHandshakeError> {
-    let irrelevant_domain =
-        ServerName::try_from("domain.is-irrelevant-as-hostname-verification-is.disabled")
-            .expect("failed to create domain");
     let tls_stream = TlsConnector::from(Arc::new(config))
-        .connect(irrelevant_domain, tcp_stream)
+        .connect(server_name, tcp_stream)
         .await
         .map_err(|e| TlsClientHandshakeError::HandshakeError {
             internal_error: format!("{}", e),
@@ -50,6 +50,25 @@ impl NodeServerCertVerifier {
     }
 }
 
+/// Implements `ServerCertVerifier` without consulting the registry. The peer
+/// certificate is considered trusted if the following conditions hold:
+/// * No intermediate certificates.
+/// * The end entitiy certificate can be parsed from DER.
+/// * The end entity certificate subject CN can be parsed as a `NodeId`.
+/// * The end entity certificate equals the `pinned_cert` (as passed to `new`).
+///
+/// If any of these conditions does not hold, a `TLSError` is returned.
+pub struct PinnedServerCertVerifier {
+    pinned_cert: TlsPublicKeyCert,
+}
+
+impl PinnedServerCertVerifier {
+    /// Creates a verifier that considers only the `pinned_cert` as trusted.
+    pub fn new(pinned_cert: TlsPublicKeyCert) -> Self {
+        Self { pinned_cert }
+    }
+}
+
 /// Implements `ClientCertVerifier`. The peer
 /// certificate is considered trusted if the following conditions hold:
 /// * No intermediate certificates.
@@ -111,6 +130,21 @@ impl ServerCertVerifier for NodeServerCertVerifier {
     }
 }
 
+impl ServerCertVerifier for PinnedServerCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, TLSError> {
+        verify_pinned_cert(end_entity, intermediates, &self.pinned_cert)
+            .map(|_| ServerCertVerified::assertion())
+    }
+}
+
 impl ClientCertVerifier for NodeClientCertVerifier {
     fn offer_client_auth(&self) -> bool {
         true
@@ -165,6 +199,27 @@ fn verify_node_cert(
     Ok(())
 }
 
+fn verify_pinned_cert(
+    end_entity_der: &Certificate,
+    intermediates: &[Certificate],
+    pinned_cert: &TlsPublicKeyCert,
+) -> Result<(), TLSError> {
+    ensure_intermediate_certs_empty(intermediates)?;
+    let end_entity = cert_from_der(end_entity_der.0.clone())?;
+    let end_entity_node_id = node_id_from_subject_cn(&end_entity)?;
+    if &end_entity != pinned_cert {
+        return Err(TLSError::General(format!(
+            "The peer certificate is not trusted since it differs from the pinned certificate. \
+            NodeId of presented cert: {}",
+            end_entity_node_id
+        )));
+    }
+    // As for certificates from the registry, the validity check is only done
+    // after checking equality to the trusted pinned certificate.
+    ensure_node_certificate_is_valid(end_entity_der.0.clone(), end_entity_node_id)?;
+    Ok(())
+}
+
 fn ensure_intermediate_certs_empty(intermediates: &[Certificate]) -> Result<(), TLSError> {
     if !intermediates.is_empty() {
         return Err(TLSError::General(format!(
@@ -1,9 +1,10 @@
 use crate::tls::rustls::node_cert_verifier::NodeClientCertVerifier;
 use crate::tls::rustls::node_cert_verifier::NodeServerCertVerifier;
+use crate::tls::rustls::node_cert_verifier::PinnedServerCertVerifier;
 use ic_base_types::NodeId;
 use ic_crypto_test_utils::tls::registry::{TlsRegistry, REG_V1};
 use ic_crypto_test_utils::tls::x509_certificates::{x509_public_key_cert, CertWithPrivateKey};
-use ic_crypto_tls_interfaces::SomeOrAllNodes;
+use ic_crypto_tls_interfaces::{SomeOrAllNodes, TlsPublicKeyCert};
 use ic_types_test_utils::ids::{NODE_1, NODE_2, NODE_3};
 use maplit::btreeset;
 use tokio_rustls::rustls::{
@@ -440,3 +441,88 @@ mod server_cert_verifier_tests {
         NodeServerCertVerifier::new(allowed_nodes, registry.get(), REG_V1)
     }
 }
+
+mod pinned_server_cert_verifier_tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+    use tokio_rustls::rustls::ServerName;
+
+    #[test]
+    fn should_return_ok_if_certificate_equals_pinned_certificate() {
+        let node_1_cert = CertWithPrivateKey::builder()
+            .cn(NODE_1.to_string())
+            .build_ed25519();
+        let verifier = verifier_with_pinned_cert(&node_1_cert);
+
+        let result = verifier.verify_server_cert(
+            &Certificate(node_1_cert.cert_der()),
+            &[],
+            &ServerName::try_from("www.irrelevant.com").expect("could not parse DNS name"),
+            &mut [].iter().copied(),
+            &[],
+            UNIX_EPOCH,
+        );
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn should_return_error_if_certificate_differs_from_pinned_certificate() {
+        let presented_node_1_cert = CertWithPrivateKey::builder()
+            .cn(NODE_1.to_string())
+            .build_ed25519();
+        let pinned_node_1_cert_different_from_presented_cert = CertWithPrivateKey::builder()
+            .cn(NODE_1.to_string())
+            .build_ed25519();
+        let verifier = verifier_with_pinned_cert(&pinned_node_1_cert_different_from_presented_cert);
+
+        let result = verifier.verify_server_cert(
+            &Certificate(presented_node_1_cert.cert_der()),
+            &[],
+            &ServerName::try_from("www.irrelevant.com").expect("could not parse DNS name"),
+            &mut [].iter().copied(),
+            &[],
+            UNIX_EPOCH,
+        );
+
+        assert_eq!(
+            result.err(),
+            Some(TLSError::General(
+                "The peer certificate is not trusted since it differs from the pinned certificate. \
+                NodeId of presented cert: 3jo2y-lqbaa-aaaaa-aaaap-2ai"
+                    .to_string(),
+            ))
+        );
+    }
+
+    #[test]
+    fn should_return_error_if_intermediate_certs_not_empty() {
+        let node_1_cert = CertWithPrivateKey::builder()
+            .cn(NODE_1.to_string())
+            .build_ed25519();
+        let verifier = verifier_with_pinned_cert(&node_1_cert);
+
+        let result = verifier.verify_server_cert(
+            &Certificate(node_1_cert.cert_der()),
+            &[Certificate(node_1_cert.cert_der())],
+            &ServerName::try_from("www.irrelevant.com").expect("could not parse DNS name"),
+            &mut [].iter().copied(),
+            &[],
+            UNIX_EPOCH,
+        );
+
+        assert_eq!(
+            result.err(),
+            Some(TLSError::General(
+                "The peer must send exactly one self signed certificate, but it sent 2 certificates."
+                    .to_string(),
+            ))
+        );
+    }
+
+    fn verifier_with_pinned_cert(cert: &CertWithPrivateKey) -> PinnedServerCertVerifier {
+        let pinned_cert = TlsPublicKeyCert::new_from_der(cert.cert_der())
+            .expect("failed to create TlsPublicKeyCert");
+        PinnedServerCertVerifier::new(pinned_cert)
+    }
+}
@@ -0,0 +1,43 @@
+use ic_config::crypto::{TlsCipherSuite, TlsKeyExchangeGroup, TlsPolicy};
+use tokio_rustls::rustls::{
+    cipher_suite::{TLS13_AES_128_GCM_SHA256, TLS13_AES_256_GCM_SHA384},
+    kx_group::{SECP256R1, SECP384R1, X25519},
+    version::TLS13,
+    SupportedCipherSuite, SupportedKxGroup, SupportedProtocolVersion,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Returns the rustls cipher suites allowed by `policy`, in the order of
+/// preference.
+pub fn cipher_suites(policy: &TlsPolicy) -> Vec<SupportedCipherSuite> {
+    policy
+        .cipher_suites
+        .iter()
+        .map(|cipher_suite| match cipher_suite {
+            TlsCipherSuite::Aes256GcmSha384 => TLS13_AES_256_GCM_SHA384,
+            TlsCipherSuite::Aes128GcmSha256 => TLS13_AES_128_GCM_SHA256,
+        })
+        .collect()
+}
+
+/// Returns the rustls key exchange groups allowed by `policy`, in the order
+/// of preference.
+pub fn kx_groups(policy: &TlsPolicy) -> Vec<&'static SupportedKxGroup> {
+    policy
+        .key_exchange_groups
+        .iter()
+        .map(|group| match group {
+            TlsKeyExchangeGroup::X25519 => &X25519,
+            TlsKeyExchangeGroup::Secp256r1 => &SECP256R1,
+            TlsKeyExchangeGroup::Secp384r1 => &SECP384R1,
+        })
+        .collect()
+}
+
+/// Returns the rustls protocol versions, which only include TLS 1.3, the only
+/// version the IC supports, independently of the policy.
+pub fn protocol_versions() -> Vec<&'static SupportedProtocolVersion> {
+    vec![&TLS13]
+}
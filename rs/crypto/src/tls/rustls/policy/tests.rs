@@ -0,0 +1,64 @@
+use crate::tls::rustls::policy::{cipher_suites, kx_groups, protocol_versions};
+use ic_config::crypto::{TlsCipherSuite, TlsKeyExchangeGroup, TlsPolicy};
+use tokio_rustls::rustls::{CipherSuite, NamedGroup, ProtocolVersion};
+
+#[test]
+fn should_allow_aes_cipher_suites_and_safe_default_kx_groups_by_default() {
+    let policy = TlsPolicy::default();
+
+    assert_eq!(
+        cipher_suites(&policy)
+            .iter()
+            .map(|suite| suite.suite())
+            .collect::<Vec<_>>(),
+        vec![
+            CipherSuite::TLS13_AES_256_GCM_SHA384,
+            CipherSuite::TLS13_AES_128_GCM_SHA256
+        ]
+    );
+    assert_eq!(
+        kx_groups(&policy)
+            .iter()
+            .map(|group| group.name)
+            .collect::<Vec<_>>(),
+        vec![
+            NamedGroup::X25519,
+            NamedGroup::secp256r1,
+            NamedGroup::secp384r1
+        ]
+    );
+}
+
+#[test]
+fn should_only_allow_algorithms_of_policy_in_order_of_policy() {
+    let policy = TlsPolicy {
+        cipher_suites: vec![TlsCipherSuite::Aes128GcmSha256],
+        key_exchange_groups: vec![TlsKeyExchangeGroup::Secp384r1, TlsKeyExchangeGroup::X25519],
+    };
+
+    assert_eq!(
+        cipher_suites(&policy)
+            .iter()
+            .map(|suite| suite.suite())
+            .collect::<Vec<_>>(),
+        vec![CipherSuite::TLS13_AES_128_GCM_SHA256]
+    );
+    assert_eq!(
+        kx_groups(&policy)
+            .iter()
+            .map(|group| group.name)
+            .collect::<Vec<_>>(),
+        vec![NamedGroup::secp384r1, NamedGroup::X25519]
+    );
+}
+
+#[test]
+fn should_only_allow_tls_1_3() {
+    assert_eq!(
+        protocol_versions()
+            .iter()
+            .map(|version| version.version)
+            .collect::<Vec<_>>(),
+        vec![ProtocolVersion::TLSv1_3]
+    );
+}
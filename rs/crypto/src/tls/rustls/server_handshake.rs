@@ -1,10 +1,13 @@
 use crate::tls::rustls::cert_resolver::StaticCertResolver;
 use crate::tls::rustls::csp_server_signing_key::CspServerEd25519SigningKey;
 use crate::tls::rustls::node_cert_verifier::NodeClientCertVerifier;
+use crate::tls::rustls::policy::{cipher_suites, kx_groups, protocol_versions};
+use crate::tls::rustls::session_cache::TlsSessionCache;
 use crate::tls::rustls::{certified_key, RustlsTlsStream};
 use crate::tls::{
     node_id_from_cert_subject_common_name, tls_cert_from_registry, TlsCertFromRegistryError,
 };
+use ic_config::crypto::TlsPolicy;
 use ic_crypto_internal_csp::api::CspTlsHandshakeSignerProvider;
 use ic_crypto_internal_csp::key_id::KeyId;
 use ic_crypto_tls_interfaces::{
@@ -13,14 +16,13 @@ use ic_crypto_tls_interfaces::{
 use ic_interfaces_registry::RegistryClient;
 use ic_types::{NodeId, RegistryVersion};
 use std::sync::Arc;
+use std::time::SystemTime;
 use tokio::net::TcpStream;
 use tokio_rustls::{
     rustls::{
-        cipher_suite::{TLS13_AES_128_GCM_SHA256, TLS13_AES_256_GCM_SHA384},
         server::{ClientCertVerifier, NoClientAuth, ResolvesServerCert},
         sign::CertifiedKey,
-        version::TLS13,
-        ServerConfig, SignatureScheme,
+        Certificate, ServerConfig, SignatureScheme,
     },
     TlsAcceptor,
 };
@@ -29,9 +31,12 @@ pub async fn perform_tls_server_handshake<P: CspTlsHandshakeSignerProvider>(
     signer_provider: &P,
     self_node_id: NodeId,
     registry_client: Arc<dyn RegistryClient>,
+    session_cache: &TlsSessionCache,
+    tls_policy: &TlsPolicy,
     tcp_stream: TcpStream,
     allowed_clients: AllowedClients,
     registry_version: RegistryVersion,
+    alpn_protocols: Vec<Vec<u8>>,
 ) -> Result<(Box<dyn TlsStream>, AuthenticatedPeer), TlsServerHandshakeError> {
     let self_tls_cert =
         tls_cert_from_registry(registry_client.as_ref(), self_node_id, registry_version)?;
@@ -40,22 +45,43 @@ pub async fn perform_tls_server_handshake<P: CspTlsHandshakeSignerProvider>(
             internal_error: format!("Cannot instantiate KeyId: {:?}", error),
         }
     })?;
-    let client_cert_verifier = NodeClientCertVerifier::new_with_mandatory_client_auth(
+    let client_cert_verifier = Arc::new(NodeClientCertVerifier::new_with_mandatory_client_auth(
         allowed_clients.nodes().clone(),
         registry_client,
         registry_version,
-    );
+    ));
     let ed25519_signing_key =
         CspServerEd25519SigningKey::new(self_tls_cert_key_id, signer_provider.handshake_signer());
-    let config = server_config_with_tls13_and_aes_ciphersuites_and_ed25519_signing_key(
-        Arc::new(client_cert_verifier),
+    let mut config = server_config_with_tls13_and_ed25519_signing_key(
+        tls_policy,
+        client_cert_verifier.clone(),
         self_tls_cert,
         ed25519_signing_key,
     );
+    let session_store = session_cache.server_session_store();
+    config.session_storage = session_store.clone();
+    config.alpn_protocols = alpn_protocols;
 
     let rustls_stream = accept_connection(tcp_stream, config).await?;
 
     let client_cert_from_handshake = single_client_cert_from_handshake(&rustls_stream)?;
+    if session_store.session_resumed() {
+        // The client certificate of a resumed session was verified when the
+        // session was established, possibly for other allowed clients or at
+        // another registry version, so it must be verified for this handshake.
+        client_cert_verifier
+            .verify_client_cert(
+                &Certificate(client_cert_from_handshake.as_der().clone()),
+                &[],
+                SystemTime::now(),
+            )
+            .map_err(|e| TlsServerHandshakeError::HandshakeError {
+                internal_error: format!(
+                    "failed to verify the client certificate of the resumed session: {}",
+                    e
+                ),
+            })?;
+    }
     let authenticated_peer = node_id_from_cert_subject_common_name(&client_cert_from_handshake)?;
     let tls_stream = RustlsTlsStream::new(tokio_rustls::TlsStream::from(rustls_stream));
 
@@ -69,6 +95,7 @@ pub async fn perform_tls_server_handshake_without_client_auth<P: CspTlsHandshake
     signer_provider: &P,
     self_node_id: NodeId,
     registry_client: &dyn RegistryClient,
+    tls_policy: &TlsPolicy,
     tcp_stream: TcpStream,
     registry_version: RegistryVersion,
 ) -> Result<Box<dyn TlsStream>, TlsServerHandshakeError> {
@@ -80,7 +107,8 @@ pub async fn perform_tls_server_handshake_without_client_auth<P: CspTlsHandshake
     })?;
     let ed25519_signing_key =
         CspServerEd25519SigningKey::new(self_tls_cert_key_id, signer_provider.handshake_signer());
-    let config = server_config_with_tls13_and_aes_ciphersuites_and_ed25519_signing_key(
+    let config = server_config_with_tls13_and_ed25519_signing_key(
+        tls_policy,
         NoClientAuth::boxed(),
         self_tls_cert,
         ed25519_signing_key,
@@ -93,15 +121,16 @@ pub async fn perform_tls_server_handshake_without_client_auth<P: CspTlsHandshake
     )))
 }
 
-fn server_config_with_tls13_and_aes_ciphersuites_and_ed25519_signing_key(
+fn server_config_with_tls13_and_ed25519_signing_key(
+    tls_policy: &TlsPolicy,
     client_cert_verifier: Arc<dyn ClientCertVerifier>,
     self_tls_cert: TlsPublicKeyCert,
     ed25519_signing_key: CspServerEd25519SigningKey,
 ) -> ServerConfig {
     let config = ServerConfig::builder()
-        .with_cipher_suites(&[TLS13_AES_256_GCM_SHA384, TLS13_AES_128_GCM_SHA256])
-        .with_safe_default_kx_groups()
-        .with_protocol_versions(&[&TLS13])
+        .with_cipher_suites(&cipher_suites(tls_policy))
+        .with_kx_groups(&kx_groups(tls_policy))
+        .with_protocol_versions(&protocol_versions())
         .expect("Valid rustls server config.")
         .with_client_cert_verifier(client_cert_verifier)
         .with_cert_resolver(static_cert_resolver(
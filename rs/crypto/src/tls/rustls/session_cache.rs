@@ -0,0 +1,113 @@
+use ic_types::{NodeId, RegistryVersion};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio_rustls::rustls::{
+    client::{ClientSessionMemoryCache, Resumption},
+    server::{ServerSessionMemoryCache, StoresServerSessions},
+    ServerName,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The maximum number of TLS sessions that are cached for resumption, both in
+/// the client and in the server role.
+pub const TLS_SESSION_CACHE_CAPACITY: usize = 1024;
+
+/// Bounded in-memory caches of TLS 1.3 sessions, which allow to resume the
+/// sessions of earlier handshakes with a peer instead of performing a full
+/// handshake.
+///
+/// The caches are shared by all handshakes of a crypto component.
+pub struct TlsSessionCache {
+    client_sessions: Arc<ClientSessionMemoryCache>,
+    server_sessions: Arc<ServerSessionMemoryCache>,
+}
+
+#[allow(clippy::new_without_default)] // we don't need a default impl
+impl TlsSessionCache {
+    pub fn new() -> Self {
+        Self {
+            client_sessions: Arc::new(ClientSessionMemoryCache::new(TLS_SESSION_CACHE_CAPACITY)),
+            server_sessions: ServerSessionMemoryCache::new(TLS_SESSION_CACHE_CAPACITY),
+        }
+    }
+
+    /// Returns the resumption configuration for a client handshake.
+    pub fn client_resumption(&self) -> Resumption {
+        Resumption::store(self.client_sessions.clone())
+    }
+
+    /// Returns the session store for a single server handshake.
+    pub fn server_session_store(&self) -> Arc<ResumptionTrackingSessionStore> {
+        Arc::new(ResumptionTrackingSessionStore {
+            sessions: Arc::clone(&self.server_sessions),
+            resumed: AtomicBool::new(false),
+        })
+    }
+}
+
+/// Returns the server name under which the client caches the sessions with
+/// `server`.
+///
+/// Rustls only resumes a session with the server name that the session was
+/// established with. Since the name includes the `server`'s node ID and
+/// the `registry_version`, a session is only resumed with the node whose
+/// certificate was verified in the full handshake, and only as long as the
+/// certificate is expected to be the one in the registry at the same version.
+pub fn session_cache_server_name(server: NodeId, registry_version: RegistryVersion) -> ServerName {
+    let name = format!(
+        "{}.v{}",
+        hex::encode(server.get().as_slice()),
+        registry_version.get()
+    );
+    ServerName::try_from(name.as_str()).expect("failed to create server name")
+}
+
+/// A session store of a single server handshake, which records whether a
+/// session was found for resumption.
+///
+/// When resuming a session, rustls neither receives the client certificate
+/// nor calls the client certificate verifier, but the certificate from the
+/// resumed session is presented as peer certificate. A session found in the
+/// store thus means that the peer certificate has to be verified again after
+/// the handshake.
+pub struct ResumptionTrackingSessionStore {
+    sessions: Arc<ServerSessionMemoryCache>,
+    resumed: AtomicBool,
+}
+
+impl ResumptionTrackingSessionStore {
+    /// Returns whether a session to resume was taken from the store.
+    ///
+    /// This may also be the case if rustls eventually rejected the resumption
+    /// and performed a full handshake instead.
+    pub fn session_resumed(&self) -> bool {
+        self.resumed.load(Ordering::SeqCst)
+    }
+
+    fn record_resumption(&self, session: Option<Vec<u8>>) -> Option<Vec<u8>> {
+        if session.is_some() {
+            self.resumed.store(true, Ordering::SeqCst);
+        }
+        session
+    }
+}
+
+impl StoresServerSessions for ResumptionTrackingSessionStore {
+    fn put(&self, key: Vec<u8>, value: Vec<u8>) -> bool {
+        self.sessions.put(key, value)
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.record_resumption(self.sessions.get(key))
+    }
+
+    fn take(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.record_resumption(self.sessions.take(key))
+    }
+
+    fn can_cache(&self) -> bool {
+        self.sessions.can_cache()
+    }
+}
@@ -0,0 +1,83 @@
+use crate::tls::rustls::session_cache::{session_cache_server_name, TlsSessionCache};
+use ic_types::{NodeId, PrincipalId, RegistryVersion};
+use ic_types_test_utils::ids::{NODE_1, NODE_2};
+use tokio_rustls::rustls::server::StoresServerSessions;
+
+const REG_V1: RegistryVersion = RegistryVersion::new(1);
+const REG_V2: RegistryVersion = RegistryVersion::new(2);
+
+mod server_session_store {
+    use super::*;
+
+    #[test]
+    fn should_not_record_resumption_if_no_session_taken() {
+        let store = TlsSessionCache::new().server_session_store();
+
+        assert!(store.put(b"session ID".to_vec(), b"session".to_vec()));
+
+        assert!(!store.session_resumed());
+    }
+
+    #[test]
+    fn should_not_record_resumption_if_session_not_found() {
+        let store = TlsSessionCache::new().server_session_store();
+        store.put(b"session ID".to_vec(), b"session".to_vec());
+
+        assert_eq!(store.take(b"other session ID"), None);
+
+        assert!(!store.session_resumed());
+    }
+
+    #[test]
+    fn should_record_resumption_if_session_taken() {
+        let store = TlsSessionCache::new().server_session_store();
+        store.put(b"session ID".to_vec(), b"session".to_vec());
+
+        assert_eq!(store.take(b"session ID"), Some(b"session".to_vec()));
+
+        assert!(store.session_resumed());
+    }
+
+    #[test]
+    fn should_share_sessions_but_not_resumption_between_handshakes() {
+        let cache = TlsSessionCache::new();
+        let first_handshake_store = cache.server_session_store();
+        first_handshake_store.put(b"session ID".to_vec(), b"session".to_vec());
+        let second_handshake_store = cache.server_session_store();
+
+        assert_eq!(
+            second_handshake_store.take(b"session ID"),
+            Some(b"session".to_vec())
+        );
+
+        assert!(second_handshake_store.session_resumed());
+        assert!(!first_handshake_store.session_resumed());
+    }
+}
+
+mod server_name {
+    use super::*;
+
+    #[test]
+    fn should_create_server_name_for_self_authenticating_node_id() {
+        let node_id = NodeId::from(PrincipalId::new_self_authenticating(b"node public key"));
+
+        let _panic_if_invalid = session_cache_server_name(node_id, REG_V1);
+    }
+
+    #[test]
+    fn should_create_distinct_server_names_for_distinct_nodes() {
+        assert_ne!(
+            session_cache_server_name(NODE_1, REG_V1),
+            session_cache_server_name(NODE_2, REG_V1)
+        );
+    }
+
+    #[test]
+    fn should_create_distinct_server_names_for_distinct_registry_versions() {
+        assert_ne!(
+            session_cache_server_name(NODE_1, REG_V1),
+            session_cache_server_name(NODE_1, REG_V2)
+        );
+    }
+}
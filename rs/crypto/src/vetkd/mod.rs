@@ -0,0 +1,418 @@
+//! Verifiably encrypted threshold key derivation (vetKD)
+//!
+//! The nodes holding a share of the threshold key established by an NI-DKG
+//! each derive a share of the key for the given derivation path and ID,
+//! encrypted to the transport public key of the recipient. Any threshold of
+//! valid shares can be combined into the encrypted derived key, which only
+//! the holder of the transport secret key can decrypt.
+use crate::sign::{debug_ok_content, log_err, ThresholdSigDataStore};
+use crate::{get_log_id, CryptoComponentImpl, LockableThresholdSigDataStore};
+use ic_crypto_internal_bls12_381_type::G2Affine;
+use ic_crypto_internal_bls12_381_vetkd::{
+    DerivationPath, EncryptedKey, EncryptedKeyShare, NodeIndex, TransportPublicKey, VetKdError,
+};
+use ic_crypto_internal_csp::api::CspVetKdProtocol;
+use ic_crypto_internal_csp::types::CspPublicCoefficients;
+use ic_crypto_internal_csp::vault::api::CspVetKdEncryptedKeyShareCreationError;
+use ic_crypto_internal_csp::CryptoServiceProvider;
+use ic_crypto_internal_logmon::metrics::{MetricsDomain, MetricsResult, MetricsScope};
+use ic_crypto_internal_threshold_sig_bls12381::api::individual_public_key;
+use ic_interfaces::crypto::VetKdProtocol;
+use ic_logger::{debug, new_logger};
+use ic_types::crypto::canister_threshold_sig::ExtendedDerivationPath;
+use ic_types::crypto::vetkd::{
+    VetKdArgs, VetKdEncryptedKey, VetKdEncryptedKeyShare, VetKdKeyShareCombinationError,
+    VetKdKeyShareCreationError, VetKdKeyShareVerificationError, VetKdKeyVerificationError,
+};
+use ic_types::NodeId;
+use std::collections::BTreeMap;
+
+#[cfg(test)]
+mod tests;
+
+impl<C: CryptoServiceProvider> VetKdProtocol for CryptoComponentImpl<C> {
+    fn create_encrypted_key_share(
+        &self,
+        args: VetKdArgs,
+    ) -> Result<VetKdEncryptedKeyShare, VetKdKeyShareCreationError> {
+        let log_id = get_log_id(&self.logger, module_path!());
+        let logger = new_logger!(&self.logger;
+            crypto.log_id => log_id,
+            crypto.trait_name => "VetKdProtocol",
+            crypto.method_name => "create_encrypted_key_share",
+        );
+        debug!(logger;
+            crypto.description => "start",
+            crypto.dkg_id => format!("{}", args.dkg_id),
+            crypto.signature_inputs => format!("{:?}", args),
+        );
+        let start_time = self.metrics.now();
+        let result = create_encrypted_key_share_internal(
+            &self.lockable_threshold_sig_data_store,
+            &self.csp,
+            args,
+        );
+        self.metrics.observe_duration_seconds(
+            MetricsDomain::VetKd,
+            MetricsScope::Full,
+            "create_encrypted_key_share",
+            MetricsResult::from(&result),
+            start_time,
+        );
+        debug!(logger;
+            crypto.description => "end",
+            crypto.is_ok => result.is_ok(),
+            crypto.error => log_err(result.as_ref().err()),
+            crypto.signature_shares => debug_ok_content(&result),
+        );
+        result
+    }
+
+    fn verify_encrypted_key_share(
+        &self,
+        signer: NodeId,
+        key_share: &VetKdEncryptedKeyShare,
+        args: &VetKdArgs,
+    ) -> Result<(), VetKdKeyShareVerificationError> {
+        let log_id = get_log_id(&self.logger, module_path!());
+        let logger = new_logger!(&self.logger;
+            crypto.log_id => log_id,
+            crypto.trait_name => "VetKdProtocol",
+            crypto.method_name => "verify_encrypted_key_share",
+        );
+        debug!(logger;
+            crypto.description => "start",
+            crypto.dkg_id => format!("{}", args.dkg_id),
+            crypto.signer => format!("{:?}", signer),
+            crypto.signature_shares => format!("{:?}", key_share),
+            crypto.signature_inputs => format!("{:?}", args),
+        );
+        let start_time = self.metrics.now();
+        let result = verify_encrypted_key_share_internal(
+            &self.lockable_threshold_sig_data_store,
+            signer,
+            key_share,
+            args,
+        );
+        self.metrics.observe_duration_seconds(
+            MetricsDomain::VetKd,
+            MetricsScope::Full,
+            "verify_encrypted_key_share",
+            MetricsResult::from(&result),
+            start_time,
+        );
+        debug!(logger;
+            crypto.description => "end",
+            crypto.is_ok => result.is_ok(),
+            crypto.error => log_err(result.as_ref().err()),
+        );
+        result
+    }
+
+    fn combine_encrypted_key_shares(
+        &self,
+        shares: &BTreeMap<NodeId, VetKdEncryptedKeyShare>,
+        args: &VetKdArgs,
+    ) -> Result<VetKdEncryptedKey, VetKdKeyShareCombinationError> {
+        let log_id = get_log_id(&self.logger, module_path!());
+        let logger = new_logger!(&self.logger;
+            crypto.log_id => log_id,
+            crypto.trait_name => "VetKdProtocol",
+            crypto.method_name => "combine_encrypted_key_shares",
+        );
+        debug!(logger;
+            crypto.description => "start",
+            crypto.dkg_id => format!("{}", args.dkg_id),
+            crypto.signature_shares => format!("{:?}", shares),
+            crypto.signature_inputs => format!("{:?}", args),
+        );
+        let start_time = self.metrics.now();
+        let result = combine_encrypted_key_shares_internal(
+            &self.lockable_threshold_sig_data_store,
+            shares,
+            args,
+        );
+        self.metrics.observe_duration_seconds(
+            MetricsDomain::VetKd,
+            MetricsScope::Full,
+            "combine_encrypted_key_shares",
+            MetricsResult::from(&result),
+            start_time,
+        );
+        debug!(logger;
+            crypto.description => "end",
+            crypto.is_ok => result.is_ok(),
+            crypto.error => log_err(result.as_ref().err()),
+            crypto.signature => debug_ok_content(&result),
+        );
+        result
+    }
+
+    fn verify_encrypted_key(
+        &self,
+        key: &VetKdEncryptedKey,
+        args: &VetKdArgs,
+    ) -> Result<(), VetKdKeyVerificationError> {
+        let log_id = get_log_id(&self.logger, module_path!());
+        let logger = new_logger!(&self.logger;
+            crypto.log_id => log_id,
+            crypto.trait_name => "VetKdProtocol",
+            crypto.method_name => "verify_encrypted_key",
+        );
+        debug!(logger;
+            crypto.description => "start",
+            crypto.dkg_id => format!("{}", args.dkg_id),
+            crypto.signature => format!("{:?}", key),
+            crypto.signature_inputs => format!("{:?}", args),
+        );
+        let start_time = self.metrics.now();
+        let result =
+            verify_encrypted_key_internal(&self.lockable_threshold_sig_data_store, key, args);
+        self.metrics.observe_duration_seconds(
+            MetricsDomain::VetKd,
+            MetricsScope::Full,
+            "verify_encrypted_key",
+            MetricsResult::from(&result),
+            start_time,
+        );
+        debug!(logger;
+            crypto.description => "end",
+            crypto.is_ok => result.is_ok(),
+            crypto.error => log_err(result.as_ref().err()),
+        );
+        result
+    }
+}
+
+fn create_encrypted_key_share_internal<C: CspVetKdProtocol>(
+    lockable_threshold_sig_data_store: &LockableThresholdSigDataStore,
+    csp: &C,
+    args: VetKdArgs,
+) -> Result<VetKdEncryptedKeyShare, VetKdKeyShareCreationError> {
+    let dkg_id = args.dkg_id;
+    let public_coefficients = lockable_threshold_sig_data_store
+        .read()
+        .transcript_data(dkg_id)
+        .map(|data| data.public_coefficients().clone())
+        .ok_or(VetKdKeyShareCreationError::ThresholdSigDataNotFound { dkg_id })?;
+    csp.create_encrypted_vetkd_key_share(
+        public_coefficients,
+        args.encryption_public_key,
+        args.derivation_path,
+        args.derivation_id,
+    )
+    .map_err(|error| match error {
+        CspVetKdEncryptedKeyShareCreationError::SecretKeyNotFound { key_id } => {
+            VetKdKeyShareCreationError::SecretKeyNotFound {
+                dkg_id,
+                key_id: key_id.to_string(),
+            }
+        }
+        CspVetKdEncryptedKeyShareCreationError::InvalidArgumentEncryptionPublicKey => {
+            VetKdKeyShareCreationError::InvalidArgument("invalid encryption public key".to_string())
+        }
+        CspVetKdEncryptedKeyShareCreationError::TransientInternalError { internal_error } => {
+            VetKdKeyShareCreationError::TransientInternalError { internal_error }
+        }
+        // The remaining errors concern the threshold key of a loaded
+        // transcript and are therefore not caused by the caller.
+        CspVetKdEncryptedKeyShareCreationError::WrongSecretKeyType { .. }
+        | CspVetKdEncryptedKeyShareCreationError::InvalidArgumentMasterPublicKey
+        | CspVetKdEncryptedKeyShareCreationError::InternalError { .. } => {
+            VetKdKeyShareCreationError::InternalError {
+                internal_error: format!("{:?}", error),
+            }
+        }
+    })
+}
+
+fn verify_encrypted_key_share_internal(
+    lockable_threshold_sig_data_store: &LockableThresholdSigDataStore,
+    signer: NodeId,
+    key_share: &VetKdEncryptedKeyShare,
+    args: &VetKdArgs,
+) -> Result<(), VetKdKeyShareVerificationError> {
+    let dkg_id = args.dkg_id;
+    let (public_coefficients, node_index) = {
+        let store = lockable_threshold_sig_data_store.read();
+        let transcript_data = store
+            .transcript_data(dkg_id)
+            .ok_or(VetKdKeyShareVerificationError::ThresholdSigDataNotFound { dkg_id })?;
+        let node_index = *transcript_data
+            .index(signer)
+            .ok_or(VetKdKeyShareVerificationError::SignerNotAllowed { node_id: signer })?;
+        (transcript_data.public_coefficients().clone(), node_index)
+    };
+    let internal_error =
+        |internal_error| VetKdKeyShareVerificationError::InternalError { internal_error };
+    let master_public_key = master_public_key(&public_coefficients).map_err(internal_error)?;
+    let public_key_share =
+        public_key_share(&public_coefficients, node_index).map_err(internal_error)?;
+    let transport_public_key = TransportPublicKey::deserialize(&args.encryption_public_key)
+        .map_err(|_| {
+            VetKdKeyShareVerificationError::InvalidArgument(
+                "invalid encryption public key".to_string(),
+            )
+        })?;
+    let key_share =
+        EncryptedKeyShare::deserialize(&key_share.encrypted_key_share).map_err(|_| {
+            VetKdKeyShareVerificationError::InvalidArgument(
+                "invalid encrypted key share".to_string(),
+            )
+        })?;
+
+    if key_share.is_valid(
+        &master_public_key,
+        &public_key_share,
+        &derivation_path(&args.derivation_path),
+        &args.derivation_id,
+        &transport_public_key,
+    ) {
+        Ok(())
+    } else {
+        Err(VetKdKeyShareVerificationError::VerificationError)
+    }
+}
+
+fn combine_encrypted_key_shares_internal(
+    lockable_threshold_sig_data_store: &LockableThresholdSigDataStore,
+    shares: &BTreeMap<NodeId, VetKdEncryptedKeyShare>,
+    args: &VetKdArgs,
+) -> Result<VetKdEncryptedKey, VetKdKeyShareCombinationError> {
+    let dkg_id = args.dkg_id;
+    let internal_error =
+        |internal_error| VetKdKeyShareCombinationError::InternalError { internal_error };
+    let (public_coefficients, node_indexes) = {
+        let store = lockable_threshold_sig_data_store.read();
+        let transcript_data = store
+            .transcript_data(dkg_id)
+            .ok_or(VetKdKeyShareCombinationError::ThresholdSigDataNotFound { dkg_id })?;
+        let node_indexes = shares
+            .keys()
+            .map(|node_id| {
+                transcript_data
+                    .index(*node_id)
+                    .map(|index| (*node_id, *index))
+                    .ok_or(VetKdKeyShareCombinationError::SignerNotAllowed { node_id: *node_id })
+            })
+            .collect::<Result<BTreeMap<NodeId, NodeIndex>, _>>()?;
+        (transcript_data.public_coefficients().clone(), node_indexes)
+    };
+    let master_public_key = master_public_key(&public_coefficients).map_err(internal_error)?;
+    let transport_public_key = TransportPublicKey::deserialize(&args.encryption_public_key)
+        .map_err(|_| {
+            VetKdKeyShareCombinationError::InvalidArgument(
+                "invalid encryption public key".to_string(),
+            )
+        })?;
+
+    let mut indexed_shares = BTreeMap::new();
+    for (node_id, share) in shares {
+        let node_index = node_indexes[node_id];
+        let share = EncryptedKeyShare::deserialize(&share.encrypted_key_share).map_err(|_| {
+            VetKdKeyShareCombinationError::InvalidArgument(format!(
+                "invalid encrypted key share of node {}",
+                node_id
+            ))
+        })?;
+        let public_key_share =
+            public_key_share(&public_coefficients, node_index).map_err(internal_error)?;
+        indexed_shares.insert(node_index, (public_key_share, share));
+    }
+
+    let threshold = reconstruction_threshold(&public_coefficients);
+    EncryptedKey::combine_valid_shares(
+        &indexed_shares,
+        threshold,
+        &master_public_key,
+        &derivation_path(&args.derivation_path),
+        &args.derivation_id,
+        &transport_public_key,
+    )
+    .map(|encrypted_key| VetKdEncryptedKey {
+        encrypted_key: encrypted_key.serialize().to_vec(),
+    })
+    .map_err(|error| match error {
+        VetKdError::InsufficientShares { threshold, shares } => {
+            VetKdKeyShareCombinationError::UnsatisfiedReconstructionThreshold {
+                threshold: threshold as u32,
+                share_count: shares,
+            }
+        }
+        VetKdError::InsufficientValidShares {
+            threshold,
+            valid_shares,
+        } => VetKdKeyShareCombinationError::InsufficientValidShares {
+            threshold: threshold as u32,
+            valid_share_count: valid_shares,
+        },
+        other => VetKdKeyShareCombinationError::InternalError {
+            internal_error: format!("{:?}", other),
+        },
+    })
+}
+
+fn verify_encrypted_key_internal(
+    lockable_threshold_sig_data_store: &LockableThresholdSigDataStore,
+    key: &VetKdEncryptedKey,
+    args: &VetKdArgs,
+) -> Result<(), VetKdKeyVerificationError> {
+    let dkg_id = args.dkg_id;
+    let public_coefficients = lockable_threshold_sig_data_store
+        .read()
+        .transcript_data(dkg_id)
+        .map(|data| data.public_coefficients().clone())
+        .ok_or(VetKdKeyVerificationError::ThresholdSigDataNotFound { dkg_id })?;
+    let master_public_key = master_public_key(&public_coefficients)
+        .map_err(|internal_error| VetKdKeyVerificationError::InternalError { internal_error })?;
+    let transport_public_key = TransportPublicKey::deserialize(&args.encryption_public_key)
+        .map_err(|_| {
+            VetKdKeyVerificationError::InvalidArgument("invalid encryption public key".to_string())
+        })?;
+    let key = EncryptedKey::deserialize(&key.encrypted_key).map_err(|_| {
+        VetKdKeyVerificationError::InvalidArgument("invalid encrypted key".to_string())
+    })?;
+
+    if key.is_valid(
+        &master_public_key,
+        &derivation_path(&args.derivation_path),
+        &args.derivation_id,
+        &transport_public_key,
+    ) {
+        Ok(())
+    } else {
+        Err(VetKdKeyVerificationError::VerificationError)
+    }
+}
+
+fn derivation_path(derivation_path: &ExtendedDerivationPath) -> DerivationPath {
+    DerivationPath::new(
+        derivation_path.caller.as_slice(),
+        &derivation_path.derivation_path,
+    )
+}
+
+/// The master public key is the constant term of the public coefficients.
+fn master_public_key(public_coefficients: &CspPublicCoefficients) -> Result<G2Affine, String> {
+    let CspPublicCoefficients::Bls12_381(public_coefficients) = public_coefficients;
+    let constant_term = public_coefficients
+        .coefficients
+        .first()
+        .ok_or_else(|| "empty public coefficients".to_string())?;
+    G2Affine::deserialize(&constant_term.0).map_err(|_| "malformed master public key".to_string())
+}
+
+fn public_key_share(
+    public_coefficients: &CspPublicCoefficients,
+    node_index: NodeIndex,
+) -> Result<G2Affine, String> {
+    let CspPublicCoefficients::Bls12_381(public_coefficients) = public_coefficients;
+    let public_key_share = individual_public_key(public_coefficients, node_index)
+        .map_err(|error| format!("failed to compute public key share: {}", error))?;
+    G2Affine::deserialize(&public_key_share.0).map_err(|_| "malformed public key share".to_string())
+}
+
+fn reconstruction_threshold(public_coefficients: &CspPublicCoefficients) -> usize {
+    let CspPublicCoefficients::Bls12_381(public_coefficients) = public_coefficients;
+    public_coefficients.coefficients.len()
+}
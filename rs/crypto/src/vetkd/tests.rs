@@ -0,0 +1,174 @@
+#![allow(clippy::unwrap_used)]
+
+use super::*;
+use assert_matches::assert_matches;
+use ic_base_types::PrincipalId;
+use ic_crypto_internal_csp::key_id::KeyId;
+use ic_crypto_internal_types::sign::threshold_sig::ni_dkg::ni_dkg_groth20_bls12_381::PublicCoefficientsBytes;
+use ic_crypto_internal_types::sign::threshold_sig::public_key::bls12_381::PublicKeyBytes;
+use ic_crypto_test_utils_csp::MockAllCryptoServiceProvider;
+use ic_types::crypto::threshold_sig::ni_dkg::{
+    DkgId, NiDkgId, NiDkgTag, NiDkgTargetId, NiDkgTargetSubnet,
+};
+use ic_types::Height;
+use ic_types_test_utils::ids::{NODE_1, NODE_2, SUBNET_1};
+
+const NI_DKG_ID: NiDkgId = NiDkgId {
+    start_block_height: Height::new(3),
+    dealer_subnet: SUBNET_1,
+    dkg_tag: NiDkgTag::HighThreshold,
+    target_subnet: NiDkgTargetSubnet::Remote(NiDkgTargetId::new([42; 32])),
+};
+
+fn vetkd_args(encryption_public_key: Vec<u8>) -> VetKdArgs {
+    VetKdArgs {
+        dkg_id: DkgId::NiDkgId(NI_DKG_ID),
+        derivation_path: ExtendedDerivationPath {
+            caller: PrincipalId::new_user_test_id(1),
+            derivation_path: vec![],
+        },
+        derivation_id: b"message".to_vec(),
+        encryption_public_key,
+    }
+}
+
+fn pub_coeffs() -> CspPublicCoefficients {
+    CspPublicCoefficients::Bls12_381(PublicCoefficientsBytes {
+        coefficients: vec![PublicKeyBytes(G2Affine::generator().serialize())],
+    })
+}
+
+fn store_with_transcript_data_for(node_id: NodeId) -> LockableThresholdSigDataStore {
+    let store = LockableThresholdSigDataStore::new();
+    store.write().insert_transcript_data(
+        DkgId::NiDkgId(NI_DKG_ID),
+        pub_coeffs(),
+        BTreeMap::from([(node_id, 0)]),
+    );
+    store
+}
+
+#[test]
+fn should_fail_to_create_key_share_without_transcript_data() {
+    let csp = MockAllCryptoServiceProvider::new();
+
+    let result = create_encrypted_key_share_internal(
+        &LockableThresholdSigDataStore::new(),
+        &csp,
+        vetkd_args(vec![]),
+    );
+
+    assert_eq!(
+        result,
+        Err(VetKdKeyShareCreationError::ThresholdSigDataNotFound {
+            dkg_id: DkgId::NiDkgId(NI_DKG_ID)
+        })
+    );
+}
+
+#[test]
+fn should_create_key_share_with_public_coefficients_from_store() {
+    let args = vetkd_args(vec![1; 48]);
+    let expected_args = args.clone();
+    let key_share = VetKdEncryptedKeyShare {
+        encrypted_key_share: vec![42; EncryptedKeyShare::BYTES],
+    };
+    let mut csp = MockAllCryptoServiceProvider::new();
+    csp.expect_create_encrypted_vetkd_key_share()
+        .withf(
+            move |public_coefficients, encryption_public_key, derivation_path, derivation_id| {
+                *public_coefficients == pub_coeffs()
+                    && *encryption_public_key == expected_args.encryption_public_key
+                    && *derivation_path == expected_args.derivation_path
+                    && *derivation_id == expected_args.derivation_id
+            },
+        )
+        .times(1)
+        .return_const(Ok(key_share.clone()));
+
+    let result =
+        create_encrypted_key_share_internal(&store_with_transcript_data_for(NODE_1), &csp, args);
+
+    assert_eq!(result, Ok(key_share));
+}
+
+#[test]
+fn should_map_missing_secret_key_error_of_csp() {
+    let mut csp = MockAllCryptoServiceProvider::new();
+    csp.expect_create_encrypted_vetkd_key_share()
+        .times(1)
+        .return_const(Err(
+            CspVetKdEncryptedKeyShareCreationError::SecretKeyNotFound {
+                key_id: KeyId::from([42; 32]),
+            },
+        ));
+
+    let result = create_encrypted_key_share_internal(
+        &store_with_transcript_data_for(NODE_1),
+        &csp,
+        vetkd_args(vec![]),
+    );
+
+    assert_eq!(
+        result,
+        Err(VetKdKeyShareCreationError::SecretKeyNotFound {
+            dkg_id: DkgId::NiDkgId(NI_DKG_ID),
+            key_id: KeyId::from([42; 32]).to_string(),
+        })
+    );
+}
+
+#[test]
+fn should_fail_to_verify_key_share_of_node_not_in_transcript() {
+    let key_share = VetKdEncryptedKeyShare {
+        encrypted_key_share: vec![],
+    };
+
+    let result = verify_encrypted_key_share_internal(
+        &store_with_transcript_data_for(NODE_1),
+        NODE_2,
+        &key_share,
+        &vetkd_args(vec![]),
+    );
+
+    assert_eq!(
+        result,
+        Err(VetKdKeyShareVerificationError::SignerNotAllowed { node_id: NODE_2 })
+    );
+}
+
+#[test]
+fn should_fail_to_combine_shares_with_node_not_in_transcript() {
+    let shares = BTreeMap::from([(
+        NODE_2,
+        VetKdEncryptedKeyShare {
+            encrypted_key_share: vec![],
+        },
+    )]);
+
+    let result = combine_encrypted_key_shares_internal(
+        &store_with_transcript_data_for(NODE_1),
+        &shares,
+        &vetkd_args(vec![]),
+    );
+
+    assert_eq!(
+        result,
+        Err(VetKdKeyShareCombinationError::SignerNotAllowed { node_id: NODE_2 })
+    );
+}
+
+#[test]
+fn should_fail_to_verify_key_with_invalid_encryption_public_key() {
+    let key = VetKdEncryptedKey {
+        encrypted_key: vec![],
+    };
+
+    let result = verify_encrypted_key_internal(
+        &store_with_transcript_data_for(NODE_1),
+        &key,
+        &vetkd_args(vec![0xff; 48]),
+    );
+
+    assert_matches!(result, Err(VetKdKeyVerificationError::InvalidArgument(_)));
+}
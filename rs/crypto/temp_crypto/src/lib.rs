@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 use ic_base_types::PrincipalId;
-use ic_config::crypto::{CryptoConfig, CspVaultType};
+use ic_config::crypto::{CryptoConfig, CspVaultType, KeyEscrowConfig, TlsPolicy};
 use ic_crypto::{CryptoComponent, CryptoComponentImpl};
 use ic_crypto_internal_csp::vault::local_csp_vault::ProdLocalCspVault;
 use ic_crypto_internal_csp::{CryptoServiceProvider, Csp};
@@ -18,11 +18,12 @@ use ic_crypto_tls_interfaces::{
 };
 use ic_crypto_utils_time::CurrentSystemTimeSource;
 use ic_interfaces::crypto::{
-    BasicSigVerifier, BasicSigVerifierByPublicKey, BasicSigner, CanisterSigVerifier,
-    CheckKeysWithRegistryError, CurrentNodePublicKeysError, IDkgDealingEncryptionKeyRotationError,
-    IDkgKeyRotationResult, IDkgProtocol, IdkgDealingEncPubKeysCountError, KeyManager,
-    LoadTranscriptResult, MultiSigVerifier, MultiSigner, NiDkgAlgorithm, ThresholdEcdsaSigVerifier,
-    ThresholdEcdsaSigner, ThresholdSigVerifier, ThresholdSigVerifierByPublicKey, ThresholdSigner,
+    AsyncBasicSigner, BasicSigVerifier, BasicSigVerifierByPublicKey, BasicSigner,
+    CanisterSigVerifier, CheckKeysWithRegistryError, CurrentNodePublicKeysError,
+    IDkgDealingEncryptionKeyRotationError, IDkgKeyRotationResult, IDkgProtocol,
+    IdkgDealingEncPubKeysCountError, KeyManager, LoadTranscriptResult, MultiSigVerifier,
+    MultiSigner, NiDkgAlgorithm, ThresholdEcdsaSigVerifier, ThresholdEcdsaSigner,
+    ThresholdSigVerifier, ThresholdSigVerifierByPublicKey, ThresholdSigner, VetKdProtocol,
 };
 use ic_interfaces::time_source::TimeSource;
 use ic_interfaces_registry::RegistryClient;
@@ -58,6 +59,10 @@ use ic_types::crypto::threshold_sig::ni_dkg::errors::{
     verify_dealing_error::DkgVerifyDealingError,
 };
 use ic_types::crypto::threshold_sig::ni_dkg::{DkgId, NiDkgDealing, NiDkgTranscript};
+use ic_types::crypto::vetkd::{
+    VetKdArgs, VetKdEncryptedKey, VetKdEncryptedKeyShare, VetKdKeyShareCombinationError,
+    VetKdKeyShareCreationError, VetKdKeyShareVerificationError, VetKdKeyVerificationError,
+};
 use ic_types::crypto::{
     BasicSigOf, CanisterSigOf, CombinedMultiSigOf, CombinedThresholdSigOf, CryptoResult,
     CurrentNodePublicKeys, IndividualMultiSigOf, KeyPurpose, Signable, ThresholdSigShareOf,
@@ -110,6 +115,8 @@ pub struct TempCryptoBuilder {
     logger: Option<ReplicaLogger>,
     time_source: Option<Arc<dyn TimeSource>>,
     ecdsa_subnet_config: Option<EcdsaSubnetConfig>,
+    tls_policy: Option<TlsPolicy>,
+    key_escrow: Option<KeyEscrowConfig>,
 }
 
 impl TempCryptoBuilder {
@@ -183,8 +190,24 @@ impl TempCryptoBuilder {
         self
     }
 
+    pub fn with_tls_policy(mut self, tls_policy: TlsPolicy) -> Self {
+        self.tls_policy = Some(tls_policy);
+        self
+    }
+
+    pub fn with_key_escrow_config(mut self, key_escrow: KeyEscrowConfig) -> Self {
+        self.key_escrow = Some(key_escrow);
+        self
+    }
+
     pub fn build(self) -> TempCryptoComponent {
         let (mut config, temp_dir) = CryptoConfig::new_in_temp_dir();
+        if let Some(tls_policy) = self.tls_policy {
+            config.tls_policy = tls_policy;
+        }
+        if let Some(key_escrow) = self.key_escrow {
+            config.key_escrow = Some(key_escrow);
+        }
         if let Some(source) = self.temp_dir_source {
             copy_crypto_root(&source, temp_dir.path());
         }
@@ -353,6 +376,8 @@ impl TempCryptoComponent {
             logger: None,
             time_source: None,
             ecdsa_subnet_config: None,
+            tls_policy: None,
+            key_escrow: None,
         }
     }
 
@@ -547,6 +572,34 @@ impl<C: CryptoServiceProvider, T: Signable> BasicSigner<T> for TempCryptoCompone
         self.crypto_component
             .sign_basic(message, signer, registry_version)
     }
+
+    fn sign_basic_batch(
+        &self,
+        messages: &[T],
+        signer: NodeId,
+        registry_version: RegistryVersion,
+    ) -> CryptoResult<Vec<BasicSigOf<T>>> {
+        self.crypto_component
+            .sign_basic_batch(messages, signer, registry_version)
+    }
+}
+
+#[async_trait]
+impl<C, T> AsyncBasicSigner<T> for TempCryptoComponentGeneric<C>
+where
+    C: CryptoServiceProvider + Clone + Send + Sync + 'static,
+    T: Signable + Sync,
+{
+    async fn sign_basic_async(
+        &self,
+        message: &T,
+        signer: NodeId,
+        registry_version: RegistryVersion,
+    ) -> CryptoResult<BasicSigOf<T>> {
+        self.crypto_component
+            .sign_basic_async(message, signer, registry_version)
+            .await
+    }
 }
 
 impl<C: CryptoServiceProvider, T: Signable> BasicSigVerifierByPublicKey<T>
@@ -726,6 +779,42 @@ impl<C: CryptoServiceProvider> ThresholdEcdsaSigVerifier for TempCryptoComponent
     }
 }
 
+impl<C: CryptoServiceProvider> VetKdProtocol for TempCryptoComponentGeneric<C> {
+    fn create_encrypted_key_share(
+        &self,
+        args: VetKdArgs,
+    ) -> Result<VetKdEncryptedKeyShare, VetKdKeyShareCreationError> {
+        self.crypto_component.create_encrypted_key_share(args)
+    }
+
+    fn verify_encrypted_key_share(
+        &self,
+        signer: NodeId,
+        key_share: &VetKdEncryptedKeyShare,
+        args: &VetKdArgs,
+    ) -> Result<(), VetKdKeyShareVerificationError> {
+        self.crypto_component
+            .verify_encrypted_key_share(signer, key_share, args)
+    }
+
+    fn combine_encrypted_key_shares(
+        &self,
+        shares: &BTreeMap<NodeId, VetKdEncryptedKeyShare>,
+        args: &VetKdArgs,
+    ) -> Result<VetKdEncryptedKey, VetKdKeyShareCombinationError> {
+        self.crypto_component
+            .combine_encrypted_key_shares(shares, args)
+    }
+
+    fn verify_encrypted_key(
+        &self,
+        key: &VetKdEncryptedKey,
+        args: &VetKdArgs,
+    ) -> Result<(), VetKdKeyVerificationError> {
+        self.crypto_component.verify_encrypted_key(key, args)
+    }
+}
+
 #[async_trait]
 impl<C: CryptoServiceProvider + Send + Sync> TlsHandshake for TempCryptoComponentGeneric<C> {
     async fn perform_tls_server_handshake(
@@ -733,9 +822,15 @@ impl<C: CryptoServiceProvider + Send + Sync> TlsHandshake for TempCryptoComponen
         tcp_stream: TcpStream,
         allowed_clients: AllowedClients,
         registry_version: RegistryVersion,
+        alpn_protocols: Vec<Vec<u8>>,
     ) -> Result<(Box<dyn TlsStream>, AuthenticatedPeer), TlsServerHandshakeError> {
         self.crypto_component
-            .perform_tls_server_handshake(tcp_stream, allowed_clients, registry_version)
+            .perform_tls_server_handshake(
+                tcp_stream,
+                allowed_clients,
+                registry_version,
+                alpn_protocols,
+            )
             .await
     }
 
@@ -754,9 +849,20 @@ impl<C: CryptoServiceProvider + Send + Sync> TlsHandshake for TempCryptoComponen
         tcp_stream: TcpStream,
         server: NodeId,
         registry_version: RegistryVersion,
+        alpn_protocols: Vec<Vec<u8>>,
+    ) -> Result<Box<dyn TlsStream>, TlsClientHandshakeError> {
+        self.crypto_component
+            .perform_tls_client_handshake(tcp_stream, server, registry_version, alpn_protocols)
+            .await
+    }
+
+    async fn perform_tls_client_handshake_with_pinned_cert(
+        &self,
+        tcp_stream: TcpStream,
+        server_cert: TlsPublicKeyCert,
     ) -> Result<Box<dyn TlsStream>, TlsClientHandshakeError> {
         self.crypto_component
-            .perform_tls_client_handshake(tcp_stream, server, registry_version)
+            .perform_tls_client_handshake_with_pinned_cert(tcp_stream, server_cert)
             .await
     }
 }
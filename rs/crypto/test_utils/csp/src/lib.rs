@@ -1,17 +1,21 @@
 use ic_base_types::RegistryVersion;
 use ic_crypto_internal_csp::api::NodePublicKeyDataError;
 use ic_crypto_internal_csp::api::{
-    CspCreateMEGaKeyError, CspIDkgProtocol, CspKeyGenerator, CspPublicAndSecretKeyStoreChecker,
-    CspPublicKeyStore, CspSigVerifier, CspSigner, CspThresholdEcdsaSigVerifier,
-    CspThresholdEcdsaSigner, CspThresholdSignError, CspTlsHandshakeSignerProvider, NiDkgCspClient,
-    ThresholdSignatureCspClient,
+    CspCreateMEGaKeyError, CspIDkgProtocol, CspKeyEscrow, CspKeyGenerator,
+    CspPublicAndSecretKeyStoreChecker, CspPublicKeyStore, CspSigVerifier, CspSigner,
+    CspThresholdEcdsaSigVerifier, CspThresholdEcdsaSigner, CspThresholdSignError,
+    CspTlsHandshakeSignerProvider, CspVetKdProtocol, NiDkgCspClient, ThresholdSignatureCspClient,
 };
 use ic_crypto_internal_csp::key_id::KeyId;
 use ic_crypto_internal_csp::types::ExternalPublicKeys;
 use ic_crypto_internal_csp::types::{CspPop, CspPublicCoefficients, CspPublicKey, CspSignature};
+use ic_crypto_internal_csp::vault::api::CspEscrowNodeSecretKeysError;
+use ic_crypto_internal_csp::vault::api::CspSealedBackupError;
+use ic_crypto_internal_csp::vault::api::CspVetKdEncryptedKeyShareCreationError;
 use ic_crypto_internal_csp::vault::api::PksAndSksContainsErrors;
 use ic_crypto_internal_csp::vault::api::ValidatePksAndSksError;
 use ic_crypto_internal_csp::TlsHandshakeCspVault;
+use ic_crypto_internal_key_escrow::EscrowedSecret;
 use ic_crypto_internal_threshold_sig_bls12381::api::ni_dkg_errors::{
     CspDkgCreateDealingError, CspDkgCreateFsKeyError, CspDkgCreateReshareDealingError,
     CspDkgCreateReshareTranscriptError, CspDkgCreateTranscriptError, CspDkgLoadPrivateKeyError,
@@ -38,6 +42,7 @@ use ic_types::crypto::canister_threshold_sig::error::{
 };
 use ic_types::crypto::canister_threshold_sig::ExtendedDerivationPath;
 use ic_types::crypto::threshold_sig::ni_dkg::NiDkgId;
+use ic_types::crypto::vetkd::VetKdEncryptedKeyShare;
 use ic_types::crypto::{AlgorithmId, CryptoError, CryptoResult, CurrentNodePublicKeys};
 use ic_types::{NodeId, NodeIndex, NumberOfNodes, Randomness};
 use mockall::predicate::*;
@@ -56,6 +61,13 @@ mock! {
             key_id: KeyId,
         ) -> CryptoResult<CspSignature>;
 
+        fn sign_batch(
+            &self,
+            algorithm_id: AlgorithmId,
+            msgs: &[Vec<u8>],
+            key_id: KeyId,
+        ) -> CryptoResult<Vec<CspSignature>>;
+
         fn verify(
             &self,
             sig: &CspSignature,
@@ -247,6 +259,30 @@ mock! {
         fn validate_pks_and_sks(&self) -> Result<ValidNodePublicKeys, ValidatePksAndSksError>;
     }
 
+    pub trait CspKeyEscrow {
+        fn escrow_node_secret_keys(
+            &self,
+            associated_data: Vec<u8>,
+        ) -> Result<EscrowedSecret, CspEscrowNodeSecretKeysError>;
+
+        fn export_sealed_backup(&self) -> Result<EscrowedSecret, CspSealedBackupError>;
+
+        fn restore_sealed_backup(
+            &self,
+            sealed_backup: EscrowedSecret,
+        ) -> Result<(), CspSealedBackupError>;
+    }
+
+    pub trait CspVetKdProtocol {
+        fn create_encrypted_vetkd_key_share(
+            &self,
+            public_coefficients: CspPublicCoefficients,
+            encryption_public_key: Vec<u8>,
+            derivation_path: ExtendedDerivationPath,
+            derivation_id: Vec<u8>,
+        ) -> Result<VetKdEncryptedKeyShare, CspVetKdEncryptedKeyShareCreationError>;
+    }
+
     pub trait CspPublicKeyStore {
         fn current_node_public_keys(&self) -> Result<CurrentNodePublicKeys, NodePublicKeyDataError>;
         fn current_node_public_keys_with_timestamps(&self) -> Result<CurrentNodePublicKeys, NodePublicKeyDataError>;
@@ -5,14 +5,18 @@ use ic_crypto_internal_csp::types::{CspPop, CspPublicKey, CspSignature, External
 use ic_crypto_internal_csp::vault::api::BasicSignatureCspVault;
 use ic_crypto_internal_csp::vault::api::CspBasicSignatureError;
 use ic_crypto_internal_csp::vault::api::CspBasicSignatureKeygenError;
+use ic_crypto_internal_csp::vault::api::CspEscrowNodeSecretKeysError;
 use ic_crypto_internal_csp::vault::api::CspMultiSignatureError;
 use ic_crypto_internal_csp::vault::api::CspMultiSignatureKeygenError;
 use ic_crypto_internal_csp::vault::api::CspPublicKeyStoreError;
+use ic_crypto_internal_csp::vault::api::CspSealedBackupError;
 use ic_crypto_internal_csp::vault::api::CspSecretKeyStoreContainsError;
 use ic_crypto_internal_csp::vault::api::CspThresholdSignatureKeygenError;
 use ic_crypto_internal_csp::vault::api::CspTlsKeygenError;
 use ic_crypto_internal_csp::vault::api::CspTlsSignError;
+use ic_crypto_internal_csp::vault::api::CspVetKdEncryptedKeyShareCreationError;
 use ic_crypto_internal_csp::vault::api::IDkgProtocolCspVault;
+use ic_crypto_internal_csp::vault::api::KeyEscrowCspVault;
 use ic_crypto_internal_csp::vault::api::MultiSignatureCspVault;
 use ic_crypto_internal_csp::vault::api::NiDkgCspVault;
 use ic_crypto_internal_csp::vault::api::PksAndSksContainsErrors;
@@ -25,6 +29,8 @@ use ic_crypto_internal_csp::vault::api::ThresholdEcdsaSignerCspVault;
 use ic_crypto_internal_csp::vault::api::ThresholdSignatureCspVault;
 use ic_crypto_internal_csp::vault::api::TlsHandshakeCspVault;
 use ic_crypto_internal_csp::vault::api::ValidatePksAndSksError;
+use ic_crypto_internal_csp::vault::api::VetKdCspVault;
+use ic_crypto_internal_key_escrow::EscrowedSecret;
 use ic_crypto_internal_seed::Seed;
 use ic_crypto_internal_threshold_sig_bls12381::api::ni_dkg_errors;
 use ic_crypto_internal_threshold_sig_ecdsa::{
@@ -44,6 +50,7 @@ use ic_types::crypto::canister_threshold_sig::error::{
     IDkgVerifyDealingPrivateError, ThresholdEcdsaSignShareError,
 };
 use ic_types::crypto::canister_threshold_sig::ExtendedDerivationPath;
+use ic_types::crypto::vetkd::VetKdEncryptedKeyShare;
 use ic_types::crypto::{AlgorithmId, CurrentNodePublicKeys};
 use ic_types::{NodeId, NodeIndex, NumberOfNodes, Randomness};
 use mockall::mock;
@@ -60,6 +67,13 @@ mock! {
             key_id: KeyId,
         ) -> Result<CspSignature, CspBasicSignatureError>;
 
+        fn sign_batch(
+            &self,
+            algorithm_id: AlgorithmId,
+            messages: &[Vec<u8>],
+            key_id: KeyId,
+        ) -> Result<Vec<CspSignature>, CspBasicSignatureError>;
+
         fn gen_node_signing_key_pair(&self) -> Result<CspPublicKey, CspBasicSignatureKeygenError>;
     }
 
@@ -239,4 +253,29 @@ mock! {
 
         fn idkg_dealing_encryption_pubkeys_count(&self) -> Result<usize, CspPublicKeyStoreError>;
     }
+
+    pub trait KeyEscrowCspVault {
+        fn escrow_node_secret_keys(
+            &self,
+            associated_data: Vec<u8>,
+        ) -> Result<EscrowedSecret, CspEscrowNodeSecretKeysError>;
+
+        fn export_sealed_backup(&self) -> Result<EscrowedSecret, CspSealedBackupError>;
+
+        fn restore_sealed_backup(
+            &self,
+            sealed_backup: EscrowedSecret,
+        ) -> Result<(), CspSealedBackupError>;
+    }
+
+    pub trait VetKdCspVault {
+        fn create_encrypted_vetkd_key_share(
+            &self,
+            key_id: KeyId,
+            master_public_key: Vec<u8>,
+            encryption_public_key: Vec<u8>,
+            derivation_path: ExtendedDerivationPath,
+            derivation_id: Vec<u8>,
+        ) -> Result<VetKdEncryptedKeyShare, CspVetKdEncryptedKeyShareCreationError>;
+    }
 }
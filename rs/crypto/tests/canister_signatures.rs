@@ -1,6 +1,6 @@
 #![allow(clippy::unwrap_used)]
 use assert_matches::assert_matches;
-use ic_crypto::{user_public_key_from_bytes, KeyBytesContentType};
+use ic_crypto::{user_public_key_from_bytes, verify_canister_sig, KeyBytesContentType};
 use ic_crypto_internal_basic_sig_der_utils::subject_public_key_info_der;
 use ic_crypto_internal_types::sign::threshold_sig::public_key::bls12_381;
 use ic_crypto_test_utils::canister_signatures::canister_sig_pub_key_to_bytes;
@@ -202,3 +202,58 @@ fn should_fail_to_verify_if_root_public_key_not_found_in_registry() {
         assert_matches!(result, Err(CryptoError::RootSubnetPublicKeyNotFound { .. }));
     }
 }
+
+mod verify_canister_sig_without_registry {
+    use super::*;
+
+    #[test]
+    fn should_verify_valid_canister_signature_against_root_of_trust() {
+        let mut rng = reproducible_rng();
+        for with_delegation in [false, true] {
+            let data = new_valid_sig_and_crypto_component(&mut rng, REG_V1, with_delegation);
+
+            let result = verify_canister_sig(
+                &data.msg,
+                &data.canister_sig,
+                &data.canister_pk,
+                &data.root_pk,
+            );
+
+            assert!(result.is_ok());
+        }
+    }
+
+    #[test]
+    fn should_fail_to_verify_against_wrong_root_of_trust() {
+        let mut rng = reproducible_rng();
+        let data = new_valid_sig_and_crypto_component(&mut rng, REG_V1, false);
+        let wrong_root_pk = new_valid_sig_and_crypto_component(&mut rng, REG_V1, false).root_pk;
+        assert_ne!(data.root_pk, wrong_root_pk);
+
+        let result = verify_canister_sig(
+            &data.msg,
+            &data.canister_sig,
+            &data.canister_pk,
+            &wrong_root_pk,
+        );
+
+        assert_matches!(result, Err(CryptoError::SignatureVerification { algorithm, .. })
+            if algorithm == AlgorithmId::IcCanisterSignature
+        );
+    }
+
+    #[test]
+    fn should_fail_to_verify_with_wrong_algorithm_id() {
+        let mut rng = reproducible_rng();
+        let data = new_valid_sig_and_crypto_component(&mut rng, REG_V1, false);
+        let mut wrong_pubkey = data.canister_pk;
+        wrong_pubkey.algorithm_id = AlgorithmId::Ed25519;
+
+        let result =
+            verify_canister_sig(&data.msg, &data.canister_sig, &wrong_pubkey, &data.root_pk);
+
+        assert_matches!(result, Err(CryptoError::AlgorithmNotSupported { algorithm, .. })
+            if algorithm == AlgorithmId::Ed25519
+        );
+    }
+}
@@ -0,0 +1,139 @@
+#![allow(clippy::unwrap_used)]
+use assert_matches::assert_matches;
+use ic_certification_test_utils::{CertificateBuilder, CertificateData};
+use ic_crypto::verify_certified_response;
+use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
+use ic_crypto_tree_hash::{Digest, LookupStatus, MixedHashTree};
+use ic_types::crypto::CryptoError;
+use ic_types::CanisterId;
+use ic_types_test_utils::ids::{canister_test_id, subnet_test_id};
+
+const CERTIFIED_DATA: [u8; 32] = [42; 32];
+
+fn canister_data(canister_id: CanisterId) -> CertificateData {
+    CertificateData::CanisterData {
+        canister_id,
+        certified_data: Digest(CERTIFIED_DATA),
+    }
+}
+
+fn subnet_data(canister_id_ranges: Vec<(CanisterId, CanisterId)>) -> CertificateData {
+    CertificateData::SubnetData {
+        subnet_id: subnet_test_id(1),
+        canister_id_ranges,
+    }
+}
+
+#[test]
+fn should_verify_certificate_issued_by_root_subnet() {
+    let rng = &mut reproducible_rng();
+    let canister_id = canister_test_id(1);
+    let (_cert, root_of_trust, cbor) =
+        CertificateBuilder::new_with_rng(canister_data(canister_id), rng).build();
+
+    let certificate = verify_certified_response(&cbor, &canister_id, &root_of_trust).unwrap();
+
+    assert_eq!(
+        certificate.tree.lookup(&[
+            &b"canister"[..],
+            canister_id.get_ref().as_slice(),
+            &b"certified_data"[..]
+        ]),
+        LookupStatus::Found(&MixedHashTree::Leaf(CERTIFIED_DATA.to_vec()))
+    );
+    assert!(certificate.delegation.is_none());
+}
+
+#[test]
+fn should_verify_certificate_with_delegation_to_subnet_of_canister() {
+    let rng = &mut reproducible_rng();
+    let canister_id = canister_test_id(1);
+    let (_cert, root_of_trust, cbor) =
+        CertificateBuilder::new_with_rng(canister_data(canister_id), rng)
+            .with_delegation(CertificateBuilder::new_with_rng(
+                subnet_data(vec![(canister_test_id(0), canister_test_id(10))]),
+                rng,
+            ))
+            .build();
+
+    let certificate = verify_certified_response(&cbor, &canister_id, &root_of_trust).unwrap();
+
+    assert!(certificate.delegation.is_some());
+}
+
+#[test]
+fn should_fail_to_verify_against_wrong_root_of_trust() {
+    let rng = &mut reproducible_rng();
+    let canister_id = canister_test_id(1);
+    for with_delegation in [false, true] {
+        let mut builder = CertificateBuilder::new_with_rng(canister_data(canister_id), rng);
+        if with_delegation {
+            builder = builder.with_delegation(CertificateBuilder::new_with_rng(
+                subnet_data(vec![(canister_test_id(0), canister_test_id(10))]),
+                rng,
+            ));
+        }
+        let (_cert, _root_of_trust, cbor) = builder.build();
+        let wrong_root_of_trust =
+            CertificateBuilder::new_with_rng(canister_data(canister_id), rng).get_root_public_key();
+
+        let result = verify_certified_response(&cbor, &canister_id, &wrong_root_of_trust);
+
+        assert_matches!(result, Err(CryptoError::SignatureVerification { .. }));
+    }
+}
+
+#[test]
+fn should_fail_to_verify_if_canister_is_not_in_ranges_of_delegated_subnet() {
+    let rng = &mut reproducible_rng();
+    let canister_id = canister_test_id(11);
+    let (_cert, root_of_trust, cbor) =
+        CertificateBuilder::new_with_rng(canister_data(canister_id), rng)
+            .with_delegation(CertificateBuilder::new_with_rng(
+                subnet_data(vec![(canister_test_id(0), canister_test_id(10))]),
+                rng,
+            ))
+            .build();
+
+    let result = verify_certified_response(&cbor, &canister_id, &root_of_trust);
+
+    assert_matches!(
+        result,
+        Err(CryptoError::SignatureVerification { internal_error, .. })
+            if internal_error.contains("canister id does not match")
+    );
+}
+
+#[test]
+fn should_fail_to_verify_certificate_with_nested_delegations() {
+    let rng = &mut reproducible_rng();
+    let canister_id = canister_test_id(1);
+    let ranges = vec![(canister_test_id(0), canister_test_id(10))];
+    let (_cert, root_of_trust, cbor) =
+        CertificateBuilder::new_with_rng(canister_data(canister_id), rng)
+            .with_delegation(
+                CertificateBuilder::new_with_rng(subnet_data(ranges.clone()), rng)
+                    .with_delegation(CertificateBuilder::new_with_rng(subnet_data(ranges), rng)),
+            )
+            .build();
+
+    let result = verify_certified_response(&cbor, &canister_id, &root_of_trust);
+
+    assert_matches!(
+        result,
+        Err(CryptoError::SignatureVerification { internal_error, .. })
+            if internal_error.contains("nested delegations")
+    );
+}
+
+#[test]
+fn should_fail_to_verify_malformed_certificate() {
+    let rng = &mut reproducible_rng();
+    let canister_id = canister_test_id(1);
+    let (_cert, root_of_trust, _cbor) =
+        CertificateBuilder::new_with_rng(canister_data(canister_id), rng).build();
+
+    let result = verify_certified_response(b"not a certificate", &canister_id, &root_of_trust);
+
+    assert_matches!(result, Err(CryptoError::MalformedSignature { .. }));
+}
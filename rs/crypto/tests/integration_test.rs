@@ -885,6 +885,10 @@ fn algorithm_id_should_match_algorithm_id_proto() {
         AlgorithmId::MegaSecp256k1 as i32,
         AlgorithmIdProto::MegaSecp256k1 as i32
     );
+    assert_eq!(
+        AlgorithmId::RsaPssSha256 as i32,
+        AlgorithmIdProto::RsaPssSha256 as i32
+    );
 }
 
 #[test]
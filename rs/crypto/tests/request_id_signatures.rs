@@ -1,8 +1,9 @@
 #![allow(clippy::unwrap_used)]
 use ic_config::crypto::CryptoConfig;
 use ic_crypto::{
-    ecdsa_p256_signature_from_der_bytes, ed25519_public_key_to_der, user_public_key_from_bytes,
-    CryptoComponent, KeyBytesContentType,
+    ecdsa_p256_signature_from_der_bytes, ecdsa_secp256k1_public_key_from_sec1_bytes,
+    ecdsa_secp256k1_signature_from_der_bytes, ed25519_public_key_to_der,
+    user_public_key_from_bytes, CryptoComponent, KeyBytesContentType,
 };
 use ic_crypto_internal_test_vectors::test_data;
 use ic_interfaces::crypto::BasicSigVerifierByPublicKey;
@@ -17,7 +18,8 @@ use std::sync::Arc;
 
 use ic_crypto_test_utils::ed25519_utils::ed25519_signature_and_public_key;
 use ic_interfaces::time_source::SysTimeSource;
-use openssl::ec::{EcGroup, EcKey};
+use openssl::bn::BigNumContext;
+use openssl::ec::{EcGroup, EcKey, PointConversionForm};
 use openssl::ecdsa::EcdsaSig;
 use openssl::nid::Nid;
 use openssl::pkey::PKey;
@@ -296,6 +298,60 @@ fn should_correctly_parse_der_encoded_openssl_ecdsa_secp256k1_pk() {
     assert_eq!(bytes_type, KeyBytesContentType::EcdsaSecp256k1PublicKeyDer);
 }
 
+#[test]
+fn should_verify_der_encoded_secp256k1_sig_with_sec1_encoded_pk() {
+    let request_id = MessageId::from([42; 32]);
+    let group = EcGroup::from_curve_name(Nid::SECP256K1).expect("unable to create EC group");
+    let ec_key = EcKey::generate(&group).expect("unable to generate EC key");
+    let sig_der = {
+        let mut buf = vec![];
+        buf.extend_from_slice(DOMAIN_IC_REQUEST);
+        buf.extend_from_slice(request_id.as_bytes());
+        EcdsaSig::sign(&sha256(&buf), &ec_key)
+            .and_then(|ecdsa_sig| ecdsa_sig.to_der())
+            .expect("unable to ECDSA-sign")
+    };
+    for conversion_form in [
+        PointConversionForm::COMPRESSED,
+        PointConversionForm::UNCOMPRESSED,
+    ] {
+        let pk_sec1 = ec_key
+            .public_key()
+            .to_bytes(&group, conversion_form, &mut BigNumContext::new().unwrap())
+            .expect("unable to SEC1-encode public key");
+
+        let (pk, bytes_type) = ecdsa_secp256k1_public_key_from_sec1_bytes(&pk_sec1).unwrap();
+        let sig = BasicSigOf::new(ecdsa_secp256k1_signature_from_der_bytes(&sig_der).unwrap());
+
+        assert_eq!(pk.algorithm_id, AlgorithmId::EcdsaSecp256k1);
+        assert_eq!(bytes_type, KeyBytesContentType::EcdsaSecp256k1PublicKeySec1);
+        CryptoConfig::run_with_temp_config(|config| {
+            let crypto = crypto_component(&config);
+            assert!(crypto
+                .verify_basic_sig_by_public_key(&sig, &request_id, &pk)
+                .is_ok());
+        });
+    }
+}
+
+#[test]
+fn should_fail_parsing_sec1_encoded_secp256k1_pk_on_wrong_curve() {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).expect("unable to create EC group");
+    let ec_key = EcKey::generate(&group).expect("unable to generate EC key");
+    let pk_sec1 = ec_key
+        .public_key()
+        .to_bytes(
+            &group,
+            PointConversionForm::UNCOMPRESSED,
+            &mut BigNumContext::new().unwrap(),
+        )
+        .expect("unable to SEC1-encode public key");
+
+    let pk_result = ecdsa_secp256k1_public_key_from_sec1_bytes(&pk_sec1);
+
+    assert!(pk_result.unwrap_err().is_malformed_public_key());
+}
+
 #[test]
 fn should_fail_parsing_corrupted_raw_ed25519_pk() {
     let pk_raw = hex::decode(test_data::ED25519_PK_1_RFC8032_HEX).unwrap();
@@ -1347,6 +1347,322 @@ mod communication {
     }
 }
 
+mod alpn {
+    use super::*;
+
+    const HTTP_2: &[u8] = b"h2";
+    const P2P: &[u8] = b"ic-p2p";
+
+    #[test]
+    fn should_negotiate_protocol_supported_by_client_and_server() {
+        let registry = TlsRegistry::new();
+        let (server_builder, client_builder) =
+            matching_server_and_client_builders(SERVER_ID_1, CLIENT_ID_1);
+        let server = server_builder
+            .with_alpn_protocols(&[HTTP_2, P2P])
+            .expect_alpn_protocol(Some(P2P))
+            .build(registry.get());
+        let client = client_builder
+            .with_alpn_protocols(&[P2P])
+            .expect_alpn_protocol(Some(P2P))
+            .build(registry.get());
+        registry
+            .add_cert(SERVER_ID_1, server.cert())
+            .add_cert(CLIENT_ID_1, client.cert())
+            .update();
+
+        let (client_result, authenticated_client) = new_tokio_runtime()
+            .block_on(async { tokio::join!(client.run(server.port()), server.run()) });
+
+        assert!(client_result.is_ok());
+        assert_peer_node_eq(authenticated_client.unwrap(), CLIENT_ID_1);
+    }
+
+    #[test]
+    fn should_negotiate_no_protocol_if_client_offers_none() {
+        let registry = TlsRegistry::new();
+        let (server_builder, client_builder) =
+            matching_server_and_client_builders(SERVER_ID_1, CLIENT_ID_1);
+        let server = server_builder
+            .with_alpn_protocols(&[HTTP_2, P2P])
+            .expect_alpn_protocol(None)
+            .build(registry.get());
+        let client = client_builder
+            .expect_alpn_protocol(None)
+            .build(registry.get());
+        registry
+            .add_cert(SERVER_ID_1, server.cert())
+            .add_cert(CLIENT_ID_1, client.cert())
+            .update();
+
+        let (client_result, authenticated_client) = new_tokio_runtime()
+            .block_on(async { tokio::join!(client.run(server.port()), server.run()) });
+
+        assert!(client_result.is_ok());
+        assert_peer_node_eq(authenticated_client.unwrap(), CLIENT_ID_1);
+    }
+
+    #[test]
+    fn should_return_error_if_client_and_server_have_no_protocol_in_common() {
+        let registry = TlsRegistry::new();
+        let (server_builder, client_builder) =
+            matching_server_and_client_builders(SERVER_ID_1, CLIENT_ID_1);
+        let server = server_builder
+            .with_alpn_protocols(&[HTTP_2])
+            .build(registry.get());
+        let client = client_builder
+            .with_alpn_protocols(&[P2P])
+            .build(registry.get());
+        registry
+            .add_cert(SERVER_ID_1, server.cert())
+            .add_cert(CLIENT_ID_1, client.cert())
+            .update();
+
+        let (client_result, server_result) = new_tokio_runtime()
+            .block_on(async { tokio::join!(client.run(server.port()), server.run()) });
+
+        assert_matches!(
+            client_result,
+            Err(TlsClientHandshakeError::HandshakeError { .. })
+        );
+        assert_matches!(
+            server_result,
+            Err(TlsServerHandshakeError::HandshakeError { .. })
+        );
+    }
+}
+
+mod tls_policy {
+    use super::*;
+    use ic_config::crypto::{TlsCipherSuite, TlsKeyExchangeGroup, TlsPolicy};
+
+    #[test]
+    fn should_perform_tls_handshake_if_policies_have_algorithms_in_common() {
+        let registry = TlsRegistry::new();
+        let (server_builder, client_builder) =
+            matching_server_and_client_builders(SERVER_ID_1, CLIENT_ID_1);
+        let server = server_builder
+            .with_tls_policy(TlsPolicy {
+                cipher_suites: vec![TlsCipherSuite::Aes128GcmSha256],
+                key_exchange_groups: vec![TlsKeyExchangeGroup::Secp384r1],
+                ..TlsPolicy::default()
+            })
+            .build(registry.get());
+        let client = client_builder.build(registry.get());
+        registry
+            .add_cert(SERVER_ID_1, server.cert())
+            .add_cert(CLIENT_ID_1, client.cert())
+            .update();
+
+        let (client_result, authenticated_client) = new_tokio_runtime()
+            .block_on(async { tokio::join!(client.run(server.port()), server.run()) });
+
+        assert!(client_result.is_ok());
+        assert_peer_node_eq(authenticated_client.unwrap(), CLIENT_ID_1);
+    }
+
+    #[test]
+    fn should_return_error_if_policies_have_no_cipher_suite_in_common() {
+        let registry = TlsRegistry::new();
+        let (server_builder, client_builder) =
+            matching_server_and_client_builders(SERVER_ID_1, CLIENT_ID_1);
+        let server = server_builder
+            .with_tls_policy(TlsPolicy {
+                cipher_suites: vec![TlsCipherSuite::Aes128GcmSha256],
+                ..TlsPolicy::default()
+            })
+            .build(registry.get());
+        let client = client_builder
+            .with_tls_policy(TlsPolicy {
+                cipher_suites: vec![TlsCipherSuite::Aes256GcmSha384],
+                ..TlsPolicy::default()
+            })
+            .build(registry.get());
+        registry
+            .add_cert(SERVER_ID_1, server.cert())
+            .add_cert(CLIENT_ID_1, client.cert())
+            .update();
+
+        let (client_result, server_result) = new_tokio_runtime()
+            .block_on(async { tokio::join!(client.run(server.port()), server.run()) });
+
+        assert_matches!(
+            client_result,
+            Err(TlsClientHandshakeError::HandshakeError { .. })
+        );
+        assert_matches!(
+            server_result,
+            Err(TlsServerHandshakeError::HandshakeError { .. })
+        );
+    }
+
+    #[test]
+    fn should_return_error_if_policies_have_no_key_exchange_group_in_common() {
+        let registry = TlsRegistry::new();
+        let (server_builder, client_builder) =
+            matching_server_and_client_builders(SERVER_ID_1, CLIENT_ID_1);
+        let server = server_builder
+            .with_tls_policy(TlsPolicy {
+                key_exchange_groups: vec![TlsKeyExchangeGroup::X25519],
+                ..TlsPolicy::default()
+            })
+            .build(registry.get());
+        let client = client_builder
+            .with_tls_policy(TlsPolicy {
+                key_exchange_groups: vec![TlsKeyExchangeGroup::Secp384r1],
+                ..TlsPolicy::default()
+            })
+            .build(registry.get());
+        registry
+            .add_cert(SERVER_ID_1, server.cert())
+            .add_cert(CLIENT_ID_1, client.cert())
+            .update();
+
+        let (client_result, server_result) = new_tokio_runtime()
+            .block_on(async { tokio::join!(client.run(server.port()), server.run()) });
+
+        assert_matches!(
+            client_result,
+            Err(TlsClientHandshakeError::HandshakeError { .. })
+        );
+        assert_matches!(
+            server_result,
+            Err(TlsServerHandshakeError::HandshakeError { .. })
+        );
+    }
+}
+
+mod session_resumption {
+    use super::*;
+    use ic_crypto_tls_interfaces::{AllowedClients, SomeOrAllNodes};
+    use std::collections::BTreeSet;
+
+    // The client receives the session tickets only after the handshake, when
+    // reading from the stream, so the first handshakes exchange a message.
+    fn server_and_client_exchanging_message() -> (Server, Client, TlsRegistry) {
+        let registry = TlsRegistry::new();
+        let (server_builder, client_builder) =
+            matching_server_and_client_builders(SERVER_ID_1, CLIENT_ID_1);
+        let msg = "hello from server";
+        let server = server_builder
+            .with_msg_for_client(msg)
+            .build(registry.get());
+        let client = client_builder
+            .expect_msg_from_server(msg)
+            .build(registry.get());
+        registry
+            .add_cert(SERVER_ID_1, server.cert())
+            .add_cert(CLIENT_ID_1, client.cert())
+            .update();
+        (server, client, registry)
+    }
+
+    #[test]
+    fn should_authenticate_client_in_repeated_handshakes() {
+        let (server, client, _registry) = server_and_client_exchanging_message();
+
+        let (first_results, second_results) = new_tokio_runtime().block_on(async {
+            let first_results = tokio::join!(client.run(server.port()), server.run());
+            let second_results = tokio::join!(client.run(server.port()), server.run());
+            (first_results, second_results)
+        });
+
+        for (client_result, authenticated_client) in [first_results, second_results] {
+            assert!(client_result.is_ok());
+            assert_peer_node_eq(authenticated_client.unwrap(), CLIENT_ID_1);
+        }
+    }
+
+    #[test]
+    fn should_return_error_if_client_of_resumed_session_no_longer_allowed() {
+        let (server, client, _registry) = server_and_client_exchanging_message();
+        let other_allowed_clients =
+            AllowedClients::new(SomeOrAllNodes::Some(BTreeSet::from([CLIENT_ID_2]))).unwrap();
+
+        let (first_results, (_client_result, server_result)) =
+            new_tokio_runtime().block_on(async {
+                let first_results = tokio::join!(client.run(server.port()), server.run());
+                let second_results = tokio::join!(
+                    client.run_handshake(server.port()),
+                    server.run_with_allowed_clients(other_allowed_clients)
+                );
+                (first_results, second_results)
+            });
+
+        assert_peer_node_eq(first_results.1.unwrap(), CLIENT_ID_1);
+        assert_handshake_server_error_containing(
+            &server_result,
+            &format!(
+                "failed to verify the client certificate of the resumed session: \
+                The peer certificate with node ID {} is not allowed",
+                CLIENT_ID_1
+            ),
+        );
+    }
+}
+
+mod pinned_cert {
+    use super::*;
+
+    #[test]
+    fn should_perform_tls_handshake_with_pinned_server_cert_without_client_registry() {
+        let server_registry = TlsRegistry::new();
+        let empty_client_registry = TlsRegistry::new();
+        let (server_builder, client_builder) =
+            matching_server_and_client_builders(SERVER_ID_1, CLIENT_ID_1);
+        let msg = "hello from server";
+        let server = server_builder
+            .with_msg_for_client(msg)
+            .build(server_registry.get());
+        let client = client_builder
+            .expect_msg_from_server(msg)
+            .build(empty_client_registry.get());
+        server_registry
+            .add_cert(SERVER_ID_1, server.cert())
+            .add_cert(CLIENT_ID_1, client.cert())
+            .update();
+
+        let (client_result, authenticated_client) = new_tokio_runtime().block_on(async {
+            tokio::join!(
+                client.run_with_pinned_cert(server.port(), server.cert()),
+                server.run()
+            )
+        });
+
+        assert!(client_result.is_ok());
+        assert_peer_node_eq(authenticated_client.unwrap(), CLIENT_ID_1);
+    }
+
+    #[test]
+    fn should_return_error_if_server_cert_differs_from_pinned_cert() {
+        let (server, client, registry) = matching_server_and_client(SERVER_ID_1, CLIENT_ID_1);
+        registry
+            .add_cert(SERVER_ID_1, server.cert())
+            .add_cert(CLIENT_ID_1, client.cert())
+            .update();
+
+        let (client_result, server_result) = new_tokio_runtime().block_on(async {
+            tokio::join!(
+                client.run_with_pinned_cert(server.port(), client.cert()),
+                server.run()
+            )
+        });
+
+        assert_handshake_client_error_containing(
+            &client_result,
+            &format!(
+                "The peer certificate is not trusted since it differs from the pinned \
+                certificate. NodeId of presented cert: {}",
+                SERVER_ID_1
+            ),
+        );
+        assert_handshake_server_error_containing(
+            &server_result,
+            "received fatal alert: HandshakeFailure",
+        );
+    }
+}
+
 fn new_tokio_runtime() -> tokio::runtime::Runtime {
     tokio::runtime::Runtime::new().expect("failed to build runtime")
 }
@@ -1,3 +1,4 @@
+use ic_config::crypto::TlsPolicy;
 use ic_crypto_temp_crypto::{NodeKeysToGenerate, TempCryptoComponent};
 use ic_crypto_tls_interfaces::TlsPublicKeyCert;
 use ic_registry_client_fake::FakeRegistryClient;
@@ -15,12 +16,21 @@ use ic_interfaces::crypto::KeyManager;
 pub fn temp_crypto_component_with_tls_keys(
     registry: Arc<FakeRegistryClient>,
     node_id: NodeId,
+) -> (TempCryptoComponent, TlsPublicKeyCert) {
+    temp_crypto_component_with_tls_keys_and_policy(registry, node_id, TlsPolicy::default())
+}
+
+pub fn temp_crypto_component_with_tls_keys_and_policy(
+    registry: Arc<FakeRegistryClient>,
+    node_id: NodeId,
+    tls_policy: TlsPolicy,
 ) -> (TempCryptoComponent, TlsPublicKeyCert) {
     let temp_crypto = TempCryptoComponent::builder()
         .with_registry(registry)
         .with_node_id(node_id)
         .with_keys(NodeKeysToGenerate::only_tls_key_and_cert())
         .with_remote_vault()
+        .with_tls_policy(tls_policy)
         .build();
     let tls_certificate = temp_crypto
         .current_node_public_keys()
@@ -1,8 +1,9 @@
 #![allow(clippy::unwrap_used)]
-use crate::tls_utils::{temp_crypto_component_with_tls_keys, REG_V1};
+use crate::tls_utils::{temp_crypto_component_with_tls_keys_and_policy, REG_V1};
+use ic_config::crypto::TlsPolicy;
 use ic_crypto_temp_crypto::TempCryptoComponent;
 use ic_crypto_tls_interfaces::TlsPublicKeyCert;
-use ic_crypto_tls_interfaces::{TlsClientHandshakeError, TlsHandshake};
+use ic_crypto_tls_interfaces::{TlsClientHandshakeError, TlsHandshake, TlsStream};
 use ic_protobuf::registry::crypto::v1::X509PublicKeyCert;
 use ic_registry_client_fake::FakeRegistryClient;
 use ic_types::NodeId;
@@ -16,6 +17,9 @@ pub struct ClientBuilder {
     msg_expected_from_server: Option<String>,
     msg_for_server: Option<String>,
     expected_error_substring_when_reading_stream: Option<String>,
+    alpn_protocols: Vec<Vec<u8>>,
+    expected_alpn_protocol: Option<Option<Vec<u8>>>,
+    tls_policy: TlsPolicy,
 }
 
 impl ClientBuilder {
@@ -34,8 +38,24 @@ impl ClientBuilder {
         self
     }
 
+    pub fn with_alpn_protocols(mut self, protocols: &[&[u8]]) -> Self {
+        self.alpn_protocols = protocols.iter().map(|protocol| protocol.to_vec()).collect();
+        self
+    }
+
+    pub fn expect_alpn_protocol(mut self, protocol: Option<&[u8]>) -> Self {
+        self.expected_alpn_protocol = Some(protocol.map(|protocol| protocol.to_vec()));
+        self
+    }
+
+    pub fn with_tls_policy(mut self, tls_policy: TlsPolicy) -> Self {
+        self.tls_policy = tls_policy;
+        self
+    }
+
     pub fn build(self, registry: Arc<FakeRegistryClient>) -> Client {
-        let (crypto, cert) = temp_crypto_component_with_tls_keys(registry, self.node_id);
+        let (crypto, cert) =
+            temp_crypto_component_with_tls_keys_and_policy(registry, self.node_id, self.tls_policy);
         Client {
             crypto,
             server_node_id: self.server_node_id,
@@ -43,6 +63,8 @@ impl ClientBuilder {
             msg_for_server: self.msg_for_server,
             expected_error_substring_when_reading_stream: self
                 .expected_error_substring_when_reading_stream,
+            alpn_protocols: self.alpn_protocols,
+            expected_alpn_protocol: self.expected_alpn_protocol,
             cert,
         }
     }
@@ -56,6 +78,8 @@ pub struct Client {
     msg_expected_from_server: Option<String>,
     msg_for_server: Option<String>,
     expected_error_substring_when_reading_stream: Option<String>,
+    alpn_protocols: Vec<Vec<u8>>,
+    expected_alpn_protocol: Option<Option<Vec<u8>>>,
     cert: TlsPublicKeyCert,
 }
 
@@ -67,18 +91,45 @@ impl Client {
             msg_expected_from_server: None,
             msg_for_server: None,
             expected_error_substring_when_reading_stream: None,
+            alpn_protocols: vec![],
+            expected_alpn_protocol: None,
+            tls_policy: TlsPolicy::default(),
         }
     }
 
     pub async fn run(&self, server_port: u16) -> Result<(), TlsClientHandshakeError> {
+        let tls_stream = self.run_handshake(server_port).await?;
+        self.communicate(tls_stream).await;
+        Ok(())
+    }
+
+    /// Runs the client like `run`, but authenticates the server against the
+    /// pinned `server_cert` instead of the registry.
+    pub async fn run_with_pinned_cert(
+        &self,
+        server_port: u16,
+        server_cert: X509PublicKeyCert,
+    ) -> Result<(), TlsClientHandshakeError> {
         let tcp_stream = TcpStream::connect(("127.0.0.1", server_port))
             .await
             .expect("failed to connect");
+        let server_cert = TlsPublicKeyCert::try_from(server_cert).expect("invalid server cert");
 
         let tls_stream = self
             .crypto
-            .perform_tls_client_handshake(tcp_stream, self.server_node_id, REG_V1)
+            .perform_tls_client_handshake_with_pinned_cert(tcp_stream, server_cert)
             .await?;
+        self.communicate(tls_stream).await;
+        Ok(())
+    }
+
+    async fn communicate(&self, tls_stream: Box<dyn TlsStream>) {
+        if let Some(expected_alpn_protocol) = &self.expected_alpn_protocol {
+            assert_eq!(
+                tls_stream.negotiated_alpn_protocol(),
+                expected_alpn_protocol.as_deref()
+            );
+        }
         let (mut rh, mut wh) = tokio::io::split(tls_stream);
 
         self.expect_msg_from_server_if_configured(&mut rh, &mut wh)
@@ -87,7 +138,26 @@ impl Client {
             .await;
         self.expect_error_substring_when_reading_stream_if_configured(&mut rh)
             .await;
-        Ok(())
+    }
+
+    /// Only performs the handshake, without exchanging any of the configured
+    /// messages.
+    pub async fn run_handshake(
+        &self,
+        server_port: u16,
+    ) -> Result<Box<dyn TlsStream>, TlsClientHandshakeError> {
+        let tcp_stream = TcpStream::connect(("127.0.0.1", server_port))
+            .await
+            .expect("failed to connect");
+
+        self.crypto
+            .perform_tls_client_handshake(
+                tcp_stream,
+                self.server_node_id,
+                REG_V1,
+                self.alpn_protocols.clone(),
+            )
+            .await
     }
 
     async fn send_msg_to_server_if_configured<W: AsyncWrite + Unpin, R: AsyncRead + Unpin>(
@@ -50,7 +50,7 @@ impl OpenSslClient {
 
         let _tls_stream = self
             .crypto
-            .perform_tls_client_handshake(tcp_stream, self.server_node_id, REG_V1)
+            .perform_tls_client_handshake(tcp_stream, self.server_node_id, REG_V1, vec![])
             .await?;
 
         Ok(())
@@ -1,9 +1,11 @@
 #![allow(clippy::unwrap_used)]
-use crate::tls_utils::{temp_crypto_component_with_tls_keys, REG_V1};
+use crate::tls_utils::{temp_crypto_component_with_tls_keys_and_policy, REG_V1};
+use ic_config::crypto::TlsPolicy;
 use ic_crypto_temp_crypto::TempCryptoComponent;
 use ic_crypto_tls_interfaces::TlsPublicKeyCert;
 use ic_crypto_tls_interfaces::{
     AllowedClients, AuthenticatedPeer, SomeOrAllNodes, TlsHandshake, TlsServerHandshakeError,
+    TlsStream,
 };
 use ic_protobuf::registry::crypto::v1::X509PublicKeyCert;
 use ic_registry_client_fake::FakeRegistryClient;
@@ -18,6 +20,9 @@ pub struct ServerBuilder {
     msg_for_client: Option<String>,
     msg_expected_from_client: Option<String>,
     allowed_nodes: Option<SomeOrAllNodes>,
+    alpn_protocols: Vec<Vec<u8>>,
+    expected_alpn_protocol: Option<Option<Vec<u8>>>,
+    tls_policy: TlsPolicy,
 }
 
 impl ServerBuilder {
@@ -31,6 +36,21 @@ impl ServerBuilder {
         self
     }
 
+    pub fn with_alpn_protocols(mut self, protocols: &[&[u8]]) -> Self {
+        self.alpn_protocols = protocols.iter().map(|protocol| protocol.to_vec()).collect();
+        self
+    }
+
+    pub fn expect_alpn_protocol(mut self, protocol: Option<&[u8]>) -> Self {
+        self.expected_alpn_protocol = Some(protocol.map(|protocol| protocol.to_vec()));
+        self
+    }
+
+    pub fn with_tls_policy(mut self, tls_policy: TlsPolicy) -> Self {
+        self.tls_policy = tls_policy;
+        self
+    }
+
     pub fn add_allowed_client(mut self, client: NodeId) -> Self {
         match self.allowed_nodes {
             None => {
@@ -67,7 +87,8 @@ impl ServerBuilder {
 
     pub fn build(self, registry: Arc<FakeRegistryClient>) -> Server {
         let listener = std::net::TcpListener::bind(("0.0.0.0", 0)).expect("failed to bind");
-        let (crypto, cert) = temp_crypto_component_with_tls_keys(registry, self.node_id);
+        let (crypto, cert) =
+            temp_crypto_component_with_tls_keys_and_policy(registry, self.node_id, self.tls_policy);
         let allowed_clients = AllowedClients::new(
             self.allowed_nodes
                 .unwrap_or_else(|| SomeOrAllNodes::Some(BTreeSet::new())),
@@ -79,6 +100,8 @@ impl ServerBuilder {
             allowed_clients,
             msg_for_client: self.msg_for_client,
             msg_expected_from_client: self.msg_expected_from_client,
+            alpn_protocols: self.alpn_protocols,
+            expected_alpn_protocol: self.expected_alpn_protocol,
             cert,
         }
     }
@@ -92,6 +115,8 @@ pub struct Server {
     allowed_clients: AllowedClients,
     msg_for_client: Option<String>,
     msg_expected_from_client: Option<String>,
+    alpn_protocols: Vec<Vec<u8>>,
+    expected_alpn_protocol: Option<Option<Vec<u8>>>,
     cert: TlsPublicKeyCert,
 }
 
@@ -102,16 +127,40 @@ impl Server {
             msg_for_client: None,
             msg_expected_from_client: None,
             allowed_nodes: None,
+            alpn_protocols: vec![],
+            expected_alpn_protocol: None,
+            tls_policy: TlsPolicy::default(),
         }
     }
 
     pub async fn run(&self) -> Result<AuthenticatedPeer, TlsServerHandshakeError> {
+        self.run_with_allowed_clients(self.allowed_clients.clone())
+            .await
+    }
+
+    /// Runs the server with `allowed_clients` instead of the allowed clients
+    /// configured in the builder.
+    pub async fn run_with_allowed_clients(
+        &self,
+        allowed_clients: AllowedClients,
+    ) -> Result<AuthenticatedPeer, TlsServerHandshakeError> {
         let tcp_stream = self.accept_connection_on_listener().await;
 
         let (tls_stream, authenticated_node) = self
             .crypto
-            .perform_tls_server_handshake(tcp_stream, self.allowed_clients.clone(), REG_V1)
+            .perform_tls_server_handshake(
+                tcp_stream,
+                allowed_clients,
+                REG_V1,
+                self.alpn_protocols.clone(),
+            )
             .await?;
+        if let Some(expected_alpn_protocol) = &self.expected_alpn_protocol {
+            assert_eq!(
+                tls_stream.negotiated_alpn_protocol(),
+                expected_alpn_protocol.as_deref()
+            );
+        }
         let (mut rh, mut wh) = tokio::io::split(tls_stream);
 
         self.send_msg_to_client_if_configured(&mut wh, &mut rh)
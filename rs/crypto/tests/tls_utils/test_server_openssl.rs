@@ -80,7 +80,7 @@ impl OpenSslServer {
 
         let (_tls_stream, authenticated_node) = self
             .crypto
-            .perform_tls_server_handshake(tcp_stream, self.allowed_clients.clone(), REG_V1)
+            .perform_tls_server_handshake(tcp_stream, self.allowed_clients.clone(), REG_V1, vec![])
             .await?;
 
         Ok(authenticated_node)
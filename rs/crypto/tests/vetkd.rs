@@ -0,0 +1,196 @@
+#![allow(clippy::unwrap_used)]
+use assert_matches::assert_matches;
+use ic_base_types::PrincipalId;
+use ic_crypto_internal_bls12_381_type::G2Affine;
+use ic_crypto_internal_bls12_381_vetkd::{
+    DerivationPath, DerivedPublicKey, EncryptedKey, TransportSecretKey,
+};
+use ic_crypto_temp_crypto::TempCryptoComponent;
+use ic_crypto_test_utils::crypto_for;
+use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
+use ic_crypto_test_utils_threshold_sigs::non_interactive::{
+    run_ni_dkg_and_create_single_transcript, NiDkgTestEnvironment, RandomNiDkgConfig,
+};
+use ic_interfaces::crypto::{LoadTranscriptResult, NiDkgAlgorithm, VetKdProtocol};
+use ic_types::crypto::canister_threshold_sig::ExtendedDerivationPath;
+use ic_types::crypto::threshold_sig::ni_dkg::{DkgId, NiDkgTag, NiDkgTranscript};
+use ic_types::crypto::threshold_sig::ThresholdSigPublicKey;
+use ic_types::crypto::vetkd::{
+    VetKdArgs, VetKdEncryptedKeyShare, VetKdKeyShareCombinationError,
+    VetKdKeyShareVerificationError,
+};
+use ic_types::NodeId;
+use rand::prelude::*;
+use std::collections::BTreeMap;
+
+const SUBNET_SIZE: usize = 4;
+
+#[test]
+fn should_derive_same_decryptable_key_from_any_threshold_of_shares() {
+    let rng = &mut reproducible_rng();
+    let (transcript, crypto_components) = setup_with_loaded_transcript(SUBNET_SIZE);
+    let threshold = transcript.threshold.get().get() as usize;
+    let transport_secret_key = TransportSecretKey::generate(rng);
+    let args = vetkd_args(&transcript, &transport_secret_key);
+    let key_shares = create_key_shares(&transcript, &args, &crypto_components);
+
+    let verifier = random_node_in(&transcript, rng);
+    for (signer, key_share) in &key_shares {
+        assert_eq!(
+            crypto_for(verifier, &crypto_components)
+                .verify_encrypted_key_share(*signer, key_share, &args),
+            Ok(())
+        );
+    }
+
+    let derived_keys: Vec<_> = (0..2)
+        .map(|_| {
+            let signers: BTreeMap<NodeId, VetKdEncryptedKeyShare> = key_shares
+                .iter()
+                .map(|(signer, key_share)| (*signer, key_share.clone()))
+                .choose_multiple(rng, threshold)
+                .into_iter()
+                .collect();
+            let combiner = random_node_in(&transcript, rng);
+            let encrypted_key = crypto_for(combiner, &crypto_components)
+                .combine_encrypted_key_shares(&signers, &args)
+                .unwrap();
+            assert_eq!(
+                crypto_for(verifier, &crypto_components)
+                    .verify_encrypted_key(&encrypted_key, &args),
+                Ok(())
+            );
+            transport_secret_key
+                .decrypt(
+                    &EncryptedKey::deserialize(&encrypted_key.encrypted_key).unwrap(),
+                    &derived_public_key(&transcript, &args.derivation_path),
+                    &args.derivation_id,
+                )
+                .expect("failed to decrypt the encrypted key")
+        })
+        .collect();
+    assert_eq!(derived_keys[0], derived_keys[1]);
+}
+
+#[test]
+fn should_fail_to_verify_key_share_for_other_signer_or_arguments() {
+    let rng = &mut reproducible_rng();
+    let (transcript, crypto_components) = setup_with_loaded_transcript(SUBNET_SIZE);
+    let args = vetkd_args(&transcript, &TransportSecretKey::generate(rng));
+    let key_shares = create_key_shares(&transcript, &args, &crypto_components);
+    let mut signers = key_shares.keys();
+    let (signer, other_signer) = (*signers.next().unwrap(), *signers.next().unwrap());
+    let verifier = crypto_for(random_node_in(&transcript, rng), &crypto_components);
+
+    assert_eq!(
+        verifier.verify_encrypted_key_share(other_signer, &key_shares[&signer], &args),
+        Err(VetKdKeyShareVerificationError::VerificationError)
+    );
+    let other_args = VetKdArgs {
+        derivation_id: b"other derivation ID".to_vec(),
+        ..args
+    };
+    assert_eq!(
+        verifier.verify_encrypted_key_share(signer, &key_shares[&signer], &other_args),
+        Err(VetKdKeyShareVerificationError::VerificationError)
+    );
+}
+
+#[test]
+fn should_fail_to_combine_insufficient_shares() {
+    let rng = &mut reproducible_rng();
+    let (transcript, crypto_components) = setup_with_loaded_transcript(SUBNET_SIZE);
+    let threshold = transcript.threshold.get().get();
+    let args = vetkd_args(&transcript, &TransportSecretKey::generate(rng));
+    let key_shares: BTreeMap<_, _> = create_key_shares(&transcript, &args, &crypto_components)
+        .into_iter()
+        .take(threshold as usize - 1)
+        .collect();
+
+    let result = crypto_for(random_node_in(&transcript, rng), &crypto_components)
+        .combine_encrypted_key_shares(&key_shares, &args);
+
+    assert_matches!(
+        result,
+        Err(VetKdKeyShareCombinationError::UnsatisfiedReconstructionThreshold {
+            threshold: t,
+            share_count
+        }) if t == threshold && share_count == key_shares.len()
+    );
+}
+
+fn setup_with_loaded_transcript(
+    subnet_size: usize,
+) -> (NiDkgTranscript, BTreeMap<NodeId, TempCryptoComponent>) {
+    let config = RandomNiDkgConfig::builder()
+        .subnet_size(subnet_size)
+        .dkg_tag(NiDkgTag::HighThreshold)
+        .build()
+        .into_config();
+    let crypto_components = NiDkgTestEnvironment::new_for_config(&config).crypto_components;
+    let transcript = run_ni_dkg_and_create_single_transcript(&config, &crypto_components);
+    for node_id in config.receivers().get() {
+        assert_eq!(
+            crypto_for(*node_id, &crypto_components).load_transcript(&transcript),
+            Ok(LoadTranscriptResult::SigningKeyAvailable)
+        );
+    }
+    (transcript, crypto_components)
+}
+
+fn vetkd_args(
+    transcript: &NiDkgTranscript,
+    transport_secret_key: &TransportSecretKey,
+) -> VetKdArgs {
+    VetKdArgs {
+        dkg_id: DkgId::NiDkgId(transcript.dkg_id),
+        derivation_path: ExtendedDerivationPath {
+            caller: PrincipalId::new_user_test_id(1),
+            derivation_path: vec![b"vetkd".to_vec()],
+        },
+        derivation_id: b"derivation ID".to_vec(),
+        encryption_public_key: transport_secret_key.public_key().serialize().to_vec(),
+    }
+}
+
+fn create_key_shares(
+    transcript: &NiDkgTranscript,
+    args: &VetKdArgs,
+    crypto_components: &BTreeMap<NodeId, TempCryptoComponent>,
+) -> BTreeMap<NodeId, VetKdEncryptedKeyShare> {
+    transcript
+        .committee
+        .get()
+        .iter()
+        .map(|node_id| {
+            let key_share = crypto_for(*node_id, crypto_components)
+                .create_encrypted_key_share(args.clone())
+                .unwrap_or_else(|e| panic!("key share creation by node {} failed: {}", node_id, e));
+            (*node_id, key_share)
+        })
+        .collect()
+}
+
+fn derived_public_key(
+    transcript: &NiDkgTranscript,
+    derivation_path: &ExtendedDerivationPath,
+) -> DerivedPublicKey {
+    let master_public_key =
+        G2Affine::deserialize(&ThresholdSigPublicKey::from(transcript).into_bytes()).unwrap();
+    DerivedPublicKey::compute_derived_key(
+        &master_public_key,
+        &DerivationPath::new(
+            derivation_path.caller.as_slice(),
+            &derivation_path.derivation_path,
+        ),
+    )
+}
+
+fn random_node_in<R: Rng>(transcript: &NiDkgTranscript, rng: &mut R) -> NodeId {
+    *transcript
+        .committee
+        .get()
+        .iter()
+        .choose(rng)
+        .expect("nodes empty")
+}
@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use ic_base_types::{NodeId, RegistryVersion};
 use ic_crypto_tls_interfaces::{
-    AllowedClients, AuthenticatedPeer, TlsClientHandshakeError, TlsHandshake,
+    AllowedClients, AuthenticatedPeer, TlsClientHandshakeError, TlsHandshake, TlsPublicKeyCert,
     TlsServerHandshakeError, TlsStream,
 };
 use mockall::*;
@@ -17,6 +17,7 @@ mock! {
             tcp_stream: TcpStream,
             allowed_clients: AllowedClients,
             registry_version: RegistryVersion,
+            alpn_protocols: Vec<Vec<u8>>,
         ) -> Result<(Box<dyn TlsStream>, AuthenticatedPeer), TlsServerHandshakeError>;
 
         async fn perform_tls_server_handshake_without_client_auth(
@@ -30,6 +31,13 @@ mock! {
             tcp_stream: TcpStream,
             server: NodeId,
             registry_version: RegistryVersion,
+            alpn_protocols: Vec<Vec<u8>>,
+        ) -> Result<Box<dyn TlsStream>, TlsClientHandshakeError>;
+
+        async fn perform_tls_client_handshake_with_pinned_cert(
+            &self,
+            tcp_stream: TcpStream,
+            server_cert: TlsPublicKeyCert,
         ) -> Result<Box<dyn TlsStream>, TlsClientHandshakeError>;
     }
 }
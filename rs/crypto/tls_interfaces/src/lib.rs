@@ -215,6 +215,9 @@ pub enum TlsClientHandshakeError {
         internal_error: String,
     },
     MalformedServerCertificate(MalformedPeerCertificateError),
+    SelfCertificateUnavailable {
+        internal_error: String,
+    },
     HandshakeError {
         internal_error: String,
     },
@@ -246,7 +249,11 @@ impl From<MalformedPeerCertificateError> for TlsClientHandshakeError {
 ///
 /// [tokio-rustls' documentation]: https://docs.rs/tokio-rustls/latest/tokio_rustls/
 /// [Why do I need to call poll_flush?]: https://docs.rs/tokio-rustls/latest/tokio_rustls/#why-do-i-need-to-call-poll_flush
-pub trait TlsStream: AsyncRead + AsyncWrite + Send + Unpin {}
+pub trait TlsStream: AsyncRead + AsyncWrite + Send + Unpin {
+    /// Returns the application protocol that was negotiated via ALPN in the
+    /// handshake, or `None` if no protocol was negotiated.
+    fn negotiated_alpn_protocol(&self) -> Option<&[u8]>;
+}
 
 #[async_trait]
 /// Implementors provide methods for transforming TCP streams into TLS stream.
@@ -264,6 +271,8 @@ pub trait TlsHandshake {
     /// * Minimum protocol version: TLS 1.3
     /// * Supported signature algorithms: ed25519
     /// * Allowed cipher suites: TLS_AES_128_GCM_SHA256, TLS_AES_256_GCM_SHA384
+    /// * Allowed cipher suites and key exchange groups: restricted by the
+    ///   node's TLS policy in the crypto config
     /// * Client authentication: mandatory, with ed25519 certificate
     /// * Maximum number of intermediate CA certificates: 1
     ///
@@ -279,6 +288,12 @@ pub trait TlsHandshake {
     ///    to C_handshake, then the peer successfully authenticated as node
     ///    N_claimed.
     ///
+    /// If `alpn_protocols` is not empty, the server selects the first protocol
+    /// in `alpn_protocols` that the client offers via ALPN, and the handshake
+    /// fails if the client offers protocols but none of them. The protocol is
+    /// available from the returned stream via
+    /// `TlsStream::negotiated_alpn_protocol`.
+    ///
     /// The given `tcp_stream` is consumed. If an error is returned, the TCP
     /// connection is therefore dropped.
     ///
@@ -311,6 +326,7 @@ pub trait TlsHandshake {
         tcp_stream: TcpStream,
         allowed_clients: AllowedClients,
         registry_version: RegistryVersion,
+        alpn_protocols: Vec<Vec<u8>>,
     ) -> Result<(Box<dyn TlsStream>, AuthenticatedPeer), TlsServerHandshakeError>;
 
     /// Transforms a TCP stream into a TLS stream by performing a TLS server
@@ -320,6 +336,8 @@ pub trait TlsHandshake {
     /// * Minimum protocol version: TLS 1.3
     /// * Supported signature algorithms: ed25519
     /// * Allowed cipher suites: TLS_AES_128_GCM_SHA256, TLS_AES_256_GCM_SHA384
+    /// * Allowed cipher suites and key exchange groups: restricted by the
+    ///   node's TLS policy in the crypto config
     /// * Client authentication: no client authentication is performed
     ///
     /// Whenever the TLS handshake fails, this method returns an error.
@@ -354,6 +372,8 @@ pub trait TlsHandshake {
     /// * Minimum protocol version: TLS 1.3
     /// * Supported signature algorithms: ed25519
     /// * Allowed cipher suites: TLS_AES_128_GCM_SHA256, TLS_AES_256_GCM_SHA384
+    /// * Allowed cipher suites and key exchange groups: restricted by the
+    ///   node's TLS policy in the crypto config
     /// * Server authentication: mandatory, with ed25519 certificate
     ///
     /// To determine whether the peer (that successfully performed the
@@ -366,6 +386,12 @@ pub trait TlsHandshake {
     ///    TLS certificate of node with ID N_claimed. Return an error if the
     ///    C_registry does not equal C_handshake.
     ///
+    /// If `alpn_protocols` is not empty, the client offers them via ALPN in
+    /// the given order of preference, and the handshake fails if the server
+    /// selects another protocol. The protocol selected by the server, if any,
+    /// is available from the returned stream via
+    /// `TlsStream::negotiated_alpn_protocol`.
+    ///
     /// The given `tcp_stream` is consumed. If an error is returned, the TCP
     /// connection is therefore dropped.
     ///
@@ -395,6 +421,52 @@ pub trait TlsHandshake {
         tcp_stream: TcpStream,
         server: NodeId,
         registry_version: RegistryVersion,
+        alpn_protocols: Vec<Vec<u8>>,
+    ) -> Result<Box<dyn TlsStream>, TlsClientHandshakeError>;
+
+    /// Transforms a TCP stream into a TLS stream by first performing a TLS
+    /// client handshake and then verifying that the peer presented exactly
+    /// the given `server_cert`.
+    ///
+    /// In contrast to `perform_tls_client_handshake`, the registry is not
+    /// accessed: the server is authenticated against the pinned `server_cert`
+    /// supplied by the caller, and the client authenticates with the node's
+    /// TLS certificate from its local public key store. This allows to
+    /// connect to a node before, or without, the registry being available.
+    ///
+    /// For the handshake, the client uses the same configuration as
+    /// `perform_tls_client_handshake`. Sessions are not resumed, and no
+    /// application protocol is negotiated.
+    ///
+    /// To determine whether the peer (that successfully performed the
+    /// handshake) is trusted, the following steps are taken:
+    /// 1. Determine the peer's node ID N_claimed from the _subject name_ of
+    ///    the certificate C_handshake that the peer presented during the
+    ///    handshake (and for which the peer therefore knows the private key).
+    ///    Return an error if the subject name cannot be parsed as node ID.
+    /// 2. Return an error if C_handshake does not equal `server_cert`.
+    ///
+    /// The given `tcp_stream` is consumed. If an error is returned, the TCP
+    /// connection is therefore dropped.
+    ///
+    /// # Errors
+    /// * TlsClientHandshakeError::SelfCertificateUnavailable if the node's own
+    ///   client certificate cannot be obtained from the public key store.
+    /// * TlsClientHandshakeError::MalformedSelfCertificate if the node's own
+    ///   client certificate is malformed.
+    /// * TlsClientHandshakeError::HandshakeError if there is an error during
+    ///   the TLS handshake, or the handshake fails, e.g., if the server's
+    ///   certificate presented in the handshake does not exactly match
+    ///   `server_cert`.
+    ///
+    /// # Panics
+    /// * If the secret key corresponding to the client certificate cannot be
+    ///   found or is malformed in the client's secret key store. Note that this
+    ///   is an error in the setup of the node.
+    async fn perform_tls_client_handshake_with_pinned_cert(
+        &self,
+        tcp_stream: TcpStream,
+        server_cert: TlsPublicKeyCert,
     ) -> Result<Box<dyn TlsStream>, TlsClientHandshakeError>;
 }
 
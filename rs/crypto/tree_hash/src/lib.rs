@@ -1,3 +1,16 @@
+//! Hash trees as used for the certified state of the IC, and the operations
+//! to build and verify partial trees:
+//!
+//! * [HashTreeBuilder] builds the [HashTree] of a [LabeledTree], and the
+//!   [WitnessGenerator] of the built tree generates a [Witness] or a
+//!   [MixedHashTree] for a partial tree, e.g. one obtained from a list of paths
+//!   with [sparse_labeled_tree_from_paths].
+//! * [prune_witness] and [MixedHashTree::prune] prune the parts of a witness
+//!   or a mixed hash tree that are not needed, without changing its digest.
+//! * [recompute_digest], [MixedHashTree::digest] and [MixedHashTree::lookup]
+//!   verify partial trees and look up paths in them.
+//!
+//! Operations on untrusted inputs do not panic, but return a [TreeHashError].
 #![forbid(unsafe_code)]
 #![deny(clippy::unwrap_used)]
 
@@ -22,6 +35,8 @@ mod conversion_tests;
 mod encoding_tests;
 #[cfg(test)]
 mod merge_tests;
+#[cfg(test)]
+mod prune_tests;
 
 pub use flat_map::FlatMap;
 pub use tree_hash::*;
@@ -532,6 +547,50 @@ impl MixedHashTree {
             (l, r) => panic!("inconsistent trees: {:#?}, {:#?}", l, r),
         }
     }
+    /// Prunes the parts of the tree that are not needed to look up the given
+    /// `paths`, such that the digest of the tree does not change. The subtrees
+    /// at the `paths` are kept entirely, and if no paths are given, the whole
+    /// tree is pruned.
+    ///
+    /// Returns an error if any of the `paths` cannot be found in the tree,
+    /// e.g. because it is absent or already pruned.
+    pub fn prune(&self, paths: &[Path]) -> Result<MixedHashTree, TreeHashError> {
+        let found = |path: &&Path| {
+            let labels: Vec<&[u8]> = path.iter().map(Label::as_bytes).collect();
+            self.lookup(&labels).is_found()
+        };
+        if let Some(path) = paths.iter().find(|path| !found(path)) {
+            return Err(TreeHashError::InconsistentPartialTree {
+                offending_path: path.to_vec(),
+            });
+        }
+        if paths.is_empty() {
+            return Ok(MixedHashTree::Pruned(self.digest()));
+        }
+        let wanted = tree_hash::sparse_labeled_tree_from_paths(&mut paths.to_vec());
+        Ok(self.prune_impl(&wanted))
+    }
+
+    fn prune_impl(&self, wanted: &LabeledTree<()>) -> MixedHashTree {
+        let children = match wanted {
+            LabeledTree::Leaf(()) => return self.clone(),
+            LabeledTree::SubTree(children) => children,
+        };
+        match self {
+            Self::Fork(lr) => match (lr.0.prune_impl(wanted), lr.1.prune_impl(wanted)) {
+                (Self::Pruned(l), Self::Pruned(r)) => {
+                    Self::Pruned(tree_hash::compute_fork_digest(&l, &r))
+                }
+                (l, r) => Self::Fork(Box::new((l, r))),
+            },
+            Self::Labeled(label, subtree) => match children.get(label) {
+                Some(child) => Self::Labeled(label.clone(), Box::new(subtree.prune_impl(child))),
+                None => Self::Pruned(self.digest()),
+            },
+            // All labels on the paths were found, so these are not on them.
+            Self::Empty | Self::Leaf(_) | Self::Pruned(_) => Self::Pruned(self.digest()),
+        }
+    }
 }
 
 /// An error indicating that a hash tree doesn't correspond to a valid
@@ -558,6 +617,19 @@ pub enum InvalidHashTreeError {
     LabelsNotSorted(Label),
 }
 
+impl fmt::Display for InvalidHashTreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnlabeledLeaf => write!(f, "the hash tree contains an unlabeled leaf"),
+            Self::LabelsNotSorted(label) => {
+                write!(f, "the labels of the hash tree are not sorted at {}", label)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvalidHashTreeError {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MixedHashTreeConversionError {
     /// The hash tree contains a non-root leaf that is not a direct child of a
@@ -569,6 +641,20 @@ pub enum MixedHashTreeConversionError {
     Pruned,
 }
 
+impl fmt::Display for MixedHashTreeConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnlabeledLeaf => write!(f, "the hash tree contains an unlabeled leaf"),
+            Self::LabelsNotSorted(label) => {
+                write!(f, "the labels of the hash tree are not sorted at {}", label)
+            }
+            Self::Pruned => write!(f, "the root of the hash tree is pruned"),
+        }
+    }
+}
+
+impl std::error::Error for MixedHashTreeConversionError {}
+
 /// Extracts the data part from a mixed hash tree by removing all forks and
 /// pruned nodes.
 impl TryFrom<MixedHashTree> for LabeledTree<Vec<u8>> {
@@ -782,6 +868,26 @@ pub enum TreeHashError {
     NonMinimalWitness { offending_path: Vec<Label> },
 }
 
+impl fmt::Display for TreeHashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InconsistentPartialTree { offending_path } => write!(
+                f,
+                "the partial tree is inconsistent with the hash tree at path {}",
+                Path::from_iter(offending_path)
+            ),
+            Self::InvalidArgument { info } => write!(f, "invalid argument: {}", info),
+            Self::NonMinimalWitness { offending_path } => write!(
+                f,
+                "the witness is not minimal at path {}",
+                Path::from_iter(offending_path)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TreeHashError {}
+
 /// A subset of a [`HashTree`] that is sufficient to verify whether some
 /// specific partial data is consistent with the original data (for which the
 /// [`HashTree`] was computed). In particular a [`Witness`] includes no digests
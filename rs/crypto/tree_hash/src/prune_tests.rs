@@ -0,0 +1,90 @@
+#![allow(clippy::unwrap_used)]
+use crate::{Label, MixedHashTree, Path, TreeHashError};
+use MixedHashTree::*;
+
+fn labeled(label: &str, subtree: MixedHashTree) -> MixedHashTree {
+    Labeled(Label::from(label), Box::new(subtree))
+}
+
+fn fork(left: MixedHashTree, right: MixedHashTree) -> MixedHashTree {
+    Fork(Box::new((left, right)))
+}
+
+fn path(labels: &[&str]) -> Path {
+    labels.iter().map(Label::from).collect()
+}
+
+//  + -- a -- "1"
+//  |
+//  + -- b -- + -- c -- "2"
+//  |         |
+//  |         + -- d -- "3"
+//  |
+//  + -- e -- (empty)
+fn tree() -> MixedHashTree {
+    fork(
+        labeled("a", Leaf(b"1".to_vec())),
+        fork(
+            labeled(
+                "b",
+                fork(
+                    labeled("c", Leaf(b"2".to_vec())),
+                    labeled("d", Leaf(b"3".to_vec())),
+                ),
+            ),
+            labeled("e", Empty),
+        ),
+    )
+}
+
+#[test]
+fn pruning_keeps_the_digest_and_the_paths() {
+    let tree = tree();
+
+    let pruned = tree.prune(&[path(&["b", "c"])]).unwrap();
+    assert_eq!(pruned.digest(), tree.digest());
+    assert_eq!(
+        pruned,
+        fork(
+            Pruned(labeled("a", Leaf(b"1".to_vec())).digest()),
+            fork(
+                labeled(
+                    "b",
+                    fork(
+                        labeled("c", Leaf(b"2".to_vec())),
+                        Pruned(labeled("d", Leaf(b"3".to_vec())).digest()),
+                    ),
+                ),
+                Pruned(labeled("e", Empty).digest()),
+            ),
+        )
+    );
+    assert!(pruned.lookup(&[b"b", b"d"]).is_unknown());
+
+    let pruned = tree.prune(&[path(&["b"]), path(&["a"])]).unwrap();
+    assert_eq!(pruned.digest(), tree.digest());
+    assert!(pruned.lookup(&[b"a"]).is_found());
+    assert!(pruned.lookup(&[b"b", b"d"]).is_found());
+    assert!(pruned.lookup(&[b"e"]).is_unknown());
+
+    assert_eq!(tree.prune(&[]).unwrap(), Pruned(tree.digest()));
+    assert_eq!(tree.prune(&[path(&[])]).unwrap(), tree);
+}
+
+#[test]
+fn pruning_fails_for_paths_not_in_the_tree() {
+    let tree = tree();
+    assert_eq!(
+        tree.prune(&[path(&["a"]), path(&["b", "x"])]),
+        Err(TreeHashError::InconsistentPartialTree {
+            offending_path: vec![Label::from("b"), Label::from("x")]
+        })
+    );
+
+    let pruned = tree.prune(&[path(&["a"])]).unwrap();
+    assert!(pruned.prune(&[path(&["b", "c"])]).is_err());
+    assert_eq!(
+        pruned.prune(&[path(&["b", "c"])]).unwrap_err().to_string(),
+        "the partial tree is inconsistent with the hash tree at path /b/c"
+    );
+}
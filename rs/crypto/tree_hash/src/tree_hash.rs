@@ -755,11 +755,7 @@ impl WitnessGeneratorImpl {
             LabeledTree::SubTree(_) => unreachable!(),
             LabeledTree::Leaf(data) => match orig_tree {
                 LabeledTree::Leaf(_) => Ok(Builder::make_leaf(data.as_ref())),
-                _ => panic!(
-                    "inconsistent structures, not a leaf in the original labeled tree. \n\
-                    partial tree: {:?}\ncurr_path: {:?}",
-                    partial_tree, curr_path
-                ),
+                LabeledTree::SubTree(_) => err_inconsistent_partial_tree(curr_path),
             },
         }
     }
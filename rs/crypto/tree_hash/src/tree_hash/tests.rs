@@ -645,6 +645,30 @@ fn witness_should_fail_for_non_existing_sub_path() {
     );
 }
 
+#[test]
+fn witness_should_fail_for_leaf_in_place_of_subtree() {
+    // label_b is a subtree in the original tree.
+    let label_b = Label::from("label_b");
+    let root_map = flatmap!(
+        label_b.to_owned() => LabeledTree::Leaf(Vec::from("ignored"))
+    );
+    let partial_tree = LabeledTree::SubTree(root_map);
+
+    let builder = tree_with_a_subtree();
+    let witness_generator = builder.witness_generator().unwrap();
+
+    assert_eq!(
+        err_inconsistent_partial_tree(vec![label_b.clone()]),
+        witness_generator.witness(&partial_tree)
+    );
+    assert_eq!(
+        Err(TreeHashError::InconsistentPartialTree {
+            offending_path: vec![label_b]
+        }),
+        witness_generator.mixed_hash_tree(&partial_tree)
+    );
+}
+
 fn add_leaf<T>(label: T, contents: &str, builder: &mut HashTreeBuilderImpl)
 where
     T: std::convert::AsRef<[u8]>,
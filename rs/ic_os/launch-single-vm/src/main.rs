@@ -81,6 +81,7 @@ fn main() {
                 required_host_features: Vec::new(),
                 preferred_network: None,
                 metadata: None,
+                preemptible: false,
             },
         },
     );
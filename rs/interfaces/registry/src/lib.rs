@@ -158,6 +158,11 @@ pub trait RegistryDataProvider: Send + Sync {
     /// return records must represent all updates to the registry for all
     /// versions in the interval (version..max_v]. In particular, each version
     /// must be fully contained.
+    ///
+    /// (3) If the provider compacted its history into a snapshot at version
+    /// `b`, it returns a `RegistryDataProviderError::VersionCompacted` for all
+    /// `version < b - 1`. The records since `b - 1` start with the snapshot at
+    /// `b`, which applies on top of any older version.
     fn get_updates_since(
         &self,
         version: RegistryVersion,
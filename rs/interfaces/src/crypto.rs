@@ -10,6 +10,7 @@ pub use sign::threshold_sig::ni_dkg::{LoadTranscriptResult, NiDkgAlgorithm};
 
 mod sign;
 
+pub use sign::AsyncBasicSigner;
 pub use sign::BasicSigVerifier;
 pub use sign::BasicSigVerifierByPublicKey;
 pub use sign::BasicSigner;
@@ -23,6 +24,10 @@ pub use sign::ThresholdSigner;
 
 pub use sign::canister_threshold_sig::*;
 
+mod vetkd;
+
+pub use vetkd::VetKdProtocol;
+
 use ic_types::consensus::certification::CertificationContent;
 use ic_types::consensus::dkg as consensus_dkg;
 use ic_types::consensus::{
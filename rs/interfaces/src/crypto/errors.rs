@@ -355,10 +355,13 @@ impl ErrorReproducibility for RegistryClientError {
         match &self {
             // false, as depends on the data available to the registry
             RegistryClientError::VersionNotAvailable { .. } => false,
+            // false, as depends on the history available to the registry
+            RegistryClientError::VersionCompacted { .. } => false,
             // false in both cases, these may be transient errors
             RegistryClientError::DataProviderQueryFailed { source } => match source {
                 ic_types::registry::RegistryDataProviderError::Timeout => false,
                 ic_types::registry::RegistryDataProviderError::Transfer { .. } => false,
+                ic_types::registry::RegistryDataProviderError::VersionCompacted { .. } => false,
             },
             // may be a transient error
             RegistryClientError::PollLockFailed { .. } => false,
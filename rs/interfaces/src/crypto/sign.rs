@@ -22,6 +22,7 @@
 //!
 //! Please refer to the trait documentation for details.
 
+use async_trait::async_trait;
 use ic_types::crypto::{
     BasicSigOf, CanisterSigOf, CombinedMultiSigOf, CryptoResult, IndividualMultiSigOf, Signable,
     UserPublicKey,
@@ -71,6 +72,51 @@ pub trait BasicSigner<T: Signable> {
         signer: NodeId,
         registry_version: RegistryVersion,
     ) -> CryptoResult<BasicSigOf<T>>;
+
+    /// Creates a (non-malleable) basic signature for each of the `messages`.
+    ///
+    /// The signatures are the same as those created by calling `sign_basic`
+    /// for each message, and are returned in the order of `messages`. The
+    /// signer's key is looked up only once and all messages are signed with a
+    /// single call to the CSP vault, which saves the per-call overhead of a
+    /// remote vault when signing many messages.
+    ///
+    /// # Errors
+    /// The same errors as for `sign_basic`. If any of the messages cannot be
+    /// signed, no signatures are returned.
+    ///
+    /// The same restrictions as for `sign_basic` apply when called within a
+    /// Tokio runtime.
+    fn sign_basic_batch(
+        &self,
+        messages: &[T],
+        signer: NodeId,
+        registry_version: RegistryVersion,
+    ) -> CryptoResult<Vec<BasicSigOf<T>>>;
+}
+
+/// A Crypto Component interface to create basic signatures from async code.
+///
+/// Creates the same signatures as [`BasicSigner`], but can be awaited within a
+/// Tokio runtime: the call to the CSP vault runs on a blocking thread, so no
+/// worker thread is tied up and no `block_in_place` is required.
+#[async_trait]
+pub trait AsyncBasicSigner<T: Signable + Sync> {
+    /// Creates a (non-malleable) basic signature.
+    ///
+    /// # Errors
+    /// * The errors of [`BasicSigner::sign_basic`].
+    /// * `CryptoError::InternalError`: if the blocking task that signs was
+    ///   cancelled or panicked.
+    ///
+    /// # Panics
+    /// If called outside a Tokio runtime.
+    async fn sign_basic_async(
+        &self,
+        message: &T,
+        signer: NodeId,
+        registry_version: RegistryVersion,
+    ) -> CryptoResult<BasicSigOf<T>>;
 }
 
 /// A Crypto Component interface to verify basic signatures.
@@ -0,0 +1,96 @@
+//! Traits providing the crypto component interfaces for vetKD (verifiable
+//! encrypted threshold key derivation).
+//!
+//! With vetKD, a subnet derives keys from a threshold BLS key established by
+//! a non-interactive DKG, e.g., for a canister and a derivation path, and
+//! encrypts them to a transport public key of the recipient. Each node creates
+//! a share of the encrypted key with its secret key share. Any `threshold`
+//! valid shares can be publicly combined into the encrypted key, which is
+//! verifiable with the public key of the subnet, but which only the holder of
+//! the transport secret key can decrypt.
+use ic_base_types::NodeId;
+use ic_types::crypto::vetkd::{
+    VetKdArgs, VetKdEncryptedKey, VetKdEncryptedKeyShare, VetKdKeyShareCombinationError,
+    VetKdKeyShareCreationError, VetKdKeyShareVerificationError, VetKdKeyVerificationError,
+};
+use std::collections::BTreeMap;
+
+/// A Crypto Component interface to create, verify, and combine the shares of
+/// keys encrypted to a transport public key.
+pub trait VetKdProtocol {
+    /// Creates this node's share of the key derived for `args` encrypted to
+    /// `args.encryption_public_key`.
+    ///
+    /// The threshold key of `args.dkg_id` must have been loaded with
+    /// `NiDkgAlgorithm::load_transcript`.
+    ///
+    /// # Errors
+    /// * `VetKdKeyShareCreationError::ThresholdSigDataNotFound` if the
+    ///   transcript of `args.dkg_id` was not loaded
+    /// * `VetKdKeyShareCreationError::SecretKeyNotFound` if this node has no
+    ///   secret key share of the threshold key
+    /// * `VetKdKeyShareCreationError::InvalidArgument` if the transport
+    ///   public key is malformed
+    /// * `VetKdKeyShareCreationError::TransientInternalError` if a transient
+    ///   internal error, e.g., an RPC error, occurred
+    fn create_encrypted_key_share(
+        &self,
+        args: VetKdArgs,
+    ) -> Result<VetKdEncryptedKeyShare, VetKdKeyShareCreationError>;
+
+    /// Verifies that `key_share` was created for `args` by `signer`.
+    ///
+    /// # Errors
+    /// * `VetKdKeyShareVerificationError::ThresholdSigDataNotFound` if the
+    ///   transcript of `args.dkg_id` was not loaded
+    /// * `VetKdKeyShareVerificationError::SignerNotAllowed` if `signer` is
+    ///   not a receiver of the transcript
+    /// * `VetKdKeyShareVerificationError::InvalidArgument` if the transport
+    ///   public key or the share is malformed
+    /// * `VetKdKeyShareVerificationError::VerificationError` if the share is
+    ///   invalid
+    fn verify_encrypted_key_share(
+        &self,
+        signer: NodeId,
+        key_share: &VetKdEncryptedKeyShare,
+        args: &VetKdArgs,
+    ) -> Result<(), VetKdKeyShareVerificationError>;
+
+    /// Combines the shares of at least the threshold of the transcript of
+    /// `args.dkg_id` into the encrypted key.
+    ///
+    /// The shares need not be verified beforehand: invalid shares are ignored
+    /// as long as enough valid ones remain.
+    ///
+    /// # Errors
+    /// * `VetKdKeyShareCombinationError::ThresholdSigDataNotFound` if the
+    ///   transcript of `args.dkg_id` was not loaded
+    /// * `VetKdKeyShareCombinationError::SignerNotAllowed` if a share was
+    ///   created by a node that is not a receiver of the transcript
+    /// * `VetKdKeyShareCombinationError::InvalidArgument` if the transport
+    ///   public key or a share is malformed
+    /// * `VetKdKeyShareCombinationError::UnsatisfiedReconstructionThreshold`
+    ///   if fewer shares than the threshold are given
+    /// * `VetKdKeyShareCombinationError::InsufficientValidShares` if fewer
+    ///   valid shares than the threshold are given
+    fn combine_encrypted_key_shares(
+        &self,
+        shares: &BTreeMap<NodeId, VetKdEncryptedKeyShare>,
+        args: &VetKdArgs,
+    ) -> Result<VetKdEncryptedKey, VetKdKeyShareCombinationError>;
+
+    /// Verifies that `key` is the key derived for `args` encrypted to
+    /// `args.encryption_public_key`.
+    ///
+    /// # Errors
+    /// * `VetKdKeyVerificationError::ThresholdSigDataNotFound` if the
+    ///   transcript of `args.dkg_id` was not loaded
+    /// * `VetKdKeyVerificationError::InvalidArgument` if the transport public
+    ///   key or the key is malformed
+    /// * `VetKdKeyVerificationError::VerificationError` if the key is invalid
+    fn verify_encrypted_key(
+        &self,
+        key: &VetKdEncryptedKey,
+        args: &VetKdArgs,
+    ) -> Result<(), VetKdKeyVerificationError>;
+}
@@ -173,7 +173,7 @@ pub fn make_hsm_sender(hsm_slot: &str, key_id: &str, pin: &str) -> Sender {
     )
     .execute();
 
-    if let Err(UtilityCommandError::Failed(err, _status)) = res {
+    if let Err(UtilityCommandError::Failed { stderr: err, .. }) = res {
         // The key id is not found.
         if err.contains("object not found") {
             panic!("Cannot find key with id {}", key_id);
@@ -0,0 +1,103 @@
+//! Output backends of the config writers.
+//!
+//! A [ConfigGenerator] turns the target groups discovered for a job into the
+//! content of a configuration file. New backends (e.g. promtail or the
+//! OpenTelemetry collector) only need to implement this trait to be plugged
+//! into [crate::config_writer_loop::config_writer_loop].
+use std::collections::{BTreeMap, BTreeSet};
+
+use service_discovery::{job_types::JobType, TargetGroup};
+
+use crate::vector_config_structure::VectorConfigBuilder;
+
+pub trait ConfigGenerator {
+    /// Returns the name of the file, relative to the generation directory, the
+    /// configuration for `job` is written to.
+    fn file_name(&self, job: JobType) -> String {
+        format!("{}.json", job)
+    }
+
+    /// Returns the serialized configuration for the given target groups.
+    fn generate(
+        &self,
+        target_groups: BTreeSet<TargetGroup>,
+        job: JobType,
+    ) -> std::io::Result<Vec<u8>>;
+
+    /// Returns the content of all files making up the configuration for `job`,
+    /// keyed by their name relative to the generation directory. By default,
+    /// this is the single file [ConfigGenerator::file_name], which is omitted
+    /// if there are no target groups.
+    ///
+    /// Files written for a previous set of target groups that are not part of
+    /// the returned map are removed by the config writer.
+    fn generate_files(
+        &self,
+        target_groups: BTreeSet<TargetGroup>,
+        job: JobType,
+    ) -> std::io::Result<BTreeMap<String, Vec<u8>>> {
+        if target_groups.is_empty() {
+            return Ok(BTreeMap::new());
+        }
+        let content = self.generate(target_groups, job)?;
+        Ok(BTreeMap::from([(self.file_name(job), content)]))
+    }
+}
+
+impl<T: VectorConfigBuilder> ConfigGenerator for T {
+    fn generate(
+        &self,
+        target_groups: BTreeSet<TargetGroup>,
+        job: JobType,
+    ) -> std::io::Result<Vec<u8>> {
+        let vector_config = self.build(target_groups, job);
+        serde_json::to_vec_pretty(&vector_config).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("Serialization error: {:?}", e),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use service_discovery::{job_types::JobType, TargetGroup};
+
+    use super::ConfigGenerator;
+    use crate::vector_config_structure::{VectorConfigBuilder, VectorConfigEnriched};
+
+    struct EmptyVectorConfigBuilder;
+
+    impl VectorConfigBuilder for EmptyVectorConfigBuilder {
+        fn build(&self, _: BTreeSet<TargetGroup>, _: JobType) -> VectorConfigEnriched {
+            VectorConfigEnriched::new()
+        }
+    }
+
+    #[test]
+    fn vector_config_builders_generate_json() {
+        let generator = EmptyVectorConfigBuilder;
+
+        assert_eq!(generator.file_name(JobType::Replica), "replica.json");
+        let content = generator
+            .generate(BTreeSet::new(), JobType::Replica)
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&content).unwrap(),
+            serde_json::json!({"sources": {}, "transforms": {}})
+        );
+    }
+
+    #[test]
+    fn no_files_are_generated_without_targets() {
+        let generator = EmptyVectorConfigBuilder;
+
+        assert!(generator
+            .generate_files(BTreeSet::new(), JobType::Replica)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -2,26 +2,27 @@ use std::{collections::BTreeSet, sync::Arc};
 
 use crossbeam::select;
 use crossbeam_channel::Receiver;
+use service_discovery::health::HealthStatus;
 use service_discovery::metrics::Metrics;
-use service_discovery::{jobs::Job, IcServiceDiscovery, TargetGroup};
+use service_discovery::{jobs::Job, target_filter::TargetFilter, IcServiceDiscovery, TargetGroup};
 use slog::{info, warn};
 
-use crate::{
-    config_builder::ConfigBuilder, config_updater::ConfigUpdater, filters::TargetGroupFilter,
-};
+use crate::{config_builder::ConfigBuilder, config_updater::ConfigUpdater};
 
 pub fn config_updater_loop(
     log: slog::Logger,
     discovery: Arc<dyn IcServiceDiscovery>,
-    filters: Arc<dyn TargetGroupFilter>,
+    filters: Arc<dyn TargetFilter>,
     shutdown_signal: Receiver<()>,
     jobs: Vec<Job>,
     update_signal_recv: Receiver<()>,
     mut config_builder: impl ConfigBuilder,
     config_updater: impl ConfigUpdater,
     metrics: Metrics,
+    health: HealthStatus,
 ) -> impl FnMut() {
     move || loop {
+        let mut err = false;
         for job in &jobs {
             let target_groups = match discovery.get_target_groups(job._type) {
                 Ok(t) => t,
@@ -30,13 +31,14 @@ pub fn config_updater_loop(
                         log,
                         "Failed to retrieve targets for job {}: {:?}", job._type, e
                     );
+                    err = true;
                     continue;
                 }
             };
             let filtered_target_groups: BTreeSet<TargetGroup> = target_groups
                 .clone()
                 .into_iter()
-                .filter(|tg| TargetGroupFilter::filter(filters.as_ref(), tg.clone()))
+                .filter(|tg| filters.matches(tg))
                 .collect();
 
             metrics
@@ -45,11 +47,21 @@ pub fn config_updater_loop(
                 .set(target_groups.len().try_into().unwrap());
 
             let config = config_builder.build(filtered_target_groups, job.clone());
+            if !config.updated() {
+                metrics
+                    .unchanged_configs
+                    .with_label_values(&[job._type.to_string().as_str()])
+                    .inc();
+            }
             let config_binding = config.as_ref();
             if let Err(e) = config_updater.update(config_binding) {
                 warn!(log, "Failed to write config {}: {:?}", &config.name(), e);
+                err = true;
             };
         }
+        if !err {
+            health.write_succeeded();
+        }
         select! {
             recv(shutdown_signal) -> _ => {
                     info!(log, "Received shutdown signal");
@@ -1,84 +1,297 @@
 use std::{
     collections::{BTreeMap, BTreeSet},
     error::Error,
+    fmt,
+    io::Write,
     path::{Path, PathBuf},
+    str::FromStr,
     sync::Arc,
 };
 
-use service_discovery::{job_types::JobType, TargetGroup};
+use service_discovery::{job_types::JobType, target_filter::TargetFilter, TargetGroup};
 
 use crate::{
-    config_builder::Config, config_updater::ConfigUpdater, filters::TargetGroupFilter,
-    vector_config_structure::VectorConfigBuilder,
+    config_builder::Config, config_generator::ConfigGenerator, config_updater::ConfigUpdater,
+    file_permissions::FilePermissions,
 };
-use slog::{debug, Logger};
+use slog::{debug, warn, Logger};
+
+/// How the configuration of a job is split into files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputLayout {
+    /// A single file per job containing all of its targets, as returned by
+    /// [ConfigGenerator::generate_files].
+    #[default]
+    Merged,
+    /// A file `<job>-<node_id>.json` per job and node, such that every change
+    /// only touches the files of the affected nodes.
+    PerNode,
+}
+
+#[derive(Debug)]
+pub struct OutputLayoutParseError {
+    input: String,
+}
+impl std::error::Error for OutputLayoutParseError {}
+
+impl fmt::Display for OutputLayoutParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Could not parse {} into an output layout", self.input)
+    }
+}
+
+impl FromStr for OutputLayout {
+    type Err = OutputLayoutParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "merged" => Ok(OutputLayout::Merged),
+            "per-node" => Ok(OutputLayout::PerNode),
+            _ => Err(OutputLayoutParseError {
+                input: s.to_string(),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for OutputLayout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OutputLayout::Merged => write!(f, "merged"),
+            OutputLayout::PerNode => write!(f, "per-node"),
+        }
+    }
+}
+
+/// The outcome of [ConfigWriter::write_config].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct WriteSummary {
+    /// Target groups that were part of the previous call but are gone now.
+    pub removed_targets: usize,
+    /// True if no file had to be written or removed.
+    pub unchanged: bool,
+}
 
 #[derive(Debug)]
 pub struct ConfigWriter {
     base_directory: PathBuf,
     last_targets: BTreeMap<String, BTreeSet<TargetGroup>>,
-    filters: Arc<dyn TargetGroupFilter>,
+    /// The [TargetFilter::generation] of the filters the last targets of each
+    /// job were written with.
+    last_filter_generations: BTreeMap<String, u64>,
+    /// The files written for each job by the last call to `write_config`,
+    /// loaded from the record in the base directory on the first call.
+    generated_files: BTreeMap<String, BTreeSet<PathBuf>>,
+    filters: Arc<dyn TargetFilter>,
+    permissions: FilePermissions,
+    layout: OutputLayout,
     log: slog::Logger,
 }
 
 impl ConfigWriter {
-    pub fn new<P: AsRef<Path>>(
-        write_path: P,
-        filters: Arc<dyn TargetGroupFilter>,
-        log: Logger,
-    ) -> Self {
+    pub fn new<P: AsRef<Path>>(write_path: P, filters: Arc<dyn TargetFilter>, log: Logger) -> Self {
         ConfigWriter {
             base_directory: PathBuf::from(write_path.as_ref()),
             last_targets: Default::default(),
+            last_filter_generations: Default::default(),
+            generated_files: Default::default(),
             filters,
+            permissions: Default::default(),
+            layout: Default::default(),
             log,
         }
     }
 
+    /// Sets how the configuration of a job is split into files.
+    pub fn with_layout(mut self, layout: OutputLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    pub fn permissions(&self) -> &FilePermissions {
+        &self.permissions
+    }
+
+    /// Sets the mode and ownership that are enforced on the base directory
+    /// and all generated files.
+    pub fn with_permissions(mut self, permissions: FilePermissions) -> Self {
+        self.permissions = permissions;
+        self
+    }
+
+    /// Verifies the permissions of the base directory and of the files
+    /// written for `job`, correcting them if necessary.
+    fn verify_permissions(&self, job: JobType) -> std::io::Result<()> {
+        if self.permissions.apply_to_dir(&self.base_directory)? {
+            warn!(
+                self.log,
+                "Corrected permissions of {:?}", self.base_directory
+            );
+        }
+        for file in self
+            .generated_files
+            .get(&job.to_string())
+            .into_iter()
+            .flatten()
+        {
+            if self.permissions.apply_to_file(file)? {
+                warn!(self.log, "Corrected permissions of {:?}", file);
+            }
+        }
+        Ok(())
+    }
+
+    /// Path of the record of the files generated for `job`, such that files
+    /// written before a restart can be cleaned up. It does not end in `.json`
+    /// and is thus not picked up as configuration by the consumers.
+    fn generated_files_record(&self, job: JobType) -> PathBuf {
+        self.base_directory.join(format!(".{}.generated", job))
+    }
+
+    fn load_generated_files(&self, job: JobType) -> std::io::Result<BTreeSet<PathBuf>> {
+        match std::fs::read_to_string(self.generated_files_record(job)) {
+            Ok(content) => Ok(content
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(|file_name| self.base_directory.join(file_name))
+                .collect()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Default::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn store_generated_files(
+        &self,
+        job: JobType,
+        files: &BTreeSet<PathBuf>,
+    ) -> std::io::Result<()> {
+        let content: String = files
+            .iter()
+            .filter_map(|file| file.file_name())
+            .map(|file_name| format!("{}\n", file_name.to_string_lossy()))
+            .collect();
+        let record = self.generated_files_record(job);
+        write_if_changed(&record, content.as_bytes())?;
+        self.permissions.apply_to_file(&record)
+    }
+
     /// Write configuration files for the job `job_name`.
     ///
     /// The assumption is that no external process manipulates or deletes the written files.
     /// FileSd will memoize the calls. Thus, calling this method twice with the
-    /// same arguments will have no effect.
+    /// same arguments will have no effect, unless the [TargetFilter::generation]
+    /// of the filters changed in between, e.g. because they were reloaded.
+    ///
+    /// Files written by a previous call that are no longer generated, e.g.
+    /// because the corresponding node disappeared from the registry, are
+    /// deleted, including files written before a restart of the process.
+    /// Files whose content did not change are not rewritten, such
+    /// that consumers watching them are not reloaded needlessly.
+    ///
+    /// The permissions of the generated files are verified on every call, even
+    /// if the targets did not change.
     pub fn write_config(
         &mut self,
         job: JobType,
         target_groups: BTreeSet<TargetGroup>,
-        vector_config_builder: &impl VectorConfigBuilder,
-    ) -> std::io::Result<()> {
+        config_generator: &impl ConfigGenerator,
+    ) -> std::io::Result<WriteSummary> {
+        // After a restart the previous targets are unknown, so the files are
+        // always regenerated once to clean up the recorded ones.
+        let first_call = !self.generated_files.contains_key(&job.to_string());
+        if first_call {
+            let recorded_files = self.load_generated_files(job)?;
+            self.generated_files.insert(job.to_string(), recorded_files);
+        }
+        let filter_generation = self.filters.generation();
+        let filters_changed =
+            self.last_filter_generations.get(&job.to_string()) != Some(&filter_generation);
         let last_job_targets = self.last_targets.entry(job.to_string()).or_default();
-        if last_job_targets == &target_groups {
+        if !first_call && !filters_changed && last_job_targets == &target_groups {
             debug!(
                 self.log,
                 "Targets didn't change, skipped regenerating config"
             );
-            return Ok(());
+            self.verify_permissions(job)?;
+            return Ok(WriteSummary {
+                removed_targets: 0,
+                unchanged: true,
+            });
         }
         debug!(
             self.log,
             "Targets changed, proceeding with regenerating config"
         );
-        let target_path = self.base_directory.join(format!("{}.json", job));
+        let current_nodes: BTreeSet<_> = target_groups.iter().map(|tg| tg.node_id).collect();
+        let removed_targets = last_job_targets
+            .iter()
+            .filter(|tg| !current_nodes.contains(&tg.node_id))
+            .count();
 
         let filtered_target_groups: BTreeSet<TargetGroup> = target_groups
             .clone()
             .into_iter()
-            .filter(|tg| self.filters.filter(tg.clone()))
+            .filter(|tg| self.filters.matches(tg))
             .collect();
 
-        let vector_config = vector_config_builder.build(filtered_target_groups, job);
+        let files = match self.layout {
+            OutputLayout::Merged => config_generator.generate_files(filtered_target_groups, job)?,
+            OutputLayout::PerNode => filtered_target_groups
+                .into_iter()
+                .map(|tg| {
+                    let file_name = format!("{}-{}.json", job, tg.node_id);
+                    Ok((
+                        file_name,
+                        config_generator.generate(BTreeSet::from([tg]), job)?,
+                    ))
+                })
+                .collect::<std::io::Result<_>>()?,
+        };
 
-        ic_utils::fs::write_atomically(target_path.as_path(), |f| {
-            serde_json::to_writer_pretty(f, &vector_config).map_err(|e| {
-                std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("Serialization error: {:?}", e),
-                )
-            })
-        })?;
+        let mut unchanged = true;
+        let mut written_files = BTreeSet::new();
+        for (file_name, content) in files {
+            let target_path = self.base_directory.join(file_name);
+            if write_if_changed(&target_path, &content)? {
+                unchanged = false;
+            }
+            self.permissions.apply_to_file(&target_path)?;
+            written_files.insert(target_path);
+        }
+
+        let last_files = self
+            .generated_files
+            .insert(job.to_string(), written_files.clone())
+            .unwrap_or_default();
+        for stale_file in last_files.difference(&written_files) {
+            debug!(self.log, "Removing stale config file {:?}", stale_file);
+            unchanged = false;
+            match std::fs::remove_file(stale_file) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        self.store_generated_files(job, &written_files)?;
+
+        self.permissions.apply_to_dir(&self.base_directory)?;
         self.last_targets.insert(job.to_string(), target_groups);
-        Ok(())
+        self.last_filter_generations
+            .insert(job.to_string(), filter_generation);
+        Ok(WriteSummary {
+            removed_targets,
+            unchanged,
+        })
+    }
+}
+
+/// Writes `content` to `path` unless the file already has exactly this
+/// content. Returns true if the file was written.
+fn write_if_changed(path: &Path, content: &[u8]) -> std::io::Result<bool> {
+    if std::fs::read(path).map_or(false, |current| current == content) {
+        return Ok(false);
     }
+    ic_utils::fs::write_atomically(path, |f| f.write_all(content))?;
+    Ok(true)
 }
 
 impl ConfigUpdater for ConfigWriter {
@@ -96,14 +309,219 @@ impl ConfigUpdater for ConfigWriter {
         );
         let target_path = self.base_directory.join(format!("{}.json", config.name()));
 
-        ic_utils::fs::write_atomically(target_path.as_path(), |f| {
-            serde_json::to_writer_pretty(f, &config).map_err(|e| {
-                std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("Serialization error: {:?}", e),
-                )
-            })
+        let content = serde_json::to_vec_pretty(&config).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("Serialization error: {:?}", e),
+            )
         })?;
+        if !write_if_changed(&target_path, &content)? {
+            debug!(self.log, "Config {:?} is unchanged", target_path);
+        }
+        self.permissions.apply_to_file(&target_path)?;
+        self.permissions.apply_to_dir(&self.base_directory)?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        sync::Arc,
+    };
+
+    use service_discovery::{
+        job_types::JobType,
+        target_filter::{All, Any},
+        test_utils::target_group,
+        TargetGroup,
+    };
+    use slog::o;
+    use tempfile::tempdir;
+
+    use super::{ConfigWriter, OutputLayout};
+    use crate::{config_generator::ConfigGenerator, filters::ReloadableFilter};
+
+    /// Writes one file per node.
+    struct PerNodeGenerator;
+
+    impl ConfigGenerator for PerNodeGenerator {
+        fn generate(&self, _: BTreeSet<TargetGroup>, _: JobType) -> std::io::Result<Vec<u8>> {
+            Ok(vec![])
+        }
+
+        fn generate_files(
+            &self,
+            target_groups: BTreeSet<TargetGroup>,
+            _: JobType,
+        ) -> std::io::Result<BTreeMap<String, Vec<u8>>> {
+            Ok(target_groups
+                .into_iter()
+                .map(|tg| (format!("{}.json", tg.node_id), vec![]))
+                .collect())
+        }
+    }
+
+    #[test]
+    fn stale_config_files_are_removed() {
+        let dir = tempdir().unwrap();
+        let log = slog::Logger::root(slog::Discard, o!());
+        let mut writer = ConfigWriter::new(dir.path(), Arc::new(All::default()), log);
+        let tg1 = target_group(1).build();
+        let tg2 = target_group(2).build();
+        let file = |tg: &TargetGroup| dir.path().join(format!("{}.json", tg.node_id));
+
+        let removed = writer
+            .write_config(
+                JobType::Replica,
+                BTreeSet::from([tg1.clone(), tg2.clone()]),
+                &PerNodeGenerator,
+            )
+            .unwrap()
+            .removed_targets;
+        assert_eq!(removed, 0);
+        assert!(file(&tg1).exists());
+        assert!(file(&tg2).exists());
+
+        let removed = writer
+            .write_config(
+                JobType::Replica,
+                BTreeSet::from([tg1.clone()]),
+                &PerNodeGenerator,
+            )
+            .unwrap()
+            .removed_targets;
+        assert_eq!(removed, 1);
+        assert!(file(&tg1).exists());
+        assert!(!file(&tg2).exists());
+
+        let removed = writer
+            .write_config(JobType::Replica, BTreeSet::new(), &PerNodeGenerator)
+            .unwrap()
+            .removed_targets;
+        assert_eq!(removed, 1);
+        assert!(!file(&tg1).exists());
+    }
+
+    #[test]
+    fn stale_config_files_are_removed_after_restart() {
+        let dir = tempdir().unwrap();
+        let log = slog::Logger::root(slog::Discard, o!());
+        let new_writer = || ConfigWriter::new(dir.path(), Arc::new(All::default()), log.clone());
+        let tg1 = target_group(1).build();
+        let tg2 = target_group(2).build();
+        let file = |tg: &TargetGroup| dir.path().join(format!("{}.json", tg.node_id));
+
+        new_writer()
+            .write_config(
+                JobType::Replica,
+                BTreeSet::from([tg1.clone(), tg2.clone()]),
+                &PerNodeGenerator,
+            )
+            .unwrap();
+        assert!(file(&tg1).exists());
+        assert!(file(&tg2).exists());
+
+        let summary = new_writer()
+            .write_config(
+                JobType::Replica,
+                BTreeSet::from([tg1.clone()]),
+                &PerNodeGenerator,
+            )
+            .unwrap();
+        assert!(!summary.unchanged);
+        assert!(file(&tg1).exists());
+        assert!(!file(&tg2).exists());
+
+        new_writer()
+            .write_config(JobType::Replica, BTreeSet::new(), &PerNodeGenerator)
+            .unwrap();
+        assert!(!file(&tg1).exists());
+    }
+
+    #[test]
+    fn unchanged_configs_are_not_rewritten() {
+        let dir = tempdir().unwrap();
+        let log = slog::Logger::root(slog::Discard, o!());
+        let new_writer = || ConfigWriter::new(dir.path(), Arc::new(All::default()), log.clone());
+        let target_groups = BTreeSet::from([target_group(1).build()]);
+
+        let mut writer = new_writer();
+        let summary = writer
+            .write_config(JobType::Replica, target_groups.clone(), &PerNodeGenerator)
+            .unwrap();
+        assert!(!summary.unchanged);
+        let summary = writer
+            .write_config(JobType::Replica, target_groups.clone(), &PerNodeGenerator)
+            .unwrap();
+        assert!(summary.unchanged);
+
+        // a restarted writer finds the same content on disk
+        let summary = new_writer()
+            .write_config(JobType::Replica, target_groups, &PerNodeGenerator)
+            .unwrap();
+        assert!(summary.unchanged);
+    }
+
+    #[test]
+    fn reloaded_filters_regenerate_unchanged_targets() {
+        let dir = tempdir().unwrap();
+        let log = slog::Logger::root(slog::Discard, o!());
+        let filter = ReloadableFilter::new(Arc::new(All::default()));
+        let mut writer = ConfigWriter::new(dir.path(), Arc::new(filter.clone()), log);
+        let tg = target_group(1).build();
+        let target_groups = BTreeSet::from([tg.clone()]);
+        let file = dir.path().join(format!("{}.json", tg.node_id));
+
+        writer
+            .write_config(JobType::Replica, target_groups.clone(), &PerNodeGenerator)
+            .unwrap();
+        assert!(file.exists());
+
+        filter.replace(Arc::new(Any::default()));
+        let summary = writer
+            .write_config(JobType::Replica, target_groups.clone(), &PerNodeGenerator)
+            .unwrap();
+        assert!(!summary.unchanged);
+        assert!(!file.exists());
+
+        filter.replace(Arc::new(All::default()));
+        let summary = writer
+            .write_config(JobType::Replica, target_groups, &PerNodeGenerator)
+            .unwrap();
+        assert!(!summary.unchanged);
+        assert!(file.exists());
+    }
+
+    #[test]
+    fn per_node_layout_writes_a_file_per_node() {
+        let dir = tempdir().unwrap();
+        let log = slog::Logger::root(slog::Discard, o!());
+        let mut writer = ConfigWriter::new(dir.path(), Arc::new(All::default()), log)
+            .with_layout(OutputLayout::PerNode);
+        let tg1 = target_group(1).build();
+        let tg2 = target_group(2).build();
+        let file = |tg: &TargetGroup| dir.path().join(format!("replica-{}.json", tg.node_id));
+
+        writer
+            .write_config(
+                JobType::Replica,
+                BTreeSet::from([tg1.clone(), tg2.clone()]),
+                &PerNodeGenerator,
+            )
+            .unwrap();
+        assert!(file(&tg1).exists());
+        assert!(file(&tg2).exists());
+
+        writer
+            .write_config(
+                JobType::Replica,
+                BTreeSet::from([tg1.clone()]),
+                &PerNodeGenerator,
+            )
+            .unwrap();
+        assert!(file(&tg1).exists());
+        assert!(!file(&tg2).exists());
+    }
+}
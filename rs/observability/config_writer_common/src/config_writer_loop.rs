@@ -1,6 +1,7 @@
 //! An experimental component that allows scraping logs using the http-endpoint
 //! exposed by systemd-journal-gatewayd.
 use crossbeam::select;
+use service_discovery::health::HealthStatus;
 use service_discovery::metrics::Metrics;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -10,51 +11,79 @@ use slog::{info, warn};
 
 use service_discovery::{job_types::JobType, IcServiceDiscovery};
 
+use crate::config_generator::ConfigGenerator;
 use crate::config_writer::ConfigWriter;
-use crate::filters::TargetGroupFilter;
-use crate::vector_config_structure::VectorConfigBuilder;
+use crate::manifest::GenerationManifest;
 
 pub fn config_writer_loop(
     log: slog::Logger,
     discovery: Arc<dyn IcServiceDiscovery>,
-    filters: Arc<dyn TargetGroupFilter>,
     shutdown_signal: Receiver<()>,
     jobs: Vec<JobType>,
     update_signal_recv: Receiver<()>,
-    vector_config_dir: PathBuf,
-    vector_config_builder: impl VectorConfigBuilder,
+    mut config_writer: ConfigWriter,
+    manifest_file: Option<PathBuf>,
+    config_generator: impl ConfigGenerator,
     metrics: Metrics,
+    health: HealthStatus,
 ) -> impl FnMut() {
-    move || {
-        let mut config_writer =
-            ConfigWriter::new(vector_config_dir.clone(), filters.clone(), log.clone());
-        loop {
-            for job in &jobs {
-                let targets = match discovery.get_target_groups(*job) {
-                    Ok(t) => t,
-                    Err(e) => {
-                        warn!(log, "Failed to retrieve targets for job {}: {:?}", job, e);
-                        continue;
+    move || loop {
+        let mut err = false;
+        let mut manifest = GenerationManifest::new();
+        for job in &jobs {
+            let targets = match discovery.get_target_groups(*job) {
+                Ok(t) => t,
+                Err(e) => {
+                    warn!(log, "Failed to retrieve targets for job {}: {:?}", job, e);
+                    err = true;
+                    continue;
+                }
+            };
+            manifest.add(*job, &targets);
+            metrics
+                .total_targets
+                .with_label_values(&[job.to_string().as_str()])
+                .set(targets.len().try_into().unwrap());
+            match config_writer.write_config(*job, targets, &config_generator) {
+                Ok(summary) => {
+                    metrics
+                        .removed_targets
+                        .with_label_values(&[job.to_string().as_str()])
+                        .inc_by(summary.removed_targets as u64);
+                    if summary.unchanged {
+                        metrics
+                            .unchanged_configs
+                            .with_label_values(&[job.to_string().as_str()])
+                            .inc();
                     }
-                };
-                metrics
-                    .total_targets
-                    .with_label_values(&[job.to_string().as_str()])
-                    .set(targets.len().try_into().unwrap());
-                if let Err(e) = config_writer.write_config(*job, targets, &vector_config_builder) {
+                }
+                Err(e) => {
                     warn!(
                         log,
                         "Failed to write config for targets for job {}: {:?}", job, e
                     );
-                };
-            }
-            select! {
-                recv(shutdown_signal) -> _ => {
-                        info!(log, "Received shutdown signal in log_scraper");
-                        break;
-                    },
-                recv(update_signal_recv) -> _ => continue,
+                    err = true;
+                }
             };
         }
+        if let Some(manifest_file) = &manifest_file {
+            if let Err(e) = manifest
+                .write(manifest_file)
+                .and_then(|_| config_writer.permissions().apply_to_file(manifest_file))
+            {
+                warn!(log, "Failed to write manifest {:?}: {:?}", manifest_file, e);
+                err = true;
+            }
+        }
+        if !err {
+            health.write_succeeded();
+        }
+        select! {
+            recv(shutdown_signal) -> _ => {
+                    info!(log, "Received shutdown signal in log_scraper");
+                    break;
+                },
+            recv(update_signal_recv) -> _ => continue,
+        };
     }
 }
@@ -0,0 +1,169 @@
+//! Mode and ownership of the generated files and directories.
+//!
+//! The generated configs are usually consumed by a process running as a
+//! different user, e.g. vector. Hence, the generators can enforce the mode,
+//! owner and group of everything they write. Permissions are verified, and
+//! corrected if necessary, on every generation, such that manual changes or a
+//! restrictive umask do not leave the output unreadable.
+use std::{
+    fs::Permissions,
+    os::unix::fs::{MetadataExt, PermissionsExt},
+    path::Path,
+};
+
+use clap::Args;
+use nix::unistd::{chown, Group, User};
+pub use nix::unistd::{Gid, Uid};
+
+/// The permissions of the generated files, shared as command line arguments by
+/// all generators.
+#[derive(Args, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FilePermissions {
+    /// Mode of generated files, e.g. `0o644`.
+    #[clap(
+        long = "file-mode",
+        parse(try_from_str = parse_mode),
+        help = r#"
+If specified, the octal mode, e.g. 0644, of the generated files. It is verified
+and, if necessary, corrected on every generation.
+
+"#
+    )]
+    pub file_mode: Option<u32>,
+    /// Mode of the directories files are generated to, e.g. `0o755`.
+    #[clap(
+        long = "dir-mode",
+        parse(try_from_str = parse_mode),
+        help = r#"
+If specified, the octal mode, e.g. 0755, of the directory the files are
+generated to.
+
+"#
+    )]
+    pub dir_mode: Option<u32>,
+    #[clap(
+        long = "file-owner",
+        parse(try_from_str = parse_owner),
+        help = r#"
+If specified, the user name or id that owns the generated files and their
+directory.
+
+"#
+    )]
+    pub owner: Option<Uid>,
+    #[clap(
+        long = "file-group",
+        parse(try_from_str = parse_group),
+        help = r#"
+If specified, the group name or id that owns the generated files and their
+directory.
+
+"#
+    )]
+    pub group: Option<Gid>,
+}
+
+impl FilePermissions {
+    pub fn apply_to_file(&self, path: &Path) -> std::io::Result<bool> {
+        self.apply(path, self.file_mode)
+    }
+
+    pub fn apply_to_dir(&self, path: &Path) -> std::io::Result<bool> {
+        self.apply(path, self.dir_mode)
+    }
+
+    /// Sets the mode and ownership of `path` unless they already match.
+    /// Returns true if anything had to be changed.
+    fn apply(&self, path: &Path, mode: Option<u32>) -> std::io::Result<bool> {
+        let metadata = std::fs::metadata(path)?;
+        let mut changed = false;
+        if let Some(mode) = mode {
+            if metadata.mode() & 0o7777 != mode {
+                std::fs::set_permissions(path, Permissions::from_mode(mode))?;
+                changed = true;
+            }
+        }
+        let owner = self.owner.filter(|uid| uid.as_raw() != metadata.uid());
+        let group = self.group.filter(|gid| gid.as_raw() != metadata.gid());
+        if owner.is_some() || group.is_some() {
+            chown(path, owner, group)?;
+            changed = true;
+        }
+        Ok(changed)
+    }
+}
+
+/// Parses an octal mode such as `644` or `0644`.
+pub fn parse_mode(s: &str) -> Result<u32, String> {
+    u32::from_str_radix(s.trim_start_matches("0o"), 8)
+        .ok()
+        .filter(|mode| *mode <= 0o7777)
+        .ok_or_else(|| format!("invalid octal mode: {}", s))
+}
+
+/// Parses a user name or numeric user id.
+pub fn parse_owner(s: &str) -> Result<Uid, String> {
+    if let Ok(uid) = s.parse() {
+        return Ok(Uid::from_raw(uid));
+    }
+    match User::from_name(s) {
+        Ok(Some(user)) => Ok(user.uid),
+        Ok(None) => Err(format!("unknown user: {}", s)),
+        Err(e) => Err(format!("failed to look up user {}: {}", s, e)),
+    }
+}
+
+/// Parses a group name or numeric group id.
+pub fn parse_group(s: &str) -> Result<Gid, String> {
+    if let Ok(gid) = s.parse() {
+        return Ok(Gid::from_raw(gid));
+    }
+    match Group::from_name(s) {
+        Ok(Some(group)) => Ok(group.gid),
+        Ok(None) => Err(format!("unknown group: {}", s)),
+        Err(e) => Err(format!("failed to look up group {}: {}", s, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::MetadataExt;
+
+    use nix::unistd::{getgid, getuid};
+    use tempfile::tempdir;
+
+    use super::{parse_group, parse_mode, parse_owner, FilePermissions, Gid, Uid};
+
+    #[test]
+    fn parse_permissions() {
+        assert_eq!(parse_mode("644"), Ok(0o644));
+        assert_eq!(parse_mode("0750"), Ok(0o750));
+        assert!(parse_mode("0999").is_err());
+        assert!(parse_mode("17777").is_err());
+        assert_eq!(parse_owner("0"), Ok(Uid::from_raw(0)));
+        assert_eq!(parse_group("0"), Ok(Gid::from_raw(0)));
+    }
+
+    #[test]
+    fn permissions_are_corrected() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("targets.json");
+        std::fs::write(&path, "{}").unwrap();
+        let permissions = FilePermissions {
+            file_mode: Some(0o640),
+            dir_mode: Some(0o750),
+            owner: Some(getuid()),
+            group: Some(getgid()),
+        };
+
+        permissions.apply_to_file(&path).unwrap();
+        assert!(!permissions.apply_to_file(&path).unwrap());
+        assert_eq!(std::fs::metadata(&path).unwrap().mode() & 0o7777, 0o640);
+
+        permissions.apply_to_dir(dir.path()).unwrap();
+        assert_eq!(
+            std::fs::metadata(dir.path()).unwrap().mode() & 0o7777,
+            0o750
+        );
+    }
+}
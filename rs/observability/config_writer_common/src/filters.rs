@@ -1,159 +1,69 @@
-use regex::Regex;
-use service_discovery::TargetGroup;
-use std::fmt::Debug;
-
-pub trait TargetGroupFilter: Send + Sync + Debug {
-    fn filter(&self, target_groups: TargetGroup) -> bool;
-}
-
-#[derive(Debug)]
-pub struct NodeIDRegexFilter {
-    regex: Regex,
+use service_discovery::{target_filter::TargetFilter, TargetGroup};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, RwLock,
+};
+
+/// A filter that can be swapped out while the config writers using it keep
+/// running, e.g. when the filters are reloaded from a configuration file.
+#[derive(Clone, Debug)]
+pub struct ReloadableFilter {
+    inner: Arc<RwLock<Arc<dyn TargetFilter>>>,
+    /// Incremented on every call to [ReloadableFilter::replace].
+    replacements: Arc<AtomicU64>,
 }
 
-impl NodeIDRegexFilter {
-    pub fn new(regex: Regex) -> Self {
-        Self { regex }
+impl ReloadableFilter {
+    pub fn new(filter: Arc<dyn TargetFilter>) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(filter)),
+            replacements: Default::default(),
+        }
     }
-}
 
-impl TargetGroupFilter for NodeIDRegexFilter {
-    fn filter(&self, target_group: TargetGroup) -> bool {
-        self.regex.is_match(&target_group.node_id.to_string())
+    pub fn replace(&self, filter: Arc<dyn TargetFilter>) {
+        *self.inner.write().unwrap() = filter;
+        self.replacements.fetch_add(1, Ordering::SeqCst);
     }
 }
 
-#[derive(Debug)]
-pub struct TargetGroupFilterList {
-    filters: Vec<Box<dyn TargetGroupFilter>>,
-}
-
-impl TargetGroupFilterList {
-    pub fn new(filters: Vec<Box<dyn TargetGroupFilter>>) -> Self {
-        Self { filters }
+impl TargetFilter for ReloadableFilter {
+    fn matches(&self, target_group: &TargetGroup) -> bool {
+        self.inner.read().unwrap().matches(target_group)
     }
-}
 
-impl TargetGroupFilter for TargetGroupFilterList {
-    fn filter(&self, target_group: TargetGroup) -> bool {
-        // If the group is empty, consider that as having no filter, thus always accept the element
-        if self.filters.is_empty() {
-            true
-        } else {
-            self.filters
-                .iter()
-                .map(|f| f.filter(target_group.clone()))
-                .all(|status| status)
-        }
+    fn generation(&self) -> u64 {
+        self.replacements
+            .load(Ordering::SeqCst)
+            .wrapping_add(self.inner.read().unwrap().generation())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::BTreeSet, net::SocketAddrV6, str::FromStr};
+    use std::sync::Arc;
 
-    use ic_types::{NodeId, PrincipalId, SubnetId};
+    use ic_types::{NodeId, PrincipalId};
     use regex::Regex;
-    use service_discovery::TargetGroup;
-
-    use crate::filters::TargetGroupFilter;
-
-    use super::{NodeIDRegexFilter, TargetGroupFilterList};
-
-    fn create_dummy_target_group(ipv6: &str) -> TargetGroup {
-        let mut targets = BTreeSet::new();
-        targets.insert(std::net::SocketAddr::V6(
-            SocketAddrV6::from_str(ipv6).unwrap(),
-        ));
-        TargetGroup {
-            node_id: NodeId::from(PrincipalId::new_anonymous()),
-            ic_name: "mercury".into(),
-            targets,
-            subnet_id: Some(SubnetId::from(PrincipalId::new_anonymous())),
-            dc_id: None,
-            operator_id: None,
-        }
-    }
-
-    #[test]
-    fn node_id_regex_filter_test() {
-        let filter = NodeIDRegexFilter::new(Regex::new("^i").unwrap());
-
-        let accepted_tg = TargetGroup {
-            node_id: NodeId::from(
-                PrincipalId::from_str(
-                    "iylgr-zpxwq-kqgmf-4srtx-o4eey-d6bln-smmq6-we7px-ibdea-nondy-eae",
-                )
-                .unwrap(),
-            ),
-            ic_name: "mercury".into(),
-            targets: BTreeSet::new(),
-            subnet_id: Some(SubnetId::from(PrincipalId::new_anonymous())),
-            dc_id: None,
-            operator_id: None,
-        };
-        assert!(filter.filter(accepted_tg));
-
-        let rejected_tg = TargetGroup {
-            node_id: NodeId::from(
-                PrincipalId::from_str(
-                    "x33ed-h457x-bsgyx-oqxqf-6pzwv-wkhzr-rm2j3-npodi-purzm-n66cg-gae",
-                )
-                .unwrap(),
-            ),
-            ic_name: "mercury".into(),
-            targets: BTreeSet::new(),
-            subnet_id: Some(SubnetId::from(PrincipalId::new_anonymous())),
-            dc_id: None,
-            operator_id: None,
-        };
-        assert!(!filter.filter(rejected_tg));
-    }
-
-    #[test]
-    fn filter_list_test() {
-        let filter_vec: Vec<Box<dyn TargetGroupFilter>> =
-            vec![Box::new(NodeIDRegexFilter::new(Regex::new("^i").unwrap()))];
-        let filterlist = TargetGroupFilterList::new(filter_vec);
+    use service_discovery::{
+        target_filter::{All, NodeIdRegex, TargetFilter},
+        test_utils::target_group,
+    };
 
-        let accepted_tg = TargetGroup {
-            node_id: NodeId::from(
-                PrincipalId::from_str(
-                    "iylgr-zpxwq-kqgmf-4srtx-o4eey-d6bln-smmq6-we7px-ibdea-nondy-eae",
-                )
-                .unwrap(),
-            ),
-            ic_name: "mercury".into(),
-            targets: BTreeSet::new(),
-            subnet_id: Some(SubnetId::from(PrincipalId::new_anonymous())),
-            dc_id: None,
-            operator_id: None,
-        };
-        assert!(filterlist.filter(accepted_tg));
-
-        let rejected_tg_1 = TargetGroup {
-            node_id: NodeId::from(
-                PrincipalId::from_str(
-                    "x33ed-h457x-bsgyx-oqxqf-6pzwv-wkhzr-rm2j3-npodi-purzm-n66cg-gae",
-                )
-                .unwrap(),
-            ),
-            ic_name: "mercury".into(),
-            targets: BTreeSet::new(),
-            subnet_id: Some(SubnetId::from(PrincipalId::new_anonymous())),
-            dc_id: None,
-            operator_id: None,
-        };
-        assert!(!filterlist.filter(rejected_tg_1));
-
-        let old_tg = create_dummy_target_group("[2a02:800:2:2003:5000:f6ff:fec4:4c86]:9091");
-        assert!(!filterlist.filter(old_tg));
-    }
+    use super::ReloadableFilter;
 
     #[test]
-    fn filter_list_test_empty() {
-        let filterlist = TargetGroupFilterList::new(vec![]);
-        let tg = create_dummy_target_group("[2a02:800:2:2003:6801:f6ff:fec4:4c86]:9091");
-        assert!(filterlist.filter(tg));
+    fn reloadable_filter_test() {
+        let tg = target_group(0)
+            .node_id(NodeId::from(PrincipalId::new_anonymous()))
+            .build();
+
+        let filter = ReloadableFilter::new(Arc::new(NodeIdRegex(Regex::new("^x").unwrap())));
+        assert!(!filter.matches(&tg));
+        let generation = filter.generation();
+
+        filter.replace(Arc::new(All::default()));
+        assert!(filter.matches(&tg));
+        assert_ne!(filter.generation(), generation);
     }
 }
@@ -0,0 +1,303 @@
+//! Grafana dashboards rendered from the discovered topology.
+//!
+//! For every subnet and every node among the target groups of a job, a
+//! dashboard is generated that shows the node exporter and replica metrics of
+//! its nodes. The dashboards are meant to be provisioned from the generation
+//! directory, such that Grafana picks up new subnets and nodes and drops the
+//! ones that left the topology. The queries rely on the `ic_subnet` and
+//! `ic_node` labels attached by the generated scrape configs.
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{json, Value};
+use service_discovery::{job_types::JobType, TargetGroup};
+
+use crate::config_generator::ConfigGenerator;
+use crate::labels_keys;
+
+/// The maximum length of a dashboard UID accepted by Grafana.
+const MAX_UID_LEN: usize = 40;
+/// The range of the `rate()` queries of the panels.
+const RATE_INTERVAL: &str = "5m";
+
+pub struct GrafanaDashboardGenerator {
+    /// The UID of the Prometheus data source the panels query.
+    datasource_uid: String,
+}
+
+impl GrafanaDashboardGenerator {
+    pub fn new(datasource_uid: String) -> Self {
+        Self { datasource_uid }
+    }
+
+    fn dashboard(&self, uid: String, title: String, selector: &str) -> Value {
+        let node_exporter = format!(r#"job="node_exporter",{}"#, selector);
+        let replica = format!(r#"job="replica",{}"#, selector);
+        let panels = [
+            (
+                "CPU utilization",
+                "percentunit",
+                format!(
+                    r#"1 - avg by ({node}) (rate(node_cpu_seconds_total{{mode="idle",{sel}}}[{rate}]))"#,
+                    node = labels_keys::IC_NODE,
+                    sel = node_exporter,
+                    rate = RATE_INTERVAL
+                ),
+            ),
+            (
+                "Memory utilization",
+                "percentunit",
+                format!(
+                    "1 - node_memory_MemAvailable_bytes{{{sel}}} / node_memory_MemTotal_bytes{{{sel}}}",
+                    sel = node_exporter
+                ),
+            ),
+            (
+                "Disk I/O utilization",
+                "percentunit",
+                format!(
+                    "max by ({node}) (rate(node_disk_io_time_seconds_total{{{sel}}}[{rate}]))",
+                    node = labels_keys::IC_NODE,
+                    sel = node_exporter,
+                    rate = RATE_INTERVAL
+                ),
+            ),
+            (
+                "Network received",
+                "Bps",
+                format!(
+                    r#"sum by ({node}) (rate(node_network_receive_bytes_total{{device!="lo",{sel}}}[{rate}]))"#,
+                    node = labels_keys::IC_NODE,
+                    sel = node_exporter,
+                    rate = RATE_INTERVAL
+                ),
+            ),
+            (
+                "Network transmitted",
+                "Bps",
+                format!(
+                    r#"sum by ({node}) (rate(node_network_transmit_bytes_total{{device!="lo",{sel}}}[{rate}]))"#,
+                    node = labels_keys::IC_NODE,
+                    sel = node_exporter,
+                    rate = RATE_INTERVAL
+                ),
+            ),
+            (
+                "Replica up",
+                "none",
+                format!("up{{{}}}", replica),
+            ),
+            (
+                "Finalization rate",
+                "none",
+                format!(
+                    r#"rate(artifact_pool_consensus_height_stat{{pool_type="validated",stat="max",type="finalization",{sel}}}[{rate}])"#,
+                    sel = replica,
+                    rate = RATE_INTERVAL
+                ),
+            ),
+            (
+                "Batch rate",
+                "none",
+                format!(
+                    "rate(consensus_batch_height{{{sel}}}[{rate}])",
+                    sel = replica,
+                    rate = RATE_INTERVAL
+                ),
+            ),
+            (
+                "Execution round duration (p90)",
+                "s",
+                format!(
+                    "histogram_quantile(0.9, sum by ({node}, le) (rate(execution_round_duration_seconds_bucket{{{sel}}}[{rate}])))",
+                    node = labels_keys::IC_NODE,
+                    sel = replica,
+                    rate = RATE_INTERVAL
+                ),
+            ),
+        ];
+        let panels: Vec<Value> = panels
+            .into_iter()
+            .enumerate()
+            .map(|(i, (title, unit, expr))| self.panel(i, title, unit, expr))
+            .collect();
+        json!({
+            "uid": uid,
+            "title": title,
+            "tags": ["ic", "generated"],
+            "timezone": "utc",
+            "editable": false,
+            "refresh": "1m",
+            "schemaVersion": 36,
+            "time": {"from": "now-6h", "to": "now"},
+            "panels": panels,
+        })
+    }
+
+    /// A time series panel; panels are laid out in two columns.
+    fn panel(&self, index: usize, title: &str, unit: &str, expr: String) -> Value {
+        json!({
+            "id": index + 1,
+            "type": "timeseries",
+            "title": title,
+            "datasource": {"type": "prometheus", "uid": self.datasource_uid},
+            "gridPos": {"h": 8, "w": 12, "x": (index % 2) * 12, "y": (index / 2) * 8},
+            "fieldConfig": {"defaults": {"unit": unit}, "overrides": []},
+            "targets": [{
+                "refId": "A",
+                "expr": expr,
+                "legendFormat": format!("{{{{{}}}}}", labels_keys::IC_NODE),
+            }],
+        })
+    }
+
+    fn subnet_dashboard(&self, subnet_id: &str, ic_name: &str) -> Value {
+        self.dashboard(
+            uid("subnet", subnet_id),
+            format!("{} subnet {}", ic_name, subnet_id),
+            &format!(r#"{}="{}""#, labels_keys::IC_SUBNET, subnet_id),
+        )
+    }
+
+    fn node_dashboard(&self, node_id: &str, ic_name: &str) -> Value {
+        self.dashboard(
+            uid("node", node_id),
+            format!("{} node {}", ic_name, node_id),
+            &format!(r#"{}="{}""#, labels_keys::IC_NODE, node_id),
+        )
+    }
+}
+
+/// Principals are longer than Grafana allows for UIDs, so they are truncated.
+/// The leading groups of a principal's textual form are random enough for the
+/// UIDs to be unique within an IC.
+fn uid(kind: &str, id: &str) -> String {
+    let uid = format!("ic-{}-{}", kind, id);
+    uid[..uid.len().min(MAX_UID_LEN)].to_string()
+}
+
+fn to_vec(dashboard: &Value) -> std::io::Result<Vec<u8>> {
+    serde_json::to_vec_pretty(dashboard).map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!("Serialization error: {:?}", e),
+        )
+    })
+}
+
+impl ConfigGenerator for GrafanaDashboardGenerator {
+    fn file_name(&self, job: JobType) -> String {
+        format!("{}-dashboard.json", job)
+    }
+
+    /// A single dashboard showing all nodes of the given target groups, e.g.
+    /// the one node of a target group in the per-node output layout.
+    fn generate(
+        &self,
+        target_groups: BTreeSet<TargetGroup>,
+        job: JobType,
+    ) -> std::io::Result<Vec<u8>> {
+        let target_groups: Vec<_> = target_groups.iter().collect();
+        let dashboard = match target_groups.as_slice() {
+            [tg] => self.node_dashboard(&tg.node_id.to_string(), &tg.ic_name),
+            _ => {
+                let nodes: Vec<String> = target_groups
+                    .iter()
+                    .map(|tg| tg.node_id.to_string())
+                    .collect();
+                self.dashboard(
+                    uid("job", &job.to_string()),
+                    format!("{} nodes", job),
+                    &format!(r#"{}=~"{}""#, labels_keys::IC_NODE, nodes.join("|")),
+                )
+            }
+        };
+        to_vec(&dashboard)
+    }
+
+    /// One dashboard per subnet, named `subnet-<subnet ID>.json`, and one per
+    /// node, named `node-<node ID>.json`. Unassigned nodes only get the latter.
+    fn generate_files(
+        &self,
+        target_groups: BTreeSet<TargetGroup>,
+        _job: JobType,
+    ) -> std::io::Result<BTreeMap<String, Vec<u8>>> {
+        let mut files = BTreeMap::new();
+        let mut subnets = BTreeMap::new();
+        for tg in &target_groups {
+            let node_id = tg.node_id.to_string();
+            files.insert(
+                format!("node-{}.json", node_id),
+                to_vec(&self.node_dashboard(&node_id, &tg.ic_name))?,
+            );
+            if let Some(subnet_id) = tg.subnet_id {
+                subnets.insert(subnet_id.to_string(), tg.ic_name.clone());
+            }
+        }
+        for (subnet_id, ic_name) in subnets {
+            files.insert(
+                format!("subnet-{}.json", subnet_id),
+                to_vec(&self.subnet_dashboard(&subnet_id, &ic_name))?,
+            );
+        }
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use ic_types::PrincipalId;
+    use service_discovery::{job_types::JobType, test_utils::target_group};
+
+    use super::{GrafanaDashboardGenerator, MAX_UID_LEN};
+    use crate::config_generator::ConfigGenerator;
+
+    #[test]
+    fn dashboards_are_generated_per_subnet_and_node() {
+        let generator = GrafanaDashboardGenerator::new("prometheus".into());
+        let target_groups = BTreeSet::from([
+            target_group(1).subnet(1).build(),
+            target_group(2).subnet(1).build(),
+            target_group(3).build(),
+        ]);
+
+        let files = generator
+            .generate_files(target_groups.clone(), JobType::Replica)
+            .unwrap();
+
+        let subnet_id = PrincipalId::new_subnet_test_id(1).to_string();
+        let mut expected: BTreeSet<String> = target_groups
+            .iter()
+            .map(|tg| format!("node-{}.json", tg.node_id))
+            .collect();
+        expected.insert(format!("subnet-{}.json", subnet_id));
+        assert_eq!(files.keys().cloned().collect::<BTreeSet<_>>(), expected);
+
+        let dashboard: serde_json::Value =
+            serde_json::from_slice(&files[&format!("subnet-{}.json", subnet_id)]).unwrap();
+        assert!(dashboard["uid"].as_str().unwrap().len() <= MAX_UID_LEN);
+        assert_eq!(
+            dashboard["title"],
+            format!("mercury subnet {}", subnet_id).as_str()
+        );
+        let panels = dashboard["panels"].as_array().unwrap();
+        assert!(panels.iter().all(|panel| {
+            panel["datasource"]["uid"] == "prometheus"
+                && panel["targets"][0]["expr"]
+                    .as_str()
+                    .unwrap()
+                    .contains(&format!(r#"ic_subnet="{}""#, subnet_id))
+        }));
+    }
+
+    #[test]
+    fn no_dashboards_are_generated_without_targets() {
+        let generator = GrafanaDashboardGenerator::new("prometheus".into());
+
+        assert!(generator
+            .generate_files(BTreeSet::new(), JobType::Replica)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,4 +1,5 @@
 pub const IC_NAME: &str = "ic";
 pub const IC_NODE: &str = "ic_node";
 pub const IC_SUBNET: &str = "ic_subnet";
+pub use service_discovery::IC_REGISTRY_VERSION;
 pub const JOB: &str = "job";
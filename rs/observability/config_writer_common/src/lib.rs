@@ -1,8 +1,15 @@
 pub mod config_builder;
+pub mod config_generator;
 pub mod config_updater;
 pub mod config_updater_loop;
 pub mod config_writer;
 pub mod config_writer_loop;
+pub mod file_permissions;
 pub mod filters;
+pub mod grafana_dashboards;
 pub mod labels_keys;
+pub mod manifest;
+pub mod reachability;
+pub mod remap_snippets;
 pub mod vector_config_structure;
+pub mod vector_journald_config;
@@ -0,0 +1,83 @@
+//! A manifest describing the last generation, written next to the generated
+//! configs, so that gaps in the scraped data can be correlated with changes
+//! of the topology.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+use service_discovery::{job_types::JobType, TargetGroup};
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct GenerationManifest {
+    /// Unix timestamp in seconds of the generation.
+    generated_at: u64,
+    /// The latest registry version targets were derived from, per IC.
+    registry_versions: BTreeMap<String, u64>,
+    /// The number of discovered targets, per job.
+    targets: BTreeMap<String, usize>,
+}
+
+impl GenerationManifest {
+    pub fn new() -> Self {
+        Self {
+            generated_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    pub fn add(&mut self, job: JobType, target_groups: &BTreeSet<TargetGroup>) {
+        self.targets.insert(job.to_string(), target_groups.len());
+        for target_group in target_groups {
+            if let Some(registry_version) = target_group.registry_version {
+                let version = self
+                    .registry_versions
+                    .entry(target_group.ic_name.clone())
+                    .or_default();
+                *version = std::cmp::max(*version, registry_version.get());
+            }
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        let content = serde_json::to_vec_pretty(self).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("Serialization error: {:?}", e),
+            )
+        })?;
+        ic_utils::fs::write_atomically(path, |f| f.write_all(&content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use service_discovery::{job_types::JobType, test_utils::target_group};
+
+    use super::GenerationManifest;
+
+    #[test]
+    fn manifest_keeps_latest_registry_version() {
+        let mut manifest = GenerationManifest::new();
+        manifest.add(
+            JobType::Replica,
+            &BTreeSet::from([
+                target_group(1).registry_version(12).build(),
+                target_group(2).registry_version(13).build(),
+            ]),
+        );
+        manifest.add(JobType::Orchestrator, &BTreeSet::new());
+
+        assert_eq!(manifest.registry_versions.get("mercury"), Some(&13));
+        assert_eq!(manifest.targets.get("replica"), Some(&2));
+        assert_eq!(manifest.targets.get("orchestrator"), Some(&0));
+    }
+}
@@ -0,0 +1,247 @@
+//! Periodic probes of the discovered targets. Targets that do not accept
+//! connections are written to a separate file, such that nodes that are
+//! registered but down can be told apart from nodes that fell out of the
+//! registry, whose configs are simply removed.
+use std::{
+    collections::BTreeSet,
+    io::Write,
+    net::{SocketAddr, TcpStream},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use clap::Args;
+use crossbeam::select;
+use crossbeam_channel::Receiver;
+use humantime::parse_duration;
+use serde::Serialize;
+use service_discovery::{
+    job_types::JobType, metrics::Metrics, target_filter::TargetFilter, IcServiceDiscovery,
+    TargetGroup,
+};
+use slog::{info, warn, Logger};
+
+use crate::file_permissions::FilePermissions;
+
+/// The maximum number of targets probed concurrently.
+const PROBE_PARALLELISM: usize = 64;
+const DEFAULT_PROBE_INTERVAL: Duration = Duration::from_secs(60);
+const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Command line arguments of the generators that enable the probes.
+#[derive(Args, Clone, Debug)]
+pub struct ProbeArgs {
+    #[clap(
+        long = "unreachable-targets-file",
+        help = r#"
+If specified, the discovered targets are probed every `--probe-interval` and
+the ones that do not accept TCP connections are written to this JSON file.
+This distinguishes nodes that are registered but down from nodes that left the
+registry.
+
+"#
+    )]
+    pub unreachable_targets_file: Option<PathBuf>,
+
+    #[clap(
+    long = "probe-interval",
+    parse(try_from_str = parse_duration),
+    help = r#"
+Only used with `--unreachable-targets-file`. The interval at which the
+discovered targets are probed. [default: 60s]
+
+"#
+    )]
+    pub probe_interval: Option<Duration>,
+
+    #[clap(
+    long = "probe-timeout",
+    parse(try_from_str = parse_duration),
+    help = r#"
+Only used with `--unreachable-targets-file`. The time after which a target that
+does not accept a connection is considered unreachable. [default: 5s]
+
+"#
+    )]
+    pub probe_timeout: Option<Duration>,
+}
+
+impl ProbeArgs {
+    pub fn probe_interval(&self) -> Duration {
+        self.probe_interval.unwrap_or(DEFAULT_PROBE_INTERVAL)
+    }
+
+    pub fn probe_timeout(&self) -> Duration {
+        self.probe_timeout.unwrap_or(DEFAULT_PROBE_TIMEOUT)
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct UnreachableTargets {
+    /// Unix timestamp in seconds of the probe.
+    probed_at: u64,
+    targets: Vec<UnreachableTarget>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct UnreachableTarget {
+    pub job: String,
+    pub node_id: String,
+    pub ic_name: String,
+    pub target: SocketAddr,
+    pub error: String,
+}
+
+impl UnreachableTargets {
+    pub fn new() -> Self {
+        Self {
+            probed_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        let content = serde_json::to_vec_pretty(self).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("Serialization error: {:?}", e),
+            )
+        })?;
+        ic_utils::fs::write_atomically(path, |f| f.write_all(&content))
+    }
+}
+
+/// Tries to open a TCP connection to every target of `target_groups` and
+/// returns the ones that failed within `timeout`.
+pub fn probe_targets(
+    job: JobType,
+    target_groups: &BTreeSet<TargetGroup>,
+    timeout: Duration,
+) -> Vec<UnreachableTarget> {
+    let targets: Vec<(&TargetGroup, SocketAddr)> = target_groups
+        .iter()
+        .flat_map(|tg| tg.targets.iter().map(move |target| (tg, *target)))
+        .collect();
+    let mut unreachable = vec![];
+    for chunk in targets.chunks(PROBE_PARALLELISM) {
+        let results: Vec<std::io::Result<TcpStream>> = std::thread::scope(|s| {
+            let handles: Vec<_> = chunk
+                .iter()
+                .map(|(_, target)| s.spawn(move || TcpStream::connect_timeout(target, timeout)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("Probe thread panicked"))
+                .collect()
+        });
+        for ((tg, target), result) in chunk.iter().zip(results) {
+            if let Err(e) = result {
+                unreachable.push(UnreachableTarget {
+                    job: job.to_string(),
+                    node_id: tg.node_id.to_string(),
+                    ic_name: tg.ic_name.clone(),
+                    target: *target,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+    unreachable
+}
+
+/// Returns a loop that probes the targets of `jobs` accepted by `filters`
+/// every `interval` and writes the unreachable ones to `output_file`.
+pub fn probe_loop(
+    log: Logger,
+    discovery: Arc<dyn IcServiceDiscovery>,
+    filters: Arc<dyn TargetFilter>,
+    jobs: Vec<JobType>,
+    interval: Duration,
+    timeout: Duration,
+    output_file: PathBuf,
+    permissions: FilePermissions,
+    metrics: Metrics,
+    shutdown_signal: Receiver<()>,
+) -> impl FnMut() {
+    move || loop {
+        let mut unreachable = UnreachableTargets::new();
+        for job in &jobs {
+            let target_groups: BTreeSet<TargetGroup> = match discovery.get_target_groups(*job) {
+                Ok(target_groups) => target_groups
+                    .into_iter()
+                    .filter(|tg| filters.matches(tg))
+                    .collect(),
+                Err(e) => {
+                    warn!(log, "Failed to retrieve targets for job {}: {:?}", job, e);
+                    // No value rather than a stale one until the job can be
+                    // probed again, which sets it anew.
+                    let _ = metrics
+                        .unreachable_targets
+                        .remove_label_values(&[job.to_string().as_str()]);
+                    continue;
+                }
+            };
+            let targets = probe_targets(*job, &target_groups, timeout);
+            metrics
+                .unreachable_targets
+                .with_label_values(&[job.to_string().as_str()])
+                .set(targets.len().try_into().unwrap());
+            unreachable.targets.extend(targets);
+        }
+        if let Err(e) = unreachable
+            .write(&output_file)
+            .and_then(|_| permissions.apply_to_file(&output_file))
+        {
+            warn!(
+                log,
+                "Failed to write unreachable targets {:?}: {:?}", output_file, e
+            );
+        }
+        select! {
+            recv(shutdown_signal) -> _ => {
+                info!(log, "Received shutdown signal in probe_loop");
+                break;
+            },
+            default(interval) => continue,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, net::TcpListener, time::Duration};
+
+    use ic_types::{NodeId, PrincipalId};
+    use service_discovery::{job_types::JobType, test_utils::target_group};
+
+    use super::probe_targets;
+
+    #[test]
+    fn only_closed_ports_are_unreachable() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let reachable = listener.local_addr().unwrap();
+        // Binding and dropping a listener yields a port nothing listens on.
+        let unreachable = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let target_groups = BTreeSet::from([
+            target_group(1).targets([reachable]).build(),
+            target_group(2).targets([unreachable]).build(),
+        ]);
+        let targets = probe_targets(JobType::Replica, &target_groups, Duration::from_secs(5));
+
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].target, unreachable);
+        assert_eq!(
+            targets[0].node_id,
+            NodeId::from(PrincipalId::new_node_test_id(2)).to_string()
+        );
+        assert_eq!(targets[0].job, "replica");
+    }
+}
@@ -0,0 +1,180 @@
+//! User-supplied VRL programs appended to the remap transforms generated for a
+//! job, e.g., to drop noisy fields from node exporter logs or to map the level
+//! of replica log lines to a severity.
+//!
+//! The generator does not compile the snippets: the VRL compiler is not a
+//! dependency of this workspace. A snippet is only checked lexically by
+//! `check_literals_and_brackets`, so that a truncated or mismatched file is
+//! caught at generation time. Any other error, e.g., a call of an unknown
+//! function or a type error, is only reported by vector when it loads the
+//! generated config.
+use std::path::Path;
+use std::str::FromStr;
+
+use service_discovery::job_types::JobType;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemapSnippet {
+    pub job: JobType,
+    pub source: String,
+}
+
+impl RemapSnippet {
+    /// Fails if the literals or brackets of `source` are malformed, see
+    /// `check_literals_and_brackets`. The program is not compiled.
+    pub fn new(job: JobType, source: String) -> Result<Self, String> {
+        check_literals_and_brackets(&source)?;
+        Ok(Self { job, source })
+    }
+
+    pub fn from_file(job: JobType, path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {:?}: {}", path, e))?;
+        Self::new(job, source).map_err(|e| format!("malformed VRL in {:?}: {}", path, e))
+    }
+}
+
+/// Parses `<job>=<path>`, reading and validating the snippet at `path`.
+pub fn parse_remap_snippet(s: &str) -> Result<RemapSnippet, String> {
+    let (job, path) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected <job>=<path>, got {}", s))?;
+    let job = JobType::from_str(job).map_err(|e| e.to_string())?;
+    RemapSnippet::from_file(job, Path::new(path))
+}
+
+/// Checks that `source` is not empty, that its string, raw string, regex and
+/// timestamp literals are terminated and that its brackets are balanced.
+fn check_literals_and_brackets(source: &str) -> Result<(), String> {
+    let mut chars = source.chars().peekable();
+    let mut line = 1;
+    let mut open: Vec<(char, usize)> = vec![];
+    let mut is_empty = true;
+    let mut previous = ' ';
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            '#' => while chars.next_if(|&c| c != '\n').is_some() {},
+            '"' => skip_literal(&mut chars, '"', &mut line)?,
+            'r' | 's' | 't'
+                if chars.peek() == Some(&'\'')
+                    && !(previous.is_alphanumeric() || previous == '_') =>
+            {
+                chars.next();
+                skip_literal(&mut chars, '\'', &mut line)?;
+            }
+            '(' | '[' | '{' => open.push((c, line)),
+            ')' | ']' | '}' => match open.pop() {
+                Some((opening, _)) if closing(opening) == c => {}
+                Some((opening, opened)) => {
+                    return Err(format!(
+                        "line {}: {} does not close {} of line {}",
+                        line, c, opening, opened
+                    ))
+                }
+                None => return Err(format!("line {}: unexpected {}", line, c)),
+            },
+            _ => {}
+        }
+        is_empty &= c.is_whitespace() || c == '#';
+        previous = c;
+    }
+    if let Some((opening, opened)) = open.pop() {
+        return Err(format!("line {}: {} is never closed", opened, opening));
+    }
+    if is_empty {
+        return Err("the program is empty".to_string());
+    }
+    Ok(())
+}
+
+/// Skips the rest of a literal delimited by `quote`, in which a backslash
+/// escapes the next character.
+fn skip_literal(
+    chars: &mut impl Iterator<Item = char>,
+    quote: char,
+    line: &mut usize,
+) -> Result<(), String> {
+    let start = *line;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if chars.next() == Some('\n') {
+                    *line += 1;
+                }
+            }
+            '\n' => *line += 1,
+            c if c == quote => return Ok(()),
+            _ => {}
+        }
+    }
+    Err(format!("line {}: unterminated literal", start))
+}
+
+fn closing(opening: char) -> char {
+    match opening {
+        '(' => ')',
+        '[' => ']',
+        _ => '}',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use service_discovery::job_types::{JobType, NodeOS};
+
+    use super::{check_literals_and_brackets, RemapSnippet};
+
+    #[test]
+    fn valid_programs_are_accepted() {
+        for source in [
+            "del(.fields)\n",
+            "# Drop noisy fields (like \"this)\n.severity = if .level == \"CRITICAL\" { 2 } else { 6 }",
+            r#"parsed, err = parse_regex(.message, r'^(?P<level>[A-Z]+ \'quoted\'$')"#,
+            ".ts = t'2021-02-11T10:32:50.553955473Z'",
+        ] {
+            assert_eq!(check_literals_and_brackets(source), Ok(()), "{}", source);
+        }
+    }
+
+    #[test]
+    fn malformed_programs_are_rejected() {
+        for (source, error) in [
+            ("", "the program is empty"),
+            ("# only a comment\n", "the program is empty"),
+            (".a = \"unterminated\n", "line 1: unterminated literal"),
+            (
+                ".a = 1\nif true {\n  del(.b)\n",
+                "line 2: { is never closed",
+            ),
+            ("del(.a]", "line 1: ] does not close ( of line 1"),
+            (".a = 1\n}", "line 2: unexpected }"),
+            ("parse_regex(.m, r'[)", "line 1: unterminated literal"),
+        ] {
+            assert_eq!(
+                check_literals_and_brackets(source),
+                Err(error.to_string()),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn snippets_are_read_from_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("drop_fields.vrl");
+        std::fs::write(&path, "del(.fields)").unwrap();
+
+        let snippet = super::parse_remap_snippet(&format!("node_exporter={}", path.display()));
+        assert_eq!(
+            snippet,
+            Ok(RemapSnippet {
+                job: JobType::NodeExporter(NodeOS::Guest),
+                source: "del(.fields)".to_string(),
+            })
+        );
+        assert!(super::parse_remap_snippet("unknown_job=/dev/null").is_err());
+        assert!(super::parse_remap_snippet(&path.display().to_string()).is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use std::{
     any::Any,
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet},
 };
 
 use erased_serde::serialize_trait_object;
@@ -11,10 +11,12 @@ pub trait VectorConfigBuilder {
     fn build(&self, target_groups: BTreeSet<TargetGroup>, job: JobType) -> VectorConfigEnriched;
 }
 
+/// Sources and transforms are kept sorted by their key, such that the same
+/// targets always serialize to the same config.
 #[derive(Serialize)]
 pub struct VectorConfigEnriched {
-    sources: HashMap<String, Box<dyn VectorSource>>,
-    transforms: HashMap<String, Box<dyn VectorTransform>>,
+    sources: BTreeMap<String, Box<dyn VectorSource>>,
+    transforms: BTreeMap<String, Box<dyn VectorTransform>>,
 }
 
 pub trait VectorSource: erased_serde::Serialize + ToAny {
@@ -42,8 +44,8 @@ serialize_trait_object!(VectorTransform);
 impl VectorConfigEnriched {
     pub fn new() -> Self {
         Self {
-            sources: HashMap::new(),
-            transforms: HashMap::new(),
+            sources: BTreeMap::new(),
+            transforms: BTreeMap::new(),
         }
     }
 
@@ -57,11 +59,11 @@ impl VectorConfigEnriched {
         self.transforms.insert(key + "-transform", transform);
     }
 
-    pub fn get_sources(&self) -> HashMap<String, Box<dyn VectorSource>> {
+    pub fn get_sources(&self) -> BTreeMap<String, Box<dyn VectorSource>> {
         self.sources.clone()
     }
 
-    pub fn get_transforms(&self) -> HashMap<String, Box<dyn VectorTransform>> {
+    pub fn get_transforms(&self) -> BTreeMap<String, Box<dyn VectorTransform>> {
         self.transforms.clone()
     }
 }
@@ -0,0 +1,471 @@
+//! Vector configuration for scraping logs from the http-endpoint exposed by
+//! systemd-journal-gatewayd on every discovered node.
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use crate::labels_keys;
+use crate::remap_snippets::RemapSnippet;
+use crate::vector_config_structure::{
+    VectorConfigBuilder, VectorConfigEnriched, VectorSource, VectorTransform,
+};
+use serde::Serialize;
+use std::string::ParseError;
+
+use regex::Regex;
+use service_discovery::{job_types::JobType, TargetGroup};
+
+/// Selects the jobs whose log lines are parsed into structured fields by the
+/// generated transforms.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum ReplicaLogParsing {
+    #[default]
+    Disabled,
+    AllJobs,
+    Jobs(Vec<JobType>),
+}
+
+impl ReplicaLogParsing {
+    /// Parsing is enabled by a flag and applies to all jobs unless it is
+    /// restricted to some `jobs`.
+    pub fn new(enabled: bool, jobs: Vec<JobType>) -> Self {
+        match (enabled, jobs.is_empty()) {
+            (false, _) => Self::Disabled,
+            (true, true) => Self::AllJobs,
+            (true, false) => Self::Jobs(jobs),
+        }
+    }
+
+    fn applies_to(&self, job: &JobType) -> bool {
+        match self {
+            Self::Disabled => false,
+            Self::AllJobs => true,
+            Self::Jobs(jobs) => jobs.contains(job),
+        }
+    }
+}
+
+pub struct JournaldVectorConfigBuilder {
+    batch_size: u64,
+    /// Directory below which every generated source persists its journald
+    /// cursor, each in a dedicated subdirectory.
+    cursors_dir: PathBuf,
+    /// Jobs whose logs are parsed into structured fields by the generated
+    /// transforms.
+    replica_log_parsing: ReplicaLogParsing,
+    /// Systemd units to read the journal of, e.g. `orchestrator.service`. If
+    /// empty, a single source reads the full journal of a node; otherwise there
+    /// is a source per node and unit.
+    units: Vec<String>,
+    /// VRL programs appended to the transforms of their jobs, in order.
+    remap_snippets: Vec<RemapSnippet>,
+}
+
+impl JournaldVectorConfigBuilder {
+    pub fn new(
+        batch_size: u64,
+        cursors_dir: PathBuf,
+        replica_log_parsing: ReplicaLogParsing,
+    ) -> Self {
+        Self {
+            batch_size,
+            cursors_dir,
+            replica_log_parsing,
+            units: vec![],
+            remap_snippets: vec![],
+        }
+    }
+
+    pub fn with_units(mut self, units: Vec<String>) -> Self {
+        self.units = units;
+        self
+    }
+
+    pub fn with_remap_snippets(mut self, remap_snippets: Vec<RemapSnippet>) -> Self {
+        self.remap_snippets = remap_snippets;
+        self
+    }
+}
+impl VectorConfigBuilder for JournaldVectorConfigBuilder {
+    fn build(&self, target_groups: BTreeSet<TargetGroup>, job: JobType) -> VectorConfigEnriched {
+        from_targets_into_vector_config(self, target_groups, job)
+    }
+}
+
+pub fn from_targets_into_vector_config(
+    builder: &JournaldVectorConfigBuilder,
+    records: BTreeSet<TargetGroup>,
+    job: JobType,
+) -> VectorConfigEnriched {
+    let mut config = VectorConfigEnriched::new();
+    let remap_snippets: Vec<&str> = builder
+        .remap_snippets
+        .iter()
+        .filter(|snippet| snippet.job == job)
+        .map(|snippet| snippet.source.as_str())
+        .collect();
+    for record in records {
+        let node_key = format!("{}-{}", record.node_id, job);
+        let mut source: VectorSystemdGatewayJournaldSource = record.clone().try_into().unwrap();
+        source.batch_size = builder.batch_size;
+        let parse_replica_logs = builder.replica_log_parsing.applies_to(&job);
+        let mut add_source = |key: String, mut source: VectorSystemdGatewayJournaldSource| {
+            // Every source needs its own data directory, otherwise the cursors of
+            // different nodes and units overwrite each other and a restart of
+            // vector would re-ingest or skip log ranges.
+            source.data_dir = builder.cursors_dir.join(&key).to_string_lossy().to_string();
+            let transform = VectorSystemdGatewayJournaldTransform::from(
+                record.clone(),
+                &key,
+                parse_replica_logs,
+                &remap_snippets,
+            );
+            config.add_target_group(key, Box::new(source), Box::new(transform));
+        };
+        if builder.units.is_empty() {
+            add_source(node_key, source);
+        } else {
+            for unit in &builder.units {
+                let mut source = source.clone();
+                source.include_units = vec![unit.clone()];
+                add_source(format!("{}-{}", node_key, unit_key(unit)), source);
+            }
+        }
+    }
+    config
+}
+
+/// The part of the component keys identifying `unit`, e.g. `orchestrator` for
+/// `orchestrator.service`. Vector reserves dots in component keys.
+fn unit_key(unit: &str) -> String {
+    unit.trim_end_matches(".service").replace('.', "_")
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct VectorSystemdGatewayJournaldSource {
+    #[serde(rename = "type")]
+    _type: String,
+    endpoint: String,
+    data_dir: String,
+    batch_size: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    include_units: Vec<String>,
+}
+
+impl VectorSource for VectorSystemdGatewayJournaldSource {
+    fn clone_dyn(&self) -> Box<dyn VectorSource> {
+        Box::new(self.clone())
+    }
+}
+
+impl TryFrom<TargetGroup> for VectorSystemdGatewayJournaldSource {
+    type Error = ParseError;
+
+    fn try_from(target_group: TargetGroup) -> Result<Self, Self::Error> {
+        let regex = Regex::new(r"\[.*\]").unwrap();
+
+        let endpoint = target_group
+            .targets
+            .into_iter()
+            .map(|g| match regex.find(&g.to_string()) {
+                Some(mat) => {
+                    let binding = mat.as_str().to_string();
+                    let mut chars = binding.chars();
+                    chars.next().unwrap();
+                    chars.next_back().unwrap();
+                    chars.as_str().to_string()
+                }
+                None => panic!("Couldn't parse url."),
+            })
+            .next()
+            .unwrap();
+
+        Ok(Self {
+            _type: "systemd_journal_gatewayd".into(),
+            endpoint,
+            data_dir: "logs".to_string(),
+            batch_size: 0,
+            include_units: vec![],
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct VectorSystemdGatewayJournaldTransform {
+    #[serde(rename = "type")]
+    _type: String,
+    inputs: Vec<String>,
+    source: String,
+}
+
+impl VectorTransform for VectorSystemdGatewayJournaldTransform {
+    fn clone_dyn(&self) -> Box<dyn VectorTransform> {
+        Box::new(self.clone())
+    }
+}
+
+const IC_NAME: &str = "ic";
+const IC_NODE: &str = "ic_node";
+const IC_SUBNET: &str = "ic_subnet";
+const DC: &str = "dc";
+
+/// VRL program extracting the level, crate, module and correlation id (the
+/// ingress message id, if any) from replica log lines. Both the JSON and the
+/// full text format of the replica logger are supported; lines in any other
+/// format are passed through unchanged.
+const REPLICA_LOG_PARSER: &str = r#"structured, err = parse_json(.message)
+if err == null && is_object(structured.log_entry) {
+  .level = structured.log_entry.level
+  .crate = structured.log_entry.crate_
+  .module = structured.log_entry.module
+  .correlation_id = structured.log_entry.ingress_message.message_id
+  .message = structured.log_entry.message
+} else {
+  parsed, err = parse_regex(.message, r'^\S+ \S+ (?P<level>[A-Z]+) s:[^/]*/n:[^/]*/(?P<crate>[^/\s]+)/(?P<module>\S+) (?P<message>.*)$')
+  if err == null {
+    .level = parsed.level
+    .crate = parsed.crate
+    .module = parsed.module
+    .message = parsed.message
+  }
+}"#;
+
+impl VectorSystemdGatewayJournaldTransform {
+    fn from(
+        target_group: TargetGroup,
+        key: &str,
+        parse_replica_logs: bool,
+        remap_snippets: &[&str],
+    ) -> Self {
+        let mut labels: BTreeMap<String, String> = target_group.custom_labels.clone();
+        labels.insert(IC_NAME.into(), target_group.ic_name);
+        labels.insert(IC_NODE.into(), target_group.node_id.to_string());
+        if let Some(subnet_id) = target_group.subnet_id {
+            labels.insert(IC_SUBNET.into(), subnet_id.to_string());
+        }
+        if let Some(dc) = target_group.dc_id {
+            labels.insert(DC.into(), dc);
+        }
+        if let Some(registry_version) = target_group.registry_version {
+            labels.insert(
+                labels_keys::IC_REGISTRY_VERSION.into(),
+                registry_version.to_string(),
+            );
+        }
+        let mut source = labels
+            .into_iter()
+            // Might be dangerous as the tag value is coming from an outside source and
+            // is not escaped.
+            .map(|(k, v)| format!(".{} = \"{}\"", k, v))
+            .collect::<Vec<String>>();
+        if parse_replica_logs {
+            source.push(REPLICA_LOG_PARSER.to_string());
+        }
+        // The snippets run last, such that they can use the parsed fields.
+        source.extend(remap_snippets.iter().map(|snippet| snippet.to_string()));
+        Self {
+            _type: "remap".into(),
+            inputs: vec![format!("{}-source", key)],
+            source: source.join("\n"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, path::PathBuf, str::FromStr};
+
+    use ic_types::{NodeId, PrincipalId};
+    use service_discovery::{
+        job_types::{JobType, NodeOS},
+        test_utils::target_group,
+        TargetGroup,
+    };
+
+    use super::{from_targets_into_vector_config, JournaldVectorConfigBuilder, REPLICA_LOG_PARSER};
+    use crate::remap_snippets::RemapSnippet;
+
+    fn node_target_group(node_id: &str, ipv6: &str) -> TargetGroup {
+        target_group(0)
+            .node_id(NodeId::from(PrincipalId::from_str(node_id).unwrap()))
+            .target(ipv6)
+            .build()
+    }
+
+    #[test]
+    fn every_source_persists_its_cursor_separately() {
+        let mut target_groups = BTreeSet::new();
+        target_groups.insert(node_target_group(
+            "iylgr-zpxwq-kqgmf-4srtx-o4eey-d6bln-smmq6-we7px-ibdea-nondy-eae",
+            "[2a02:800:2:2003:6801:f6ff:fec4:4c86]:9100",
+        ));
+        target_groups.insert(node_target_group(
+            "x33ed-h457x-bsgyx-oqxqf-6pzwv-wkhzr-rm2j3-npodi-purzm-n66cg-gae",
+            "[2a02:800:2:2003:6801:f6ff:fec4:4c87]:9100",
+        ));
+
+        let builder = JournaldVectorConfigBuilder::new(
+            32,
+            PathBuf::from("/var/lib/vector/cursors"),
+            ReplicaLogParsing::Disabled,
+        );
+        let config = from_targets_into_vector_config(
+            &builder,
+            target_groups,
+            JobType::NodeExporter(NodeOS::Guest),
+        );
+        let config = serde_json::to_value(&config).unwrap();
+
+        let data_dirs: BTreeSet<String> = config["sources"]
+            .as_object()
+            .unwrap()
+            .values()
+            .map(|source| source["data_dir"].as_str().unwrap().to_string())
+            .collect();
+        let expected: BTreeSet<String> = [
+            "/var/lib/vector/cursors/iylgr-zpxwq-kqgmf-4srtx-o4eey-d6bln-smmq6-we7px-ibdea-nondy-eae-node_exporter",
+            "/var/lib/vector/cursors/x33ed-h457x-bsgyx-oqxqf-6pzwv-wkhzr-rm2j3-npodi-purzm-n66cg-gae-node_exporter",
+        ]
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+        assert_eq!(data_dirs, expected);
+    }
+
+    #[test]
+    fn replica_log_parser_is_added_only_to_configured_jobs() {
+        let mut target_groups = BTreeSet::new();
+        target_groups.insert(node_target_group(
+            "iylgr-zpxwq-kqgmf-4srtx-o4eey-d6bln-smmq6-we7px-ibdea-nondy-eae",
+            "[2a02:800:2:2003:6801:f6ff:fec4:4c86]:9100",
+        ));
+        let builder = JournaldVectorConfigBuilder::new(
+            32,
+            PathBuf::from("logs"),
+            ReplicaLogParsing::Jobs(vec![JobType::NodeExporter(NodeOS::Guest)]),
+        );
+
+        let parsed = serde_json::to_value(&from_targets_into_vector_config(
+            &builder,
+            target_groups.clone(),
+            JobType::NodeExporter(NodeOS::Guest),
+        ))
+        .unwrap();
+        let not_parsed = serde_json::to_value(&from_targets_into_vector_config(
+            &builder,
+            target_groups,
+            JobType::NodeExporter(NodeOS::Host),
+        ))
+        .unwrap();
+
+        let remap_source = |config: &serde_json::Value| {
+            config["transforms"]
+                .as_object()
+                .unwrap()
+                .values()
+                .next()
+                .unwrap()["source"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+        assert!(remap_source(&parsed).ends_with(REPLICA_LOG_PARSER));
+        assert!(!remap_source(&not_parsed).contains(REPLICA_LOG_PARSER));
+    }
+
+    #[test]
+    fn replica_log_parsing_applies_to_all_jobs_unless_restricted() {
+        let guest = JobType::NodeExporter(NodeOS::Guest);
+        let host = JobType::NodeExporter(NodeOS::Host);
+
+        assert_eq!(
+            ReplicaLogParsing::new(false, vec![guest]),
+            ReplicaLogParsing::Disabled
+        );
+        assert!(ReplicaLogParsing::new(true, vec![]).applies_to(&host));
+        let restricted = ReplicaLogParsing::new(true, vec![guest]);
+        assert!(restricted.applies_to(&guest));
+        assert!(!restricted.applies_to(&host));
+    }
+
+    #[test]
+    fn every_unit_gets_its_own_source() {
+        let mut target_groups = BTreeSet::new();
+        target_groups.insert(node_target_group(
+            "iylgr-zpxwq-kqgmf-4srtx-o4eey-d6bln-smmq6-we7px-ibdea-nondy-eae",
+            "[2a02:800:2:2003:6801:f6ff:fec4:4c86]:9100",
+        ));
+        let builder = JournaldVectorConfigBuilder::new(
+            32,
+            PathBuf::from("logs"),
+            ReplicaLogParsing::Disabled,
+        )
+        .with_units(vec![
+            "orchestrator.service".into(),
+            "ic-replica.service".into(),
+        ]);
+
+        let config = serde_json::to_value(&from_targets_into_vector_config(
+            &builder,
+            target_groups,
+            JobType::NodeExporter(NodeOS::Guest),
+        ))
+        .unwrap();
+
+        let key = "iylgr-zpxwq-kqgmf-4srtx-o4eey-d6bln-smmq6-we7px-ibdea-nondy-eae-node_exporter";
+        let sources = config["sources"].as_object().unwrap();
+        assert_eq!(sources.len(), 2);
+        let source = &sources[&format!("{}-orchestrator-source", key)];
+        assert_eq!(
+            source["include_units"],
+            serde_json::json!(["orchestrator.service"])
+        );
+        assert_eq!(
+            source["data_dir"],
+            format!("logs/{}-orchestrator", key).as_str()
+        );
+        assert_eq!(
+            config["transforms"][&format!("{}-ic-replica-transform", key)]["inputs"],
+            serde_json::json!([format!("{}-ic-replica-source", key)])
+        );
+    }
+
+    #[test]
+    fn remap_snippets_are_appended_to_the_transforms_of_their_job() {
+        let mut target_groups = BTreeSet::new();
+        target_groups.insert(node_target_group(
+            "iylgr-zpxwq-kqgmf-4srtx-o4eey-d6bln-smmq6-we7px-ibdea-nondy-eae",
+            "[2a02:800:2:2003:6801:f6ff:fec4:4c86]:9100",
+        ));
+        let guest = JobType::NodeExporter(NodeOS::Guest);
+        let snippet = |job, source: &str| RemapSnippet::new(job, source.to_string()).unwrap();
+        let builder =
+            JournaldVectorConfigBuilder::new(32, PathBuf::from("logs"), ReplicaLogParsing::AllJobs)
+                .with_remap_snippets(vec![
+                    snippet(guest, "del(.fields)"),
+                    snippet(JobType::NodeExporter(NodeOS::Host), "del(.host)"),
+                    snippet(guest, ".severity = .level"),
+                ]);
+
+        let config = serde_json::to_value(&from_targets_into_vector_config(
+            &builder,
+            target_groups,
+            guest,
+        ))
+        .unwrap();
+
+        let source = config["transforms"]
+            .as_object()
+            .unwrap()
+            .values()
+            .next()
+            .unwrap()["source"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(source.ends_with(&format!(
+            "{}\ndel(.fields)\n.severity = .level",
+            REPLICA_LOG_PARSER
+        )));
+        assert!(!source.contains("del(.host)"));
+    }
+}
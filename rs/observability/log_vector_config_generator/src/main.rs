@@ -3,11 +3,16 @@ use std::net::SocketAddr;
 use std::vec;
 use std::{path::PathBuf, sync::Arc, time::Duration};
 
-use crate::vector_config_structure::VectorConfigBuilderImpl;
 use anyhow::{bail, Result};
 use clap::Parser;
+use config_writer_common::config_writer::{ConfigWriter, OutputLayout};
 use config_writer_common::config_writer_loop::config_writer_loop;
-use config_writer_common::filters::{NodeIDRegexFilter, TargetGroupFilter, TargetGroupFilterList};
+use config_writer_common::file_permissions::FilePermissions;
+use config_writer_common::reachability::{probe_loop, ProbeArgs};
+use config_writer_common::remap_snippets::{parse_remap_snippet, RemapSnippet};
+use config_writer_common::vector_journald_config::{
+    JournaldVectorConfigBuilder, ReplicaLogParsing,
+};
 use futures_util::FutureExt;
 use humantime::parse_duration;
 use ic_async_utils::shutdown_signal;
@@ -15,41 +20,53 @@ use ic_config::metrics::{Config as MetricsConfig, Exporter};
 use ic_http_endpoints_metrics::MetricsHttpEndpoint;
 use ic_metrics::MetricsRegistry;
 use regex::Regex;
+use service_discovery::grace_period::GracePeriodDiscovery;
+use service_discovery::health::{make_systemd_notify_loop, start_health_server, HealthStatus};
 use service_discovery::job_types::{JobType, NodeOS};
+use service_discovery::logger::{make_logger, LogFormat};
 use service_discovery::registry_sync::sync_local_registry;
-use service_discovery::IcServiceDiscoveryImpl;
-use service_discovery::{metrics::Metrics, poll_loop::make_poll_loop};
-use slog::{info, o, Drain, Logger};
+use service_discovery::rest_api::start_http_server;
+use service_discovery::static_targets::StaticTargetsDiscovery;
+use service_discovery::target_filter::{command_line_filter, TargetAttributeFilter};
+use service_discovery::{
+    metrics::Metrics,
+    poll_loop::{make_poll_loop, poll_on_new_ics, poll_on_sighup, PollInterval},
+};
+use service_discovery::{IcServiceDiscovery, IcServiceDiscoveryImpl};
+use slog::{info, warn};
 use url::Url;
 
-mod vector_config_structure;
-
 fn main() -> Result<()> {
     let cli_args = CliArgs::parse().validate()?;
     let rt = tokio::runtime::Runtime::new()?;
-    let log = make_logger();
+    let log = make_logger(cli_args.log_format);
     let metrics_registry = MetricsRegistry::new();
     let shutdown_signal = shutdown_signal(log.clone()).shared();
     let mut handles = vec![];
 
     info!(log, "Starting mercury ...");
+    let metrics = Metrics::new(metrics_registry.clone());
     let mercury_dir = cli_args.targets_dir.join("mercury");
     rt.block_on(sync_local_registry(
         log.clone(),
         mercury_dir,
         cli_args.nns_url,
         cli_args.skip_sync,
-    ));
+        cli_args.max_registry_age,
+        &metrics,
+    ))?;
 
     info!(log, "Starting IcServiceDiscovery ...");
-    let ic_discovery = Arc::new(IcServiceDiscoveryImpl::new(
-        log.clone(),
-        cli_args.targets_dir,
-        cli_args.registry_query_timeout,
-        get_jobs(),
-    )?);
+    let ic_discovery = Arc::new(
+        IcServiceDiscoveryImpl::new(
+            log.clone(),
+            cli_args.targets_dir.clone(),
+            cli_args.registry_query_timeout,
+            get_jobs(),
+        )?
+        .with_ic_precedence(cli_args.ic_precedence.clone()),
+    );
 
-    let metrics = Metrics::new(metrics_registry.clone());
     info!(
         log,
         "Metrics are exposed on {}.", cli_args.metrics_listen_addr
@@ -66,15 +83,33 @@ fn main() -> Result<()> {
     );
 
     let (stop_signal_sender, stop_signal_rcv) = crossbeam::channel::bounded::<()>(0);
+    let health = HealthStatus::new(cli_args.health_max_age);
+    if let Some(notify_loop) =
+        make_systemd_notify_loop(log.clone(), health.clone(), stop_signal_rcv.clone())
+    {
+        handles.push(std::thread::spawn(notify_loop));
+    }
     let (update_signal_sender, update_signal_rcv) = crossbeam::channel::bounded::<()>(0);
+    let (poll_now_sender, poll_now_rcv) = crossbeam::channel::bounded::<()>(1);
+    std::thread::spawn(poll_on_new_ics(
+        log.clone(),
+        cli_args.targets_dir.clone(),
+        poll_now_sender.clone(),
+    ));
+    rt.spawn(poll_on_sighup(log.clone(), poll_now_sender));
     let poll_loop = make_poll_loop(
         log.clone(),
         rt.handle().clone(),
         ic_discovery.clone(),
         stop_signal_rcv.clone(),
-        cli_args.poll_interval,
+        poll_now_rcv,
+        PollInterval::new(
+            cli_args.poll_interval,
+            cli_args.max_poll_interval.unwrap_or(cli_args.poll_interval),
+        ),
         metrics.clone(),
-        Some(update_signal_sender),
+        vec![update_signal_sender],
+        health.clone(),
     );
 
     info!(
@@ -84,30 +119,99 @@ fn main() -> Result<()> {
     let join_handle = std::thread::spawn(poll_loop);
     handles.push(join_handle);
 
-    let mut filters_vec: Vec<Box<dyn TargetGroupFilter>> = vec![];
-    if let Some(filter_node_id_regex) = &cli_args.filter_node_id_regex {
-        filters_vec.push(Box::new(NodeIDRegexFilter::new(
-            filter_node_id_regex.clone(),
-        )));
+    let targets_discovery: Arc<dyn IcServiceDiscovery> = match &cli_args.static_targets_file {
+        Some(path) => Arc::new(StaticTargetsDiscovery::new(
+            ic_discovery.clone(),
+            path.clone(),
+        )),
+        None => ic_discovery.clone(),
+    };
+    let targets_discovery: Arc<dyn IcServiceDiscovery> = match cli_args.removal_grace_period {
+        Some(grace_period) => Arc::new(GracePeriodDiscovery::new(targets_discovery, grace_period)),
+        None => targets_discovery,
     };
 
-    let filters = Arc::new(TargetGroupFilterList::new(filters_vec));
+    let filters = Arc::new(command_line_filter(
+        cli_args.filter_node_id_regex.as_ref(),
+        &cli_args.target_filters,
+    ));
+
+    let permissions = cli_args.permissions.clone();
+
+    if let Some(unreachable_targets_file) = cli_args.probe.unreachable_targets_file {
+        let probe_loop = probe_loop(
+            log.clone(),
+            targets_discovery.clone(),
+            filters.clone(),
+            vec![JobType::NodeExporter(NodeOS::Guest)],
+            cli_args.probe.probe_interval(),
+            cli_args.probe.probe_timeout(),
+            unreachable_targets_file,
+            permissions.clone(),
+            metrics.clone(),
+            stop_signal_rcv.clone(),
+        );
+        handles.push(std::thread::spawn(probe_loop));
+    }
 
     let config_generator_loop = config_writer_loop(
         log.clone(),
-        ic_discovery,
-        filters,
+        targets_discovery.clone(),
         stop_signal_rcv,
         vec![JobType::NodeExporter(NodeOS::Guest)],
         update_signal_rcv,
-        cli_args.vector_config_dir,
-        VectorConfigBuilderImpl::new(cli_args.batch_size),
+        ConfigWriter::new(cli_args.vector_config_dir, filters, log.clone())
+            .with_permissions(permissions)
+            .with_layout(cli_args.output_layout),
+        cli_args.manifest_file,
+        JournaldVectorConfigBuilder::new(
+            cli_args.batch_size,
+            cli_args.cursors_dir,
+            ReplicaLogParsing::new(
+                cli_args.parse_replica_logs,
+                cli_args.parse_replica_logs_jobs,
+            ),
+        )
+        .with_units(cli_args.journald_units)
+        .with_remap_snippets(cli_args.remap_snippets),
         metrics,
+        health.clone(),
     );
     info!(log, "Spawning config generator thread.");
     let config_join_handle = std::thread::spawn(config_generator_loop);
     handles.push(config_join_handle);
 
+    if let Some(sd_listen_addr) = cli_args.http_sd_listen_addr {
+        info!(
+            log,
+            "Serving discovered targets for http_sd on {}.", sd_listen_addr
+        );
+        let http_sd_log = log.clone();
+        let http_sd = start_http_server(
+            log.clone(),
+            targets_discovery,
+            sd_listen_addr,
+            shutdown_signal.clone(),
+        );
+        rt.spawn(async move {
+            if let Err(e) = http_sd.await {
+                warn!(http_sd_log, "http_sd server failed: {:?}", e);
+            }
+        });
+    }
+
+    if let Some(health_listen_addr) = cli_args.health_listen_addr {
+        info!(log, "Serving /healthz on {}.", health_listen_addr);
+        let health_log = log.clone();
+        let health_server =
+            start_health_server(health.clone(), health_listen_addr, shutdown_signal.clone());
+        rt.spawn(async move {
+            if let Err(e) = health_server.await {
+                warn!(health_log, "Health server failed: {:?}", e);
+            }
+        });
+    }
+
     rt.block_on(shutdown_signal);
 
     for handle in handles {
@@ -118,13 +222,6 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn make_logger() -> Logger {
-    let decorator = slog_term::TermDecorator::new().build();
-    let drain = slog_term::FullFormat::new(decorator).build().fuse();
-    let drain = slog_async::Async::new(drain).chan_size(8192).build();
-    slog::Logger::root(drain.fuse(), o!())
-}
-
 #[derive(Parser, Debug)]
 #[clap(about, version)]
 pub struct CliArgs {
@@ -147,12 +244,25 @@ initialized with a hardcoded initial registry.
     default_value = "10s",
     parse(try_from_str = parse_duration),
     help = r#"
-The interval at which ICs are polled for updates.
+The interval at which ICs are polled for updates. If `--max-poll-interval`
+is specified, this is the interval used while registry versions are advancing.
 
 "#
     )]
     poll_interval: Duration,
 
+    #[clap(
+    long = "max-poll-interval",
+    parse(try_from_str = parse_duration),
+    help = r#"
+If specified, the poll interval is doubled every time no new registry version
+is observed, up to the given ceiling, and reset to `--poll-interval` as soon as
+a registry advances.
+
+"#
+    )]
+    max_poll_interval: Option<Duration>,
+
     #[clap(
     long = "query-request-timeout",
     default_value = "5s",
@@ -173,6 +283,55 @@ Regex used to filter the node IDs
     )]
     filter_node_id_regex: Option<Regex>,
 
+    #[clap(
+        long = "static-targets-file",
+        help = r#"
+JSON file listing targets that are not part of any registry. Each entry has a
+`job`, a list of `targets` ("ip:port") and optional `labels` and `ic_name`. The
+file is re-read on every generation.
+
+"#
+    )]
+    static_targets_file: Option<PathBuf>,
+
+    #[clap(
+        long = "ic-precedence",
+        help = r#"
+Name of an IC, i.e. of a directory in `--targets-dir`, whose targets take
+precedence if the same targets are listed by more than one IC. Can be specified
+multiple times, in decreasing order of precedence. ICs that are not listed come
+afterwards in alphabetical order.
+
+"#
+    )]
+    ic_precedence: Vec<String>,
+
+    #[clap(
+    long = "removal-grace-period",
+    parse(try_from_str = parse_duration),
+    help = r#"
+If specified, targets that vanish from discovery are kept in the generated
+configs, labeled with `pending_removal="true"`, for the given duration before
+they are removed. This avoids config churn when registry reads flap.
+
+"#
+    )]
+    removal_grace_period: Option<Duration>,
+
+    #[clap(
+        long = "target-filter",
+        help = r#"
+Only keep targets matching the filter, given as `<key>=<value>[,<value>...]`
+where key is one of `node_id`, `subnet_id`, `operator_id`, `dc_id` or
+`label.<name>` for the custom label `<name>`. A target matches if its attribute
+equals any of the values. With `<key>!=<value>[,<value>...]`, a target matches
+if its attribute equals none of the values. Can be specified multiple times, in
+which case a target has to match all filters.
+
+"#
+    )]
+    target_filters: Vec<TargetAttributeFilter>,
+
     #[clap(
         long = "generation-dir",
         help = r#"
@@ -185,6 +344,36 @@ https://www.freedesktop.org/software/systemd/man/systemd-journal-gatewayd.servic
     )]
     vector_config_dir: PathBuf,
 
+    #[clap(flatten)]
+    permissions: FilePermissions,
+
+    #[clap(
+        long = "output-layout",
+        default_value = "merged",
+        help = r#"
+How the generated configs are split into files: `merged` writes a single file
+per job containing all of its targets, `per-node` writes a file per job and
+node. The latter limits the changes to the affected nodes, but large fleets may
+run into inode or inotify limits.
+
+"#
+    )]
+    output_layout: OutputLayout,
+
+    #[clap(
+        long = "manifest-file",
+        help = r#"
+If specified, a JSON manifest with the time of the last generation, the latest
+registry version per IC and the number of targets per job is written to this
+file after every generation.
+
+"#
+    )]
+    manifest_file: Option<PathBuf>,
+
+    #[clap(flatten)]
+    probe: ProbeArgs,
+
     #[clap(
         long = "nns-url",
         default_value = "https://ic0.app",
@@ -203,6 +392,17 @@ Possible only if the version is not a ZERO_REGISTRY_VERSION
     )]
     skip_sync: bool,
 
+    #[clap(
+    long = "max-registry-age",
+    parse(try_from_str = parse_duration),
+    help = r#"
+Only used together with `--skip-sync`. If the local registry was certified
+longer ago than the given duration, refuse to start.
+
+"#
+    )]
+    max_registry_age: Option<Duration>,
+
     #[clap(
         long = "batch-size",
         help = r#"
@@ -213,6 +413,78 @@ persisting the cursor. Default value is 32
     )]
     batch_size: u64,
 
+    #[clap(
+        long = "cursors-dir",
+        default_value = "logs",
+        help = r#"
+Directory in which the generated sources persist their journald cursors. Each
+source gets its own subdirectory, so that restarting vector resumes every node
+from where it stopped.
+
+"#
+    )]
+    cursors_dir: PathBuf,
+
+    #[clap(
+        long = "parse-replica-logs",
+        help = r#"
+Parse the replica's structured log lines into `level`, `crate`, `module` and
+`correlation_id` fields by the generated transforms.
+
+"#
+    )]
+    parse_replica_logs: bool,
+
+    #[clap(
+        long = "parse-replica-logs-job",
+        requires = "parse-replica-logs",
+        help = r#"
+Only used with `--parse-replica-logs`. Restricts the parsing to the logs of
+this job. Can be given multiple times, e.g.
+`--parse-replica-logs-job node_exporter`. If not specified, the logs of all
+jobs are parsed.
+
+"#
+    )]
+    parse_replica_logs_jobs: Vec<JobType>,
+
+    #[clap(
+        long = "journald-unit",
+        help = r#"
+Systemd unit whose journal is read, e.g. `orchestrator.service` or
+`ic-replica.service`. Can be given multiple times, in which case a separate
+source is generated per node and unit, allowing a pipeline per service. If not
+specified, the full journal of every node is read.
+
+"#
+    )]
+    journald_units: Vec<String>,
+
+    #[clap(
+        long = "remap-snippet",
+        parse(try_from_str = parse_remap_snippet),
+        help = r#"
+A file with a VRL program that is appended to the generated transforms of a
+job, given as `<job>=<path>`, e.g. `node_exporter=/etc/vector/drop_fields.vrl`.
+The program runs after the labels are set and the log lines are parsed, and is
+checked for unterminated literals and unbalanced brackets on startup. It is
+only compiled by vector when it loads the generated config. Can be given
+multiple times, in which case the programs of a job run in the given order.
+
+"#
+    )]
+    remap_snippets: Vec<RemapSnippet>,
+
+    #[clap(
+        long = "log-format",
+        default_value = "text",
+        help = r#"
+The format of the log output, either `text` or `json`.
+
+"#
+    )]
+    log_format: LogFormat,
+
     #[clap(
         long = "metrics-listen-addr",
         default_value = "[::]:9099",
@@ -222,6 +494,42 @@ The listen address on which metrics for this service should be exposed.
 "#
     )]
     metrics_listen_addr: SocketAddr,
+
+    #[clap(
+        long = "http-sd-listen-addr",
+        help = r#"
+If specified, the discovered targets are exposed on the given address in the
+format expected by Prometheus' http_sd, with one endpoint per job, e.g.
+http://[::]:11235/replica
+
+"#
+    )]
+    http_sd_listen_addr: Option<SocketAddr>,
+
+    #[clap(
+        long = "health-listen-addr",
+        help = r#"
+If specified, the health of the generator is exposed as JSON on
+http://<addr>/healthz, responding with 503 if the last successful discovery or
+config write is older than `--health-max-age`.
+
+"#
+    )]
+    health_listen_addr: Option<SocketAddr>,
+
+    #[clap(
+    long = "health-max-age",
+    default_value = "10m",
+    parse(try_from_str = parse_duration),
+    help = r#"
+The generator is considered unhealthy if it did not discover targets and write
+configs successfully within this duration. When run by systemd with
+`WatchdogSec` set, watchdog notifications are only sent while the generator is
+healthy.
+
+"#
+    )]
+    health_max_age: Duration,
 }
 
 impl CliArgs {
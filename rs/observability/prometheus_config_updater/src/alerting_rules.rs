@@ -0,0 +1,219 @@
+//! Prometheus alerting rules derived from the discovered topology. For every
+//! subnet, the rules alert if fewer replicas are up than the subnet needs to
+//! make progress, and if the finalized height of some replica lags behind the
+//! rest of the subnet. As the thresholds are rendered from the current subnet
+//! membership, they follow node additions and removals without manual edits.
+//!
+//! The rules are written as JSON, which Prometheus accepts as a YAML rule file.
+use std::collections::{BTreeMap, BTreeSet};
+
+use config_writer_common::{
+    config_builder::{Config, ConfigBuilder},
+    labels_keys,
+};
+use ic_types::SubnetId;
+use serde::Serialize;
+use service_discovery::{job_types::JobType, jobs::Job, TargetGroup};
+
+/// How long a condition has to hold before an alert fires.
+const ALERT_FOR: &str = "5m";
+
+/// The replica metric exposing the finalized height of a node.
+const FINALIZED_HEIGHT: &str =
+    r#"artifact_pool_consensus_height_stat{pool_type="validated",stat="max",type="finalization"}"#;
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct AlertingRule {
+    alert: String,
+    expr: String,
+    #[serde(rename = "for")]
+    for_duration: String,
+    labels: BTreeMap<String, String>,
+    annotations: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RuleGroup {
+    name: String,
+    rules: Vec<AlertingRule>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct AlertingRules {
+    groups: Vec<RuleGroup>,
+    #[serde(skip)]
+    job: JobType,
+    #[serde(skip)]
+    updated: bool,
+}
+
+impl Config for AlertingRules {
+    fn updated(&self) -> bool {
+        self.updated
+    }
+
+    fn name(&self) -> String {
+        format!("{}_alerting_rules", self.job)
+    }
+}
+
+pub struct AlertingRulesBuilder {
+    /// Number of heights a replica may fall behind the most advanced replica
+    /// of its subnet before an alert fires.
+    finalization_lag_threshold: u64,
+    last_groups: Option<Vec<RuleGroup>>,
+}
+
+impl AlertingRulesBuilder {
+    pub fn new(finalization_lag_threshold: u64) -> Self {
+        Self {
+            finalization_lag_threshold,
+            last_groups: None,
+        }
+    }
+
+    fn subnet_rules(&self, ic_name: &str, subnet_id: SubnetId, nodes: usize) -> RuleGroup {
+        let selector = format!(
+            r#"{}="{}",{}="{}""#,
+            labels_keys::IC_NAME,
+            ic_name,
+            labels_keys::IC_SUBNET,
+            subnet_id
+        );
+        let labels = BTreeMap::from([
+            (labels_keys::IC_NAME.to_string(), ic_name.to_string()),
+            (labels_keys::IC_SUBNET.to_string(), subnet_id.to_string()),
+        ]);
+        let min_healthy_nodes = min_healthy_nodes(nodes);
+        let finalized_height = FINALIZED_HEIGHT.replacen('}', &format!(",{}}}", selector), 1);
+        RuleGroup {
+            name: format!("{}_{}", ic_name, subnet_id),
+            rules: vec![
+                AlertingRule {
+                    alert: "IcSubnetTooFewHealthyNodes".into(),
+                    expr: format!(
+                        r#"(count(up{{{},{}="{}"}} == 1) or vector(0)) < {}"#,
+                        selector,
+                        labels_keys::JOB,
+                        JobType::Replica,
+                        min_healthy_nodes
+                    ),
+                    for_duration: ALERT_FOR.into(),
+                    labels: labels.clone(),
+                    annotations: BTreeMap::from([(
+                        "summary".to_string(),
+                        format!(
+                            "Fewer than {} of the {} replicas of subnet {} are up",
+                            min_healthy_nodes, nodes, subnet_id
+                        ),
+                    )]),
+                },
+                AlertingRule {
+                    alert: "IcSubnetFinalizationLag".into(),
+                    expr: format!(
+                        "max by ({}) ({}) - on({}) group_right {} > {}",
+                        labels_keys::IC_SUBNET,
+                        finalized_height,
+                        labels_keys::IC_SUBNET,
+                        finalized_height,
+                        self.finalization_lag_threshold
+                    ),
+                    for_duration: ALERT_FOR.into(),
+                    labels,
+                    annotations: BTreeMap::from([(
+                        "summary".to_string(),
+                        format!(
+                            "A replica of subnet {} lags more than {} heights behind",
+                            subnet_id, self.finalization_lag_threshold
+                        ),
+                    )]),
+                },
+            ],
+        }
+    }
+}
+
+/// The number of nodes that have to be up for a subnet of `nodes` nodes to make
+/// progress, i.e. all but the at most `(nodes - 1) / 3` faulty ones.
+fn min_healthy_nodes(nodes: usize) -> usize {
+    nodes - nodes.saturating_sub(1) / 3
+}
+
+impl ConfigBuilder for AlertingRulesBuilder {
+    fn build(&mut self, target_groups: BTreeSet<TargetGroup>, job: Job) -> Box<dyn Config> {
+        let mut subnets: BTreeMap<(String, SubnetId), usize> = BTreeMap::new();
+        for tg in target_groups {
+            if let Some(subnet_id) = tg.subnet_id {
+                *subnets.entry((tg.ic_name, subnet_id)).or_default() += 1;
+            }
+        }
+        let groups: Vec<RuleGroup> = subnets
+            .into_iter()
+            .map(|((ic_name, subnet_id), nodes)| self.subnet_rules(&ic_name, subnet_id, nodes))
+            .collect();
+
+        let updated = self.last_groups.as_ref() != Some(&groups);
+        if updated {
+            self.last_groups = Some(groups.clone());
+        }
+
+        Box::new(AlertingRules {
+            groups,
+            job: job._type,
+            updated,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use config_writer_common::config_builder::ConfigBuilder;
+    use service_discovery::{test_utils::target_group, TargetGroup};
+
+    use super::{min_healthy_nodes, AlertingRulesBuilder};
+    use crate::jobs;
+
+    #[test]
+    fn min_healthy_nodes_test() {
+        assert_eq!(min_healthy_nodes(1), 1);
+        assert_eq!(min_healthy_nodes(4), 3);
+        assert_eq!(min_healthy_nodes(13), 9);
+        assert_eq!(min_healthy_nodes(28), 19);
+    }
+
+    #[test]
+    fn rules_follow_subnet_membership() {
+        let mut builder = AlertingRulesBuilder::new(100);
+        let mut target_groups: BTreeSet<TargetGroup> = (0..4)
+            .map(|node| target_group(node).subnet(1).build())
+            .collect();
+        // Unassigned nodes do not get any rules.
+        target_groups.insert(target_group(4).build());
+
+        let config = builder.build(target_groups.clone(), jobs::JOB_REPLICA);
+        assert!(config.updated());
+        assert_eq!(config.name(), "replica_alerting_rules");
+        let rules = serde_json::to_value(&config).unwrap();
+        let groups = rules["groups"].as_array().unwrap();
+        assert_eq!(groups.len(), 1);
+        let expr = groups[0]["rules"][0]["expr"].as_str().unwrap();
+        assert!(expr.ends_with("< 3"), "{}", expr);
+        assert_eq!(groups[0]["rules"][0]["for"], "5m");
+
+        let config = builder.build(target_groups.clone(), jobs::JOB_REPLICA);
+        assert!(!config.updated());
+
+        target_groups.insert(target_group(5).subnet(2).build());
+        let config = builder.build(target_groups, jobs::JOB_REPLICA);
+        assert!(config.updated());
+        assert_eq!(
+            serde_json::to_value(&config).unwrap()["groups"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+}
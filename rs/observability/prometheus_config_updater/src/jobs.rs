@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use service_discovery::{
-    job_types::{JobType, NodeOS},
+    job_types::{JobType, NodeOS, ProbeKind},
     jobs::Job,
 };
 
@@ -29,12 +29,27 @@ pub const JOB_ORCHESTRATOR: Job = Job {
     endpoint: "/",
 };
 
+/// The port is not used by ICMP probes, only the node's address.
+pub const JOB_BLACKBOX_ICMP: Job = Job {
+    _type: JobType::Blackbox(ProbeKind::Icmp),
+    port: 0,
+    endpoint: "",
+};
+
+pub const JOB_BLACKBOX_HTTPS: Job = Job {
+    _type: JobType::Blackbox(ProbeKind::Https),
+    port: 8080,
+    endpoint: "/api/v2/status",
+};
+
 pub fn jobs_list() -> Vec<Job> {
     vec![
         JOB_NODE_EXPORTER_GUEST,
         JOB_NODE_EXPORTER_HOST,
         JOB_ORCHESTRATOR,
         JOB_REPLICA,
+        JOB_BLACKBOX_ICMP,
+        JOB_BLACKBOX_HTTPS,
     ]
 }
 
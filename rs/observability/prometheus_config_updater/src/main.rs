@@ -4,7 +4,7 @@ use std::{path::PathBuf, sync::Arc, time::Duration};
 use anyhow::{bail, Result};
 use clap::Parser;
 use config_writer_common::config_writer::ConfigWriter;
-use config_writer_common::filters::{NodeIDRegexFilter, TargetGroupFilter, TargetGroupFilterList};
+use config_writer_common::file_permissions::FilePermissions;
 use futures_util::FutureExt;
 use humantime::parse_duration;
 use ic_async_utils::shutdown_signal;
@@ -12,14 +12,26 @@ use ic_config::metrics::{Config as MetricsConfig, Exporter};
 use ic_http_endpoints_metrics::MetricsHttpEndpoint;
 use ic_metrics::MetricsRegistry;
 use regex::Regex;
+use service_discovery::grace_period::GracePeriodDiscovery;
+use service_discovery::health::{make_systemd_notify_loop, start_health_server, HealthStatus};
+use service_discovery::logger::{make_logger, LogFormat};
 use service_discovery::registry_sync::sync_local_registry;
-use service_discovery::{metrics::Metrics, poll_loop::make_poll_loop, IcServiceDiscoveryImpl};
-use slog::{info, o, Drain, Logger};
+use service_discovery::rest_api::start_http_server;
+use service_discovery::static_targets::StaticTargetsDiscovery;
+use service_discovery::target_filter::{command_line_filter, TargetAttributeFilter, TargetFilter};
+use service_discovery::{
+    metrics::Metrics,
+    poll_loop::{make_poll_loop, poll_on_new_ics, poll_on_sighup, PollInterval},
+    IcServiceDiscovery, IcServiceDiscoveryImpl,
+};
+use slog::{info, warn};
 use url::Url;
 
+use crate::alerting_rules::AlertingRulesBuilder;
 use crate::custom_filters::OldMachinesFilter;
 use crate::prometheus_config::PrometheusConfigBuilder;
 
+mod alerting_rules;
 mod custom_filters;
 mod jobs;
 mod prometheus_config;
@@ -34,31 +46,36 @@ pub struct JobParameters {
 fn main() -> Result<()> {
     let cli_args = CliArgs::parse().validate()?;
     let rt = tokio::runtime::Runtime::new()?;
-    let log = make_logger();
+    let log = make_logger(cli_args.log_format);
     let metrics_registry = MetricsRegistry::new();
     let shutdown_signal = shutdown_signal(log.clone()).shared();
     let mut handles = vec![];
 
     info!(log, "Starting prometheus-config-updater");
+    let metrics = Metrics::new(metrics_registry.clone());
     let mercury_dir = cli_args.targets_dir.join("mercury");
     rt.block_on(sync_local_registry(
         log.clone(),
         mercury_dir,
         cli_args.nns_url,
         cli_args.skip_sync,
-    ));
+        cli_args.max_registry_age,
+        &metrics,
+    ))?;
 
     let jobs = jobs::get_jobs();
 
     info!(log, "Starting IcServiceDiscovery ...");
-    let ic_discovery = Arc::new(IcServiceDiscoveryImpl::new(
-        log.clone(),
-        cli_args.targets_dir,
-        cli_args.registry_query_timeout,
-        jobs,
-    )?);
+    let ic_discovery = Arc::new(
+        IcServiceDiscoveryImpl::new(
+            log.clone(),
+            cli_args.targets_dir.clone(),
+            cli_args.registry_query_timeout,
+            jobs,
+        )?
+        .with_ic_precedence(cli_args.ic_precedence.clone()),
+    );
 
-    let metrics = Metrics::new(metrics_registry.clone());
     info!(
         log,
         "Metrics are exposed on {}.", cli_args.metrics_listen_addr
@@ -75,80 +92,169 @@ fn main() -> Result<()> {
     );
 
     let (stop_signal_sender, stop_signal_rcv) = crossbeam::channel::bounded::<()>(0);
+    let health = HealthStatus::new(cli_args.health_max_age);
+    if let Some(notify_loop) =
+        make_systemd_notify_loop(log.clone(), health.clone(), stop_signal_rcv.clone())
+    {
+        handles.push(std::thread::spawn(notify_loop));
+    }
     let (update_signal_sender, update_signal_rcv) = crossbeam::channel::bounded::<()>(0);
+    let (poll_now_sender, poll_now_rcv) = crossbeam::channel::bounded::<()>(1);
+    std::thread::spawn(poll_on_new_ics(
+        log.clone(),
+        cli_args.targets_dir.clone(),
+        poll_now_sender.clone(),
+    ));
+    rt.spawn(poll_on_sighup(log.clone(), poll_now_sender));
+    let (rules_update_signal_sender, rules_update_signal_rcv) =
+        crossbeam::channel::bounded::<()>(0);
+    let mut update_notifiers = vec![update_signal_sender];
+    if cli_args.alerting_rules_dir.is_some() {
+        update_notifiers.push(rules_update_signal_sender);
+    }
     let loop_fn = make_poll_loop(
         log.clone(),
         rt.handle().clone(),
         ic_discovery.clone(),
         stop_signal_rcv.clone(),
-        cli_args.poll_interval,
+        poll_now_rcv,
+        PollInterval::new(
+            cli_args.poll_interval,
+            cli_args.max_poll_interval.unwrap_or(cli_args.poll_interval),
+        ),
         metrics.clone(),
-        Some(update_signal_sender),
+        update_notifiers,
+        health.clone(),
     );
     let join_handle = std::thread::spawn(loop_fn);
     handles.push(join_handle);
+
+    let targets_discovery: Arc<dyn IcServiceDiscovery> = match &cli_args.static_targets_file {
+        Some(path) => Arc::new(StaticTargetsDiscovery::new(
+            ic_discovery.clone(),
+            path.clone(),
+        )),
+        None => ic_discovery.clone(),
+    };
+    let targets_discovery: Arc<dyn IcServiceDiscovery> = match cli_args.removal_grace_period {
+        Some(grace_period) => Arc::new(GracePeriodDiscovery::new(targets_discovery, grace_period)),
+        None => targets_discovery,
+    };
     info!(
         log,
         "Scraping thread spawned. Interval: {:?}", cli_args.poll_interval
     );
 
-    let mut filters_vec: Vec<Box<dyn TargetGroupFilter>> = vec![];
-    if let Some(filter_node_id_regex) = &cli_args.filter_node_id_regex {
-        filters_vec.push(Box::new(NodeIDRegexFilter::new(
-            filter_node_id_regex.clone(),
-        )));
-    };
-
     // We need to filter old nodes for host node exporters, but not for everything else
     // To do that, we will create 2 separate updated nodes, with different filters for them
     let jobs = vec![
         jobs::JOB_NODE_EXPORTER_GUEST,
         jobs::JOB_ORCHESTRATOR,
         jobs::JOB_REPLICA,
+        jobs::JOB_BLACKBOX_ICMP,
+        jobs::JOB_BLACKBOX_HTTPS,
     ];
 
-    let filters = Arc::new(TargetGroupFilterList::new(filters_vec));
+    let permissions = cli_args.permissions.clone();
+
+    let filters = Arc::new(command_line_filter(
+        cli_args.filter_node_id_regex.as_ref(),
+        &cli_args.target_filters,
+    ));
     let config_updater_loop = config_writer_common::config_updater_loop::config_updater_loop(
         log.clone(),
-        ic_discovery.clone(),
+        targets_discovery.clone(),
         filters.clone(),
         stop_signal_rcv.clone(),
         jobs,
         update_signal_rcv.clone(),
         PrometheusConfigBuilder::new(),
-        ConfigWriter::new(cli_args.generation_dir.clone(), filters, log.clone()),
+        ConfigWriter::new(
+            cli_args.generation_dir.clone(),
+            filters.clone(),
+            log.clone(),
+        )
+        .with_permissions(permissions.clone()),
         metrics.clone(),
+        health.clone(),
     );
     let config_join_handle = std::thread::spawn(config_updater_loop);
     handles.push(config_join_handle);
 
-    // creating the filters vector again because of ownership
-    let mut filters_vec: Vec<Box<dyn TargetGroupFilter>> = vec![];
-    if let Some(filter_node_id_regex) = &cli_args.filter_node_id_regex {
-        filters_vec.push(Box::new(NodeIDRegexFilter::new(
-            filter_node_id_regex.clone(),
-        )));
-    };
+    if let Some(alerting_rules_dir) = cli_args.alerting_rules_dir.clone() {
+        let rules_updater_loop = config_writer_common::config_updater_loop::config_updater_loop(
+            log.clone(),
+            targets_discovery.clone(),
+            filters.clone(),
+            stop_signal_rcv.clone(),
+            vec![jobs::JOB_REPLICA],
+            rules_update_signal_rcv,
+            AlertingRulesBuilder::new(cli_args.finalization_lag_threshold),
+            ConfigWriter::new(alerting_rules_dir, filters, log.clone())
+                .with_permissions(permissions.clone()),
+            metrics.clone(),
+            health.clone(),
+        );
+        handles.push(std::thread::spawn(rules_updater_loop));
+    }
+
     // Second loop, with the old machines filter
     let jobs = vec![jobs::JOB_NODE_EXPORTER_HOST];
 
-    filters_vec.push(Box::new(OldMachinesFilter {}));
-
-    let filters = Arc::new(TargetGroupFilterList::new(filters_vec));
+    let filters = Arc::new(
+        command_line_filter(
+            cli_args.filter_node_id_regex.as_ref(),
+            &cli_args.target_filters,
+        )
+        .and(OldMachinesFilter {}),
+    );
     let config_updater_loop = config_writer_common::config_updater_loop::config_updater_loop(
         log.clone(),
-        ic_discovery,
+        targets_discovery.clone(),
         filters.clone(),
         stop_signal_rcv,
         jobs,
         update_signal_rcv,
         PrometheusConfigBuilder::new(),
-        ConfigWriter::new(cli_args.generation_dir, filters, log.clone()),
+        ConfigWriter::new(cli_args.generation_dir, filters, log.clone())
+            .with_permissions(permissions),
         metrics,
+        health.clone(),
     );
     let config_join_handle = std::thread::spawn(config_updater_loop);
     handles.push(config_join_handle);
 
+    if let Some(sd_listen_addr) = cli_args.http_sd_listen_addr {
+        info!(
+            log,
+            "Serving discovered targets for http_sd on {}.", sd_listen_addr
+        );
+        let http_sd_log = log.clone();
+        let http_sd = start_http_server(
+            log.clone(),
+            targets_discovery,
+            sd_listen_addr,
+            shutdown_signal.clone(),
+        );
+        rt.spawn(async move {
+            if let Err(e) = http_sd.await {
+                warn!(http_sd_log, "http_sd server failed: {:?}", e);
+            }
+        });
+    }
+
+    if let Some(health_listen_addr) = cli_args.health_listen_addr {
+        info!(log, "Serving /healthz on {}.", health_listen_addr);
+        let health_log = log.clone();
+        let health_server =
+            start_health_server(health.clone(), health_listen_addr, shutdown_signal.clone());
+        rt.spawn(async move {
+            if let Err(e) = health_server.await {
+                warn!(health_log, "Health server failed: {:?}", e);
+            }
+        });
+    }
+
     rt.block_on(shutdown_signal);
 
     for handle in handles {
@@ -158,13 +264,6 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn make_logger() -> Logger {
-    let decorator = slog_term::TermDecorator::new().build();
-    let drain = slog_term::FullFormat::new(decorator).build().fuse();
-    let drain = slog_async::Async::new(drain).chan_size(8192).build();
-    slog::Logger::root(drain.fuse(), o!())
-}
-
 #[derive(Parser, Debug)]
 #[clap(about, version)]
 pub struct CliArgs {
@@ -187,12 +286,25 @@ initialized with a hardcoded initial registry.
     default_value = "10s",
     parse(try_from_str = parse_duration),
     help = r#"
-The interval at which ICs are polled for updates.
+The interval at which ICs are polled for updates. If `--max-poll-interval`
+is specified, this is the interval used while registry versions are advancing.
 
 "#
     )]
     poll_interval: Duration,
 
+    #[clap(
+    long = "max-poll-interval",
+    parse(try_from_str = parse_duration),
+    help = r#"
+If specified, the poll interval is doubled every time no new registry version
+is observed, up to the given ceiling, and reset to `--poll-interval` as soon as
+a registry advances.
+
+"#
+    )]
+    max_poll_interval: Option<Duration>,
+
     #[clap(
     long = "query-request-timeout",
     default_value = "5s",
@@ -215,6 +327,33 @@ targets.
     )]
     generation_dir: PathBuf,
 
+    #[clap(
+        long = "alerting-rules-dir",
+        help = r#"
+If specified, Prometheus alerting rules are written to
+<alerting_rules_dir>/replica_alerting_rules.json. For every subnet, they alert
+if fewer replicas are up than the subnet needs to make progress, and if a
+replica falls behind in finalization. The thresholds follow the subnet
+membership as discovered from the registry.
+
+"#
+    )]
+    alerting_rules_dir: Option<PathBuf>,
+
+    #[clap(
+        long = "finalization-lag-threshold",
+        default_value = "100",
+        help = r#"
+Only used with `--alerting-rules-dir`. The number of heights a replica may lag
+behind the most advanced replica of its subnet before an alert fires.
+
+"#
+    )]
+    finalization_lag_threshold: u64,
+
+    #[clap(flatten)]
+    permissions: FilePermissions,
+
     #[clap(
         long = "filter-node-id-regex",
         help = r#"
@@ -224,6 +363,55 @@ Regex used to filter the node IDs
     )]
     filter_node_id_regex: Option<Regex>,
 
+    #[clap(
+        long = "static-targets-file",
+        help = r#"
+JSON file listing targets that are not part of any registry. Each entry has a
+`job`, a list of `targets` ("ip:port") and optional `labels` and `ic_name`. The
+file is re-read on every generation.
+
+"#
+    )]
+    static_targets_file: Option<PathBuf>,
+
+    #[clap(
+        long = "ic-precedence",
+        help = r#"
+Name of an IC, i.e. of a directory in `--targets-dir`, whose targets take
+precedence if the same targets are listed by more than one IC. Can be specified
+multiple times, in decreasing order of precedence. ICs that are not listed come
+afterwards in alphabetical order.
+
+"#
+    )]
+    ic_precedence: Vec<String>,
+
+    #[clap(
+    long = "removal-grace-period",
+    parse(try_from_str = parse_duration),
+    help = r#"
+If specified, targets that vanish from discovery are kept in the generated
+configs, labeled with `pending_removal="true"`, for the given duration before
+they are removed. This avoids config churn when registry reads flap.
+
+"#
+    )]
+    removal_grace_period: Option<Duration>,
+
+    #[clap(
+        long = "target-filter",
+        help = r#"
+Only keep targets matching the filter, given as `<key>=<value>[,<value>...]`
+where key is one of `node_id`, `subnet_id`, `operator_id`, `dc_id` or
+`label.<name>` for the custom label `<name>`. A target matches if its attribute
+equals any of the values. With `<key>!=<value>[,<value>...]`, a target matches
+if its attribute equals none of the values. Can be specified multiple times, in
+which case a target has to match all filters.
+
+"#
+    )]
+    target_filters: Vec<TargetAttributeFilter>,
+
     #[clap(
         long = "nns-url",
         default_value = "https://ic0.app",
@@ -242,6 +430,27 @@ Possible only if the version is not a ZERO_REGISTRY_VERSION
     )]
     skip_sync: bool,
 
+    #[clap(
+    long = "max-registry-age",
+    parse(try_from_str = parse_duration),
+    help = r#"
+Only used together with `--skip-sync`. If the local registry was certified
+longer ago than the given duration, refuse to start.
+
+"#
+    )]
+    max_registry_age: Option<Duration>,
+
+    #[clap(
+        long = "log-format",
+        default_value = "text",
+        help = r#"
+The format of the log output, either `text` or `json`.
+
+"#
+    )]
+    log_format: LogFormat,
+
     #[clap(
         long = "metrics-listen-addr",
         default_value = "[::]:9099",
@@ -251,6 +460,42 @@ The listen address on which metrics for this service should be exposed.
 "#
     )]
     metrics_listen_addr: SocketAddr,
+
+    #[clap(
+        long = "http-sd-listen-addr",
+        help = r#"
+If specified, the discovered targets are exposed on the given address in the
+format expected by Prometheus' http_sd, with one endpoint per job, e.g.
+http://[::]:11235/replica
+
+"#
+    )]
+    http_sd_listen_addr: Option<SocketAddr>,
+
+    #[clap(
+        long = "health-listen-addr",
+        help = r#"
+If specified, the health of the generator is exposed as JSON on
+http://<addr>/healthz, responding with 503 if the last successful discovery or
+config write is older than `--health-max-age`.
+
+"#
+    )]
+    health_listen_addr: Option<SocketAddr>,
+
+    #[clap(
+    long = "health-max-age",
+    default_value = "10m",
+    parse(try_from_str = parse_duration),
+    help = r#"
+The generator is considered unhealthy if it did not discover targets and write
+configs successfully within this duration. When run by systemd with
+`WatchdogSec` set, watchdog notifications are only sent while the generator is
+healthy.
+
+"#
+    )]
+    health_max_age: Duration,
 }
 impl CliArgs {
     fn validate(self) -> Result<Self> {
@@ -5,7 +5,11 @@ use config_writer_common::{
     labels_keys,
 };
 use serde::{Serialize, Serializer};
-use service_discovery::{job_types::JobType, jobs::Job, TargetGroup};
+use service_discovery::{
+    job_types::{JobType, ProbeKind},
+    jobs::Job,
+    TargetGroup,
+};
 
 #[derive(Serialize, Debug, Clone, PartialEq, PartialOrd, Ord, Eq)]
 pub struct PrometheusStaticConfig {
@@ -59,11 +63,18 @@ impl PrometheusConfigBuilder {
     }
 }
 
-fn get_endpoints(target_group: TargetGroup, _job: Job) -> BTreeSet<String> {
+/// Blackbox exporter targets are passed to the probe as the `target` parameter,
+/// so they are rendered in the form the respective prober expects: a bare IP
+/// for ICMP and a URL for HTTPS.
+fn get_endpoints(target_group: TargetGroup, job: Job) -> BTreeSet<String> {
     target_group
         .targets
         .into_iter()
-        .map(|g| g.to_string())
+        .map(|g| match job._type {
+            JobType::Blackbox(ProbeKind::Icmp) => g.ip().to_string(),
+            JobType::Blackbox(ProbeKind::Https) => format!("https://{}{}", g, job.endpoint),
+            _ => g.to_string(),
+        })
         .collect()
 }
 
@@ -74,12 +85,18 @@ impl ConfigBuilder for PrometheusConfigBuilder {
             .map(|tg| PrometheusStaticConfig {
                 targets: get_endpoints(tg.clone(), job.clone()),
                 labels: {
-                    let mut labels = BTreeMap::new();
+                    let mut labels = tg.custom_labels.clone();
                     labels.insert(labels_keys::IC_NAME.into(), tg.ic_name);
                     labels.insert(labels_keys::IC_NODE.into(), tg.node_id.to_string());
                     if let Some(subnet_id) = tg.subnet_id {
                         labels.insert(labels_keys::IC_SUBNET.into(), subnet_id.to_string());
                     }
+                    if let Some(registry_version) = tg.registry_version {
+                        labels.insert(
+                            labels_keys::IC_REGISTRY_VERSION.into(),
+                            registry_version.to_string(),
+                        );
+                    }
                     labels.insert(labels_keys::JOB.into(), job._type.to_string());
                     labels
                 },
@@ -108,34 +125,26 @@ impl ConfigBuilder for PrometheusConfigBuilder {
 
 #[cfg(test)]
 mod prometheus_serialize {
-    use std::{collections::BTreeSet, net::SocketAddrV6, str::FromStr};
+    use std::collections::BTreeSet;
 
     use ic_types::{NodeId, PrincipalId, SubnetId};
     use serde_json::json;
-    use service_discovery::TargetGroup;
+    use service_discovery::{test_utils::target_group, TargetGroup};
 
     use crate::{jobs, prometheus_config::PrometheusConfigBuilder};
     use config_writer_common::config_builder::ConfigBuilder;
 
     use super::get_endpoints;
 
-    fn create_dummy_target_group(ipv6: &str, with_subnet_id: bool) -> TargetGroup {
-        let mut targets = BTreeSet::new();
-        targets.insert(std::net::SocketAddr::V6(
-            SocketAddrV6::from_str(ipv6).unwrap(),
-        ));
-        let subnet_id = match with_subnet_id {
-            true => Some(SubnetId::from(PrincipalId::new_anonymous())),
-            false => None,
-        };
-        TargetGroup {
-            node_id: NodeId::from(PrincipalId::new_anonymous()),
-            ic_name: "mercury".into(),
-            targets,
-            subnet_id,
-            dc_id: None,
-            operator_id: None,
+    fn anonymous_target_group(ipv6: &str, with_subnet_id: bool) -> TargetGroup {
+        let builder = target_group(0)
+            .node_id(NodeId::from(PrincipalId::new_anonymous()))
+            .target(ipv6);
+        match with_subnet_id {
+            true => builder.subnet_id(SubnetId::from(PrincipalId::new_anonymous())),
+            false => builder,
         }
+        .build()
     }
 
     #[test]
@@ -143,10 +152,10 @@ mod prometheus_serialize {
         let mut cb = Box::new(PrometheusConfigBuilder::new()) as Box<dyn ConfigBuilder>;
         let mut target_groups: BTreeSet<TargetGroup> = BTreeSet::new();
 
-        let tg1 = create_dummy_target_group("[2a02:800:2:2003:6801:f6ff:fec4:4c86]:9091", true);
+        let tg1 = anonymous_target_group("[2a02:800:2:2003:6801:f6ff:fec4:4c86]:9091", true);
         target_groups.insert(tg1.clone());
 
-        let tg2 = create_dummy_target_group("[2a02:800:2:2003:6801:f6ff:fec4:4c87]:9091", false);
+        let tg2 = anonymous_target_group("[2a02:800:2:2003:6801:f6ff:fec4:4c87]:9091", false);
         target_groups.insert(tg2.clone());
 
         let config = cb.build(target_groups, jobs::JOB_REPLICA);
@@ -184,7 +193,7 @@ mod prometheus_serialize {
         let mut cb = Box::new(PrometheusConfigBuilder::new()) as Box<dyn ConfigBuilder>;
         let mut target_groups: BTreeSet<TargetGroup> = BTreeSet::new();
 
-        let tg1 = create_dummy_target_group("[2a02:800:2:2003:6801:f6ff:fec4:4c86]:9091", true);
+        let tg1 = anonymous_target_group("[2a02:800:2:2003:6801:f6ff:fec4:4c86]:9091", true);
         target_groups.insert(tg1);
 
         let config = cb.build(target_groups.clone(), jobs::JOB_REPLICA);
@@ -193,7 +202,7 @@ mod prometheus_serialize {
         let config = cb.build(target_groups.clone(), jobs::JOB_REPLICA);
         assert!(!config.updated());
 
-        let tg2 = create_dummy_target_group("[2a02:800:2:2003:6801:f6ff:fec4:4c87]:9091", true);
+        let tg2 = anonymous_target_group("[2a02:800:2:2003:6801:f6ff:fec4:4c87]:9091", true);
         target_groups.insert(tg2);
 
         let config = cb.build(target_groups.clone(), jobs::JOB_REPLICA);
@@ -203,11 +212,31 @@ mod prometheus_serialize {
     #[test]
     fn test_get_endpoints() {
         let target_group =
-            create_dummy_target_group("[2a02:800:2:2003:6801:f6ff:fec4:4c87]:9091", true);
+            anonymous_target_group("[2a02:800:2:2003:6801:f6ff:fec4:4c87]:9091", true);
         let endpoints = get_endpoints(target_group, jobs::JOB_REPLICA);
         let mut expected_endpoints = BTreeSet::new();
         expected_endpoints.insert("[2a02:800:2:2003:6801:f6ff:fec4:4c87]:9091".to_string());
 
         assert_eq!(endpoints, expected_endpoints)
     }
+
+    #[test]
+    fn test_get_blackbox_endpoints() {
+        let target_group =
+            anonymous_target_group("[2a02:800:2:2003:6801:f6ff:fec4:4c87]:8080", true);
+
+        let endpoints = get_endpoints(target_group.clone(), jobs::JOB_BLACKBOX_ICMP);
+        assert_eq!(
+            endpoints,
+            BTreeSet::from(["2a02:800:2:2003:6801:f6ff:fec4:4c87".to_string()])
+        );
+
+        let endpoints = get_endpoints(target_group, jobs::JOB_BLACKBOX_HTTPS);
+        assert_eq!(
+            endpoints,
+            BTreeSet::from([
+                "https://[2a02:800:2:2003:6801:f6ff:fec4:4c87]:8080/api/v2/status".to_string()
+            ])
+        );
+    }
 }
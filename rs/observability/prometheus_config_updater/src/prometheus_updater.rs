@@ -1,4 +1,4 @@
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use serde::Serialize;
 
@@ -151,7 +151,7 @@ impl VectorTransform for VectorPrometheusScrapeTransform {
 
 impl VectorPrometheusScrapeTransform {
     fn from_target_group_with_job(tg: TargetGroup, job: &JobType) -> Self {
-        let mut labels: HashMap<String, String> = HashMap::new();
+        let mut labels: BTreeMap<String, String> = BTreeMap::new();
         labels.insert(IC_NAME.into(), tg.ic_name);
         labels.insert(IC_NODE.into(), tg.node_id.to_string());
         if let Some(subnet_id) = tg.subnet_id {
@@ -181,7 +181,6 @@ impl VectorPrometheusScrapeTransform {
 mod tests {
     use std::{
         collections::{BTreeSet, HashMap},
-        net::{SocketAddr, SocketAddrV6},
         str::FromStr,
     };
 
@@ -189,7 +188,7 @@ mod tests {
     use ic_types::{NodeId, PrincipalId, SubnetId};
     use service_discovery::{
         job_types::{JobType, NodeOS},
-        TargetGroup,
+        test_utils::target_group,
     };
 
     use crate::{Job, JobParameters};
@@ -200,30 +199,22 @@ mod tests {
     fn try_from_prometheus_target_group_to_vector_config_correct_inputs() {
         let original_addr = "[2a02:800:2:2003:5000:f6ff:fec4:4c86]:9091";
         let sources_key = String::from(original_addr) + "-source";
-        let mut targets = BTreeSet::new();
 
-        targets.insert(SocketAddr::V6(
-            SocketAddrV6::from_str(original_addr).unwrap(),
-        ));
-
-        let ptg = TargetGroup {
-            node_id: NodeId::from(
+        let ptg = target_group(0)
+            .node_id(NodeId::from(
                 PrincipalId::from_str(
                     "iylgr-zpxwq-kqgmf-4srtx-o4eey-d6bln-smmq6-we7px-ibdea-nondy-eae",
                 )
                 .unwrap(),
-            ),
-            ic_name: "mercury".into(),
-            targets,
-            subnet_id: Some(SubnetId::from(
+            ))
+            .target(original_addr)
+            .subnet_id(SubnetId::from(
                 PrincipalId::from_str(
                     "x33ed-h457x-bsgyx-oqxqf-6pzwv-wkhzr-rm2j3-npodi-purzm-n66cg-gae",
                 )
                 .unwrap(),
-            )),
-            dc_id: None,
-            operator_id: None,
-        };
+            ))
+            .build();
 
         let mut tg_set = BTreeSet::new();
         tg_set.insert(ptg);
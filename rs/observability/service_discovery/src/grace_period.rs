@@ -0,0 +1,162 @@
+//! Keeps targets that vanish from discovery around for a grace period, so that
+//! registry reads that flap do not cause churn in the generated configs.
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use ic_types::NodeId;
+
+use crate::{job_types::JobType, IcServiceDiscovery, IcServiceDiscoveryError, TargetGroup};
+
+/// The label attached to targets that vanished from discovery and are only
+/// kept until their grace period expires.
+pub const PENDING_REMOVAL_LABEL: &str = "pending_removal";
+
+/// Wraps an [IcServiceDiscovery]. Target groups returned by a previous call
+/// that are missing from the current one are still returned, labeled with
+/// [PENDING_REMOVAL_LABEL], until `grace_period` has elapsed since they
+/// vanished. If a target group reappears within the grace period, the label is
+/// dropped again.
+pub struct GracePeriodDiscovery {
+    discovery: Arc<dyn IcServiceDiscovery>,
+    grace_period: Duration,
+    /// The target groups seen per job, with the time they vanished at, if they
+    /// did.
+    known_targets: Mutex<HashMap<JobType, BTreeMap<NodeId, (TargetGroup, Option<Instant>)>>>,
+}
+
+impl GracePeriodDiscovery {
+    pub fn new(discovery: Arc<dyn IcServiceDiscovery>, grace_period: Duration) -> Self {
+        Self {
+            discovery,
+            grace_period,
+            known_targets: Default::default(),
+        }
+    }
+}
+
+impl IcServiceDiscovery for GracePeriodDiscovery {
+    fn get_target_groups(
+        &self,
+        job: JobType,
+    ) -> Result<BTreeSet<TargetGroup>, IcServiceDiscoveryError> {
+        let target_groups = self.discovery.get_target_groups(job)?;
+        let now = Instant::now();
+
+        let mut known_targets = self.known_targets.lock().unwrap();
+        let known_job_targets = known_targets.entry(job).or_default();
+        let mut current: BTreeMap<_, _> = target_groups
+            .into_iter()
+            .map(|tg| (tg.node_id, (tg, None)))
+            .collect();
+        for (node_id, (target_group, vanished_at)) in std::mem::take(known_job_targets) {
+            if current.contains_key(&node_id) {
+                continue;
+            }
+            let vanished_at = vanished_at.unwrap_or(now);
+            if now.duration_since(vanished_at) < self.grace_period {
+                current.insert(node_id, (target_group, Some(vanished_at)));
+            }
+        }
+        *known_job_targets = current;
+
+        Ok(known_job_targets
+            .values()
+            .map(|(target_group, vanished_at)| {
+                let mut target_group = target_group.clone();
+                if vanished_at.is_some() {
+                    target_group
+                        .custom_labels
+                        .insert(PENDING_REMOVAL_LABEL.into(), "true".into());
+                }
+                target_group
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeSet,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use super::{GracePeriodDiscovery, PENDING_REMOVAL_LABEL};
+    use crate::{
+        job_types::JobType, test_utils::target_group, IcServiceDiscovery, IcServiceDiscoveryError,
+        TargetGroup,
+    };
+
+    #[derive(Default)]
+    struct FakeDiscovery {
+        target_groups: Mutex<BTreeSet<TargetGroup>>,
+    }
+
+    impl IcServiceDiscovery for FakeDiscovery {
+        fn get_target_groups(
+            &self,
+            _job: JobType,
+        ) -> Result<BTreeSet<TargetGroup>, IcServiceDiscoveryError> {
+            Ok(self.target_groups.lock().unwrap().clone())
+        }
+    }
+
+    fn pending_removal(target_groups: &BTreeSet<TargetGroup>) -> Vec<bool> {
+        target_groups
+            .iter()
+            .map(|tg| tg.custom_labels.contains_key(PENDING_REMOVAL_LABEL))
+            .collect()
+    }
+
+    #[test]
+    fn vanished_targets_are_kept_during_grace_period() {
+        let inner = Arc::new(FakeDiscovery::default());
+        let discovery = GracePeriodDiscovery::new(inner.clone(), Duration::from_secs(3600));
+        let tg1 = target_group(1).build();
+        let tg2 = target_group(2).build();
+
+        *inner.target_groups.lock().unwrap() = BTreeSet::from([tg1.clone(), tg2.clone()]);
+        let target_groups = discovery.get_target_groups(JobType::Replica).unwrap();
+        assert_eq!(pending_removal(&target_groups), vec![false, false]);
+
+        *inner.target_groups.lock().unwrap() = BTreeSet::from([tg1.clone()]);
+        let target_groups = discovery.get_target_groups(JobType::Replica).unwrap();
+        assert_eq!(target_groups.len(), 2);
+        assert_eq!(
+            target_groups
+                .iter()
+                .find(|tg| tg.node_id == tg2.node_id)
+                .unwrap()
+                .custom_labels
+                .get(PENDING_REMOVAL_LABEL)
+                .unwrap(),
+            "true"
+        );
+
+        *inner.target_groups.lock().unwrap() = BTreeSet::from([tg1, tg2]);
+        let target_groups = discovery.get_target_groups(JobType::Replica).unwrap();
+        assert_eq!(pending_removal(&target_groups), vec![false, false]);
+    }
+
+    #[test]
+    fn vanished_targets_are_removed_after_grace_period() {
+        let inner = Arc::new(FakeDiscovery::default());
+        let discovery = GracePeriodDiscovery::new(inner.clone(), Duration::ZERO);
+
+        *inner.target_groups.lock().unwrap() = BTreeSet::from([target_group(1).build()]);
+        assert_eq!(
+            discovery.get_target_groups(JobType::Replica).unwrap().len(),
+            1
+        );
+
+        inner.target_groups.lock().unwrap().clear();
+        assert!(discovery
+            .get_target_groups(JobType::Replica)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,303 @@
+//! Expose the discovered target groups over gRPC, see the `ServiceDiscovery`
+//! service in `service_discovery_service`, for tools that consume the topology
+//! of an IC without syncing its registry themselves.
+//!
+//! `ListTargets` returns the target groups of a job, optionally narrowed down
+//! to a subnet or node operator. `WatchTargets` sends the same response, and
+//! then a new one whenever the target groups change. Changes are detected by
+//! re-reading the target groups every `watch_interval`.
+
+use anyhow::Result;
+use futures::Stream;
+use ic_types::PrincipalId;
+use service_discovery_service::{
+    service_discovery_server::{ServiceDiscovery, ServiceDiscoveryServer},
+    ListTargetsRequest, ListTargetsResponse, TargetGroup as pbTargetGroup,
+};
+use slog::{info, warn};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tonic::{transport::Server, Request, Response, Status};
+
+use super::{IcServiceDiscovery, TargetGroup};
+use crate::job_types::JobType;
+
+pub async fn start_grpc_server<F>(
+    log: slog::Logger,
+    scraper: Arc<dyn IcServiceDiscovery>,
+    socket_addr: SocketAddr,
+    watch_interval: Duration,
+    shutdown_signal: F,
+) -> Result<()>
+where
+    F: Future<Output = ()>,
+{
+    info!(log, "Serving target groups over gRPC on {}", socket_addr);
+    Server::builder()
+        .add_service(ServiceDiscoveryServer::new(GrpcApi {
+            log,
+            scraper,
+            watch_interval,
+        }))
+        .serve_with_shutdown(socket_addr, shutdown_signal)
+        .await?;
+    Ok(())
+}
+
+/// The target groups selected by a [ListTargetsRequest].
+#[derive(Clone, Debug)]
+struct TargetFilter {
+    job: JobType,
+    subnet_id: Option<PrincipalId>,
+    operator_id: Option<PrincipalId>,
+}
+
+impl TryFrom<ListTargetsRequest> for TargetFilter {
+    type Error = Status;
+
+    fn try_from(request: ListTargetsRequest) -> Result<Self, Self::Error> {
+        fn principal(field: &str, value: &str) -> Result<Option<PrincipalId>, Status> {
+            if value.is_empty() {
+                return Ok(None);
+            }
+            PrincipalId::from_str(value)
+                .map(Some)
+                .map_err(|e| Status::invalid_argument(format!("invalid {}: {}", field, e)))
+        }
+
+        Ok(Self {
+            job: JobType::from_str(&request.job)
+                .map_err(|e| Status::invalid_argument(e.to_string()))?,
+            subnet_id: principal("subnet_id", &request.subnet_id)?,
+            operator_id: principal("operator_id", &request.operator_id)?,
+        })
+    }
+}
+
+impl TargetFilter {
+    fn matches(&self, target_group: &TargetGroup) -> bool {
+        self.subnet_id.map_or(true, |subnet_id| {
+            target_group.subnet_id.map(|s| s.get()) == Some(subnet_id)
+        }) && self.operator_id.map_or(true, |operator_id| {
+            target_group.operator_id == Some(operator_id)
+        })
+    }
+}
+
+fn list_targets(
+    scraper: &dyn IcServiceDiscovery,
+    filter: &TargetFilter,
+) -> Result<ListTargetsResponse, Status> {
+    let target_groups = scraper
+        .get_target_groups(filter.job)
+        .map_err(|e| Status::unavailable(e.to_string()))?;
+    Ok(ListTargetsResponse {
+        target_groups: target_groups
+            .into_iter()
+            .filter(|tg| filter.matches(tg))
+            .map(pbTargetGroup::from)
+            .collect(),
+    })
+}
+
+impl From<TargetGroup> for pbTargetGroup {
+    fn from(tg: TargetGroup) -> Self {
+        Self {
+            node_id: tg.node_id.to_string(),
+            ic_name: tg.ic_name,
+            targets: tg.targets.iter().map(|addr| addr.to_string()).collect(),
+            subnet_id: tg.subnet_id.map(|s| s.to_string()).unwrap_or_default(),
+            dc_id: tg.dc_id.unwrap_or_default(),
+            operator_id: tg.operator_id.map(|o| o.to_string()).unwrap_or_default(),
+            registry_version: tg.registry_version.map(|v| v.get()).unwrap_or_default(),
+            custom_labels: tg.custom_labels.into_iter().collect(),
+        }
+    }
+}
+
+struct GrpcApi {
+    log: slog::Logger,
+    scraper: Arc<dyn IcServiceDiscovery>,
+    watch_interval: Duration,
+}
+
+type WatchTargetsStream = Pin<Box<dyn Stream<Item = Result<ListTargetsResponse, Status>> + Send>>;
+
+/// Sends the current response, followed by every change of the response. The
+/// stream ends after the first error.
+fn watch_targets(
+    scraper: Arc<dyn IcServiceDiscovery>,
+    filter: TargetFilter,
+    watch_interval: Duration,
+) -> WatchTargetsStream {
+    // The state is the last response sent, or `None` once the stream ended.
+    let initial_state: Option<Option<ListTargetsResponse>> = Some(None);
+    Box::pin(futures::stream::unfold(initial_state, move |state| {
+        let scraper = scraper.clone();
+        let filter = filter.clone();
+        async move {
+            let last = state?;
+            loop {
+                if last.is_some() {
+                    tokio::time::sleep(watch_interval).await;
+                }
+                match list_targets(scraper.as_ref(), &filter) {
+                    Ok(response) if last.as_ref() == Some(&response) => continue,
+                    Ok(response) => return Some((Ok(response.clone()), Some(Some(response)))),
+                    Err(status) => return Some((Err(status), None)),
+                }
+            }
+        }
+    }))
+}
+
+#[tonic::async_trait]
+impl ServiceDiscovery for GrpcApi {
+    async fn list_targets(
+        &self,
+        request: Request<ListTargetsRequest>,
+    ) -> Result<Response<ListTargetsResponse>, Status> {
+        let filter = TargetFilter::try_from(request.into_inner())?;
+        list_targets(self.scraper.as_ref(), &filter)
+            .map(Response::new)
+            .map_err(|status| {
+                warn!(self.log, "Error when listing targets: {:?}", status);
+                status
+            })
+    }
+
+    type WatchTargetsStream = WatchTargetsStream;
+
+    async fn watch_targets(
+        &self,
+        request: Request<ListTargetsRequest>,
+    ) -> Result<Response<Self::WatchTargetsStream>, Status> {
+        let filter = TargetFilter::try_from(request.into_inner())?;
+        info!(self.log, "Watching targets: {:?}", filter);
+        Ok(Response::new(watch_targets(
+            self.scraper.clone(),
+            filter,
+            self.watch_interval,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::sync::Mutex;
+
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::{test_utils, IcServiceDiscoveryError};
+
+    struct FakeDiscovery(Mutex<BTreeSet<TargetGroup>>);
+
+    impl IcServiceDiscovery for FakeDiscovery {
+        fn get_target_groups(
+            &self,
+            _job: JobType,
+        ) -> Result<BTreeSet<TargetGroup>, IcServiceDiscoveryError> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+    }
+
+    fn target_group(node: u64, subnet: u64, operator: u64) -> TargetGroup {
+        test_utils::target_group(node)
+            .target("[::1]:9090")
+            .subnet(subnet)
+            .operator(operator)
+            .build()
+    }
+
+    fn request(job: &str, subnet: Option<u64>, operator: Option<u64>) -> ListTargetsRequest {
+        ListTargetsRequest {
+            job: job.into(),
+            subnet_id: subnet
+                .map(|s| PrincipalId::new_subnet_test_id(s).to_string())
+                .unwrap_or_default(),
+            operator_id: operator
+                .map(|o| PrincipalId::new_user_test_id(o).to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn node_ids(response: &ListTargetsResponse) -> Vec<String> {
+        response
+            .target_groups
+            .iter()
+            .map(|tg| tg.node_id.clone())
+            .collect()
+    }
+
+    #[test]
+    fn targets_are_filtered_by_subnet_and_operator() {
+        let scraper = FakeDiscovery(Mutex::new(BTreeSet::from([
+            target_group(1, 1, 1),
+            target_group(2, 1, 2),
+            target_group(3, 2, 2),
+        ])));
+        let list = |request| list_targets(&scraper, &TargetFilter::try_from(request).unwrap());
+        let node = |node| PrincipalId::new_node_test_id(node).to_string();
+
+        assert_eq!(
+            list(request("replica", None, None))
+                .unwrap()
+                .target_groups
+                .len(),
+            3
+        );
+        assert_eq!(
+            node_ids(&list(request("replica", Some(1), None)).unwrap()),
+            vec![node(1), node(2)]
+        );
+        assert_eq!(
+            node_ids(&list(request("replica", Some(1), Some(2))).unwrap()),
+            vec![node(2)]
+        );
+
+        let response = list(request("replica", Some(2), None)).unwrap();
+        assert_eq!(response.target_groups[0].targets, vec!["[::1]:9090"]);
+        assert_eq!(response.target_groups[0].dc_id, "");
+
+        for invalid in [
+            request("unknown", None, None),
+            ListTargetsRequest {
+                subnet_id: "not a principal".into(),
+                ..request("replica", None, None)
+            },
+        ] {
+            assert_eq!(
+                TargetFilter::try_from(invalid).unwrap_err().code(),
+                tonic::Code::InvalidArgument
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn watch_sends_changes_only() {
+        let scraper = Arc::new(FakeDiscovery(Mutex::new(BTreeSet::from([target_group(
+            1, 1, 1,
+        )]))));
+        let filter = TargetFilter::try_from(request("replica", None, None)).unwrap();
+        let mut stream = watch_targets(scraper.clone(), filter, Duration::from_millis(10));
+
+        let first = stream.next().await.unwrap().unwrap();
+        assert_eq!(first.target_groups.len(), 1);
+
+        // Nothing is sent until the target groups change.
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), stream.next())
+                .await
+                .is_err()
+        );
+        scraper.0.lock().unwrap().insert(target_group(2, 1, 1));
+        let second = stream.next().await.unwrap().unwrap();
+        assert_eq!(second.target_groups.len(), 2);
+    }
+}
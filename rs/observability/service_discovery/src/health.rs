@@ -0,0 +1,229 @@
+//! Liveness and readiness reporting for the generators.
+//!
+//! The poll loop and the config writers record their last successful
+//! iteration in a shared [HealthStatus]. It is exposed on a `/healthz`
+//! endpoint and, when running as a systemd service with `Type=notify`, used to
+//! send `READY=1` after the first successful write and `WATCHDOG=1` for as long
+//! as the generator is healthy.
+use std::{
+    convert::Infallible,
+    future::Future,
+    net::SocketAddr,
+    os::unix::net::UnixDatagram,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use crossbeam::select;
+use crossbeam_channel::Receiver;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response,
+};
+use serde::Serialize;
+use slog::{info, warn};
+
+/// Before the service is ready, readiness is checked at this interval.
+const READINESS_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, Default)]
+struct HealthTimes {
+    last_discovery: Option<SystemTime>,
+    last_write: Option<SystemTime>,
+}
+
+/// The times of the last successful discovery and config write. A generator
+/// is considered healthy if both happened within `max_age`.
+#[derive(Clone, Debug)]
+pub struct HealthStatus {
+    times: Arc<RwLock<HealthTimes>>,
+    max_age: Duration,
+}
+
+#[derive(Debug, Serialize)]
+struct HealthReport {
+    healthy: bool,
+    /// Unix timestamps in seconds.
+    last_successful_discovery: Option<u64>,
+    last_successful_write: Option<u64>,
+}
+
+impl HealthStatus {
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            times: Default::default(),
+            max_age,
+        }
+    }
+
+    pub fn discovery_succeeded(&self) {
+        self.times.write().unwrap().last_discovery = Some(SystemTime::now());
+    }
+
+    pub fn write_succeeded(&self) {
+        self.times.write().unwrap().last_write = Some(SystemTime::now());
+    }
+
+    /// Returns true once the first config has been written.
+    pub fn is_ready(&self) -> bool {
+        self.times.read().unwrap().last_write.is_some()
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        let times = *self.times.read().unwrap();
+        let recent = |time: Option<SystemTime>| {
+            time.and_then(|t| t.elapsed().ok())
+                .map_or(false, |age| age <= self.max_age)
+        };
+        recent(times.last_discovery) && recent(times.last_write)
+    }
+
+    fn report(&self) -> HealthReport {
+        let times = *self.times.read().unwrap();
+        let unix_secs = |time: Option<SystemTime>| {
+            time.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+        };
+        HealthReport {
+            healthy: self.is_healthy(),
+            last_successful_discovery: unix_secs(times.last_discovery),
+            last_successful_write: unix_secs(times.last_write),
+        }
+    }
+}
+
+/// Serves the [HealthStatus] as JSON on `/healthz`. The status code is 200 if
+/// the generator is healthy and 503 otherwise.
+pub async fn start_health_server<F>(
+    health: HealthStatus,
+    socket_addr: SocketAddr,
+    shutdown_signal: F,
+) -> Result<()>
+where
+    F: Future<Output = ()>,
+{
+    let make_service = make_service_fn(move |_| {
+        let health = health.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                let health = health.clone();
+                async move { health_response(&health, req) }
+            }))
+        }
+    });
+    hyper::Server::bind(&socket_addr)
+        .serve(make_service)
+        .with_graceful_shutdown(shutdown_signal)
+        .await?;
+    Ok(())
+}
+
+fn health_response(
+    health: &HealthStatus,
+    req: Request<Body>,
+) -> Result<Response<Body>, hyper::http::Error> {
+    if req.uri().path() != "/healthz" {
+        return Response::builder()
+            .status(404)
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .body(format!("path not found: {}", req.uri().path()).into());
+    }
+    let report = health.report();
+    Response::builder()
+        .status(if report.healthy { 200 } else { 503 })
+        .header("Content-Type", "application/json; charset=utf-8")
+        .body(serde_json::to_vec(&report).unwrap().into())
+}
+
+/// Returns a loop that notifies systemd about the state of the generator, or
+/// `None` if the process is not run by systemd with `NOTIFY_SOCKET` set.
+///
+/// `READY=1` is sent as soon as the first config has been written. If the unit
+/// sets `WatchdogSec`, `WATCHDOG=1` is sent at half the watchdog interval as
+/// long as the generator is healthy, such that systemd restarts a wedged
+/// generator.
+pub fn make_systemd_notify_loop(
+    log: slog::Logger,
+    health: HealthStatus,
+    stop_signal: Receiver<()>,
+) -> Option<impl FnMut()> {
+    let socket_path = PathBuf::from(std::env::var_os("NOTIFY_SOCKET")?);
+    let watchdog_interval = std::env::var("WATCHDOG_USEC")
+        .ok()
+        .and_then(|usec| usec.parse::<u64>().ok())
+        .map(|usec| Duration::from_micros(usec) / 2);
+    Some(move || {
+        let mut ready = false;
+        loop {
+            if !ready && health.is_ready() {
+                info!(log, "Notifying systemd that the service is ready");
+                ready = notify(&log, &socket_path, "READY=1");
+            }
+            if ready && watchdog_interval.is_some() {
+                if health.is_healthy() {
+                    notify(&log, &socket_path, "WATCHDOG=1");
+                } else {
+                    warn!(log, "Unhealthy, skipping watchdog notification");
+                }
+            }
+            let tick = match (ready, watchdog_interval) {
+                (false, _) => crossbeam::channel::after(READINESS_CHECK_INTERVAL),
+                (true, Some(interval)) => crossbeam::channel::after(interval),
+                // nothing left to do but waiting for the shutdown
+                (true, None) => crossbeam::channel::never(),
+            };
+            select! {
+                recv(stop_signal) -> _ => {
+                    info!(log, "Received shutdown signal in systemd_notify_loop");
+                    return
+                },
+                recv(tick) -> _ => {}
+            };
+        }
+    })
+}
+
+fn notify(log: &slog::Logger, socket_path: &Path, state: &str) -> bool {
+    let result =
+        UnixDatagram::unbound().and_then(|socket| socket.send_to(state.as_bytes(), socket_path));
+    if let Err(e) = &result {
+        warn!(log, "Failed to notify systemd ({}): {:?}", state, e);
+    }
+    result.is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::HealthStatus;
+
+    #[test]
+    fn healthy_after_recent_discovery_and_write() {
+        let health = HealthStatus::new(Duration::from_secs(60));
+        assert!(!health.is_ready());
+        assert!(!health.is_healthy());
+
+        health.discovery_succeeded();
+        assert!(!health.is_healthy());
+
+        health.write_succeeded();
+        assert!(health.is_ready());
+        assert!(health.is_healthy());
+        assert!(health.report().last_successful_write.is_some());
+    }
+
+    #[test]
+    fn unhealthy_if_too_old() {
+        let health = HealthStatus::new(Duration::ZERO);
+        health.discovery_succeeded();
+        health.write_succeeded();
+        std::thread::sleep(Duration::from_millis(10));
+
+        assert!(health.is_ready());
+        assert!(!health.is_healthy());
+        assert!(!health.report().healthy);
+    }
+}
@@ -6,11 +6,21 @@ pub enum NodeOS {
     Host,
 }
 
+/// The blackbox exporter module used to probe a target.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum ProbeKind {
+    /// ICMP echo to the node's IPv6 address.
+    Icmp,
+    /// HTTPS request to the replica's public endpoint.
+    Https,
+}
+
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum JobType {
     Replica,
     NodeExporter(NodeOS),
     Orchestrator,
+    Blackbox(ProbeKind),
 }
 
 #[derive(Debug)]
@@ -34,6 +44,8 @@ impl FromStr for JobType {
             "node_exporter" => Ok(JobType::NodeExporter(NodeOS::Guest)),
             "host_node_exporter" => Ok(JobType::NodeExporter(NodeOS::Host)),
             "orchestrator" => Ok(JobType::Orchestrator),
+            "blackbox_icmp" => Ok(JobType::Blackbox(ProbeKind::Icmp)),
+            "blackbox_https" => Ok(JobType::Blackbox(ProbeKind::Https)),
             _ => Err(JobTypeParseError {
                 input: s.to_string(),
             }),
@@ -48,6 +60,8 @@ impl fmt::Display for JobType {
             JobType::NodeExporter(NodeOS::Guest) => write!(f, "node_exporter"),
             JobType::NodeExporter(NodeOS::Host) => write!(f, "host_node_exporter"),
             JobType::Orchestrator => write!(f, "orchestrator"),
+            JobType::Blackbox(ProbeKind::Icmp) => write!(f, "blackbox_icmp"),
+            JobType::Blackbox(ProbeKind::Https) => write!(f, "blackbox_https"),
         }
     }
 }
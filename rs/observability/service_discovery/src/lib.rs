@@ -36,20 +36,27 @@ use ic_types::{
     },
     NodeId, PrincipalId, RegistryVersion, SubnetId,
 };
-use job_types::{JobType, NodeOS};
+use job_types::{JobType, NodeOS, ProbeKind};
 use serde::Serialize;
 use slog::{warn, Logger};
 use thiserror::Error;
 
 pub mod file_sd;
+pub mod grace_period;
+pub mod grpc_api;
+pub mod health;
 pub mod job_types;
 pub mod jobs;
+pub mod logger;
 pub mod mainnet_registry;
 pub mod metrics;
 pub mod poll_loop;
 pub mod registry_sync;
 pub mod rest_api;
 pub mod service_discovery_record;
+pub mod static_targets;
+pub mod target_filter;
+pub mod test_utils;
 
 /// Provide service discovery for a set of Internet Computers.
 pub trait IcServiceDiscovery: Send + Sync {
@@ -64,9 +71,16 @@ pub trait IcServiceDiscovery: Send + Sync {
     ) -> Result<BTreeSet<TargetGroup>, IcServiceDiscoveryError>;
 }
 
+/// The label under which the registry version of a [TargetGroup] is exported.
+pub const IC_REGISTRY_VERSION: &str = "ic_registry_version";
+
 /// A [TargetGroup] associates a set of scrape targets with
 /// a set of labels.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+///
+/// The registry version is not considered when comparing target groups, such
+/// that a new registry version that does not change any target does not
+/// cause the generated configuration to be rewritten.
+#[derive(Debug, Clone, Serialize)]
 pub struct TargetGroup {
     pub node_id: NodeId,
     pub ic_name: String,
@@ -78,6 +92,56 @@ pub struct TargetGroup {
 
     pub dc_id: Option<String>,
     pub operator_id: Option<PrincipalId>,
+    /// The registry version the target group was derived from.
+    pub registry_version: Option<RegistryVersion>,
+    /// Additional labels attached to the targets, e.g. the ones given for
+    /// static targets. Labels derived from the registry take precedence.
+    pub custom_labels: BTreeMap<String, String>,
+}
+
+impl TargetGroup {
+    #[allow(clippy::type_complexity)]
+    fn cmp_key(
+        &self,
+    ) -> (
+        &NodeId,
+        &String,
+        &BTreeSet<SocketAddr>,
+        &Option<SubnetId>,
+        &Option<String>,
+        &Option<PrincipalId>,
+        &BTreeMap<String, String>,
+    ) {
+        (
+            &self.node_id,
+            &self.ic_name,
+            &self.targets,
+            &self.subnet_id,
+            &self.dc_id,
+            &self.operator_id,
+            &self.custom_labels,
+        )
+    }
+}
+
+impl PartialEq for TargetGroup {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp_key() == other.cmp_key()
+    }
+}
+
+impl Eq for TargetGroup {}
+
+impl PartialOrd for TargetGroup {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TargetGroup {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.cmp_key().cmp(&other.cmp_key())
+    }
 }
 
 /// Exposes service discovery data for a set of Internet Computers. Manages a
@@ -95,6 +159,9 @@ pub struct IcServiceDiscoveryImpl {
     registries: Arc<RwLock<BTreeMap<String, LocalRegistry>>>,
 
     jobs: HashMap<JobType, u16>,
+    /// The ICs whose targets win if the same targets are listed by several
+    /// ICs, in order of precedence. See [merge_target_groups].
+    ic_precedence: Vec<String>,
 }
 
 impl IcServiceDiscoveryImpl {
@@ -119,30 +186,75 @@ impl IcServiceDiscoveryImpl {
             registry_query_timeout,
             registries,
             jobs,
+            ic_precedence: vec![],
         };
         self_.load_new_ics(log)?;
         Ok(self_)
     }
 
+    /// Sets the precedence of ICs for targets listed by more than one IC.
+    pub fn with_ic_precedence(mut self, ic_precedence: Vec<String>) -> Self {
+        self.ic_precedence = ic_precedence;
+        self
+    }
+
+    /// Returns the number of target groups that are dropped because their
+    /// targets are also listed by an IC with higher precedence.
+    pub fn target_conflicts(&self) -> Result<usize, IcServiceDiscoveryError> {
+        let registries_lock_guard = self.registries.read().unwrap();
+        Ok(self.get_all_targets(&registries_lock_guard)?.1)
+    }
+
+    /// Returns the deduplicated target groups of all registries, together with
+    /// the number of conflicting target groups that were dropped.
+    fn get_all_targets(
+        &self,
+        registries: &BTreeMap<String, LocalRegistry>,
+    ) -> Result<(BTreeSet<TargetGroup>, usize), IcServiceDiscoveryError> {
+        let target_groups_per_ic = registries
+            .iter()
+            .map(|(ic_name, registry)| Ok((ic_name.clone(), Self::get_targets(registry, ic_name)?)))
+            .collect::<Result<BTreeMap<_, _>, IcServiceDiscoveryError>>()?;
+        Ok(merge_target_groups(
+            &self.ic_precedence,
+            target_groups_per_ic,
+        ))
+    }
+
     /// Update each scraping target by fetching update for the respective
     /// registry.
     ///
-    /// If all updates succeed, returns `Ok(())`. Otherwise an error is returned
-    /// containing all failed update attempts.
+    /// The registries are updated concurrently, such that a slow or failing
+    /// registry does not delay the updates of the other ones. If all updates
+    /// succeed, returns `Ok(())`. Otherwise an error is returned containing all
+    /// failed update attempts.
     pub async fn update_registries(&self) -> Result<(), IcServiceDiscoveryError> {
         let cache = self.registries.read().unwrap();
-        let mut failures = vec![];
-        for (ic_name, registry) in cache.iter() {
-            if let Err(e) = registry.sync_with_nns().await {
-                failures.push((ic_name.to_string(), e));
-            }
-        }
+        let results =
+            futures::future::join_all(cache.iter().map(|(ic_name, registry)| async move {
+                (ic_name, registry.sync_with_nns().await)
+            }))
+            .await;
+        let failures: Vec<_> = results
+            .into_iter()
+            .filter_map(|(ic_name, result)| result.err().map(|e| (ic_name.to_string(), e)))
+            .collect();
         if !failures.is_empty() {
             return Err(IcServiceDiscoveryError::SyncWithNnsFailed { failures });
         }
         Ok(())
     }
 
+    /// Returns the latest registry version known for each observed Internet
+    /// Computer.
+    pub fn get_latest_versions(&self) -> BTreeMap<String, RegistryVersion> {
+        let registries_lock_guard = self.registries.read().unwrap();
+        registries_lock_guard
+            .iter()
+            .map(|(ic_name, registry)| (ic_name.clone(), registry.get_latest_version()))
+            .collect()
+    }
+
     /// Synchronizes the in-memory cache with the state on disk.
     ///
     /// # Known Limitations
@@ -266,6 +378,8 @@ impl IcServiceDiscoveryImpl {
             ic_name: ic_name.into(),
             dc_id: Some(node_operator.dc_id),
             operator_id: Some(operator_id),
+            registry_version: Some(latest_version),
+            custom_labels: Default::default(),
         });
 
         Ok(())
@@ -355,20 +469,17 @@ impl IcServiceDiscovery for IcServiceDiscoveryImpl {
         }
 
         let registries_lock_guard = self.registries.read().unwrap();
-        let target_list = registries_lock_guard.iter().try_fold(
-            BTreeSet::new(),
-            |mut a, (ic_name, registry)| {
-                a.append(&mut Self::get_targets(registry, ic_name)?);
-                Ok::<_, IcServiceDiscoveryError>(a)
-            },
-        )?;
+        let (target_list, _conflicts) = self.get_all_targets(&registries_lock_guard)?;
 
         Ok(target_list
             .into_iter()
             .filter_map(|target_group| {
-                // replica targets are only exposed if they are assigned to a
-                // subnet (i.e. if the subnet id is set)
-                if job != JobType::Replica || target_group.subnet_id.is_some() {
+                // replica targets (and probes of the replica's public
+                // endpoint) are only exposed if they are assigned to a subnet
+                // (i.e. if the subnet id is set)
+                let requires_subnet =
+                    matches!(job, JobType::Replica | JobType::Blackbox(ProbeKind::Https));
+                if !requires_subnet || target_group.subnet_id.is_some() {
                     let targets: BTreeSet<_> = target_group
                         .targets
                         .into_iter()
@@ -387,6 +498,40 @@ impl IcServiceDiscovery for IcServiceDiscoveryImpl {
     }
 }
 
+/// Merges the target groups of several ICs. If the same set of targets is
+/// listed by more than one IC, e.g. during a testnet recovery, only the target
+/// group of the IC with the highest precedence is kept: ICs listed in
+/// `ic_precedence` come first, in the given order, followed by all other ICs
+/// in alphabetical order. Returns the merged target groups and the number of
+/// target groups dropped.
+fn merge_target_groups(
+    ic_precedence: &[String],
+    target_groups_per_ic: BTreeMap<String, BTreeSet<TargetGroup>>,
+) -> (BTreeSet<TargetGroup>, usize) {
+    let rank = |ic_name: &String| {
+        ic_precedence
+            .iter()
+            .position(|p| p == ic_name)
+            .unwrap_or(usize::MAX)
+    };
+    let mut target_groups_per_ic: Vec<_> = target_groups_per_ic.into_iter().collect();
+    target_groups_per_ic.sort_by(|(a, _), (b, _)| rank(a).cmp(&rank(b)).then_with(|| a.cmp(b)));
+
+    let mut seen_targets = BTreeSet::new();
+    let mut merged = BTreeSet::new();
+    let mut conflicts = 0;
+    for (_, target_groups) in target_groups_per_ic {
+        for target_group in target_groups {
+            if seen_targets.insert(target_group.targets.clone()) {
+                merged.insert(target_group);
+            } else {
+                conflicts += 1;
+            }
+        }
+    }
+    (merged, conflicts)
+}
+
 fn set_port(port: u16) -> Box<dyn Fn(SocketAddr) -> SocketAddr> {
     Box::new(move |mut sockaddr: SocketAddr| {
         sockaddr.set_port(port);
@@ -494,6 +639,9 @@ pub enum IcServiceDiscoveryError {
 
     #[error("couldn't find from {information} from local registry")]
     NotFoundInRegistry { information: String },
+
+    #[error("failed to load static targets from {path:?}: {reason}")]
+    StaticTargetsFile { path: PathBuf, reason: String },
 }
 
 #[cfg(test)]
@@ -505,9 +653,60 @@ mod tests {
 
     use super::*;
     use crate::mainnet_registry::{create_local_store_from_changelog, get_mainnet_delta_6d_c1};
+    use crate::test_utils;
     use itertools::Itertools; // for the function [unique_by]
 
     const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn duplicate_targets_are_merged_by_precedence() {
+        let target_group = |ic_name: &str, node: u64| {
+            test_utils::target_group(node)
+                .ic_name(ic_name)
+                .targets([SocketAddr::from(([2, 0, 0, 0, 0, 0, 0, node as u16], 9090))])
+                .build()
+        };
+        let target_groups_per_ic: BTreeMap<_, _> = vec![
+            ("mainnet".to_string(), vec![target_group("mainnet", 1)]),
+            (
+                "recovery".to_string(),
+                vec![target_group("recovery", 1), target_group("recovery", 2)],
+            ),
+        ]
+        .into_iter()
+        .map(|(ic_name, target_groups)| (ic_name, target_groups.into_iter().collect()))
+        .collect();
+
+        let (merged, conflicts) = merge_target_groups(&[], target_groups_per_ic.clone());
+        assert_eq!(conflicts, 1);
+        assert_eq!(
+            merged
+                .iter()
+                .map(|tg| tg.ic_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["mainnet", "recovery"]
+        );
+
+        let (merged, conflicts) =
+            merge_target_groups(&["recovery".to_string()], target_groups_per_ic);
+        assert_eq!(conflicts, 1);
+        assert!(merged.iter().all(|tg| tg.ic_name == "recovery"));
+        assert_eq!(merged.len(), 2);
+    }
+
+    #[test]
+    fn registry_version_is_ignored_when_comparing_target_groups() {
+        let target_group = |registry_version: u64| {
+            test_utils::target_group(1)
+                .ic_name("mainnet")
+                .targets([])
+                .registry_version(registry_version)
+                .build()
+        };
+        assert_eq!(target_group(1), target_group(2));
+        assert_eq!(BTreeSet::from([target_group(1), target_group(2)]).len(), 1);
+    }
+
     #[test]
     fn can_get_nns_targets_for() {
         let mainnet_prefix = "tdb26";
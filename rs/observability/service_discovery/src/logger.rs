@@ -0,0 +1,80 @@
+//! The logger shared by the generator binaries.
+use std::{fmt, str::FromStr};
+
+use slog::{o, Drain, Logger};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human readable output for terminals.
+    Text,
+    /// One JSON object per line with the stable keys `ts`, `level` and `msg`
+    /// plus the key-value pairs of the record.
+    Json,
+}
+
+#[derive(Debug)]
+pub struct LogFormatParseError {
+    input: String,
+}
+impl std::error::Error for LogFormatParseError {}
+
+impl fmt::Display for LogFormatParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Could not parse {} into a log format", self.input)
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = LogFormatParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(LogFormatParseError {
+                input: s.to_string(),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LogFormat::Text => write!(f, "text"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+pub fn make_logger(format: LogFormat) -> Logger {
+    let drain: Box<dyn Drain<Ok = (), Err = slog::Never> + Send> = match format {
+        LogFormat::Text => {
+            let decorator = slog_term::TermDecorator::new().build();
+            Box::new(slog_term::FullFormat::new(decorator).build().fuse())
+        }
+        LogFormat::Json => Box::new(
+            slog_json::Json::new(std::io::stderr())
+                .add_default_keys()
+                .build()
+                .fuse(),
+        ),
+    };
+    let drain = slog_async::Async::new(drain).chan_size(8192).build();
+    slog::Logger::root(drain.fuse(), o!())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::LogFormat;
+
+    #[test]
+    fn parse_log_format() {
+        assert_eq!(LogFormat::from_str("text").unwrap(), LogFormat::Text);
+        assert_eq!(LogFormat::from_str("json").unwrap(), LogFormat::Json);
+        assert!(LogFormat::from_str("yaml").is_err());
+        assert_eq!(LogFormat::Json.to_string(), "json");
+    }
+}
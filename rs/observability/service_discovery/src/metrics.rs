@@ -2,7 +2,7 @@ use ic_metrics::{
     buckets::{add_bucket, decimal_buckets},
     MetricsRegistry,
 };
-use prometheus::{Histogram, IntCounterVec, IntGaugeVec};
+use prometheus::{Histogram, IntCounterVec, IntGauge, IntGaugeVec};
 
 #[derive(Clone)]
 pub struct Metrics {
@@ -16,11 +16,32 @@ pub struct Metrics {
     pub registries_update_latency_seconds: Histogram,
     /// Total targets
     pub total_targets: IntGaugeVec,
+    /// Targets that disappeared from discovery and whose generated config was
+    /// removed.
+    pub removed_targets: IntCounterVec,
+    /// Generations that left the config of a job unchanged.
+    pub unchanged_configs: IntCounterVec,
+    /// Discovered targets that did not accept connections when last probed.
+    pub unreachable_targets: IntGaugeVec,
+    /// Target groups dropped because the same targets are listed by an IC with
+    /// higher precedence.
+    pub target_conflicts: IntGauge,
+    /// Age in seconds of the local registry snapshot used on startup, i.e. the
+    /// time elapsed since the certified time of its latest version.
+    pub registry_staleness_seconds: IntGauge,
+    /// Unix timestamp of the last successful registry sync, per IC.
+    pub last_successful_sync_timestamp_seconds: IntGaugeVec,
+    /// The latest registry version in the local store, per IC.
+    pub registry_version: IntGaugeVec,
+    /// 1 while the poll loop backs off because syncing the registries keeps
+    /// failing, 0 otherwise.
+    pub circuit_breaker_open: IntGauge,
 }
 
 pub const ERROR_TYPE: &str = "error_type";
 pub const POLL_STATUS: &str = "poll_status";
 pub const JOB_TYPE: &str = "job_type";
+pub const IC_NAME: &str = "ic";
 
 impl Metrics {
     pub fn new(metrics_registry: MetricsRegistry) -> Self {
@@ -46,6 +67,43 @@ impl Metrics {
                 "total targets found by service discovery",
                 &[JOB_TYPE],
             ),
+            removed_targets: metrics_registry.int_counter_vec(
+                "discovery_removed_targets",
+                "Total number of targets that disappeared from service discovery.",
+                &[JOB_TYPE],
+            ),
+            unchanged_configs: metrics_registry.int_counter_vec(
+                "discovery_unchanged_configs",
+                "Total number of generations that did not change the config of a job.",
+                &[JOB_TYPE],
+            ),
+            unreachable_targets: metrics_registry.int_gauge_vec(
+                "discovery_unreachable_targets",
+                "Number of discovered targets that did not accept connections when last probed.",
+                &[JOB_TYPE],
+            ),
+            target_conflicts: metrics_registry.int_gauge(
+                "discovery_target_conflicts",
+                "Number of targets listed by more than one IC.",
+            ),
+            registry_staleness_seconds: metrics_registry.int_gauge(
+                "discovery_registry_staleness_seconds",
+                "Age of the local registry snapshot when syncing with the NNS is skipped.",
+            ),
+            last_successful_sync_timestamp_seconds: metrics_registry.int_gauge_vec(
+                "discovery_last_successful_sync_timestamp_seconds",
+                "Unix timestamp of the last successful sync of the registry with the NNS.",
+                &[IC_NAME],
+            ),
+            registry_version: metrics_registry.int_gauge_vec(
+                "discovery_registry_version",
+                "The latest registry version in the local store.",
+                &[IC_NAME],
+            ),
+            circuit_breaker_open: metrics_registry.int_gauge(
+                "discovery_circuit_breaker_open",
+                "Whether the poll loop backs off because syncing the registries keeps failing.",
+            ),
         }
     }
 }
@@ -1,25 +1,159 @@
 use std::{
+    collections::BTreeSet,
+    path::PathBuf,
     sync::Arc,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use crate::{metrics::Metrics, IcServiceDiscoveryImpl};
+use crate::{
+    health::HealthStatus, metrics::Metrics, IcServiceDiscoveryError, IcServiceDiscoveryImpl,
+};
 use crossbeam::select;
-use crossbeam_channel::{Receiver, Sender};
-use slog::{info, warn};
+use crossbeam_channel::{Receiver, Sender, TrySendError};
+use notify::{watcher, DebouncedEvent, RecursiveMode, Watcher};
+use rand::Rng;
+use slog::{debug, info, warn};
+use tokio::signal::unix::{signal, SignalKind};
+
+/// The time the poll loop waits between two iterations.
+///
+/// The wait starts at `min` and doubles with every iteration that does not
+/// observe a new registry version, until it reaches `max`. As soon as any of
+/// the registries advances, the wait is reset to `min`.
+#[derive(Clone, Copy, Debug)]
+pub struct PollInterval {
+    min: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl PollInterval {
+    pub fn new(min: Duration, max: Duration) -> Self {
+        let max = std::cmp::max(min, max);
+        Self {
+            min,
+            max,
+            current: min,
+        }
+    }
+
+    /// A poll interval that never backs off.
+    pub fn fixed(interval: Duration) -> Self {
+        Self::new(interval, interval)
+    }
+
+    /// Returns the time to wait before the next iteration.
+    fn next(&mut self, registries_advanced: bool) -> Duration {
+        let wait = if registries_advanced {
+            self.min
+        } else {
+            self.current
+        };
+        self.current = std::cmp::min(wait.saturating_mul(2), self.max);
+        wait
+    }
+}
 
+/// The number of consecutive failed registry syncs after which the poll loop
+/// backs off.
+const FAILURE_THRESHOLD: u32 = 3;
+/// The longest the poll loop backs off after failed registry syncs.
+const MAX_FAILURE_BACKOFF: Duration = Duration::from_secs(15 * 60);
+
+/// Tracks consecutive failures to sync the registries with the NNS.
+///
+/// Once `threshold` syncs in a row have failed, the circuit is open: the poll
+/// loop waits `base * 2^n` before the next attempt, where `n` is the number of
+/// failures beyond the threshold, capped at `max_backoff` and with up to 20%
+/// of random jitter, so that several generators do not hammer a struggling
+/// NNS in lockstep. A successful sync closes the circuit again.
+#[derive(Clone, Copy, Debug)]
+struct CircuitBreaker {
+    threshold: u32,
+    max_backoff: Duration,
+    consecutive_failures: u32,
+}
+
+impl CircuitBreaker {
+    fn new(threshold: u32, max_backoff: Duration) -> Self {
+        Self {
+            threshold,
+            max_backoff,
+            consecutive_failures: 0,
+        }
+    }
+
+    /// Returns the number of consecutive failures, including this one.
+    fn record_failure(&mut self) -> u32 {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        self.consecutive_failures
+    }
+
+    /// Returns the number of consecutive failures that preceded this success,
+    /// if any.
+    fn record_success(&mut self) -> Option<u32> {
+        let failures = std::mem::take(&mut self.consecutive_failures);
+        (failures > 0).then_some(failures)
+    }
+
+    fn is_open(&self) -> bool {
+        self.consecutive_failures >= self.threshold
+    }
+
+    /// Returns the minimal time to wait before the next attempt.
+    fn backoff(&self, base: Duration) -> Duration {
+        if !self.is_open() {
+            return Duration::ZERO;
+        }
+        let exponent = std::cmp::min(self.consecutive_failures - self.threshold, 16);
+        let backoff = std::cmp::min(base.saturating_mul(1 << exponent), self.max_backoff);
+        backoff.mul_f64(1.0 + rand::thread_rng().gen_range(0.0..0.2))
+    }
+}
+
+/// Condenses a sync error into a single line naming the failed ICs.
+fn summarize_error(e: &IcServiceDiscoveryError) -> String {
+    match e {
+        IcServiceDiscoveryError::SyncWithNnsFailed { failures } => format!(
+            "failed ICs: [{}]",
+            failures
+                .iter()
+                .map(|(ic_name, _)| ic_name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        e => e.to_string(),
+    }
+}
+
+/// Returns the poll loop. After every iteration, each of the
+/// `update_notifiers` is signaled, so that any number of config writers can
+/// share the same discovery. Iterations without errors are recorded in
+/// `health`.
+///
+/// A message on `poll_now` starts the next iteration right away instead of
+/// waiting for the poll interval to elapse.
+///
+/// While syncing the registries keeps failing, the loop backs off and only
+/// logs a summary of the errors, see [CircuitBreaker].
 pub fn make_poll_loop(
     log: slog::Logger,
     rt: tokio::runtime::Handle,
     ic_discovery: Arc<IcServiceDiscoveryImpl>,
     stop_signal: Receiver<()>,
-    poll_interval: Duration,
+    mut poll_now: Receiver<()>,
+    mut poll_interval: PollInterval,
     metrics: Metrics,
-    update_notifier: Option<Sender<()>>,
+    update_notifiers: Vec<Sender<()>>,
+    health: HealthStatus,
 ) -> impl FnMut() {
-    let interval = crossbeam::channel::tick(poll_interval);
     move || {
+        let mut circuit_breaker = CircuitBreaker::new(FAILURE_THRESHOLD, MAX_FAILURE_BACKOFF);
         let mut tick = Instant::now();
+        let mut last_versions = ic_discovery.get_latest_versions();
+        // The registry versions for which the target conflicts were last
+        // computed. The targets only change with the registry versions.
+        let mut conflicts_versions = None;
         loop {
             let mut err = false;
             info!(log, "Loading new scraping targets (tick: {:?})", tick);
@@ -36,32 +170,265 @@ pub fn make_poll_loop(
             }
             info!(log, "Update registries");
             let timer = metrics.registries_update_latency_seconds.start_timer();
-            if let Err(e) = rt.block_on(ic_discovery.update_registries()) {
-                warn!(
-                    log,
-                    "Failed to sync registry @ interval {:?}: {:?}", tick, e
-                );
+            let sync_result = rt.block_on(ic_discovery.update_registries());
+            if let Err(e) = &sync_result {
+                let failures = circuit_breaker.record_failure();
+                if !circuit_breaker.is_open() {
+                    warn!(
+                        log,
+                        "Failed to sync registry @ interval {:?}: {:?}", tick, e
+                    );
+                } else if failures == circuit_breaker.threshold {
+                    warn!(
+                        log,
+                        "Failed to sync registry {} times in a row, backing off: {}",
+                        failures,
+                        summarize_error(e)
+                    );
+                } else {
+                    debug!(
+                        log,
+                        "Failed to sync registry @ interval {:?}: {:?}", tick, e
+                    );
+                }
                 metrics
                     .poll_error_count
                     .with_label_values(&["update_registries"])
                     .inc();
                 err = true;
+            } else if let Some(failures) = circuit_breaker.record_success() {
+                info!(
+                    log,
+                    "Synced registry again after {} consecutive failures", failures
+                );
+            }
+            metrics
+                .circuit_breaker_open
+                .set(circuit_breaker.is_open() as i64);
+            record_synced_versions(&metrics, &ic_discovery, &sync_result);
+            let versions = ic_discovery.get_latest_versions();
+            if conflicts_versions.as_ref() != Some(&versions) {
+                match ic_discovery.target_conflicts() {
+                    Ok(conflicts) => {
+                        metrics.target_conflicts.set(conflicts as i64);
+                        conflicts_versions = Some(versions.clone());
+                    }
+                    Err(e) => warn!(log, "Failed to check for conflicting targets: {:?}", e),
+                }
             }
-            if let Some(sender) = &update_notifier {
+            for sender in &update_notifiers {
                 if let Err(e) = sender.send(()) {
                     warn!(log, "Failed to send update signal : {:?}", e);
                 }
             }
             std::mem::drop(timer);
+            if !err {
+                health.discovery_succeeded();
+            }
             let poll_status = if err { "error" } else { "successful" };
             metrics.poll_count.with_label_values(&[poll_status]).inc();
-            tick = select! {
+
+            let wait = std::cmp::max(
+                poll_interval.next(versions != last_versions),
+                circuit_breaker.backoff(poll_interval.min),
+            );
+            last_versions = versions;
+            debug!(log, "Next poll in {:?}", wait);
+
+            select! {
                 recv(stop_signal) -> _ => {
                     info!(log, "Received shutdown signal in poll_loop");
                     return
                 },
-                recv(interval) -> msg => msg.expect("tick failed!")
+                recv(crossbeam::channel::after(wait)) -> msg => {
+                    msg.expect("tick failed!");
+                    tick = Instant::now();
+                },
+                recv(poll_now) -> msg => {
+                    if msg.is_err() {
+                        // nobody can request a poll anymore
+                        poll_now = crossbeam::channel::never();
+                        continue;
+                    }
+                    info!(log, "Polling on request");
+                    tick = Instant::now();
+                }
             };
         }
     }
 }
+
+/// Exports the latest registry version of every IC and, for the ICs whose
+/// registry was synced successfully, the time of the sync. Alerting on the
+/// latter detects an IC whose discovery is stuck while the others progress.
+fn record_synced_versions(
+    metrics: &Metrics,
+    ic_discovery: &IcServiceDiscoveryImpl,
+    sync_result: &Result<(), IcServiceDiscoveryError>,
+) {
+    let failed_ics: BTreeSet<&str> = match sync_result {
+        Ok(()) => BTreeSet::new(),
+        Err(IcServiceDiscoveryError::SyncWithNnsFailed { failures }) => failures
+            .iter()
+            .map(|(ic_name, _)| ic_name.as_str())
+            .collect(),
+        // it is unknown which registries were synced
+        Err(_) => return,
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+    for (ic_name, version) in ic_discovery.get_latest_versions() {
+        metrics
+            .registry_version
+            .with_label_values(&[&ic_name])
+            .set(version.get() as i64);
+        if !failed_ics.contains(ic_name.as_str()) {
+            metrics
+                .last_successful_sync_timestamp_seconds
+                .with_label_values(&[&ic_name])
+                .set(now);
+        }
+    }
+}
+
+/// Signals `poll_now` whenever the process receives SIGHUP, so that operators
+/// can force an immediate registry poll and config regeneration. Requests
+/// arriving while one is still pending are coalesced, hence `poll_now` should
+/// have a capacity of one.
+pub async fn poll_on_sighup(log: slog::Logger, poll_now: Sender<()>) {
+    let mut sighup = match signal(SignalKind::hangup()) {
+        Ok(sighup) => sighup,
+        Err(e) => {
+            warn!(log, "Failed to install SIGHUP handler: {:?}", e);
+            return;
+        }
+    };
+    while sighup.recv().await.is_some() {
+        info!(log, "Received SIGHUP, requesting an immediate poll");
+        if let Err(TrySendError::Disconnected(_)) = poll_now.try_send(()) {
+            return;
+        }
+    }
+}
+
+/// Events in the targets directory within this delay are reported at once.
+const TARGETS_DIR_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Returns a loop that signals `poll_now` whenever an entry is created in or
+/// moved into `targets_dir`, such that the local store of a new IC is loaded
+/// and synced right away instead of at the next poll. A local store that is
+/// still being written when the poll starts is skipped and loaded by a later
+/// poll, hence local stores should be moved into `targets_dir` once complete.
+pub fn poll_on_new_ics(
+    log: slog::Logger,
+    targets_dir: PathBuf,
+    poll_now: Sender<()>,
+) -> impl FnOnce() {
+    move || {
+        let (events_sender, events) = std::sync::mpsc::channel();
+        let mut watcher = match watcher(events_sender, TARGETS_DIR_DEBOUNCE) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                warn!(log, "Failed to create a watcher for new ICs: {:?}", e);
+                return;
+            }
+        };
+        if let Err(e) = watcher.watch(&targets_dir, RecursiveMode::NonRecursive) {
+            warn!(
+                log,
+                "Failed to watch {:?} for new ICs: {:?}", targets_dir, e
+            );
+            return;
+        }
+        for event in events {
+            let path = match event {
+                DebouncedEvent::Create(path) | DebouncedEvent::Rename(_, path) => path,
+                DebouncedEvent::Error(e, path) => {
+                    warn!(log, "Error watching {:?}: {:?}", path, e);
+                    continue;
+                }
+                _ => continue,
+            };
+            info!(log, "{:?} was added, requesting an immediate poll", path);
+            if let Err(TrySendError::Disconnected(_)) = poll_now.try_send(()) {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use slog::o;
+
+    use super::{poll_on_new_ics, CircuitBreaker, PollInterval};
+
+    #[test]
+    fn poll_interval_backs_off_and_resets() {
+        let mut interval = PollInterval::new(Duration::from_secs(10), Duration::from_secs(60));
+
+        assert_eq!(interval.next(false), Duration::from_secs(10));
+        assert_eq!(interval.next(false), Duration::from_secs(20));
+        assert_eq!(interval.next(false), Duration::from_secs(40));
+        assert_eq!(interval.next(false), Duration::from_secs(60));
+        assert_eq!(interval.next(false), Duration::from_secs(60));
+
+        assert_eq!(interval.next(true), Duration::from_secs(10));
+        assert_eq!(interval.next(false), Duration::from_secs(20));
+    }
+
+    #[test]
+    fn fixed_poll_interval_never_changes() {
+        let mut interval = PollInterval::fixed(Duration::from_secs(10));
+
+        assert_eq!(interval.next(false), Duration::from_secs(10));
+        assert_eq!(interval.next(false), Duration::from_secs(10));
+        assert_eq!(interval.next(true), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn circuit_breaker_opens_after_threshold() {
+        let base = Duration::from_secs(10);
+        let mut breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        assert_eq!(breaker.record_success(), None);
+
+        assert_eq!(breaker.record_failure(), 1);
+        assert!(!breaker.is_open());
+        assert_eq!(breaker.backoff(base), Duration::ZERO);
+
+        assert_eq!(breaker.record_failure(), 2);
+        assert!(breaker.is_open());
+        let backoff = breaker.backoff(base);
+        assert!(backoff >= base && backoff <= base.mul_f64(1.2));
+
+        for _ in 0..10 {
+            breaker.record_failure();
+        }
+        let backoff = breaker.backoff(base);
+        assert!(backoff >= Duration::from_secs(60) && backoff <= Duration::from_secs(72));
+
+        assert_eq!(breaker.record_success(), Some(12));
+        assert!(!breaker.is_open());
+    }
+
+    #[test]
+    fn new_ics_trigger_a_poll() {
+        let targets_dir = tempfile::tempdir().unwrap();
+        let (poll_now_sender, poll_now) = crossbeam::channel::bounded::<()>(1);
+        std::thread::spawn(poll_on_new_ics(
+            slog::Logger::root(slog::Discard, o!()),
+            targets_dir.path().to_path_buf(),
+            poll_now_sender,
+        ));
+        // Give the watcher time to start.
+        std::thread::sleep(Duration::from_secs(1));
+
+        std::fs::create_dir(targets_dir.path().join("mercury")).unwrap();
+
+        assert!(poll_now.recv_timeout(Duration::from_secs(30)).is_ok());
+    }
+}
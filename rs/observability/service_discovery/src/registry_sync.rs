@@ -3,10 +3,12 @@ use std::{
     ops::Add,
     path::{Path, PathBuf},
     sync::Arc,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
-use ic_interfaces_registry::{RegistryClient, RegistryValue, ZERO_REGISTRY_VERSION};
+use ic_interfaces_registry::{
+    LocalStoreCertifiedTimeReader, RegistryClient, RegistryValue, ZERO_REGISTRY_VERSION,
+};
 use ic_protobuf::registry::crypto::v1::PublicKey;
 use ic_registry_client::client::ThresholdSigPublicKey;
 use ic_registry_client_fake::FakeRegistryClient;
@@ -18,17 +20,28 @@ use ic_registry_local_store::{
     Changelog, ChangelogEntry, KeyMutation, LocalStoreImpl, LocalStoreWriter,
 };
 use ic_registry_nns_data_provider::registry::RegistryCanister;
-use ic_types::{PrincipalId, RegistryVersion, SubnetId};
+use ic_types::{time::current_time, PrincipalId, RegistryVersion, SubnetId};
 use registry_canister::mutations::common::decode_registry_value;
-use slog::{error, info, Logger};
+use slog::{error, info, warn, Logger};
 use url::Url;
 
+use crate::metrics::Metrics;
+
+/// Syncs the local store at `local_path` with the registry canister reachable
+/// at `nns_url`.
+///
+/// If `use_current_version` is set and the local store is not empty, syncing
+/// is skipped. In that case the age of the local snapshot is logged and
+/// exported through `metrics`; if it exceeds `max_registry_age`, an error is
+/// returned instead of generating configs from an outdated topology.
 pub async fn sync_local_registry(
     log: Logger,
     local_path: PathBuf,
     nns_url: Url,
     use_current_version: bool,
-) {
+    max_registry_age: Option<Duration>,
+    metrics: &Metrics,
+) -> anyhow::Result<()> {
     let start = Instant::now();
     let local_store = Arc::new(LocalStoreImpl::new(local_path.clone()));
     let registry_canister = RegistryCanister::new(vec![nns_url]);
@@ -47,7 +60,15 @@ pub async fn sync_local_registry(
 
     if use_current_version && latest_version != ZERO_REGISTRY_VERSION {
         info!(log, "Skipping syncing with registry, using local version");
-        return;
+        let age = local_registry_age(local_store.as_ref());
+        metrics
+            .registry_staleness_seconds
+            .set(age.as_secs().try_into().unwrap_or(i64::MAX));
+        warn!(
+            log,
+            "Local registry at version {} was certified {:?} ago", latest_version, age
+        );
+        return ensure_registry_age_within(age, max_registry_age);
     } else if use_current_version {
         info!(
             log,
@@ -155,7 +176,38 @@ pub async fn sync_local_registry(
         log,
         "Synced all registry versions in : {:?}",
         start.elapsed()
-    )
+    );
+    Ok(())
+}
+
+/// Returns the time elapsed since the certified time of the latest version in
+/// `local_store`. A local store without a certified time is considered to be
+/// as old as the UNIX epoch.
+fn local_registry_age(local_store: &dyn LocalStoreCertifiedTimeReader) -> Duration {
+    let certified_time = Duration::from_nanos(
+        local_store
+            .read_certified_time()
+            .as_nanos_since_unix_epoch(),
+    );
+    let now = Duration::from_nanos(current_time().as_nanos_since_unix_epoch());
+    now.saturating_sub(certified_time)
+}
+
+/// Returns an error if `age` exceeds `max_registry_age`, if any.
+fn ensure_registry_age_within(
+    age: Duration,
+    max_registry_age: Option<Duration>,
+) -> anyhow::Result<()> {
+    if let Some(max_age) = max_registry_age {
+        if age > max_age {
+            anyhow::bail!(
+                "Local registry is older than the allowed maximum age ({:?} > {:?})",
+                age,
+                max_age
+            );
+        }
+    }
+    Ok(())
 }
 
 async fn nns_public_key(
@@ -183,3 +235,56 @@ async fn nns_public_key(
     )
     .expect("failed to create thresholdsig public key"))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ic_interfaces_registry::LocalStoreCertifiedTimeReader;
+    use ic_types::time::{current_time, Time, UNIX_EPOCH};
+
+    use super::{ensure_registry_age_within, local_registry_age};
+
+    const MAX_REGISTRY_AGE: Duration = Duration::from_secs(3600);
+
+    struct FakeCertifiedTimeReader(Time);
+
+    impl LocalStoreCertifiedTimeReader for FakeCertifiedTimeReader {
+        fn read_certified_time(&self) -> Time {
+            self.0
+        }
+    }
+
+    fn certified_ago(age: Duration) -> FakeCertifiedTimeReader {
+        FakeCertifiedTimeReader(Time::from_nanos_since_unix_epoch(
+            current_time().as_nanos_since_unix_epoch() - age.as_nanos() as u64,
+        ))
+    }
+
+    #[test]
+    fn registry_below_max_age_is_accepted() {
+        let age = local_registry_age(&certified_ago(Duration::from_secs(60)));
+
+        assert!(age >= Duration::from_secs(60) && age < MAX_REGISTRY_AGE);
+        assert!(ensure_registry_age_within(age, Some(MAX_REGISTRY_AGE)).is_ok());
+    }
+
+    #[test]
+    fn registry_above_max_age_is_rejected() {
+        let age = local_registry_age(&certified_ago(2 * MAX_REGISTRY_AGE));
+
+        assert!(age >= 2 * MAX_REGISTRY_AGE);
+        assert!(ensure_registry_age_within(age, Some(MAX_REGISTRY_AGE)).is_err());
+        assert!(ensure_registry_age_within(age, None).is_ok());
+    }
+
+    #[test]
+    fn registry_without_certified_time_is_as_old_as_the_epoch() {
+        let since_epoch = Duration::from_nanos(current_time().as_nanos_since_unix_epoch());
+
+        let age = local_registry_age(&FakeCertifiedTimeReader(UNIX_EPOCH));
+
+        assert!(age >= since_epoch);
+        assert!(ensure_registry_age_within(age, Some(MAX_REGISTRY_AGE)).is_err());
+    }
+}
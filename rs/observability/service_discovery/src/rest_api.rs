@@ -115,9 +115,19 @@ impl Service<Request<Body>> for RestApi {
             s if !s.is_empty() && s[1..].bytes().all(is_ident) => {
                 // strip leading `/`
                 let job_name = &s[1..];
-                let job = JobType::from_str(job_name).unwrap();
-                let targets = self.scraper.get_target_groups(job);
-                self.target_groups_to_response(targets)
+                match JobType::from_str(job_name) {
+                    Ok(job) => {
+                        let targets = self.scraper.get_target_groups(job);
+                        self.target_groups_to_response(targets)
+                    }
+                    Err(e) => {
+                        warn!(self.log, "Unknown job requested: {:?}", job_name);
+                        Response::builder()
+                            .status(404)
+                            .header("Content-Type", "text/plain; charset=utf-8")
+                            .body(e.to_string().into())
+                    }
+                }
             }
             path => {
                 warn!(self.log, "Path not found: {:?}", path);
@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 
-use super::TargetGroup;
+use super::{TargetGroup, IC_REGISTRY_VERSION};
 
 /// Record of the shape as described in
 /// https://prometheus.io/docs/prometheus/latest/http_sd/
@@ -15,13 +15,16 @@ pub struct ServiceDiscoveryRecord {
 impl From<TargetGroup> for ServiceDiscoveryRecord {
     fn from(group: TargetGroup) -> Self {
         let targets: Vec<_> = group.targets.into_iter().map(|x| x.to_string()).collect();
-        let mut labels = BTreeMap::new();
+        let mut labels = group.custom_labels;
 
         labels.insert(IC_NAME.into(), group.ic_name);
         labels.insert(IC_NODE.into(), group.node_id.to_string());
         if let Some(subnet_id) = group.subnet_id {
             labels.insert(IC_SUBNET.into(), subnet_id.to_string());
         }
+        if let Some(registry_version) = group.registry_version {
+            labels.insert(IC_REGISTRY_VERSION.into(), registry_version.to_string());
+        }
         Self { targets, labels }
     }
 }
@@ -0,0 +1,190 @@
+//! Targets that are not part of any registry, e.g. standalone gateways or
+//! auxiliary services, loaded from a JSON file of the form:
+//!
+//! ```json
+//! [
+//!   {
+//!     "job": "node_exporter",
+//!     "targets": ["[2a00:fb01:400:100:5000:5bff:fe6b:75c6]:9100"],
+//!     "labels": { "env": "staging" }
+//!   }
+//! ]
+//! ```
+//!
+//! The static targets are merged into the targets discovered from the
+//! registries.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::SocketAddr,
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+};
+
+use ic_types::{NodeId, PrincipalId};
+use serde::Deserialize;
+
+use crate::{job_types::JobType, IcServiceDiscovery, IcServiceDiscoveryError, TargetGroup};
+
+/// The name used as `ic_name` for static targets unless the entry specifies
+/// one.
+pub const STATIC_IC_NAME: &str = "static";
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct StaticTarget {
+    pub job: String,
+    pub targets: BTreeSet<SocketAddr>,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub ic_name: Option<String>,
+}
+
+impl StaticTarget {
+    /// Static targets have no node id. To allow config builders to key their
+    /// output by node id, a stable id is derived from the target addresses.
+    fn node_id(&self) -> NodeId {
+        let addresses = self
+            .targets
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        NodeId::from(PrincipalId::new_self_authenticating(addresses.as_bytes()))
+    }
+
+    fn into_target_group(self) -> TargetGroup {
+        TargetGroup {
+            node_id: self.node_id(),
+            ic_name: self.ic_name.unwrap_or_else(|| STATIC_IC_NAME.into()),
+            targets: self.targets,
+            subnet_id: None,
+            dc_id: None,
+            operator_id: None,
+            registry_version: None,
+            custom_labels: self.labels,
+        }
+    }
+}
+
+/// Wraps an [IcServiceDiscovery] and adds the static targets listed in
+/// `static_targets_file`. The file is read on every call, so that changes are
+/// picked up without restarting.
+pub struct StaticTargetsDiscovery {
+    discovery: Arc<dyn IcServiceDiscovery>,
+    static_targets_file: PathBuf,
+}
+
+impl StaticTargetsDiscovery {
+    pub fn new(discovery: Arc<dyn IcServiceDiscovery>, static_targets_file: PathBuf) -> Self {
+        Self {
+            discovery,
+            static_targets_file,
+        }
+    }
+
+    fn load_static_targets(&self) -> Result<Vec<StaticTarget>, IcServiceDiscoveryError> {
+        let error = |reason: String| IcServiceDiscoveryError::StaticTargetsFile {
+            path: self.static_targets_file.clone(),
+            reason,
+        };
+        let content = std::fs::read(&self.static_targets_file).map_err(|e| error(e.to_string()))?;
+        let static_targets: Vec<StaticTarget> =
+            serde_json::from_slice(&content).map_err(|e| error(e.to_string()))?;
+        for static_target in &static_targets {
+            JobType::from_str(&static_target.job).map_err(|e| error(e.to_string()))?;
+        }
+        Ok(static_targets)
+    }
+}
+
+impl IcServiceDiscovery for StaticTargetsDiscovery {
+    fn get_target_groups(
+        &self,
+        job: JobType,
+    ) -> Result<BTreeSet<TargetGroup>, IcServiceDiscoveryError> {
+        let mut target_groups = self.discovery.get_target_groups(job)?;
+        target_groups.extend(
+            self.load_static_targets()?
+                .into_iter()
+                .filter(|static_target| static_target.job == job.to_string())
+                .map(StaticTarget::into_target_group),
+        );
+        Ok(target_groups)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, io::Write, str::FromStr, sync::Arc};
+
+    use tempfile::NamedTempFile;
+
+    use super::{StaticTargetsDiscovery, STATIC_IC_NAME};
+    use crate::{job_types::JobType, IcServiceDiscovery, IcServiceDiscoveryError, TargetGroup};
+
+    struct NoTargets;
+
+    impl IcServiceDiscovery for NoTargets {
+        fn get_target_groups(
+            &self,
+            _job: JobType,
+        ) -> Result<BTreeSet<TargetGroup>, IcServiceDiscoveryError> {
+            Ok(BTreeSet::new())
+        }
+    }
+
+    fn discovery_with_file(content: &str) -> (StaticTargetsDiscovery, NamedTempFile) {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        (
+            StaticTargetsDiscovery::new(Arc::new(NoTargets), file.path().to_path_buf()),
+            file,
+        )
+    }
+
+    #[test]
+    fn static_targets_are_merged_per_job() {
+        let (discovery, _file) = discovery_with_file(
+            r#"[
+                {
+                    "job": "node_exporter",
+                    "targets": ["[2a00:fb01:400:100:5000:5bff:fe6b:75c6]:9100"],
+                    "labels": { "env": "staging" }
+                },
+                {
+                    "job": "replica",
+                    "targets": ["[2a00:fb01:400:100:5000:5bff:fe6b:75c6]:9090"],
+                    "ic_name": "gateways"
+                }
+            ]"#,
+        );
+
+        let node_exporter = discovery
+            .get_target_groups(JobType::from_str("node_exporter").unwrap())
+            .unwrap();
+        assert_eq!(node_exporter.len(), 1);
+        let target_group = node_exporter.into_iter().next().unwrap();
+        assert_eq!(target_group.ic_name, STATIC_IC_NAME);
+        assert_eq!(target_group.custom_labels.get("env").unwrap(), "staging");
+
+        let replica = discovery.get_target_groups(JobType::Replica).unwrap();
+        assert_eq!(replica.into_iter().next().unwrap().ic_name, "gateways");
+
+        assert!(discovery
+            .get_target_groups(JobType::Orchestrator)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn unknown_job_is_rejected() {
+        let (discovery, _file) =
+            discovery_with_file(r#"[{ "job": "unknown", "targets": ["[::1]:9100"] }]"#);
+
+        assert!(matches!(
+            discovery.get_target_groups(JobType::Replica),
+            Err(IcServiceDiscoveryError::StaticTargetsFile { .. })
+        ));
+    }
+}
@@ -0,0 +1,417 @@
+//! Predicates selecting the [TargetGroup]s the generators write configs for.
+//!
+//! Filters are combined with [TargetFilter::and], [TargetFilter::or] and
+//! [TargetFilter::not], e.g.
+//!
+//! ```ignore
+//! let filter = by_subnet(vec![subnet_id])
+//!     .or(by_node(vec![node_id]))
+//!     .and(by_label("env", vec!["testnet".into()]).not());
+//! ```
+//!
+//! On the command line, filters are given as [TargetAttributeFilter]s and
+//! combined by [command_line_filter].
+use std::{
+    fmt::{self, Debug},
+    str::FromStr,
+    sync::Arc,
+};
+
+use ic_types::{NodeId, PrincipalId, SubnetId};
+use regex::Regex;
+
+use crate::TargetGroup;
+
+pub trait TargetFilter: Send + Sync + Debug {
+    /// Returns true if `target_group` is to be kept.
+    fn matches(&self, target_group: &TargetGroup) -> bool;
+
+    /// A counter that changes whenever the results of [TargetFilter::matches]
+    /// may have changed for the same target groups, e.g. because the filter
+    /// was reloaded. Constant for filters that never change.
+    fn generation(&self) -> u64 {
+        0
+    }
+
+    fn and<F: TargetFilter>(self, other: F) -> And<Self, F>
+    where
+        Self: Sized,
+    {
+        And(self, other)
+    }
+
+    fn or<F: TargetFilter>(self, other: F) -> Or<Self, F>
+    where
+        Self: Sized,
+    {
+        Or(self, other)
+    }
+
+    fn not(self) -> Not<Self>
+    where
+        Self: Sized,
+    {
+        Not(self)
+    }
+}
+
+impl<F: TargetFilter + ?Sized> TargetFilter for Box<F> {
+    fn matches(&self, target_group: &TargetGroup) -> bool {
+        (**self).matches(target_group)
+    }
+
+    fn generation(&self) -> u64 {
+        (**self).generation()
+    }
+}
+
+impl<F: TargetFilter + ?Sized> TargetFilter for Arc<F> {
+    fn matches(&self, target_group: &TargetGroup) -> bool {
+        (**self).matches(target_group)
+    }
+
+    fn generation(&self) -> u64 {
+        (**self).generation()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct And<A, B>(pub A, pub B);
+
+impl<A: TargetFilter, B: TargetFilter> TargetFilter for And<A, B> {
+    fn matches(&self, target_group: &TargetGroup) -> bool {
+        self.0.matches(target_group) && self.1.matches(target_group)
+    }
+
+    fn generation(&self) -> u64 {
+        self.0.generation().wrapping_add(self.1.generation())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Or<A, B>(pub A, pub B);
+
+impl<A: TargetFilter, B: TargetFilter> TargetFilter for Or<A, B> {
+    fn matches(&self, target_group: &TargetGroup) -> bool {
+        self.0.matches(target_group) || self.1.matches(target_group)
+    }
+
+    fn generation(&self) -> u64 {
+        self.0.generation().wrapping_add(self.1.generation())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Not<A>(pub A);
+
+impl<A: TargetFilter> TargetFilter for Not<A> {
+    fn matches(&self, target_group: &TargetGroup) -> bool {
+        !self.0.matches(target_group)
+    }
+
+    fn generation(&self) -> u64 {
+        self.0.generation()
+    }
+}
+
+/// Matches if all of the filters match, in particular if there are none.
+#[derive(Debug, Default)]
+pub struct All(pub Vec<Box<dyn TargetFilter>>);
+
+impl TargetFilter for All {
+    fn matches(&self, target_group: &TargetGroup) -> bool {
+        self.0.iter().all(|f| f.matches(target_group))
+    }
+
+    fn generation(&self) -> u64 {
+        self.0
+            .iter()
+            .fold(0, |sum, f| sum.wrapping_add(f.generation()))
+    }
+}
+
+/// Matches if any of the filters matches, i.e. never if there are none.
+#[derive(Debug, Default)]
+pub struct Any(pub Vec<Box<dyn TargetFilter>>);
+
+impl TargetFilter for Any {
+    fn matches(&self, target_group: &TargetGroup) -> bool {
+        self.0.iter().any(|f| f.matches(target_group))
+    }
+
+    fn generation(&self) -> u64 {
+        self.0
+            .iter()
+            .fold(0, |sum, f| sum.wrapping_add(f.generation()))
+    }
+}
+
+/// Matches the target groups whose node ID matches the regex.
+#[derive(Clone, Debug)]
+pub struct NodeIdRegex(pub Regex);
+
+impl TargetFilter for NodeIdRegex {
+    fn matches(&self, target_group: &TargetGroup) -> bool {
+        self.0.is_match(&target_group.node_id.to_string())
+    }
+}
+
+/// A filter on one of the attributes of a [TargetGroup], as given on the
+/// command line in the form `<key>=<value>[,<value>...]`. The supported keys
+/// are `node_id`, `subnet_id`, `operator_id`, `dc_id` and `label.<name>` for
+/// the custom label `<name>`. A target group is accepted if its attribute
+/// matches any of the listed values. With `<key>!=<value>...`, target groups
+/// matching any of the values are rejected instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TargetAttributeFilter {
+    NodeId(Vec<NodeId>),
+    SubnetId(Vec<SubnetId>),
+    OperatorId(Vec<PrincipalId>),
+    DcId(Vec<String>),
+    Label(String, Vec<String>),
+    Not(Box<TargetAttributeFilter>),
+}
+
+pub fn by_node(ids: Vec<NodeId>) -> TargetAttributeFilter {
+    TargetAttributeFilter::NodeId(ids)
+}
+
+pub fn by_subnet(ids: Vec<SubnetId>) -> TargetAttributeFilter {
+    TargetAttributeFilter::SubnetId(ids)
+}
+
+pub fn by_operator(ids: Vec<PrincipalId>) -> TargetAttributeFilter {
+    TargetAttributeFilter::OperatorId(ids)
+}
+
+pub fn by_dc(ids: Vec<String>) -> TargetAttributeFilter {
+    TargetAttributeFilter::DcId(ids)
+}
+
+pub fn by_label(key: impl Into<String>, values: Vec<String>) -> TargetAttributeFilter {
+    TargetAttributeFilter::Label(key.into(), values)
+}
+
+#[derive(Debug)]
+pub struct TargetAttributeFilterParseError {
+    input: String,
+    reason: String,
+}
+impl std::error::Error for TargetAttributeFilterParseError {}
+
+impl fmt::Display for TargetAttributeFilterParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Could not parse {} into a target filter: {}",
+            self.input, self.reason
+        )
+    }
+}
+
+impl FromStr for TargetAttributeFilter {
+    type Err = TargetAttributeFilterParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = |reason: String| TargetAttributeFilterParseError {
+            input: s.to_string(),
+            reason,
+        };
+        let (key, values) = s
+            .split_once('=')
+            .ok_or_else(|| err("expected <key>=<value>".into()))?;
+        let (key, negated) = match key.trim().strip_suffix('!') {
+            Some(key) => (key.trim(), true),
+            None => (key.trim(), false),
+        };
+        let values: Vec<&str> = values.split(',').map(str::trim).collect();
+        if values.iter().any(|v| v.is_empty()) {
+            return Err(err("empty value".into()));
+        }
+        let strings = || -> Vec<String> { values.iter().map(|v| v.to_string()).collect() };
+        let principals = || {
+            values
+                .iter()
+                .map(|v| PrincipalId::from_str(v).map_err(|e| err(e.to_string())))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let filter = match key {
+            "node_id" => by_node(principals()?.into_iter().map(NodeId::from).collect()),
+            "subnet_id" => by_subnet(principals()?.into_iter().map(SubnetId::from).collect()),
+            "operator_id" => by_operator(principals()?),
+            "dc_id" => by_dc(strings()),
+            other => match other.strip_prefix("label.") {
+                Some(label) if !label.is_empty() => by_label(label, strings()),
+                _ => return Err(err(format!("unknown key `{}`", other))),
+            },
+        };
+        Ok(if negated {
+            Self::Not(Box::new(filter))
+        } else {
+            filter
+        })
+    }
+}
+
+impl TargetFilter for TargetAttributeFilter {
+    fn matches(&self, target_group: &TargetGroup) -> bool {
+        match self {
+            Self::NodeId(ids) => ids.contains(&target_group.node_id),
+            Self::SubnetId(ids) => target_group
+                .subnet_id
+                .map(|id| ids.contains(&id))
+                .unwrap_or(false),
+            Self::OperatorId(ids) => target_group
+                .operator_id
+                .map(|id| ids.contains(&id))
+                .unwrap_or(false),
+            Self::DcId(ids) => target_group
+                .dc_id
+                .as_ref()
+                .map(|id| ids.contains(id))
+                .unwrap_or(false),
+            Self::Label(key, values) => target_group
+                .custom_labels
+                .get(key)
+                .map(|value| values.contains(value))
+                .unwrap_or(false),
+            Self::Not(filter) => !filter.matches(target_group),
+        }
+    }
+}
+
+/// Returns the filter given by the `--filter-node-id-regex` and
+/// `--target-filter` command line options, i.e. a target group has to match
+/// the regex, if any, and all target filters.
+pub fn command_line_filter(
+    filter_node_id_regex: Option<&Regex>,
+    target_filters: &[TargetAttributeFilter],
+) -> All {
+    let mut filters: Vec<Box<dyn TargetFilter>> = vec![];
+    if let Some(regex) = filter_node_id_regex {
+        filters.push(Box::new(NodeIdRegex(regex.clone())));
+    }
+    for target_filter in target_filters {
+        filters.push(Box::new(target_filter.clone()));
+    }
+    All(filters)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ic_types::{NodeId, PrincipalId, SubnetId};
+    use regex::Regex;
+
+    use crate::{test_utils::target_group, TargetGroup};
+
+    use super::{
+        by_dc, by_label, by_node, command_line_filter, All, Any, NodeIdRegex,
+        TargetAttributeFilter, TargetFilter,
+    };
+
+    fn node_target_group(node_id: &str) -> TargetGroup {
+        target_group(0)
+            .node_id(NodeId::from(PrincipalId::from_str(node_id).unwrap()))
+            .targets([])
+            .subnet_id(SubnetId::from(PrincipalId::new_anonymous()))
+            .dc_id("an1")
+            .operator_id(PrincipalId::new_anonymous())
+            .custom_label("env", "prod")
+            .build()
+    }
+
+    const NODE_I: &str = "iylgr-zpxwq-kqgmf-4srtx-o4eey-d6bln-smmq6-we7px-ibdea-nondy-eae";
+    const NODE_X: &str = "x33ed-h457x-bsgyx-oqxqf-6pzwv-wkhzr-rm2j3-npodi-purzm-n66cg-gae";
+
+    #[test]
+    fn node_id_regex_filter_test() {
+        let filter = NodeIdRegex(Regex::new("^i").unwrap());
+
+        assert!(filter.matches(&node_target_group(NODE_I)));
+        assert!(!filter.matches(&node_target_group(NODE_X)));
+    }
+
+    #[test]
+    fn combinators_test() {
+        let tg_i = node_target_group(NODE_I);
+        let tg_x = node_target_group(NODE_X);
+        let node_i = tg_i.node_id;
+
+        let filter = by_node(vec![node_i]).or(by_dc(vec!["zh2".into()]));
+        assert!(filter.matches(&tg_i));
+        assert!(!filter.matches(&tg_x));
+
+        let filter = by_dc(vec!["an1".into()]).and(by_node(vec![node_i]).not());
+        assert!(!filter.matches(&tg_i));
+        assert!(filter.matches(&tg_x));
+
+        assert!(All::default().matches(&tg_i));
+        assert!(!Any::default().matches(&tg_i));
+        let filter = Any(vec![
+            Box::new(by_label("env", vec!["test".into()])),
+            Box::new(by_label("env", vec!["prod".into()])),
+        ]);
+        assert!(filter.matches(&tg_i));
+    }
+
+    #[test]
+    fn command_line_filter_test() {
+        let regex = Regex::new("^i").unwrap();
+        let filter = command_line_filter(Some(&regex), &[]);
+        assert!(filter.matches(&node_target_group(NODE_I)));
+        assert!(!filter.matches(&node_target_group(NODE_X)));
+
+        let filter = command_line_filter(None, &[]);
+        assert!(filter.matches(&node_target_group(NODE_X)));
+
+        let filter = command_line_filter(
+            Some(&regex),
+            &[TargetAttributeFilter::from_str("dc_id=zh2").unwrap()],
+        );
+        assert!(!filter.matches(&node_target_group(NODE_I)));
+    }
+
+    #[test]
+    fn target_attribute_filter_parse_test() {
+        assert_eq!(
+            TargetAttributeFilter::from_str("dc_id=an1, zh2").unwrap(),
+            TargetAttributeFilter::DcId(vec!["an1".into(), "zh2".into()])
+        );
+        assert_eq!(
+            TargetAttributeFilter::from_str("operator_id=2vxsx-fae").unwrap(),
+            TargetAttributeFilter::OperatorId(vec![PrincipalId::new_anonymous()])
+        );
+        assert_eq!(
+            TargetAttributeFilter::from_str("label.env=prod").unwrap(),
+            by_label("env", vec!["prod".into()])
+        );
+        assert_eq!(
+            TargetAttributeFilter::from_str("dc_id!=an1").unwrap(),
+            TargetAttributeFilter::Not(Box::new(by_dc(vec!["an1".into()])))
+        );
+        assert!(TargetAttributeFilter::from_str("dc_id").is_err());
+        assert!(TargetAttributeFilter::from_str("dc_id=").is_err());
+        assert!(TargetAttributeFilter::from_str("label.=prod").is_err());
+        assert!(TargetAttributeFilter::from_str("operator_id=not-a-principal").is_err());
+        assert!(TargetAttributeFilter::from_str("unknown=1").is_err());
+    }
+
+    #[test]
+    fn target_attribute_filter_test() {
+        let mut tg = node_target_group(NODE_I);
+
+        let filter = TargetAttributeFilter::from_str("dc_id=zh2,an1").unwrap();
+        assert!(filter.matches(&tg));
+        let filter = TargetAttributeFilter::from_str("dc_id=zh2").unwrap();
+        assert!(!filter.matches(&tg));
+        let filter = TargetAttributeFilter::from_str("label.env!=prod").unwrap();
+        assert!(!filter.matches(&tg));
+        let filter = TargetAttributeFilter::from_str("operator_id=2vxsx-fae").unwrap();
+        assert!(filter.matches(&tg));
+
+        tg.operator_id = None;
+        assert!(!filter.matches(&tg));
+    }
+}
@@ -0,0 +1,93 @@
+//! Helpers to create [TargetGroup]s in the tests of the service discovery and
+//! of the crates consuming its target groups.
+use std::{collections::BTreeSet, net::SocketAddr};
+
+use ic_types::{NodeId, PrincipalId, RegistryVersion, SubnetId};
+
+use crate::TargetGroup;
+
+/// The single target of the target groups created by [target_group].
+pub const TEST_TARGET: &str = "[2a02:800:2:2003:6801:f6ff:fec4:4c86]:9091";
+
+/// Returns a builder for the target group of the node with the test ID `node`
+/// on the IC `mercury`. Unless set otherwise, the target group contains
+/// [TEST_TARGET] as its only target and none of the optional attributes.
+pub fn target_group(node: u64) -> TargetGroupBuilder {
+    TargetGroupBuilder(TargetGroup {
+        node_id: NodeId::from(PrincipalId::new_node_test_id(node)),
+        ic_name: "mercury".into(),
+        targets: BTreeSet::from([TEST_TARGET.parse().unwrap()]),
+        subnet_id: None,
+        dc_id: None,
+        operator_id: None,
+        registry_version: None,
+        custom_labels: Default::default(),
+    })
+}
+
+/// Builds a [TargetGroup], see [target_group].
+#[derive(Clone, Debug)]
+pub struct TargetGroupBuilder(TargetGroup);
+
+impl TargetGroupBuilder {
+    pub fn node_id(mut self, node_id: NodeId) -> Self {
+        self.0.node_id = node_id;
+        self
+    }
+
+    pub fn ic_name(mut self, ic_name: &str) -> Self {
+        self.0.ic_name = ic_name.into();
+        self
+    }
+
+    /// Replaces the targets of the target group with the single `target`, e.g.,
+    /// `[::1]:9090`.
+    pub fn target(self, target: &str) -> Self {
+        self.targets([target.parse().unwrap()])
+    }
+
+    /// Replaces the targets of the target group.
+    pub fn targets<I: IntoIterator<Item = SocketAddr>>(mut self, targets: I) -> Self {
+        self.0.targets = targets.into_iter().collect();
+        self
+    }
+
+    /// Sets the subnet ID to the subnet test ID `subnet`.
+    pub fn subnet(self, subnet: u64) -> Self {
+        self.subnet_id(SubnetId::from(PrincipalId::new_subnet_test_id(subnet)))
+    }
+
+    pub fn subnet_id(mut self, subnet_id: SubnetId) -> Self {
+        self.0.subnet_id = Some(subnet_id);
+        self
+    }
+
+    pub fn dc_id(mut self, dc_id: &str) -> Self {
+        self.0.dc_id = Some(dc_id.into());
+        self
+    }
+
+    /// Sets the operator ID to the user test ID `operator`.
+    pub fn operator(self, operator: u64) -> Self {
+        self.operator_id(PrincipalId::new_user_test_id(operator))
+    }
+
+    pub fn operator_id(mut self, operator_id: PrincipalId) -> Self {
+        self.0.operator_id = Some(operator_id);
+        self
+    }
+
+    pub fn registry_version(mut self, registry_version: u64) -> Self {
+        self.0.registry_version = Some(RegistryVersion::from(registry_version));
+        self
+    }
+
+    pub fn custom_label(mut self, key: &str, value: &str) -> Self {
+        self.0.custom_labels.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn build(self) -> TargetGroup {
+        self.0
+    }
+}
@@ -0,0 +1 @@
+tonic::include_proto!("service_discovery.v1");
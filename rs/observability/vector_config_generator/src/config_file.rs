@@ -0,0 +1,290 @@
+//! An optional YAML configuration file for the generator, e.g.:
+//!
+//! ```yaml
+//! targets_dir: /var/lib/vector-config-generator/targets
+//! generation_dir: /etc/vector/metrics
+//! nns_url: https://ic0.app
+//! jobs: [replica, orchestrator]
+//! target_filters: ["dc_id=zh2,an1"]
+//! scrape_interval: 30
+//! output_layout: per-node
+//! file_mode: "0644"
+//! file_owner: vector
+//! logs_generation_dir: /etc/vector/logs
+//! dashboards_generation_dir: /var/lib/grafana/dashboards
+//! health_max_age: 15m
+//! ```
+//!
+//! The keys are the names of the command line options with underscores, except
+//! for `--config-file` itself and `--skip-sync`. Settings given on the command
+//! line take precedence over the ones in the file. The filters are re-applied
+//! whenever the file changes; all other settings are only read at startup.
+use std::{
+    fmt::Display,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context, Result};
+use config_writer_common::{
+    config_writer::OutputLayout,
+    file_permissions::{parse_group, parse_mode, parse_owner, Gid, Uid},
+};
+use crossbeam::select;
+use crossbeam_channel::Receiver;
+use humantime::parse_duration;
+use regex::Regex;
+use serde::{de::Error, Deserialize, Deserializer};
+use service_discovery::{
+    job_types::JobType, logger::LogFormat, target_filter::TargetAttributeFilter,
+};
+use slog::{info, warn, Logger};
+use url::Url;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub targets_dir: Option<PathBuf>,
+    #[serde(default, deserialize_with = "duration_opt")]
+    pub poll_interval: Option<Duration>,
+    #[serde(default, deserialize_with = "duration_opt")]
+    pub max_poll_interval: Option<Duration>,
+    #[serde(default, deserialize_with = "duration_opt")]
+    pub registry_query_timeout: Option<Duration>,
+    pub generation_dir: Option<PathBuf>,
+    #[serde(default, deserialize_with = "mode_opt")]
+    pub file_mode: Option<u32>,
+    #[serde(default, deserialize_with = "mode_opt")]
+    pub dir_mode: Option<u32>,
+    #[serde(default, deserialize_with = "owner_opt")]
+    pub file_owner: Option<Uid>,
+    #[serde(default, deserialize_with = "group_opt")]
+    pub file_group: Option<Gid>,
+    #[serde(default, deserialize_with = "from_str_opt")]
+    pub output_layout: Option<OutputLayout>,
+    pub manifest_file: Option<PathBuf>,
+    pub unreachable_targets_file: Option<PathBuf>,
+    #[serde(default, deserialize_with = "duration_opt")]
+    pub probe_interval: Option<Duration>,
+    #[serde(default, deserialize_with = "duration_opt")]
+    pub probe_timeout: Option<Duration>,
+    pub nns_url: Option<Url>,
+    #[serde(default, deserialize_with = "from_str_seq")]
+    pub jobs: Vec<JobType>,
+    #[serde(default, deserialize_with = "from_str_opt")]
+    pub filter_node_id_regex: Option<Regex>,
+    #[serde(default, deserialize_with = "from_str_seq")]
+    pub target_filters: Vec<TargetAttributeFilter>,
+    pub static_targets_file: Option<PathBuf>,
+    pub scrape_interval: Option<u64>,
+    pub proxy_url: Option<Url>,
+    pub logs_generation_dir: Option<PathBuf>,
+    pub batch_size: Option<u64>,
+    pub cursors_dir: Option<PathBuf>,
+    pub parse_replica_logs: Option<bool>,
+    #[serde(default, deserialize_with = "from_str_seq")]
+    pub parse_replica_logs_jobs: Vec<JobType>,
+    #[serde(default)]
+    pub journald_units: Vec<String>,
+    pub source_template: Option<PathBuf>,
+    pub dashboards_generation_dir: Option<PathBuf>,
+    pub grafana_datasource_uid: Option<String>,
+    #[serde(default)]
+    pub ic_precedence: Vec<String>,
+    #[serde(default, deserialize_with = "duration_opt")]
+    pub removal_grace_period: Option<Duration>,
+    #[serde(default, deserialize_with = "duration_opt")]
+    pub max_registry_age: Option<Duration>,
+    #[serde(default, deserialize_with = "from_str_opt")]
+    pub log_format: Option<LogFormat>,
+    pub metrics_listen_addr: Option<SocketAddr>,
+    pub http_sd_listen_addr: Option<SocketAddr>,
+    pub grpc_listen_addr: Option<SocketAddr>,
+    pub health_listen_addr: Option<SocketAddr>,
+    #[serde(default, deserialize_with = "duration_opt")]
+    pub health_max_age: Option<Duration>,
+}
+
+impl ConfigFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {:?}", path))?;
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse config file {:?}", path))
+    }
+}
+
+/// Returns a loop that checks the configuration file for modifications every
+/// `interval` and passes the new content to `on_reload`. If the file fails to
+/// parse, the error is logged and the last valid configuration stays in
+/// effect.
+pub fn config_reload_loop(
+    log: Logger,
+    path: PathBuf,
+    interval: Duration,
+    stop_signal: Receiver<()>,
+    on_reload: impl Fn(ConfigFile) + Send + 'static,
+) -> impl FnMut() {
+    move || {
+        let mut last_modified = modified(&path);
+        loop {
+            select! {
+                recv(stop_signal) -> _ => {
+                    info!(log, "Received shutdown signal in config_reload_loop");
+                    return
+                },
+                recv(crossbeam::channel::after(interval)) -> _ => {}
+            };
+            let modified = modified(&path);
+            if modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+            match ConfigFile::load(&path) {
+                Ok(config_file) => {
+                    info!(log, "Reloaded config file {:?}", path);
+                    on_reload(config_file);
+                }
+                Err(e) => warn!(log, "Failed to reload config file: {:?}", e),
+            }
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn from_str_opt<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    parse_opt(deserializer, T::from_str)
+}
+
+fn duration_opt<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    parse_opt(deserializer, parse_duration)
+}
+
+fn mode_opt<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u32>, D::Error> {
+    parse_opt(deserializer, parse_mode)
+}
+
+fn owner_opt<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Uid>, D::Error> {
+    parse_opt(deserializer, parse_owner)
+}
+
+fn group_opt<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Gid>, D::Error> {
+    parse_opt(deserializer, parse_group)
+}
+
+/// Deserializes an optional string and parses it with the parser of the
+/// corresponding command line option.
+fn parse_opt<'de, D, T, E>(
+    deserializer: D,
+    parse: impl Fn(&str) -> Result<T, E>,
+) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    E: Display,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| parse(&s).map_err(D::Error::custom))
+        .transpose()
+}
+
+fn from_str_seq<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|s| T::from_str(s).map_err(D::Error::custom))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use config_writer_common::config_writer::OutputLayout;
+    use service_discovery::{
+        job_types::JobType, logger::LogFormat, target_filter::TargetAttributeFilter,
+    };
+
+    use super::ConfigFile;
+
+    #[test]
+    fn parse_config_file() {
+        let config_file: ConfigFile = serde_yaml::from_str(
+            r#"
+targets_dir: /tmp/targets
+generation_dir: /tmp/generated
+output_layout: per-node
+file_mode: "0640"
+manifest_file: /tmp/manifest.json
+probe_interval: 2m
+nns_url: https://ic0.app
+jobs: [replica, orchestrator]
+filter_node_id_regex: "^a"
+target_filters: ["dc_id=zh2,an1"]
+scrape_interval: 60
+parse_replica_logs: true
+parse_replica_logs_jobs: [replica]
+log_format: json
+grpc_listen_addr: "[::]:11236"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config_file.targets_dir.unwrap().to_str(),
+            Some("/tmp/targets")
+        );
+        assert_eq!(
+            config_file.generation_dir.unwrap().to_str(),
+            Some("/tmp/generated")
+        );
+        assert_eq!(config_file.output_layout, Some(OutputLayout::PerNode));
+        assert_eq!(config_file.file_mode, Some(0o640));
+        assert!(config_file.manifest_file.is_some());
+        assert_eq!(config_file.probe_interval, Some(Duration::from_secs(120)));
+        assert_eq!(config_file.nns_url.unwrap().as_str(), "https://ic0.app/");
+        assert_eq!(
+            config_file.jobs,
+            vec![JobType::Replica, JobType::Orchestrator]
+        );
+        assert_eq!(config_file.filter_node_id_regex.unwrap().as_str(), "^a");
+        assert_eq!(
+            config_file.target_filters,
+            vec![TargetAttributeFilter::DcId(vec![
+                "zh2".into(),
+                "an1".into()
+            ])]
+        );
+        assert_eq!(config_file.scrape_interval, Some(60));
+        assert_eq!(config_file.parse_replica_logs, Some(true));
+        assert_eq!(config_file.parse_replica_logs_jobs, vec![JobType::Replica]);
+        assert_eq!(config_file.log_format, Some(LogFormat::Json));
+        assert_eq!(
+            config_file.grpc_listen_addr,
+            Some("[::]:11236".parse().unwrap())
+        );
+        assert!(config_file.logs_generation_dir.is_none());
+        assert!(config_file.health_max_age.is_none());
+    }
+
+    #[test]
+    fn invalid_config_file_is_rejected() {
+        assert!(serde_yaml::from_str::<ConfigFile>("jobs: [unknown]").is_err());
+        assert!(serde_yaml::from_str::<ConfigFile>("unknown_setting: 1").is_err());
+        assert!(serde_yaml::from_str::<ConfigFile>("file_mode: \"0999\"").is_err());
+        assert!(serde_yaml::from_str::<ConfigFile>("probe_timeout: soon").is_err());
+    }
+}
@@ -4,8 +4,15 @@ use std::{path::PathBuf, sync::Arc, time::Duration};
 
 use anyhow::{bail, Result};
 use clap::Parser;
+use config_writer_common::config_writer::{ConfigWriter, OutputLayout};
 use config_writer_common::config_writer_loop::config_writer_loop;
-use config_writer_common::filters::{NodeIDRegexFilter, TargetGroupFilter, TargetGroupFilterList};
+use config_writer_common::file_permissions::FilePermissions;
+use config_writer_common::filters::ReloadableFilter;
+use config_writer_common::grafana_dashboards::GrafanaDashboardGenerator;
+use config_writer_common::reachability::{probe_loop, ProbeArgs};
+use config_writer_common::vector_journald_config::{
+    JournaldVectorConfigBuilder, ReplicaLogParsing,
+};
 use futures_util::FutureExt;
 use humantime::parse_duration;
 use ic_async_utils::shutdown_signal;
@@ -13,22 +20,45 @@ use ic_config::metrics::{Config as MetricsConfig, Exporter};
 use ic_http_endpoints_metrics::MetricsHttpEndpoint;
 use ic_metrics::MetricsRegistry;
 use regex::Regex;
+use service_discovery::grace_period::GracePeriodDiscovery;
+use service_discovery::grpc_api::start_grpc_server;
+use service_discovery::health::{make_systemd_notify_loop, start_health_server, HealthStatus};
+use service_discovery::logger::{make_logger, LogFormat};
 use service_discovery::registry_sync::sync_local_registry;
+use service_discovery::rest_api::start_http_server;
+use service_discovery::static_targets::StaticTargetsDiscovery;
+use service_discovery::target_filter::{
+    command_line_filter, All, TargetAttributeFilter, TargetFilter,
+};
 use service_discovery::{
     job_types::{JobType, NodeOS},
     metrics::Metrics,
-    poll_loop::make_poll_loop,
-    IcServiceDiscoveryImpl,
+    poll_loop::{make_poll_loop, poll_on_new_ics, poll_on_sighup, PollInterval},
+    IcServiceDiscovery, IcServiceDiscoveryImpl,
 };
-use slog::{info, o, Drain, Logger};
+use slog::{info, warn};
 use url::Url;
 
+use crate::config_file::{config_reload_loop, ConfigFile};
 use crate::custom_filters::OldMachinesFilter;
+use crate::source_template::SourceTemplate;
 use crate::vector_configuration::VectorConfigBuilderImpl;
 
+mod config_file;
 mod custom_filters;
+mod source_template;
 mod vector_configuration;
 
+const DEFAULT_NNS_URL: &str = "https://ic0.app";
+const DEFAULT_SCRAPE_INTERVAL: u64 = 30;
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+const DEFAULT_REGISTRY_QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_BATCH_SIZE: u64 = 32;
+const DEFAULT_CURSORS_DIR: &str = "logs";
+const DEFAULT_GRAFANA_DATASOURCE_UID: &str = "prometheus";
+const DEFAULT_METRICS_LISTEN_ADDR: &str = "[::]:9099";
+const DEFAULT_HEALTH_MAX_AGE: Duration = Duration::from_secs(600);
+
 #[derive(Clone, Debug)]
 pub struct JobParameters {
     pub port: u16,
@@ -87,39 +117,59 @@ fn get_jobs_parameters() -> HashMap<JobType, JobParameters> {
 }
 
 fn main() -> Result<()> {
-    let cli_args = CliArgs::parse().validate()?;
+    let cli_overrides = CliArgs::parse();
+    let config_file = match &cli_overrides.config_file {
+        Some(path) => ConfigFile::load(path)?,
+        None => ConfigFile::default(),
+    };
+    let cli_args = cli_overrides.clone().merge(config_file).validate()?;
+    // Both directories are checked to be given by `validate`.
+    let targets_dir = cli_args.targets_dir.clone().unwrap();
+    let generation_dir = cli_args.generation_dir.clone().unwrap();
+    let poll_interval = cli_args.poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL);
+    let output_layout = cli_args.output_layout.unwrap_or_default();
     let rt = tokio::runtime::Runtime::new()?;
-    let log = make_logger();
+    let log = make_logger(cli_args.log_format.unwrap_or(LogFormat::Text));
     let metrics_registry = MetricsRegistry::new();
     let shutdown_signal = shutdown_signal(log.clone()).shared();
     let mut handles = vec![];
 
     info!(log, "Starting vector-config-generator");
-    let mercury_dir = cli_args.targets_dir.join("mercury");
+    let metrics = Metrics::new(metrics_registry.clone());
+    let mercury_dir = targets_dir.join("mercury");
     rt.block_on(sync_local_registry(
         log.clone(),
         mercury_dir,
-        cli_args.nns_url,
+        cli_args
+            .nns_url
+            .clone()
+            .unwrap_or_else(|| Url::parse(DEFAULT_NNS_URL).unwrap()),
         cli_args.skip_sync,
-    ));
+        cli_args.max_registry_age,
+        &metrics,
+    ))?;
 
     let jobs = get_jobs();
 
     info!(log, "Starting IcServiceDiscovery ...");
-    let ic_discovery = Arc::new(IcServiceDiscoveryImpl::new(
-        log.clone(),
-        cli_args.targets_dir,
-        cli_args.registry_query_timeout,
-        jobs.clone(),
-    )?);
-
-    let metrics = Metrics::new(metrics_registry.clone());
-    info!(
-        log,
-        "Metrics are exposed on {}.", cli_args.metrics_listen_addr
+    let ic_discovery = Arc::new(
+        IcServiceDiscoveryImpl::new(
+            log.clone(),
+            targets_dir.clone(),
+            cli_args
+                .registry_query_timeout
+                .unwrap_or(DEFAULT_REGISTRY_QUERY_TIMEOUT),
+            jobs.clone(),
+        )?
+        .with_ic_precedence(cli_args.ic_precedence.clone()),
     );
+
+    let metrics_listen_addr = cli_args
+        .metrics_listen_addr
+        .unwrap_or_else(|| DEFAULT_METRICS_LISTEN_ADDR.parse().unwrap());
+    info!(log, "Metrics are exposed on {}.", metrics_listen_addr);
     let exporter_config = MetricsConfig {
-        exporter: Exporter::Http(cli_args.metrics_listen_addr),
+        exporter: Exporter::Http(metrics_listen_addr),
         ..Default::default()
     };
     let _metrics_endpoint = MetricsHttpEndpoint::new_insecure(
@@ -130,52 +180,247 @@ fn main() -> Result<()> {
     );
 
     let (stop_signal_sender, stop_signal_rcv) = crossbeam::channel::bounded::<()>(0);
+    let health = HealthStatus::new(cli_args.health_max_age.unwrap_or(DEFAULT_HEALTH_MAX_AGE));
+    if let Some(notify_loop) =
+        make_systemd_notify_loop(log.clone(), health.clone(), stop_signal_rcv.clone())
+    {
+        handles.push(std::thread::spawn(notify_loop));
+    }
     let (update_signal_sender, update_signal_rcv) = crossbeam::channel::bounded::<()>(0);
+    let (poll_now_sender, poll_now_rcv) = crossbeam::channel::bounded::<()>(1);
+    std::thread::spawn(poll_on_new_ics(
+        log.clone(),
+        targets_dir.clone(),
+        poll_now_sender.clone(),
+    ));
+    rt.spawn(poll_on_sighup(log.clone(), poll_now_sender.clone()));
+    let (logs_update_signal_sender, logs_update_signal_rcv) = crossbeam::channel::bounded::<()>(0);
+    let (dashboards_update_signal_sender, dashboards_update_signal_rcv) =
+        crossbeam::channel::bounded::<()>(0);
+    let mut update_notifiers = vec![update_signal_sender];
+    if cli_args.logs_generation_dir.is_some() {
+        update_notifiers.push(logs_update_signal_sender);
+    }
+    if cli_args.dashboards_generation_dir.is_some() {
+        update_notifiers.push(dashboards_update_signal_sender);
+    }
     let loop_fn = make_poll_loop(
         log.clone(),
         rt.handle().clone(),
         ic_discovery.clone(),
         stop_signal_rcv.clone(),
-        cli_args.poll_interval,
+        poll_now_rcv,
+        PollInterval::new(
+            poll_interval,
+            cli_args.max_poll_interval.unwrap_or(poll_interval),
+        ),
         metrics.clone(),
-        Some(update_signal_sender),
+        update_notifiers,
+        health.clone(),
     );
     let join_handle = std::thread::spawn(loop_fn);
     handles.push(join_handle);
+
+    let targets_discovery: Arc<dyn IcServiceDiscovery> = match &cli_args.static_targets_file {
+        Some(path) => Arc::new(StaticTargetsDiscovery::new(
+            ic_discovery.clone(),
+            path.clone(),
+        )),
+        None => ic_discovery.clone(),
+    };
+    let targets_discovery: Arc<dyn IcServiceDiscovery> = match cli_args.removal_grace_period {
+        Some(grace_period) => Arc::new(GracePeriodDiscovery::new(targets_discovery, grace_period)),
+        None => targets_discovery,
+    };
     info!(
         log,
-        "Scraping thread spawned. Interval: {:?}", cli_args.poll_interval
+        "Scraping thread spawned. Interval: {:?}", poll_interval
     );
 
-    let mut filters_vec: Vec<Box<dyn TargetGroupFilter>> = vec![];
-    if let Some(filter_node_id_regex) = &cli_args.filter_node_id_regex {
-        filters_vec.push(Box::new(NodeIDRegexFilter::new(
-            filter_node_id_regex.clone(),
-        )));
-    };
+    let logs_filter = ReloadableFilter::new(Arc::new(make_filters(&cli_args)));
+    let metrics_filter = ReloadableFilter::new(Arc::new(make_metrics_filters(&cli_args)));
+    if let Some(config_file_path) = cli_overrides.config_file.clone() {
+        let logs_filter = logs_filter.clone();
+        let metrics_filter = metrics_filter.clone();
+        let reload_loop = config_reload_loop(
+            log.clone(),
+            config_file_path,
+            poll_interval,
+            stop_signal_rcv.clone(),
+            move |config_file| {
+                let cli_args = cli_overrides.clone().merge(config_file);
+                logs_filter.replace(Arc::new(make_filters(&cli_args)));
+                metrics_filter.replace(Arc::new(make_metrics_filters(&cli_args)));
+                // The config writers only apply the new filters once they are
+                // signaled, which happens after the next poll. If a poll is
+                // already pending, the channel is full and that one suffices.
+                let _ = poll_now_sender.try_send(());
+            },
+        );
+        handles.push(std::thread::spawn(reload_loop));
+    }
 
-    filters_vec.push(Box::new(OldMachinesFilter {}));
+    let permissions = cli_args.permissions.clone();
+
+    if let Some(logs_generation_dir) = cli_args.logs_generation_dir.clone() {
+        let logs_config_writer_loop = config_writer_loop(
+            log.clone(),
+            targets_discovery.clone(),
+            stop_signal_rcv.clone(),
+            vec![JobType::NodeExporter(NodeOS::Guest)],
+            logs_update_signal_rcv,
+            ConfigWriter::new(logs_generation_dir, Arc::new(logs_filter), log.clone())
+                .with_permissions(permissions.clone())
+                .with_layout(output_layout),
+            None,
+            JournaldVectorConfigBuilder::new(
+                cli_args.batch_size.unwrap_or(DEFAULT_BATCH_SIZE),
+                cli_args
+                    .cursors_dir
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_CURSORS_DIR.into()),
+                ReplicaLogParsing::new(
+                    cli_args.parse_replica_logs.unwrap_or_default(),
+                    cli_args.parse_replica_logs_jobs.clone(),
+                ),
+            )
+            .with_units(cli_args.journald_units.clone()),
+            metrics.clone(),
+            health.clone(),
+        );
+        handles.push(std::thread::spawn(logs_config_writer_loop));
+        info!(log, "Logs config generator thread spawned.");
+    }
 
-    let filters = Arc::new(TargetGroupFilterList::new(filters_vec));
+    if let Some(dashboards_generation_dir) = cli_args.dashboards_generation_dir.clone() {
+        // The dashboards cover the node exporter metrics of the replica nodes
+        // as well, so the topology is taken from the replica targets only.
+        let dashboards_config_writer_loop = config_writer_loop(
+            log.clone(),
+            targets_discovery.clone(),
+            stop_signal_rcv.clone(),
+            vec![JobType::Replica],
+            dashboards_update_signal_rcv,
+            ConfigWriter::new(
+                dashboards_generation_dir,
+                Arc::new(metrics_filter.clone()),
+                log.clone(),
+            )
+            .with_permissions(permissions.clone()),
+            None,
+            GrafanaDashboardGenerator::new(
+                cli_args
+                    .grafana_datasource_uid
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_GRAFANA_DATASOURCE_UID.into()),
+            ),
+            metrics.clone(),
+            health.clone(),
+        );
+        handles.push(std::thread::spawn(dashboards_config_writer_loop));
+        info!(log, "Grafana dashboards generator thread spawned.");
+    }
+
+    let generated_jobs = if cli_args.jobs.is_empty() {
+        jobs.into_keys().collect()
+    } else {
+        cli_args.jobs.clone()
+    };
+
+    let source_template = cli_args
+        .source_template
+        .as_deref()
+        .map(|path| SourceTemplate::load(path, log.clone()))
+        .transpose()?
+        .map(Arc::new);
+
+    let metrics_filter = Arc::new(metrics_filter);
+    if let Some(unreachable_targets_file) = cli_args.probe.unreachable_targets_file.clone() {
+        let probe_loop = probe_loop(
+            log.clone(),
+            targets_discovery.clone(),
+            metrics_filter.clone(),
+            generated_jobs.clone(),
+            cli_args.probe.probe_interval(),
+            cli_args.probe.probe_timeout(),
+            unreachable_targets_file,
+            permissions.clone(),
+            metrics.clone(),
+            stop_signal_rcv.clone(),
+        );
+        handles.push(std::thread::spawn(probe_loop));
+    }
 
     let config_writer_loop = config_writer_loop(
         log.clone(),
-        ic_discovery,
-        filters,
+        targets_discovery.clone(),
         stop_signal_rcv,
-        jobs.into_keys().collect(),
+        generated_jobs,
         update_signal_rcv,
-        cli_args.generation_dir,
+        ConfigWriter::new(generation_dir, metrics_filter, log.clone())
+            .with_permissions(permissions)
+            .with_layout(output_layout),
+        cli_args.manifest_file.clone(),
         VectorConfigBuilderImpl::new(
             cli_args.proxy_url,
-            cli_args.scrape_interval,
+            cli_args.scrape_interval.unwrap_or(DEFAULT_SCRAPE_INTERVAL),
             get_jobs_parameters(),
-        ),
+        )
+        .with_source_template(source_template),
         metrics,
+        health.clone(),
     );
     let config_join_handle = std::thread::spawn(config_writer_loop);
     handles.push(config_join_handle);
 
+    if let Some(sd_listen_addr) = cli_args.http_sd_listen_addr {
+        info!(
+            log,
+            "Serving discovered targets for http_sd on {}.", sd_listen_addr
+        );
+        let http_sd_log = log.clone();
+        let http_sd = start_http_server(
+            log.clone(),
+            targets_discovery.clone(),
+            sd_listen_addr,
+            shutdown_signal.clone(),
+        );
+        rt.spawn(async move {
+            if let Err(e) = http_sd.await {
+                warn!(http_sd_log, "http_sd server failed: {:?}", e);
+            }
+        });
+    }
+
+    if let Some(grpc_listen_addr) = cli_args.grpc_listen_addr {
+        let grpc_log = log.clone();
+        // The discovered targets only change after a poll of the registry.
+        let grpc_server = start_grpc_server(
+            log.clone(),
+            targets_discovery,
+            grpc_listen_addr,
+            poll_interval,
+            shutdown_signal.clone(),
+        );
+        rt.spawn(async move {
+            if let Err(e) = grpc_server.await {
+                warn!(grpc_log, "gRPC server failed: {:?}", e);
+            }
+        });
+    }
+
+    if let Some(health_listen_addr) = cli_args.health_listen_addr {
+        info!(log, "Serving /healthz on {}.", health_listen_addr);
+        let health_log = log.clone();
+        let health_server =
+            start_health_server(health.clone(), health_listen_addr, shutdown_signal.clone());
+        rt.spawn(async move {
+            if let Err(e) = health_server.await {
+                warn!(health_log, "Health server failed: {:?}", e);
+            }
+        });
+    }
+
     rt.block_on(shutdown_signal);
 
     for handle in handles {
@@ -185,16 +430,36 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn make_logger() -> Logger {
-    let decorator = slog_term::TermDecorator::new().build();
-    let drain = slog_term::FullFormat::new(decorator).build().fuse();
-    let drain = slog_async::Async::new(drain).chan_size(8192).build();
-    slog::Logger::root(drain.fuse(), o!())
+fn make_filters(cli_args: &CliArgs) -> All {
+    command_line_filter(
+        cli_args.filter_node_id_regex.as_ref(),
+        &cli_args.target_filters,
+    )
+}
+
+/// The metrics configs additionally skip the host node exporters of old
+/// machines.
+fn make_metrics_filters(cli_args: &CliArgs) -> impl TargetFilter {
+    make_filters(cli_args).and(OldMachinesFilter {})
 }
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[clap(about, version)]
 pub struct CliArgs {
+    #[clap(
+        long = "config-file",
+        help = r#"
+YAML file with settings for the generator. The keys are the names of the
+corresponding command line options with underscores, e.g. `nns_url`,
+`targets_dir` or `file_mode`, except for `--skip-sync`. Lists such as `jobs` or
+`target_filters` use the plural. Options given on the command line take
+precedence. Changes to the filters are picked up at runtime, all other settings
+require a restart.
+
+"#
+    )]
+    config_file: Option<PathBuf>,
+
     #[clap(
         long = "targets-dir",
         help = r#"
@@ -205,31 +470,45 @@ If the directory does not contain a directory called 'mercury' and
 `--no-mercury` is *not* specified, a corresponding target will be generated and
 initialized with a hardcoded initial registry.
 
+Required, either on the command line or in the config file.
+
 "#
     )]
-    targets_dir: PathBuf,
+    targets_dir: Option<PathBuf>,
 
     #[clap(
     long = "poll-interval",
-    default_value = "10s",
     parse(try_from_str = parse_duration),
     help = r#"
-The interval at which ICs are polled for updates.
+The interval at which ICs are polled for updates. If `--max-poll-interval`
+is specified, this is the interval used while registry versions are advancing.
+[default: 10s]
 
 "#
     )]
-    poll_interval: Duration,
+    poll_interval: Option<Duration>,
+
+    #[clap(
+    long = "max-poll-interval",
+    parse(try_from_str = parse_duration),
+    help = r#"
+If specified, the poll interval is doubled every time no new registry version
+is observed, up to the given ceiling, and reset to `--poll-interval` as soon as
+a registry advances.
+
+"#
+    )]
+    max_poll_interval: Option<Duration>,
 
     #[clap(
     long = "query-request-timeout",
-    default_value = "5s",
     parse(try_from_str = parse_duration),
     help = r#"
-The HTTP-request timeout used when quering for registry updates.
+The HTTP-request timeout used when quering for registry updates. [default: 5s]
 
 "#
     )]
-    registry_query_timeout: Duration,
+    registry_query_timeout: Option<Duration>,
 
     #[clap(
         long = "generation-dir",
@@ -238,9 +517,143 @@ If specified, for each job, a json file containing the targets will be written
 to <generation_dir>/<job_name>.json containing the corresponding
 targets.
 
+Required, either on the command line or in the config file.
+
+"#
+    )]
+    generation_dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    permissions: FilePermissions,
+
+    #[clap(
+        long = "output-layout",
+        help = r#"
+How the generated configs are split into files: `merged` writes a single file
+per job containing all of its targets, `per-node` writes a file per job and
+node. The latter limits the changes to the affected nodes, but large fleets may
+run into inode or inotify limits. [default: merged]
+
+"#
+    )]
+    output_layout: Option<OutputLayout>,
+
+    #[clap(
+        long = "manifest-file",
+        help = r#"
+If specified, a JSON manifest with the time of the last generation, the latest
+registry version per IC and the number of targets per job is written to this
+file after every generation.
+
+"#
+    )]
+    manifest_file: Option<PathBuf>,
+
+    #[clap(flatten)]
+    probe: ProbeArgs,
+
+    #[clap(
+        long = "logs-generation-dir",
+        help = r#"
+If specified, vector configs for scraping the logs of all discovered nodes
+through systemd-journal-gatewayd are generated to this directory as well, using
+the same discovery as the metrics configs.
+
+"#
+    )]
+    logs_generation_dir: Option<PathBuf>,
+
+    #[clap(
+        long = "batch-size",
+        help = r#"
+Only used with `--logs-generation-dir`. Batch size for generated log sources. It
+represents the amount of log lines before persisting the cursor. [default: 32]
+
+"#
+    )]
+    batch_size: Option<u64>,
+
+    #[clap(
+        long = "cursors-dir",
+        help = r#"
+Only used with `--logs-generation-dir`. Directory in which the generated log
+sources persist their journald cursors. [default: logs]
+
+"#
+    )]
+    cursors_dir: Option<PathBuf>,
+
+    #[clap(
+        long = "parse-replica-logs",
+        min_values = 0,
+        require_equals = true,
+        default_missing_value = "true",
+        help = r#"
+Only used with `--logs-generation-dir`. Parse the replica's structured log
+lines into fields by the generated transforms. `--parse-replica-logs=false`
+disables the parsing if the config file enables it.
+
+"#
+    )]
+    parse_replica_logs: Option<bool>,
+
+    #[clap(
+        long = "parse-replica-logs-job",
+        help = r#"
+Only used with `--parse-replica-logs`. Restricts the parsing to the logs of
+this job. Can be given multiple times. If not specified, the logs of all jobs
+are parsed.
+
+"#
+    )]
+    parse_replica_logs_jobs: Vec<JobType>,
+
+    #[clap(
+        long = "journald-unit",
+        help = r#"
+Only used with `--logs-generation-dir`. Systemd unit whose journal is read, e.g.
+`orchestrator.service`. Can be given multiple times, in which case a separate
+source is generated per node and unit. If not specified, the full journal is
+read.
+
 "#
     )]
-    generation_dir: PathBuf,
+    journald_units: Vec<String>,
+
+    #[clap(
+        long = "dashboards-generation-dir",
+        help = r#"
+If specified, Grafana dashboards showing the node exporter and replica metrics
+of every discovered subnet and node are generated to this directory. The
+dashboards are regenerated as the topology changes; those of subnets and nodes
+that disappeared are removed.
+
+"#
+    )]
+    dashboards_generation_dir: Option<PathBuf>,
+
+    #[clap(
+        long = "grafana-datasource-uid",
+        help = r#"
+Only used with `--dashboards-generation-dir`. UID of the Grafana data source
+the panels of the generated dashboards query. [default: prometheus]
+
+"#
+    )]
+    grafana_datasource_uid: Option<String>,
+
+    #[clap(
+        long = "source-template",
+        help = r#"
+Handlebars template rendering the vector source of a single target as YAML. The
+discovery fields, e.g. `node_id`, `ic_name`, `dc_id`, `endpoints` and
+`scrape_interval_secs`, are available as variables and `{{json <value>}}`
+renders a value as JSON. If not specified, a `prometheus_scrape` source is
+generated.
+
+"#
+    )]
+    source_template: Option<PathBuf>,
 
     #[clap(
         long = "filter-node-id-regex",
@@ -251,15 +664,73 @@ Regex used to filter the node IDs
     )]
     filter_node_id_regex: Option<Regex>,
 
+    #[clap(
+        long = "static-targets-file",
+        help = r#"
+JSON file listing targets that are not part of any registry. Each entry has a
+`job`, a list of `targets` ("ip:port") and optional `labels` and `ic_name`. The
+file is re-read on every generation.
+
+"#
+    )]
+    static_targets_file: Option<PathBuf>,
+
+    #[clap(
+        long = "ic-precedence",
+        help = r#"
+Name of an IC, i.e. of a directory in `--targets-dir`, whose targets take
+precedence if the same targets are listed by more than one IC. Can be specified
+multiple times, in decreasing order of precedence. ICs that are not listed come
+afterwards in alphabetical order.
+
+"#
+    )]
+    ic_precedence: Vec<String>,
+
+    #[clap(
+    long = "removal-grace-period",
+    parse(try_from_str = parse_duration),
+    help = r#"
+If specified, targets that vanish from discovery are kept in the generated
+configs, labeled with `pending_removal="true"`, for the given duration before
+they are removed. This avoids config churn when registry reads flap.
+
+"#
+    )]
+    removal_grace_period: Option<Duration>,
+
+    #[clap(
+        long = "target-filter",
+        help = r#"
+Only keep targets matching the filter, given as `<key>=<value>[,<value>...]`
+where key is one of `node_id`, `subnet_id`, `operator_id`, `dc_id` or
+`label.<name>` for the custom label `<name>`. A target matches if its attribute
+equals any of the values. With `<key>!=<value>[,<value>...]`, a target matches
+if its attribute equals none of the values. Can be specified multiple times, in
+which case a target has to match all filters.
+
+"#
+    )]
+    target_filters: Vec<TargetAttributeFilter>,
+
+    #[clap(
+        long = "job",
+        help = r#"
+Only generate configs for the given job. Can be specified multiple times.
+Defaults to all jobs.
+
+"#
+    )]
+    jobs: Vec<JobType>,
+
     #[clap(
         long = "scrape-interval",
-        default_value = "30",
         help = r#"
-Interval for metrics scraping in the generated configuration
+Interval for metrics scraping in the generated configuration [default: 30]
 
 "#
     )]
-    scrape_interval: u64,
+    scrape_interval: Option<u64>,
 
     #[clap(
         long = "proxy-url",
@@ -272,12 +743,11 @@ URL of the proxy to use in the generated config
 
     #[clap(
         long = "nns-url",
-        default_value = "https://ic0.app",
         help = r#"
-NNS-url to use for syncing the registry version.
+NNS-url to use for syncing the registry version. [default: https://ic0.app]
 "#
     )]
-    nns_url: Url,
+    nns_url: Option<Url>,
 
     #[clap(
         long = "skip-sync",
@@ -288,28 +758,197 @@ Possible only if the version is not a ZERO_REGISTRY_VERSION
     )]
     skip_sync: bool,
 
+    #[clap(
+    long = "max-registry-age",
+    parse(try_from_str = parse_duration),
+    help = r#"
+Only used together with `--skip-sync`. If the local registry was certified
+longer ago than the given duration, refuse to start.
+
+"#
+    )]
+    max_registry_age: Option<Duration>,
+
+    #[clap(
+        long = "log-format",
+        help = r#"
+The format of the log output, either `text` or `json`. [default: text]
+
+"#
+    )]
+    log_format: Option<LogFormat>,
+
     #[clap(
         long = "metrics-listen-addr",
-        default_value = "[::]:9099",
         help = r#"
 The listen address on which metrics for this service should be exposed.
+[default: [::]:9099]
+
+"#
+    )]
+    metrics_listen_addr: Option<SocketAddr>,
+
+    #[clap(
+        long = "http-sd-listen-addr",
+        help = r#"
+If specified, the discovered targets are exposed on the given address in the
+format expected by Prometheus' http_sd, with one endpoint per job, e.g.
+http://[::]:11235/replica
+
+"#
+    )]
+    http_sd_listen_addr: Option<SocketAddr>,
+
+    #[clap(
+        long = "grpc-listen-addr",
+        help = r#"
+If specified, the discovered targets are exposed on the given address by the
+gRPC service `service_discovery.v1.ServiceDiscovery`, which lists the targets
+of a job, optionally of a single subnet or node operator, and streams their
+changes.
+
+"#
+    )]
+    grpc_listen_addr: Option<SocketAddr>,
+
+    #[clap(
+        long = "health-listen-addr",
+        help = r#"
+If specified, the health of the generator is exposed as JSON on
+http://<addr>/healthz, responding with 503 if the last successful discovery or
+config write is older than `--health-max-age`.
 
 "#
     )]
-    metrics_listen_addr: SocketAddr,
+    health_listen_addr: Option<SocketAddr>,
+
+    #[clap(
+    long = "health-max-age",
+    parse(try_from_str = parse_duration),
+    help = r#"
+The generator is considered unhealthy if it did not discover targets and write
+configs successfully within this duration. When run by systemd with
+`WatchdogSec` set, watchdog notifications are only sent while the generator is
+healthy. [default: 10m]
+
+"#
+    )]
+    health_max_age: Option<Duration>,
 }
 impl CliArgs {
+    /// Fills in the settings that were not given on the command line from the
+    /// config file.
+    fn merge(mut self, config_file: ConfigFile) -> Self {
+        self.targets_dir = self.targets_dir.or(config_file.targets_dir);
+        self.poll_interval = self.poll_interval.or(config_file.poll_interval);
+        self.max_poll_interval = self.max_poll_interval.or(config_file.max_poll_interval);
+        self.registry_query_timeout = self
+            .registry_query_timeout
+            .or(config_file.registry_query_timeout);
+        self.generation_dir = self.generation_dir.or(config_file.generation_dir);
+        self.permissions.file_mode = self.permissions.file_mode.or(config_file.file_mode);
+        self.permissions.dir_mode = self.permissions.dir_mode.or(config_file.dir_mode);
+        self.permissions.owner = self.permissions.owner.or(config_file.file_owner);
+        self.permissions.group = self.permissions.group.or(config_file.file_group);
+        self.output_layout = self.output_layout.or(config_file.output_layout);
+        self.manifest_file = self.manifest_file.or(config_file.manifest_file);
+        self.probe.unreachable_targets_file = self
+            .probe
+            .unreachable_targets_file
+            .or(config_file.unreachable_targets_file);
+        self.probe.probe_interval = self.probe.probe_interval.or(config_file.probe_interval);
+        self.probe.probe_timeout = self.probe.probe_timeout.or(config_file.probe_timeout);
+        self.nns_url = self.nns_url.or(config_file.nns_url);
+        if self.jobs.is_empty() {
+            self.jobs = config_file.jobs;
+        }
+        self.filter_node_id_regex = self
+            .filter_node_id_regex
+            .or(config_file.filter_node_id_regex);
+        if self.target_filters.is_empty() {
+            self.target_filters = config_file.target_filters;
+        }
+        self.static_targets_file = self.static_targets_file.or(config_file.static_targets_file);
+        self.scrape_interval = self.scrape_interval.or(config_file.scrape_interval);
+        self.proxy_url = self.proxy_url.or(config_file.proxy_url);
+        self.logs_generation_dir = self.logs_generation_dir.or(config_file.logs_generation_dir);
+        self.batch_size = self.batch_size.or(config_file.batch_size);
+        self.cursors_dir = self.cursors_dir.or(config_file.cursors_dir);
+        self.parse_replica_logs = self.parse_replica_logs.or(config_file.parse_replica_logs);
+        if self.parse_replica_logs_jobs.is_empty() {
+            self.parse_replica_logs_jobs = config_file.parse_replica_logs_jobs;
+        }
+        if self.journald_units.is_empty() {
+            self.journald_units = config_file.journald_units;
+        }
+        self.source_template = self.source_template.or(config_file.source_template);
+        self.dashboards_generation_dir = self
+            .dashboards_generation_dir
+            .or(config_file.dashboards_generation_dir);
+        self.grafana_datasource_uid = self
+            .grafana_datasource_uid
+            .or(config_file.grafana_datasource_uid);
+        if self.ic_precedence.is_empty() {
+            self.ic_precedence = config_file.ic_precedence;
+        }
+        self.removal_grace_period = self
+            .removal_grace_period
+            .or(config_file.removal_grace_period);
+        self.max_registry_age = self.max_registry_age.or(config_file.max_registry_age);
+        self.log_format = self.log_format.or(config_file.log_format);
+        self.metrics_listen_addr = self.metrics_listen_addr.or(config_file.metrics_listen_addr);
+        self.http_sd_listen_addr = self.http_sd_listen_addr.or(config_file.http_sd_listen_addr);
+        self.grpc_listen_addr = self.grpc_listen_addr.or(config_file.grpc_listen_addr);
+        self.health_listen_addr = self.health_listen_addr.or(config_file.health_listen_addr);
+        self.health_max_age = self.health_max_age.or(config_file.health_max_age);
+        self
+    }
+
     fn validate(self) -> Result<Self> {
-        if !self.targets_dir.exists() {
-            bail!("Path does not exist: {:?}", self.targets_dir);
+        let targets_dir = match &self.targets_dir {
+            Some(targets_dir) => targets_dir,
+            None => bail!("Neither --targets-dir nor `targets_dir` in the config file is given"),
+        };
+
+        if !targets_dir.exists() {
+            bail!("Path does not exist: {:?}", targets_dir);
+        }
+
+        if !targets_dir.is_dir() {
+            bail!("Not a directory: {:?}", targets_dir);
+        }
+
+        let generation_dir = match &self.generation_dir {
+            Some(generation_dir) => generation_dir,
+            None => {
+                bail!("Neither --generation-dir nor `generation_dir` in the config file is given")
+            }
+        };
+
+        if !generation_dir.is_dir() {
+            bail!("Not a directory: {:?}", generation_dir)
+        }
+
+        if !self.parse_replica_logs.unwrap_or_default() && !self.parse_replica_logs_jobs.is_empty()
+        {
+            bail!("--parse-replica-logs-job requires --parse-replica-logs")
+        }
+
+        if let Some(logs_generation_dir) = &self.logs_generation_dir {
+            if !logs_generation_dir.is_dir() {
+                bail!("Not a directory: {:?}", logs_generation_dir)
+            }
         }
 
-        if !self.targets_dir.is_dir() {
-            bail!("Not a directory: {:?}", self.targets_dir);
+        if let Some(dashboards_generation_dir) = &self.dashboards_generation_dir {
+            if !dashboards_generation_dir.is_dir() {
+                bail!("Not a directory: {:?}", dashboards_generation_dir)
+            }
         }
 
-        if !self.generation_dir.is_dir() {
-            bail!("Not a directory: {:?}", self.generation_dir)
+        let known_jobs = get_jobs();
+        if let Some(job) = self.jobs.iter().find(|job| !known_jobs.contains_key(job)) {
+            bail!("Job not supported by this generator: {}", job)
         }
 
         Ok(self)
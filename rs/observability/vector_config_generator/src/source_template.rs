@@ -0,0 +1,185 @@
+//! User-provided [handlebars](https://handlebarsjs.com/) templates for the
+//! per-target vector sources, e.g.:
+//!
+//! ```yaml
+//! type: prometheus_scrape
+//! endpoints: {{json endpoints}}
+//! scrape_interval_secs: {{scrape_interval_secs}}
+//! scrape_timeout_secs: 5
+//! instance_tag: instance
+//! endpoint_tag: endpoint
+//! {{#if dc_id}}
+//! query:
+//!   dc: ["{{dc_id}}"]
+//! {{/if}}
+//! ```
+//!
+//! The rendered template is parsed as YAML (and hence JSON) and used as the
+//! source in place of the built-in `prometheus_scrape` one. The fields of
+//! [SourceContext] are available as variables; `{{json <value>}}` renders a
+//! value as JSON.
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::{bail, Context, Result};
+use handlebars::{handlebars_helper, no_escape, Handlebars};
+use serde::Serialize;
+use slog::{warn, Logger};
+
+use config_writer_common::vector_config_structure::VectorSource;
+
+const TEMPLATE_NAME: &str = "source";
+
+handlebars_helper!(json: |value: Json| serde_json::to_string(value).unwrap_or_default());
+
+/// The discovery fields of a target group that are injected into the
+/// template.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct SourceContext {
+    pub node_id: String,
+    pub ic_name: String,
+    pub subnet_id: Option<String>,
+    pub dc_id: Option<String>,
+    pub operator_id: Option<String>,
+    pub registry_version: Option<u64>,
+    pub job: String,
+    pub targets: Vec<String>,
+    /// The URLs the built-in source would scrape.
+    pub endpoints: Vec<String>,
+    pub scrape_interval_secs: u64,
+    pub proxy_url: Option<String>,
+    pub custom_labels: BTreeMap<String, String>,
+}
+
+pub struct SourceTemplate {
+    handlebars: Handlebars<'static>,
+    log: Logger,
+}
+
+impl SourceTemplate {
+    /// Loads the template from `path` and checks that it renders to a YAML
+    /// mapping for a sample target.
+    pub fn load(path: &Path, log: Logger) -> Result<Self> {
+        let template = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read source template {:?}", path))?;
+        let template = Self::new(&template, log)
+            .with_context(|| format!("Invalid source template {:?}", path))?;
+        template
+            .render(&SourceContext {
+                node_id: "sample-node".into(),
+                ic_name: "mercury".into(),
+                job: "replica".into(),
+                targets: vec!["[::1]:9090".into()],
+                endpoints: vec!["http://[::1]:9090/".into()],
+                scrape_interval_secs: 30,
+                ..Default::default()
+            })
+            .with_context(|| format!("Failed to render source template {:?}", path))?;
+        Ok(template)
+    }
+
+    fn new(template: &str, log: Logger) -> Result<Self> {
+        let mut handlebars = Handlebars::new();
+        handlebars.set_strict_mode(true);
+        // the output is YAML, not HTML
+        handlebars.register_escape_fn(no_escape);
+        handlebars.register_helper("json", Box::new(json));
+        handlebars.register_template_string(TEMPLATE_NAME, template)?;
+        Ok(Self { handlebars, log })
+    }
+
+    fn render(&self, context: &SourceContext) -> Result<serde_json::Value> {
+        let rendered = self.handlebars.render(TEMPLATE_NAME, context)?;
+        let source: serde_json::Value = serde_yaml::from_str(&rendered)?;
+        if !source.is_object() {
+            bail!("Rendered source is not a mapping: {}", rendered);
+        }
+        Ok(source)
+    }
+
+    /// Returns the rendered source for the target group described by
+    /// `context`. If rendering fails, e.g. because a value makes the output
+    /// invalid YAML, the error is logged and `None` is returned, such that the
+    /// built-in source is used instead.
+    pub fn source(&self, context: &SourceContext) -> Option<Box<dyn VectorSource>> {
+        match self.render(context) {
+            Ok(source) => Some(Box::new(TemplatedSource(source))),
+            Err(e) => {
+                warn!(
+                    self.log,
+                    "Failed to render source template for node {}: {:?}", context.node_id, e
+                );
+                None
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(transparent)]
+pub struct TemplatedSource(serde_json::Value);
+
+impl VectorSource for TemplatedSource {
+    fn clone_dyn(&self) -> Box<dyn VectorSource> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use slog::o;
+
+    use super::{SourceContext, SourceTemplate};
+
+    fn context() -> SourceContext {
+        SourceContext {
+            node_id: "node-1".into(),
+            ic_name: "mercury".into(),
+            dc_id: Some("zh2".into()),
+            job: "replica".into(),
+            endpoints: vec!["http://[::1]:9090/".into()],
+            scrape_interval_secs: 30,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn template_renders_discovery_fields() {
+        let template = SourceTemplate::new(
+            r#"
+type: prometheus_scrape
+endpoints: {{json endpoints}}
+scrape_interval_secs: {{scrape_interval_secs}}
+{{#if dc_id}}
+query:
+  dc: ["{{dc_id}}"]
+{{/if}}
+"#,
+            slog::Logger::root(slog::Discard, o!()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            template.render(&context()).unwrap(),
+            json!({
+                "type": "prometheus_scrape",
+                "endpoints": ["http://[::1]:9090/"],
+                "scrape_interval_secs": 30,
+                "query": {"dc": ["zh2"]},
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_templates_are_rejected() {
+        let log = slog::Logger::root(slog::Discard, o!());
+        assert!(SourceTemplate::new("{{#if}}", log.clone()).is_err());
+
+        let template = SourceTemplate::new("{{unknown_field}}", log.clone()).unwrap();
+        assert!(template.render(&context()).is_err());
+        assert!(template.source(&context()).is_none());
+
+        let template = SourceTemplate::new("just a string", log).unwrap();
+        assert!(template.render(&context()).is_err());
+    }
+}
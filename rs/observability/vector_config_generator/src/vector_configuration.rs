@@ -1,4 +1,7 @@
-use std::collections::{BTreeSet, HashMap};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Arc,
+};
 
 use serde::Serialize;
 
@@ -11,12 +14,16 @@ use config_writer_common::{
 use service_discovery::{job_types::JobType, TargetGroup};
 use url::Url;
 
-use crate::JobParameters;
+use crate::{
+    source_template::{SourceContext, SourceTemplate},
+    JobParameters,
+};
 
 pub struct VectorConfigBuilderImpl {
     proxy_url: Option<Url>,
     scrape_interval: u64,
     jobs_parameters: HashMap<JobType, JobParameters>,
+    source_template: Option<Arc<SourceTemplate>>,
 }
 
 impl VectorConfigBuilderImpl {
@@ -29,9 +36,17 @@ impl VectorConfigBuilderImpl {
             proxy_url,
             scrape_interval,
             jobs_parameters,
+            source_template: None,
         }
     }
 
+    /// Renders the sources from `source_template` instead of using the
+    /// built-in `prometheus_scrape` source.
+    pub fn with_source_template(mut self, source_template: Option<Arc<SourceTemplate>>) -> Self {
+        self.source_template = source_template;
+        self
+    }
+
     fn add_target_groups_with_job(
         &self,
         targets: BTreeSet<TargetGroup>,
@@ -47,18 +62,57 @@ impl VectorConfigBuilderImpl {
                 .next()
                 .unwrap();
 
-            let source = VectorPrometheusScrapeSource::from_target_group_with_job(
-                target.clone(),
-                self.jobs_parameters.get(&job).unwrap(),
-                self.scrape_interval,
-                self.proxy_url.as_ref().cloned(),
-            );
+            let job_parameters = self.jobs_parameters.get(&job).unwrap();
+            let templated_source = self.source_template.as_ref().and_then(|template| {
+                template.source(&self.source_context(&target, job, job_parameters))
+            });
+            let source = templated_source.unwrap_or_else(|| {
+                Box::new(VectorPrometheusScrapeSource::from_target_group_with_job(
+                    target.clone(),
+                    job_parameters,
+                    self.scrape_interval,
+                    self.proxy_url.as_ref().cloned(),
+                ))
+            });
             let transform =
                 VectorPrometheusScrapeTransform::from_target_group_with_job(target, &job);
-            config.add_target_group(key, Box::new(source), Box::new(transform))
+            config.add_target_group(key, source, Box::new(transform))
         }
         config
     }
+
+    fn source_context(
+        &self,
+        tg: &TargetGroup,
+        job: JobType,
+        job_parameters: &JobParameters,
+    ) -> SourceContext {
+        SourceContext {
+            node_id: tg.node_id.to_string(),
+            ic_name: tg.ic_name.clone(),
+            subnet_id: tg.subnet_id.map(|id| id.to_string()),
+            dc_id: tg.dc_id.clone(),
+            operator_id: tg.operator_id.map(|id| id.to_string()),
+            registry_version: tg.registry_version.map(|v| v.get()),
+            job: job.to_string(),
+            targets: tg.targets.iter().map(|t| t.to_string()).collect(),
+            endpoints: endpoints(tg, job_parameters),
+            scrape_interval_secs: self.scrape_interval,
+            proxy_url: self.proxy_url.as_ref().map(|url| url.to_string()),
+            custom_labels: tg.custom_labels.clone(),
+        }
+    }
+}
+
+/// Returns the URLs to scrape the targets of `tg` at.
+fn endpoints(tg: &TargetGroup, job_parameters: &JobParameters) -> Vec<String> {
+    tg.targets
+        .iter()
+        .map(|g| g.to_string())
+        .map(|g| format!("http://{}{}", g, job_parameters.endpoint))
+        .map(|g| url::Url::parse(&g).unwrap())
+        .map(|g| g.to_string())
+        .collect()
 }
 
 impl VectorConfigBuilder for VectorConfigBuilderImpl {
@@ -92,14 +146,7 @@ impl VectorPrometheusScrapeSource {
         scrape_interval: u64,
         proxy_url: Option<Url>,
     ) -> Self {
-        let endpoints: Vec<String> = tg
-            .targets
-            .into_iter()
-            .map(|g| g.to_string())
-            .map(|g| format!("http://{}{}", g, job_parameters.endpoint))
-            .map(|g| url::Url::parse(&g).unwrap())
-            .map(|g| g.to_string())
-            .collect();
+        let endpoints = endpoints(&tg, job_parameters);
 
         // TODO Pass URL through args
 
@@ -150,12 +197,18 @@ impl VectorTransform for VectorPrometheusScrapeTransform {
 
 impl VectorPrometheusScrapeTransform {
     fn from_target_group_with_job(tg: TargetGroup, job: &JobType) -> Self {
-        let mut labels: HashMap<String, String> = HashMap::new();
+        let mut labels: BTreeMap<String, String> = tg.custom_labels.clone();
         labels.insert(labels_keys::IC_NAME.into(), tg.ic_name);
         labels.insert(labels_keys::IC_NODE.into(), tg.node_id.to_string());
         if let Some(subnet_id) = tg.subnet_id {
             labels.insert(labels_keys::IC_SUBNET.into(), subnet_id.to_string());
         }
+        if let Some(registry_version) = tg.registry_version {
+            labels.insert(
+                labels_keys::IC_REGISTRY_VERSION.into(),
+                registry_version.to_string(),
+            );
+        }
         labels.insert(labels_keys::JOB.into(), job.to_string());
         Self {
             _type: "remap".into(),
@@ -180,7 +233,6 @@ impl VectorPrometheusScrapeTransform {
 mod tests {
     use std::{
         collections::{BTreeSet, HashMap},
-        net::{SocketAddr, SocketAddrV6},
         str::FromStr,
     };
 
@@ -188,7 +240,7 @@ mod tests {
     use ic_types::{NodeId, PrincipalId, SubnetId};
     use service_discovery::{
         job_types::{JobType, NodeOS},
-        TargetGroup,
+        test_utils::target_group,
     };
 
     use crate::{Job, JobParameters};
@@ -199,30 +251,22 @@ mod tests {
     fn try_from_prometheus_target_group_to_vector_config_correct_inputs() {
         let original_addr = "[2a02:800:2:2003:5000:f6ff:fec4:4c86]:9091";
         let sources_key = String::from(original_addr) + "-source";
-        let mut targets = BTreeSet::new();
-
-        targets.insert(SocketAddr::V6(
-            SocketAddrV6::from_str(original_addr).unwrap(),
-        ));
 
-        let ptg = TargetGroup {
-            node_id: NodeId::from(
+        let ptg = target_group(0)
+            .node_id(NodeId::from(
                 PrincipalId::from_str(
                     "iylgr-zpxwq-kqgmf-4srtx-o4eey-d6bln-smmq6-we7px-ibdea-nondy-eae",
                 )
                 .unwrap(),
-            ),
-            ic_name: "mercury".into(),
-            targets,
-            subnet_id: Some(SubnetId::from(
+            ))
+            .target(original_addr)
+            .subnet_id(SubnetId::from(
                 PrincipalId::from_str(
                     "x33ed-h457x-bsgyx-oqxqf-6pzwv-wkhzr-rm2j3-npodi-purzm-n66cg-gae",
                 )
                 .unwrap(),
-            )),
-            dc_id: None,
-            operator_id: None,
-        };
+            ))
+            .build();
 
         let mut tg_set = BTreeSet::new();
         tg_set.insert(ptg);
@@ -1,5 +1,8 @@
 use clap::Parser;
-use ic_config::{logger::LogFormat, registry_client::DataProviderConfig, Config};
+use ic_config::{
+    logger::LogFormat, nns_registry_replicator::MIN_LOCAL_STORE_RETAINED_VERSIONS,
+    registry_client::DataProviderConfig, Config,
+};
 use slog::Level;
 use std::{
     net::{Ipv4Addr, SocketAddr, SocketAddrV4},
@@ -48,6 +51,22 @@ pub struct RegistryReplicatorArgs {
     /// The delay between NNS polls in milliseconds
     #[clap(long, default_value = "5000")]
     pub poll_delay_duration_ms: u64,
+
+    /// If set, compact the local store history older than this number of
+    /// versions, which must be at least 10000
+    #[clap(long, parse(try_from_str = parse_retained_versions))]
+    pub local_store_retained_versions: Option<u64>,
+}
+
+fn parse_retained_versions(s: &str) -> Result<u64, String> {
+    let retained_versions = s.parse::<u64>().map_err(|e| e.to_string())?;
+    if retained_versions < MIN_LOCAL_STORE_RETAINED_VERSIONS {
+        return Err(format!(
+            "must be at least {}, such that readers of the local store do not lag behind the compacted versions",
+            MIN_LOCAL_STORE_RETAINED_VERSIONS
+        ));
+    }
+    Ok(retained_versions)
 }
 
 impl RegistryReplicatorArgs {
@@ -71,6 +90,8 @@ impl RegistryReplicatorArgs {
             self.local_store_path.clone(),
         ));
         config.nns_registry_replicator.poll_delay_duration_ms = self.poll_delay_duration_ms;
+        config.nns_registry_replicator.local_store_retained_versions =
+            self.local_store_retained_versions;
 
         (config, _dir)
     }
@@ -17,7 +17,9 @@ use ic_registry_keys::{
     make_routing_table_record_key, make_subnet_list_record_key, make_subnet_record_key,
     ROOT_SUBNET_ID_KEY,
 };
-use ic_registry_local_store::{Changelog, ChangelogEntry, KeyMutation, LocalStore};
+use ic_registry_local_store::{
+    read_changelog, restore_changelog, Changelog, ChangelogEntry, KeyMutation, LocalStore,
+};
 use ic_registry_nns_data_provider::registry::RegistryCanister;
 use ic_registry_routing_table::{CanisterIdRange, RoutingTable};
 use ic_types::{crypto::threshold_sig::ThresholdSigPublicKey, Time};
@@ -252,14 +254,13 @@ impl InternalState {
         };
 
         // IOErrors are treated as fatal.
-        let mut changelog = self
-            .local_store
-            .get_changelog_since_version(RegistryVersion::from(0))
-            .expect("Could not read changelog from disk.");
-        changelog.truncate(k.get() as usize);
+        let (version, mut changelog) =
+            read_changelog(self.local_store.as_ref()).expect("Could not read changelog from disk.");
+        changelog.truncate(k.get().saturating_sub(version.get()) as usize);
 
         self.apply_switch_over_to_last_changelog_entry(
             changelog.as_mut_slice(),
+            k,
             subnet_id,
             subnet_record,
         );
@@ -267,11 +268,8 @@ impl InternalState {
             .clear()
             .expect("Could not clear registry local store");
 
-        for (v, cle) in changelog.into_iter().enumerate() {
-            self.local_store
-                .store(RegistryVersion::from((v + 1) as u64), cle)
-                .expect("Could not store change log entry");
-        }
+        restore_changelog(self.local_store.as_ref(), version, changelog)
+            .expect("Could not store change log entry");
 
         warn!(
             self.logger,
@@ -280,8 +278,9 @@ impl InternalState {
         std::process::exit(1);
     }
 
-    /// Given a `changelog`, this function adjusts the following entries of the
-    /// last registry changelog entry:
+    /// Given a `changelog` ending with the entry of `registry_version`, this
+    /// function adjusts the following entries of the last registry changelog
+    /// entry:
     /// * Update subnet type to be `system`
     /// * Update root subnet ID to be new NNS subnet ID
     /// * Assign canister ranges of old NNS to new NNS in routing table
@@ -289,11 +288,11 @@ impl InternalState {
     fn apply_switch_over_to_last_changelog_entry(
         &self,
         changelog: &mut [ChangelogEntry],
+        registry_version: RegistryVersion,
         new_nns_subnet_id: SubnetId,
         mut new_nns_subnet_record: SubnetRecord,
     ) {
         use prost::Message;
-        let registry_version = RegistryVersion::from(changelog.len() as u64);
 
         let routing_table = self
             .registry_client
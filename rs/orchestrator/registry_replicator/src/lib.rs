@@ -30,14 +30,20 @@
 
 use crate::internal_state::InternalState;
 use ic_config::metrics::{Config as MetricsConfig, Exporter};
-use ic_config::{registry_client::DataProviderConfig, Config};
+use ic_config::{
+    nns_registry_replicator::MIN_LOCAL_STORE_RETAINED_VERSIONS,
+    registry_client::DataProviderConfig, Config,
+};
 use ic_crypto_utils_threshold_sig_der::parse_threshold_sig_key;
 use ic_http_endpoints_metrics::MetricsHttpEndpoint;
 use ic_interfaces_registry::{RegistryClient, RegistryDataProvider, ZERO_REGISTRY_VERSION};
 use ic_logger::{debug, info, warn, ReplicaLogger};
 use ic_metrics::MetricsRegistry;
 use ic_registry_client::client::RegistryClientImpl;
-use ic_registry_local_store::{Changelog, ChangelogEntry, KeyMutation, LocalStore, LocalStoreImpl};
+use ic_registry_local_store::{
+    read_changelog, restore_changelog, Changelog, ChangelogEntry, KeyMutation, LocalStore,
+    LocalStoreImpl,
+};
 use ic_registry_nns_data_provider::registry::RegistryCanister;
 use ic_types::crypto::threshold_sig::ThresholdSigPublicKey;
 use ic_types::{NodeId, RegistryVersion};
@@ -62,6 +68,8 @@ pub struct RegistryReplicator {
     started: Arc<AtomicBool>,
     cancelled: Arc<AtomicBool>,
     poll_delay: Duration,
+    /// See [ic_config::nns_registry_replicator::Config::local_store_retained_versions].
+    retained_versions: Option<u64>,
     metrics: Arc<RegistryreplicatorMetrics>,
 }
 
@@ -82,10 +90,24 @@ impl RegistryReplicator {
             started: Arc::new(AtomicBool::new(false)),
             cancelled: Arc::new(AtomicBool::new(false)),
             poll_delay,
+            retained_versions: None,
             metrics,
         }
     }
 
+    /// Compact the local store while polling, such that only the latest
+    /// `retained_versions` versions are kept in full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `retained_versions` is below
+    /// [MIN_LOCAL_STORE_RETAINED_VERSIONS].
+    pub fn with_retained_versions(mut self, retained_versions: u64) -> Self {
+        Self::check_retained_versions(retained_versions);
+        self.retained_versions = Some(retained_versions);
+        self
+    }
+
     pub fn new_from_config(
         logger: ReplicaLogger,
         node_id: Option<NodeId>,
@@ -105,6 +127,11 @@ impl RegistryReplicator {
         let poll_delay =
             std::time::Duration::from_millis(config.nns_registry_replicator.poll_delay_duration_ms);
 
+        let retained_versions = config.nns_registry_replicator.local_store_retained_versions;
+        if let Some(retained_versions) = retained_versions {
+            Self::check_retained_versions(retained_versions);
+        }
+
         // Initialize registry client and start polling/caching *local* store for
         // updates
         let registry_client = Self::initialize_registry_client(local_store.clone());
@@ -119,6 +146,7 @@ impl RegistryReplicator {
             started: Arc::new(AtomicBool::new(false)),
             cancelled: Arc::new(AtomicBool::new(false)),
             poll_delay,
+            retained_versions,
             metrics,
         }
     }
@@ -217,10 +245,9 @@ impl RegistryReplicator {
         nns_pub_key: Option<ThresholdSigPublicKey>,
     ) {
         // If the local registry store is not empty, exit.
-        if !self
-            .local_store
-            .get_changelog_since_version(ZERO_REGISTRY_VERSION)
+        if !read_changelog(self.local_store.as_ref())
             .expect("Could not read registry local store.")
+            .1
             .is_empty()
         {
             info!(
@@ -341,8 +368,11 @@ impl RegistryReplicator {
         let registry_client = self.registry_client.clone();
         let cancelled = Arc::clone(&self.cancelled);
         let poll_delay = self.poll_delay;
+        let local_store = self.local_store.clone();
+        let retained_versions = self.retained_versions;
         info!(logger, "Spawning background thread.");
         let handle = tokio::spawn(async move {
+            let mut compacted_version = ZERO_REGISTRY_VERSION;
             while !cancelled.load(Ordering::Relaxed) {
                 let timer = metrics.poll_duration.start_timer();
                 // The relevant I/O-operation of the poll() function is querying
@@ -361,29 +391,70 @@ impl RegistryReplicator {
                 metrics
                     .registry_version
                     .set(registry_client.get_latest_version().get() as i64);
+                if let Some(retained) = retained_versions {
+                    Self::compact_local_store(
+                        &logger,
+                        local_store.as_ref(),
+                        registry_client.get_latest_version(),
+                        retained,
+                        &mut compacted_version,
+                    );
+                }
                 tokio::time::sleep(poll_delay).await;
             }
         });
         Ok(handle)
     }
 
+    /// Refuses to compact the local store such that running readers lag behind
+    /// the compacted versions.
+    fn check_retained_versions(retained_versions: u64) {
+        assert!(
+            retained_versions >= MIN_LOCAL_STORE_RETAINED_VERSIONS,
+            "The local store must retain at least {} versions, but {} were configured.",
+            MIN_LOCAL_STORE_RETAINED_VERSIONS,
+            retained_versions
+        );
+    }
+
+    /// Compacts the local store below `latest_version` minus `retained`, once
+    /// `retained` versions were added since the `compacted_version`.
+    fn compact_local_store(
+        logger: &ReplicaLogger,
+        local_store: &dyn LocalStore,
+        latest_version: RegistryVersion,
+        retained: u64,
+        compacted_version: &mut RegistryVersion,
+    ) {
+        let version = RegistryVersion::from(latest_version.get().saturating_sub(retained));
+        if version.get() < compacted_version.get() + retained.max(1) {
+            return;
+        }
+        match local_store.compact(version) {
+            Ok(()) => {
+                info!(logger, "Compacted the local store at version {}.", version);
+                *compacted_version = version;
+            }
+            Err(e) => warn!(
+                logger,
+                "Failed to compact the local store at version {}: {:?}", version, e
+            ),
+        }
+    }
+
     /// Set the local registry data to what is contained in the provided local
     /// store.
     fn set_local_registry_data(&self, source_registry: &dyn LocalStore) {
         // Read the registry data.
-        let changelog = source_registry
-            .get_changelog_since_version(RegistryVersion::from(0))
+        let (version, changelog) = read_changelog(source_registry)
             .expect("Could not read changelog from source registry.");
 
         // Reset the local store and fill it with the read registry data.
         self.local_store
             .clear()
             .expect("Could not clear registry local store");
-        for (v, cle) in changelog.into_iter().enumerate() {
-            self.local_store
-                .store(RegistryVersion::from((v + 1) as u64), cle)
-                .expect("Could not store change log entry");
-        }
+        restore_changelog(self.local_store.as_ref(), version, changelog)
+            .expect("Could not store change log entry");
     }
 
     pub fn stop_polling_and_set_local_registry_data(&self, source_registry: &dyn LocalStore) {
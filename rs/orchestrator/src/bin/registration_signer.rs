@@ -0,0 +1,115 @@
+//! Signs the registration requests of nodes registered without attaching the
+//! HSM, see `registration.offline_registration_dir` in the replica config.
+//!
+//! The orchestrator exports the request of the node to
+//! `<offline_registration_dir>/registration_request.json`. On a disconnected
+//! machine with the HSM attached, the operator signs it with
+//!
+//! ```text
+//! registration-signer sign --request registration_request.json \
+//!     --output registration_request.signed.json
+//! ```
+//!
+//! and imports the output into the same directory, after which the node
+//! registers itself.
+//!
+//! The HSM PIN is never passed as an argument, where other users could see it
+//! in the process list. It is read from the file given by `--hsm-pin-file` or
+//! from the environment variable `HSM_PIN`, and signing fails if neither is
+//! set: there is no default PIN.
+use clap::Parser;
+use ic_types::time::current_time;
+use orchestrator::signer::{read_json, sign_registration_request, write_json, RegistrationRequest};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The environment variable with the HSM PIN if no `--hsm-pin-file` is given
+const HSM_PIN_ENV_VAR: &str = "HSM_PIN";
+
+#[derive(Debug, Parser)]
+#[clap(
+    name = "registration-signer",
+    about = "Signs node registration requests with an HSM attached to this machine.",
+    version
+)]
+enum Command {
+    /// Sign the registration request exported by a node.
+    Sign(SignArgs),
+}
+
+#[derive(Debug, Parser)]
+struct SignArgs {
+    /// The registration request exported by the node
+    #[clap(long, parse(from_os_str))]
+    request: PathBuf,
+
+    /// Where to write the signed registration request
+    #[clap(long, parse(from_os_str))]
+    output: PathBuf,
+
+    /// How many days the node can register itself with the signed request
+    #[clap(long, default_value = "7")]
+    valid_for_days: u64,
+
+    /// The HSM slot, defaults to the pre-agreed one
+    #[clap(long)]
+    hsm_slot: Option<String>,
+
+    /// A file with the HSM PIN, whose trailing newline is ignored. Without it,
+    /// the PIN is read from the environment variable HSM_PIN, which is then
+    /// required.
+    #[clap(long, parse(from_os_str))]
+    hsm_pin_file: Option<PathBuf>,
+
+    /// The ID of the HSM key, defaults to the pre-agreed one
+    #[clap(long)]
+    hsm_key_id: Option<String>,
+}
+
+fn main() -> Result<(), String> {
+    match Command::parse() {
+        Command::Sign(args) => {
+            let request: RegistrationRequest =
+                read_json(&args.request).map_err(|e| e.to_string())?;
+            println!(
+                "Signing the registration request of node {}.",
+                request.node_id
+            );
+            let hsm_pin = read_hsm_pin(args.hsm_pin_file.as_deref())?;
+            let expiration =
+                current_time() + Duration::from_secs(args.valid_for_days * 24 * 60 * 60);
+            let signed = sign_registration_request(
+                request,
+                expiration,
+                args.hsm_slot.as_deref(),
+                Some(hsm_pin.as_str()),
+                args.hsm_key_id.as_deref(),
+            )
+            .map_err(|e| format!("Failed to sign with the HSM: {}", e))?;
+            write_json(&args.output, &signed, 0o644).map_err(|e| e.to_string())?;
+            println!(
+                "Wrote the signed registration request to {:?}, valid until {}.",
+                args.output, expiration
+            );
+            Ok(())
+        }
+    }
+}
+
+fn read_hsm_pin(pin_file: Option<&Path>) -> Result<String, String> {
+    let pin = match pin_file {
+        Some(path) => std::fs::read_to_string(path)
+            .map(|pin| pin.trim_end_matches(&['\r', '\n'][..]).to_string())
+            .map_err(|e| format!("Failed to read the HSM PIN from {:?}: {}", path, e))?,
+        None => std::env::var(HSM_PIN_ENV_VAR).map_err(|e| {
+            format!(
+                "The HSM PIN is required, pass --hsm-pin-file or set {}: {}",
+                HSM_PIN_ENV_VAR, e
+            )
+        })?,
+    };
+    if pin.is_empty() {
+        return Err("The HSM PIN must not be empty".to_string());
+    }
+    Ok(pin)
+}
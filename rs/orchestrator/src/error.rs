@@ -1,5 +1,6 @@
 use ic_http_utils::file_downloader::FileDownloadError;
 use ic_image_upgrader::error::UpgradeError;
+use ic_sys::utility_command::UtilityCommandError;
 use ic_types::replica_version::ReplicaVersionParseError;
 use ic_types::{registry::RegistryClientError, NodeId, RegistryVersion, ReplicaVersion, SubnetId};
 use std::error::Error;
@@ -51,6 +52,24 @@ pub enum OrchestratorError {
 
     /// Generic error while handling reboot time
     RebootTimeError(String),
+
+    /// A utility command, e.g., the one signing with the HSM, failed
+    UtilityCommandError(UtilityCommandError),
+
+    /// The given file does not contain what it is expected to contain
+    MalformedFileError(PathBuf, String),
+
+    /// The signed registration request has not been imported to the given path
+    /// yet
+    SignedRegistrationRequestMissingError(PathBuf),
+
+    /// The signed registration request at the given path does not sign the
+    /// request of this node and its current session key
+    SignedRegistrationRequestMismatchError(PathBuf),
+
+    /// The delegation of the signed registration request at the given path
+    /// expired
+    SignedRegistrationRequestExpiredError(PathBuf),
 }
 
 impl OrchestratorError {
@@ -61,6 +80,10 @@ impl OrchestratorError {
     pub(crate) fn invalid_configuration_error(msg: impl ToString) -> Self {
         OrchestratorError::InvalidConfigurationError(msg.to_string())
     }
+
+    pub(crate) fn file_read_error(file_path: &Path, e: io::Error) -> Self {
+        OrchestratorError::IoError(format!("Failed to read file: {:?}", file_path), e)
+    }
 }
 
 impl fmt::Display for OrchestratorError {
@@ -108,6 +131,25 @@ impl fmt::Display for OrchestratorError {
                 subnet_id, registry_version,
             ),
             OrchestratorError::UpgradeError(msg) => write!(f, "Failed to upgrade: {}", msg),
+            OrchestratorError::UtilityCommandError(e) => write!(f, "{}", e),
+            OrchestratorError::MalformedFileError(path, msg) => {
+                write!(f, "Malformed file {:?}: {}", path, msg)
+            }
+            OrchestratorError::SignedRegistrationRequestMissingError(path) => write!(
+                f,
+                "The signed registration request has not been imported to {:?} yet",
+                path
+            ),
+            OrchestratorError::SignedRegistrationRequestMismatchError(path) => write!(
+                f,
+                "{:?} signs the request of another node or session key",
+                path
+            ),
+            OrchestratorError::SignedRegistrationRequestExpiredError(path) => write!(
+                f,
+                "The delegation of {:?} expired, the request must be signed again",
+                path
+            ),
         }
     }
 }
@@ -118,6 +160,12 @@ impl From<FileDownloadError> for OrchestratorError {
     }
 }
 
+impl From<UtilityCommandError> for OrchestratorError {
+    fn from(e: UtilityCommandError) -> Self {
+        OrchestratorError::UtilityCommandError(e)
+    }
+}
+
 impl From<UpgradeError> for OrchestratorError {
     fn from(e: UpgradeError) -> Self {
         match e {
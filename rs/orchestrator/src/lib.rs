@@ -42,6 +42,6 @@ pub mod orchestrator;
 mod registration;
 mod registry_helper;
 mod replica_process;
-mod signer;
+pub mod signer;
 mod ssh_access_manager;
 mod upgrade;
@@ -2,7 +2,7 @@
 use crate::{
     error::{OrchestratorError, OrchestratorResult},
     metrics::{KeyRotationStatus, OrchestratorMetrics},
-    signer::{Hsm, NodeProviderSigner, Signer},
+    signer::{Hsm, NodeProviderSigner, OfflineSigner, Signer},
 };
 use candid::Encode;
 use ic_canister_client::{Agent, Sender};
@@ -25,7 +25,7 @@ use ic_registry_client_helpers::{
     subnet::{SubnetRegistry, SubnetTransportRegistry},
 };
 use ic_registry_local_store::LocalStore;
-use ic_sys::utility_command::UtilityCommand;
+use ic_sys::utility_command::{UtilityCommand, UtilityCommandError};
 use ic_types::{crypto::KeyPurpose, messages::MessageId, NodeId, RegistryVersion, SubnetId};
 use prost::Message;
 use rand::prelude::*;
@@ -54,7 +54,8 @@ pub(crate) struct NodeRegistration {
 
 impl NodeRegistration {
     /// If the PEM is present, use the NodeProviderSigner.
-    /// Else, use the HSM.
+    /// Else, if an offline registration directory is configured, use the
+    /// OfflineSigner. Else, use the HSM.
     pub(crate) fn new(
         log: ReplicaLogger,
         node_config: Config,
@@ -73,7 +74,10 @@ impl NodeRegistration {
             .and_then(|path| NodeProviderSigner::new(path.as_path()))
         {
             Some(signer) => Box::new(signer),
-            None => Box::new(Hsm),
+            None => match node_config.registration.offline_registration_dir.clone() {
+                Some(dir) => Box::new(OfflineSigner::new(dir, node_id)),
+                None => Box::new(Hsm),
+            },
         };
         Self {
             log,
@@ -132,6 +136,23 @@ impl NodeRegistration {
                         warn!(self.log, "Registration request failed: {:?}", e);
                     };
                 }
+                Err(OrchestratorError::UtilityCommandError(
+                    UtilityCommandError::BinaryNotFound { program },
+                )) => {
+                    warn!(
+                        self.log,
+                        "Failed to create the message signer, {} is not installed.", program
+                    );
+                }
+                Err(OrchestratorError::UtilityCommandError(UtilityCommandError::Timeout {
+                    ..
+                })) => {
+                    UtilityCommand::notify_host("The HSM did not respond, please re-insert it.", 1);
+                    warn!(
+                        self.log,
+                        "Failed to create the message signer: the HSM did not respond."
+                    );
+                }
                 Err(e) => {
                     warn!(self.log, "Failed to create the message signer: {:?}", e);
                 }
@@ -707,6 +728,7 @@ mod tests {
         use ic_crypto_tls_interfaces::AuthenticatedPeer;
         use ic_crypto_tls_interfaces::TlsClientHandshakeError;
         use ic_crypto_tls_interfaces::TlsHandshake;
+        use ic_crypto_tls_interfaces::TlsPublicKeyCert;
         use ic_crypto_tls_interfaces::TlsServerHandshakeError;
         use ic_crypto_tls_interfaces::TlsStream;
         use ic_interfaces::crypto::IDkgDealingEncryptionKeyRotationError;
@@ -772,6 +794,13 @@ mod tests {
                     signer: NodeId,
                     registry_version: RegistryVersion,
                 ) -> CryptoResult<BasicSigOf<MessageId>>;
+
+                fn sign_basic_batch(
+                    &self,
+                    messages: &[MessageId],
+                    signer: NodeId,
+                    registry_version: RegistryVersion,
+                ) -> CryptoResult<Vec<BasicSigOf<MessageId>>>;
             }
 
             pub trait ThresholdSigVerifierByPublicKey<CatchUpContentProtobufBytes> {
@@ -791,6 +820,7 @@ mod tests {
                     tcp_stream: TcpStream,
                     allowed_clients: AllowedClients,
                     registry_version: RegistryVersion,
+                    alpn_protocols: Vec<Vec<u8>>,
                 ) -> Result<(Box<dyn TlsStream>, AuthenticatedPeer), TlsServerHandshakeError>;
 
                 async fn perform_tls_server_handshake_without_client_auth(
@@ -804,6 +834,13 @@ mod tests {
                     tcp_stream: TcpStream,
                     server: NodeId,
                     registry_version: RegistryVersion,
+                    alpn_protocols: Vec<Vec<u8>>,
+                ) -> Result<Box<dyn TlsStream>, TlsClientHandshakeError>;
+
+                async fn perform_tls_client_handshake_with_pinned_cert(
+                    &self,
+                    tcp_stream: TcpStream,
+                    server_cert: TlsPublicKeyCert,
                 ) -> Result<Box<dyn TlsStream>, TlsClientHandshakeError>;
             }
         }
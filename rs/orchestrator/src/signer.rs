@@ -1,25 +1,48 @@
+use crate::error::{OrchestratorError, OrchestratorResult};
 use ic_canister_client::Sender;
-use ic_canister_client_sender::{Secp256k1KeyPair, SigKeys};
+use ic_canister_client_sender::{ed25519_public_key_to_der, Ed25519KeyPair, Secp256k1KeyPair};
+use ic_nns_constants::REGISTRY_CANISTER_ID;
 use ic_sys::utility_command::{UtilityCommand, UtilityCommandResult};
-use std::path::Path;
+use ic_types::{
+    crypto::Signable,
+    messages::{Delegation, SignedDelegation},
+    time::current_time,
+    NodeId, Time,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// The registration request exported by the orchestrator in the offline
+/// registration directory.
+pub const REGISTRATION_REQUEST_FILE: &str = "registration_request.json";
+/// The signed registration request the operator imports into the offline
+/// registration directory.
+pub const SIGNED_REGISTRATION_REQUEST_FILE: &str = "registration_request.signed.json";
+/// The session key of the node, which never leaves the offline registration
+/// directory.
+const SESSION_KEY_FILE: &str = "session_key.json";
+
 /// An abstract message signer interface.
 pub trait Signer: Send + Sync {
     /// Returns the message signer bundle containing the public key and a signing command. This
     /// object is intended to be used with an agent to send messages to IC canisters.
-    fn get(&self) -> UtilityCommandResult<Sender>;
+    fn get(&self) -> OrchestratorResult<Sender>;
 }
 
 pub struct Hsm;
 
 impl Signer for Hsm {
-    fn get(&self) -> UtilityCommandResult<Sender> {
+    fn get(&self) -> OrchestratorResult<Sender> {
         UtilityCommand::notify_host("Starting node registration.", 1);
         UtilityCommand::notify_host("Attaching HSM.", 1);
         UtilityCommand::try_to_attach_hsm();
-        let pub_key = UtilityCommand::read_public_key(None, None).execute()?;
+        let pub_key = read_hsm_public_key(None, None);
         UtilityCommand::try_to_detach_hsm();
+        let pub_key = pub_key?;
         fn get_sign_command(msg: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
             UtilityCommand::notify_host("Attaching HSM.", 1);
             UtilityCommand::try_to_attach_hsm();
@@ -51,9 +74,283 @@ impl NodeProviderSigner {
 }
 
 impl Signer for NodeProviderSigner {
-    fn get(&self) -> UtilityCommandResult<Sender> {
-        Ok(Sender::SigKeys(SigKeys::EcdsaSecp256k1(
-            self.keypair.clone(),
-        )))
+    fn get(&self) -> OrchestratorResult<Sender> {
+        Ok(Sender::from_secp256k1_key_pair(self.keypair.clone()))
+    }
+}
+
+fn read_hsm_public_key(
+    hsm_slot: Option<&str>,
+    key_id: Option<&str>,
+) -> UtilityCommandResult<Vec<u8>> {
+    UtilityCommand::read_public_key(hsm_slot, key_id).execute_and_parse(|stdout| {
+        if stdout.is_empty() {
+            Err("the HSM returned an empty public key".to_string())
+        } else {
+            Ok(stdout.to_vec())
+        }
+    })
+}
+
+/// A request to the node operator to delegate the registration of a node to
+/// a session key of the node, for data centers where the HSM must not be
+/// attached to the node. The operator signs the request on a disconnected
+/// machine with `registration-signer sign`, such that the node can register
+/// itself with the session key once the signed request is imported.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistrationRequest {
+    /// The ID of the node, for the operator to check it against the one the
+    /// node shows.
+    pub node_id: String,
+    /// DER encoded Ed25519 public key of the session key of the node
+    #[serde(with = "hex_bytes")]
+    pub session_pubkey: Vec<u8>,
+}
+
+impl RegistrationRequest {
+    /// The delegation from the operator's key to the session key. It is only
+    /// valid for calls to the registry and until `expiration`.
+    pub fn delegation(&self, expiration: Time) -> Delegation {
+        Delegation::new_with_targets(
+            self.session_pubkey.clone(),
+            expiration,
+            vec![REGISTRY_CANISTER_ID],
+        )
+    }
+}
+
+/// A [`RegistrationRequest`] with the delegation signed by the operator's HSM.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedRegistrationRequest {
+    pub request: RegistrationRequest,
+    /// DER encoded public key of the operator's HSM
+    #[serde(with = "hex_bytes")]
+    pub operator_pubkey: Vec<u8>,
+    /// The expiration of the delegation in nanoseconds since the Unix epoch
+    pub expiration: u64,
+    #[serde(with = "hex_bytes")]
+    pub signature: Vec<u8>,
+}
+
+impl SignedRegistrationRequest {
+    pub fn expiration(&self) -> Time {
+        Time::from_nanos_since_unix_epoch(self.expiration)
+    }
+
+    pub fn signed_delegation(&self) -> SignedDelegation {
+        SignedDelegation::new(
+            self.request.delegation(self.expiration()),
+            self.signature.clone(),
+        )
+    }
+}
+
+/// Signs the delegation of `request` with the HSM attached to this machine.
+/// `None` selects the pre-agreed default of the HSM parameters.
+pub fn sign_registration_request(
+    request: RegistrationRequest,
+    expiration: Time,
+    hsm_slot: Option<&str>,
+    pin: Option<&str>,
+    key_id: Option<&str>,
+) -> UtilityCommandResult<SignedRegistrationRequest> {
+    let operator_pubkey = read_hsm_public_key(hsm_slot, key_id)?;
+    let signature = UtilityCommand::sign_message(
+        request.delegation(expiration).as_signed_bytes(),
+        hsm_slot,
+        pin,
+        key_id,
+    )
+    .execute()?;
+    Ok(SignedRegistrationRequest {
+        request,
+        operator_pubkey,
+        expiration: expiration.as_nanos_since_unix_epoch(),
+        signature,
+    })
+}
+
+/// Signer for nodes registered with a signed [`RegistrationRequest`]. The
+/// signer exports the request of the node to `dir` and fails until the signed
+/// request was imported into it.
+pub struct OfflineSigner {
+    dir: PathBuf,
+    node_id: NodeId,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SessionKey {
+    #[serde(with = "hex_bytes")]
+    secret_key: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    public_key: Vec<u8>,
+}
+
+impl OfflineSigner {
+    pub fn new(dir: PathBuf, node_id: NodeId) -> Self {
+        Self { dir, node_id }
+    }
+
+    /// Returns the session key, which is generated once, such that it matches
+    /// the exported request after a restart.
+    fn session_key(&self) -> OrchestratorResult<Ed25519KeyPair> {
+        let path = self.dir.join(SESSION_KEY_FILE);
+        if path.exists() {
+            let key: SessionKey = read_json(&path)?;
+            let malformed = |_| {
+                OrchestratorError::MalformedFileError(path.clone(), "invalid key length".into())
+            };
+            return Ok(Ed25519KeyPair {
+                secret_key: key.secret_key.as_slice().try_into().map_err(malformed)?,
+                public_key: key.public_key.as_slice().try_into().map_err(malformed)?,
+            });
+        }
+        let key_pair = Ed25519KeyPair::generate(&mut rand::thread_rng());
+        let key = SessionKey {
+            secret_key: key_pair.secret_key.to_vec(),
+            public_key: key_pair.public_key.to_vec(),
+        };
+        write_json(&path, &key, 0o600)?;
+        Ok(key_pair)
+    }
+}
+
+impl Signer for OfflineSigner {
+    fn get(&self) -> OrchestratorResult<Sender> {
+        let session_key = self.session_key()?;
+        let request = RegistrationRequest {
+            node_id: self.node_id.to_string(),
+            session_pubkey: ed25519_public_key_to_der(session_key.public_key.to_vec()),
+        };
+        let request_path = self.dir.join(REGISTRATION_REQUEST_FILE);
+        if read_json::<RegistrationRequest>(&request_path)
+            .ok()
+            .as_ref()
+            != Some(&request)
+        {
+            write_json(&request_path, &request, 0o644)?;
+        }
+
+        let signed_path = self.dir.join(SIGNED_REGISTRATION_REQUEST_FILE);
+        if !signed_path.exists() {
+            UtilityCommand::notify_host("Waiting for the signed registration request.", 1);
+            return Err(OrchestratorError::SignedRegistrationRequestMissingError(
+                signed_path,
+            ));
+        }
+        let signed: SignedRegistrationRequest = read_json(&signed_path)?;
+        if signed.request != request {
+            return Err(OrchestratorError::SignedRegistrationRequestMismatchError(
+                signed_path,
+            ));
+        }
+        if signed.expiration() <= current_time() {
+            return Err(OrchestratorError::SignedRegistrationRequestExpiredError(
+                signed_path,
+            ));
+        }
+        Ok(Sender::Delegation {
+            pub_key: signed.operator_pubkey.clone(),
+            delegations: vec![signed.signed_delegation()],
+            session_key,
+        })
+    }
+}
+
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> OrchestratorResult<T> {
+    let contents = std::fs::read(path).map_err(|e| OrchestratorError::file_read_error(path, e))?;
+    serde_json::from_slice(&contents)
+        .map_err(|e| OrchestratorError::MalformedFileError(path.to_path_buf(), e.to_string()))
+}
+
+pub fn write_json<T: Serialize>(path: &Path, value: &T, mode: u32) -> OrchestratorResult<()> {
+    let contents = serde_json::to_vec_pretty(value).expect("failed to serialize");
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(mode)
+        .open(path)
+        .and_then(|mut file| file.write_all(&contents))
+        .map_err(|e| OrchestratorError::file_write_error(path, e))
+}
+
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        hex::decode(String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_types::PrincipalId;
+
+    #[test]
+    fn offline_signer_exports_request_and_uses_imported_delegation() {
+        let dir = tempfile::tempdir().unwrap();
+        let node_id = NodeId::from(PrincipalId::new_node_test_id(1));
+        let signer = OfflineSigner::new(dir.path().to_path_buf(), node_id);
+
+        assert!(matches!(
+            signer.get(),
+            Err(OrchestratorError::SignedRegistrationRequestMissingError(_))
+        ));
+        let request: RegistrationRequest =
+            read_json(&dir.path().join(REGISTRATION_REQUEST_FILE)).unwrap();
+        assert_eq!(request.node_id, node_id.to_string());
+        // The session key is kept when the signer is re-created.
+        assert!(OfflineSigner::new(dir.path().to_path_buf(), node_id)
+            .get()
+            .is_err());
+        assert_eq!(
+            read_json::<RegistrationRequest>(&dir.path().join(REGISTRATION_REQUEST_FILE)).unwrap(),
+            request
+        );
+
+        let signed = SignedRegistrationRequest {
+            request: request.clone(),
+            operator_pubkey: vec![1, 2, 3],
+            expiration: (current_time() + std::time::Duration::from_secs(3600))
+                .as_nanos_since_unix_epoch(),
+            signature: vec![4, 5, 6],
+        };
+        let signed_path = dir.path().join(SIGNED_REGISTRATION_REQUEST_FILE);
+        write_json(&signed_path, &signed, 0o644).unwrap();
+        let sender = signer.get().unwrap();
+        assert_eq!(sender.sender_pubkey_der(), Some(vec![1, 2, 3]));
+        assert_eq!(
+            sender.sender_delegation(),
+            Some(vec![signed.signed_delegation()])
+        );
+
+        let expired = SignedRegistrationRequest {
+            expiration: 0,
+            ..signed.clone()
+        };
+        write_json(&signed_path, &expired, 0o644).unwrap();
+        assert!(matches!(
+            signer.get(),
+            Err(OrchestratorError::SignedRegistrationRequestExpiredError(_))
+        ));
+
+        let other_node = SignedRegistrationRequest {
+            request: RegistrationRequest {
+                node_id: PrincipalId::new_node_test_id(2).to_string(),
+                ..request
+            },
+            ..signed
+        };
+        write_json(&signed_path, &other_node, 0o644).unwrap();
+        assert!(matches!(
+            signer.get(),
+            Err(OrchestratorError::SignedRegistrationRequestMismatchError(_))
+        ));
     }
 }
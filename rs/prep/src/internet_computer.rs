@@ -231,6 +231,12 @@ pub struct IcConfig {
     initial_release_package_url: Option<Url>,
     /// The hash of the initial release package.
     initial_release_package_sha256_hex: Option<String>,
+    /// Further versions that subnets of the initial topology or unassigned
+    /// nodes run, along with their release packages.
+    additional_replica_versions: BTreeMap<ReplicaVersion, ReplicaVersionRecord>,
+    /// The version of the unassigned nodes, if it differs from the initial
+    /// replica version.
+    unassigned_nodes_replica_version_id: Option<ReplicaVersion>,
     /// Should the tool generate the subnet records.
     generate_subnet_records: bool,
     /// The index of the NNS subnet, if any.
@@ -355,6 +361,8 @@ impl IcConfig {
             nns_subnet_index,
             initial_release_package_url: release_package_url,
             initial_release_package_sha256_hex: release_package_sha256_hex,
+            additional_replica_versions: BTreeMap::new(),
+            unassigned_nodes_replica_version_id: None,
             initial_registry_node_operator_entries: Vec::new(),
             provisional_whitelist,
             initial_mutations: Vec::new(),
@@ -366,6 +374,30 @@ impl IcConfig {
         }
     }
 
+    /// Blesses the version `replica_version_id` in addition to the initial
+    /// replica version, such that subnets of the initial topology can run it.
+    pub fn add_replica_version(
+        &mut self,
+        replica_version_id: ReplicaVersion,
+        release_package_url: Url,
+        release_package_sha256_hex: String,
+    ) {
+        self.additional_replica_versions.insert(
+            replica_version_id,
+            ReplicaVersionRecord {
+                release_package_sha256_hex,
+                release_package_urls: vec![release_package_url.to_string()],
+                guest_launch_measurement_sha256_hex: None,
+            },
+        );
+    }
+
+    /// Sets the version of the unassigned nodes, which has to be either the
+    /// initial replica version or added via `add_replica_version`.
+    pub fn set_unassigned_nodes_replica_version(&mut self, replica_version_id: ReplicaVersion) {
+        self.unassigned_nodes_replica_version_id = Some(replica_version_id);
+    }
+
     pub fn set_use_specified_ids_allocation_range(
         &mut self,
         use_specified_ids_allocation_range: bool,
@@ -504,8 +536,22 @@ impl IcConfig {
             guest_launch_measurement_sha256_hex: self.initial_guest_launch_measurement_sha256_hex,
         };
 
+        let mut blessed_version_ids = vec![initial_replica_version];
+        for (replica_version_id, replica_version_record) in self.additional_replica_versions {
+            if replica_version_id == self.initial_replica_version_id {
+                continue;
+            }
+            blessed_version_ids.push(replica_version_id.to_string());
+            write_registry_entry(
+                &data_provider,
+                self.target_dir.as_path(),
+                make_replica_version_key(replica_version_id).as_ref(),
+                version,
+                replica_version_record,
+            );
+        }
         let blessed_replica_versions_record = BlessedReplicaVersions {
-            blessed_version_ids: vec![initial_replica_version],
+            blessed_version_ids,
         };
 
         write_registry_entry(
@@ -557,7 +603,10 @@ impl IcConfig {
         }
 
         let unassigned_nodes_config = UnassignedNodesConfigRecord {
-            replica_version: self.initial_replica_version_id.to_string(),
+            replica_version: self
+                .unassigned_nodes_replica_version_id
+                .unwrap_or(self.initial_replica_version_id)
+                .to_string(),
             ssh_readonly_access: self.ssh_readonly_access_to_unassigned_nodes,
         };
 
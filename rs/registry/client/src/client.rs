@@ -83,23 +83,31 @@ impl RegistryClientImpl {
     /// provider failed. Returns `Ok` if querying the data provider succeeded,
     /// regardless of whether a newer registry version was available or not.
     pub fn poll_once(&self) -> Result<(), RegistryClientError> {
-        let (records, version) = {
+        let (records, version, base_version) = {
             let latest_version = self.cache.read().unwrap().latest_version;
-            let records = match self
-                .data_provider
-                .get_updates_since(latest_version)
+            let (records, base_version) = match self.data_provider.get_updates_since(latest_version)
             {
-                Ok(records) if !records.is_empty() => records,
-                Ok(_) /*if version == cache_state.latest_version*/ => return Ok(()),
-                Err(e) => return Err(RegistryClientError::from(e)),
+                // The versions up to the base version are gone, but the
+                // snapshot at the base version applies on top of the latest
+                // version.
+                Err(RegistryDataProviderError::VersionCompacted { base_version, .. }) => {
+                    let records = self
+                        .data_provider
+                        .get_updates_since(RegistryVersion::from(base_version.get() - 1))?;
+                    (records, Some(base_version))
+                }
+                res => (res?, None),
             };
+            if records.is_empty() {
+                return Ok(());
+            }
             let new_version = records
                 .iter()
                 .max_by_key(|r| r.version)
                 .map(|r| r.version)
                 .unwrap_or(latest_version);
 
-            (records, new_version)
+            (records, new_version, base_version)
         };
 
         // Ensure exclusive access to the cache.
@@ -108,6 +116,9 @@ impl RegistryClientImpl {
         // Check version again under write lock, to prevent race conditions.
         if version > cache_state.latest_version {
             self.metrics.registry_version.set(version.get() as i64);
+            if let Some(base_version) = base_version {
+                cache_state.add_compacted_versions(base_version);
+            }
             cache_state.update(records, version);
         }
         Ok(())
@@ -148,6 +159,16 @@ impl RegistryClientImpl {
         if version > cache_state.latest_version {
             return Err(RegistryClientError::VersionNotAvailable { version });
         }
+        if let Some((_, &base_version)) =
+            cache_state.compacted_versions.range(..=version).next_back()
+        {
+            if version < base_version {
+                return Err(RegistryClientError::VersionCompacted {
+                    version,
+                    base_version,
+                });
+            }
+        }
         Ok(cache_state)
     }
 }
@@ -182,6 +203,9 @@ struct CacheState {
     records: Vec<RegistryTransportRecord>,
     timestamps: BTreeMap<RegistryVersion, Time>,
     latest_version: RegistryVersion,
+    /// The versions that were compacted away before they were fetched, as a
+    /// map from the first missing version to the base version of each gap.
+    compacted_versions: BTreeMap<RegistryVersion, RegistryVersion>,
 }
 
 impl CacheState {
@@ -190,6 +214,16 @@ impl CacheState {
             records: vec![],
             latest_version: ZERO_REGISTRY_VERSION,
             timestamps: Default::default(),
+            compacted_versions: Default::default(),
+        }
+    }
+
+    /// Records that the versions between the latest version and
+    /// `base_version` are not available.
+    fn add_compacted_versions(&mut self, base_version: RegistryVersion) {
+        if base_version.get() > self.latest_version.get() + 1 {
+            self.compacted_versions
+                .insert(self.latest_version + RegistryVersion::from(1), base_version);
         }
     }
 
@@ -646,6 +680,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn versions_below_the_compacted_version_are_reported_as_compacted() {
+        let data_provider = Arc::new(ProtoRegistryDataProvider::new());
+        let set = |key: &str, ver: u64| data_provider.add(key, v(ver), Some(value(ver))).unwrap();
+        let rem = |key: &str, ver: u64| data_provider.add::<TestProto>(key, v(ver), None).unwrap();
+
+        set("A", 1);
+        set("A", 3);
+        set("B", 2);
+        rem("B", 4);
+        set("C", 6);
+
+        let compacted_data_provider = Arc::new(CompactedDataProvider {
+            base_version: v(5),
+            data_provider: data_provider.clone(),
+        });
+        let registry = RegistryClientImpl::new(compacted_data_provider, None);
+        let get = |key: &str, t: u64| registry.get_test_proto(key, v(t));
+
+        registry.poll_once().unwrap();
+        assert_eq!(registry.get_latest_version(), v(6));
+        assert_eq!(get("A", 5).unwrap(), Some(value(3)));
+        assert_eq!(get("B", 5).unwrap(), None);
+        assert_eq!(get("C", 5).unwrap(), None);
+        assert_eq!(get("C", 6).unwrap(), Some(value(6)));
+        assert_eq!(get("A", 0).unwrap(), None);
+        for t in 1..5 {
+            assert_matches!(
+                get("A", t),
+                Err(RegistryClientError::VersionCompacted { version, base_version })
+                    if version == v(t) && base_version == v(5)
+            );
+        }
+    }
+
     fn v(v: u64) -> RegistryVersion {
         RegistryVersion::new(v)
     }
@@ -668,6 +737,41 @@ mod tests {
         }
     }
 
+    /// Serves the registry of `data_provider` as if its history was compacted
+    /// into a snapshot at `base_version`.
+    struct CompactedDataProvider {
+        base_version: RegistryVersion,
+        data_provider: Arc<dyn RegistryDataProvider>,
+    }
+
+    impl RegistryDataProvider for CompactedDataProvider {
+        fn get_updates_since(
+            &self,
+            version: RegistryVersion,
+        ) -> Result<Vec<RegistryTransportRecord>, RegistryDataProviderError> {
+            if version.get() + 1 < self.base_version.get() {
+                return Err(RegistryDataProviderError::VersionCompacted {
+                    version,
+                    base_version: self.base_version,
+                });
+            }
+            let mut records = self.data_provider.get_updates_since(version)?;
+            records.sort_by_key(|r| r.version);
+            let mut snapshot = BTreeMap::new();
+            let mut res = vec![];
+            for mut record in records {
+                if record.version <= self.base_version {
+                    record.version = self.base_version;
+                    snapshot.insert(record.key.clone(), record);
+                } else {
+                    res.push(record);
+                }
+            }
+            res.extend(snapshot.into_values());
+            Ok(res)
+        }
+    }
+
     struct LimitingDataProvider {
         changelog_size: RegistryVersion,
         data_provider: Arc<dyn RegistryDataProvider>,
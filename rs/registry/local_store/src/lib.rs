@@ -10,6 +10,8 @@ use ic_types::RegistryVersion;
 use ic_utils::fs::write_protobuf_using_tmp_file;
 use prost::Message;
 use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
     io::{self},
     path::{Path, PathBuf},
     sync::{Arc, Mutex},
@@ -41,9 +43,42 @@ pub trait LocalStoreReader: Send + Sync {
     /// `cl` where the subsequence `cl[0..i]`, `0 <= i <= len(ds)`, applied
     /// to a registry at latest version `v` represents the registry at
     /// version `v+i+1`.
+    ///
+    /// If the store was compacted at a version `b` (see
+    /// [LocalStoreWriter::compact]), the changelog since version `b-1` starts
+    /// with the snapshot of the registry at version `b`, which also applies on
+    /// top of any older version. Reading the changelog since a version
+    /// `v < b-1` fails with a [CompactedVersionError] (see
+    /// [compacted_version_error]), as the history between `v` and `b` is
+    /// gone.
     fn get_changelog_since_version(&self, version: RegistryVersion) -> io::Result<Changelog>;
 }
 
+/// The error reading the changelog since `version` fails with, if the store
+/// was compacted at `base_version`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompactedVersionError {
+    pub version: RegistryVersion,
+    pub base_version: RegistryVersion,
+}
+
+impl fmt::Display for CompactedVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Version {} was compacted into the snapshot at version {}.",
+            self.version, self.base_version
+        )
+    }
+}
+
+impl std::error::Error for CompactedVersionError {}
+
+/// Returns the [CompactedVersionError] behind `e`, if any.
+pub fn compacted_version_error(e: &io::Error) -> Option<&CompactedVersionError> {
+    e.get_ref()?.downcast_ref()
+}
+
 pub trait LocalStoreWriter: Send + Sync {
     /// Store the changelog at the given version.
     ///
@@ -59,6 +94,26 @@ pub trait LocalStoreWriter: Send + Sync {
     /// certified timestamp file in the root of the local store.
     fn clear(&self) -> io::Result<()>;
 
+    /// Squashes the history up to and including `retained_version` into a
+    /// snapshot of the registry at `retained_version`, and deletes the
+    /// changelog entries of the older versions. Compacting at or below the
+    /// version of a previous compaction does nothing.
+    ///
+    /// Precondition: A changelog_entry for `retained_version` must exist in
+    /// the store.
+    ///
+    /// Readers of the store lagging behind `retained_version` can only catch
+    /// up on the snapshot, see [LocalStoreReader::get_changelog_since_version],
+    /// and lose the versions in between.
+    fn compact(&self, retained_version: RegistryVersion) -> io::Result<()>;
+
+    /// Stores `snapshot`, the complete registry at `version`, into an empty
+    /// store, as if the store was compacted at `version`. Fails for version 0,
+    /// the empty registry.
+    ///
+    /// Precondition: The given snapshot must be nonempty list of KeyMutations.
+    fn store_snapshot(&self, version: RegistryVersion, snapshot: ChangelogEntry) -> io::Result<()>;
+
     /// Update the locally stored certified time to `unix_epoch_nanos`.
     fn update_certified_time(&self, unix_epoch_nanos: u64) -> io::Result<()>;
 }
@@ -133,6 +188,67 @@ impl LocalStoreImpl {
         self.path.join(fname)
     }
 
+    /// The file recording the version the store was last compacted at, as a
+    /// delta with an empty changelog.
+    fn base_version_path(&self) -> PathBuf {
+        let fname = "base.local_store.v1.Delta.pb";
+        self.path.join(fname)
+    }
+
+    /// The version the store was last compacted at, or 0 if it never was.
+    pub fn base_version(&self) -> io::Result<RegistryVersion> {
+        let path = self.base_version_path();
+        if !path.exists() {
+            return Ok(RegistryVersion::from(0));
+        }
+        let bytes = std::fs::read(path)?;
+        let delta = PbDelta::decode(bytes.as_slice())
+            .map_err(|e| io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        Ok(RegistryVersion::from(delta.registry_version))
+    }
+
+    fn write_base_version(&self, version: u64) -> io::Result<()> {
+        let pb = PbDelta {
+            registry_version: version,
+            changelog: vec![],
+        };
+        write_protobuf_using_tmp_file(self.base_version_path(), &pb)
+    }
+
+    fn read_changelog_from(&self, start: u64) -> io::Result<Changelog> {
+        (start..)
+            .map(|i| self.get_path(i))
+            .take_while(|p| p.exists())
+            .try_fold(vec![], |mut res, p| {
+                res.push(changelog_entry_try_from_proto(Self::read_changelog_entry(
+                    p,
+                )?)?);
+                Ok(res)
+            })
+    }
+
+    /// Deletes the changelog entries of the versions in `versions` and the
+    /// directories left empty.
+    fn remove_versions(&self, versions: std::ops::Range<u64>) -> io::Result<()> {
+        let mut dirs = BTreeSet::new();
+        for version in versions {
+            let path = self.get_path(version);
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+            dirs.extend(path.ancestors().skip(1).take(3).map(PathBuf::from));
+        }
+        // Deepest directories first, such that their parents might be empty.
+        let mut dirs: Vec<_> = dirs.into_iter().collect();
+        dirs.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+        for dir in dirs {
+            // Fails for directories still holding entries of other versions.
+            let _ = std::fs::remove_dir(dir);
+        }
+        Ok(())
+    }
+
     fn write_changelog_entry(&self, version: u64, pb: PbChangelogEntry) -> io::Result<()> {
         self.write_changelog_entry_(version, pb, |p, m| write_protobuf_using_tmp_file(p, &m))
     }
@@ -157,16 +273,17 @@ impl LocalStore for LocalStoreImpl {}
 
 impl LocalStoreReader for LocalStoreImpl {
     fn get_changelog_since_version(&self, version: RegistryVersion) -> io::Result<Changelog> {
-        let start = version.get() + 1;
-        (start..)
-            .map(|i| self.get_path(i))
-            .take_while(|p| p.exists())
-            .try_fold(vec![], |mut res, p| {
-                res.push(changelog_entry_try_from_proto(Self::read_changelog_entry(
-                    p,
-                )?)?);
-                Ok(res)
-            })
+        let base_version = self.base_version()?;
+        if version.get() + 1 < base_version.get() {
+            return Err(io::Error::new(
+                std::io::ErrorKind::NotFound,
+                CompactedVersionError {
+                    version,
+                    base_version,
+                },
+            ));
+        }
+        self.read_changelog_from(version.get() + 1)
     }
 }
 
@@ -185,7 +302,65 @@ impl LocalStoreWriter for LocalStoreImpl {
             } else {
                 Ok(())
             }
-        })
+        })?;
+        match std::fs::remove_file(self.base_version_path()) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    fn compact(&self, retained_version: RegistryVersion) -> io::Result<()> {
+        let base = self.base_version()?.get();
+        let retained = retained_version.get();
+        if retained <= base {
+            return Ok(());
+        }
+        if !self.get_path(retained).exists() {
+            return Err(io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Version {} does not exist.", retained),
+            ));
+        }
+        let mut records = BTreeMap::new();
+        self.read_changelog_from(base.max(1))?
+            .into_iter()
+            .take((retained - base.max(1) + 1) as usize)
+            .flatten()
+            .for_each(|km| {
+                records.insert(km.key, km.value);
+            });
+        // Deleted keys are kept as unset mutations, such that the snapshot
+        // also applies on top of the older versions until they are deleted.
+        let snapshot: ChangelogEntry = records
+            .into_iter()
+            .map(|(key, value)| KeyMutation { key, value })
+            .collect();
+        // Every step leaves a consistent store behind: the snapshot replaces
+        // the entry of `retained_version` before the older versions are
+        // reported as compacted, and the older versions are deleted last.
+        let pb = changelog_entry_to_protobuf(snapshot);
+        write_protobuf_using_tmp_file(self.get_path(retained), &pb)?;
+        self.write_base_version(retained)?;
+        self.remove_versions(base.max(1)..retained)
+    }
+
+    fn store_snapshot(&self, version: RegistryVersion, snapshot: ChangelogEntry) -> io::Result<()> {
+        if version.get() == 0 {
+            return Err(io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Version must be > 0.",
+            ));
+        }
+        let path = self.get_path(version.get());
+        let dir = path.parent().ok_or_else(|| {
+            io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Path {:?} has no parent directory.", path),
+            )
+        })?;
+        std::fs::create_dir_all(dir)?;
+        write_protobuf_using_tmp_file(path, &changelog_entry_to_protobuf(snapshot))?;
+        self.write_base_version(version.get())
     }
 
     // Store the certified time
@@ -201,11 +376,22 @@ impl RegistryDataProvider for LocalStoreImpl {
         &self,
         version: RegistryVersion,
     ) -> Result<Vec<RegistryTransportRecord>, RegistryDataProviderError> {
-        let changelog = self.get_changelog_since_version(version).map_err(|e| {
-            RegistryDataProviderError::Transfer {
-                source: format!("Error when reading changelog from local storage: {:?}", e),
-            }
-        })?;
+        let changelog = match self.get_changelog_since_version(version) {
+            Ok(changelog) => changelog,
+            Err(e) => match compacted_version_error(&e) {
+                Some(e) => {
+                    return Err(RegistryDataProviderError::VersionCompacted {
+                        version: e.version,
+                        base_version: e.base_version,
+                    })
+                }
+                None => {
+                    return Err(RegistryDataProviderError::Transfer {
+                        source: format!("Error when reading changelog from local storage: {:?}", e),
+                    })
+                }
+            },
+        };
         let res: Vec<_> = changelog
             .iter()
             .enumerate()
@@ -350,6 +536,41 @@ pub fn changelog_to_compact_delta(
     Ok(buf)
 }
 
+/// Reads the complete changelog of the possibly compacted `store`. Returns
+/// the version the changelog applies on top of, which is 0, unless the store
+/// was compacted and the changelog starts with the snapshot at the next
+/// version.
+pub fn read_changelog<S: LocalStoreReader + ?Sized>(
+    store: &S,
+) -> io::Result<(RegistryVersion, Changelog)> {
+    let e = match store.get_changelog_since_version(RegistryVersion::from(0)) {
+        Ok(changelog) => return Ok((RegistryVersion::from(0), changelog)),
+        Err(e) => e,
+    };
+    let version = match compacted_version_error(&e).map(|e| e.base_version) {
+        Some(base_version) => RegistryVersion::from(base_version.get() - 1),
+        None => return Err(e),
+    };
+    Ok((version, store.get_changelog_since_version(version)?))
+}
+
+/// Stores `changelog`, as returned with `version` by [read_changelog], into
+/// the empty `store`.
+pub fn restore_changelog<S: LocalStoreWriter + ?Sized>(
+    store: &S,
+    version: RegistryVersion,
+    changelog: Changelog,
+) -> io::Result<()> {
+    changelog.into_iter().enumerate().try_for_each(|(i, ce)| {
+        let v = version + RegistryVersion::from((i + 1) as u64);
+        if i == 0 && version.get() > 0 {
+            store.store_snapshot(v, ce)
+        } else {
+            store.store(v, ce)
+        }
+    })
+}
+
 fn changelog_entry_to_protobuf(ce: ChangelogEntry) -> PbChangelogEntry {
     assert!(!ce.is_empty());
     let key_mutations = ce
@@ -456,6 +677,115 @@ mod tests {
         }
     }
 
+    #[test]
+    fn compaction_preserves_the_registry() {
+        let tempdir = TempDir::new().unwrap();
+        let store = LocalStoreImpl::new(tempdir.path());
+        let mut rng = rand::thread_rng();
+
+        let mut changelog = get_random_changelog(300, &mut rng);
+        changelog.iter().enumerate().for_each(|(i, c)| {
+            store
+                .store(RegistryVersion::from((i + 1) as u64), c.clone())
+                .unwrap()
+        });
+
+        store.compact(RegistryVersion::from(260)).unwrap();
+        assert_eq!(store.base_version().unwrap(), RegistryVersion::from(260));
+        assert!(!store.get_path(1).exists());
+        assert!(!store.get_path(259).exists());
+
+        let (version, compacted) = read_changelog(&store).unwrap();
+        assert_eq!(version, RegistryVersion::from(259));
+        assert_eq!(compacted.len(), changelog.len() - 259);
+        assert_eq!(apply(&compacted), apply(&changelog));
+        assert_eq!(
+            apply(&compacted[..1]),
+            apply(&changelog[..260]),
+            "the snapshot is the registry at the retained version"
+        );
+        let lagging = [&changelog[..100], compacted.as_slice()].concat();
+        assert_eq!(
+            apply(&lagging),
+            apply(&changelog),
+            "the snapshot applies on top of older versions"
+        );
+        for i in (260..changelog.len()).step_by(10) {
+            let cl = store
+                .get_changelog_since_version(RegistryVersion::from(i as u64))
+                .unwrap();
+            assert_eq!(&changelog[i..], cl.as_slice());
+        }
+        for i in [0, 100, 258] {
+            let e = store
+                .get_changelog_since_version(RegistryVersion::from(i))
+                .unwrap_err();
+            assert_eq!(
+                compacted_version_error(&e),
+                Some(&CompactedVersionError {
+                    version: RegistryVersion::from(i),
+                    base_version: RegistryVersion::from(260),
+                })
+            );
+        }
+
+        // Compacting below the base does nothing, while the store can be
+        // extended and compacted again.
+        store.compact(RegistryVersion::from(100)).unwrap();
+        assert_eq!(store.base_version().unwrap(), RegistryVersion::from(260));
+        let mut new_changelog = get_random_changelog(20, &mut rng);
+        new_changelog.iter().enumerate().for_each(|(i, c)| {
+            store
+                .store(RegistryVersion::from((i + 301) as u64), c.clone())
+                .unwrap()
+        });
+        changelog.append(&mut new_changelog);
+        store.compact(RegistryVersion::from(310)).unwrap();
+        assert!(!store.get_path(260).exists());
+        let (version, compacted) = read_changelog(&store).unwrap();
+        assert_eq!(version, RegistryVersion::from(309));
+        assert_eq!(compacted.len(), changelog.len() - 309);
+        assert_eq!(apply(&compacted), apply(&changelog));
+
+        let restored_dir = TempDir::new().unwrap();
+        let restored = LocalStoreImpl::new(restored_dir.path());
+        restore_changelog(&restored, version, compacted.clone()).unwrap();
+        assert_eq!(restored.base_version().unwrap(), RegistryVersion::from(310));
+        assert_eq!(read_changelog(&restored).unwrap(), (version, compacted));
+
+        store.clear().unwrap();
+        assert_eq!(store.base_version().unwrap(), RegistryVersion::from(0));
+        assert!(store
+            .get_changelog_since_version(RegistryVersion::from(0))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn storing_snapshot_at_version_0_fails() {
+        let tempdir = TempDir::new().unwrap();
+        let store = LocalStoreImpl::new(tempdir.path());
+        let mut rng = rand::thread_rng();
+        let changelog = get_random_changelog(1, &mut rng);
+
+        let e = store
+            .store_snapshot(RegistryVersion::from(0), changelog[0].clone())
+            .unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(store.base_version().unwrap(), RegistryVersion::from(0));
+    }
+
+    fn apply(changelog: &[ChangelogEntry]) -> BTreeMap<String, Vec<u8>> {
+        let mut registry = BTreeMap::new();
+        for km in changelog.iter().flatten() {
+            match &km.value {
+                Some(value) => registry.insert(km.key.clone(), value.clone()),
+                None => registry.remove(&km.key),
+            };
+        }
+        registry
+    }
+
     #[test]
     fn can_store_and_read_certified_time() {
         use std::time::{SystemTime, UNIX_EPOCH};
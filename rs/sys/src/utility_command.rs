@@ -1,22 +1,65 @@
-use std::io::Write;
+use std::io::{ErrorKind, Read, Write};
 use std::os::unix::fs::PermissionsExt;
-use std::process::{Command as StdCommand, ExitStatus, Stdio};
+use std::process::{Child, Command as StdCommand, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
 
 const VSOCK_AGENT_PATH: &str = "/opt/ic/bin/vsock_agent";
+/// How long the commands talking to the USB HSM may take.
+const HSM_COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+/// How often a command with a timeout is checked for having exited.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// How long the rest of the output is awaited after the command exited or was
+/// killed, as processes it spawned may keep the pipes open.
+const OUTPUT_GRACE_PERIOD: Duration = Duration::from_secs(1);
 
-#[derive(Clone, Debug)]
+/// The errors of a [UtilityCommand], such that callers can tell why it failed.
+/// The `command` of the variants is the displayed [UtilityCommand].
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum UtilityCommandError {
+    /// The program does not exist.
+    BinaryNotFound { program: String },
+    /// Running the program or communicating with it failed.
     IoError(String),
-    Failed(String, ExitStatus),
+    /// The program exited with a non-zero status.
+    Failed {
+        command: String,
+        status: ExitStatus,
+        stderr: String,
+    },
+    /// The program did not exit within the timeout and was killed.
+    Timeout { command: String, timeout: Duration },
+    /// The program succeeded, but its output could not be parsed.
+    MalformedOutput { command: String, reason: String },
 }
 
 impl std::fmt::Display for UtilityCommandError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            UtilityCommandError::IoError(err) => write!(f, "{}", err),
-            UtilityCommandError::Failed(err, status) => {
-                write!(f, "Utility command failed with status {}: {}", status, err)
+            UtilityCommandError::BinaryNotFound { program } => {
+                write!(f, "Utility command '{}' not found", program)
             }
+            UtilityCommandError::IoError(err) => write!(f, "{}", err),
+            UtilityCommandError::Failed {
+                command,
+                status,
+                stderr,
+            } => write!(
+                f,
+                "Utility command failed with status {}: Error while running '{}': {}",
+                status, command, stderr
+            ),
+            UtilityCommandError::Timeout { command, timeout } => write!(
+                f,
+                "Utility command '{}' did not finish within {:?}",
+                command, timeout
+            ),
+            UtilityCommandError::MalformedOutput { command, reason } => write!(
+                f,
+                "Utility command '{}' returned malformed output: {}",
+                command, reason
+            ),
         }
     }
 }
@@ -34,6 +77,7 @@ pub struct UtilityCommand {
     program: String,
     args: Vec<String>,
     input: Vec<u8>,
+    timeout: Option<Duration>,
 }
 
 impl UtilityCommand {
@@ -43,6 +87,7 @@ impl UtilityCommand {
             program,
             args,
             input: vec![],
+            timeout: None,
         }
     }
 
@@ -51,6 +96,12 @@ impl UtilityCommand {
         self
     }
 
+    /// Kill the command if it did not exit after `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     /// Execute the command and capture the output.
     pub fn execute(&self) -> UtilityCommandResult<Vec<u8>> {
         let mut cmd = StdCommand::new(self.program.clone());
@@ -62,7 +113,12 @@ impl UtilityCommand {
         let map_to_err = |e: std::io::Error| {
             UtilityCommandError::IoError(format!("Error while running '{}': {}", self, e))
         };
-        let mut child = cmd.spawn().map_err(map_to_err)?;
+        let mut child = cmd.spawn().map_err(|e| match e.kind() {
+            ErrorKind::NotFound => UtilityCommandError::BinaryNotFound {
+                program: self.program.clone(),
+            },
+            _ => map_to_err(e),
+        })?;
 
         let mut stdin = match child.stdin.take() {
             Some(v) => v,
@@ -76,18 +132,66 @@ impl UtilityCommand {
         stdin.write_all(self.input.as_slice()).map_err(map_to_err)?;
         stdin.flush().map_err(map_to_err)?;
         drop(stdin);
-        let output = child.wait_with_output().map_err(map_to_err)?;
-        if output.status.success() {
-            Ok(output.stdout)
-        } else {
-            Err(UtilityCommandError::Failed(
-                format!(
-                    "Error while running '{}': {}",
-                    self,
-                    std::str::from_utf8(output.stderr.as_slice()).unwrap()
-                ),
-                output.status,
-            ))
+        // The outputs are read while waiting, such that a command filling the
+        // pipes does not block.
+        let stdout = read_chunks(child.stdout.take());
+        let stderr = read_chunks(child.stderr.take());
+        let status = self.wait(&mut child).map_err(map_to_err)?;
+        let deadline = Instant::now() + OUTPUT_GRACE_PERIOD;
+        let stdout = collect_output(stdout, deadline).map_err(map_to_err)?;
+        let stderr = collect_output(stderr, deadline).map_err(map_to_err)?;
+        match status {
+            None => Err(UtilityCommandError::Timeout {
+                command: self.to_string(),
+                timeout: self.timeout.unwrap_or_default(),
+            }),
+            Some(status) if status.success() => Ok(stdout),
+            Some(status) => Err(UtilityCommandError::Failed {
+                command: self.to_string(),
+                status,
+                stderr: String::from_utf8_lossy(&stderr).to_string(),
+            }),
+        }
+    }
+
+    /// Execute the command and parse its output with `parse`, which returns
+    /// the reason if the output is malformed.
+    pub fn execute_and_parse<T, F>(&self, parse: F) -> UtilityCommandResult<T>
+    where
+        F: FnOnce(&[u8]) -> Result<T, String>,
+    {
+        let stdout = self.execute()?;
+        parse(stdout.as_slice()).map_err(|reason| UtilityCommandError::MalformedOutput {
+            command: self.to_string(),
+            reason,
+        })
+    }
+
+    /// Execute the command and return its output as UTF-8 text.
+    pub fn execute_utf8(&self) -> UtilityCommandResult<String> {
+        self.execute_and_parse(|stdout| {
+            String::from_utf8(stdout.to_vec()).map_err(|e| e.to_string())
+        })
+    }
+
+    /// Waits for the child to exit. Returns `None` if it was killed after the
+    /// timeout.
+    fn wait(&self, child: &mut Child) -> std::io::Result<Option<ExitStatus>> {
+        let timeout = match self.timeout {
+            Some(timeout) => timeout,
+            None => return child.wait().map(Some),
+        };
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return Ok(Some(status));
+            }
+            if Instant::now() > deadline {
+                child.kill()?;
+                child.wait()?;
+                return Ok(None);
+            }
+            thread::sleep(WAIT_POLL_INTERVAL);
         }
     }
 
@@ -109,6 +213,7 @@ impl UtilityCommand {
             .map(|s| s.to_string())
             .collect::<Vec<_>>(),
         )
+        .with_timeout(HSM_COMMAND_TIMEOUT)
     }
 
     pub fn sign_message(
@@ -135,6 +240,7 @@ impl UtilityCommand {
             .collect::<Vec<_>>(),
         )
         .with_input(ic_crypto_sha::Sha256::hash(msg.as_slice()).to_vec())
+        .with_timeout(HSM_COMMAND_TIMEOUT)
     }
 
     /// Try to attach the USB HSM, if the VSOCK_AGENT_PATH binary
@@ -195,6 +301,46 @@ impl UtilityCommand {
     }
 }
 
+/// Reads `pipe` in a separate thread and sends its chunks as they arrive, such
+/// that a command filling the pipe does not block. The channel is closed at the
+/// end of the output, or if reading fails.
+fn read_chunks<R: Read + Send + 'static>(pipe: Option<R>) -> Receiver<std::io::Result<Vec<u8>>> {
+    let (sender, receiver) = mpsc::channel();
+    if let Some(mut pipe) = pipe {
+        thread::spawn(move || {
+            let mut chunk = [0u8; 8192];
+            loop {
+                let read = match pipe.read(&mut chunk) {
+                    Ok(0) => break,
+                    Ok(n) => Ok(chunk[..n].to_vec()),
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => Err(e),
+                };
+                let failed = read.is_err();
+                // Stops once the output is no longer awaited.
+                if sender.send(read).is_err() || failed {
+                    break;
+                }
+            }
+        });
+    }
+    receiver
+}
+
+/// Collects the chunks sent by [read_chunks] until the end of the output, but
+/// at most until `deadline`. The reading thread then exits once the pipe is
+/// closed by the processes still holding it.
+fn collect_output(
+    chunks: Receiver<std::io::Result<Vec<u8>>>,
+    deadline: Instant,
+) -> std::io::Result<Vec<u8>> {
+    let mut output = vec![];
+    while let Ok(chunk) = chunks.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        output.extend(chunk?);
+    }
+    Ok(output)
+}
+
 impl std::fmt::Display for UtilityCommand {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "`{}", self.program)?;
@@ -202,3 +348,75 @@ impl std::fmt::Display for UtilityCommand {
         write!(f, "` input: {}", hex::encode(self.input.clone()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sh(script: &str) -> UtilityCommand {
+        UtilityCommand::new("sh".to_string(), vec!["-c".to_string(), script.to_string()])
+    }
+
+    #[test]
+    fn failures_are_distinguished() {
+        let missing = UtilityCommand::new("/nonexistent/pkcs11-tool".to_string(), vec![]);
+        assert_eq!(
+            missing.execute(),
+            Err(UtilityCommandError::BinaryNotFound {
+                program: "/nonexistent/pkcs11-tool".to_string()
+            })
+        );
+
+        match sh("echo output; echo 'object not found' >&2; exit 3").execute() {
+            Err(UtilityCommandError::Failed { status, stderr, .. }) => {
+                assert_eq!(status.code(), Some(3));
+                assert_eq!(stderr, "object not found\n");
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        let timeout = Duration::from_millis(100);
+        assert!(matches!(
+            sh("sleep 10").with_timeout(timeout).execute(),
+            Err(UtilityCommandError::Timeout { timeout: t, .. }) if t == timeout
+        ));
+
+        let start = Instant::now();
+        assert!(matches!(
+            sh("sleep 10 & sleep 10").with_timeout(timeout).execute(),
+            Err(UtilityCommandError::Timeout { .. })
+        ));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        assert!(matches!(
+            sh("printf '\\377'").execute_utf8(),
+            Err(UtilityCommandError::MalformedOutput { .. })
+        ));
+    }
+
+    #[test]
+    fn output_is_parsed() {
+        let cmd = sh("echo 42").with_timeout(Duration::from_secs(10));
+        assert_eq!(cmd.execute_utf8().unwrap(), "42\n");
+        assert_eq!(
+            cmd.execute_and_parse(|stdout| {
+                std::str::from_utf8(stdout)
+                    .map_err(|e| e.to_string())?
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|e| e.to_string())
+            }),
+            Ok(42)
+        );
+    }
+
+    #[test]
+    fn output_is_returned_while_spawned_processes_keep_the_pipes_open() {
+        let start = Instant::now();
+        assert_eq!(
+            sh("sleep 10 & echo started").execute_utf8().unwrap(),
+            "started\n"
+        );
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -257,6 +257,15 @@ impl<T: Signable> BasicSigner<T> for CryptoReturningOk {
     ) -> CryptoResult<BasicSigOf<T>> {
         Ok(BasicSigOf::new(BasicSig(vec![])))
     }
+
+    fn sign_basic_batch(
+        &self,
+        messages: &[T],
+        _signer: NodeId,
+        _registry_version: RegistryVersion,
+    ) -> CryptoResult<Vec<BasicSigOf<T>>> {
+        Ok(vec![BasicSigOf::new(BasicSig(vec![])); messages.len()])
+    }
 }
 
 impl<T: Signable> BasicSigVerifier<T> for CryptoReturningOk {
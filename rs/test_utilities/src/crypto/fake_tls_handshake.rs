@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 use ic_crypto_tls_interfaces::{
-    AllowedClients, AuthenticatedPeer, TlsClientHandshakeError, TlsHandshake,
+    AllowedClients, AuthenticatedPeer, TlsClientHandshakeError, TlsHandshake, TlsPublicKeyCert,
     TlsServerHandshakeError, TlsStream,
 };
 use ic_types::{NodeId, RegistryVersion};
@@ -29,6 +29,7 @@ impl TlsHandshake for FakeTlsHandshake {
         _tcp_stream: TcpStream,
         _allowed_clients: AllowedClients,
         _registry_version: RegistryVersion,
+        _alpn_protocols: Vec<Vec<u8>>,
     ) -> Result<(Box<dyn TlsStream>, AuthenticatedPeer), TlsServerHandshakeError> {
         unimplemented!()
     }
@@ -46,6 +47,15 @@ impl TlsHandshake for FakeTlsHandshake {
         _tcp_stream: TcpStream,
         _server: NodeId,
         _registry_version: RegistryVersion,
+        _alpn_protocols: Vec<Vec<u8>>,
+    ) -> Result<Box<dyn TlsStream>, TlsClientHandshakeError> {
+        unimplemented!()
+    }
+
+    async fn perform_tls_client_handshake_with_pinned_cert(
+        &self,
+        _tcp_stream: TcpStream,
+        _server_cert: TlsPublicKeyCert,
     ) -> Result<Box<dyn TlsStream>, TlsClientHandshakeError> {
         unimplemented!()
     }
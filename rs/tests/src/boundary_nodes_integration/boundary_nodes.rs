@@ -20,16 +20,17 @@ use crate::{
     driver::{
         boundary_node::{BoundaryNode, BoundaryNodeVm},
         ic::{InternetComputer, Subnet},
+        ssh_exec::{CommandOutput, SshCommand, SshExec},
         test_env::TestEnv,
         test_env_api::{
             retry_async, HasPublicApiUrl, HasTopologySnapshot, HasVm, HasWasm, IcNodeContainer,
-            NnsInstallationExt, RetrieveIpv4Addr, SshSession, READY_WAIT_TIMEOUT, RETRY_BACKOFF,
+            NnsInstallationExt, RetrieveIpv4Addr, READY_WAIT_TIMEOUT, RETRY_BACKOFF,
         },
     },
     util::{assert_create_agent, delay},
 };
 
-use std::{convert::TryFrom, io::Read, net::SocketAddrV6, time::Duration};
+use std::{convert::TryFrom, net::SocketAddrV6, time::Duration};
 
 use anyhow::{anyhow, bail, Context, Error};
 use futures::stream::FuturesUnordered;
@@ -85,11 +86,15 @@ impl Drop for PanicHandler {
             .get_snapshot()
             .unwrap();
 
-        let (list_dependencies, exit_status) = exec_ssh_command(
-            &boundary_node,
-            "systemctl list-dependencies systemd-sysusers.service --all --reverse --no-pager",
-        )
-        .unwrap();
+        let CommandOutput {
+            stdout: list_dependencies,
+            exit_status,
+            ..
+        } = boundary_node
+            .ssh_exec(&SshCommand::new(
+                "systemctl list-dependencies systemd-sysusers.service --all --reverse --no-pager",
+            ))
+            .unwrap();
 
         info!(
             logger,
@@ -98,18 +103,6 @@ impl Drop for PanicHandler {
     }
 }
 
-fn exec_ssh_command(vm: &dyn SshSession, command: &str) -> Result<(String, i32), Error> {
-    let mut channel = vm.block_on_ssh_session()?.channel_session()?;
-
-    channel.exec(command)?;
-
-    let mut output = String::new();
-    channel.read_to_string(&mut output)?;
-    channel.wait_close()?;
-
-    Ok((output, channel.exit_status()?))
-}
-
 fn get_install_url(env: &TestEnv) -> Result<(url::Url, PrincipalId), Error> {
     let subnet = env
         .topology_snapshot()
@@ -265,7 +258,13 @@ fn setup(bn_https_config: BoundaryNodeHttpsConfig, env: TestEnv) {
     info!(&logger, "Waiting for routes file");
     let routes_path = "/var/opt/nginx/ic/ic_routes.js";
     let sleep_command = format!("while grep -q '// PLACEHOLDER' {routes_path}; do sleep 5; done");
-    let (cmd_output, exit_status) = exec_ssh_command(&boundary_node, &sleep_command).unwrap();
+    let CommandOutput {
+        stdout: cmd_output,
+        exit_status,
+        ..
+    } = boundary_node
+        .ssh_exec(&SshCommand::new(&sleep_command))
+        .unwrap();
     info!(
         logger,
         "{BOUNDARY_NODE_NAME} ran `{sleep_command}`: '{}'. Exit status = {exit_status}",
@@ -582,7 +581,13 @@ pub fn nginx_valid_config_test(env: TestEnv) {
         .get_snapshot()
         .unwrap();
 
-    let (cmd_output, exit_status) = exec_ssh_command(&boundary_node, "sudo nginx -t 2>&1").unwrap();
+    let CommandOutput {
+        stdout: cmd_output,
+        exit_status,
+        ..
+    } = boundary_node
+        .ssh_exec(&SshCommand::new("sudo nginx -t 2>&1"))
+        .unwrap();
 
     info!(
         logger,
@@ -648,7 +653,11 @@ pub fn denylist_test(env: TestEnv) {
 
         // Update the denylist and reload nginx
         let denylist_command = format!(r#"printf "\"~^{} .*$\" \"1\";\n" | sudo tee /var/opt/nginx/denylist/denylist.map && sudo service nginx reload"#, canister_id);
-        let (cmd_output, exit_status) = exec_ssh_command(&boundary_node, &denylist_command).unwrap();
+        let CommandOutput {
+            stdout: cmd_output,
+            exit_status,
+            ..
+        } = boundary_node.ssh_exec(&SshCommand::new(&denylist_command)).unwrap();
         info!(
             logger,
             "update denylist {BOUNDARY_NODE_NAME} with {denylist_command} to \n'{}'\n. Exit status = {}",
@@ -762,13 +771,14 @@ pub fn canister_allowlist_test(env: TestEnv) {
         assert_eq!(res, reqwest::StatusCode::OK, "expected OK, got {}", res);
 
         // Update denylist with canister ID
-        let (cmd_output, exit_status) = exec_ssh_command(
-            &boundary_node,
-            &format!(
+        let CommandOutput {
+            stdout: cmd_output,
+            exit_status,
+            ..
+        } = boundary_node.ssh_exec(&SshCommand::new(&format!(
                 r#"printf "\"~^{} .*$\" 1;\n" | sudo tee /var/opt/nginx/denylist/denylist.map"#,
                 canister_id
-            ),
-        )
+            )))
         .unwrap();
 
         info!(
@@ -779,10 +789,11 @@ pub fn canister_allowlist_test(env: TestEnv) {
         );
 
         // Reload Nginx
-        let (cmd_output, exit_status) = exec_ssh_command(
-            &boundary_node,
-            "sudo service nginx restart",
-        )
+        let CommandOutput {
+            stdout: cmd_output,
+            exit_status,
+            ..
+        } = boundary_node.ssh_exec(&SshCommand::new("sudo service nginx restart"))
         .unwrap();
 
         info!(
@@ -805,10 +816,11 @@ pub fn canister_allowlist_test(env: TestEnv) {
         assert_eq!(res, reqwest::StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS, "expected 451, got {}", res);
 
         // Update allowlist with canister ID
-        let (cmd_output, exit_status) = exec_ssh_command(
-            &boundary_node,
-            &format!(r#"printf "{} 1;\n" | sudo tee /run/ic-node/allowlist_canisters.map && sudo mount -o ro,bind /run/ic-node/allowlist_canisters.map /etc/nginx/allowlist_canisters.map"#, canister_id),
-        )
+        let CommandOutput {
+            stdout: cmd_output,
+            exit_status,
+            ..
+        } = boundary_node.ssh_exec(&SshCommand::new(&format!(r#"printf "{} 1;\n" | sudo tee /run/ic-node/allowlist_canisters.map && sudo mount -o ro,bind /run/ic-node/allowlist_canisters.map /etc/nginx/allowlist_canisters.map"#, canister_id)))
         .unwrap();
 
         info!(
@@ -819,10 +831,11 @@ pub fn canister_allowlist_test(env: TestEnv) {
         );
 
         // Reload Nginx
-        let (cmd_output, exit_status) = exec_ssh_command(
-            &boundary_node,
-            "sudo service nginx restart",
-        )
+        let CommandOutput {
+            stdout: cmd_output,
+            exit_status,
+            ..
+        } = boundary_node.ssh_exec(&SshCommand::new("sudo service nginx restart"))
         .unwrap();
 
         info!(
@@ -2242,7 +2255,13 @@ pub fn reboot_test(env: TestEnv) {
     info!(&logger, "Waiting for routes file");
     let routes_path = "/var/opt/nginx/ic/ic_routes.js";
     let sleep_command = format!("while grep -q '// PLACEHOLDER' {routes_path}; do sleep 5; done");
-    let (cmd_output, exit_status) = exec_ssh_command(&boundary_node, &sleep_command).unwrap();
+    let CommandOutput {
+        stdout: cmd_output,
+        exit_status,
+        ..
+    } = boundary_node
+        .ssh_exec(&SshCommand::new(&sleep_command))
+        .unwrap();
     info!(
         logger,
         "{BOUNDARY_NODE_NAME} ran `{sleep_command}`: '{}'. Exit status = {exit_status}",
@@ -7,6 +7,7 @@ use crate::driver::{
     // TODO: Uncomment this once spm41 is fixed for virsh
     //farm::HostFeature,
     ic::{AmountOfMemoryKiB, InternetComputer, Subnet, VmResources},
+    ssh_exec::{CommandOutput, SshCommand, SshExec},
     test_env::TestEnv,
     test_env_api::{
         retry_async, HasPublicApiUrl, HasTopologySnapshot, IcNodeContainer, NnsInstallationExt,
@@ -14,9 +15,7 @@ use crate::driver::{
     },
 };
 
-use std::io::Read;
-
-use anyhow::{Context, Error};
+use anyhow::Context;
 use ic_interfaces_registry::RegistryValue;
 use ic_protobuf::registry::routing_table::v1::RoutingTable as PbRoutingTable;
 use ic_registry_keys::make_routing_table_record_key;
@@ -27,18 +26,6 @@ use slog::info;
 
 const BOUNDARY_NODE_SNP_NAME: &str = "boundary-node-snp-1";
 
-fn exec_ssh_command(vm: &dyn SshSession, command: &str) -> Result<(String, i32), Error> {
-    let mut channel = vm.block_on_ssh_session()?.channel_session()?;
-
-    channel.exec(command)?;
-
-    let mut output = String::new();
-    channel.read_to_string(&mut output)?;
-    channel.wait_close()?;
-
-    Ok((output, channel.exit_status()?))
-}
-
 pub fn config(env: TestEnv) {
     let logger = env.logger();
 
@@ -110,7 +97,13 @@ pub fn config(env: TestEnv) {
     info!(&logger, "Waiting for routes file");
     let routes_path = "/var/opt/nginx/ic/ic_routes.js";
     let sleep_command = format!("while grep -q '// PLACEHOLDER' {routes_path}; do sleep 5; done");
-    let (cmd_output, exit_status) = exec_ssh_command(&boundary_node_vm, &sleep_command).unwrap();
+    let CommandOutput {
+        stdout: cmd_output,
+        exit_status,
+        ..
+    } = boundary_node_vm
+        .ssh_exec(&SshCommand::new(&sleep_command))
+        .unwrap();
     info!(
         logger,
         "{BOUNDARY_NODE_SNP_NAME} ran `{sleep_command}`: '{}'. Exit status = {exit_status}",
@@ -16,24 +16,21 @@ Success::
 
 end::catalog[] */
 
-use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 
 use crate::ckbtc::lib::install_bitcoin_canister;
+use crate::driver::auxiliary_services::{BitcoindVm, HasAuxiliaryServices, BITCOIND_VM_NAME};
+use crate::driver::ic::{InternetComputer, Subnet};
 use crate::driver::test_env::TestEnv;
 use crate::driver::test_env_api::{
-    retry, retry_async, HasDependencies, HasPublicApiUrl, HasTopologySnapshot, IcNodeContainer,
-    SshSession, READY_WAIT_TIMEOUT, RETRY_BACKOFF,
+    retry, retry_async, HasPublicApiUrl, HasTopologySnapshot, IcNodeContainer, SshSession,
+    READY_WAIT_TIMEOUT, RETRY_BACKOFF,
 };
 use crate::driver::universal_vm::UniversalVms;
 use crate::util::runtime_from_url;
 use crate::util::{block_on, UniversalCanister};
-use crate::{
-    driver::ic::{InternetComputer, Subnet},
-    driver::universal_vm::UniversalVm,
-};
 use anyhow::bail;
-use bitcoincore_rpc::{Auth, Client, RpcApi};
+use bitcoincore_rpc::RpcApi;
 use candid::Decode;
 use ic_registry_subnet_type::SubnetType;
 use ic_types::Height;
@@ -41,7 +38,7 @@ use ic_universal_canister::{management, wasm};
 use slog::info;
 use std::{io::Read, path::Path};
 
-const UNIVERSAL_VM_NAME: &str = "btc-node";
+const UNIVERSAL_VM_NAME: &str = BITCOIND_VM_NAME;
 
 pub fn config(env: TestEnv) {
     let logger = env.logger();
@@ -49,18 +46,12 @@ pub fn config(env: TestEnv) {
     // docker bitcoind image uses 8332 for the rpc server
     // https://en.bitcoinwiki.org/wiki/Running_Bitcoind
 
-    UniversalVm::new(String::from(UNIVERSAL_VM_NAME))
-        .with_config_img(env.get_dependency_path("rs/tests/btc_uvm_config_image.zst"))
-        .disable_ipv4()
+    BitcoindVm::default()
         .start(&env)
         .expect("failed to setup universal VM");
 
-    let deployed_universal_vm = env.get_deployed_universal_vm(UNIVERSAL_VM_NAME).unwrap();
-    let universal_vm = deployed_universal_vm.get_vm().unwrap();
-    let btc_node_ipv6 = universal_vm.ipv6;
-
     InternetComputer::new()
-        .with_bitcoind_addr(SocketAddr::new(IpAddr::V6(btc_node_ipv6), 18444))
+        .with_bitcoind_addr(env.bitcoind_addr().unwrap())
         .add_subnet(
             Subnet::new(SubnetType::System)
                 .with_dkg_interval_length(Height::from(10))
@@ -107,21 +98,7 @@ fn get_bitcoind_log(env: &TestEnv) {
 pub fn get_balance(env: TestEnv) {
     let logger = env.logger();
 
-    let deployed_universal_vm = env.get_deployed_universal_vm(UNIVERSAL_VM_NAME).unwrap();
-
-    let btc_rpc = Arc::new(
-        self::Client::new(
-            &format!(
-                "http://[{}]:8332",
-                deployed_universal_vm.get_vm().unwrap().ipv6
-            ),
-            Auth::UserPass(
-                "btc-dev-preview".to_string(),
-                "Wjh4u6SAjT4UMJKxPmoZ0AN2r9qbE-ksXQ5I2_-Hm4w=".to_string(),
-            ),
-        )
-        .unwrap(),
-    );
+    let btc_rpc = Arc::new(env.bitcoind_rpc_client().unwrap());
 
     // Create a wallet.
     // Retry since the bitcoind VM might not be up yet.
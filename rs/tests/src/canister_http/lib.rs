@@ -1,3 +1,4 @@
+use crate::driver::auxiliary_services::{HttpbinVm, HTTPBIN_VM_NAME};
 use crate::driver::ic::{InternetComputer, Subnet};
 use crate::driver::test_env_api::{HasTopologySnapshot, IcNodeContainer, RetrieveIpv4Addr};
 use crate::driver::universal_vm::*;
@@ -12,7 +13,7 @@ use slog::info;
 use std::net::{Ipv4Addr, Ipv6Addr};
 use std::time::Duration;
 
-pub const UNIVERSAL_VM_NAME: &str = "httpbin";
+pub const UNIVERSAL_VM_NAME: &str = HTTPBIN_VM_NAME;
 pub const EXPIRATION: Duration = Duration::from_secs(120);
 pub const BACKOFF_DELAY: Duration = Duration::from_secs(5);
 
@@ -63,8 +64,7 @@ pub fn install_mainnet_nns_canisters(env: &TestEnv) {
 
 pub fn config(env: TestEnv) {
     // Set up Universal VM with HTTP Bin testing service
-    UniversalVm::new(String::from(UNIVERSAL_VM_NAME))
-        .with_config_img(env.get_dependency_path("rs/tests/http_uvm_config_image.zst"))
+    HttpbinVm::default()
         .start(&env)
         .expect("failed to set up universal VM");
 
@@ -16,7 +16,6 @@ it to fall behind triggering state_sync from remaining replicas
 Success:: State hashes computed by all nodes at a given height agree and
 CUP height keeps on advancing
 
-
 end::catalog[] */
 
 use ic_agent::Agent;
@@ -30,7 +29,7 @@ use rand::Rng;
 use crate::{
     driver::{
         ic::{InternetComputer, Subnet},
-        test_env::TestEnv,
+        test_env::{HasDefaultRng, TestEnv},
         test_env_api::{HasPublicApiUrl, HasTopologySnapshot, HasVm, IcNodeContainer},
     },
     util::*,
@@ -44,8 +43,6 @@ const COW_SAFETY_CANISTER: &[u8] = include_bytes!("cow_safety.wasm");
 const MAX_MEM_SIZE: u128 = 128 * 1024;
 const TEST_MEM_SIZE: u128 = 64 * 1024;
 const MAX_NODES: usize = 4;
-// Seed for a random generator
-const RND_SEED: u64 = 42;
 
 pub fn config(env: TestEnv) {
     InternetComputer::new()
@@ -74,7 +71,7 @@ pub fn test(env: TestEnv) {
     let canister_id =
         block_on(async move { install_canister(&agent, node.effective_canister_id()).await });
     let mut should_match = 0;
-    let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(RND_SEED);
+    let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(env.default_rng_seed());
     for (i, node) in topology.root_subnet().nodes().enumerate() {
         let agent = node.with_default_agent(|agent| async move { agent });
         should_match = block_on(async {
@@ -18,13 +18,12 @@ hence, checked by default.
 Coverage::
 . Consensus doesn't break in the presence of simple malicious behavior
 
-
 end::catalog[] */
 
 use crate::{
     driver::{
         ic::{InternetComputer, Subnet},
-        test_env::TestEnv,
+        test_env::{HasDefaultRng, TestEnv},
         test_env_api::{HasPublicApiUrl, HasTopologySnapshot, IcNodeContainer},
     },
     util::{assert_malicious_from_topo, UniversalCanister},
@@ -39,8 +38,6 @@ use rand_chacha::ChaCha8Rng;
 use slog::{debug, info, Logger};
 
 const MSG_LEN: usize = 8;
-// Seed for a random generator
-const RND_SEED: u64 = 42;
 
 pub fn config(env: TestEnv) {
     let malicious_behaviour =
@@ -96,7 +93,7 @@ pub fn test(env: TestEnv) {
     //  0        len         2*len      (n-1)*len        n*len
     //
     let rt = tokio::runtime::Runtime::new().expect("Could not create tokio runtime.");
-    let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(RND_SEED);
+    let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(env.default_rng_seed());
     let (last_pulled_msg, last_pushed_msg) = rt.block_on(do_the_work(
         &log,
         &mut rng,
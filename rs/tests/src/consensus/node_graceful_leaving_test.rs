@@ -20,7 +20,7 @@ end::catalog[] */
 use crate::{
     driver::ic::{InternetComputer, Subnet},
     driver::{
-        test_env::TestEnv,
+        test_env::{HasDefaultRng, TestEnv},
         test_env_api::{
             HasPublicApiUrl, HasTopologySnapshot, HasVm, IcNodeContainer, NnsInstallationExt,
         },
@@ -40,8 +40,6 @@ use slog::info;
 const DKG_INTERVAL: u64 = 14;
 const NODES_COUNT: usize = 4;
 const REMOVE_NODES_COUNT: usize = (NODES_COUNT / 3) + 1;
-// Seed for a random generator
-const RND_SEED: u64 = 42;
 
 pub fn config(env: TestEnv) {
     InternetComputer::new()
@@ -66,7 +64,7 @@ pub fn test(env: TestEnv) {
 
     let mut nns_nodes: Vec<_> = topology.root_subnet().nodes().collect();
     let (nns_node, nns_nodes_to_remove) = {
-        let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(RND_SEED);
+        let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(env.default_rng_seed());
         nns_nodes.shuffle(&mut rng);
         (&nns_nodes[0], &nns_nodes[1..REMOVE_NODES_COUNT + 1])
     };
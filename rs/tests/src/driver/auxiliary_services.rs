@@ -0,0 +1,147 @@
+//! Auxiliary services the IC under test talks to, each running on a universal
+//! VM of its own, such that canister HTTP and Bitcoin integration tests do not
+//! depend on services outside the testnet:
+//!
+//! * [HttpbinVm] serves `httpbin` over HTTPS with a certificate signed by the
+//!   test CA trusted by the replica, as a target of HTTPS outcalls.
+//! * [BitcoindVm] runs a `bitcoind` node in regtest mode, which the Bitcoin
+//!   adapters of the IC connect to if its address is passed to
+//!   [InternetComputer::with_bitcoind_addr](crate::driver::ic::InternetComputer::with_bitcoind_addr).
+//!
+//! Both start from a configuration image, which is a runtime dependency the
+//! test has to declare, and are awaited until they serve requests. Tests find
+//! them with [HasAuxiliaryServices].
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use anyhow::Result;
+use bitcoincore_rpc::{Auth, Client, RpcApi};
+use reqwest::Url;
+use slog::info;
+
+use crate::driver::{
+    test_env::TestEnv,
+    test_env_api::{retry, HasDependencies, READY_WAIT_TIMEOUT, RETRY_BACKOFF},
+    universal_vm::{UniversalVm, UniversalVms},
+};
+
+pub const HTTPBIN_VM_NAME: &str = "httpbin";
+pub const HTTPBIN_UVM_CONFIG_IMAGE: &str = "rs/tests/http_uvm_config_image.zst";
+/// The port of the HTTPS endpoint of httpbin.
+pub const HTTPBIN_PORT: u16 = 20443;
+
+pub const BITCOIND_VM_NAME: &str = "btc-node";
+pub const BITCOIND_UVM_CONFIG_IMAGE: &str = "rs/tests/btc_uvm_config_image.zst";
+/// The port the Bitcoin adapters connect to.
+pub const BITCOIND_P2P_PORT: u16 = 18444;
+pub const BITCOIND_RPC_PORT: u16 = 8332;
+/// The credentials of the RPC server, as set in `btc_integration/bitcoin.conf`.
+pub const BITCOIND_RPC_USER: &str = "btc-dev-preview";
+pub const BITCOIND_RPC_PASSWORD: &str = "Wjh4u6SAjT4UMJKxPmoZ0AN2r9qbE-ksXQ5I2_-Hm4w=";
+
+const SERVICE_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct HttpbinVm {
+    universal_vm: UniversalVm,
+}
+
+impl Default for HttpbinVm {
+    fn default() -> Self {
+        HttpbinVm {
+            universal_vm: UniversalVm::new(HTTPBIN_VM_NAME.to_string()),
+        }
+    }
+}
+
+impl HttpbinVm {
+    /// Serve httpbin on IPv6 only, e.g., to test outcalls to IPv6 servers.
+    pub fn disable_ipv4(mut self) -> Self {
+        self.universal_vm = self.universal_vm.disable_ipv4();
+        self
+    }
+
+    /// Starts the VM and waits until httpbin serves requests.
+    pub fn start(self, env: &TestEnv) -> Result<()> {
+        self.universal_vm
+            .with_config_img(env.get_dependency_path(HTTPBIN_UVM_CONFIG_IMAGE))
+            .start(env)?;
+        let url = env.httpbin_url()?;
+        info!(env.logger(), "Awaiting httpbin at {} ...", url);
+        // The certificate of httpbin is signed by the test CA.
+        let client = reqwest::blocking::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .timeout(SERVICE_REQUEST_TIMEOUT)
+            .build()?;
+        retry(env.logger(), READY_WAIT_TIMEOUT, RETRY_BACKOFF, || {
+            client.get(url.join("get")?).send()?.error_for_status()?;
+            Ok(())
+        })
+    }
+}
+
+pub struct BitcoindVm {
+    universal_vm: UniversalVm,
+}
+
+impl Default for BitcoindVm {
+    fn default() -> Self {
+        BitcoindVm {
+            universal_vm: UniversalVm::new(BITCOIND_VM_NAME.to_string()).disable_ipv4(),
+        }
+    }
+}
+
+impl BitcoindVm {
+    /// Starts the VM and waits until bitcoind answers RPCs.
+    pub fn start(self, env: &TestEnv) -> Result<()> {
+        self.universal_vm
+            .with_config_img(env.get_dependency_path(BITCOIND_UVM_CONFIG_IMAGE))
+            .start(env)?;
+        info!(env.logger(), "Awaiting bitcoind ...");
+        let rpc = env.bitcoind_rpc_client()?;
+        retry(env.logger(), READY_WAIT_TIMEOUT, RETRY_BACKOFF, || {
+            rpc.get_blockchain_info()?;
+            Ok(())
+        })
+    }
+}
+
+pub trait HasAuxiliaryServices {
+    /// The base URL of the HTTPS endpoint of the [HttpbinVm].
+    fn httpbin_url(&self) -> Result<Url>;
+
+    /// The address of the [BitcoindVm] the Bitcoin adapters connect to.
+    fn bitcoind_addr(&self) -> Result<SocketAddr>;
+
+    /// A client of the RPC server of the [BitcoindVm], e.g., to mine blocks.
+    fn bitcoind_rpc_client(&self) -> Result<Client>;
+}
+
+impl HasAuxiliaryServices for TestEnv {
+    fn httpbin_url(&self) -> Result<Url> {
+        let vm = self.get_deployed_universal_vm(HTTPBIN_VM_NAME)?.get_vm()?;
+        Ok(Url::parse(&format!(
+            "https://[{}]:{}/",
+            vm.ipv6, HTTPBIN_PORT
+        ))?)
+    }
+
+    fn bitcoind_addr(&self) -> Result<SocketAddr> {
+        let vm = self.get_deployed_universal_vm(BITCOIND_VM_NAME)?.get_vm()?;
+        Ok(SocketAddr::new(IpAddr::V6(vm.ipv6), BITCOIND_P2P_PORT))
+    }
+
+    fn bitcoind_rpc_client(&self) -> Result<Client> {
+        let vm = self.get_deployed_universal_vm(BITCOIND_VM_NAME)?.get_vm()?;
+        Ok(Client::new(
+            &format!("http://[{}]:{}", vm.ipv6, BITCOIND_RPC_PORT),
+            Auth::UserPass(
+                BITCOIND_RPC_USER.to_string(),
+                BITCOIND_RPC_PASSWORD.to_string(),
+            ),
+        )?)
+    }
+}
@@ -25,8 +25,9 @@ use std::thread::{self, JoinHandle};
 use url::Url;
 
 use crate::driver::{
-    config::NODES_INFO, driver_setup::SSH_AUTHORIZED_PUB_KEYS_DIR, farm::Farm,
-    node_software_version::NodeSoftwareVersion, port_allocator::AddrType, resource::AllocatedVm,
+    config::NODES_INFO, console_log::stream_console_log, driver_setup::SSH_AUTHORIZED_PUB_KEYS_DIR,
+    farm::Farm, node_software_version::NodeSoftwareVersion, port_allocator::AddrType,
+    resource::AllocatedVm,
 };
 
 use crate::driver::farm::FileId;
@@ -116,7 +117,13 @@ pub fn init_ic(
             SubnetConfig::new(
                 subnet_index,
                 nodes,
-                Some(initial_replica.replica_version.clone()),
+                Some(
+                    subnet
+                        .initial_replica_version
+                        .as_ref()
+                        .map_or(&initial_replica.replica_version, |v| &v.replica_version)
+                        .clone(),
+                ),
                 subnet.ingress_bytes_per_block_soft_cap,
                 subnet.max_ingress_bytes_per_message,
                 subnet.max_ingress_messages_per_block,
@@ -182,6 +189,21 @@ pub fn init_ic(
         /* guest_launch_measurement_sha256_hex= */ None,
     );
 
+    for version in ic
+        .subnets
+        .iter()
+        .filter_map(|subnet| subnet.initial_replica_version.as_ref())
+        .chain(ic.unassigned_nodes_replica_version.as_ref())
+    {
+        ic_config.add_replica_version(
+            version.replica_version.clone(),
+            version.release_package_url.clone(),
+            version.release_package_sha256_hex.clone(),
+        );
+    }
+    if let Some(version) = &ic.unassigned_nodes_replica_version {
+        ic_config.set_unassigned_nodes_replica_version(version.replica_version.clone());
+    }
     ic_config.set_use_specified_ids_allocation_range(specific_ids);
 
     info!(test_env.logger(), "Initializing via {:?}", &ic_config);
@@ -224,6 +246,7 @@ pub fn setup_and_start_vms(
             std::fs::remove_file(conf_img_path)?;
             t_farm.attach_disk_images(&group_name, &vm_name, "usb-storage", vec![image_id])?;
             t_farm.start_vm(&group_name, &vm_name)?;
+            stream_console_log(&t_env, &t_farm, &group_name, &vm_name);
             Ok(())
         }));
     }
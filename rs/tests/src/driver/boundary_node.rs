@@ -27,7 +27,7 @@ use crate::{
     util::{create_agent, create_agent_mapping},
 };
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
 use flate2::{write::GzEncoder, Compression};
 use ic_agent::{Agent, AgentError};
@@ -37,6 +37,7 @@ use slog::info;
 use ssh2::Session;
 
 use crate::driver::{
+    console_log::stream_console_log,
     farm::{FileId, PlaynetCertificate},
     test_env_api::HasIcDependencies,
 };
@@ -44,12 +45,16 @@ use crate::driver::{
 const DEFAULT_VCPUS_PER_VM: NrOfVCPUs = NrOfVCPUs::new(4);
 const DEFAULT_MEMORY_KIB_PER_VM: AmountOfMemoryKiB = AmountOfMemoryKiB::new(25165824); // 24GiB
 
-const BOUNDARY_NODE_VMS_DIR: &str = "boundary_node_vms";
+pub(crate) const BOUNDARY_NODE_VMS_DIR: &str = "boundary_node_vms";
 const BOUNDARY_NODE_VM_PATH: &str = "vm.json";
 const BOUNDARY_NODE_PLAYNET_PATH: &str = "playnet.json";
 const CONF_IMG_FNAME: &str = "config_disk.img";
 const CERT_DIR: &str = "certificate";
 const PLAYNET_PATH: &str = "playnet.json";
+/// The routing table of the boundary node, which contains a placeholder until
+/// the boundary node fetched the routes of the IC from the registry.
+const ROUTES_PATH: &str = "/var/opt/nginx/ic/ic_routes.js";
+const ROUTES_PLACEHOLDER: &str = "// PLACEHOLDER";
 
 fn mk_compressed_img_path() -> std::string::String {
     format!("{}.gz", CONF_IMG_FNAME)
@@ -228,6 +233,7 @@ impl BoundaryNodeWithVm {
         )?;
 
         farm.start_vm(&pot_setup.farm_group_name, &self.name)?;
+        stream_console_log(env, &farm, &pot_setup.farm_group_name, &self.name);
 
         if self.has_ipv4 {
             // Provision an A record pointing ic{ix}.farm.dfinity.systems
@@ -323,6 +329,7 @@ impl BoundaryNode {
         let farm = Farm::new(farm_url, logger.clone());
         let boundary_node_img_url = env.get_boundary_node_img_url()?;
         let boundary_node_img_sha256 = env.get_boundary_node_img_sha256()?;
+        let vm_resources = self.vm_resources.or(pot_setup.default_vm_resources);
 
         let create_vm_req = CreateVmRequest::new(
             self.name.clone(),
@@ -331,18 +338,10 @@ impl BoundaryNode {
             } else {
                 VmType::Production
             },
-            self.vm_resources.vcpus.unwrap_or_else(|| {
-                pot_setup
-                    .default_vm_resources
-                    .and_then(|vm_resources| vm_resources.vcpus)
-                    .unwrap_or(DEFAULT_VCPUS_PER_VM)
-            }),
-            self.vm_resources.memory_kibibytes.unwrap_or_else(|| {
-                pot_setup
-                    .default_vm_resources
-                    .and_then(|vm_resources| vm_resources.memory_kibibytes)
-                    .unwrap_or(DEFAULT_MEMORY_KIB_PER_VM)
-            }),
+            vm_resources.vcpus.unwrap_or(DEFAULT_VCPUS_PER_VM),
+            vm_resources
+                .memory_kibibytes
+                .unwrap_or(DEFAULT_MEMORY_KIB_PER_VM),
             self.qemu_cli_args.clone(),
             match &self.boot_image {
                 None => {
@@ -352,13 +351,7 @@ impl BoundaryNode {
                 }
                 Some(disk_image) => From::from(disk_image.clone()),
             },
-            self.vm_resources
-                .boot_image_minimal_size_gibibytes
-                .or_else(|| {
-                    pot_setup
-                        .default_vm_resources
-                        .and_then(|vm_resources| vm_resources.boot_image_minimal_size_gibibytes)
-                }),
+            vm_resources.boot_image_minimal_size_gibibytes,
             self.has_ipv4,
             self.vm_allocation.clone(),
             self.required_host_features.clone(),
@@ -601,6 +594,29 @@ impl BoundaryNodeSnapshot {
     pub fn get_playnet(&self) -> Option<String> {
         self.playnet.clone()
     }
+
+    /// Waits until the boundary node fetched the routes to the subnets of the
+    /// IC from the registry.
+    pub fn await_routes(&self) -> Result<()> {
+        info!(
+            self.env.logger(),
+            "Awaiting the routes of {} ...", self.name
+        );
+        let script =
+            format!("test -f {ROUTES_PATH} && ! grep -q '{ROUTES_PLACEHOLDER}' {ROUTES_PATH}");
+        retry(self.env.logger(), READY_WAIT_TIMEOUT, RETRY_BACKOFF, || {
+            self.block_on_bash_script(&script)
+                .map(|_| ())
+                .map_err(|_| anyhow!("{} has no routes yet", self.name))
+        })
+    }
+
+    /// Waits until the boundary node routes requests to the IC and reports
+    /// healthy.
+    pub fn await_ready(&self) -> Result<()> {
+        self.await_routes()?;
+        self.await_status_is_healthy()
+    }
 }
 
 impl HasTestEnv for BoundaryNodeSnapshot {
@@ -0,0 +1,242 @@
+//! Colocated execution of a system test: instead of running the test driver on
+//! the machine invoking the test, the test binary is started in a docker
+//! container on a universal VM on Farm, such that long-running tests, e.g.,
+//! nightly ones, talk to their testnet from within the Farm network. See
+//! [SystemTestGroup::with_colocation](crate::driver::group::SystemTestGroup::with_colocation).
+//!
+//! The universal VM boots from the `<name>_uvm_config_image` of the test,
+//! which loads the `<name>_image` docker image containing the test binary and
+//! its runtime dependencies. The test has to be declared with
+//! `system_test(..., colocate = True)`, which makes the configuration image
+//! available to the test and sets the environment variables read here.
+
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use slog::{info, warn};
+
+use crate::driver::{
+    context::GroupContext,
+    test_env::{TestEnv, TestEnvAttribute},
+    test_env_api::{shell_quote, HasDependencies, SshSession},
+    universal_vm::{UniversalVm, UniversalVms},
+};
+
+/// Set in the environment of the colocated test driver, such that it runs the
+/// tests itself instead of colocating them again.
+pub const COLOCATED_ENV_VAR: &str = "SYSTEM_TESTS_COLOCATED";
+/// The name of the universal VM running the colocated test driver.
+pub const COLOCATED_VM_NAME: &str = "test-driver";
+/// The name of the test function awaiting the colocated test driver.
+pub const COLOCATED_TEST_NAME: &str = "colocated_test_driver";
+/// Name of the directory within the test environment holding the results
+/// written by the colocated test driver.
+pub const COLOCATED_RESULTS_DIR: &str = "colocated_results";
+
+const COLOCATED_IMAGE_ENV_VAR: &str = "COLOCATED_TEST_IMAGE";
+const COLOCATED_BIN_ENV_VAR: &str = "COLOCATED_TEST_BIN";
+const COLOCATED_UVM_CONFIG_IMAGE_ENV_VAR: &str = "COLOCATED_UVM_CONFIG_IMAGE";
+
+/// The directory shared between the universal VM and the docker container
+/// holding the results of the colocated run.
+const VM_RESULTS_DIR: &str = "/home/admin/results";
+const CONTAINER_RESULTS_DIR: &str = "/home/root/results";
+const CONTAINER_DEPENDENCIES_DIR: &str = "/home/root/root_env/dependencies";
+const JUNIT_XML_FILE: &str = "junit.xml";
+const JSON_FILE: &str = "results.json";
+
+/// Returns whether this process is the colocated test driver.
+pub fn is_colocated() -> bool {
+    std::env::var_os(COLOCATED_ENV_VAR).is_some()
+}
+
+/// How the test binary is started on the universal VM. The processes of the
+/// local test driver read it from the root environment.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Colocation {
+    /// The tag of the docker image containing the test binary.
+    pub image: String,
+    /// The path of the test binary relative to the runtime dependencies.
+    pub bin: String,
+    /// The path of the universal VM configuration image relative to the
+    /// runtime dependencies.
+    pub uvm_config_image: String,
+    /// The command line arguments passed on to the colocated test driver.
+    pub args: Vec<String>,
+}
+
+impl TestEnvAttribute for Colocation {
+    fn attribute_name() -> String {
+        String::from("colocation")
+    }
+}
+
+impl Colocation {
+    /// Reads the images of the test from the environment variables set by the
+    /// `system_test` rule.
+    pub fn from_env(args: Vec<String>) -> Result<Self> {
+        let var = |name: &str| {
+            std::env::var(name).with_context(|| {
+                format!(
+                    "{} is not set, declare the test with system_test(..., colocate = True)",
+                    name
+                )
+            })
+        };
+        Ok(Self {
+            image: var(COLOCATED_IMAGE_ENV_VAR)?,
+            bin: var(COLOCATED_BIN_ENV_VAR)?,
+            uvm_config_image: var(COLOCATED_UVM_CONFIG_IMAGE_ENV_VAR)?,
+            args,
+        })
+    }
+
+    /// The script running the test binary in its docker container. The results
+    /// are written to the directory shared with the universal VM.
+    fn docker_run_script(&self) -> String {
+        let mut args: Vec<String> = vec!["--working-dir".to_string(), ".".to_string()];
+        args.extend(self.args.iter().cloned());
+        args.extend([
+            "--junit-xml-output".to_string(),
+            format!("{}/{}", CONTAINER_RESULTS_DIR, JUNIT_XML_FILE),
+            "--json-output".to_string(),
+            format!("{}/{}", CONTAINER_RESULTS_DIR, JSON_FILE),
+            "run".to_string(),
+        ]);
+        let args: Vec<String> = args.iter().map(|arg| shell_quote(arg)).collect();
+        format!(
+            r#"set -euo pipefail
+# The activation script of the VM loads the image in the background.
+for i in $(seq 60); do docker image inspect {image} > /dev/null 2>&1 && break; sleep 5; done
+mkdir -p {vm_results}
+docker run --rm --network host -e {env_var}=1 -v {vm_results}:{container_results} --entrypoint {dependencies}/{bin} {image} {args} 2>&1"#,
+            vm_results = VM_RESULTS_DIR,
+            env_var = COLOCATED_ENV_VAR,
+            container_results = CONTAINER_RESULTS_DIR,
+            dependencies = CONTAINER_DEPENDENCIES_DIR,
+            bin = self.bin,
+            image = self.image,
+            args = args.join(" "),
+        )
+    }
+}
+
+/// Starts the universal VM running the colocated test driver.
+pub fn setup_colocated_vm(env: TestEnv) {
+    let colocation = Colocation::read_attribute(&env);
+    UniversalVm::new(String::from(COLOCATED_VM_NAME))
+        .with_config_img(env.get_dependency_path(&colocation.uvm_config_image))
+        .disable_ipv4()
+        .start(&env)
+        .expect("failed to set up the universal VM of the colocated test driver");
+}
+
+/// Runs the test binary on the universal VM, logs its output as it arrives and
+/// copies its results into the test environment.
+pub fn run_colocated_test(env: TestEnv) {
+    let log = env.logger();
+    let colocation = Colocation::read_attribute(&env);
+    let vm = env
+        .get_deployed_universal_vm(COLOCATED_VM_NAME)
+        .expect("the universal VM of the colocated test driver is not deployed");
+    let session = vm
+        .block_on_ssh_session()
+        .expect("failed to ssh into the universal VM of the colocated test driver");
+
+    info!(
+        log,
+        "Starting the colocated test driver {} ...", colocation.image
+    );
+    let exit_status = (|| -> Result<i32> {
+        let mut channel = session.channel_session()?;
+        channel.exec("bash")?;
+        channel.write_all(colocation.docker_run_script().as_bytes())?;
+        channel.flush()?;
+        channel.send_eof()?;
+        for line in BufReader::new(&mut channel).lines() {
+            info!(log, "[{}] {}", COLOCATED_VM_NAME, line?);
+        }
+        channel.wait_close()?;
+        Ok(channel.exit_status()?)
+    })()
+    .expect("failed to run the colocated test driver");
+
+    let results_dir = env.get_path(COLOCATED_RESULTS_DIR);
+    for file in [JUNIT_XML_FILE, JSON_FILE] {
+        let remote = Path::new(VM_RESULTS_DIR).join(file);
+        if let Err(e) = download(&session, &remote, &results_dir.join(file)) {
+            warn!(log, "Failed to download {:?}: {:?}", remote, e);
+        }
+    }
+    if exit_status != 0 {
+        panic!(
+            "The colocated test driver failed with exit code {}, see {:?} for its results.",
+            exit_status, results_dir
+        );
+    }
+    info!(log, "The colocated test driver succeeded.");
+}
+
+/// Copies the results of the colocated test driver to the output files of
+/// this run, if requested. Returns false if any of them is missing, e.g.,
+/// because the universal VM did not start.
+pub fn copy_colocated_results(
+    ctx: &GroupContext,
+    junit_xml_output: Option<&Path>,
+    json_output: Option<&Path>,
+) -> bool {
+    let results_dir = match ctx.get_test_env(COLOCATED_TEST_NAME) {
+        Ok(env) => env.get_path(COLOCATED_RESULTS_DIR),
+        Err(_) => return false,
+    };
+    [(JUNIT_XML_FILE, junit_xml_output), (JSON_FILE, json_output)]
+        .into_iter()
+        .filter_map(|(file, output)| output.map(|output| (results_dir.join(file), output)))
+        .all(|(source, output)| match fs::copy(&source, output) {
+            Ok(_) => true,
+            Err(e) => {
+                warn!(
+                    ctx.log(),
+                    "Failed to copy {:?} to {:?}: {:?}", source, output, e
+                );
+                false
+            }
+        })
+}
+
+fn download(session: &ssh2::Session, remote: &Path, local: &Path) -> Result<()> {
+    let (mut channel, stat) = session.scp_recv(remote)?;
+    if stat.size() == 0 {
+        bail!("{:?} is empty", remote);
+    }
+    fs::create_dir_all(local.parent().unwrap())?;
+    let mut file = fs::File::create(local)?;
+    std::io::copy(&mut channel, &mut file)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn docker_run_script_passes_on_the_arguments() {
+        let colocation = Colocation {
+            image: "bazel/rs/tests:my_test_image".to_string(),
+            bin: "rs/tests/my_test_bin".to_string(),
+            uvm_config_image: "rs/tests/my_test_uvm_config_image.zst".to_string(),
+            args: vec!["--include-tests".to_string(), "it's".to_string()],
+        };
+        let script = colocation.docker_run_script();
+        assert!(script.contains(
+            "--entrypoint /home/root/root_env/dependencies/rs/tests/my_test_bin bazel/rs/tests:my_test_image '--working-dir' '.' '--include-tests' 'it'\\''s'"
+        ));
+        assert!(script.contains("-e SYSTEM_TESTS_COLOCATED=1"));
+        assert!(script.contains("'--json-output' '/home/root/results/results.json' 'run'"));
+    }
+}
@@ -0,0 +1,127 @@
+//! Streaming of the serial console of the VMs of a testnet, such that boot
+//! failures during the setup can be diagnosed from the test artifacts instead
+//! of visiting the console of every VM on Farm.
+//!
+//! The console of every started VM is polled and appended to
+//! `<env>/console_logs/<vm_name>.log`. Lines at ERROR level are additionally
+//! logged to the logger of the test as they arrive.
+
+use std::{
+    collections::HashSet,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::Mutex,
+    thread,
+    time::Duration,
+};
+
+use lazy_static::lazy_static;
+use slog::{error, warn, Logger};
+
+use crate::driver::{
+    farm::{ConsoleLogRead, Farm, FarmError},
+    test_env::TestEnv,
+};
+
+/// Name of the directory within the environment holding the console logs.
+pub const CONSOLE_LOGS_DIR: &str = "console_logs";
+
+/// How often the console of a VM is polled.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+lazy_static! {
+    /// The VMs whose console is streamed by this process, such that starting
+    /// a VM again does not stream its console twice.
+    static ref STREAMED_VMS: Mutex<HashSet<(String, String)>> = Mutex::new(HashSet::new());
+}
+
+/// Streams the console of the VM `vm_name` into the directory of `env` until
+/// the VM is destroyed or this process exits.
+pub fn stream_console_log(env: &TestEnv, farm: &Farm, group_name: &str, vm_name: &str) {
+    let key = (group_name.to_string(), vm_name.to_string());
+    if !STREAMED_VMS.lock().unwrap().insert(key) {
+        return;
+    }
+    let log = env.logger();
+    let path = env
+        .get_path(CONSOLE_LOGS_DIR)
+        .join(format!("{}.log", vm_name));
+    let farm = farm.clone();
+    let group_name = group_name.to_string();
+    let vm_name = vm_name.to_string();
+    thread::spawn(move || {
+        if let Err(e) = poll_console_log(&log, &farm, &group_name, &vm_name, path) {
+            warn!(
+                log,
+                "Stopped streaming the console of VM {}: {:?}", vm_name, e
+            );
+        }
+    });
+}
+
+fn poll_console_log(
+    log: &Logger,
+    farm: &Farm,
+    group_name: &str,
+    vm_name: &str,
+    path: PathBuf,
+) -> std::io::Result<()> {
+    fs::create_dir_all(path.parent().unwrap())?;
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    let mut offset = 0;
+    // The bytes of the last line that is not complete yet.
+    let mut partial_line = vec![];
+    loop {
+        match farm.read_console_log(group_name, vm_name, offset) {
+            Ok(ConsoleLogRead::Truncated) => {
+                warn!(
+                    log,
+                    "The console log of VM {} was truncated, reading it from the start", vm_name
+                );
+                offset = 0;
+                // Terminate the last line of the previous log.
+                if !partial_line.is_empty() {
+                    partial_line.clear();
+                    file.write_all(b"\n")?;
+                }
+                continue;
+            }
+            Ok(ConsoleLogRead::Appended(bytes)) => {
+                offset += bytes.len() as u64;
+                file.write_all(&bytes)?;
+                partial_line.extend_from_slice(&bytes);
+                if let Some(end) = partial_line.iter().rposition(|b| *b == b'\n') {
+                    let lines: Vec<u8> = partial_line.drain(..=end).collect();
+                    for line in String::from_utf8_lossy(&lines).lines() {
+                        if is_error_line(line) {
+                            error!(log, "VM({}) console: {}", vm_name, line.trim_end());
+                        }
+                    }
+                }
+            }
+            // The VM has been destroyed.
+            Err(FarmError::NotFound { .. }) => return Ok(()),
+            Err(e) => warn!(log, "Failed to read the console of VM {}: {:?}", vm_name, e),
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn is_error_line(line: &str) -> bool {
+    line.contains("ERROR") || line.contains("[FAILED]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_lines_are_detected() {
+        assert!(is_error_line(
+            "Oct 14 12:00:00 replica[1]: ERROR: could not read the registry"
+        ));
+        assert!(is_error_line("[FAILED] Failed to start Setup the node id."));
+        assert!(!is_error_line("[  OK  ] Started Orchestrator."));
+    }
+}
@@ -12,6 +12,8 @@ use std::time::Duration;
 ///   |- setup/                  <-- test_env
 ///      |- ic_prep
 ///      |- test.log             <-- prefix
+///      |- testnet.json         <-- description of the testnet, written after the setup
+///      |- console_logs/        <-- serial console of the VMs started during setup
 ///   |- tests/
 ///      |- basic_health_test/   <-- test_env
 ///         |- ic_prep
@@ -19,6 +21,9 @@ use std::time::Duration;
 ///      |- other_test/          <-- test_env
 ///         |- ic_prep
 ///         |- test.log          <-- prefix :: log2
+///   |- failure_artifacts/     <-- test_env, only if a test failed or a process crashed
+///      |- nodes/<node_id>/     <-- logs and registry local store of a node
+///         |- crash/            <-- core dumps, backtraces and versions, only if a process crashed
 ///   |- tear_down/
 ///         |- ic_prep
 ///         |- test.log          <-- prefix :: finalization_log
@@ -32,6 +37,13 @@ pub const TESTS_DIR: &str = "tests";
 // Name of the group setup directory within the working directory.
 pub const GROUP_SETUP_DIR: &str = "setup";
 
+// Name of the teardown directory within the working directory.
+pub const TEARDOWN_DIR: &str = "tear_down";
+
+// Name of the directory within the working directory holding the artifacts
+// collected after a test failed.
+pub const FAILURE_ARTIFACTS_DIR: &str = "failure_artifacts";
+
 // Name of the root test environment.
 pub const ROOT_ENV_DIR: &str = "root_env";
 
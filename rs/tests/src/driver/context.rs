@@ -2,6 +2,7 @@
 
 use crate::driver::test_env::TestEnv;
 use anyhow::{bail, Context, Result};
+use regex::Regex;
 use slog::Logger;
 use std::{
     fs,
@@ -13,11 +14,39 @@ use crate::driver::{constants, event::TaskId, subprocess_ipc::LogSender};
 
 use slog::debug;
 
+/// Selects the test functions of a group that are executed by their name. All
+/// others are skipped.
+#[derive(Debug, Clone, Default)]
+pub struct TestFilter {
+    /// A substring the name has to contain.
+    pub include_substring: Option<String>,
+    /// A regular expression the name has to match.
+    pub include_pattern: Option<Regex>,
+    /// A regular expression the name must not match.
+    pub skip_pattern: Option<Regex>,
+}
+
+impl TestFilter {
+    pub fn matches(&self, test_name: &str) -> bool {
+        self.include_substring
+            .as_ref()
+            .map_or(true, |s| test_name.contains(s.as_str()))
+            && self
+                .include_pattern
+                .as_ref()
+                .map_or(true, |p| p.is_match(test_name))
+            && !self
+                .skip_pattern
+                .as_ref()
+                .map_or(false, |p| p.is_match(test_name))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GroupContext {
     pub exec_path: PathBuf,
     pub group_dir: PathBuf,
-    pub filter_tests: Option<String>,
+    pub filter_tests: TestFilter,
     logger: Logger,
     pub sock_id: u64,
     pub debug_keepalive: bool,
@@ -32,7 +61,7 @@ impl GroupContext {
     pub fn new(
         group_dir: PathBuf,
         subproc_info: Option<(TaskId, u64)>,
-        filter_tests: Option<String>,
+        filter_tests: TestFilter,
         debug_keepalive: bool,
     ) -> Result<Self> {
         let task_id = subproc_info.as_ref().map(|t| t.0.clone());
@@ -84,6 +113,37 @@ impl GroupContext {
         TestEnv::new(test_path, self.logger.clone())
     }
 
+    /// Renames the artifact directory of a failed attempt of [test_name], such
+    /// that a retry starts from a fresh copy of the setup directory while the
+    /// artifacts of the failed attempt are kept.
+    pub fn archive_test_dir(&self, test_name: &str, attempt: usize) -> Result<()> {
+        let test_path = self.group_dir.join(constants::TESTS_DIR).join(test_name);
+        if test_path.is_dir() {
+            let archive_path = self.archived_test_dir(test_name, attempt);
+            fs::rename(&test_path, &archive_path)
+                .with_context(|| format!("Could not move {:?} to {:?}", test_path, archive_path))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the existing artifact directories of [test_name], the ones of
+    /// the failed attempts first.
+    pub fn test_artifact_dirs(&self, test_name: &str, retries: usize) -> Vec<PathBuf> {
+        (1..=retries)
+            .map(|attempt| self.archived_test_dir(test_name, attempt))
+            .chain(std::iter::once(
+                self.group_dir.join(constants::TESTS_DIR).join(test_name),
+            ))
+            .filter(|dir| dir.is_dir())
+            .collect()
+    }
+
+    fn archived_test_dir(&self, test_name: &str, attempt: usize) -> PathBuf {
+        self.group_dir
+            .join(constants::TESTS_DIR)
+            .join(format!("{test_name}_attempt_{attempt}"))
+    }
+
     fn ensure_dir<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         let path = self.group_dir.parent().unwrap().join(path.as_ref());
         if path.is_dir() {
@@ -151,21 +211,69 @@ impl GroupContext {
     }
 
     pub fn create_test_env(&self, test_name: &str) -> Result<TestEnv> {
+        self.create_test_env_from(test_name, None)
+    }
+
+    /// Like [create_test_env](Self::create_test_env), but forks the
+    /// environment of the test `source_test` instead of the setup environment,
+    /// if given, e.g., the environment prepared by the setup of a suite.
+    pub fn create_test_env_from(
+        &self,
+        test_name: &str,
+        source_test: Option<&str>,
+    ) -> Result<TestEnv> {
+        let source_dir = match source_test {
+            Some(source_test) => {
+                let source_dir = self.group_dir.join(constants::TESTS_DIR).join(source_test);
+                source_dir.is_dir().then_some(source_dir)
+            }
+            None => self.get_setup_dir(),
+        };
         let target_dir = self.create_test_dir(test_name)?;
-        if let Some(setup_dir) = self.get_setup_dir() {
+        if let Some(source_dir) = source_dir {
             TestEnv::fork_from(
-                setup_dir.as_path(),
+                source_dir.as_path(),
                 target_dir.as_path(),
                 self.logger.clone(),
             )
         } else {
             bail!(
-                "cannot create TestEnv for {} as setup directory does not exist yet",
-                test_name
+                "cannot create TestEnv for {} as the directory of {} does not exist yet",
+                test_name,
+                source_test.unwrap_or("the setup")
             )
         }
     }
 
+    /// Creates the environment of the teardown function as a fork of the setup
+    /// environment or, if the setup did not start, of the root environment.
+    pub fn create_teardown_env(&self) -> Result<TestEnv> {
+        let source_dir = self
+            .get_setup_dir()
+            .unwrap_or_else(|| self.group_dir.join(constants::ROOT_ENV_DIR));
+        let target_dir = self.group_dir.join(constants::TEARDOWN_DIR);
+        TestEnv::fork_from(
+            source_dir.as_path(),
+            target_dir.as_path(),
+            self.logger.clone(),
+        )
+    }
+
+    /// Creates the environment into which the artifacts of a failed run are
+    /// collected as a fork of the setup environment, if the setup started.
+    pub fn create_failure_artifacts_env(&self) -> Result<Option<TestEnv>> {
+        let target_dir = self.group_dir.join(constants::FAILURE_ARTIFACTS_DIR);
+        self.get_setup_dir()
+            .map(|source_dir| {
+                TestEnv::fork_from(
+                    source_dir.as_path(),
+                    target_dir.as_path(),
+                    self.logger.clone(),
+                )
+            })
+            .transpose()
+    }
+
     pub fn logger(&self) -> Logger {
         self.logger.clone()
     }
@@ -5,19 +5,71 @@ use std::{
     collections::BTreeMap,
     panic::UnwindSafe,
     path::{Path},
+    time::Duration,
 };
 
 use crate::driver::{
     pot_dsl::{PotSetupFn, SysTestFn},
+    success_criteria::SuccessCriterion,
     test_env::TestEnv,
 };
 use slog::Logger;
 
+/// Registers a test function under its own name:
+///
+/// ```ignore
+/// SystemTestGroup::new()
+///     .add_test(systest!(test))
+///     .add_test(systest!(long_test, Duration::from_secs(30 * 60)))
+/// ```
+///
+/// A test function taking parameters can be registered several times with a
+/// closure capturing them, each time under a distinct name:
+///
+/// ```ignore
+/// for rps in [100, 1000] {
+///     group = group.add_test(systest!(
+///         name = format!("workload_test_{}_rps", rps),
+///         move |env| workload_test(env, rps)
+///     ));
+/// }
+/// ```
 #[macro_export]
 macro_rules! systest {
+    (name = $name:expr, $f:expr) => {
+        ic_tests::driver::dsl::TestFunction::new(&$name, $f)
+    };
+    (name = $name:expr, $f:expr, $timeout:expr) => {
+        ic_tests::driver::dsl::TestFunction::new(&$name, $f).with_timeout($timeout)
+    };
     ($a:path) => {
         ic_tests::driver::dsl::TestFunction::new(std::stringify!($a), $a)
     };
+    ($a:path, $timeout:expr) => {
+        ic_tests::driver::dsl::TestFunction::new(std::stringify!($a), $a).with_timeout($timeout)
+    };
+}
+
+/// Composes tests and subgroups into a subgroup running all of them at the
+/// same time, e.g., a workload next to the injection of failures.
+#[macro_export]
+macro_rules! parallel {
+    ($($sub_group:expr),+ $(,)?) => {
+        ic_tests::driver::group::SystemTestSubGroup::parallel(vec![
+            $(ic_tests::driver::group::SystemTestSubGroup::from($sub_group)),+
+        ])
+    };
+}
+
+/// Composes tests and subgroups into a subgroup running them one after the
+/// other, in the given order.
+#[macro_export]
+macro_rules! sequential {
+    ($($sub_group:expr),+ $(,)?) => {
+        ic_tests::driver::group::SystemTestSubGroup::sequential(vec![
+            $(ic_tests::driver::group::SystemTestSubGroup::from($sub_group)),+
+        ])
+    };
 }
 
 pub struct SystemTestGroup {
@@ -28,6 +80,11 @@ pub struct SystemTestGroup {
 pub struct TestFunction {
     name: String,
     f: Box<dyn SysTestFn>,
+    timeout: Option<Duration>,
+    retries: Option<usize>,
+    prerequisites: Vec<String>,
+    success_criteria: Vec<SuccessCriterion>,
+    conditions: Vec<(String, Box<dyn Fn() -> bool>)>,
 }
 
 impl TestFunction {
@@ -35,13 +92,95 @@ impl TestFunction {
         Self {
             name: name.to_string(),
             f: Box::new(f),
+            timeout: None,
+            retries: None,
+            prerequisites: vec![],
+            success_criteria: vec![],
+            conditions: vec![],
         }
     }
 
+    /// Overrides the group's timeout per test for this test function only.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Overrides the group's number of retries for this test function only.
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = Some(retries);
+        self
+    }
+
+    /// Runs this test function only after the test function `prerequisite`
+    /// passed, even if both are in a parallel subgroup, and skips it if
+    /// `prerequisite` failed or was skipped. Within a suite, `prerequisite`
+    /// names a test of the same suite. The time waiting for `prerequisite`
+    /// counts towards the timeout of this test function.
+    pub fn after(mut self, prerequisite: &str) -> Self {
+        self.prerequisites.push(prerequisite.to_string());
+        self
+    }
+
+    /// Fails this test function if `criterion` is violated while it runs or
+    /// once it finished, see [SuccessCriterion]. The group needs to deploy a
+    /// [PrometheusVm](crate::driver::prometheus_vm::PrometheusVm).
+    pub fn with_success_criterion(mut self, criterion: SuccessCriterion) -> Self {
+        self.success_criteria.push(criterion);
+        self
+    }
+
+    /// Runs this test function only if `predicate` holds when the group is
+    /// composed, and reports it as skipped otherwise, e.g., if it `requires`
+    /// hardware that is not available:
+    ///
+    /// ```ignore
+    /// systest!(sign_with_hsm).only_if("an HSM", || Path::new("/dev/hsm").exists())
+    /// ```
+    pub fn only_if<P: Fn() -> bool + 'static>(mut self, requires: &str, predicate: P) -> Self {
+        self.conditions
+            .push((requires.to_string(), Box::new(predicate)));
+        self
+    }
+
+    /// Runs this test function only if the environment variable `var` is set
+    /// to a non-empty value, and reports it as skipped otherwise.
+    pub fn only_if_env(self, var: &str) -> Self {
+        let name = var.to_string();
+        self.only_if(&format!("${} to be set", var), move || {
+            std::env::var_os(&name).map_or(false, |value| !value.is_empty())
+        })
+    }
+
+    /// The reason to skip this test function, i.e., the first of its
+    /// conditions that does not hold, if any.
+    pub fn skip_reason(&self) -> Option<String> {
+        self.conditions
+            .iter()
+            .find(|(_, predicate)| !predicate())
+            .map(|(requires, _)| format!("requires {}", requires))
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub fn retries(&self) -> Option<usize> {
+        self.retries
+    }
+
+    pub fn prerequisites(&self) -> &[String] {
+        &self.prerequisites
+    }
+
+    pub fn success_criteria(&self) -> &[SuccessCriterion] {
+        &self.success_criteria
+    }
+
     pub fn f(self) -> Box<dyn SysTestFn> {
         self.f
     }
@@ -79,6 +79,13 @@ pub enum EventPayload {
         task_id: TaskId,
         msg: String,
     },
+    /// The process of a task failed and is restarted; `attempt` is the number
+    /// of the retry that is about to start.
+    TaskRetried {
+        task_id: TaskId,
+        attempt: usize,
+        msg: String,
+    },
     TaskSubReport {
         task_id: TaskId,
         sub_report: String,
@@ -86,6 +93,12 @@ pub enum EventPayload {
     TaskStopped {
         task_id: TaskId,
     },
+    /// The scheduler stopped a task before it finished on its own, e.g., as
+    /// the group timed out. It precedes the [EventPayload::TaskStopped] event
+    /// of the task.
+    TaskStopRequested {
+        task_id: TaskId,
+    },
     StartSchedule,
 }
 
@@ -113,6 +126,14 @@ impl Event {
         Self::now(EventPayload::TaskFailed { task_id, msg })
     }
 
+    pub fn task_retried(task_id: TaskId, attempt: usize, msg: String) -> Self {
+        Self::now(EventPayload::TaskRetried {
+            task_id,
+            attempt,
+            msg,
+        })
+    }
+
     pub fn task_sub_report(task_id: TaskId, sub_report: String) -> Self {
         Self::now(EventPayload::TaskSubReport {
             task_id,
@@ -124,6 +145,10 @@ impl Event {
         Self::now(EventPayload::TaskStopped { task_id })
     }
 
+    pub fn task_stop_requested(task_id: TaskId) -> Self {
+        Self::now(EventPayload::TaskStopRequested { task_id })
+    }
+
     fn now(what: EventPayload) -> Self {
         Self {
             when: SystemTime::now(),
@@ -0,0 +1,229 @@
+//! Collection of the artifacts needed to debug a failed [SystemTestGroup](crate::driver::group::SystemTestGroup)
+//! run, such that test authors do not need to script it themselves. For every
+//! node of the testnet, the replica and orchestrator logs as well as the
+//! registry local store of the node are downloaded. If the group deployed a
+//! Prometheus VM, its data directory is downloaded as well.
+//!
+//! Nodes on which a process dumped core additionally get their core dumps, the
+//! backtraces extracted from them and the versions of the IC binaries
+//! downloaded. The core dumps are read from the directory in which
+//! systemd-coredump, which is installed on the dev images, stores them. As
+//! replicas may crash without failing a test, e.g., if the rest of their subnet
+//! makes progress, the nodes are checked for core dumps after passing runs as
+//! well, see [crashed_nodes].
+//!
+//! Collection is best-effort: nodes that cannot be reached are skipped and
+//! errors are only logged, such that they do not mask the outcome of the tests.
+
+use std::{
+    fs,
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::Path,
+    time::Duration,
+};
+
+use anyhow::{bail, Result};
+use ic_types::NodeId;
+use slog::{info, warn, Logger};
+
+use crate::driver::{
+    prometheus_vm::HasPrometheus,
+    ssh_exec::{SshCommand, SshExec},
+    test_env::TestEnv,
+    test_env_api::{HasTopologySnapshot, IcNodeContainer, IcNodeSnapshot},
+};
+
+/// Name of the directory within the environment holding the per-node artifacts.
+const NODES_DIR: &str = "nodes";
+/// Timeout of each SSH operation, such that an unresponsive node does not
+/// stall the collection.
+const SSH_TIMEOUT: Duration = Duration::from_secs(2 * 60); // 2 minutes
+
+/// The files downloaded from every node along with the commands producing them.
+const NODE_ARTIFACTS: &[(&str, &str)] = &[
+    ("replica.log", "journalctl --no-pager _COMM=replica"),
+    (
+        "orchestrator.log",
+        "journalctl --no-pager _COMM=orchestrator",
+    ),
+    ("journal.log", "journalctl --no-pager --boot"),
+    (
+        "ic_registry_local_store.tar.gz",
+        "sudo tar -cz -C /var/lib/ic/data ic_registry_local_store",
+    ),
+];
+
+/// Where systemd-coredump stores the core dumps of all processes.
+const CORE_DUMP_DIR: &str = "/var/lib/systemd/coredump";
+/// Lists the core dumps of all processes, failing if there is no directory
+/// yet, as it is only created on the first core dump.
+const LIST_CORE_DUMPS: &str = "sudo find /var/lib/systemd/coredump -type f -name 'core.*'";
+/// The files downloaded from nodes with core dumps, within `crash/`.
+const CRASH_ARTIFACTS: &[(&str, &str)] = &[
+    ("core_dumps.txt", LIST_CORE_DUMPS),
+    ("backtraces.txt", "sudo coredumpctl --no-pager info"),
+    (
+        "core_dumps.tar",
+        "sudo tar -c -C /var/lib/systemd/coredump .",
+    ),
+    (
+        "version.txt",
+        "cat /opt/ic/share/version.txt && sha256sum /opt/ic/bin/replica /opt/ic/bin/orchestrator",
+    ),
+];
+const CRASH_DIR: &str = "crash";
+
+/// Downloads the artifacts of all nodes of the topology of `env` into the
+/// directory of `env`.
+pub fn collect_failure_artifacts(env: &TestEnv) {
+    let log = env.logger();
+    info!(
+        log,
+        "Collecting failure artifacts into {:?} ...",
+        env.base_path()
+    );
+    match topology_nodes(env) {
+        Some(nodes) => {
+            for node in nodes {
+                let node_dir = env.get_path(NODES_DIR).join(node.node_id.to_string());
+                if let Err(e) = collect_node_artifacts(&log, &node, &node_dir) {
+                    warn!(
+                        log,
+                        "Failed to collect artifacts of node {}: {:?}", node.node_id, e
+                    );
+                }
+            }
+        }
+        None => warn!(log, "No topology found, skipping the node artifacts."),
+    }
+    if catch_unwind(AssertUnwindSafe(|| {
+        env.download_prometheus_data_dir_if_exists()
+    }))
+    .is_err()
+    {
+        warn!(log, "Failed to download the Prometheus data directory.");
+    }
+    info!(log, "Finished collecting failure artifacts.");
+}
+
+/// Returns the IDs of the nodes of the topology of `env` on which a process
+/// dumped core. Nodes that cannot be reached are skipped.
+pub fn crashed_nodes(env: &TestEnv) -> Vec<NodeId> {
+    let log = env.logger();
+    topology_nodes(env)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|node| match has_core_dumps(node) {
+            Ok(crashed) => crashed,
+            Err(e) => {
+                warn!(
+                    log,
+                    "Failed to check node {} for core dumps: {:?}", node.node_id, e
+                );
+                false
+            }
+        })
+        .map(|node| node.node_id)
+        .collect()
+}
+
+/// Downloads the crash artifacts of the nodes `node_ids` of the topology of
+/// `env` into the directory of `env`.
+pub fn collect_crash_artifacts(env: &TestEnv, node_ids: &[NodeId]) {
+    let log = env.logger();
+    info!(
+        log,
+        "Collecting crash artifacts of {} nodes into {:?} ...",
+        node_ids.len(),
+        env.base_path()
+    );
+    for node in topology_nodes(env)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|node| node_ids.contains(&node.node_id))
+    {
+        let node_dir = env.get_path(NODES_DIR).join(node.node_id.to_string());
+        if let Err(e) = download_crash_artifacts(&log, &node, &node_dir) {
+            warn!(
+                log,
+                "Failed to collect crash artifacts of node {}: {:?}", node.node_id, e
+            );
+        }
+    }
+}
+
+/// The nodes of the topology of `env`, or `None` if it has no IC.
+fn topology_nodes(env: &TestEnv) -> Option<Vec<IcNodeSnapshot>> {
+    // Obtaining the topology panics if the group did not deploy an IC.
+    catch_unwind(AssertUnwindSafe(|| {
+        let topology = env.topology_snapshot();
+        topology
+            .subnets()
+            .flat_map(|subnet| subnet.nodes())
+            .chain(topology.unassigned_nodes())
+            .collect::<Vec<_>>()
+    }))
+    .ok()
+}
+
+fn ssh_command(command: &str) -> SshCommand {
+    // A single attempt, as a node which is down would otherwise stall the
+    // collection for the whole SSH retry timeout.
+    SshCommand::new(command)
+        .with_timeout(SSH_TIMEOUT)
+        .with_retry_timeout(Duration::ZERO)
+}
+
+fn collect_node_artifacts(log: &Logger, node: &IcNodeSnapshot, node_dir: &Path) -> Result<()> {
+    fs::create_dir_all(node_dir)?;
+    download_artifacts(log, node, NODE_ARTIFACTS, node_dir);
+    if has_core_dumps(node)? {
+        download_crash_artifacts(log, node, node_dir)?;
+    }
+    Ok(())
+}
+
+fn download_crash_artifacts(log: &Logger, node: &IcNodeSnapshot, node_dir: &Path) -> Result<()> {
+    warn!(
+        log,
+        "Node {} has core dumps in {}.", node.node_id, CORE_DUMP_DIR
+    );
+    let crash_dir = node_dir.join(CRASH_DIR);
+    fs::create_dir_all(&crash_dir)?;
+    download_artifacts(log, node, CRASH_ARTIFACTS, &crash_dir);
+    Ok(())
+}
+
+/// Downloads the output of each command of `artifacts` into the file of the
+/// given name in `dir`, logging failures.
+fn download_artifacts(log: &Logger, node: &IcNodeSnapshot, artifacts: &[(&str, &str)], dir: &Path) {
+    for (file_name, command) in artifacts {
+        let path = dir.join(file_name);
+        if let Err(e) = download_command_output(node, command, &path) {
+            warn!(
+                log,
+                "Failed to download {} of node {}: {:?}", file_name, node.node_id, e
+            );
+        }
+    }
+}
+
+fn has_core_dumps(node: &IcNodeSnapshot) -> Result<bool> {
+    let output = node.ssh_exec(&ssh_command(LIST_CORE_DUMPS))?;
+    Ok(output.success() && !output.stdout.trim().is_empty())
+}
+
+/// Runs `command` on `node` and writes its stdout to `path`, also if it fails.
+fn download_command_output(node: &IcNodeSnapshot, command: &str, path: &Path) -> Result<()> {
+    let output = node.ssh_exec_raw(&ssh_command(command))?;
+    fs::write(path, &output.stdout)?;
+    if output.exit_status != 0 {
+        bail!(
+            "`{}` exited with {}: {}",
+            command,
+            output.exit_status,
+            output.stderr.trim()
+        );
+    }
+    Ok(())
+}
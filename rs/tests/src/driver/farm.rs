@@ -10,6 +10,7 @@ use anyhow::Result;
 use chrono::{DateTime, Utc};
 use ic_crypto_sha::Sha256;
 use reqwest::blocking::{multipart, Client, RequestBuilder};
+use reqwest::StatusCode;
 use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use slog::{error, info, warn, Logger};
@@ -18,7 +19,12 @@ use std::io::Write;
 use thiserror::Error;
 use url::Url;
 
-use crate::driver::{ic::ImageSizeGiB, test_env::TestEnv};
+use crate::driver::{
+    ic::ImageSizeGiB,
+    local_backend::{LocalBackend, LOCAL_BACKEND_SCHEME},
+    resource_usage::ResourceUsage,
+    test_env::TestEnv,
+};
 
 pub type FarmResult<T> = Result<T, FarmError>;
 
@@ -39,12 +45,14 @@ const TIMEOUT_SETTINGS: TimeoutSettings = TimeoutSettings {
 
 /// Farm managed resources that make up the Internet Computer under test. The
 /// `Farm`-structure translates abstract requests (for resources) to concrete
-/// http-requests.
+/// http-requests. For base urls with the `local` scheme, the requests are
+/// served by a [LocalBackend] instead.
 #[derive(Clone, Debug)]
 pub struct Farm {
     pub base_url: Url,
     pub logger: Logger,
     client: Client,
+    local: Option<LocalBackend>,
 }
 
 impl Farm {
@@ -53,14 +61,18 @@ impl Farm {
             .timeout(TIMEOUT_SETTINGS.max_http_timeout)
             .build()
             .expect("This should not fail.");
+        let local = (base_url.scheme() == LOCAL_BACKEND_SCHEME)
+            .then(|| LocalBackend::new(&base_url, logger.clone()));
         Farm {
             base_url,
             client,
             logger,
+            local,
         }
     }
 
     pub fn acquire_playnet_certificate(&self, group_name: &str) -> FarmResult<PlaynetCertificate> {
+        self.fail_if_local("acquiring a playnet certificate")?;
         let path = format!("group/{}/playnet/certificate", group_name);
         let resp = self.retry_until_success_long(self.post(&path))?;
         let playnet_cert = resp.json::<PlaynetCertificate>()?;
@@ -74,6 +86,9 @@ impl Farm {
         spec: GroupSpec,
         env: &TestEnv,
     ) -> FarmResult<()> {
+        if let Some(local) = &self.local {
+            return local.create_group(group_name);
+        }
         let path = format!("group/{}", group_name);
         let ttl = ttl.as_secs() as u32;
         let spec = spec.add_meta(env);
@@ -86,6 +101,9 @@ impl Farm {
     /// creates a vm under the group `group_name` and returns the associated
     /// IpAddr
     pub fn create_vm(&self, group_name: &str, vm: CreateVmRequest) -> FarmResult<VMCreateResponse> {
+        if let Some(local) = &self.local {
+            return local.create_vm(group_name, vm);
+        }
         let path = format!("group/{}/vm/{}", group_name, &vm.name);
         let rb = Self::json(self.post(&path), &vm);
         let resp = self.retry_until_success_long(rb)?;
@@ -104,6 +122,9 @@ impl Farm {
     }
 
     pub fn claim_file(&self, file_id: &FileId) -> FarmResult<ClaimResult> {
+        if let Some(local) = &self.local {
+            return local.claim_file(file_id);
+        }
         let path = format!("file/{}", file_id);
         let rb = self.put(&path);
         match self.retry_until_success(rb) {
@@ -118,6 +139,9 @@ impl Farm {
 
     /// uploads an image an returns the image id
     pub fn upload_file<P: AsRef<Path>>(&self, path: P, filename: &str) -> FarmResult<FileId> {
+        if let Some(local) = &self.local {
+            return local.upload_file(path, filename);
+        }
         let form = multipart::Form::new()
             .file(filename.to_string(), path)
             .expect("could not create multipart for image");
@@ -142,6 +166,9 @@ impl Farm {
         template_name: &str,
         image_ids: Vec<FileId>,
     ) -> FarmResult<()> {
+        if let Some(local) = &self.local {
+            return local.attach_disk_images(group_name, vm_name, image_ids);
+        }
         let path = format!(
             "group/{}/vm/{}/drive-templates/{}",
             group_name, vm_name, template_name
@@ -160,6 +187,9 @@ impl Farm {
     }
 
     pub fn start_vm(&self, group_name: &str, vm_name: &str) -> FarmResult<()> {
+        if let Some(local) = &self.local {
+            return local.start_vm(group_name, vm_name);
+        }
         let path = format!("group/{}/vm/{}/start", group_name, vm_name);
         let rb = self.put(&path);
         let _resp = self.retry_until_success(rb)?;
@@ -172,6 +202,9 @@ impl Farm {
     }
 
     pub fn destroy_vm(&self, group_name: &str, vm_name: &str) -> FarmResult<()> {
+        if let Some(local) = &self.local {
+            return local.destroy_vm(group_name, vm_name);
+        }
         let path = format!("group/{}/vm/{}/destroy", group_name, vm_name);
         let rb = self.put(&path);
         let _resp = self.retry_until_success(rb)?;
@@ -179,15 +212,113 @@ impl Farm {
     }
 
     pub fn reboot_vm(&self, group_name: &str, vm_name: &str) -> FarmResult<()> {
+        if let Some(local) = &self.local {
+            return local.reboot_vm(group_name, vm_name);
+        }
         let path = format!("group/{}/vm/{}/reboot", group_name, vm_name);
         let rb = self.put(&path);
         let _resp = self.retry_until_success(rb)?;
         Ok(())
     }
 
+    /// Returns the output of the serial console of a VM starting at byte
+    /// `offset`. The request is not retried, as it is meant to be polled.
+    pub fn read_console_log(
+        &self,
+        group_name: &str,
+        vm_name: &str,
+        offset: u64,
+    ) -> FarmResult<ConsoleLogRead> {
+        if let Some(local) = &self.local {
+            return local.read_console_log(group_name, vm_name, offset);
+        }
+        let path = format!("group/{}/vm/{}/console.log", group_name, vm_name);
+        let resp = self
+            .client
+            .get(self.url_from_path(&path))
+            .header(reqwest::header::RANGE, format!("bytes={}-", offset))
+            .send()?;
+        match resp.status() {
+            // The range of an unsatisfiable request is `bytes */<log length>`.
+            StatusCode::RANGE_NOT_SATISFIABLE => {
+                let log_len = resp
+                    .headers()
+                    .get(reqwest::header::CONTENT_RANGE)
+                    .and_then(|range| range.to_str().ok())
+                    .and_then(|range| range.strip_prefix("bytes */"))
+                    .and_then(|len| len.parse::<u64>().ok());
+                Ok(match log_len {
+                    Some(len) if len < offset => ConsoleLogRead::Truncated,
+                    _ => ConsoleLogRead::Appended(vec![]),
+                })
+            }
+            StatusCode::NOT_FOUND => Err(FarmError::NotFound {
+                message: resp.text().unwrap_or_default(),
+            }),
+            StatusCode::PARTIAL_CONTENT => Ok(ConsoleLogRead::Appended(resp.bytes()?.to_vec())),
+            // The whole log is returned if ranges are not supported.
+            status if status.is_success() => {
+                let log = resp.bytes()?;
+                if (log.len() as u64) < offset {
+                    return Ok(ConsoleLogRead::Truncated);
+                }
+                Ok(ConsoleLogRead::Appended(
+                    log.into_iter().skip(offset as usize).collect(),
+                ))
+            }
+            status => Err(FarmError::InvalidResponse {
+                message: format!("reading the console log returned {}", status),
+            }),
+        }
+    }
+
+    /// Snapshots the disks and the memory of a running VM under the name
+    /// `snapshot_name`, replacing an existing snapshot of the same name.
+    pub fn snapshot_vm(
+        &self,
+        group_name: &str,
+        vm_name: &str,
+        snapshot_name: &str,
+    ) -> FarmResult<()> {
+        if let Some(local) = &self.local {
+            return local.snapshot_vm(group_name, vm_name, snapshot_name);
+        }
+        let path = format!(
+            "group/{}/vm/{}/snapshot/{}",
+            group_name, vm_name, snapshot_name
+        );
+        let rb = self.put(&path);
+        let _resp = self.retry_until_success_long(rb)?;
+        Ok(())
+    }
+
+    /// Restores the disks and the memory of a VM from the snapshot
+    /// `snapshot_name`, after which the VM continues to run from that state.
+    pub fn restore_vm(
+        &self,
+        group_name: &str,
+        vm_name: &str,
+        snapshot_name: &str,
+    ) -> FarmResult<()> {
+        if let Some(local) = &self.local {
+            return local.restore_vm(group_name, vm_name, snapshot_name);
+        }
+        let path = format!(
+            "group/{}/vm/{}/snapshot/{}/restore",
+            group_name, vm_name, snapshot_name
+        );
+        let rb = self.put(&path);
+        let _resp = self.retry_until_success_long(rb)?;
+        Ok(())
+    }
+
     // delete with large timeout but only one attempt, because it takes a long time and farm's
     // garbage collector would interfere with retries.
     pub fn delete_group(&self, group_name: &str) {
+        if let Some(local) = &self.local {
+            local.delete_group(group_name);
+            return;
+        }
         // bump TTL, so that farm garbage collector does not remove while we remove
         if self
             .set_group_ttl(group_name, Duration::from_secs(120))
@@ -217,6 +348,7 @@ impl Farm {
         group_name: &str,
         dns_records: Vec<DnsRecord>,
     ) -> FarmResult<String> {
+        self.fail_if_local("creating DNS records")?;
         let path = format!("group/{}/dns", group_name);
         let rb = Self::json(self.post(&path), &dns_records);
         let resp = self.retry_until_success_long(rb)?;
@@ -233,6 +365,7 @@ impl Farm {
         group_name: &str,
         dns_records: Vec<DnsRecord>,
     ) -> FarmResult<String> {
+        self.fail_if_local("creating playnet DNS records")?;
         let path = format!("group/{}/playnet/dns", group_name);
         let rb = Self::json(self.post(&path), &dns_records);
         let resp = self.retry_until_success_long(rb)?;
@@ -240,13 +373,91 @@ impl Farm {
         Ok(create_dns_records_result.suffix)
     }
 
+    /// Returns the names of the VMs of the group that were preempted, which
+    /// only happens to groups allocated on preemptible resources, see
+    /// [GroupSpec::preemptible].
+    pub fn preempted_vms(&self, group_name: &str) -> FarmResult<Vec<String>> {
+        // Local VMs are never preempted.
+        if self.local.is_some() {
+            return Ok(vec![]);
+        }
+        let path = format!("group/{}", group_name);
+        let rb = self.get(&path);
+        let resp = self.retry_until_success(rb)?;
+        let group = resp.json::<GroupInfo>()?;
+        Ok(group
+            .vms
+            .into_iter()
+            .filter(|vm| vm.preempted)
+            .map(|vm| vm.name)
+            .collect())
+    }
+
+    /// Returns the resources allocated to the VMs of the group. Local VMs do
+    /// not consume Farm resources.
+    pub fn group_usage(&self, group_name: &str) -> FarmResult<ResourceUsage> {
+        if self.local.is_some() {
+            return Ok(ResourceUsage::default());
+        }
+        let path = format!("group/{}", group_name);
+        let rb = self.get(&path);
+        let resp = self.retry_until_success(rb)?;
+        let group = resp.json::<GroupInfo>()?;
+        Ok(group
+            .vms
+            .iter()
+            .fold(ResourceUsage::default(), |usage, vm| ResourceUsage {
+                vms: usage.vms + 1,
+                vcpus: usage.vcpus + vm.spec.as_ref().map_or(0, |spec| spec.v_cpus),
+                memory_kibibytes: usage.memory_kibibytes
+                    + vm.spec.as_ref().map_or(0, |spec| spec.memory_ki_b),
+            }))
+    }
+
+    /// Re-allocates a preempted VM on another host, keeping its name, spec,
+    /// IPv6 address and attached disk images. The VM needs to be started
+    /// afterwards.
+    pub fn reallocate_vm(&self, group_name: &str, vm_name: &str) -> FarmResult<VMCreateResponse> {
+        self.fail_if_local("re-allocating VMs")?;
+        let path = format!("group/{}/vm/{}/reallocate", group_name, vm_name);
+        let rb = self.put(&path);
+        let resp = self.retry_until_success_long(rb)?;
+        let created_vm = resp.json::<VMCreateResponse>()?;
+        info!(
+            self.logger,
+            "VM({}) re-allocated on Host: {} IPv6: {}",
+            vm_name,
+            created_vm.hostname,
+            created_vm.ipv6
+        );
+        Ok(created_vm)
+    }
+
     pub fn set_group_ttl(&self, group_name: &str, duration: Duration) -> FarmResult<()> {
+        // Local groups live until they are deleted.
+        if self.local.is_some() {
+            return Ok(());
+        }
         let path = format!("group/{}/ttl/{}", group_name, duration.as_secs());
         let rb = self.put(&path);
         let _resp = self.retry_until_success(rb)?;
         Ok(())
     }
 
+    fn fail_if_local(&self, operation: &str) -> FarmResult<()> {
+        if self.local.is_some() {
+            return Err(FarmError::LocalBackendError {
+                message: format!("{} is not supported", operation),
+            });
+        }
+        Ok(())
+    }
+
+    fn get(&self, path: &str) -> RequestBuilder {
+        let url = self.url_from_path(path);
+        self.client.get(url)
+    }
+
     fn post(&self, path: &str) -> RequestBuilder {
         let url = self.url_from_path(path);
         self.client.post(url)
@@ -342,6 +553,16 @@ pub enum ClaimResult {
     FileClaimed(FileExpiration),
 }
 
+/// The output of a serial console read starting at an offset.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConsoleLogRead {
+    /// The bytes following the offset, empty if nothing was appended.
+    Appended(Vec<u8>),
+    /// The log is shorter than the offset, i.e., it was truncated or rotated,
+    /// and has to be read from the start again.
+    Truncated,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct FileExpiration {
     pub expiration: Option<DateTime<Utc>>,
@@ -390,6 +611,10 @@ pub struct GroupSpec {
     pub preferred_network: Option<String>,
     #[serde(rename = "metadata")]
     pub metadata: Option<GroupMetadata>,
+    /// Allocate the VMs of the group on cheaper resources, which Farm might
+    /// reclaim at any time, see [Farm::preempted_vms].
+    #[serde(rename = "preemptible")]
+    pub preemptible: bool,
 }
 
 impl GroupSpec {
@@ -543,6 +768,10 @@ pub struct CreateVmRequest {
 }
 
 impl CreateVmRequest {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn new(
         name: String,
         vm_type: VmType,
@@ -610,6 +839,9 @@ pub enum FarmError {
 
     #[error(transparent)]
     IoError(#[from] std::io::Error),
+
+    #[error("Local backend: {message}")]
+    LocalBackendError { message: String },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -627,6 +859,20 @@ pub struct VmSpec {
     pub memory_ki_b: u64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct GroupInfo {
+    pub vms: Vec<VmInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct VmInfo {
+    pub name: String,
+    #[serde(default)]
+    pub preempted: bool,
+    #[serde(default)]
+    pub spec: Option<VmSpec>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct ImageUploadResponse {
@@ -1,56 +1,75 @@
 #![allow(dead_code)]
 #[rustfmt::skip]
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::driver::{
     farm::Farm,
     test_env_api::{FarmBaseUrl, HasGroupSetup},
     {
         action_graph::ActionGraph,
-        context::{GroupContext, ProcessContext},
+        context::{GroupContext, ProcessContext, TestFilter},
         dsl::{SubprocessFn, TestFunction},
         event::{
             BroadcastingEventSubscriberFactory, Event, EventBroadcaster, EventPayload, TaskId,
         },
         plan::{EvalOrder, Plan},
+        preemption::watch_preemptions,
         process::ProcessEventPayload,
         report::{
             Outcome, SystemTestGroupError, SystemTestGroupReport, TargetFunctionFailure,
             TargetFunctionSuccess,
         },
+        resource_usage::{sample_usage, UsageSamples},
         task::{DebugKeepaliveTask, EmptyTask},
         task_scheduler::{new_task_scheduler, TaskTable},
     },
 };
 use crate::driver::{
     pot_dsl::{PotSetupFn, SysTestFn},
-    test_env::{TestEnv, TestEnvAttribute},
+    test_env::{TestEnv, TestEnvAttribute, TestSeed},
     test_env_api::HasIcDependencies,
     test_setup::GroupSetup,
+    testnet_snapshot::HasTestnetSnapshot,
 };
 use serde::{Deserialize, Serialize};
 
 use anyhow::{bail, Result};
 use clap::Parser;
+use regex::Regex;
 use tokio::runtime::{Builder, Handle, Runtime};
 
 use crate::driver::{
-    constants::{kibana_link, GROUP_TTL, KEEPALIVE_INTERVAL},
-    subprocess_task::SubprocessTask,
+    colocate::{self, Colocation, COLOCATED_TEST_NAME, COLOCATED_VM_NAME},
+    constants::{
+        kibana_link, FAILURE_ARTIFACTS_DIR, GROUP_SETUP_DIR, GROUP_TTL, KEEPALIVE_INTERVAL,
+        TEARDOWN_DIR,
+    },
+    failure_artifacts::{collect_crash_artifacts, collect_failure_artifacts, crashed_nodes},
+    keepalive::keep_testnet_alive,
+    local_backend::{check_local_backend_prerequisites, local_backend_url, DEFAULT_LOCAL_BRIDGE},
+    results::TestStatus,
+    shard::{Shard, ShardAssignment},
+    subprocess_task::{panic_to_result, SubprocessTask},
+    success_criteria::{run_with_success_criteria, SuccessCriterion},
     task::{SkipTestTask, Task},
+    test_dependencies::{check_dependencies, DependentTask, TestOutcomes, TestPosition},
+    testnet_description::write_testnet_description,
     timeout::TimeoutTask,
 };
 use std::{
+    collections::BTreeMap,
     iter::once,
+    panic::{catch_unwind, AssertUnwindSafe},
     sync::Arc,
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 
-use slog::{debug, info, trace, Logger};
+use slog::{debug, error, info, trace, warn, Logger};
 
 const DEFAULT_TIMEOUT_PER_TEST: Duration = Duration::from_secs(60 * 10); // 10 minutes
 const DEFAULT_OVERALL_TIMEOUT: Duration = Duration::from_secs(60 * 10); // 10 minutes
+const DEFAULT_TIMEOUT_GRACE_PERIOD: Duration = Duration::from_secs(60 * 5); // 5 minutes
 const MAX_RUNTIME_THREADS: usize = 16;
 const MAX_RUNTIME_BLOCKING_THREADS: usize = 16;
 
@@ -58,7 +77,18 @@ const DEBUG_KEEPALIVE_TASK_NAME: &str = "debug_keepalive";
 const REPORT_TASK_NAME: &str = "report";
 const KEEPALIVE_TASK_NAME: &str = "keepalive";
 const SETUP_TASK_NAME: &str = "setup";
+const TEARDOWN_TASK_NAME: &str = "teardown";
+/// The name under which exceeding `--vm-hours-budget` is reported as failure.
+const VM_HOURS_BUDGET_TASK_NAME: &str = "vm_hours_budget";
 const LIFETIME_GUARD_TASK_PREFIX: &str = "lifetime_guard_";
+const GROUP_TIMEOUT_TASK_NAME: &str = "::group";
+/// The time a colocating run needs on top of the overall timeout of the
+/// colocated test driver, e.g., to boot its universal VM.
+const COLOCATION_OVERHEAD: Duration = Duration::from_secs(60 * 15); // 15 minutes
+const SETUP_SNAPSHOT_NAME: &str = "setup";
+/// How long the Farm group of an exported or reused setup is kept alive after
+/// the run finished.
+const EXPORTED_GROUP_TTL: Duration = Duration::from_secs(60 * 60 * 4); // 4 hours
 
 #[derive(Parser, Debug)]
 pub struct CliArgs {
@@ -80,19 +110,178 @@ pub struct CliArgs {
     )]
     pub filter_tests: Option<String>,
 
+    #[clap(
+        long = "include-pattern",
+        help = r#"Execute only those test functions whose name matches a regular expression and skip all the others."#
+    )]
+    pub include_pattern: Option<Regex>,
+
+    #[clap(
+        long = "skip-pattern",
+        help = r#"Skip the test functions whose name matches a regular expression."#
+    )]
+    pub skip_pattern: Option<Regex>,
+
     #[clap(
         long = "farm-base-url",
         help = r#"Use a custom url for the Farm webservice."#
     )]
     pub farm_base_url: Option<url::Url>,
+
+    #[clap(
+        long = "local-backend",
+        help = r#"
+Provision the VMs as local qemu VMs with their state in this directory instead
+of using Farm. Containers are not supported. The host needs qemu-system-x86_64
+with KVM, the OVMF firmware at /usr/share/OVMF/OVMF_CODE.fd and the bridge given
+by --local-bridge, which has to exist, be allowed in the ACL of the qemu bridge
+helper and announce the prefix fd00:1c::/64 via router advertisements. The VMs
+get no IPv4 addresses and no DNS records."#
+    )]
+    pub local_backend: Option<PathBuf>,
+
+    #[clap(
+        long = "local-bridge",
+        default_value = DEFAULT_LOCAL_BRIDGE,
+        help = r#"The bridge the VMs of the local backend are attached to."#
+    )]
+    pub local_bridge: String,
+
+    #[clap(
+        long = "junit-xml-output",
+        help = r#"Write the test results in JUnit XML format to this file."#
+    )]
+    pub junit_xml_output: Option<PathBuf>,
+
+    #[clap(
+        long = "json-output",
+        help = r#"Write the test results, including durations, failure messages and artifact directories, as JSON to this file."#
+    )]
+    pub json_output: Option<PathBuf>,
+
+    #[clap(
+        long = "export-setup",
+        help = r#"
+After a successful setup, copy the setup environment to this directory and keep
+its Farm group alive for 4 hours, such that later runs can attach to it with
+--reuse-setup."#
+    )]
+    pub export_setup: Option<PathBuf>,
+
+    #[clap(
+        long = "reuse-setup",
+        help = r#"
+Attach to a setup environment exported by a previous run with --export-setup
+instead of running the setup function. The Farm group of the setup is kept
+alive for another 4 hours after the run."#
+    )]
+    pub reuse_setup: Option<PathBuf>,
+
+    #[clap(
+        long = "no-failure-artifacts",
+        help = r#"
+Do not collect the replica and orchestrator logs, the registry local stores and
+the Prometheus data of the testnet if a test failed, nor the core dumps of
+crashed processes."#
+    )]
+    pub no_failure_artifacts: bool,
+
+    #[clap(
+        long = "seed",
+        help = r#"
+Seed the randomness of the tests with this number instead of a random one. The
+seed of a run is logged and recorded in the results, such that a failed run can
+be reproduced."#
+    )]
+    pub seed: Option<u64>,
+
+    #[clap(
+        long = "colocate",
+        help = r#"
+Run the test driver and the tests on a universal VM on Farm instead of on this
+machine and wait for it to finish. The test has to be declared with
+system_test(..., colocate = True)."#
+    )]
+    pub colocate: bool,
+
+    #[clap(
+        long = "keepalive-on-failure",
+        help = r#"
+Like --debug-keepalive, but only if a test failed: After all tests finished,
+print how to access the testnet and keep it alive until ctrl+c is pressed."#
+    )]
+    pub keepalive_on_failure: bool,
+
+    #[clap(
+        long = "preemptible-vms",
+        help = r#"
+Allocate the VMs on cheaper preemptible Farm resources, e.g., for long soak
+tests. Preempted VMs are re-allocated while the tests run."#
+    )]
+    pub preemptible_vms: bool,
+
+    #[clap(
+        long = "vm-hours-budget",
+        help = r#"
+Fail the group if its Farm VMs were allocated for more than this many VM-hours
+in total. The VM-hours and peak resources used while each test ran are listed
+in the report either way."#
+    )]
+    pub vm_hours_budget: Option<f64>,
 }
 
 impl CliArgs {
     fn validate(self) -> Result<Self> {
-        // nothing to validate at the moment
+        if let Some(ref dir) = self.reuse_setup {
+            if !dir.is_dir() {
+                bail!("--reuse-setup: {:?} is not a directory", dir)
+            }
+        }
+        if self.local_backend.is_some() && self.farm_base_url.is_some() {
+            bail!("--local-backend and --farm-base-url are mutually exclusive")
+        }
+        if self.local_backend.is_some() && self.preemptible_vms {
+            bail!("--local-backend and --preemptible-vms are mutually exclusive")
+        }
         Ok(self)
     }
 
+    /// The arguments passed on to a colocated test driver, which runs the
+    /// tests of this invocation.
+    fn colocated_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        let mut push = |name: &str, value: Option<String>| {
+            if let Some(value) = value {
+                args.push(format!("--{}", name));
+                args.push(value);
+            }
+        };
+        push("include-tests", self.filter_tests.clone());
+        push(
+            "include-pattern",
+            self.include_pattern.as_ref().map(|r| r.to_string()),
+        );
+        push(
+            "skip-pattern",
+            self.skip_pattern.as_ref().map(|r| r.to_string()),
+        );
+        push(
+            "farm-base-url",
+            self.farm_base_url.as_ref().map(|u| u.to_string()),
+        );
+        push(
+            "vm-hours-budget",
+            self.vm_hours_budget.map(|budget| budget.to_string()),
+        );
+        if self.debug_keepalive {
+            args.push(String::from("--debug-keepalive"));
+        }
+        if self.no_failure_artifacts {
+            args.push(String::from("--no-failure-artifacts"));
+        }
+        args
+    }
+
     /// A convenience method to get the task id of this subprocess, *if* it is in fact a
     /// subprocess.
     fn subproc_id(&self) -> Option<(TaskId, u64)> {
@@ -140,6 +329,32 @@ impl TestEnvAttribute for SetupResult {
     }
 }
 
+/// The suites whose setup succeeded in an environment, including the ones of
+/// the enclosing suites, see [SystemTestSuite].
+#[derive(Default, Deserialize, Serialize)]
+struct SuiteSetupResults {
+    suites: Vec<String>,
+}
+
+impl TestEnvAttribute for SuiteSetupResults {
+    fn attribute_name() -> String {
+        String::from("suite_setups_succeeded")
+    }
+}
+
+/// Marks a setup environment that was copied from an exported one, such that
+/// the setup task does not run the setup function again.
+#[derive(Deserialize, Serialize)]
+struct ReusedSetup {
+    source: PathBuf,
+}
+
+impl TestEnvAttribute for ReusedSetup {
+    fn attribute_name() -> String {
+        String::from("setup_reused")
+    }
+}
+
 fn is_task_visible_to_user(task_id: &TaskId) -> bool {
     matches!(task_id, TaskId::Test(task_name) if task_name.ne(REPORT_TASK_NAME) && task_name.ne(KEEPALIVE_TASK_NAME) && !task_name.starts_with(LIFETIME_GUARD_TASK_PREFIX) && !task_name.starts_with("dummy("))
 }
@@ -154,6 +369,34 @@ pub struct ComposeContext<'a> {
     subs: Subs,
     logger: Logger,
     timeout_per_test: Duration,
+    retries: usize,
+    restore_testnet_snapshot: bool,
+    /// The full name of the suite whose tests are being composed, if any.
+    suite: Option<String>,
+    outcomes: TestOutcomes,
+    /// The position of the subgroup being composed.
+    position: TestPosition,
+    test_positions: BTreeMap<String, TestPosition>,
+    /// The prerequisites of each dependent test, see [TestFunction::after].
+    dependencies: BTreeMap<String, Vec<String>>,
+    /// The assignment of the tests to shards if only one shard runs.
+    shards: Option<ShardAssignment>,
+}
+
+impl ComposeContext<'_> {
+    /// Composes `sub_group` as the `idx`-th child of a composition with
+    /// `ordering`, keeping track of the positions of its tests.
+    fn child_plan(
+        &mut self,
+        ordering: &EvalOrder,
+        idx: usize,
+        sub_group: SystemTestSubGroup,
+    ) -> Plan<Box<dyn Task>> {
+        self.position.push((ordering.clone(), idx));
+        let plan = sub_group.into_plan(self);
+        self.position.pop();
+        plan
+    }
 }
 
 fn subproc(
@@ -252,9 +495,175 @@ fn get_setup_env(gctx: GroupContext) -> TestEnv {
 }
 
 fn get_or_create_env(gctx: GroupContext, task_id: TaskId) -> Result<TestEnv> {
-    trace!(gctx.log(), "create_env(task_id={})", &task_id);
+    get_or_create_env_in_suite(gctx, task_id, None)
+}
+
+/// Creates the environment of `task_id` as a fork of the environment prepared
+/// by the setup of `suite` or, if `suite` is `None`, of the setup environment.
+fn get_or_create_env_in_suite(
+    gctx: GroupContext,
+    task_id: TaskId,
+    suite: Option<&str>,
+) -> Result<TestEnv> {
+    trace!(
+        gctx.log(),
+        "create_env(task_id={}, suite={:?})",
+        &task_id,
+        suite
+    );
     let process_ctx = ProcessContext::new(gctx, task_id.name()).unwrap();
-    process_ctx.group_context.create_test_env(&task_id.name())
+    let source_test = suite.map(suite_setup_task_name);
+    process_ctx
+        .group_context
+        .create_test_env_from(&task_id.name(), source_test.as_deref())
+}
+
+/// Separates the names of suites and of their tests in the reported names, e.g.
+/// `nns::governance::upgrade_test`.
+pub const SUITE_SEPARATOR: &str = "::";
+
+fn suite_setup_task_name(suite: &str) -> String {
+    format!("{suite}{SUITE_SEPARATOR}{SETUP_TASK_NAME}")
+}
+
+/// Panics if the setup of the group or, if `suite` is given, the setup of that
+/// suite did not succeed in `env`.
+fn assert_setup_succeeded(env: &TestEnv, suite: Option<&str>) {
+    match suite {
+        None => {
+            if SetupResult::try_read_attribute(env).is_err() {
+                panic!(
+                    "Failed to find SetupResult attribute after setup. Cancelling test function."
+                );
+            }
+        }
+        Some(suite) => {
+            let results = SuiteSetupResults::try_read_attribute(env).unwrap_or_default();
+            if !results.suites.iter().any(|s| s == suite) {
+                panic!("Setup of suite {suite} did not succeed. Cancelling test function.");
+            }
+        }
+    }
+}
+
+/// A named suite of tests within a [SystemTestGroup]. The setup of a suite
+/// augments the setup of the group (or of the enclosing suite) for the tests of
+/// the suite only, e.g., by installing canisters. The tests of a suite are
+/// reported as `<suite>::<test>`:
+///
+/// ```ignore
+/// SystemTestGroup::new()
+///     .with_setup(setup)
+///     .add_suite(
+///         SystemTestSuite::new("governance")
+///             .with_setup(install_nns)
+///             .with_timeout_per_test(Duration::from_secs(20 * 60))
+///             .add_test(systest!(proposal_test))
+///             .add_suite(SystemTestSuite::new("upgrades").add_test(systest!(upgrade_test))),
+///     )
+/// ```
+pub struct SystemTestSuite {
+    name: String,
+    setup: Option<Box<dyn PotSetupFn>>,
+    tests: Vec<SystemTestSubGroup>,
+    timeout_per_test: Option<Duration>,
+}
+
+impl SystemTestSuite {
+    pub fn new(name: &str) -> Self {
+        assert!(
+            !name.is_empty() && !name.contains(SUITE_SEPARATOR),
+            "invalid suite name {name:?}"
+        );
+        Self {
+            name: name.to_string(),
+            setup: None,
+            tests: vec![],
+            timeout_per_test: None,
+        }
+    }
+
+    /// Run `setup` on a fork of the enclosing setup before the tests of this
+    /// suite, which run on forks of its outcome.
+    pub fn with_setup<F: PotSetupFn>(mut self, setup: F) -> Self {
+        self.setup = Some(Box::new(setup));
+        self
+    }
+
+    /// Overrides the timeout per test of the enclosing group or suite for the
+    /// setup and the tests of this suite.
+    pub fn with_timeout_per_test(mut self, timeout: Duration) -> Self {
+        self.timeout_per_test = Some(timeout);
+        self
+    }
+
+    pub fn add_test(mut self, test: TestFunction) -> Self {
+        self.tests.push(SystemTestSubGroup::from(test));
+        self
+    }
+
+    pub fn add(mut self, sub_group: SystemTestSubGroup) -> Self {
+        self.tests.push(sub_group);
+        self
+    }
+
+    pub fn add_suite(self, suite: SystemTestSuite) -> Self {
+        self.add(SystemTestSubGroup::Suite(suite))
+    }
+
+    fn into_plan(self, ctx: &mut ComposeContext) -> Plan<Box<dyn Task>> {
+        let name = match &ctx.suite {
+            Some(parent) => format!("{parent}{SUITE_SEPARATOR}{}", self.name),
+            None => self.name,
+        };
+        let parent = ctx.suite.replace(name.clone());
+        let parent_timeout_per_test = ctx.timeout_per_test;
+        ctx.timeout_per_test = self.timeout_per_test.unwrap_or(parent_timeout_per_test);
+
+        let setup_plan = {
+            let task_id = TaskId::Test(suite_setup_task_name(&name));
+            let logger = ctx.logger.clone();
+            let group_ctx = ctx.group_ctx.clone();
+            let setup_fn = self.setup;
+            let parent = parent.clone();
+            let closure = {
+                let task_id = task_id.clone();
+                move || {
+                    debug!(logger, ">>> suite_setup_fn({})", &task_id);
+                    let env =
+                        get_or_create_env_in_suite(group_ctx, task_id, parent.as_deref()).unwrap();
+                    assert_setup_succeeded(&env, parent.as_deref());
+                    if let Some(setup_fn) = setup_fn {
+                        setup_fn(env.clone());
+                    }
+                    let mut results =
+                        SuiteSetupResults::try_read_attribute(&env).unwrap_or_default();
+                    results.suites.push(name);
+                    results.write_attribute(&env);
+                }
+            };
+            timed(
+                Plan::Leaf {
+                    task: Box::from(subproc(task_id, closure, ctx)),
+                },
+                ctx.timeout_per_test,
+                None,
+                ctx,
+            )
+        };
+        let children = once(setup_plan)
+            .chain(
+                self.tests
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, test)| ctx.child_plan(&EvalOrder::Sequential, i + 1, test)),
+            )
+            .collect();
+
+        ctx.suite = parent;
+        ctx.timeout_per_test = parent_timeout_per_test;
+        compose(None, EvalOrder::Sequential, children, ctx)
+    }
 }
 
 pub enum SystemTestSubGroup {
@@ -265,7 +674,36 @@ pub enum SystemTestSubGroup {
     Singleton {
         task_fn: Box<dyn SysTestFn>,
         task_id: TaskId,
+        /// Overrides the group's timeout per test, if set.
+        timeout: Option<Duration>,
+        /// Overrides the group's number of retries, if set.
+        retries: Option<usize>,
+        prerequisites: Vec<String>,
+        success_criteria: Vec<SuccessCriterion>,
+        /// Set if the test is skipped as one of its conditions does not hold.
+        skip_reason: Option<String>,
     },
+    Suite(SystemTestSuite),
+}
+
+impl From<TestFunction> for SystemTestSubGroup {
+    fn from(test: TestFunction) -> Self {
+        let task_id = TaskId::Test(String::from(test.name()));
+        let timeout = test.timeout();
+        let retries = test.retries();
+        let prerequisites = test.prerequisites().to_vec();
+        let success_criteria = test.success_criteria().to_vec();
+        let skip_reason = test.skip_reason();
+        Self::Singleton {
+            task_fn: test.f(),
+            task_id,
+            timeout,
+            retries,
+            prerequisites,
+            success_criteria,
+            skip_reason,
+        }
+    }
 }
 
 impl Default for SystemTestSubGroup {
@@ -282,12 +720,24 @@ impl SystemTestSubGroup {
         }
     }
 
+    /// A subgroup running all `sub_groups` at the same time, see [parallel](crate::parallel).
+    pub fn parallel(sub_groups: Vec<SystemTestSubGroup>) -> Self {
+        Self::Multiple {
+            tasks: sub_groups,
+            ordering: EvalOrder::Parallel,
+        }
+    }
+
+    /// A subgroup running `sub_groups` one after the other, see [sequential](crate::sequential).
+    pub fn sequential(sub_groups: Vec<SystemTestSubGroup>) -> Self {
+        Self::Multiple {
+            tasks: sub_groups,
+            ordering: EvalOrder::Sequential,
+        }
+    }
+
     pub fn add_test(self, test: TestFunction) -> Self {
-        let task_is = TaskId::Test(String::from(test.name()));
-        let singleton = Self::Singleton {
-            task_fn: test.f(),
-            task_id: task_is,
-        };
+        let singleton = Self::from(test);
         match self {
             Self::Multiple { tasks, .. } if tasks.is_empty() => {
                 // This case is only to support the builder pattern
@@ -297,7 +747,7 @@ impl SystemTestSubGroup {
                 tasks: tasks.into_iter().chain(once(singleton)).collect(),
                 ordering,
             },
-            sub_group @ Self::Singleton { .. } => {
+            sub_group @ (Self::Singleton { .. } | Self::Suite(_)) => {
                 Self::Multiple {
                     tasks: once(sub_group).chain(once(singleton)).collect(),
                     ordering: EvalOrder::Parallel, // TODO: generalize this
@@ -306,55 +756,171 @@ impl SystemTestSubGroup {
         }
     }
 
+    /// The longest time this subgroup can take, i.e., if all of its tests and
+    /// suite setups run until their timeouts. The timeout of a test bounds all
+    /// of its attempts together, so retries do not extend it.
+    fn max_duration(&self, timeout_per_test: Duration) -> Duration {
+        match self {
+            Self::Multiple {
+                tasks,
+                ordering: EvalOrder::Parallel,
+            } => tasks
+                .iter()
+                .map(|t| t.max_duration(timeout_per_test))
+                .max()
+                .unwrap_or_default(),
+            Self::Multiple {
+                tasks,
+                ordering: EvalOrder::Sequential,
+            } => tasks.iter().map(|t| t.max_duration(timeout_per_test)).sum(),
+            Self::Singleton {
+                skip_reason: Some(_),
+                ..
+            } => Duration::ZERO,
+            Self::Singleton { timeout, .. } => timeout.unwrap_or(timeout_per_test),
+            Self::Suite(suite) => {
+                let timeout_per_test = suite.timeout_per_test.unwrap_or(timeout_per_test);
+                timeout_per_test
+                    + suite
+                        .tests
+                        .iter()
+                        .map(|t| t.max_duration(timeout_per_test))
+                        .sum::<Duration>()
+            }
+        }
+    }
+
+    /// Whether this subgroup contains a suite.
+    fn has_suites(&self) -> bool {
+        match self {
+            Self::Multiple { tasks, .. } => tasks.iter().any(Self::has_suites),
+            Self::Singleton { .. } => false,
+            Self::Suite(_) => true,
+        }
+    }
+
+    /// Whether this subgroup runs several tests at the same time.
+    fn has_parallel_tests(&self) -> bool {
+        match self {
+            Self::Multiple { tasks, ordering } => {
+                (matches!(ordering, EvalOrder::Parallel) && tasks.len() > 1)
+                    || tasks.iter().any(Self::has_parallel_tests)
+            }
+            Self::Singleton { .. } => false,
+            Self::Suite(suite) => suite.tests.iter().any(Self::has_parallel_tests),
+        }
+    }
+
     pub fn into_plan(self, ctx: &mut ComposeContext) -> Plan<Box<dyn Task>> {
         match self {
-            SystemTestSubGroup::Multiple { tasks, ordering } => compose(
-                None,
-                ordering,
-                tasks
+            SystemTestSubGroup::Multiple { tasks, ordering } => {
+                let children = tasks
                     .into_iter()
-                    .map(|sub_group| sub_group.into_plan(ctx))
-                    .collect(),
-                ctx,
-            ),
-            // If filtering flag `--include-tests` is set, then for all
-            // skipped test function we execute a SkipTestTask, which sends EventPayload::TaskSkipped.
-            SystemTestSubGroup::Singleton { task_fn, task_id } => {
+                    .enumerate()
+                    .map(|(i, sub_group)| ctx.child_plan(&ordering, i, sub_group))
+                    .collect();
+                compose(None, ordering, children, ctx)
+            }
+            SystemTestSubGroup::Suite(suite) => suite.into_plan(ctx),
+            // If filtering flags `--include-tests`, `--include-pattern` or `--skip-pattern` are set,
+            // or a condition of the test does not hold, then for all skipped test function we
+            // execute a SkipTestTask, which sends EventPayload::TaskSkipped.
+            SystemTestSubGroup::Singleton {
+                task_fn,
+                task_id,
+                timeout,
+                retries,
+                prerequisites,
+                success_criteria,
+                skip_reason,
+            } => {
                 let logger = ctx.logger.clone();
                 let group_ctx = ctx.group_ctx.clone();
-                if let Some(ref filter) = group_ctx.filter_tests {
-                    if let TaskId::Test(ref name) = task_id {
-                        if !name.contains(filter) {
+                let task_id = match (&ctx.suite, task_id) {
+                    (Some(suite), TaskId::Test(name)) => {
+                        TaskId::Test(format!("{suite}{SUITE_SEPARATOR}{name}"))
+                    }
+                    (_, task_id) => task_id,
+                };
+                if let TaskId::Test(ref name) = task_id {
+                    ctx.test_positions
+                        .insert(name.clone(), ctx.position.clone());
+                    if !prerequisites.is_empty() {
+                        // Prerequisites are tests of the same suite.
+                        let prerequisites = prerequisites
+                            .iter()
+                            .map(|p| match &ctx.suite {
+                                Some(suite) => format!("{suite}{SUITE_SEPARATOR}{p}"),
+                                None => p.clone(),
+                            })
+                            .collect();
+                        ctx.dependencies.insert(name.clone(), prerequisites);
+                    }
+                    // The tests of other shards are skipped under a name that
+                    // is not visible to the user, such that they are left out of
+                    // the report.
+                    if let Some(shards) = ctx.shards.as_mut() {
+                        let prerequisites =
+                            ctx.dependencies.get(name).map_or(&[][..], Vec::as_slice);
+                        if !shards.includes(name, prerequisites) {
+                            let task_id =
+                                TaskId::Test(format!("dummy({})", ctx.empty_task_counter));
+                            ctx.empty_task_counter += 1;
                             return Plan::Leaf {
-                                task: Box::from(SkipTestTask::new(
-                                    ctx.subs.clone(),
-                                    task_id.clone(),
-                                )),
+                                task: Box::from(SkipTestTask::new(ctx.subs.clone(), task_id)),
                             };
                         }
                     }
+                    if !group_ctx.filter_tests.matches(name) {
+                        return Plan::Leaf {
+                            task: Box::from(SkipTestTask::new(ctx.subs.clone(), task_id.clone())),
+                        };
+                    }
+                    if let Some(reason) = skip_reason {
+                        info!(logger, "Skipping test {}: {}", name, reason);
+                        return Plan::Leaf {
+                            task: Box::from(
+                                SkipTestTask::new(ctx.subs.clone(), task_id.clone())
+                                    .with_reason(reason),
+                            ),
+                        };
+                    }
                 }
                 let closure = {
                     let task_id = task_id.clone();
                     let group_ctx = ctx.group_ctx.clone();
+                    let restore_testnet_snapshot = ctx.restore_testnet_snapshot;
+                    let suite = ctx.suite.clone();
                     move || {
                         debug!(logger, ">>> test_fn({})", &task_id);
-                        let env = get_or_create_env(group_ctx, task_id).unwrap();
+                        let env = get_or_create_env_in_suite(group_ctx, task_id, suite.as_deref())
+                            .unwrap();
                         // This function will only be called after setup finishes
-                        if SetupResult::try_read_attribute(&env).is_err() {
-                            panic!("Failed to find SetupResult attribute after setup. Cancelling test function.");
+                        assert_setup_succeeded(&env, suite.as_deref());
+                        if restore_testnet_snapshot {
+                            env.restore_testnet()
+                                .expect("Failed to restore the testnet snapshot of the setup.");
                         }
-                        task_fn(env)
+                        run_with_success_criteria(&env, success_criteria, || task_fn(env.clone()))
                     }
                 };
-                timed(
-                    Plan::Leaf {
-                        task: Box::from(subproc(task_id, closure, ctx)),
-                    },
-                    ctx.timeout_per_test,
-                    None,
-                    ctx,
-                )
+                let prerequisites = ctx.dependencies.get(&task_id.name()).cloned();
+                let timeout = timeout.unwrap_or(ctx.timeout_per_test);
+                let task: Box<dyn Task> = Box::from(
+                    subproc(task_id, closure, ctx)
+                        .with_retries(retries.unwrap_or(ctx.retries), timeout),
+                );
+                let task: Box<dyn Task> = match prerequisites {
+                    Some(prerequisites) => Box::from(DependentTask::new(
+                        task,
+                        prerequisites,
+                        ctx.outcomes.clone(),
+                        ctx.subs.clone(),
+                        ctx.logger.clone(),
+                    )),
+                    None => task,
+                };
+                timed(Plan::Leaf { task }, timeout, None, ctx)
             }
         }
     }
@@ -362,10 +928,15 @@ impl SystemTestSubGroup {
 
 pub struct SystemTestGroup {
     setup: Option<Box<dyn PotSetupFn>>,
+    teardown: Option<Box<dyn PotSetupFn>>,
     tests: Vec<SystemTestSubGroup>,
     timeout_per_test: Option<Duration>,
     overall_timeout: Option<Duration>,
+    timeout_grace_period: Option<Duration>,
+    retries: usize,
+    with_testnet_snapshot: bool,
     with_farm: bool,
+    colocate: bool,
 }
 
 impl Default for SystemTestGroup {
@@ -387,10 +958,15 @@ impl SystemTestGroup {
     pub fn new() -> Self {
         Self {
             setup: Default::default(),
+            teardown: Default::default(),
             tests: Default::default(),
             timeout_per_test: None,
             overall_timeout: None,
+            timeout_grace_period: None,
+            retries: 0,
+            with_testnet_snapshot: false,
             with_farm: true,
+            colocate: false,
         }
     }
 
@@ -402,6 +978,26 @@ impl SystemTestGroup {
         self.overall_timeout.unwrap_or(DEFAULT_OVERALL_TIMEOUT)
     }
 
+    fn effective_timeout_grace_period(&self) -> Duration {
+        self.timeout_grace_period
+            .unwrap_or(DEFAULT_TIMEOUT_GRACE_PERIOD)
+    }
+
+    /// The TTL of the Farm group: the time the setup and the tests take if all
+    /// of them run until their timeouts, but at most the overall timeout, plus
+    /// the grace period for the teardown. The keepalive task extends the group
+    /// if it lives longer, e.g., with `--debug-keepalive`.
+    fn required_group_ttl(&self) -> Duration {
+        let timeout_per_test = self.effective_timeout_per_test();
+        let tests: Duration = self
+            .tests
+            .iter()
+            .map(|t| t.max_duration(timeout_per_test))
+            .sum();
+        (timeout_per_test + tests).min(self.effective_overall_timeout())
+            + self.effective_timeout_grace_period()
+    }
+
     pub fn without_farm(mut self) -> Self {
         self.with_farm = false;
         self
@@ -412,20 +1008,56 @@ impl SystemTestGroup {
         self
     }
 
+    /// When the overall timeout fires, the collection of the failure artifacts
+    /// and the teardown function still get up to `grace_period` to finish
+    /// before the Farm group is deleted. The timeout of the test target needs
+    /// to cover both the overall timeout and the grace period.
+    pub fn with_timeout_grace_period(mut self, grace_period: Duration) -> Self {
+        self.timeout_grace_period = Some(grace_period);
+        self
+    }
+
     pub fn with_setup<F: PotSetupFn>(mut self, setup: F) -> Self {
         self.setup = Some(Box::new(setup));
         self
     }
 
+    /// Run `teardown` after all tests finished, failed or timed out, e.g., to
+    /// release external resources that the Farm does not know about. It runs
+    /// in the parent process on a fork of the setup environment. If the setup
+    /// is exported or reused, its resources are kept and teardown is skipped.
+    pub fn with_teardown<F: PotSetupFn>(mut self, teardown: F) -> Self {
+        self.teardown = Some(Box::new(teardown));
+        self
+    }
+
     pub fn add_test(mut self, test: TestFunction) -> Self {
-        let task_id = TaskId::Test(String::from(test.name()));
-        self.tests.push(SystemTestSubGroup::Singleton {
-            task_fn: test.f(),
-            task_id,
-        });
+        self.tests.push(SystemTestSubGroup::from(test));
+        self
+    }
+
+    /// Add a subgroup keeping its own order of execution, e.g., one composed
+    /// with [parallel](crate::parallel) and [sequential](crate::sequential):
+    ///
+    /// ```ignore
+    /// SystemTestGroup::new()
+    ///     .with_setup(setup)
+    ///     .add(parallel!(
+    ///         systest!(run_workload),
+    ///         sequential!(systest!(kill_nodes), systest!(restart_nodes)),
+    ///     ))
+    /// ```
+    pub fn add(mut self, sub_group: SystemTestSubGroup) -> Self {
+        self.tests.push(sub_group);
         self
     }
 
+    /// Add a named suite of tests with its own setup and timeout per test, see
+    /// [SystemTestSuite].
+    pub fn add_suite(self, suite: SystemTestSuite) -> Self {
+        self.add(SystemTestSubGroup::Suite(suite))
+    }
+
     fn add_group(mut self, sub_group: SystemTestSubGroup, ordering: EvalOrder) -> Self {
         self.tests.push(match sub_group {
             SystemTestSubGroup::Multiple { tasks, .. } => {
@@ -476,7 +1108,9 @@ impl SystemTestGroup {
             lifetime_guard_task,
         );
         let lifetime_guard_sub_group = match sub_group {
-            SystemTestSubGroup::Singleton { .. } => sub_group.add_test(lifetime_guard_task),
+            SystemTestSubGroup::Singleton { .. } | SystemTestSubGroup::Suite(_) => {
+                sub_group.add_test(lifetime_guard_task)
+            }
             SystemTestSubGroup::Multiple {
                 tasks: _,
                 ordering: EvalOrder::Parallel,
@@ -508,15 +1142,108 @@ impl SystemTestGroup {
         self
     }
 
+    /// Retry each failing test up to `retries` times in a fresh process on a
+    /// fresh copy of the setup environment. The timeout per test covers all
+    /// attempts. Tests that pass on a retry are marked as such in the report.
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Snapshot all VMs of the testnet, including their memory, after the
+    /// setup and restore the snapshot before each test (and each retry), such
+    /// that all tests start from the state right after the setup. As a test
+    /// resets the testnet for all tests running at the same time, the group
+    /// must not contain parallel subgroups.
+    pub fn with_testnet_snapshot(mut self) -> Self {
+        self.with_testnet_snapshot = true;
+        self
+    }
+
+    /// Always run the test driver and the tests colocated on a universal VM
+    /// on Farm, as with `--colocate`, e.g., for long-running nightly tests.
+    pub fn with_colocation(mut self) -> Self {
+        self.colocate = true;
+        self
+    }
+
+    /// Replaces the setup and the tests of this group with the ones starting
+    /// the colocated test driver and awaiting its outcome. The timeouts cover
+    /// the overall timeout of the colocated test driver.
+    fn into_colocated(self) -> Self {
+        let colocated_timeout =
+            self.effective_overall_timeout() + self.effective_timeout_grace_period();
+        Self::new()
+            .with_setup(colocate::setup_colocated_vm)
+            .add_test(TestFunction::new(
+                COLOCATED_TEST_NAME,
+                colocate::run_colocated_test,
+            ))
+            .with_timeout_per_test(colocated_timeout + COLOCATION_OVERHEAD)
+            .with_overall_timeout(colocated_timeout + 2 * COLOCATION_OVERHEAD)
+    }
+
+    /// Makes the test added last run only after `prerequisite` passed, see
+    /// [TestFunction::after]:
+    ///
+    /// ```ignore
+    /// SystemTestGroup::new()
+    ///     .with_setup(setup)
+    ///     .add_parallel(
+    ///         SystemTestSubGroup::new()
+    ///             .add_test(systest!(install))
+    ///             .add_test(systest!(load)),
+    ///     )
+    ///     .add_test(systest!(upgrade))
+    ///     .after("install")
+    /// ```
+    pub fn after(mut self, prerequisite: &str) -> Self {
+        let test = match self.tests.pop() {
+            Some(SystemTestSubGroup::Singleton {
+                task_fn,
+                task_id,
+                timeout,
+                retries,
+                mut prerequisites,
+                success_criteria,
+                skip_reason,
+            }) => {
+                prerequisites.push(prerequisite.to_string());
+                SystemTestSubGroup::Singleton {
+                    task_fn,
+                    task_id,
+                    timeout,
+                    retries,
+                    prerequisites,
+                    success_criteria,
+                    skip_reason,
+                }
+            }
+            _ => panic!("after({prerequisite:?}) does not follow add_test"),
+        };
+        self.tests.push(test);
+        self
+    }
+
     fn make_plan(
         self,
         rh: &Handle,
         group_ctx: GroupContext,
         subs: Subs,
+        outcomes: TestOutcomes,
+        shard: Option<Shard>,
     ) -> Result<Plan<Box<dyn Task>>> {
         debug!(group_ctx.log(), "SystemTestGroup.make_plan");
 
         let effective_overall_timeout = self.effective_overall_timeout();
+        assert!(
+            !self.with_testnet_snapshot || !self.tests.iter().any(|t| t.has_parallel_tests()),
+            "tests restoring a testnet snapshot cannot run in parallel"
+        );
+        assert!(
+            !self.with_testnet_snapshot || !self.tests.iter().any(|t| t.has_suites()),
+            "tests restoring a testnet snapshot cannot be grouped into suites"
+        );
 
         let mut compose_ctx = ComposeContext {
             rh,
@@ -525,6 +1252,14 @@ impl SystemTestGroup {
             subs: subs.clone(),
             logger: group_ctx.logger().clone(),
             timeout_per_test: self.effective_timeout_per_test(),
+            retries: self.retries,
+            restore_testnet_snapshot: self.with_testnet_snapshot,
+            suite: None,
+            outcomes,
+            position: vec![],
+            test_positions: BTreeMap::new(),
+            dependencies: BTreeMap::new(),
+            shards: shard.map(ShardAssignment::new),
         };
 
         // The ID of the root task is needed outside this function for awaiting when the plan execution finishes.
@@ -538,6 +1273,8 @@ impl SystemTestGroup {
                     move || {
                         let group_ctx = group_ctx.clone();
                         debug!(logger, ">>> keepalive");
+                        let start = Instant::now();
+                        let mut watching_preemptions = false;
                         loop {
                             let group_ctx = group_ctx.clone();
                             if let Ok((group_setup, env)) =
@@ -548,7 +1285,21 @@ impl SystemTestGroup {
                                 let farm_url = env.get_farm_url().unwrap();
                                 let farm = Farm::new(farm_url.clone(), env.logger());
                                 let group_name = group_setup.farm_group_name;
-                                if let Err(e) = farm.set_group_ttl(&group_name, GROUP_TTL) {
+                                if group_setup.preemptible_vms && !watching_preemptions {
+                                    watch_preemptions(
+                                        get_setup_env(group_ctx.clone()),
+                                        farm.clone(),
+                                        group_name.clone(),
+                                    );
+                                    watching_preemptions = true;
+                                }
+                                // Keep the group alive for the rest of its planned lifetime,
+                                // such that a stalled keepalive does not let it expire.
+                                let ttl = group_setup
+                                    .group_timeout
+                                    .saturating_sub(start.elapsed())
+                                    .max(GROUP_TTL);
+                                if let Err(e) = farm.set_group_ttl(&group_name, ttl) {
                                     panic!(
                                         "{}",
                                         format!(
@@ -561,7 +1312,7 @@ impl SystemTestGroup {
                                     logger,
                                     "Group {} TTL set to +{:?} from now (Farm endpoint: {:?})",
                                     group_name,
-                                    GROUP_TTL,
+                                    ttl,
                                     farm_url
                                 );
                             } else {
@@ -583,12 +1334,22 @@ impl SystemTestGroup {
             let setup_fn = self
                 .setup
                 .unwrap_or_else(|| panic!("setup function not specified for SystemTestGroup."));
+            let with_testnet_snapshot = self.with_testnet_snapshot;
             let setup_task = subproc(
                 TaskId::Test(String::from(SETUP_TASK_NAME)),
                 move || {
                     debug!(logger, ">>> setup_fn");
                     let env = get_setup_env(group_ctx);
-                    setup_fn(env.clone());
+                    if let Ok(reused) = ReusedSetup::try_read_attribute(&env) {
+                        info!(logger, "Reusing setup exported to {:?}", reused.source);
+                    } else {
+                        setup_fn(env.clone());
+                        if with_testnet_snapshot {
+                            env.snapshot_testnet(SETUP_SNAPSHOT_NAME)
+                                .expect("Failed to snapshot the testnet after setup.");
+                        }
+                    }
+                    write_testnet_description(&env);
                     SetupResult {}.write_attribute(&env);
                 },
                 &mut compose_ctx,
@@ -602,6 +1363,28 @@ impl SystemTestGroup {
                 &mut compose_ctx,
             )
         };
+        let test_plans: Vec<_> = self
+            .tests
+            .into_iter()
+            .enumerate()
+            .map(|(i, sub_group)| compose_ctx.child_plan(&EvalOrder::Sequential, i + 1, sub_group))
+            .collect();
+        if let Err(counterexample) =
+            check_dependencies(&compose_ctx.test_positions, &compose_ctx.dependencies)
+        {
+            bail!(SystemTestGroupError::PreconditionViolation {
+                condition: "Prerequisites of tests must exist and must not run after the tests"
+                    .to_string(),
+                counterexample,
+            })
+        }
+        if let Some(conflict) = compose_ctx.shards.as_ref().and_then(|s| s.conflict()) {
+            bail!(SystemTestGroupError::PreconditionViolation {
+                condition: "The prerequisites of a test must be assigned to the same shard"
+                    .to_string(),
+                counterexample: conflict.to_string(),
+            })
+        }
 
         // normal case: no debugkeepalive, overall timeout is active
         if !group_ctx.debug_keepalive {
@@ -611,13 +1394,7 @@ impl SystemTestGroup {
                 vec![compose(
                     None,
                     EvalOrder::Sequential,
-                    std::iter::once(setup_plan)
-                        .chain(
-                            self.tests
-                                .into_iter()
-                                .map(|sub_group| sub_group.into_plan(&mut compose_ctx)),
-                        )
-                        .collect(),
+                    once(setup_plan).chain(test_plans).collect(),
                     &mut compose_ctx,
                 )],
                 &mut compose_ctx,
@@ -632,7 +1409,7 @@ impl SystemTestGroup {
                 vec![timed(
                     plan,
                     effective_overall_timeout,
-                    Some(String::from("::group")),
+                    Some(String::from(GROUP_TIMEOUT_TASK_NAME)),
                     &mut compose_ctx,
                 )],
                 &mut compose_ctx,
@@ -659,13 +1436,7 @@ impl SystemTestGroup {
             vec![compose(
                 None,
                 EvalOrder::Sequential,
-                std::iter::once(setup_plan)
-                    .chain(
-                        self.tests
-                            .into_iter()
-                            .map(|sub_group| sub_group.into_plan(&mut compose_ctx)),
-                    )
-                    .collect(),
+                once(setup_plan).chain(test_plans).collect(),
                 &mut compose_ctx,
             )],
             &mut compose_ctx,
@@ -679,7 +1450,7 @@ impl SystemTestGroup {
         ))
     }
 
-    pub fn execute(self) -> Result<Outcome> {
+    pub fn execute(mut self) -> Result<Outcome> {
         // TODO: check preconditions:
         // 0. None of the test functions (modulo the setup function) have the literal name "setup"
         // 1. There exists at least one test after setup
@@ -691,21 +1462,85 @@ impl SystemTestGroup {
         // 2. Test / setup functions are not specified more than once in the group
         let args = CliArgs::parse().validate()?;
         let is_parent_process = matches!(args.action, SystemTestsSubcommand::Run);
+        let colocate = (args.colocate || self.colocate) && !colocate::is_colocated();
+        if colocate
+            && (args.local_backend.is_some()
+                || args.export_setup.is_some()
+                || args.reuse_setup.is_some()
+                || args.keepalive_on_failure)
+        {
+            bail!("colocated tests cannot use --local-backend, --export-setup, --reuse-setup or --keepalive-on-failure")
+        }
+        // Only the parent process plans which tests to run, the child
+        // processes run the test they are spawned for.
+        let shard = if is_parent_process {
+            Shard::from_env()?
+        } else {
+            None
+        };
+        if colocate && shard.is_some() {
+            bail!("colocated tests cannot be sharded")
+        }
+        let colocated_args = args.colocated_args();
 
         let group_ctx = GroupContext::new(
             args.group_dir.path.clone(),
             args.subproc_id(),
-            args.filter_tests,
+            TestFilter {
+                include_substring: args.filter_tests,
+                include_pattern: args.include_pattern,
+                skip_pattern: args.skip_pattern,
+            },
             args.debug_keepalive,
         )?;
         if is_parent_process {
             let root_env = group_ctx.get_root_env().unwrap();
-            FarmBaseUrl::new_or_default(args.farm_base_url).write_attribute(&root_env);
-            if self.with_farm {
-                root_env.create_group_setup();
+            let farm_base_url = match args.local_backend {
+                Some(ref dir) => {
+                    check_local_backend_prerequisites(&args.local_bridge)?;
+                    Some(local_backend_url(dir, &args.local_bridge)?)
+                }
+                None => args.farm_base_url,
+            };
+            FarmBaseUrl::new_or_default(farm_base_url).write_attribute(&root_env);
+            let seed = args.seed.unwrap_or_else(rand::random);
+            info!(
+                group_ctx.log(),
+                "Using seed {}, rerun with --seed {} to reproduce this run.", seed, seed
+            );
+            TestSeed(seed).write_attribute(&root_env);
+            if let Some(shard) = shard {
+                info!(
+                    group_ctx.log(),
+                    "Running the tests of shard index {} of {} shards.", shard.index, shard.count
+                );
+            }
+            if colocate {
+                let mut colocated_args = colocated_args;
+                colocated_args.extend([String::from("--seed"), seed.to_string()]);
+                Colocation::from_env(colocated_args)?.write_attribute(&root_env);
+            }
+            if let Some(ref source) = args.reuse_setup {
+                Self::reuse_setup(&group_ctx, source)?;
+            } else if self.with_farm || colocate {
+                root_env.create_group_setup(args.preemptible_vms, self.required_group_ttl());
             }
             debug!(group_ctx.log(), "Created group context: {:?}", group_ctx);
         }
+        // The child processes of a colocating run learn about it from the
+        // root environment, as they only get the working directory.
+        let colocated = group_ctx
+            .get_root_env()
+            .map_or(false, |env| Colocation::try_read_attribute(&env).is_ok());
+        if colocated {
+            if is_parent_process {
+                info!(
+                    group_ctx.log(),
+                    "Running the tests colocated on the universal VM {}", COLOCATED_VM_NAME
+                );
+            }
+            self = self.into_colocated();
+        }
         let with_farm = self.with_farm;
 
         let broadcaster = Arc::new(EventBroadcaster::start());
@@ -734,7 +1569,16 @@ impl SystemTestGroup {
                 .unwrap()
         };
         let subs: Arc<dyn BroadcastingEventSubscriberFactory> = broadcaster.clone(); // a shallow copy - the broadcaster is shared!
-        let plan = self.make_plan(runtime.handle(), group_ctx.clone(), subs)?;
+        let teardown = self.teardown.take();
+        let timeout_grace_period = self.effective_timeout_grace_period();
+        let outcomes = TestOutcomes::default();
+        let plan = self.make_plan(
+            runtime.handle(),
+            group_ctx.clone(),
+            subs,
+            outcomes.clone(),
+            shard,
+        )?;
         if is_parent_process {
             info!(group_ctx.log(), "Generated plan: {:?}", plan);
         }
@@ -782,12 +1626,21 @@ impl SystemTestGroup {
                 let scheduler = new_task_scheduler(table, action_graph, group_ctx.logger());
                 info!(group_ctx.log(), "Generated task_scheduler");
 
+                // Dependent tests await the outcomes of their prerequisites.
+                broadcaster.subscribe(outcomes.subscriber());
+
                 broadcaster.subscribe(Box::new(scheduler));
                 info!(
                     group_ctx.log(),
                     "Scheduler is now subscribed to broadcaster"
                 );
 
+                let usage_samples = UsageSamples::default();
+                let (stop_sampling, sampling_stopped) = crossbeam_channel::bounded::<()>(0);
+                let usage_sampler = with_farm.then(|| {
+                    sample_usage(group_ctx.clone(), usage_samples.clone(), sampling_stopped)
+                });
+
                 let ctx = group_ctx.clone();
                 // subscribe to the root task's terminal events
                 // Note: synchronization is done via a zero-capacity crossbeam channel
@@ -821,8 +1674,12 @@ impl SystemTestGroup {
                                 // report.set_test_as_timed_out(TaskId::Test(timed_task_id.clone()));
                                 let timed_task_id = TaskId::Test(timed_task_name.clone());
                                 report.set_test_as_timed_out(timed_task_id);
-                                // Set the group timeout flag
-                                report.set_group_timed_out();
+                                // Set the group timeout flag only if the overall timeout fired,
+                                // such that other tests failing after a per-test timeout are
+                                // still reported as panicked.
+                                if timed_task_name.eq(GROUP_TIMEOUT_TASK_NAME) {
+                                    report.set_group_timed_out();
+                                }
                             } else {
                                 // 2.2. When a regular (i.e., not Timeout) tasks fails, Write down its end time.
                                 if is_task_visible_to_user(task_id) {
@@ -855,6 +1712,9 @@ impl SystemTestGroup {
                                     );
                                 }
                             }
+                        } else if let EventPayload::TaskRetried { ref task_id, attempt, .. } = event.what {
+                            // Remember the retries of this task for the report
+                            report.set_test_retried(task_id.clone(), attempt);
                         } else if let EventPayload::TaskSubReport { task_id, sub_report } = event.clone().what {
                             // 3. Handle the sub-report generated by this task
                             report.set_test_sub_report(task_id, sub_report);
@@ -875,10 +1735,12 @@ impl SystemTestGroup {
                         } else if let EventPayload::TaskCaughtPanic { ref task_id, ref msg } = event.what {
                             report.set_assert_failure_message(task_id.clone(), msg);
                         } else if let EventPayload::TaskSkipped { ref task_id } = event.what {
-                            report.add_skipped(TargetFunctionSuccess {
-                                task_id: task_id.clone(),
-                                runtime: Duration::ZERO,
-                            });
+                            if is_task_visible_to_user(task_id) {
+                                report.add_skipped(TargetFunctionSuccess {
+                                    task_id: task_id.clone(),
+                                    runtime: Duration::ZERO,
+                                });
+                            }
                         }
                         // else { non-terminal event }
                         match event.what {
@@ -910,10 +1772,114 @@ impl SystemTestGroup {
                 });
 
                 // await root task's final event and produce appropriate return code
-                let report = terminal_event_receiver.recv().unwrap();
+                let mut report = terminal_event_receiver.recv().unwrap();
+
+                if with_farm && args.keepalive_on_failure && !report.is_failure_free() {
+                    keep_testnet_alive(runtime.handle(), &get_setup_env(ctx.clone()));
+                }
+
+                let keep_farm_group = match args.export_setup {
+                    Some(ref target) => Self::export_setup(&ctx, target),
+                    None => args.reuse_setup.is_some(),
+                };
+                let collect_artifacts =
+                    with_farm && !report.is_failure_free() && !args.no_failure_artifacts;
+                if collect_artifacts && keep_farm_group {
+                    info!(
+                        ctx.log(),
+                        "Not collecting failure artifacts as the setup is kept alive."
+                    );
+                }
+                let teardown = match teardown {
+                    Some(_) if keep_farm_group => {
+                        info!(ctx.log(), "Skipping teardown as the setup is kept alive.");
+                        None
+                    }
+                    teardown => teardown,
+                };
+                let runs_teardown = teardown.is_some();
+                let cleanup = {
+                    let ctx = ctx.clone();
+                    let check_crashes = with_farm
+                        && report.is_failure_free()
+                        && !args.no_failure_artifacts
+                        && !keep_farm_group;
+                    let collect_artifacts = collect_artifacts && !keep_farm_group;
+                    move || {
+                        if collect_artifacts {
+                            Self::collect_failure_artifacts(&ctx);
+                        } else if check_crashes {
+                            Self::collect_crash_artifacts(&ctx);
+                        }
+                        teardown.map(|teardown| Self::run_teardown(&ctx, teardown))
+                    }
+                };
+                let teardown_outcome = if report.is_group_timed_out() {
+                    match Self::within_grace_period(&ctx, timeout_grace_period, cleanup) {
+                        Some(outcome) => outcome,
+                        None if runs_teardown => Some(Err(TargetFunctionFailure::TimedOut {
+                            task_id: TaskId::Test(String::from(TEARDOWN_TASK_NAME)),
+                            timeout: timeout_grace_period,
+                        })),
+                        None => None,
+                    }
+                } else {
+                    cleanup()
+                };
+                match teardown_outcome {
+                    Some(Ok(success)) => report.add_succ(success),
+                    Some(Err(failure)) => report.add_fail(failure),
+                    None => {}
+                }
+
+                drop(stop_sampling);
+                if let Some(usage_sampler) = usage_sampler {
+                    let _ = usage_sampler.join();
+                    report.set_resource_usage(&usage_samples.get());
+                    info!(
+                        ctx.log(),
+                        "The group consumed {:.2} VM-hours.",
+                        report.total_vm_hours()
+                    );
+                }
+                if let Some(budget) = args.vm_hours_budget {
+                    if report.total_vm_hours() > budget {
+                        report.add_fail(TargetFunctionFailure::Panicked {
+                            task_id: TaskId::Test(String::from(VM_HOURS_BUDGET_TASK_NAME)),
+                            message: format!(
+                                "The group consumed {:.2} VM-hours, exceeding its budget of {:.2} VM-hours",
+                                report.total_vm_hours(),
+                                budget
+                            ),
+                            runtime: Duration::ZERO,
+                        });
+                    }
+                }
+
+                let copied_colocated_results = colocated
+                    && colocate::copy_colocated_results(
+                        &ctx,
+                        args.junit_xml_output.as_deref(),
+                        args.json_output.as_deref(),
+                    );
+                if !copied_colocated_results
+                    && (args.junit_xml_output.is_some() || args.json_output.is_some())
+                {
+                    write_results(
+                        &ctx,
+                        &report,
+                        shard,
+                        args.junit_xml_output.as_deref(),
+                        args.json_output.as_deref(),
+                    );
+                }
 
                 if with_farm {
-                    Self::delete_farm_group(ctx);
+                    if keep_farm_group {
+                        Self::keep_farm_group(ctx);
+                    } else {
+                        Self::delete_farm_group(ctx);
+                    }
                 }
                 if report.is_failure_free() {
                     Ok(Outcome::FromParentProcess(report))
@@ -948,6 +1914,176 @@ impl SystemTestGroup {
         }
     }
 
+    /// Runs the teardown function and returns its outcome for the report.
+    fn run_teardown(
+        ctx: &GroupContext,
+        teardown: Box<dyn PotSetupFn>,
+    ) -> Result<TargetFunctionSuccess, TargetFunctionFailure> {
+        info!(ctx.log(), ">>> teardown_fn");
+        let task_id = TaskId::Test(String::from(TEARDOWN_TASK_NAME));
+        let start = Instant::now();
+        let result = ctx
+            .create_teardown_env()
+            .map_err(|e| format!("Could not create teardown environment: {e:?}"))
+            .and_then(|env| panic_to_result(catch_unwind(AssertUnwindSafe(|| teardown(env)))));
+        let runtime = start.elapsed();
+        match result {
+            Ok(()) => {
+                info!(ctx.log(), "Teardown finished in {:?}", runtime);
+                Ok(TargetFunctionSuccess { task_id, runtime })
+            }
+            Err(message) => {
+                error!(ctx.log(), "Teardown failed: {}", message);
+                Err(TargetFunctionFailure::Panicked {
+                    task_id,
+                    message,
+                    runtime,
+                })
+            }
+        }
+    }
+
+    /// Runs `cleanup` after the overall timeout fired and waits for at most
+    /// `grace_period` for it to finish. The environment is torn down
+    /// afterwards even if `cleanup` is still running.
+    fn within_grace_period<T, F>(
+        ctx: &GroupContext,
+        grace_period: Duration,
+        cleanup: F,
+    ) -> Option<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        info!(
+            ctx.log(),
+            "Overall timeout fired, cleaning up within a grace period of {:?} ...", grace_period
+        );
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        std::thread::spawn(move || {
+            let _ = sender.send(cleanup());
+        });
+        match receiver.recv_timeout(grace_period) {
+            Ok(outcome) => Some(outcome),
+            Err(e) => {
+                warn!(
+                    ctx.log(),
+                    "Cleanup did not finish within the grace period of {:?}: {:?}", grace_period, e
+                );
+                None
+            }
+        }
+    }
+
+    /// Collects the artifacts of the testnet before the teardown function or
+    /// the deletion of the Farm group can destroy them.
+    fn collect_failure_artifacts(ctx: &GroupContext) {
+        match ctx.create_failure_artifacts_env() {
+            Ok(Some(env)) => collect_failure_artifacts(&env),
+            Ok(None) => info!(
+                ctx.log(),
+                "Not collecting failure artifacts as the setup did not start."
+            ),
+            Err(e) => warn!(
+                ctx.log(),
+                "Could not create failure artifacts environment: {:?}", e
+            ),
+        }
+    }
+
+    /// Collects the crash artifacts of the nodes of the testnet that dumped
+    /// core, although all tests passed.
+    fn collect_crash_artifacts(ctx: &GroupContext) {
+        let crashed = match ctx.get_setup_env() {
+            Ok(env) => crashed_nodes(&env),
+            Err(_) => return,
+        };
+        if crashed.is_empty() {
+            return;
+        }
+        warn!(
+            ctx.log(),
+            "All tests passed, but processes crashed on nodes {:?}.", crashed
+        );
+        match ctx.create_failure_artifacts_env() {
+            Ok(Some(env)) => collect_crash_artifacts(&env, &crashed),
+            Ok(None) => {}
+            Err(e) => warn!(
+                ctx.log(),
+                "Could not create failure artifacts environment: {:?}", e
+            ),
+        }
+    }
+
+    /// Copies the exported setup environment `source` into the setup directory
+    /// of this group.
+    fn reuse_setup(ctx: &GroupContext, source: &Path) -> Result<()> {
+        let source_env = TestEnv::new(source, ctx.logger())?;
+        if SetupResult::try_read_attribute(&source_env).is_err() {
+            bail!(
+                "{:?} does not contain a successfully completed setup",
+                source
+            );
+        }
+        let setup_dir = ctx.group_dir().join(GROUP_SETUP_DIR);
+        TestEnv::shell_copy(source, &setup_dir)?;
+        let env = TestEnv::new(setup_dir, ctx.logger())?;
+        ReusedSetup {
+            source: source.to_path_buf(),
+        }
+        .write_attribute(&env);
+        // The tests of this run use the seed of this run.
+        TestSeed::read_attribute(&ctx.get_root_env()?).write_attribute(&env);
+        info!(ctx.log(), "Attached to the setup exported to {:?}", source);
+        Ok(())
+    }
+
+    /// Copies the setup environment of this group to `target` if the setup
+    /// succeeded. Returns whether the setup was exported.
+    fn export_setup(ctx: &GroupContext, target: &Path) -> bool {
+        let setup_succeeded = ctx
+            .get_setup_env()
+            .map_or(false, |env| SetupResult::try_read_attribute(&env).is_ok());
+        if !setup_succeeded {
+            warn!(ctx.log(), "Not exporting the setup as it did not succeed.");
+            return false;
+        }
+        match TestEnv::shell_copy(ctx.group_dir().join(GROUP_SETUP_DIR), target) {
+            Ok(()) => {
+                info!(
+                    ctx.log(),
+                    "Exported the setup to {:?}. Attach to it with --reuse-setup.", target
+                );
+                true
+            }
+            Err(e) => {
+                warn!(
+                    ctx.log(),
+                    "Failed to export the setup to {:?}: {:?}", target, e
+                );
+                false
+            }
+        }
+    }
+
+    fn keep_farm_group(ctx: GroupContext) {
+        let env = get_setup_env(ctx.clone());
+        let group_setup = GroupSetup::read_attribute(&env);
+        let farm_url = env.get_farm_url().unwrap();
+        let farm = Farm::new(farm_url, env.logger());
+        let group_name = group_setup.farm_group_name;
+        match farm.set_group_ttl(&group_name, EXPORTED_GROUP_TTL) {
+            Ok(_) => info!(
+                ctx.log(),
+                "Keeping farm group {} alive for {:?}.", group_name, EXPORTED_GROUP_TTL
+            ),
+            Err(e) => warn!(
+                ctx.log(),
+                "Failed to keep farm group {} alive: {:?}", group_name, e
+            ),
+        }
+    }
+
     fn delete_farm_group(ctx: GroupContext) {
         info!(ctx.log(), "Deleting farm group.");
         let env = get_setup_env(ctx);
@@ -971,6 +2107,74 @@ fn print_report(ctx: &GroupContext, report: &SystemTestGroupReport) {
     };
 }
 
+/// Writes the results of the group in the requested formats. Failures are only
+/// logged, such that they do not mask the outcome of the tests.
+fn write_results(
+    ctx: &GroupContext,
+    report: &SystemTestGroupReport,
+    shard: Option<Shard>,
+    junit_xml_output: Option<&Path>,
+    json_output: Option<&Path>,
+) {
+    let group = ctx
+        .exec_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let failure_artifacts_dir = ctx.group_dir().join(FAILURE_ARTIFACTS_DIR);
+    let seed = ctx
+        .get_root_env()
+        .ok()
+        .and_then(|env| TestSeed::try_read_attribute(&env).ok())
+        .map(|seed| seed.0);
+    let mut results = report.to_results(&group, seed, |task_id, retries| {
+        let name = task_id.name();
+        if name == TEARDOWN_TASK_NAME {
+            vec![ctx.group_dir().join(TEARDOWN_DIR)]
+        } else if name == SETUP_TASK_NAME {
+            let setup_dir = ctx.group_dir().join(GROUP_SETUP_DIR);
+            setup_dir
+                .is_dir()
+                .then_some(setup_dir)
+                .into_iter()
+                .collect()
+        } else {
+            ctx.test_artifact_dirs(&name, retries)
+        }
+    });
+    results.shard = shard;
+    if failure_artifacts_dir.is_dir() {
+        for test in results
+            .tests
+            .iter_mut()
+            .filter(|t| matches!(t.status, TestStatus::Failed | TestStatus::TimedOut))
+        {
+            test.artifacts.push(failure_artifacts_dir.clone());
+        }
+    }
+    let flaky: Vec<&str> = results.flaky_tests().map(|t| t.name.as_str()).collect();
+    if !flaky.is_empty() {
+        warn!(
+            ctx.log(),
+            "{} tests passed only after retries and are flaky: {}",
+            flaky.len(),
+            flaky.join(", ")
+        );
+    }
+    if let Some(path) = junit_xml_output {
+        match results.write_junit_xml(path) {
+            Ok(()) => info!(ctx.log(), "Wrote JUnit XML results to {:?}", path),
+            Err(e) => warn!(ctx.log(), "{:?}", e),
+        }
+    }
+    if let Some(path) = json_output {
+        match results.write_json(path) {
+            Ok(()) => info!(ctx.log(), "Wrote JSON results to {:?}", path),
+            Err(e) => warn!(ctx.log(), "{:?}", e),
+        }
+    }
+}
+
 #[inline]
 fn log_event(log: &Logger, e: &Event) {
     match &e.what {
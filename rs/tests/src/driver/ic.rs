@@ -1,7 +1,7 @@
 use crate::driver::{
     bootstrap::{init_ic, setup_and_start_vms},
     farm::{Farm, HostFeature},
-    node_software_version::NodeSoftwareVersion,
+    node_software_version::{InitialReplicaVersion, NodeSoftwareVersion},
     resource::{allocate_resources, get_resource_request, ResourceGroup},
     test_env::{TestEnv, TestEnvAttribute},
     test_env_api::{HasIcDependencies, HasRegistryLocalStore, HasTopologySnapshot},
@@ -38,6 +38,7 @@ pub struct InternetComputer {
     pub node_operator: Option<PrincipalId>,
     pub node_provider: Option<PrincipalId>,
     pub unassigned_nodes: Vec<Node>,
+    pub unassigned_nodes_replica_version: Option<InitialReplicaVersion>,
     pub ssh_readonly_access_to_unassigned_nodes: Vec<String>,
     name: String,
     pub bitcoind_addr: Option<SocketAddr>,
@@ -71,8 +72,8 @@ impl InternetComputer {
     /// implicitly constructed subnets and nodes (like unassigned nodes
     /// added via `with_unassigned_nodes`).
     ///
-    /// Setting the VM resources for explicitly constructed subnets
-    /// has to be done on the subnet itself.
+    /// Explicitly constructed subnets can set their own VM resources. Resources
+    /// they leave unset are taken from the IC.
     pub fn with_default_vm_resources(mut self, default_vm_resources: VmResources) -> Self {
         self.default_vm_resources = default_vm_resources;
         self
@@ -113,6 +114,13 @@ impl InternetComputer {
         self
     }
 
+    /// Let the unassigned nodes run `version` instead of the initial replica
+    /// version of the IC.
+    pub fn with_unassigned_nodes_replica_version(mut self, version: InitialReplicaVersion) -> Self {
+        self.unassigned_nodes_replica_version = Some(version);
+        self
+    }
+
     pub fn with_node_operator(mut self, principal_id: PrincipalId) -> Self {
         self.node_operator = Some(principal_id);
         self
@@ -309,6 +317,7 @@ pub struct Subnet {
     pub max_number_of_canisters: Option<u64>,
     pub ssh_readonly_access: Vec<String>,
     pub ssh_backup_access: Vec<String>,
+    pub initial_replica_version: Option<InitialReplicaVersion>,
 }
 
 impl Subnet {
@@ -335,14 +344,16 @@ impl Subnet {
             subnet_type,
             ssh_readonly_access: vec![],
             ssh_backup_access: vec![],
+            initial_replica_version: None,
         }
     }
 
     /// Set the VM resources (like number of virtual CPUs and memory) of all
     /// implicitly constructed nodes.
     ///
-    /// Setting the VM resources for explicitly constructed nodes
-    /// has to be via `Node::new_with_vm_resources`.
+    /// Explicitly constructed nodes can set their own VM resources via
+    /// `Node::new_with_vm_resources`. Resources they leave unset are taken from
+    /// the subnet.
     pub fn with_default_vm_resources(mut self, default_vm_resources: VmResources) -> Self {
         self.default_vm_resources = default_vm_resources;
         self
@@ -425,6 +436,13 @@ impl Subnet {
         })
     }
 
+    /// Let the subnet run `version` instead of the initial replica version of
+    /// the IC.
+    pub fn with_initial_replica_version(mut self, version: InitialReplicaVersion) -> Self {
+        self.initial_replica_version = Some(version);
+        self
+    }
+
     pub fn add_node(mut self, node: Node) -> Self {
         self.nodes.push(node);
         self
@@ -517,6 +535,7 @@ impl Default for Subnet {
             max_number_of_canisters: None,
             ssh_readonly_access: vec![],
             ssh_backup_access: vec![],
+            initial_replica_version: None,
         }
     }
 }
@@ -537,6 +556,46 @@ pub struct VmResources {
     pub boot_image_minimal_size_gibibytes: Option<ImageSizeGiB>,
 }
 
+impl VmResources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_vcpus(mut self, vcpus: u64) -> Self {
+        self.vcpus = Some(NrOfVCPUs::new(vcpus));
+        self
+    }
+
+    pub fn with_memory_kibibytes(mut self, memory_kibibytes: u64) -> Self {
+        self.memory_kibibytes = Some(AmountOfMemoryKiB::new(memory_kibibytes));
+        self
+    }
+
+    pub fn with_memory_gibibytes(self, memory_gibibytes: u64) -> Self {
+        self.with_memory_kibibytes(memory_gibibytes * 1024 * 1024)
+    }
+
+    /// Set the minimal size of the disk of the VM. The boot image is grown to
+    /// this size if it is smaller.
+    pub fn with_boot_image_minimal_size_gibibytes(mut self, size_gibibytes: u64) -> Self {
+        self.boot_image_minimal_size_gibibytes = Some(ImageSizeGiB::new(size_gibibytes));
+        self
+    }
+
+    /// Returns these resources where every resource that is not set is taken
+    /// from `fallback`.
+    pub fn or(self, fallback: Option<VmResources>) -> Self {
+        let fallback = fallback.unwrap_or_default();
+        Self {
+            vcpus: self.vcpus.or(fallback.vcpus),
+            memory_kibibytes: self.memory_kibibytes.or(fallback.memory_kibibytes),
+            boot_image_minimal_size_gibibytes: self
+                .boot_image_minimal_size_gibibytes
+                .or(fallback.boot_image_minimal_size_gibibytes),
+        }
+    }
+}
+
 /// A builder for the initial configuration of a node.
 #[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Node {
@@ -553,6 +612,12 @@ impl Node {
         Default::default()
     }
 
+    pub fn new_with_vm_resources(vm_resources: VmResources) -> Self {
+        let mut node = Node::new();
+        node.vm_resources = vm_resources;
+        node
+    }
+
     pub fn new_with_settings(
         vm_resources: VmResources,
         vm_allocation: Option<VmAllocationStrategy>,
@@ -0,0 +1,270 @@
+//! Sending ingress to the IC at a fixed rate from within a test, and returning
+//! the latency and error statistics of the requests to the test:
+//!
+//! ```ignore
+//! let app_subnet = topology.subnets().find(|s| s.subnet_type() == SubnetType::Application).unwrap();
+//! let stats = app_subnet.generate_ingress(100, Duration::from_secs(60), move |_idx| {
+//!     Request::Update(CallSpec::new(canister_id, "write", vec![0; 1024]))
+//! })?;
+//! assert!(stats.success_ratio() > 0.95, "{}", stats);
+//! assert!(stats.latency_percentile(90.0) < Duration::from_secs(2), "{}", stats);
+//! ```
+//!
+//! Requests are sent round-robin via agents of all nodes of the targeted
+//! subnets, or of a single node. The latency of a request is the time until its
+//! outcome is known, e.g., until an [Request::UpdateE2e] is executed.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Result};
+use ic_agent::Agent;
+use slog::{info, Logger};
+
+use crate::{
+    driver::test_env_api::{
+        HasPublicApiUrl, HasTestEnv, IcNodeContainer, IcNodeSnapshot, SubnetSnapshot,
+    },
+    generic_workload_engine::engine::Engine,
+    util::block_on,
+    workload::{call, Request},
+};
+
+/// Requests are dispatched over `duration`, plus at most this fraction of it
+/// to account for the scheduling of their tasks.
+const DISPATCH_TIMEOUT_SLACK: f64 = 1.0 / 50.0;
+/// Requests are dispatched over `duration`, plus at least this time.
+const MIN_DISPATCH_TIMEOUT_SLACK: Duration = Duration::from_secs(2);
+
+/// The targets of ingress, e.g., a subnet or a single node.
+pub trait IngressWorkload {
+    /// The agents the requests are sent via, in the order they are used.
+    fn ingress_agents(&self) -> Vec<Agent>;
+
+    fn ingress_logger(&self) -> Logger;
+
+    /// Sends `rate` requests per second for `duration`, where `payload_fn`
+    /// returns the request with the given index, and awaits their outcomes.
+    fn generate_ingress<F>(
+        &self,
+        rate: usize,
+        duration: Duration,
+        payload_fn: F,
+    ) -> Result<IngressStats>
+    where
+        F: Fn(usize) -> Request + Send + 'static,
+    {
+        let agents = self.ingress_agents();
+        if agents.is_empty() {
+            bail!("There are no nodes to send the ingress to.");
+        }
+        let log = self.ingress_logger();
+        info!(
+            log,
+            "Sending {} requests per second for {:?} via {} agents ...",
+            rate,
+            duration,
+            agents.len()
+        );
+        let futures_generator = move |idx: usize| {
+            let request = payload_fn(idx);
+            let agent = agents[idx % agents.len()].clone();
+            async move {
+                let start = Instant::now();
+                let result = call(request, &agent).await.map_err(|e| e.to_string());
+                (start.elapsed(), result)
+            }
+        };
+        let slack = duration
+            .mul_f64(DISPATCH_TIMEOUT_SLACK)
+            .max(MIN_DISPATCH_TIMEOUT_SLACK);
+        let engine = Engine::new(log.clone(), futures_generator, rate, duration)
+            .increase_dispatch_timeout(slack);
+        let stats = block_on(engine.execute(IngressStats::default(), IngressStats::record))
+            .map_err(|e| anyhow!("Failed to generate the ingress: {:?}", e))?
+            .sorted();
+        info!(log, "{}", stats);
+        Ok(stats)
+    }
+}
+
+impl IngressWorkload for IcNodeSnapshot {
+    fn ingress_agents(&self) -> Vec<Agent> {
+        vec![self.build_default_agent()]
+    }
+
+    fn ingress_logger(&self) -> Logger {
+        self.test_env().logger()
+    }
+}
+
+impl IngressWorkload for SubnetSnapshot {
+    fn ingress_agents(&self) -> Vec<Agent> {
+        self.nodes()
+            .map(|node| node.build_default_agent())
+            .collect()
+    }
+
+    fn ingress_logger(&self) -> Logger {
+        self.test_env().logger()
+    }
+}
+
+impl IngressWorkload for [SubnetSnapshot] {
+    fn ingress_agents(&self) -> Vec<Agent> {
+        self.iter()
+            .flat_map(|subnet| subnet.ingress_agents())
+            .collect()
+    }
+
+    fn ingress_logger(&self) -> Logger {
+        match self.first() {
+            Some(subnet) => subnet.ingress_logger(),
+            None => Logger::root(slog::Discard, slog::o!()),
+        }
+    }
+}
+
+/// The outcomes of the requests sent by [IngressWorkload::generate_ingress].
+/// Latencies are those of all requests, including the failed ones.
+#[derive(Clone, Debug, Default)]
+pub struct IngressStats {
+    /// Sorted in ascending order once all requests finished.
+    latencies: Vec<Duration>,
+    failures: usize,
+    errors: BTreeMap<String, usize>,
+}
+
+impl IngressStats {
+    fn record(mut self, (latency, result): (Duration, Result<(), String>)) -> Self {
+        self.latencies.push(latency);
+        if let Err(e) = result {
+            self.failures += 1;
+            *self.errors.entry(e).or_default() += 1;
+        }
+        self
+    }
+
+    fn sorted(mut self) -> Self {
+        self.latencies.sort();
+        self
+    }
+
+    pub fn requests(&self) -> usize {
+        self.latencies.len()
+    }
+
+    pub fn successes(&self) -> usize {
+        self.requests() - self.failures
+    }
+
+    pub fn failures(&self) -> usize {
+        self.failures
+    }
+
+    /// The ratio of successful requests, or 0 if no request was sent.
+    pub fn success_ratio(&self) -> f64 {
+        if self.requests() == 0 {
+            return 0.0;
+        }
+        self.successes() as f64 / self.requests() as f64
+    }
+
+    /// The number of failed requests by error message.
+    pub fn errors(&self) -> &BTreeMap<String, usize> {
+        &self.errors
+    }
+
+    /// The latency not exceeded by `percentile` percent of the requests, or 0
+    /// if no request was sent.
+    pub fn latency_percentile(&self, percentile: f64) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = (percentile / 100.0 * self.latencies.len() as f64).ceil() as usize;
+        self.latencies[rank.clamp(1, self.latencies.len()) - 1]
+    }
+
+    pub fn min_latency(&self) -> Duration {
+        self.latencies.first().copied().unwrap_or_default()
+    }
+
+    pub fn max_latency(&self) -> Duration {
+        self.latencies.last().copied().unwrap_or_default()
+    }
+
+    pub fn mean_latency(&self) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        self.latencies.iter().sum::<Duration>() / self.latencies.len() as u32
+    }
+
+    /// The ratio of requests with a latency below `threshold`.
+    pub fn ratio_below(&self, threshold: Duration) -> f64 {
+        if self.latencies.is_empty() {
+            return 0.0;
+        }
+        let below = self
+            .latencies
+            .partition_point(|latency| *latency < threshold);
+        below as f64 / self.latencies.len() as f64
+    }
+}
+
+impl Display for IngressStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "IngressStats {{ requests: {}, success_rate: {:.2}%, min: {}ms, p50: {}ms, p90: {}ms, p99: {}ms, max: {}ms, errors: {:?} }}",
+            self.requests(),
+            100.0 * self.success_ratio(),
+            self.min_latency().as_millis(),
+            self.latency_percentile(50.0).as_millis(),
+            self.latency_percentile(90.0).as_millis(),
+            self.latency_percentile(99.0).as_millis(),
+            self.max_latency().as_millis(),
+            self.errors
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_aggregate_latencies_and_errors() {
+        let stats = (1..=10)
+            .rev()
+            .map(|i| {
+                let result = match i {
+                    3 | 7 => Err("timeout".to_string()),
+                    5 => Err("rejected".to_string()),
+                    _ => Ok(()),
+                };
+                (Duration::from_millis(100 * i), result)
+            })
+            .fold(IngressStats::default(), IngressStats::record)
+            .sorted();
+        assert_eq!(stats.requests(), 10);
+        assert_eq!(stats.successes(), 7);
+        assert_eq!(stats.failures(), 3);
+        assert_eq!(stats.errors()["timeout"], 2);
+        assert_eq!(stats.errors()["rejected"], 1);
+        assert_eq!(stats.min_latency(), Duration::from_millis(100));
+        assert_eq!(stats.latency_percentile(50.0), Duration::from_millis(500));
+        assert_eq!(stats.latency_percentile(90.0), Duration::from_millis(900));
+        assert_eq!(stats.latency_percentile(100.0), Duration::from_millis(1000));
+        assert_eq!(stats.max_latency(), Duration::from_millis(1000));
+        assert_eq!(stats.mean_latency(), Duration::from_millis(550));
+        assert_eq!(stats.ratio_below(Duration::from_millis(300)), 0.2);
+        assert_eq!(
+            IngressStats::default().latency_percentile(50.0),
+            Duration::ZERO
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Keeping the testnet of a finished group alive until the user interrupts the
+//! test driver, such that a failed environment can be inspected instead of
+//! re-running the test. See the `--keepalive-on-failure` flag of
+//! [SystemTestGroup](crate::driver::group::SystemTestGroup).
+
+use std::{fmt::Write as _, fs, path::Path, thread};
+
+use crossbeam_channel::RecvTimeoutError;
+use slog::{info, warn};
+use tokio::runtime::Handle;
+
+use crate::driver::{
+    boundary_node::{BoundaryNodeVm, BOUNDARY_NODE_VMS_DIR},
+    constants::{kibana_link, GROUP_TTL, KEEPALIVE_INTERVAL, SSH_USERNAME},
+    driver_setup::SSH_AUTHORIZED_PRIV_KEYS_DIR,
+    farm::Farm,
+    test_env::{HasIcPrepDir, TestEnv, TestEnvAttribute},
+    test_env_api::{HasIcDependencies, HasPublicApiUrl, HasTopologySnapshot, IcNodeContainer},
+    test_setup::GroupSetup,
+    universal_vm::{UniversalVms, UNIVERSAL_VMS_DIR},
+};
+
+/// Logs how to access the VMs of the testnet of `env`, then extends the TTL of
+/// its Farm group periodically until ctrl+c is pressed.
+pub fn keep_testnet_alive(rh: &Handle, env: &TestEnv) {
+    let log = env.logger();
+    let group_name = match GroupSetup::try_read_attribute(env) {
+        Ok(group_setup) => group_setup.farm_group_name,
+        Err(_) => {
+            warn!(
+                log,
+                "Not keeping the testnet alive as no Farm group was created."
+            );
+            return;
+        }
+    };
+    info!(log, "{}", testnet_details(env, &group_name));
+    let farm = Farm::new(env.get_farm_url().unwrap(), log.clone());
+    // The sender is dropped once ctrl+c is pressed, which stops the thread.
+    let (stop_sender, stop_receiver) = crossbeam_channel::bounded::<()>(0);
+    let ttl_thread = thread::spawn({
+        let log = log.clone();
+        move || loop {
+            match farm.set_group_ttl(&group_name, GROUP_TTL) {
+                Ok(_) => info!(
+                    log,
+                    "Keeping Farm group {} alive for another {:?}. Press ctrl+c to stop.",
+                    group_name,
+                    GROUP_TTL
+                ),
+                Err(e) => warn!(
+                    log,
+                    "Failed to keep Farm group {} alive: {:?}", group_name, e
+                ),
+            }
+            if let Err(RecvTimeoutError::Disconnected) =
+                stop_receiver.recv_timeout(KEEPALIVE_INTERVAL)
+            {
+                return;
+            }
+        }
+    });
+    if let Err(e) = rh.block_on(tokio::signal::ctrl_c()) {
+        warn!(log, "Failed to await ctrl+c: {:?}", e);
+    }
+    info!(log, "Interrupted, stopping to keep the testnet alive.");
+    drop(stop_sender);
+    let _ = ttl_thread.join();
+}
+
+/// Describes how to log into the VMs of the testnet and where to reach its
+/// endpoints.
+fn testnet_details(env: &TestEnv, group_name: &str) -> String {
+    let priv_key = env
+        .get_path(SSH_AUTHORIZED_PRIV_KEYS_DIR)
+        .join(SSH_USERNAME);
+    let ssh = |ip: &dyn std::fmt::Display| {
+        format!("ssh -i {} {}@{}", priv_key.display(), SSH_USERNAME, ip)
+    };
+    let mut details = format!(
+        "The testnet is kept alive:\n  Farm group: {}\n  Replica logs: {}\n",
+        group_name,
+        kibana_link(group_name)
+    );
+    if env.prep_dir("").is_some() {
+        let topology = env.topology_snapshot();
+        for subnet in topology.subnets() {
+            let _ = writeln!(
+                details,
+                "  Subnet {} ({:?}):",
+                subnet.subnet_id,
+                subnet.subnet_type()
+            );
+            for node in subnet.nodes() {
+                let _ = writeln!(
+                    details,
+                    "    Node {}: {}, {}",
+                    node.node_id,
+                    node.get_public_url(),
+                    ssh(&node.get_ip_addr())
+                );
+            }
+        }
+        for node in topology.unassigned_nodes() {
+            let _ = writeln!(
+                details,
+                "  Unassigned node {}: {}",
+                node.node_id,
+                ssh(&node.get_ip_addr())
+            );
+        }
+    }
+    for name in vm_names(&env.get_path(UNIVERSAL_VMS_DIR)) {
+        if let Ok(vm) = env
+            .get_deployed_universal_vm(&name)
+            .and_then(|vm| vm.get_vm())
+        {
+            let _ = writeln!(details, "  Universal VM {}: {}", name, ssh(&vm.ipv6));
+        }
+    }
+    for name in vm_names(&env.get_path(BOUNDARY_NODE_VMS_DIR)) {
+        if let Ok(bn) = env
+            .get_deployed_boundary_node(&name)
+            .and_then(|bn| bn.get_snapshot())
+        {
+            let _ = writeln!(
+                details,
+                "  Boundary node {}: {}, {}",
+                name,
+                bn.get_public_url(),
+                ssh(&bn.ipv6())
+            );
+        }
+    }
+    details
+}
+
+/// The names of the VMs deployed into the subdirectories of `dir`.
+pub(crate) fn vm_names(dir: &Path) -> Vec<String> {
+    fs::read_dir(dir).map_or(vec![], |entries| {
+        entries
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect()
+    })
+}
@@ -0,0 +1,720 @@
+//! A backend provisioning the VMs of a system test as local qemu VMs instead of
+//! Farm VMs, such that system tests can run on developer machines and in CI
+//! without access to Farm. The backend is selected with the `--local-backend
+//! <dir>` flag of the test driver and is used transparently by [Farm] for base
+//! urls with the `local` scheme.
+//!
+//! All state lives in the given directory:
+//!
+//! - files/<file_id>/<file_name>  <-- uploaded and downloaded disk images
+//!   |- disk.img                  <-- the decompressed image, if used as primary image
+//! - groups/<group_name>/<vm_name>/
+//!   |- vm.json                   <-- the settings of the VM
+//!   |- primary.qcow2             <-- copy-on-write overlay of the primary image
+//!   |- drive_<i>.img             <-- decompressed attached disk images
+//!   |- qemu.pid
+//!   |- monitor.sock              <-- the qemu monitor used for snapshots
+//!   |- console.log
+//!
+//! The VMs are attached to a bridge, [DEFAULT_LOCAL_BRIDGE] unless specified
+//! otherwise, through the qemu bridge helper. The bridge has to be created
+//! beforehand and has to announce the prefix [LOCAL_IPV6_PREFIX] via router
+//! advertisements, such that the VMs obtain the SLAAC addresses derived from
+//! their MAC addresses. The test driver reaches the VMs on these addresses.
+//! IPv4 addresses, DNS records and playnet certificates are not supported.
+//!
+//! The VMs boot the IC-OS images like Farm VMs, so the backend needs
+//! qemu-system-x86_64 with KVM and the OVMF firmware on the host, see
+//! [check_local_backend_prerequisites]. Containers are not supported, as the
+//! nodes run the full IC-OS.
+//!
+//! Snapshots are internal snapshots of the primary image taken via the qemu
+//! monitor and include the memory of the VM.
+
+use std::{
+    ffi::OsStr,
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom, Write},
+    net::Ipv6Addr,
+    os::unix::{ffi::OsStrExt, net::UnixStream},
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
+};
+
+use ic_crypto_sha::Sha256;
+use serde::{Deserialize, Serialize};
+use slog::{info, warn, Logger};
+use url::Url;
+
+use crate::driver::farm::{
+    id_of_file, ClaimResult, ConsoleLogRead, CreateVmRequest, FarmError, FarmResult,
+    FileExpiration, FileId, ImageLocation, VMCreateResponse, VmSpec,
+};
+
+/// The url scheme selecting the local backend.
+pub const LOCAL_BACKEND_SCHEME: &str = "local";
+/// The bridge the VMs are attached to by default.
+pub const DEFAULT_LOCAL_BRIDGE: &str = "icbr0";
+/// The /64 prefix announced on the bridge.
+pub const LOCAL_IPV6_PREFIX: [u16; 4] = [0xfd00, 0x1c, 0, 0];
+/// The query parameter of the base url holding the bridge.
+const BRIDGE_PARAM: &str = "bridge";
+
+const FILES_DIR: &str = "files";
+const GROUPS_DIR: &str = "groups";
+const VM_SETTINGS_FILE: &str = "vm.json";
+const BASE_IMAGE_FILE: &str = "disk.img";
+const PRIMARY_IMAGE_FILE: &str = "primary.qcow2";
+const PID_FILE: &str = "qemu.pid";
+const CONSOLE_LOG_FILE: &str = "console.log";
+const MONITOR_SOCKET: &str = "monitor.sock";
+const MONITOR_PROMPT: &str = "(qemu) ";
+/// Saving and loading the memory of a VM can take a while.
+const MONITOR_TIMEOUT: Duration = Duration::from_secs(600);
+/// The UEFI firmware needed to boot IC-OS images.
+const OVMF_PATH: &str = "/usr/share/OVMF/OVMF_CODE.fd";
+
+/// Checks that the host provides what the local backend needs to start VMs
+/// attached to `bridge`, such that a missing prerequisite fails the test
+/// driver up front rather than the first VM that is started.
+pub fn check_local_backend_prerequisites(bridge: &str) -> FarmResult<()> {
+    let error = |message| Err(FarmError::LocalBackendError { message });
+    if Command::new("qemu-system-x86_64")
+        .arg("--version")
+        .output()
+        .is_err()
+    {
+        return error("qemu-system-x86_64 is not installed.".to_string());
+    }
+    if !Path::new(OVMF_PATH).is_file() {
+        return error(format!(
+            "The UEFI firmware {} is missing, install OVMF.",
+            OVMF_PATH
+        ));
+    }
+    if !Path::new("/sys/class/net")
+        .join(bridge)
+        .join("bridge")
+        .is_dir()
+    {
+        let [a, b, c, d] = LOCAL_IPV6_PREFIX;
+        return error(format!(
+            "The bridge {} does not exist. Create it and announce the prefix {}/64 on it via router advertisements.",
+            bridge,
+            Ipv6Addr::new(a, b, c, d, 0, 0, 0, 0)
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the base url selecting the local backend with state in `dir` and
+/// VMs attached to `bridge`.
+pub fn local_backend_url(dir: &Path, bridge: &str) -> FarmResult<Url> {
+    fs::create_dir_all(dir)?;
+    let dir = dir.canonicalize()?;
+    let mut url = Url::parse(&format!("{}:///", LOCAL_BACKEND_SCHEME)).map_err(|e| {
+        FarmError::LocalBackendError {
+            message: format!("Invalid local backend url: {:?}", e),
+        }
+    })?;
+    // The path is percent-encoded by the url.
+    url.set_path(&format!("{}/", dir.to_string_lossy()));
+    url.query_pairs_mut().append_pair(BRIDGE_PARAM, bridge);
+    Ok(url)
+}
+
+#[derive(Clone, Debug)]
+pub struct LocalBackend {
+    state_dir: PathBuf,
+    bridge: String,
+    logger: Logger,
+}
+
+/// The settings of a VM, persisted such that it can be (re)started by any
+/// process of the test driver.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct LocalVm {
+    vcpus: u64,
+    memory_kibibytes: u64,
+    mac: String,
+    ipv6: Ipv6Addr,
+    qemu_cli_args: Vec<String>,
+    drives: Vec<PathBuf>,
+}
+
+impl LocalBackend {
+    pub fn new(base_url: &Url, logger: Logger) -> Self {
+        let bridge = base_url
+            .query_pairs()
+            .find(|(key, _)| key == BRIDGE_PARAM)
+            .map(|(_, bridge)| bridge.into_owned())
+            .unwrap_or_else(|| DEFAULT_LOCAL_BRIDGE.to_string());
+        Self {
+            state_dir: percent_decode(base_url.path()),
+            bridge,
+            logger,
+        }
+    }
+
+    pub fn create_group(&self, group_name: &str) -> FarmResult<()> {
+        fs::create_dir_all(self.group_dir(group_name))?;
+        info!(
+            self.logger,
+            "Created local group {:?}",
+            self.group_dir(group_name)
+        );
+        Ok(())
+    }
+
+    pub fn create_vm(&self, group_name: &str, vm: CreateVmRequest) -> FarmResult<VMCreateResponse> {
+        let vm_dir = self.vm_dir(group_name, vm.name());
+        fs::create_dir_all(&vm_dir)?;
+        if vm.has_ipv4 {
+            warn!(
+                self.logger,
+                "VM({}) requested an IPv4 address, which the local backend does not provide.",
+                vm.name()
+            );
+        }
+        let base_image = self.primary_image(&vm.primary_image, &vm_dir)?;
+        let mut qemu_img = Command::new("qemu-img");
+        qemu_img
+            .args(["create", "-f", "qcow2", "-F", "raw", "-b"])
+            .arg(&base_image)
+            .arg(vm_dir.join(PRIMARY_IMAGE_FILE));
+        if let Some(size) = vm.primary_image_minimal_size_gibibytes {
+            qemu_img.arg(format!("{}G", size.get()));
+        }
+        run(&mut qemu_img)?;
+
+        let mac = mac_of_vm(group_name, vm.name());
+        let local_vm = LocalVm {
+            vcpus: vm.vcpus.get(),
+            memory_kibibytes: vm.memory_kibibytes.get(),
+            ipv6: ipv6_of_mac(&mac),
+            mac,
+            qemu_cli_args: vm.qemu_cli_args.clone(),
+            drives: vec![],
+        };
+        self.write_vm(group_name, vm.name(), &local_vm)?;
+        info!(
+            self.logger,
+            "VM({}) Host: localhost IPv6: {} vCPUs: {} Memory: {} KiB",
+            vm.name(),
+            local_vm.ipv6,
+            local_vm.vcpus,
+            local_vm.memory_kibibytes,
+        );
+        Ok(VMCreateResponse {
+            ipv6: local_vm.ipv6,
+            hostname: "localhost".to_string(),
+            spec: VmSpec {
+                v_cpus: local_vm.vcpus,
+                memory_ki_b: local_vm.memory_kibibytes,
+            },
+        })
+    }
+
+    pub fn claim_file(&self, file_id: &FileId) -> FarmResult<ClaimResult> {
+        Ok(match self.find_file(file_id)? {
+            Some(_) => ClaimResult::FileClaimed(FileExpiration { expiration: None }),
+            None => ClaimResult::FileNotFound,
+        })
+    }
+
+    pub fn upload_file<P: AsRef<Path>>(&self, path: P, filename: &str) -> FarmResult<FileId> {
+        let file_id =
+            id_of_file(path.as_ref().to_path_buf()).map_err(|e| FarmError::LocalBackendError {
+                message: format!("Could not hash {:?}: {:?}", path.as_ref(), e),
+            })?;
+        let file_dir = self.state_dir.join(FILES_DIR).join(file_id.to_string());
+        fs::create_dir_all(&file_dir)?;
+        fs::copy(path, file_dir.join(filename))?;
+        Ok(file_id)
+    }
+
+    pub fn attach_disk_images(
+        &self,
+        group_name: &str,
+        vm_name: &str,
+        image_ids: Vec<FileId>,
+    ) -> FarmResult<()> {
+        let mut local_vm = self.read_vm(group_name, vm_name)?;
+        for image_id in image_ids {
+            let file = self
+                .find_file(&image_id)?
+                .ok_or_else(|| FarmError::NotFound {
+                    message: format!("File {} was not uploaded", image_id),
+                })?;
+            let drive = self
+                .vm_dir(group_name, vm_name)
+                .join(format!("drive_{}.img", local_vm.drives.len()));
+            decompress(&file, &drive)?;
+            local_vm.drives.push(drive);
+        }
+        self.write_vm(group_name, vm_name, &local_vm)
+    }
+
+    pub fn start_vm(&self, group_name: &str, vm_name: &str) -> FarmResult<()> {
+        let local_vm = self.read_vm(group_name, vm_name)?;
+        let vm_dir = self.vm_dir(group_name, vm_name);
+        let mut qemu = Command::new("qemu-system-x86_64");
+        qemu.args(["-machine", "q35,accel=kvm", "-cpu", "host", "-daemonize"])
+            .args(["-name", vm_name, "-bios", OVMF_PATH, "-display", "none"])
+            .arg("-smp")
+            .arg(local_vm.vcpus.to_string())
+            .arg("-m")
+            .arg(format!("{}K", local_vm.memory_kibibytes))
+            .arg("-pidfile")
+            .arg(vm_dir.join(PID_FILE))
+            .arg("-serial")
+            .arg(format!("file:{}", vm_dir.join(CONSOLE_LOG_FILE).display()))
+            .arg("-monitor")
+            .arg(format!(
+                "unix:{},server,nowait",
+                vm_dir.join(MONITOR_SOCKET).display()
+            ))
+            .arg("-drive")
+            .arg(format!(
+                "file={},if=virtio,format=qcow2",
+                vm_dir.join(PRIMARY_IMAGE_FILE).display()
+            ))
+            .arg("-netdev")
+            .arg(format!("bridge,id=net0,br={}", self.bridge))
+            .arg("-device")
+            .arg(format!("virtio-net-pci,netdev=net0,mac={}", local_vm.mac))
+            .args(["-device", "qemu-xhci,id=xhci"]);
+        for (i, drive) in local_vm.drives.iter().enumerate() {
+            qemu.arg("-drive")
+                .arg(format!(
+                    // Read-only drives do not prevent internal snapshots of
+                    // the VM, which raw images do not support otherwise.
+                    "file={},if=none,id=usb{},format=raw,readonly=on",
+                    drive.display(),
+                    i
+                ))
+                .arg("-device")
+                .arg(format!("usb-storage,bus=xhci.0,drive=usb{}", i));
+        }
+        qemu.args(&local_vm.qemu_cli_args);
+        run(&mut qemu)?;
+        info!(self.logger, "Console: {:?}", vm_dir.join(CONSOLE_LOG_FILE));
+        Ok(())
+    }
+
+    pub fn read_console_log(
+        &self,
+        group_name: &str,
+        vm_name: &str,
+        offset: u64,
+    ) -> FarmResult<ConsoleLogRead> {
+        let vm_dir = self.vm_dir(group_name, vm_name);
+        if !vm_dir.exists() {
+            return Err(FarmError::NotFound {
+                message: format!("VM {} does not exist", vm_name),
+            });
+        }
+        let mut log = vec![];
+        match File::open(vm_dir.join(CONSOLE_LOG_FILE)) {
+            Ok(mut file) => {
+                // qemu truncates the log when the VM is started again.
+                if file.metadata()?.len() < offset {
+                    return Ok(ConsoleLogRead::Truncated);
+                }
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_to_end(&mut log)?;
+            }
+            // The log is created once the VM is started.
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        Ok(ConsoleLogRead::Appended(log))
+    }
+
+    pub fn destroy_vm(&self, group_name: &str, vm_name: &str) -> FarmResult<()> {
+        let pid_file = self.vm_dir(group_name, vm_name).join(PID_FILE);
+        if let Ok(pid) = fs::read_to_string(&pid_file) {
+            // The VM might have shut down already, in which case kill fails.
+            if let Err(e) = run(Command::new("kill").arg(pid.trim())) {
+                warn!(self.logger, "Failed to stop VM {}: {:?}", vm_name, e);
+            }
+            fs::remove_file(pid_file)?;
+        }
+        Ok(())
+    }
+
+    /// Saves the state of the VM as an internal snapshot of its qcow2 image.
+    pub fn snapshot_vm(
+        &self,
+        group_name: &str,
+        vm_name: &str,
+        snapshot_name: &str,
+    ) -> FarmResult<()> {
+        self.monitor_command(group_name, vm_name, &format!("savevm {}", snapshot_name))?;
+        info!(
+            self.logger,
+            "Saved snapshot {} of VM {}", snapshot_name, vm_name
+        );
+        Ok(())
+    }
+
+    pub fn restore_vm(
+        &self,
+        group_name: &str,
+        vm_name: &str,
+        snapshot_name: &str,
+    ) -> FarmResult<()> {
+        self.monitor_command(group_name, vm_name, &format!("loadvm {}", snapshot_name))?;
+        info!(
+            self.logger,
+            "Restored snapshot {} of VM {}", snapshot_name, vm_name
+        );
+        Ok(())
+    }
+
+    pub fn reboot_vm(&self, group_name: &str, vm_name: &str) -> FarmResult<()> {
+        self.destroy_vm(group_name, vm_name)?;
+        self.start_vm(group_name, vm_name)
+    }
+
+    pub fn delete_group(&self, group_name: &str) {
+        let group_dir = self.group_dir(group_name);
+        let vm_names: Vec<String> = fs::read_dir(&group_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        for vm_name in vm_names {
+            if let Err(e) = self.destroy_vm(group_name, &vm_name) {
+                warn!(self.logger, "Failed to stop VM {}: {:?}", vm_name, e);
+            }
+        }
+        if let Err(e) = fs::remove_dir_all(&group_dir) {
+            warn!(
+                self.logger,
+                "Failed to remove local group {:?}: {:?}", group_dir, e
+            );
+        }
+    }
+
+    /// Returns the decompressed primary image at `location`, using `vm_dir` as
+    /// scratch space, such that VMs created concurrently do not interfere.
+    fn primary_image(&self, location: &ImageLocation, vm_dir: &Path) -> FarmResult<PathBuf> {
+        let (file, file_key) = match location {
+            ImageLocation::ImageViaId { id } | ImageLocation::IcOsImageViaId { id } => (
+                self.find_file(id)?.ok_or_else(|| FarmError::NotFound {
+                    message: format!("File {} was not uploaded", id),
+                })?,
+                id.to_string(),
+            ),
+            ImageLocation::ImageViaUrl { url, sha256 }
+            | ImageLocation::IcOsImageViaUrl { url, sha256 } => {
+                (self.download_image(url, sha256)?, sha256.clone())
+            }
+        };
+        // The base image is shared by all VMs using it.
+        let base_image = self
+            .state_dir
+            .join(FILES_DIR)
+            .join(file_key)
+            .join(BASE_IMAGE_FILE);
+        if !base_image.exists() {
+            let scratch_image = vm_dir.join(BASE_IMAGE_FILE);
+            decompress(&file, &scratch_image)?;
+            fs::rename(scratch_image, &base_image)?;
+        }
+        Ok(base_image)
+    }
+
+    /// Downloads the image at `url` unless it is cached already.
+    fn download_image(&self, url: &Url, sha256: &str) -> FarmResult<PathBuf> {
+        let file_name = url
+            .path_segments()
+            .and_then(|segments| segments.last())
+            .filter(|name| !name.is_empty())
+            .unwrap_or("image");
+        let file_dir = self.state_dir.join(FILES_DIR).join(sha256);
+        let path = file_dir.join(file_name);
+        if path.exists() {
+            return Ok(path);
+        }
+        fs::create_dir_all(&file_dir)?;
+        info!(self.logger, "Downloading {} to {:?} ...", url, path);
+        let mut resp = reqwest::blocking::get(url.clone())?.error_for_status()?;
+        let mut file = File::create(&path)?;
+        let mut hasher = Sha256::new();
+        resp.copy_to(&mut TeeWriter(&mut file, &mut hasher))?;
+        let digest = hex::encode(hasher.finish());
+        if digest != sha256 {
+            fs::remove_file(&path)?;
+            return Err(FarmError::LocalBackendError {
+                message: format!("Image {} has sha256 {} instead of {}", url, digest, sha256),
+            });
+        }
+        Ok(path)
+    }
+
+    /// Returns the single file stored under `file_id`, if any.
+    fn find_file(&self, file_id: &FileId) -> FarmResult<Option<PathBuf>> {
+        let file_dir = self.state_dir.join(FILES_DIR).join(file_id.to_string());
+        if !file_dir.is_dir() {
+            return Ok(None);
+        }
+        for entry in fs::read_dir(file_dir)? {
+            let path = entry?.path();
+            if path.is_file()
+                && path
+                    .file_name()
+                    .map_or(false, |name| name != BASE_IMAGE_FILE)
+            {
+                return Ok(Some(path));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Runs `command` on the qemu monitor of the VM. The human monitor does not
+    /// report errors in a structured way, hence, any output is treated as one.
+    fn monitor_command(&self, group_name: &str, vm_name: &str, command: &str) -> FarmResult<()> {
+        let socket = self.vm_dir(group_name, vm_name).join(MONITOR_SOCKET);
+        let mut stream = UnixStream::connect(&socket).map_err(|e| FarmError::NotFound {
+            message: format!("VM {} is not running: {:?}", vm_name, e),
+        })?;
+        stream.set_read_timeout(Some(MONITOR_TIMEOUT))?;
+        // Skip the greeting of the monitor.
+        read_until_prompt(&mut stream)?;
+        stream.write_all(format!("{}\n", command).as_bytes())?;
+        let output = read_until_prompt(&mut stream)?;
+        // The monitor echoes the command, possibly with terminal escape codes.
+        let errors: Vec<&str> = output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.contains(command))
+            .collect();
+        if !errors.is_empty() {
+            return Err(FarmError::LocalBackendError {
+                message: format!(
+                    "`{}` failed on VM {}: {}",
+                    command,
+                    vm_name,
+                    errors.join("; ")
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    fn read_vm(&self, group_name: &str, vm_name: &str) -> FarmResult<LocalVm> {
+        let path = self.vm_dir(group_name, vm_name).join(VM_SETTINGS_FILE);
+        let file = File::open(&path).map_err(|_| FarmError::NotFound {
+            message: format!("VM {} does not exist in group {}", vm_name, group_name),
+        })?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    fn write_vm(&self, group_name: &str, vm_name: &str, local_vm: &LocalVm) -> FarmResult<()> {
+        let path = self.vm_dir(group_name, vm_name).join(VM_SETTINGS_FILE);
+        serde_json::to_writer_pretty(File::create(path)?, local_vm)?;
+        Ok(())
+    }
+
+    fn group_dir(&self, group_name: &str) -> PathBuf {
+        self.state_dir.join(GROUPS_DIR).join(group_name)
+    }
+
+    fn vm_dir(&self, group_name: &str, vm_name: &str) -> PathBuf {
+        self.group_dir(group_name).join(vm_name)
+    }
+}
+
+/// Writes `source` to `target`, decompressing it depending on its extension.
+/// IC-OS images are tarballs containing a single `disk.img`.
+fn decompress(source: &Path, target: &Path) -> FarmResult<()> {
+    let name = source.to_string_lossy();
+    if name.ends_with(".tar.zst") || name.ends_with(".tar.gz") {
+        let target_dir = target.parent().expect("target has a parent");
+        run(Command::new("tar")
+            .arg("-xf")
+            .arg(source)
+            .arg("-C")
+            .arg(target_dir))?;
+        let extracted = target_dir.join(BASE_IMAGE_FILE);
+        if extracted != target {
+            fs::rename(extracted, target)?;
+        }
+    } else if name.ends_with(".zst") {
+        run(Command::new("zstd")
+            .args(["-d", "-f", "-q"])
+            .arg(source)
+            .arg("-o")
+            .arg(target))?;
+    } else if name.ends_with(".gz") {
+        let mut decoder = flate2::read::GzDecoder::new(File::open(source)?);
+        std::io::copy(&mut decoder, &mut File::create(target)?)?;
+    } else {
+        fs::copy(source, target)?;
+    }
+    Ok(())
+}
+
+/// Reads from the qemu monitor until it prompts for the next command and
+/// returns everything before the prompt.
+fn read_until_prompt(stream: &mut UnixStream) -> FarmResult<String> {
+    let mut output = Vec::new();
+    let mut buf = [0u8; 4096];
+    while !output.ends_with(MONITOR_PROMPT.as_bytes()) {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            return Err(FarmError::LocalBackendError {
+                message: "qemu monitor closed the connection".to_string(),
+            });
+        }
+        output.extend_from_slice(&buf[..n]);
+    }
+    output.truncate(output.len() - MONITOR_PROMPT.len());
+    Ok(String::from_utf8_lossy(&output).to_string())
+}
+
+fn run(cmd: &mut Command) -> FarmResult<()> {
+    let output = cmd.output()?;
+    if !output.status.success() {
+        return Err(FarmError::LocalBackendError {
+            message: format!(
+                "{:?} failed with {}: {}",
+                cmd,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Returns a locally administered MAC address which is unique per VM.
+/// Decodes a percent-encoded url path into the path it denotes.
+fn percent_decode(path: &str) -> PathBuf {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| {
+            std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        });
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    PathBuf::from(OsStr::from_bytes(&decoded))
+}
+
+fn mac_of_vm(group_name: &str, vm_name: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.write(group_name.as_bytes());
+    hasher.write(b"/");
+    hasher.write(vm_name.as_bytes());
+    let digest = hasher.finish();
+    format!(
+        "02:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+        digest[0], digest[1], digest[2], digest[3], digest[4]
+    )
+}
+
+/// Returns the SLAAC address of the interface with the given `mac` in
+/// [LOCAL_IPV6_PREFIX], following the modified EUI-64 format.
+fn ipv6_of_mac(mac: &str) -> Ipv6Addr {
+    let b: Vec<u8> = mac
+        .split(':')
+        .map(|byte| u8::from_str_radix(byte, 16).expect("valid MAC address"))
+        .collect();
+    let [p0, p1, p2, p3] = LOCAL_IPV6_PREFIX;
+    Ipv6Addr::new(
+        p0,
+        p1,
+        p2,
+        p3,
+        u16::from_be_bytes([b[0] ^ 0x02, b[1]]),
+        u16::from_be_bytes([b[2], 0xff]),
+        u16::from_be_bytes([0xfe, b[3]]),
+        u16::from_be_bytes([b[4], b[5]]),
+    )
+}
+
+/// Writes everything to both a file and a hasher.
+struct TeeWriter<'a>(&'a mut File, &'a mut Sha256);
+
+impl Write for TeeWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.0.write(buf)?;
+        self.1.write(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ipv6_follows_modified_eui64() {
+        assert_eq!(
+            ipv6_of_mac("02:1a:2b:3c:4d:5e"),
+            "fd00:1c::1a:2bff:fe3c:4d5e".parse::<Ipv6Addr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn base_url_roundtrips_state_dir_and_bridge() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("local backend #1");
+        let logger = Logger::root(slog::Discard, slog::o!());
+
+        let backend = LocalBackend::new(&local_backend_url(&dir, "br-test").unwrap(), logger);
+        assert_eq!(backend.state_dir, dir.canonicalize().unwrap());
+        assert_eq!(backend.bridge, "br-test");
+    }
+
+    #[test]
+    fn truncated_console_logs_are_detected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let logger = Logger::root(slog::Discard, slog::o!());
+        let url = local_backend_url(tmp.path(), DEFAULT_LOCAL_BRIDGE).unwrap();
+        let backend = LocalBackend::new(&url, logger);
+        let vm_dir = backend.vm_dir("group", "vm");
+        fs::create_dir_all(&vm_dir).unwrap();
+        fs::write(vm_dir.join(CONSOLE_LOG_FILE), "boot\nlogin:").unwrap();
+
+        assert_eq!(
+            backend.read_console_log("group", "vm", 5).unwrap(),
+            ConsoleLogRead::Appended(b"login:".to_vec())
+        );
+        fs::write(vm_dir.join(CONSOLE_LOG_FILE), "boot").unwrap();
+        assert_eq!(
+            backend.read_console_log("group", "vm", 11).unwrap(),
+            ConsoleLogRead::Truncated
+        );
+    }
+
+    #[test]
+    fn macs_are_locally_administered_and_unique() {
+        let mac = mac_of_vm("group", "vm1");
+        assert!(mac.starts_with("02:"));
+        assert_eq!(mac, mac_of_vm("group", "vm1"));
+        assert_ne!(mac, mac_of_vm("group", "vm2"));
+        assert_ne!(mac, mac_of_vm("other_group", "vm1"));
+    }
+}
@@ -1,30 +1,49 @@
 pub mod action_graph;
+pub mod auxiliary_services;
 pub mod bootstrap;
 pub mod boundary_node;
+pub mod colocate;
 pub mod config;
+pub mod console_log;
 pub mod constants;
 pub mod context;
 pub mod driver_setup;
 pub mod dsl;
 pub mod event;
+pub mod failure_artifacts;
 pub mod farm;
 pub mod group;
 pub mod ic;
+pub mod ingress_workload;
+pub mod keepalive;
+pub mod local_backend;
 pub mod logger;
+pub mod network_chaos;
+pub mod node_logs;
 pub mod node_software_version;
 pub mod plan;
 pub mod port_allocator;
 pub mod pot_dsl;
+pub mod preemption;
 pub mod process;
 pub mod prometheus_vm;
 pub mod report;
 pub mod resource;
+pub mod resource_usage;
+pub mod results;
+pub mod shard;
+pub mod ssh_exec;
 pub mod subprocess_ipc;
 pub mod subprocess_task;
+pub mod success_criteria;
 pub mod task;
 pub mod task_scheduler;
+pub mod test_dependencies;
 pub mod test_env;
 pub mod test_env_api;
 pub mod test_setup;
+pub mod testnet;
+pub mod testnet_description;
+pub mod testnet_snapshot;
 pub mod timeout;
 pub mod universal_vm;
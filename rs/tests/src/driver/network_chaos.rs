@@ -0,0 +1,381 @@
+//! Helpers to impair the network between IC nodes, such that resilience tests
+//! can declare the faults they need instead of shelling into the VMs:
+//!
+//! ```ignore
+//! let nodes: Vec<_> = subnet.nodes().collect();
+//! let (minority, majority) = nodes.split_at(nodes.len() / 3);
+//! partition(minority, majority)?;
+//! impair_network(majority, majority, &NetworkImpairment::new().with_packet_loss(5.0))?;
+//! // ...
+//! heal_network(&nodes)?;
+//! ```
+//!
+//! Partitions drop all packets between the two sides in an nftables table of
+//! its own. Impairments are applied to the egress traffic of the impaired nodes
+//! with a netem qdisc, which only the traffic to the given peers passes.
+//! Impairing a node again replaces all its previous impairments. A
+//! [LatencyMatrix] sets up the impairments of all nodes at once, such that each
+//! node delays its traffic to every other subnet differently.
+
+use std::{collections::BTreeMap, fmt::Write as _, net::IpAddr, thread, time::Duration};
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::driver::{
+    constants::DEVICE_NAME,
+    test_env_api::{IcNodeSnapshot, SshSession},
+};
+
+/// The nftables table holding the partition rules.
+const CHAOS_TABLE: &str = "systest_chaos";
+/// The priority of the partition chains, such that they run before the
+/// firewall of the node.
+const CHAOS_CHAIN_PRIORITY: i32 = -10;
+/// The first band of the prio qdisc holding an impairment. The bands below are
+/// used by the traffic that is not impaired.
+const FIRST_IMPAIRMENT_BAND: usize = 4;
+/// The prio qdisc supports at most 16 bands.
+pub const MAX_IMPAIRMENTS_PER_NODE: usize = 16 - FIRST_IMPAIRMENT_BAND + 1;
+
+/// How the traffic from a node to its peers is impaired.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NetworkImpairment {
+    pub latency: Option<Duration>,
+    pub jitter: Option<Duration>,
+    pub packet_loss_percent: Option<f64>,
+    pub bandwidth_mbit: Option<u64>,
+}
+
+impl NetworkImpairment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// Varies the latency uniformly by up to `jitter`.
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = Some(jitter);
+        self
+    }
+
+    pub fn with_packet_loss(mut self, percent: f64) -> Self {
+        self.packet_loss_percent = Some(percent);
+        self
+    }
+
+    pub fn with_bandwidth_limit(mut self, mbit: u64) -> Self {
+        self.bandwidth_mbit = Some(mbit);
+        self
+    }
+
+    fn netem_args(&self) -> String {
+        let mut args = String::new();
+        if self.latency.is_some() || self.jitter.is_some() {
+            let _ = write!(
+                args,
+                " delay {}ms",
+                self.latency.unwrap_or_default().as_millis()
+            );
+            if let Some(jitter) = self.jitter {
+                let _ = write!(args, " {}ms", jitter.as_millis());
+            }
+        }
+        if let Some(percent) = self.packet_loss_percent {
+            let _ = write!(args, " loss {}%", percent);
+        }
+        if let Some(mbit) = self.bandwidth_mbit {
+            let _ = write!(args, " rate {}mbit", mbit);
+        }
+        args
+    }
+}
+
+/// Drops all traffic between the nodes of `side_a` and the nodes of `side_b`.
+pub fn partition(side_a: &[IcNodeSnapshot], side_b: &[IcNodeSnapshot]) -> Result<()> {
+    let script = partition_script(&ips(side_b));
+    for_each_node(side_a, |_| script.clone())
+}
+
+/// Impairs the traffic from each node of `from` to the nodes of `to` as
+/// specified by `impairment`. The traffic to other nodes is not affected.
+pub fn impair_network(
+    from: &[IcNodeSnapshot],
+    to: &[IcNodeSnapshot],
+    impairment: &NetworkImpairment,
+) -> Result<()> {
+    let peers = ips(to);
+    for_each_node(from, |node| {
+        impairment_script(&[(without(&peers, node), impairment.clone())])
+    })
+}
+
+/// The one-way latencies between the subnets of an IC, where subnets are
+/// identified by their index in the order they were added to the IC. Traffic
+/// within a subnet is not delayed unless set explicitly.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LatencyMatrix {
+    latencies: BTreeMap<(usize, usize), NetworkImpairment>,
+}
+
+impl LatencyMatrix {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delays the traffic between the subnets `a` and `b` by `latency` in both
+    /// directions, i.e., the round-trip time is twice `latency`. The latency
+    /// varies uniformly by up to `jitter`.
+    pub fn with_latency(mut self, a: usize, b: usize, latency: Duration, jitter: Duration) -> Self {
+        let impairment = NetworkImpairment::new()
+            .with_latency(latency)
+            .with_jitter(jitter);
+        self.latencies.insert((a, b), impairment.clone());
+        self.latencies.insert((b, a), impairment);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.latencies.is_empty()
+    }
+
+    /// Checks that the matrix only refers to the first `subnets` subnets and
+    /// can be set up on the nodes.
+    pub fn validate(&self, subnets: usize) -> Result<()> {
+        if let Some((a, b)) = self
+            .latencies
+            .keys()
+            .find(|(a, b)| *a >= subnets || *b >= subnets)
+        {
+            bail!(
+                "The latency between the subnets {} and {} is set, but there are only {} subnets.",
+                a,
+                b,
+                subnets
+            );
+        }
+        for subnet in 0..subnets {
+            if self.impairments_of(subnet).count() > MAX_IMPAIRMENTS_PER_NODE {
+                bail!(
+                    "Subnet {} has latencies to more than {} subnets.",
+                    subnet,
+                    MAX_IMPAIRMENTS_PER_NODE
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn impairments_of(&self, subnet: usize) -> impl Iterator<Item = (usize, &NetworkImpairment)> {
+        self.latencies
+            .iter()
+            .filter(move |((from, _), _)| *from == subnet)
+            .map(|((_, to), impairment)| (*to, impairment))
+    }
+
+    /// Delays the traffic between the nodes of `subnets`, which are indexed as
+    /// in the matrix.
+    pub fn apply(&self, subnets: &[Vec<IcNodeSnapshot>]) -> Result<()> {
+        self.validate(subnets.len())?;
+        let subnet_ips: Vec<Vec<IpAddr>> = subnets.iter().map(|nodes| ips(nodes)).collect();
+        for (subnet, nodes) in subnets.iter().enumerate() {
+            if self.impairments_of(subnet).next().is_none() {
+                continue;
+            }
+            for_each_node(nodes, |node| {
+                let impairments: Vec<_> = self
+                    .impairments_of(subnet)
+                    .map(|(to, impairment)| (without(&subnet_ips[to], node), impairment.clone()))
+                    .collect();
+                impairment_script(&impairments)
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Removes all partitions and impairments of the given nodes.
+pub fn heal_network(nodes: &[IcNodeSnapshot]) -> Result<()> {
+    let script = heal_script();
+    for_each_node(nodes, |_| script.clone())
+}
+
+fn ips(nodes: &[IcNodeSnapshot]) -> Vec<IpAddr> {
+    nodes.iter().map(|node| node.get_ip_addr()).collect()
+}
+
+/// The IPs of `peers` other than the one of `node`.
+fn without(peers: &[IpAddr], node: &IcNodeSnapshot) -> Vec<IpAddr> {
+    let node_ip = node.get_ip_addr();
+    peers.iter().copied().filter(|ip| *ip != node_ip).collect()
+}
+
+/// Runs the script returned by `script` on all `nodes` at the same time, such
+/// that, e.g., both sides of a partition are cut off at (almost) once.
+fn for_each_node<F>(nodes: &[IcNodeSnapshot], script: F) -> Result<()>
+where
+    F: Fn(&IcNodeSnapshot) -> String + Sync,
+{
+    let script = &script;
+    thread::scope(|s| {
+        let handles: Vec<_> = nodes
+            .iter()
+            .map(|node| {
+                (
+                    node,
+                    s.spawn(move || node.block_on_bash_script(&script(node))),
+                )
+            })
+            .collect();
+        handles
+            .into_iter()
+            .try_for_each(|(node, handle)| match handle.join() {
+                Ok(Ok(_)) => Ok(()),
+                Ok(Err(e)) => Err(anyhow!(
+                    "Failed to change the network of node {}: {:?}",
+                    node.node_id,
+                    e
+                )),
+                Err(_) => Err(anyhow!(
+                    "Changing the network of node {} panicked",
+                    node.node_id
+                )),
+            })
+    })
+}
+
+fn partition_script(peers: &[IpAddr]) -> String {
+    let mut script = format!(
+        r#"set -euo pipefail
+sudo nft add table inet {table}
+sudo nft add chain inet {table} input '{{ type filter hook input priority {priority}; }}'
+sudo nft add chain inet {table} output '{{ type filter hook output priority {priority}; }}'
+"#,
+        table = CHAOS_TABLE,
+        priority = CHAOS_CHAIN_PRIORITY,
+    );
+    for peer in peers {
+        let family = match peer {
+            IpAddr::V4(_) => "ip",
+            IpAddr::V6(_) => "ip6",
+        };
+        let _ = writeln!(
+            script,
+            "sudo nft add rule inet {table} input {family} saddr {peer} drop
+sudo nft add rule inet {table} output {family} daddr {peer} drop",
+            table = CHAOS_TABLE,
+        );
+    }
+    script
+}
+
+/// Sets up one band of the prio qdisc with its own netem qdisc per impairment,
+/// which only the traffic to its peers passes.
+fn impairment_script(impairments: &[(Vec<IpAddr>, NetworkImpairment)]) -> String {
+    // The bands from FIRST_IMPAIRMENT_BAND on are only used by the traffic
+    // matched by the filters below, all other traffic keeps the default
+    // priority map.
+    let mut script = format!(
+        r#"set -euo pipefail
+sudo tc qdisc del dev {device} root 2> /dev/null || true
+sudo tc qdisc add dev {device} root handle 1: prio bands {bands} priomap 1 2 2 2 1 2 0 0 1 1 1 1 1 1 1 1
+"#,
+        device = DEVICE_NAME,
+        bands = FIRST_IMPAIRMENT_BAND - 1 + impairments.len().max(1),
+    );
+    for (i, (peers, impairment)) in impairments.iter().enumerate() {
+        // Class and qdisc ids are hexadecimal.
+        let band = FIRST_IMPAIRMENT_BAND + i;
+        let _ = writeln!(
+            script,
+            "sudo tc qdisc add dev {device} parent 1:{band:x} handle {band:x}0: netem{netem_args}",
+            device = DEVICE_NAME,
+            netem_args = impairment.netem_args(),
+        );
+        for peer in peers {
+            let (protocol, selector, prefix) = match peer {
+                IpAddr::V4(_) => ("ip", "ip dst", 32),
+                IpAddr::V6(_) => ("ipv6", "ip6 dst", 128),
+            };
+            let _ = writeln!(
+                script,
+                "sudo tc filter add dev {device} protocol {protocol} parent 1:0 prio 1 u32 match {selector} {peer}/{prefix} flowid 1:{band:x}",
+                device = DEVICE_NAME,
+            );
+        }
+    }
+    script
+}
+
+fn heal_script() -> String {
+    format!(
+        r#"set -euo pipefail
+sudo nft delete table inet {table} 2> /dev/null || true
+sudo tc qdisc del dev {device} root 2> /dev/null || true
+"#,
+        table = CHAOS_TABLE,
+        device = DEVICE_NAME,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn netem_args_contain_all_impairments() {
+        let impairment = NetworkImpairment::new()
+            .with_latency(Duration::from_millis(200))
+            .with_jitter(Duration::from_millis(20))
+            .with_packet_loss(2.5)
+            .with_bandwidth_limit(10);
+        assert_eq!(
+            impairment.netem_args(),
+            " delay 200ms 20ms loss 2.5% rate 10mbit"
+        );
+        assert_eq!(NetworkImpairment::new().netem_args(), "");
+    }
+
+    #[test]
+    fn scripts_only_affect_the_given_peers() {
+        let peers: Vec<IpAddr> = vec!["2001:db8::1".parse().unwrap()];
+        let script = impairment_script(&[(
+            peers.clone(),
+            NetworkImpairment::new().with_packet_loss(1.0),
+        )]);
+        assert!(script.contains("match ip6 dst 2001:db8::1/128 flowid 1:4"));
+        assert!(script.contains("netem loss 1%"));
+
+        let script = partition_script(&peers);
+        assert!(script.contains("input ip6 saddr 2001:db8::1 drop"));
+        assert!(script.contains("output ip6 daddr 2001:db8::1 drop"));
+    }
+
+    #[test]
+    fn latency_matrix_gives_each_subnet_its_own_band() {
+        let latency = |ms| Duration::from_millis(ms);
+        let matrix = LatencyMatrix::new()
+            .with_latency(0, 1, latency(40), latency(5))
+            .with_latency(0, 2, latency(120), latency(10));
+        assert!(matrix.validate(3).is_ok());
+        assert!(matrix.validate(2).is_err());
+        let impairments: Vec<_> = matrix
+            .impairments_of(0)
+            .map(|(to, impairment)| {
+                (
+                    vec![format!("2001:db8::{}", to).parse().unwrap()],
+                    impairment.clone(),
+                )
+            })
+            .collect();
+        let script = impairment_script(&impairments);
+        assert!(script.contains("prio bands 5 "));
+        assert!(script.contains("parent 1:4 handle 40: netem delay 40ms 5ms"));
+        assert!(script.contains("parent 1:5 handle 50: netem delay 120ms 10ms"));
+        assert!(script.contains("match ip6 dst 2001:db8::2/128 flowid 1:5"));
+        assert_eq!(matrix.impairments_of(1).count(), 1);
+    }
+}
@@ -0,0 +1,172 @@
+//! Searching the journal of IC nodes, such that tests asserting on log lines
+//! of the replica or the orchestrator do not need to shell into the nodes:
+//!
+//! ```ignore
+//! let cursor = node.journal_cursor()?;
+//! // ... trigger the behavior to be logged ...
+//! let lines = node.await_log_lines(
+//!     &LogQuery::new("is halted").of_process("replica").after(&cursor),
+//!     Duration::from_secs(120),
+//! )?;
+//! ```
+//!
+//! Patterns are extended regular expressions as understood by `grep -E` and
+//! are matched on the nodes, such that only the matching lines are transferred.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Result};
+use slog::info;
+
+use crate::driver::test_env_api::{retry, shell_quote, HasTestEnv, IcNodeSnapshot, SshSession};
+
+/// How long to wait between two searches while awaiting a log line.
+const AWAIT_LOG_LINES_BACKOFF: Duration = Duration::from_secs(5);
+
+/// A position in the journal of a node, such that only the lines logged after
+/// it are searched.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JournalCursor(String);
+
+/// A search for the lines of the journal of a node matching a pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogQuery {
+    pattern: String,
+    process: Option<String>,
+    since: Option<SystemTime>,
+    until: Option<SystemTime>,
+    after: Option<JournalCursor>,
+}
+
+impl LogQuery {
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            process: None,
+            since: None,
+            until: None,
+            after: None,
+        }
+    }
+
+    /// Only search the lines logged by the process with this name, e.g.,
+    /// `replica` or `orchestrator`.
+    pub fn of_process(mut self, process: &str) -> Self {
+        self.process = Some(process.to_string());
+        self
+    }
+
+    /// Only search the lines logged at or after `time`.
+    pub fn since(mut self, time: SystemTime) -> Self {
+        self.since = Some(time);
+        self
+    }
+
+    /// Only search the lines logged at or before `time`.
+    pub fn until(mut self, time: SystemTime) -> Self {
+        self.until = Some(time);
+        self
+    }
+
+    /// Only search the lines logged after `cursor`.
+    pub fn after(mut self, cursor: &JournalCursor) -> Self {
+        self.after = Some(cursor.clone());
+        self
+    }
+
+    /// The script printing the matching lines. As grep exits with 1 if no line
+    /// matches, only its exit code 2 and the ones of journalctl are errors.
+    fn script(&self) -> String {
+        let mut args = vec![];
+        if let Some(ref process) = self.process {
+            args.push(shell_quote(&format!("_COMM={}", process)));
+        }
+        if let Some(since) = self.since {
+            args.push(format!("--since=@{}", unix_secs(since)));
+        }
+        if let Some(until) = self.until {
+            args.push(format!("--until=@{}", unix_secs(until)));
+        }
+        if let Some(JournalCursor(ref cursor)) = self.after {
+            args.push(format!("--after-cursor={}", shell_quote(cursor)));
+        }
+        format!(
+            "set -o pipefail\njournalctl --no-pager --output=short-iso {} | {{ grep -E -e {} || test $? -eq 1; }}",
+            args.join(" "),
+            shell_quote(&self.pattern)
+        )
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .expect("time before the UNIX epoch")
+        .as_secs()
+}
+
+pub trait NodeLogs {
+    /// Returns the position of the latest line of the journal.
+    fn journal_cursor(&self) -> Result<JournalCursor>;
+
+    /// Returns the lines of the journal matching `query`.
+    fn find_log_lines(&self, query: &LogQuery) -> Result<Vec<String>>;
+
+    /// Searches the journal until a line matches `query` and returns the
+    /// matching lines, or fails after `timeout`.
+    fn await_log_lines(&self, query: &LogQuery, timeout: Duration) -> Result<Vec<String>>;
+}
+
+impl NodeLogs for IcNodeSnapshot {
+    fn journal_cursor(&self) -> Result<JournalCursor> {
+        let out = self.block_on_bash_script(
+            "journalctl --no-pager -n1 --output=json --output-fields=__CURSOR",
+        )?;
+        let entry: serde_json::Value = serde_json::from_str(out.trim())?;
+        entry["__CURSOR"]
+            .as_str()
+            .map(|cursor| JournalCursor(cursor.to_string()))
+            .ok_or_else(|| anyhow!("the journal entry {} has no cursor", out.trim()))
+    }
+
+    fn find_log_lines(&self, query: &LogQuery) -> Result<Vec<String>> {
+        let out = self.block_on_bash_script(&query.script())?;
+        Ok(out.lines().map(String::from).collect())
+    }
+
+    fn await_log_lines(&self, query: &LogQuery, timeout: Duration) -> Result<Vec<String>> {
+        let log = self.test_env().logger();
+        info!(
+            log,
+            "Awaiting log lines matching {:?} on node {} ...", query.pattern, self.node_id
+        );
+        retry(log, timeout, AWAIT_LOG_LINES_BACKOFF, || {
+            let lines = self.find_log_lines(query)?;
+            if lines.is_empty() {
+                bail!(
+                    "No log line of node {} matches {:?} yet",
+                    self.node_id,
+                    query.pattern
+                );
+            }
+            Ok(lines)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_applies_all_filters() {
+        let query = LogQuery::new("is halted|stalled")
+            .of_process("replica")
+            .since(UNIX_EPOCH + Duration::from_secs(1000))
+            .until(UNIX_EPOCH + Duration::from_secs(2000))
+            .after(&JournalCursor("s=1;i=2".to_string()));
+        assert_eq!(
+            query.script(),
+            "set -o pipefail\njournalctl --no-pager --output=short-iso '_COMM=replica' --since=@1000 --until=@2000 --after-cursor='s=1;i=2' | { grep -E -e 'is halted|stalled' || test $? -eq 1; }"
+        );
+    }
+}
@@ -13,3 +13,27 @@ pub struct NodeSoftwareVersion {
     pub orchestrator_url: Url,
     pub orchestrator_hash: String,
 }
+
+/// A replica version that subnets or unassigned nodes run initially. Nodes
+/// whose boot image has another version upgrade to it on boot using the
+/// release package.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InitialReplicaVersion {
+    pub replica_version: ReplicaVersion,
+    pub release_package_url: Url,
+    pub release_package_sha256_hex: String,
+}
+
+impl InitialReplicaVersion {
+    pub fn new(
+        replica_version: ReplicaVersion,
+        release_package_url: Url,
+        release_package_sha256_hex: String,
+    ) -> Self {
+        Self {
+            replica_version,
+            release_package_url,
+            release_package_sha256_hex,
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Re-allocating the VMs of a group allocated on preemptible Farm resources,
+//! see the `--preemptible-vms` flag of
+//! [SystemTestGroup](crate::driver::group::SystemTestGroup). Such resources are
+//! cheaper, but Farm might reclaim them at any time, e.g., during a long soak
+//! test.
+//!
+//! While the tests run, the driver checks the group for preempted VMs every
+//! [PREEMPTION_CHECK_INTERVAL], re-allocates them with their disk images and
+//! starts them again. A re-allocated IC node boots from its config image with
+//! its keys, catches up with its subnet and is awaited until it is healthy.
+
+use std::{thread, time::Duration};
+
+use anyhow::{bail, Result};
+use slog::{info, warn};
+
+use crate::driver::{
+    farm::Farm,
+    test_env::{HasIcPrepDir, TestEnv},
+    test_env_api::{HasPublicApiUrl, HasTopologySnapshot, IcNodeContainer, IcNodeSnapshot},
+};
+
+/// How often the group is checked for preempted VMs.
+pub const PREEMPTION_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Re-allocates and restarts the preempted VMs of the group and awaits the
+/// re-allocated IC nodes. Returns the names of the re-allocated VMs.
+pub fn reallocate_preempted_vms(
+    env: &TestEnv,
+    farm: &Farm,
+    group_name: &str,
+) -> Result<Vec<String>> {
+    let log = env.logger();
+    let preempted = farm.preempted_vms(group_name)?;
+    for vm_name in &preempted {
+        warn!(log, "VM {} was preempted, re-allocating it ...", vm_name);
+        let node = find_ic_node(env, vm_name);
+        let created_vm = farm.reallocate_vm(group_name, vm_name)?;
+        if let Some(ref node) = node {
+            // The registry refers to the nodes by their address.
+            if node.get_ip_addr() != created_vm.ipv6 {
+                bail!(
+                    "Node {} was re-allocated with the address {} instead of {}, it cannot re-join its subnet",
+                    vm_name,
+                    created_vm.ipv6,
+                    node.get_ip_addr()
+                );
+            }
+        }
+        farm.start_vm(group_name, vm_name)?;
+        if let Some(node) = node {
+            node.await_status_is_healthy()?;
+            info!(log, "Node {} re-joined after its preemption.", vm_name);
+        }
+    }
+    Ok(preempted)
+}
+
+/// Checks the group for preempted VMs every [PREEMPTION_CHECK_INTERVAL] and
+/// re-allocates them, as long as the test driver runs. Failures are only
+/// logged, as the tests using the VMs fail anyway.
+pub(crate) fn watch_preemptions(env: TestEnv, farm: Farm, group_name: String) {
+    info!(
+        env.logger(),
+        "Watching the preemptible VMs of group {} ...", group_name
+    );
+    thread::spawn(move || loop {
+        thread::sleep(PREEMPTION_CHECK_INTERVAL);
+        if let Err(e) = reallocate_preempted_vms(&env, &farm, &group_name) {
+            warn!(
+                env.logger(),
+                "Failed to re-allocate the preempted VMs of group {}: {:?}", group_name, e
+            );
+        }
+    });
+}
+
+/// The IC node deployed to the VM `vm_name`, whose name is the node ID.
+fn find_ic_node(env: &TestEnv, vm_name: &str) -> Option<IcNodeSnapshot> {
+    env.prep_dir("")?;
+    env.topology_snapshot()
+        .nodes()
+        .find(|node| node.node_id.to_string() == vm_name)
+}
@@ -5,9 +5,9 @@ use std::{
     time::Duration,
 };
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use reqwest::Url;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use slog::{debug, info};
 
@@ -54,6 +54,12 @@ const REPLICA_METRICS_PORT: u16 = 9090;
 const ORCHESTRATOR_METRICS_PORT: u16 = 9091;
 const NODE_EXPORTER_METRICS_PORT: u16 = 9100;
 
+/// The port of the HTTP API of Prometheus on the Prometheus VM.
+const PROMETHEUS_API_PORT: u16 = 9090;
+const PROMETHEUS_QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often [HasPrometheus::await_metric] evaluates its query.
+const AWAIT_METRIC_BACKOFF: Duration = Duration::from_secs(5);
+
 const PROMETHEUS_DOMAIN_NAME: &str = "prometheus";
 const GRAFANA_DOMAIN_NAME: &str = "grafana";
 
@@ -190,6 +196,36 @@ pub trait HasPrometheus {
     /// This allows this function to be used in a finalizer where no prometheus
     /// server has been setup.
     fn download_prometheus_data_dir_if_exists(&self);
+
+    /// Evaluates the PromQL `query` on the Prometheus VM and returns the
+    /// samples of the resulting instant vector.
+    fn query_prometheus(&self, query: &str) -> Result<Vec<PrometheusSample>>;
+
+    /// Evaluates the PromQL `query` until `predicate` holds for the resulting
+    /// samples and returns them, or fails after `timeout`, e.g.:
+    ///
+    /// ```ignore
+    /// env.await_metric(
+    ///     r#"rate(consensus_batch_height[1m])"#,
+    ///     |samples| !samples.is_empty() && samples.iter().all(|s| s.value > 0.5),
+    ///     Duration::from_secs(5 * 60),
+    /// )?;
+    /// ```
+    fn await_metric<P>(
+        &self,
+        query: &str,
+        predicate: P,
+        timeout: Duration,
+    ) -> Result<Vec<PrometheusSample>>
+    where
+        P: Fn(&[PrometheusSample]) -> bool;
+}
+
+/// A sample of the instant vector resulting from a PromQL query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrometheusSample {
+    pub labels: HashMap<String, String>,
+    pub value: f64,
 }
 
 impl HasPrometheus for TestEnv {
@@ -286,6 +322,91 @@ sudo tar -cf "{tarball_full_path:?}" \
             "Failed to write the tarball of prometheus data directory {vm_name}:{tarball_full_path:?} to {destination:?}",
         );
     }
+
+    fn query_prometheus(&self, query: &str) -> Result<Vec<PrometheusSample>> {
+        let prometheus_vm = self
+            .get_deployed_universal_vm(PROMETHEUS_VM_NAME)?
+            .get_vm()?;
+        let url = format!(
+            "http://[{}]:{}/api/v1/query",
+            prometheus_vm.ipv6, PROMETHEUS_API_PORT
+        );
+        let body = reqwest::blocking::Client::builder()
+            .timeout(PROMETHEUS_QUERY_TIMEOUT)
+            .build()?
+            .get(url)
+            .query(&[("query", query)])
+            .send()?
+            .text()?;
+        parse_query_response(&body)
+    }
+
+    fn await_metric<P>(
+        &self,
+        query: &str,
+        predicate: P,
+        timeout: Duration,
+    ) -> Result<Vec<PrometheusSample>>
+    where
+        P: Fn(&[PrometheusSample]) -> bool,
+    {
+        info!(self.logger(), "Awaiting metric {query} ...");
+        retry(self.logger(), timeout, AWAIT_METRIC_BACKOFF, || {
+            let samples = self.query_prometheus(query)?;
+            if !predicate(&samples) {
+                bail!("Predicate does not hold for the samples {samples:?} of {query}");
+            }
+            Ok(samples)
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct QueryResponse {
+    status: String,
+    data: Option<QueryData>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct QueryData {
+    #[serde(rename = "resultType")]
+    result_type: String,
+    result: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct VectorSample {
+    metric: HashMap<String, String>,
+    /// The timestamp and the value of the sample.
+    value: (f64, String),
+}
+
+fn parse_query_response(body: &str) -> Result<Vec<PrometheusSample>> {
+    let response: QueryResponse = serde_json::from_str(body)?;
+    let data = match response.data {
+        Some(data) if response.status == "success" => data,
+        _ => bail!(
+            "Prometheus query failed: {}",
+            response.error.unwrap_or(response.status)
+        ),
+    };
+    if data.result_type != "vector" {
+        bail!(
+            "Expected an instant vector, but the query resulted in a {}",
+            data.result_type
+        );
+    }
+    let samples: Vec<VectorSample> = serde_json::from_value(data.result)?;
+    samples
+        .into_iter()
+        .map(|sample| -> Result<PrometheusSample> {
+            Ok(PrometheusSample {
+                labels: sample.metric,
+                value: sample.value.1.parse()?,
+            })
+        })
+        .collect()
 }
 
 #[derive(Serialize)]
@@ -389,3 +510,31 @@ fn sync_prometheus_config_dir(
 fn scraping_target_url(node: &IcNodeSnapshot, port: u16) -> String {
     format!("[{:?}]:{:?}", node.get_ip_addr(), port)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_responses_are_parsed() {
+        let samples = parse_query_response(
+            r#"{"status":"success","data":{"resultType":"vector","result":[
+                {"metric":{"ic_node":"a"},"value":[1681462800.123,"0.5"]},
+                {"metric":{"ic_node":"b"},"value":[1681462800.123,"NaN"]}]}}"#,
+        )
+        .unwrap();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].labels["ic_node"], "a");
+        assert_eq!(samples[0].value, 0.5);
+        assert!(samples[1].value.is_nan());
+
+        assert!(parse_query_response(
+            r#"{"status":"error","errorType":"bad_data","error":"parse error"}"#
+        )
+        .is_err());
+        assert!(parse_query_response(
+            r#"{"status":"success","data":{"resultType":"scalar","result":[1681462800.123,"1"]}}"#
+        )
+        .is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use std::{
     collections::{BTreeMap, BTreeSet, HashSet},
     fmt::{Display, Formatter, Result},
+    path::PathBuf,
     time::{Duration, Instant},
 };
 
@@ -9,6 +10,8 @@ use serde::{Deserialize, Serialize};
 use crate::driver::test_setup::GroupSetup;
 
 use crate::driver::event::TaskId;
+use crate::driver::resource_usage::{TestResourceUsage, UsageSample};
+use crate::driver::results::{AttemptStats, SystemTestGroupResults, TestResult, TestStatus};
 
 pub trait TargetFunctionOutcome {
     fn task_id(&self) -> TaskId;
@@ -22,6 +25,7 @@ pub trait TargetFunctionOutcome {
             name: self.task_id().to_string(),
             runtime: self.runtime_duration().as_secs_f64(),
             message: self.message(),
+            retries: 0,
         }
     }
 }
@@ -79,6 +83,7 @@ fn fmt_succs(
     succs: &[TargetFunctionSuccess],
     skips: &HashSet<TaskId>,
     sub_reports: &BTreeMap<TaskId, String>,
+    retries: &BTreeMap<TaskId, usize>,
     f: &mut Formatter<'_>,
     min_width: usize,
 ) -> Result {
@@ -89,13 +94,18 @@ fn fmt_succs(
         } else {
             "".to_string()
         };
+        let retried = match retries.get(&tid) {
+            Some(n) => format!(" on retry {}", n),
+            None => "".to_string(),
+        };
         let print_result = if skips.contains(&success.task_id) {
             format!("Test {:<min_width$}  SKIPPED {}", tid.name(), sub_report)
         } else {
             format!(
-                "Test {:<min_width$}  PASSED in {:>6.2}s{}",
+                "Test {:<min_width$}  PASSED in {:>6.2}s{}{}",
                 tid.name(),
                 success.runtime.as_secs_f64(),
+                retried,
                 sub_report
             )
         };
@@ -152,6 +162,16 @@ pub struct SystemTestGroupReport {
 
     sub_reports: BTreeMap<TaskId, String>,
 
+    // the number of retries of each test that was retried after a failure
+    retries: BTreeMap<TaskId, usize>,
+
+    // the times at which the failed attempts of each retried test ended
+    retry_times: BTreeMap<TaskId, Vec<Instant>>,
+
+    // the Farm resources consumed while each test ran, and by the whole group
+    resource_usage: BTreeMap<TaskId, TestResourceUsage>,
+    total_resource_usage: Option<TestResourceUsage>,
+
     pub farm_group_report: Option<FarmGroupReport>,
 }
 
@@ -287,15 +307,142 @@ impl SystemTestGroupReport {
         }
     }
 
+    pub fn set_test_retried(&mut self, test_id: TaskId, attempt: usize) {
+        self.retry_times
+            .entry(test_id.clone())
+            .or_default()
+            .push(Instant::now());
+        self.retries.insert(test_id, attempt);
+    }
+
+    /// Returns the number of retries needed by the given test, which is zero
+    /// for tests that passed or failed on the first attempt.
+    pub fn get_test_retries(&self, test_id: &TaskId) -> usize {
+        self.retries.get(test_id).copied().unwrap_or_default()
+    }
+
+    /// Returns the durations of the attempts of the given test that ended,
+    /// where each retry started once the previous attempt failed.
+    pub fn get_test_attempt_durations(&self, test_id: &TaskId) -> Vec<Duration> {
+        let start = match self.start_times.get(test_id) {
+            Some(start) => *start,
+            None => return vec![],
+        };
+        let ends = self
+            .retry_times
+            .get(test_id)
+            .into_iter()
+            .flatten()
+            .chain(self.end_times.get(test_id));
+        let mut attempt_start = start;
+        ends.map(|end| {
+            let duration = end.duration_since(attempt_start);
+            attempt_start = *end;
+            duration
+        })
+        .collect()
+    }
+
+    pub fn get_test_attempt_stats(&self, test_id: &TaskId, passed: bool) -> AttemptStats {
+        AttemptStats::new(
+            self.get_test_retries(test_id),
+            &self.get_test_attempt_durations(test_id),
+            passed,
+        )
+    }
+
+    /// Derives the resources consumed by each test that ran, and by the group
+    /// since the first sample, from the `samples` of the Farm group.
+    pub fn set_resource_usage(&mut self, samples: &[UsageSample]) {
+        for (test_id, start) in &self.start_times {
+            if let Some(end) = self.end_times.get(test_id) {
+                self.resource_usage.insert(
+                    test_id.clone(),
+                    TestResourceUsage::from_samples(samples, *start, *end),
+                );
+            }
+        }
+        self.total_resource_usage = samples
+            .first()
+            .map(|first| TestResourceUsage::from_samples(samples, first.time, Instant::now()));
+    }
+
+    pub fn get_test_resource_usage(&self, test_id: &TaskId) -> Option<TestResourceUsage> {
+        self.resource_usage.get(test_id).copied()
+    }
+
+    /// The VM-hours consumed by the group, or 0 if its VMs were not sampled.
+    pub fn total_vm_hours(&self) -> f64 {
+        self.total_resource_usage
+            .map(|usage| usage.vm_hours)
+            .unwrap_or_default()
+    }
+
+    /// Returns the per-test results of the group `group`, listing the
+    /// directories returned by `artifacts` for each test.
+    pub fn to_results(
+        &self,
+        group: &str,
+        seed: Option<u64>,
+        artifacts: impl Fn(&TaskId, usize) -> Vec<PathBuf>,
+    ) -> SystemTestGroupResults {
+        let result = |outcome: &dyn TargetFunctionOutcome, status: TestStatus| {
+            let task_id = outcome.task_id();
+            let retries = self.get_test_retries(&task_id);
+            TestResult {
+                name: task_id.name(),
+                status,
+                duration_secs: outcome.runtime_duration().as_secs_f64(),
+                retries,
+                message: match status {
+                    // Skipped tests report the reason for skipping them, if any.
+                    TestStatus::Skipped => self.sub_reports.get(&task_id).cloned(),
+                    _ => outcome.message(),
+                },
+                artifacts: artifacts(&task_id, retries),
+                attempt_stats: match status {
+                    TestStatus::Skipped => AttemptStats::default(),
+                    _ => self.get_test_attempt_stats(&task_id, status == TestStatus::Passed),
+                },
+                resource_usage: self.get_test_resource_usage(&task_id),
+            }
+        };
+        let successes = self.successes.iter().map(|x| {
+            if self.skips.contains(&x.task_id()) {
+                result(x, TestStatus::Skipped)
+            } else {
+                result(x, TestStatus::Passed)
+            }
+        });
+        let failures = self.failures.iter().map(|x| match x {
+            TargetFunctionFailure::Panicked { .. } => result(x, TestStatus::Failed),
+            TargetFunctionFailure::TimedOut { .. } => result(x, TestStatus::TimedOut),
+        });
+        SystemTestGroupResults {
+            group: group.to_string(),
+            seed,
+            shard: None,
+            tests: successes.chain(failures).collect(),
+        }
+    }
+
     pub fn to_summary(&self) -> SystemTestGroupReportSummary {
+        let with_retries = |summary: TestResultSummary, test_id: TaskId| TestResultSummary {
+            retries: self.get_test_retries(&test_id),
+            ..summary
+        };
         SystemTestGroupReportSummary {
             success: self
                 .successes
                 .iter()
                 .filter(|x| !self.skips.contains(&x.task_id()))
-                .map(|x| x.to_summary())
+                .map(|x| with_retries(x.to_summary(), x.task_id()))
+                .collect(),
+            failure: self
+                .failures
+                .iter()
+                .map(|x| with_retries(x.to_summary(), x.task_id()))
                 .collect(),
-            failure: self.failures.iter().map(|x| x.to_summary()).collect(),
             skipped: self
                 .successes
                 .iter()
@@ -324,6 +471,10 @@ pub struct TestResultSummary {
     pub name: String,
     pub runtime: f64,
     pub message: Option<String>,
+    /// The number of retries after which the test passed or finally failed.
+    /// A test that passed with a non-zero number of retries is flaky.
+    #[serde(default)]
+    pub retries: usize,
 }
 
 fn compute_min_width<T>(xs: &[T]) -> Option<usize>
@@ -348,7 +499,15 @@ impl Display for SystemTestGroupReport {
             .and(if self.failures.is_empty() && self.successes.is_empty() {
                 writeln!(f, "No test outcomes were reported.")
             } else if self.failures.is_empty() {
-                fmt_succs(&self.successes, &self.skips, &self.sub_reports, f, w).and(writeln!(
+                fmt_succs(
+                    &self.successes,
+                    &self.skips,
+                    &self.sub_reports,
+                    &self.retries,
+                    f,
+                    w,
+                )
+                .and(writeln!(
                     f,
                     "{:.^table_width$}",
                     format!(
@@ -364,31 +523,117 @@ impl Display for SystemTestGroupReport {
                     format!(" All {} tests failed ", self.failures.len())
                 ))
             } else {
-                fmt_succs(&self.successes, &self.skips, &self.sub_reports, f, w)
-                    .and(writeln!(
-                        f,
-                        "{:.^table_width$}",
-                        format!(
-                            " Tests passed: {:>2} ",
-                            self.successes.len() - self.skips.len()
-                        )
-                    ))
-                    .and(writeln!(
-                        f,
-                        "{:.^table_width$}",
-                        format!(" Tests skipped: {:>2} ", self.skips.len())
-                    ))
-                    .and(fmt_fails(&self.failures, f, w))
-                    .and(writeln!(
-                        f,
-                        "{:.^table_width$}",
-                        format!(" Tests failed: {:>2} ", self.failures.len())
-                    ))
+                fmt_succs(
+                    &self.successes,
+                    &self.skips,
+                    &self.sub_reports,
+                    &self.retries,
+                    f,
+                    w,
+                )
+                .and(writeln!(
+                    f,
+                    "{:.^table_width$}",
+                    format!(
+                        " Tests passed: {:>2} ",
+                        self.successes.len() - self.skips.len()
+                    )
+                ))
+                .and(writeln!(
+                    f,
+                    "{:.^table_width$}",
+                    format!(" Tests skipped: {:>2} ", self.skips.len())
+                ))
+                .and(fmt_fails(&self.failures, f, w))
+                .and(writeln!(
+                    f,
+                    "{:.^table_width$}",
+                    format!(" Tests failed: {:>2} ", self.failures.len())
+                ))
             })
+            .and(self.fmt_flaky(f, w, table_width))
+            .and(self.fmt_resource_usage(f, w, table_width))
             .and(write!(f, "{:=^table_width$}", ""))
     }
 }
 
+impl SystemTestGroupReport {
+    /// Lists the tests that passed after failed attempts.
+    fn fmt_flaky(&self, f: &mut Formatter<'_>, min_width: usize, table_width: usize) -> Result {
+        let flaky: Vec<(TaskId, AttemptStats)> = self
+            .successes
+            .iter()
+            .filter(|x| !self.skips.contains(&x.task_id()))
+            .map(|x| (x.task_id(), self.get_test_attempt_stats(&x.task_id(), true)))
+            .filter(|(_, stats)| stats.flaky)
+            .collect();
+        if flaky.is_empty() {
+            return Ok(());
+        }
+        flaky
+            .iter()
+            .fold(write!(f, ""), |acc, (task_id, stats)| {
+                acc.and(writeln!(
+                    f,
+                    "Test {:<min_width$}   FLAKY passed {} of {} attempts, {:.2}s \u{b1} {:.2}s per attempt",
+                    task_id.name(),
+                    stats.passes,
+                    stats.attempts,
+                    stats.mean_duration_secs,
+                    stats.stddev_duration_secs
+                ))
+            })
+            .and(writeln!(
+                f,
+                "{:.^table_width$}",
+                format!(" Flaky tests: {:>2} ", flaky.len())
+            ))
+    }
+}
+
+impl SystemTestGroupReport {
+    /// Lists the resources consumed by the tests that ran, the most expensive
+    /// ones first.
+    fn fmt_resource_usage(
+        &self,
+        f: &mut Formatter<'_>,
+        min_width: usize,
+        table_width: usize,
+    ) -> Result {
+        let total = match self.total_resource_usage {
+            Some(total) => total,
+            None => return Ok(()),
+        };
+        let mut usages: Vec<(TaskId, TestResourceUsage)> = self
+            .successes
+            .iter()
+            .map(|x| x.task_id())
+            .chain(self.failures.iter().map(|x| x.task_id()))
+            .filter_map(|task_id| Some((task_id.clone(), self.get_test_resource_usage(&task_id)?)))
+            .collect();
+        usages.sort_by(|(_, a), (_, b)| b.vm_hours.total_cmp(&a.vm_hours));
+        usages
+            .iter()
+            .fold(write!(f, ""), |acc, (task_id, usage)| {
+                acc.and(writeln!(
+                    f,
+                    "Test {:<min_width$}   USAGE {:>6.2} VM-hours in {:.2}s, peak {} VMs, {} vCPUs, {:.1} GiB",
+                    task_id.name(),
+                    usage.vm_hours,
+                    usage.wall_time_secs,
+                    usage.peak.vms,
+                    usage.peak.vcpus,
+                    usage.peak.memory_kibibytes as f64 / (1024.0 * 1024.0)
+                ))
+            })
+            .and(writeln!(
+                f,
+                "{:.^table_width$}",
+                format!(" VM-hours: {:.2} ", total.vm_hours)
+            ))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum SystemTestGroupError {
     TestDriverError {
@@ -157,13 +157,17 @@ pub fn get_resource_request(
     let pot_setup = GroupSetup::read_attribute(test_env);
     let default_vm_resources = pot_setup.default_vm_resources;
     res_req.group_name = group_name.to_string();
+    // Nodes fall back to the resources of their subnet, then to the ones of
+    // the IC and finally to the ones of the group.
+    let ic_vm_resources = config.default_vm_resources.or(default_vm_resources);
     for s in &config.subnets {
+        let subnet_vm_resources = s.default_vm_resources.or(Some(ic_vm_resources));
         for n in &s.nodes {
-            res_req.add_vm_request(vm_spec_from_node(n, default_vm_resources));
+            res_req.add_vm_request(vm_spec_from_node(n, Some(subnet_vm_resources)));
         }
     }
     for n in &config.unassigned_nodes {
-        res_req.add_vm_request(vm_spec_from_node(n, default_vm_resources));
+        res_req.add_vm_request(vm_spec_from_node(n, Some(ic_vm_resources)));
     }
     Ok(res_req)
 }
@@ -190,29 +194,15 @@ pub fn get_resource_request_for_universal_vm(
         primary_image.sha256,
     );
     res_req.group_name = group_name.to_string();
-    let vm_resources = universal_vm.vm_resources;
+    let vm_resources = universal_vm.vm_resources.or(pot_setup.default_vm_resources);
     res_req.add_vm_request(VmSpec {
         name: universal_vm.name.clone(),
-        vcpus: vm_resources.vcpus.unwrap_or_else(|| {
-            pot_setup
-                .default_vm_resources
-                .and_then(|vm_resources| vm_resources.vcpus)
-                .unwrap_or(DEFAULT_VCPUS_PER_VM)
-        }),
-        memory_kibibytes: vm_resources.memory_kibibytes.unwrap_or_else(|| {
-            pot_setup
-                .default_vm_resources
-                .and_then(|vm_resources| vm_resources.memory_kibibytes)
-                .unwrap_or(DEFAULT_MEMORY_KIB_PER_VM)
-        }),
+        vcpus: vm_resources.vcpus.unwrap_or(DEFAULT_VCPUS_PER_VM),
+        memory_kibibytes: vm_resources
+            .memory_kibibytes
+            .unwrap_or(DEFAULT_MEMORY_KIB_PER_VM),
         boot_image: BootImage::GroupDefault,
-        boot_image_minimal_size_gibibytes: vm_resources.boot_image_minimal_size_gibibytes.or_else(
-            || {
-                pot_setup
-                    .default_vm_resources
-                    .and_then(|vm_resources| vm_resources.boot_image_minimal_size_gibibytes)
-            },
-        ),
+        boot_image_minimal_size_gibibytes: vm_resources.boot_image_minimal_size_gibibytes,
         has_ipv4: universal_vm.has_ipv4,
         vm_allocation: universal_vm.vm_allocation.clone(),
         required_host_features: universal_vm.required_host_features.clone(),
@@ -252,26 +242,15 @@ pub fn allocate_resources(farm: &Farm, req: &ResourceRequest) -> FarmResult<Reso
 }
 
 fn vm_spec_from_node(n: &Node, default_vm_resources: Option<VmResources>) -> VmSpec {
-    let vm_resources = &n.vm_resources;
+    let vm_resources = n.vm_resources.or(default_vm_resources);
     VmSpec {
         name: n.id().to_string(),
-        vcpus: vm_resources.vcpus.unwrap_or_else(|| {
-            default_vm_resources
-                .and_then(|vm_resources| vm_resources.vcpus)
-                .unwrap_or(DEFAULT_VCPUS_PER_VM)
-        }),
-        memory_kibibytes: vm_resources.memory_kibibytes.unwrap_or_else(|| {
-            default_vm_resources
-                .and_then(|vm_resources| vm_resources.memory_kibibytes)
-                .unwrap_or(DEFAULT_MEMORY_KIB_PER_VM)
-        }),
+        vcpus: vm_resources.vcpus.unwrap_or(DEFAULT_VCPUS_PER_VM),
+        memory_kibibytes: vm_resources
+            .memory_kibibytes
+            .unwrap_or(DEFAULT_MEMORY_KIB_PER_VM),
         boot_image: BootImage::GroupDefault,
-        boot_image_minimal_size_gibibytes: vm_resources.boot_image_minimal_size_gibibytes.or_else(
-            || {
-                default_vm_resources
-                    .and_then(|vm_resources| vm_resources.boot_image_minimal_size_gibibytes)
-            },
-        ),
+        boot_image_minimal_size_gibibytes: vm_resources.boot_image_minimal_size_gibibytes,
         has_ipv4: false,
         vm_allocation: n.vm_allocation.clone(),
         required_host_features: n.required_host_features.clone(),
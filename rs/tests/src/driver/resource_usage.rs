@@ -0,0 +1,170 @@
+//! The Farm resources consumed by the tests of a
+//! [SystemTestGroup](crate::driver::group::SystemTestGroup), e.g., to find the
+//! most expensive tests of a suite or to enforce its budget with
+//! `--vm-hours-budget`.
+//!
+//! While the tests run, the driver samples the VMs of the Farm group every
+//! [USAGE_SAMPLING_INTERVAL]. The usage of a test is derived from the samples
+//! taken while it ran: the VM-hours consumed by the group in that time and the
+//! peak number of VMs, vCPUs and memory allocated. As the tests of a group
+//! share its VMs, concurrently running tests are attributed the same usage.
+
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossbeam_channel::{Receiver, RecvTimeoutError};
+use serde::{Deserialize, Serialize};
+use slog::{debug, warn};
+
+use crate::driver::{
+    context::GroupContext, farm::Farm, test_env_api::HasIcDependencies, test_setup::GroupSetup,
+};
+
+/// How often the VMs of the group are sampled.
+pub const USAGE_SAMPLING_INTERVAL: Duration = Duration::from_secs(60);
+
+const SECS_PER_HOUR: f64 = 3600.0;
+
+/// The resources allocated to the VMs of a group at some point in time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    pub vms: u64,
+    pub vcpus: u64,
+    pub memory_kibibytes: u64,
+}
+
+impl ResourceUsage {
+    /// The component-wise maximum of both usages.
+    fn max(self, other: Self) -> Self {
+        Self {
+            vms: self.vms.max(other.vms),
+            vcpus: self.vcpus.max(other.vcpus),
+            memory_kibibytes: self.memory_kibibytes.max(other.memory_kibibytes),
+        }
+    }
+}
+
+/// The usage sampled at `time`, which holds until the next sample.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UsageSample {
+    pub time: Instant,
+    pub usage: ResourceUsage,
+}
+
+/// The resources consumed while a test ran.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TestResourceUsage {
+    pub wall_time_secs: f64,
+    pub vm_hours: f64,
+    pub peak: ResourceUsage,
+}
+
+impl TestResourceUsage {
+    /// The usage between `start` and `end` according to the `samples`, which
+    /// are ordered by time. Nothing is consumed before the first sample.
+    pub fn from_samples(samples: &[UsageSample], start: Instant, end: Instant) -> Self {
+        let mut usage = Self {
+            wall_time_secs: end.saturating_duration_since(start).as_secs_f64(),
+            ..Default::default()
+        };
+        for (i, sample) in samples.iter().enumerate() {
+            let next = samples.get(i + 1).map(|next| next.time);
+            if sample.time >= end || next.map_or(false, |next| next <= start) {
+                continue;
+            }
+            let from = sample.time.max(start);
+            let until = next.map_or(end, |next| next.min(end));
+            let overlap = until.saturating_duration_since(from);
+            usage.vm_hours += sample.usage.vms as f64 * overlap.as_secs_f64() / SECS_PER_HOUR;
+            usage.peak = usage.peak.max(sample.usage);
+        }
+        usage
+    }
+}
+
+/// The samples taken so far, shared with the sampling thread.
+#[derive(Clone, Debug, Default)]
+pub struct UsageSamples(Arc<Mutex<Vec<UsageSample>>>);
+
+impl UsageSamples {
+    pub fn get(&self) -> Vec<UsageSample> {
+        self.0.lock().unwrap().clone()
+    }
+
+    fn push(&self, sample: UsageSample) {
+        self.0.lock().unwrap().push(sample)
+    }
+}
+
+/// Samples the VMs of the Farm group every [USAGE_SAMPLING_INTERVAL] until
+/// `stop` is dropped, starting once the group was created. Failed samples are
+/// logged and skipped, such that the last sample holds until the next one.
+pub(crate) fn sample_usage(
+    ctx: GroupContext,
+    samples: UsageSamples,
+    stop: Receiver<()>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || loop {
+        let group = ctx.get_setup_env().and_then(|env| {
+            let group_setup = GroupSetup::try_read_attribute(&env)?;
+            let farm = Farm::new(env.get_farm_url()?, env.logger());
+            Ok((farm, group_setup.farm_group_name))
+        });
+        if let Ok((farm, group_name)) = group {
+            match farm.group_usage(&group_name) {
+                Ok(usage) => {
+                    debug!(ctx.log(), "Group {} uses {:?}", group_name, usage);
+                    samples.push(UsageSample {
+                        time: Instant::now(),
+                        usage,
+                    });
+                }
+                Err(e) => warn!(
+                    ctx.log(),
+                    "Failed to sample the resources of group {}: {:?}", group_name, e
+                ),
+            }
+        }
+        if let Err(RecvTimeoutError::Disconnected) = stop.recv_timeout(USAGE_SAMPLING_INTERVAL) {
+            break;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_is_integrated_over_the_samples_within_the_test() {
+        let t0 = Instant::now();
+        let at = |mins: u64| t0 + Duration::from_secs(60 * mins);
+        let sample = |mins, vms| UsageSample {
+            time: at(mins),
+            usage: ResourceUsage {
+                vms,
+                vcpus: 6 * vms,
+                memory_kibibytes: 25165824 * vms,
+            },
+        };
+        let samples = [sample(0, 4), sample(30, 8), sample(60, 2), sample(120, 0)];
+
+        // 15 minutes with 4 VMs and 30 minutes with 8 VMs.
+        let usage = TestResourceUsage::from_samples(&samples, at(15), at(60));
+        assert_eq!(usage.wall_time_secs, 45.0 * 60.0);
+        assert_eq!(usage.vm_hours, 1.0 + 4.0);
+        assert_eq!(usage.peak, sample(30, 8).usage);
+
+        // The last sample holds until the end of the test.
+        let usage = TestResourceUsage::from_samples(&samples, at(90), at(150));
+        assert_eq!(usage.vm_hours, 1.0);
+        assert_eq!(usage.peak, sample(60, 2).usage);
+
+        let usage = TestResourceUsage::from_samples(&[], at(0), at(60));
+        assert_eq!(usage.vm_hours, 0.0);
+        assert_eq!(usage.peak, ResourceUsage::default());
+    }
+}
@@ -0,0 +1,406 @@
+//! Machine-readable results of a [SystemTestGroup](crate::driver::group::SystemTestGroup)
+//! run, written as JUnit XML and as JSON, such that CI systems and dashboards
+//! can ingest them without parsing the logs of the test driver.
+
+use std::{
+    fmt::Write as _,
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::driver::{group::SUITE_SEPARATOR, resource_usage::TestResourceUsage, shard::Shard};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    TimedOut,
+    Skipped,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestResult {
+    pub name: String,
+    pub status: TestStatus,
+    pub duration_secs: f64,
+    /// The number of retries after which the test passed or finally failed.
+    pub retries: usize,
+    /// The panic message of a failed test, or the reason for skipping a test.
+    pub message: Option<String>,
+    /// The environment directories of the test, including the ones of failed
+    /// attempts that were retried.
+    pub artifacts: Vec<PathBuf>,
+    #[serde(default)]
+    pub attempt_stats: AttemptStats,
+    /// The Farm resources consumed while the test ran, if they were sampled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_usage: Option<TestResourceUsage>,
+}
+
+/// The outcomes and durations of the attempts of a test, of which there are
+/// more than one if the test was retried after failures.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AttemptStats {
+    pub attempts: usize,
+    pub passes: usize,
+    pub failures: usize,
+    /// A test is flaky if it passed after failed attempts.
+    pub flaky: bool,
+    pub durations_secs: Vec<f64>,
+    pub mean_duration_secs: f64,
+    /// The standard deviation of the durations of the attempts.
+    pub stddev_duration_secs: f64,
+}
+
+impl AttemptStats {
+    /// The stats of a test that ran `retries + 1` times, where only the last
+    /// attempt can have `passed`, as passed tests are not retried.
+    pub fn new(retries: usize, durations: &[Duration], passed: bool) -> Self {
+        let attempts = retries + 1;
+        let passes = usize::from(passed);
+        let durations_secs: Vec<f64> = durations.iter().map(Duration::as_secs_f64).collect();
+        let (mean, variance) = if durations_secs.is_empty() {
+            (0.0, 0.0)
+        } else {
+            let n = durations_secs.len() as f64;
+            let mean = durations_secs.iter().sum::<f64>() / n;
+            let variance = durations_secs
+                .iter()
+                .map(|d| (d - mean).powi(2))
+                .sum::<f64>()
+                / n;
+            (mean, variance)
+        };
+        Self {
+            attempts,
+            passes,
+            failures: attempts - passes,
+            flaky: passed && retries > 0,
+            durations_secs,
+            mean_duration_secs: mean,
+            stddev_duration_secs: variance.sqrt(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SystemTestGroupResults {
+    pub group: String,
+    /// The seed of the run, see [TestSeed](crate::driver::test_env::TestSeed).
+    pub seed: Option<u64>,
+    /// The shard of the group whose tests ran, if the group was sharded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shard: Option<Shard>,
+    pub tests: Vec<TestResult>,
+}
+
+impl SystemTestGroupResults {
+    pub fn count(&self, status: TestStatus) -> usize {
+        self.tests.iter().filter(|t| t.status == status).count()
+    }
+
+    /// The tests that passed after failed attempts, e.g., to quarantine them.
+    pub fn flaky_tests(&self) -> impl Iterator<Item = &TestResult> {
+        self.tests.iter().filter(|t| t.attempt_stats.flaky)
+    }
+
+    pub fn to_junit_xml(&self) -> String {
+        let total_secs: f64 = self.tests.iter().map(|t| t.duration_secs).sum();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n");
+        let _ = writeln!(
+            xml,
+            r#"  <testsuite name="{}" tests="{}" failures="{}" errors="0" skipped="{}" time="{:.3}">"#,
+            escape(&self.group),
+            self.tests.len(),
+            self.count(TestStatus::Failed) + self.count(TestStatus::TimedOut),
+            self.count(TestStatus::Skipped),
+            total_secs
+        );
+        // JUnit only allows properties on test suites, hence the ones of the
+        // test cases are prefixed with the name of the test.
+        let mut properties: Vec<(String, String)> = vec![];
+        if let Some(seed) = self.seed {
+            properties.push(("seed".to_string(), seed.to_string()));
+        }
+        if let Some(shard) = self.shard {
+            properties.push(("shard_index".to_string(), shard.index.to_string()));
+            properties.push(("total_shards".to_string(), shard.count.to_string()));
+        }
+        for test in &self.tests {
+            let mut property = |name: &str, value: String| {
+                properties.push((format!("{}.{}", test.name, name), value))
+            };
+            property("retries", test.retries.to_string());
+            if test.status != TestStatus::Skipped {
+                let stats = &test.attempt_stats;
+                property("attempts", stats.attempts.to_string());
+                property("passed_attempts", stats.passes.to_string());
+                property("failed_attempts", stats.failures.to_string());
+                property("flaky", stats.flaky.to_string());
+                property(
+                    "attempt_duration_stddev",
+                    format!("{:.3}", stats.stddev_duration_secs),
+                );
+            }
+            if let Some(usage) = test.resource_usage {
+                property("vm_hours", format!("{:.3}", usage.vm_hours));
+                property("peak_vms", usage.peak.vms.to_string());
+                property("peak_vcpus", usage.peak.vcpus.to_string());
+                property(
+                    "peak_memory_kibibytes",
+                    usage.peak.memory_kibibytes.to_string(),
+                );
+            }
+            for artifact in &test.artifacts {
+                property("artifact", artifact.to_string_lossy().to_string());
+            }
+        }
+        if !properties.is_empty() {
+            xml.push_str("    <properties>\n");
+            for (name, value) in properties {
+                let _ = writeln!(
+                    xml,
+                    r#"      <property name="{}" value="{}"/>"#,
+                    escape(&name),
+                    escape(&value)
+                );
+            }
+            xml.push_str("    </properties>\n");
+        }
+        for test in &self.tests {
+            // The tests of suites are reported as test cases of nested classes.
+            let (classname, name) = match test.name.rsplit_once(SUITE_SEPARATOR) {
+                Some((suite, name)) if !suite.is_empty() => (
+                    format!("{}.{}", self.group, suite.replace(SUITE_SEPARATOR, ".")),
+                    name,
+                ),
+                _ => (self.group.clone(), test.name.as_str()),
+            };
+            let _ = writeln!(
+                xml,
+                r#"    <testcase name="{}" classname="{}" time="{:.3}">"#,
+                escape(name),
+                escape(&classname),
+                test.duration_secs
+            );
+            match test.status {
+                TestStatus::Passed => {}
+                TestStatus::Failed => {
+                    let message = test.message.as_deref().unwrap_or_default();
+                    let _ = writeln!(
+                        xml,
+                        r#"      <failure message="{}" type="panic"/>"#,
+                        escape(message)
+                    );
+                }
+                TestStatus::TimedOut => {
+                    let _ = writeln!(
+                        xml,
+                        r#"      <failure message="Timed out after {:.3}s" type="timeout"/>"#,
+                        test.duration_secs
+                    );
+                }
+                TestStatus::Skipped => match test.message {
+                    Some(ref message) => {
+                        let _ = writeln!(xml, r#"      <skipped message="{}"/>"#, escape(message));
+                    }
+                    None => xml.push_str("      <skipped/>\n"),
+                },
+            }
+            xml.push_str("    </testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+
+    pub fn write_junit_xml(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_junit_xml())
+            .with_context(|| format!("Could not write JUnit XML results to {:?}", path))
+    }
+
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        let mut file = std::fs::File::create(path)
+            .with_context(|| format!("Could not create JSON results file {:?}", path))?;
+        serde_json::to_writer_pretty(&mut file, self)?;
+        file.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+/// Escapes the characters that are not allowed in XML attribute values.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            c if c.is_control() && c != '\t' && c != '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::resource_usage::ResourceUsage;
+
+    fn results() -> SystemTestGroupResults {
+        SystemTestGroupResults {
+            group: "my_test".to_string(),
+            seed: Some(1234),
+            shard: None,
+            tests: vec![
+                TestResult {
+                    name: "setup".to_string(),
+                    status: TestStatus::Passed,
+                    duration_secs: 1.5,
+                    retries: 0,
+                    message: None,
+                    artifacts: vec![PathBuf::from("/group/setup")],
+                    attempt_stats: AttemptStats::new(0, &[Duration::from_millis(1500)], true),
+                    resource_usage: Some(TestResourceUsage {
+                        wall_time_secs: 1.5,
+                        vm_hours: 0.25,
+                        peak: ResourceUsage {
+                            vms: 4,
+                            vcpus: 24,
+                            memory_kibibytes: 100663296,
+                        },
+                    }),
+                },
+                TestResult {
+                    name: "test_to_fail".to_string(),
+                    status: TestStatus::Failed,
+                    duration_secs: 2.0,
+                    retries: 1,
+                    message: Some("assertion `a < b` failed\nleft: 2".to_string()),
+                    artifacts: vec![],
+                    attempt_stats: AttemptStats::new(
+                        1,
+                        &[Duration::from_secs(3), Duration::from_secs(2)],
+                        false,
+                    ),
+                    resource_usage: None,
+                },
+                TestResult {
+                    name: "never_ending_task".to_string(),
+                    status: TestStatus::TimedOut,
+                    duration_secs: 10.0,
+                    retries: 0,
+                    message: None,
+                    artifacts: vec![],
+                    attempt_stats: AttemptStats::new(0, &[Duration::from_secs(10)], false),
+                    resource_usage: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn junit_xml_reports_failures_and_timeouts() {
+        let xml = results().to_junit_xml();
+        assert!(xml.contains(
+            r#"<testsuite name="my_test" tests="3" failures="2" errors="0" skipped="0" time="13.500">"#
+        ));
+        assert!(xml.contains(
+            r#"<failure message="assertion `a &lt; b` failed&#10;left: 2" type="panic"/>"#
+        ));
+        assert!(xml.contains(r#"<failure message="Timed out after 10.000s" type="timeout"/>"#));
+        assert!(xml.contains(r#"<property name="setup.artifact" value="/group/setup"/>"#));
+        assert!(xml.contains(r#"<property name="test_to_fail.retries" value="1"/>"#));
+        assert!(xml.contains(r#"<property name="seed" value="1234"/>"#));
+        assert!(xml.contains(r#"<property name="setup.vm_hours" value="0.250"/>"#));
+        assert!(xml.contains(r#"<property name="setup.peak_vcpus" value="24"/>"#));
+        // properties are only allowed on test suites
+        assert_eq!(xml.matches("<properties>").count(), 1);
+        assert!(xml.find("</properties>").unwrap() < xml.find("<testcase").unwrap());
+        assert!(!xml.contains("shard_index"));
+    }
+
+    #[test]
+    fn junit_xml_reports_the_shard() {
+        let mut results = results();
+        results.shard = Some(Shard { index: 1, count: 4 });
+        let xml = results.to_junit_xml();
+        assert!(xml.contains(
+            r#"      <property name="seed" value="1234"/>
+      <property name="shard_index" value="1"/>
+      <property name="total_shards" value="4"/>
+    </properties>"#
+        ));
+    }
+
+    #[test]
+    fn junit_xml_nests_the_tests_of_suites() {
+        let mut results = results();
+        results.tests[1].name = "nns::governance::test_to_fail".to_string();
+        let xml = results.to_junit_xml();
+        assert!(xml.contains(
+            r#"<testcase name="test_to_fail" classname="my_test.nns.governance" time="2.000">"#
+        ));
+        assert!(xml.contains(r#"<testcase name="setup" classname="my_test" time="1.500">"#));
+    }
+
+    #[test]
+    fn junit_xml_reports_skip_reasons() {
+        let mut results = results();
+        results.tests[2].status = TestStatus::Skipped;
+        results.tests[2].message = Some("requires $HSM_SLOT to be set".to_string());
+        assert!(results
+            .to_junit_xml()
+            .contains(r#"<skipped message="requires $HSM_SLOT to be set"/>"#));
+        results.tests[2].message = None;
+        assert!(results.to_junit_xml().contains("<skipped/>"));
+    }
+
+    #[test]
+    fn attempt_stats_identify_flaky_tests() {
+        let durations: Vec<Duration> = [2, 4, 4, 4, 5, 5, 7, 9]
+            .iter()
+            .map(|secs| Duration::from_secs(*secs))
+            .collect();
+        let stats = AttemptStats::new(7, &durations, true);
+        assert_eq!((stats.attempts, stats.passes, stats.failures), (8, 1, 7));
+        assert!(stats.flaky);
+        assert_eq!(stats.mean_duration_secs, 5.0);
+        assert_eq!(stats.stddev_duration_secs, 2.0);
+
+        let mut results = results();
+        assert_eq!(results.flaky_tests().count(), 0);
+        assert_eq!(results.tests[1].attempt_stats.failures, 2);
+        assert!(results
+            .to_junit_xml()
+            .contains(r#"<property name="test_to_fail.attempt_duration_stddev" value="0.500"/>"#));
+        results.tests[1].status = TestStatus::Passed;
+        results.tests[1].attempt_stats = AttemptStats::new(1, &durations[..2], true);
+        let flaky: Vec<&str> = results.flaky_tests().map(|t| t.name.as_str()).collect();
+        assert_eq!(flaky, vec!["test_to_fail"]);
+        assert!(results
+            .to_junit_xml()
+            .contains(r#"<property name="test_to_fail.flaky" value="true"/>"#));
+    }
+
+    #[test]
+    fn json_results_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.json");
+        results().write_json(&path).unwrap();
+        let read: SystemTestGroupResults =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(read, results());
+        assert!(String::from_utf8(std::fs::read(&path).unwrap())
+            .unwrap()
+            .contains(r#""status": "timed_out""#));
+    }
+}
@@ -0,0 +1,190 @@
+//! Sharding of the tests of a [SystemTestGroup](crate::driver::group::SystemTestGroup)
+//! across several invocations of the test driver, e.g., the shards of a Bazel
+//! test target with `shard_count`.
+//!
+//! Each invocation runs the setup and the tests of its shard only. The tests
+//! are assigned to the shards round-robin in the order of their registration,
+//! except that a test and its prerequisites, see
+//! [TestFunction::after](crate::driver::dsl::TestFunction::after), are always
+//! assigned to the same shard. As the assignment only depends on the group,
+//! every shard arrives at the same one. The tests of other shards are left out
+//! of the report of an invocation, such that the reports of all shards add up
+//! to the one of an unsharded run.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// The number of shards, set by Bazel for sharded tests.
+pub const TOTAL_SHARDS_ENV: &str = "TEST_TOTAL_SHARDS";
+/// The zero-based index of the shard to run, set by Bazel for sharded tests.
+pub const SHARD_INDEX_ENV: &str = "TEST_SHARD_INDEX";
+/// The file that a test touches to tell Bazel that it supports sharding.
+pub const SHARD_STATUS_FILE_ENV: &str = "TEST_SHARD_STATUS_FILE";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shard {
+    pub index: usize,
+    pub count: usize,
+}
+
+impl Shard {
+    pub fn new(index: usize, count: usize) -> Result<Self> {
+        if index >= count {
+            bail!("shard index {} is out of range for {} shards", index, count)
+        }
+        Ok(Self { index, count })
+    }
+
+    /// Reads the shard to run from [TOTAL_SHARDS_ENV] and [SHARD_INDEX_ENV],
+    /// if set, and then touches the [SHARD_STATUS_FILE_ENV].
+    pub fn from_env() -> Result<Option<Self>> {
+        let var = |name| std::env::var(name).ok();
+        let shard = Self::parse(var(TOTAL_SHARDS_ENV), var(SHARD_INDEX_ENV))?;
+        if let (Some(_), Some(path)) = (shard, var(SHARD_STATUS_FILE_ENV)) {
+            File::create(&path).with_context(|| format!("Failed to touch {:?}", path))?;
+        }
+        Ok(shard)
+    }
+
+    fn parse(total_shards: Option<String>, shard_index: Option<String>) -> Result<Option<Self>> {
+        let parse = |name: &str, value: String| {
+            value
+                .parse::<usize>()
+                .with_context(|| format!("{}={:?} is not a number", name, value))
+        };
+        match (total_shards, shard_index) {
+            (None, None) => Ok(None),
+            (Some(total_shards), Some(shard_index)) => Self::new(
+                parse(SHARD_INDEX_ENV, shard_index)?,
+                parse(TOTAL_SHARDS_ENV, total_shards)?,
+            )
+            .map(Some),
+            _ => bail!(
+                "{} and {} have to be set together",
+                TOTAL_SHARDS_ENV,
+                SHARD_INDEX_ENV
+            ),
+        }
+    }
+}
+
+/// Assigns the tests of a group to shards in the order of their registration.
+#[derive(Debug)]
+pub struct ShardAssignment {
+    shard: Shard,
+    next: usize,
+    shards: BTreeMap<String, usize>,
+    /// The shards of prerequisites that are registered after their tests.
+    pinned: BTreeMap<String, usize>,
+    conflicts: Vec<String>,
+}
+
+impl ShardAssignment {
+    pub fn new(shard: Shard) -> Self {
+        Self {
+            shard,
+            next: 0,
+            shards: BTreeMap::new(),
+            pinned: BTreeMap::new(),
+            conflicts: vec![],
+        }
+    }
+
+    /// Assigns `test` to a shard and returns whether it is the shard to run.
+    pub fn includes(&mut self, test: &str, prerequisites: &[String]) -> bool {
+        let assigned = self
+            .pinned
+            .get(test)
+            .or_else(|| prerequisites.iter().find_map(|p| self.shards.get(p)))
+            .copied();
+        let shard = assigned.unwrap_or_else(|| {
+            let shard = self.next;
+            self.next = (self.next + 1) % self.shard.count;
+            shard
+        });
+        self.shards.insert(test.to_string(), shard);
+        for prerequisite in prerequisites {
+            let other = self
+                .shards
+                .get(prerequisite)
+                .or_else(|| self.pinned.get(prerequisite))
+                .copied();
+            match other {
+                Some(other) if other != shard => self.conflicts.push(format!(
+                    "test {} is assigned to shard {}, but its prerequisite {} to shard {}",
+                    test, shard, prerequisite, other
+                )),
+                Some(_) => {}
+                None => {
+                    self.pinned.insert(prerequisite.clone(), shard);
+                }
+            }
+        }
+        shard == self.shard.index
+    }
+
+    /// Describes a test whose prerequisites are in another shard, if any.
+    pub fn conflict(&self) -> Option<&str> {
+        self.conflicts.first().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn some(value: &str) -> Option<String> {
+        Some(value.to_string())
+    }
+
+    #[test]
+    fn shard_is_parsed_from_both_variables() {
+        assert_eq!(Shard::parse(None, None).unwrap(), None);
+        assert_eq!(
+            Shard::parse(some("3"), some("2")).unwrap(),
+            Some(Shard { index: 2, count: 3 })
+        );
+        assert!(Shard::parse(some("3"), some("3")).is_err());
+        assert!(Shard::parse(some("3"), None).is_err());
+        assert!(Shard::parse(some("three"), some("0")).is_err());
+    }
+
+    #[test]
+    fn tests_are_assigned_round_robin_and_with_their_prerequisites() {
+        let tests = |index| {
+            let mut assignment = ShardAssignment::new(Shard { index, count: 2 });
+            let mut included = vec![];
+            let mut add = |test: &str, prerequisites: &[&str]| {
+                let prerequisites: Vec<String> =
+                    prerequisites.iter().map(|p| p.to_string()).collect();
+                if assignment.includes(test, &prerequisites) {
+                    included.push(test.to_string());
+                }
+            };
+            add("a", &[]);
+            add("b", &[]);
+            add("c", &["a"]);
+            // A prerequisite registered after its test.
+            add("d", &["e"]);
+            add("e", &[]);
+            add("f", &[]);
+            assert_eq!(assignment.conflict(), None);
+            included
+        };
+
+        assert_eq!(tests(0), vec!["a", "c", "d", "e"]);
+        assert_eq!(tests(1), vec!["b", "f"]);
+    }
+
+    #[test]
+    fn prerequisites_in_different_shards_conflict() {
+        let mut assignment = ShardAssignment::new(Shard { index: 0, count: 2 });
+        assignment.includes("a", &[]);
+        assignment.includes("b", &[]);
+        assignment.includes("c", &["a".to_string(), "b".to_string()]);
+        assert!(assignment.conflict().unwrap().contains("prerequisite b"));
+    }
+}
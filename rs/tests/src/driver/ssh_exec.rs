@@ -0,0 +1,313 @@
+//! Running commands on the VMs of a testnet over SSH, such that tests do not
+//! need to handle sessions and channels themselves:
+//!
+//! ```ignore
+//! let output = node.ssh_exec(
+//!     &SshCommand::new("sudo nft list ruleset").with_timeout(Duration::from_secs(30)),
+//! )?;
+//! assert!(output.stdout.contains("table inet filter"));
+//! // A shorthand failing on non-zero exit codes:
+//! let uptime = node.ssh_run("uptime")?;
+//! ```
+//!
+//! Sessions are kept per VM and reused by later commands. Establishing the
+//! session and starting the command are retried until they succeed, e.g., while
+//! the VM is booting or after its pooled session broke. Once the command
+//! started, failures are not retried, as the command might have had an effect.
+
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Result};
+use lazy_static::lazy_static;
+use slog::{info, warn};
+use ssh2::{Channel, Session};
+
+use crate::driver::test_env_api::{
+    HasTestEnv, HasVmName, SshSession, RETRY_BACKOFF, SSH_RETRY_TIMEOUT,
+};
+
+/// How long a command may run by default.
+pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(10 * 60); // 10 minutes
+/// How long to wait between two reads of the output of a running command.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+lazy_static! {
+    /// The idle sessions by VM name. A session is taken out while a command
+    /// runs on it, such that concurrent commands do not share sessions.
+    static ref SESSIONS: Mutex<HashMap<String, Vec<Session>>> = Mutex::new(HashMap::new());
+}
+
+/// A command run by the default shell of the user on the VM.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SshCommand {
+    pub command: String,
+    pub stdin: Option<String>,
+    pub timeout: Duration,
+    /// How long to retry establishing the session and starting the command.
+    pub retry_timeout: Duration,
+}
+
+impl SshCommand {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            stdin: None,
+            timeout: DEFAULT_COMMAND_TIMEOUT,
+            retry_timeout: SSH_RETRY_TIMEOUT,
+        }
+    }
+
+    /// Runs `script` with bash, e.g., to use pipes and multiple lines.
+    pub fn bash(script: &str) -> Self {
+        Self::new("bash").with_stdin(script)
+    }
+
+    pub fn with_stdin(mut self, stdin: &str) -> Self {
+        self.stdin = Some(stdin.to_string());
+        self
+    }
+
+    /// Fails the command if it did not finish after `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_retry_timeout(mut self, retry_timeout: Duration) -> Self {
+        self.retry_timeout = retry_timeout;
+        self
+    }
+}
+
+/// The outcome of a command that ran to completion.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandOutput {
+    pub exit_status: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CommandOutput {
+    pub fn success(&self) -> bool {
+        self.exit_status == 0
+    }
+
+    /// Returns the standard output, or fails with the standard error if the
+    /// command exited with a non-zero code.
+    pub fn into_stdout(self) -> Result<String> {
+        if !self.success() {
+            bail!(
+                "the command exited with code {}, stderr: {}",
+                self.exit_status,
+                self.stderr.trim_end()
+            );
+        }
+        Ok(self.stdout)
+    }
+}
+
+/// The outcome of a command whose standard output is kept as is, e.g., as it
+/// is an archive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawCommandOutput {
+    pub exit_status: i32,
+    pub stdout: Vec<u8>,
+    pub stderr: String,
+}
+
+impl From<RawCommandOutput> for CommandOutput {
+    fn from(output: RawCommandOutput) -> Self {
+        Self {
+            exit_status: output.exit_status,
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: output.stderr,
+        }
+    }
+}
+
+pub trait SshExec {
+    /// Runs `command` on the VM and returns its exit code and raw output.
+    fn ssh_exec_raw(&self, command: &SshCommand) -> Result<RawCommandOutput>;
+
+    /// Runs `command` on the VM and returns its exit code and output.
+    fn ssh_exec(&self, command: &SshCommand) -> Result<CommandOutput> {
+        self.ssh_exec_raw(command).map(CommandOutput::from)
+    }
+
+    /// Runs `command` with the default settings and returns its standard
+    /// output, failing if it exited with a non-zero code.
+    fn ssh_run(&self, command: &str) -> Result<String> {
+        self.ssh_exec(&SshCommand::new(command))?.into_stdout()
+    }
+}
+
+impl<T: SshSession + HasVmName + HasTestEnv> SshExec for T {
+    fn ssh_exec_raw(&self, command: &SshCommand) -> Result<RawCommandOutput> {
+        let log = self.test_env().logger();
+        let vm_name = self.vm_name();
+        let start = Instant::now();
+        let (session, mut channel) = loop {
+            match start_command(self, &vm_name, command) {
+                Ok(started) => break started,
+                Err(e) if start.elapsed() > command.retry_timeout => {
+                    bail!(
+                        "Failed to start {:?} on {} within {:?}: {:?}",
+                        command.command,
+                        vm_name,
+                        command.retry_timeout,
+                        e
+                    )
+                }
+                Err(e) => {
+                    info!(
+                        log,
+                        "Failed to start {:?} on {}, retrying: {}", command.command, vm_name, e
+                    );
+                    thread::sleep(RETRY_BACKOFF);
+                }
+            }
+        };
+        match await_output(&session, &mut channel, command.timeout) {
+            Ok(output) => {
+                session.set_timeout(0);
+                SESSIONS
+                    .lock()
+                    .unwrap()
+                    .entry(vm_name)
+                    .or_default()
+                    .push(session);
+                Ok(output)
+            }
+            // The session is dropped, as the command might still be running.
+            Err(e) => {
+                warn!(log, "{:?} failed on {}: {:?}", command.command, vm_name, e);
+                Err(e.context(format!("{:?} failed on {}", command.command, vm_name)))
+            }
+        }
+    }
+}
+
+/// Opens a channel on a pooled or new session of the VM and starts `command`.
+fn start_command<T: SshSession>(
+    vm: &T,
+    vm_name: &str,
+    command: &SshCommand,
+) -> Result<(Session, Channel)> {
+    let pooled = SESSIONS
+        .lock()
+        .unwrap()
+        .get_mut(vm_name)
+        .and_then(|sessions| sessions.pop());
+    let session = match pooled {
+        Some(session) => session,
+        None => vm.get_ssh_session()?,
+    };
+    session.set_blocking(true);
+    session.set_timeout(command.timeout.as_millis() as u32);
+    let mut channel = session.channel_session()?;
+    channel.exec(&command.command)?;
+    if let Some(ref stdin) = command.stdin {
+        channel.write_all(stdin.as_bytes())?;
+        channel.flush()?;
+    }
+    channel.send_eof()?;
+    Ok((session, channel))
+}
+
+/// Reads the standard output and error of the command as they arrive, such
+/// that a command filling one of them does not block, until it exits.
+fn await_output(
+    session: &Session,
+    channel: &mut Channel,
+    timeout: Duration,
+) -> Result<RawCommandOutput> {
+    let deadline = Instant::now() + timeout;
+    let (mut stdout, mut stderr) = (vec![], vec![]);
+    let (mut stdout_done, mut stderr_done) = (false, false);
+    session.set_blocking(false);
+    while !(stdout_done && stderr_done) {
+        if Instant::now() > deadline {
+            session.set_blocking(true);
+            return Err(anyhow!("timed out after {:?}", timeout));
+        }
+        let read_stdout = read_available(channel, &mut stdout, &mut stdout_done);
+        let read_stderr = read_available(&mut channel.stderr(), &mut stderr, &mut stderr_done);
+        match (read_stdout, read_stderr) {
+            (Ok(false), Ok(false)) => thread::sleep(POLL_INTERVAL),
+            (Ok(_), Ok(_)) => {}
+            (Err(e), _) | (_, Err(e)) => {
+                session.set_blocking(true);
+                return Err(e.into());
+            }
+        }
+    }
+    session.set_blocking(true);
+    channel.wait_close()?;
+    Ok(RawCommandOutput {
+        exit_status: channel.exit_status()?,
+        stdout,
+        stderr: String::from_utf8_lossy(&stderr).to_string(),
+    })
+}
+
+/// Appends the bytes available in `stream` to `buf`. Returns whether any bytes
+/// were read and sets `done` at the end of the stream.
+fn read_available(stream: &mut impl Read, buf: &mut Vec<u8>, done: &mut bool) -> io::Result<bool> {
+    if *done {
+        return Ok(false);
+    }
+    let mut chunk = [0u8; 8192];
+    match stream.read(&mut chunk) {
+        Ok(0) => {
+            *done = true;
+            Ok(false)
+        }
+        Ok(n) => {
+            buf.extend_from_slice(&chunk[..n]);
+            Ok(true)
+        }
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outputs_of_failed_commands_are_errors() {
+        let output = CommandOutput {
+            exit_status: 2,
+            stdout: String::new(),
+            stderr: "grep: /var/log/missing: No such file or directory\n".to_string(),
+        };
+        assert!(!output.success());
+        assert_eq!(
+            output.into_stdout().unwrap_err().to_string(),
+            "the command exited with code 2, stderr: grep: /var/log/missing: No such file or directory"
+        );
+        let output = CommandOutput {
+            exit_status: 0,
+            stdout: "up 3 min\n".to_string(),
+            stderr: String::new(),
+        };
+        assert_eq!(output.into_stdout().unwrap(), "up 3 min\n");
+    }
+
+    #[test]
+    fn available_bytes_are_read_until_the_end_of_the_stream() {
+        let mut stream: &[u8] = b"hello";
+        let (mut buf, mut done) = (vec![], false);
+        assert!(read_available(&mut stream, &mut buf, &mut done).unwrap());
+        assert!(!read_available(&mut stream, &mut buf, &mut done).unwrap());
+        assert!(done);
+        assert_eq!(buf, b"hello");
+    }
+}
@@ -15,11 +15,12 @@ use crate::driver::{
 use slog::{crit, error, info, Logger};
 use std::{
     panic::catch_unwind,
-    process::Command,
+    process::{Command, ExitStatus},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, Mutex,
     },
+    time::{Duration, Instant},
 };
 use tokio::{runtime::Handle as RtHandle, task::JoinHandle, time::timeout};
 
@@ -32,6 +33,9 @@ pub struct SubprocessTask {
     spawned: AtomicBool,
     group_ctx: GroupContext,
     sub_fact: Arc<dyn BroadcastingEventSubscriberFactory>,
+    retries: usize,
+    /// The time all attempts together may take, if retries are enabled.
+    max_duration: Option<Duration>,
 }
 
 impl SubprocessTask {
@@ -49,8 +53,20 @@ impl SubprocessTask {
             spawned: Default::default(),
             group_ctx,
             sub_fact,
+            retries: 0,
+            max_duration: None,
         }
     }
+
+    /// Restarts the child process up to `retries` times if it fails, but
+    /// only as long as less than `max_duration` passed since the task was
+    /// spawned, such that all attempts together stay within `max_duration`.
+    /// Each retry runs on a fresh copy of the setup environment.
+    pub fn with_retries(mut self, retries: usize, max_duration: Duration) -> Self {
+        self.retries = retries;
+        self.max_duration = Some(max_duration);
+        self
+    }
 }
 
 impl Task for SubprocessTask {
@@ -59,32 +75,14 @@ impl Task for SubprocessTask {
             panic!("Respawned already spawned task '{}'", self.task_id);
         }
 
-        // select a random socket id used for this child process
-        use rand::Rng;
-        let sock_id: u64 = rand::thread_rng().gen();
-        let sock_path = GroupContext::log_socket_path(sock_id);
-
-        let mut child_cmd = Command::new(self.group_ctx.exec_path.clone());
-        child_cmd
-            .arg("--working-dir") // TODO: rename as --group-dir
-            .arg(self.group_ctx.group_dir().as_os_str())
-            .arg("spawn-child")
-            .arg(self.task_id.name())
-            .arg(sock_id.to_string());
-
         let mut sub = self.sub_fact.create_broadcasting_subscriber();
         (sub)(Event::task_spawned(self.task_id.clone()));
-
-        info!(self.group_ctx.log(), "Spawning {:?} ...", child_cmd);
+        let spawned_at = Instant::now();
 
         let log = self.group_ctx.logger();
-        let log_rcvr = self
-            .rt
-            .block_on(LogReceiver::new(sock_path, log.clone()))
-            .expect("Could not start LogReceiver");
-        let (proc, kill) = self.rt.block_on(Process::new(
+        let (log_rcvr, proc, kill) = self.rt.block_on(start_child(
             self.task_id.clone(),
-            child_cmd,
+            &self.group_ctx,
             self.sub_fact.clone(),
         ));
 
@@ -92,38 +90,67 @@ impl Task for SubprocessTask {
         let jh = self.rt.spawn({
             let task_id = self.task_id.clone();
             let task_state = task_state.clone();
+            let group_ctx = self.group_ctx.clone();
+            let sub_fact = self.sub_fact.clone();
+            let retries = self.retries;
+            let max_duration = self.max_duration;
             async move {
-                let log_jh = tokio::task::spawn(async move { log_rcvr.receive_all().await });
-                let exit_code = proc.block_on_exit().await;
-
-                info!(
-                    log,
-                    "Task '{task_id}' finished with exit code: {exit_code:?}"
-                );
+                let mut child = (log_rcvr, proc);
+                let mut attempt = 0;
+                let (exit_code, report_or_failure) = loop {
+                    let (log_rcvr, proc) = child;
+                    let (exit_code, report_or_failure) =
+                        await_child(&task_id, &log, log_rcvr, proc).await;
+                    let succeeded = matches!(&exit_code, Ok(status) if status.success());
+                    let is_running = task_state.lock().unwrap().is_running();
+                    if succeeded || attempt >= retries || !is_running {
+                        break (exit_code, report_or_failure);
+                    }
+                    if max_duration.map_or(false, |d| spawned_at.elapsed() >= d) {
+                        info!(
+                            log,
+                            "Not retrying task '{task_id}' as its {:?} for all attempts elapsed",
+                            max_duration.unwrap()
+                        );
+                        break (exit_code, report_or_failure);
+                    }
 
-                // A misbehaving child might have not connected to the parent at all. In such a
-                // case, this join would block forever.
+                    attempt += 1;
+                    let msg = match report_or_failure {
+                        Some(ReportOrFailure::Failure(msg)) => msg,
+                        _ => format!("Task {} failed with exit code: {:?}.", task_id, exit_code),
+                    };
+                    info!(
+                        log,
+                        "Retrying task '{task_id}' ({attempt}/{retries}) after failure: {msg}"
+                    );
+                    (sub)(Event::task_retried(task_id.clone(), attempt, msg));
+                    if let Err(e) = group_ctx.archive_test_dir(&task_id.name(), attempt) {
+                        error!(log, "[Driver Error] Archiving test directory failed: {e:?}");
+                    }
 
-                use ReportOrFailure::*;
-                match timeout(LOG_CLOSE_TIMEOUT, log_jh).await {
-                    Ok(jh_res) => match jh_res.unwrap() {
-                        Ok(Some(Report(msg))) => {
-                            (sub)(Event::task_sub_report(task_id.clone(), msg))
-                        }
-                        Ok(Some(Failure(msg))) => {
-                            (sub)(Event::task_caught_panic(task_id.clone(), msg))
-                        }
-                        Ok(_) => {}
-                        Err(e) => {
-                            error!(log, "[Driver Error] Reading logs failed: {e:?}");
+                    let (log_rcvr, proc, kill) =
+                        start_child(task_id.clone(), &group_ctx, sub_fact.clone()).await;
+                    {
+                        let mut task_state = task_state.lock().unwrap();
+                        if task_state.is_running() {
+                            *task_state = TaskState::Running(Box::new(kill));
+                        } else {
+                            // The task was stopped or failed while the retry was starting.
+                            (kill)();
                         }
-                    },
-                    Err(e) => {
-                        error!(
-                            log,
-                            "Timeout occurred when waiting for log channel to close: {e:?}"
-                        );
                     }
+                    child = (log_rcvr, proc);
+                };
+
+                match report_or_failure {
+                    Some(ReportOrFailure::Report(msg)) => {
+                        (sub)(Event::task_sub_report(task_id.clone(), msg))
+                    }
+                    Some(ReportOrFailure::Failure(msg)) => {
+                        (sub)(Event::task_caught_panic(task_id.clone(), msg))
+                    }
+                    None => {}
                 }
 
                 let mut task_state = task_state.lock().unwrap();
@@ -152,7 +179,10 @@ impl Task for SubprocessTask {
                     // If either a stop or a failure was requested by the
                     // scheduler, we ignore the exit code. I.e., the request
                     // overrides the result from the process.
-                    TaskState::StopRequested => Event::task_stopped(task_id),
+                    TaskState::StopRequested => {
+                        (sub)(Event::task_stop_requested(task_id.clone()));
+                        Event::task_stopped(task_id)
+                    }
                     TaskState::FailRequested => Event::task_failed(
                         task_id.clone(),
                         format!("Task '{task_id}' failed with exit code: {exit_code:?}."),
@@ -261,7 +291,73 @@ impl TaskState {
     }
 }
 
-fn panic_to_result(panic_res: std::thread::Result<()>) -> Result<(), String> {
+/// Starts a child process executing the task `task_id` together with the
+/// receiver of its logs.
+async fn start_child(
+    task_id: TaskId,
+    group_ctx: &GroupContext,
+    sub_fact: Arc<dyn BroadcastingEventSubscriberFactory>,
+) -> (LogReceiver, Process, impl KillFn) {
+    // select a random socket id used for this child process
+    use rand::Rng;
+    let sock_id: u64 = rand::thread_rng().gen();
+    let sock_path = GroupContext::log_socket_path(sock_id);
+
+    let mut child_cmd = Command::new(group_ctx.exec_path.clone());
+    child_cmd
+        .arg("--working-dir") // TODO: rename as --group-dir
+        .arg(group_ctx.group_dir().as_os_str())
+        .arg("spawn-child")
+        .arg(task_id.name())
+        .arg(sock_id.to_string());
+
+    info!(group_ctx.log(), "Spawning {:?} ...", child_cmd);
+
+    let log_rcvr = LogReceiver::new(sock_path, group_ctx.logger())
+        .await
+        .expect("Could not start LogReceiver");
+    let (proc, kill) = Process::new(task_id, child_cmd, sub_fact).await;
+    (log_rcvr, proc, kill)
+}
+
+/// Waits for the child process to exit and returns its exit code together
+/// with the report or failure message it sent, if any.
+async fn await_child(
+    task_id: &TaskId,
+    log: &Logger,
+    log_rcvr: LogReceiver,
+    proc: Process,
+) -> (std::io::Result<ExitStatus>, Option<ReportOrFailure>) {
+    let log_jh = tokio::task::spawn(async move { log_rcvr.receive_all().await });
+    let exit_code = proc.block_on_exit().await;
+
+    info!(
+        log,
+        "Task '{task_id}' finished with exit code: {exit_code:?}"
+    );
+
+    // A misbehaving child might have not connected to the parent at all. In such a
+    // case, this join would block forever.
+    let report_or_failure = match timeout(LOG_CLOSE_TIMEOUT, log_jh).await {
+        Ok(jh_res) => match jh_res.unwrap() {
+            Ok(report_or_failure) => report_or_failure,
+            Err(e) => {
+                error!(log, "[Driver Error] Reading logs failed: {e:?}");
+                None
+            }
+        },
+        Err(e) => {
+            error!(
+                log,
+                "Timeout occurred when waiting for log channel to close: {e:?}"
+            );
+            None
+        }
+    };
+    (exit_code, report_or_failure)
+}
+
+pub(crate) fn panic_to_result(panic_res: std::thread::Result<()>) -> Result<(), String> {
     if let Err(panic_res) = panic_res {
         if let Some(s) = panic_res.downcast_ref::<String>() {
             Err(s.to_string())
@@ -0,0 +1,321 @@
+//! Success criteria of tests on the metrics of the testnet, see
+//! [TestFunction::with_success_criterion](crate::driver::dsl::TestFunction::with_success_criterion):
+//!
+//! ```ignore
+//! TestFunction::new("upgrade_under_load", test)
+//!     .with_success_criterion(SuccessCriterion::finalization_rate_at_least(
+//!         0.9,
+//!         Duration::from_secs(5 * 60),
+//!     ))
+//!     .with_success_criterion(SuccessCriterion::no_replica_restarts())
+//! ```
+//!
+//! Criteria are PromQL queries whose samples must stay within a bound. They are
+//! evaluated on the Prometheus VM of the group, see
+//! [PrometheusVm](crate::driver::prometheus_vm::PrometheusVm), every
+//! [CRITERIA_EVALUATION_INTERVAL] while the test runs and once it finished. A
+//! test violating any of its criteria fails with a report of the violations
+//! once it finished.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    sync::mpsc::{channel, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use slog::{info, warn, Logger};
+
+use crate::driver::{
+    prometheus_vm::{HasPrometheus, PrometheusSample},
+    test_env::TestEnv,
+};
+
+/// How often the criteria are evaluated while the test runs.
+pub const CRITERIA_EVALUATION_INTERVAL: Duration = Duration::from_secs(30);
+/// The window in which [SuccessCriterion::no_replica_restarts] looks for
+/// restarts. It exceeds the evaluation interval, such that every restart is
+/// seen by at least one evaluation.
+const RESTARTS_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// The bound the samples of a criterion must stay within.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bound {
+    AtLeast(f64),
+    AtMost(f64),
+}
+
+impl Bound {
+    fn holds(&self, value: f64) -> bool {
+        match *self {
+            Bound::AtLeast(min) => value >= min,
+            Bound::AtMost(max) => value <= max,
+        }
+    }
+}
+
+impl Display for Bound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Bound::AtLeast(min) => write!(f, ">= {}", min),
+            Bound::AtMost(max) => write!(f, "<= {}", max),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SuccessCriterion {
+    pub description: String,
+    /// A PromQL query resulting in an instant vector.
+    pub query: String,
+    /// The bound every sample of the query must stay within.
+    pub bound: Bound,
+}
+
+impl SuccessCriterion {
+    pub fn new(description: &str, query: &str, bound: Bound) -> Self {
+        Self {
+            description: description.to_string(),
+            query: query.to_string(),
+            bound,
+        }
+    }
+
+    /// Every replica finalizes at least `blocks_per_sec` blocks per second on
+    /// average over the preceding `window`.
+    pub fn finalization_rate_at_least(blocks_per_sec: f64, window: Duration) -> Self {
+        Self::new(
+            &format!(
+                "finalization rate >= {} blocks/s over the last {}s",
+                blocks_per_sec,
+                window.as_secs()
+            ),
+            &format!(
+                r#"rate(artifact_pool_consensus_height_stat{{job="replica",pool_type="validated",stat="max",type="finalization"}}[{}s])"#,
+                window.as_secs()
+            ),
+            Bound::AtLeast(blocks_per_sec),
+        )
+    }
+
+    /// No replica restarts while the test runs.
+    pub fn no_replica_restarts() -> Self {
+        Self::new(
+            "no replica restarts",
+            &format!(
+                r#"changes(process_start_time_seconds{{job="replica"}}[{}s])"#,
+                RESTARTS_WINDOW.as_secs()
+            ),
+            Bound::AtMost(0.0),
+        )
+    }
+
+    /// Describes the samples violating the bound, where NaN never satisfies
+    /// it. An empty result is a violation only if samples are `required`.
+    fn violations(&self, samples: &[PrometheusSample], required: bool) -> Option<String> {
+        if samples.is_empty() {
+            return required.then(|| "the query resulted in no samples".to_string());
+        }
+        let violating: Vec<String> = samples
+            .iter()
+            .filter(|sample| !self.bound.holds(sample.value))
+            .map(|sample| {
+                let labels: BTreeMap<_, _> = sample.labels.iter().collect();
+                let labels: Vec<String> = labels
+                    .into_iter()
+                    .map(|(name, value)| format!("{}={:?}", name, value))
+                    .collect();
+                format!("{{{}}} = {}", labels.join(","), sample.value)
+            })
+            .collect();
+        (!violating.is_empty()).then(|| violating.join(", "))
+    }
+}
+
+/// The first violation of a criterion.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CriterionViolation {
+    pub criterion: SuccessCriterion,
+    /// The time since the start of the test, or `None` at its end.
+    pub elapsed: Option<Duration>,
+    pub details: String,
+}
+
+impl Display for CriterionViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let when = match self.elapsed {
+            Some(elapsed) => format!("after {}s", elapsed.as_secs()),
+            None => "at the end of the test".to_string(),
+        };
+        write!(
+            f,
+            "'{}' ({} {}) was violated {}: {}",
+            self.criterion.description,
+            self.criterion.query,
+            self.criterion.bound,
+            when,
+            self.details
+        )
+    }
+}
+
+fn violation_report(violations: &[CriterionViolation]) -> String {
+    let lines: Vec<String> = violations.iter().map(|v| format!("- {}", v)).collect();
+    format!(
+        "{} success criteria were violated:\n{}",
+        violations.len(),
+        lines.join("\n")
+    )
+}
+
+/// Evaluates `criteria`, skipping those already in `violations`, and records
+/// the first violation of each. Errors of the queries only count at the end.
+fn evaluate(
+    env: &TestEnv,
+    log: &Logger,
+    criteria: &[SuccessCriterion],
+    elapsed: Option<Duration>,
+    violations: &mut Vec<CriterionViolation>,
+) {
+    for criterion in criteria {
+        if violations.iter().any(|v| &v.criterion == criterion) {
+            continue;
+        }
+        let details = match env.query_prometheus(&criterion.query) {
+            Ok(samples) => criterion.violations(&samples, elapsed.is_none()),
+            Err(e) if elapsed.is_none() => Some(format!("failed to evaluate the query: {:?}", e)),
+            Err(e) => {
+                warn!(
+                    log,
+                    "Failed to evaluate the success criterion '{}': {:?}", criterion.description, e
+                );
+                None
+            }
+        };
+        if let Some(details) = details {
+            let violation = CriterionViolation {
+                criterion: criterion.clone(),
+                elapsed,
+                details,
+            };
+            warn!(log, "Success criterion {}", violation);
+            violations.push(violation);
+        }
+    }
+}
+
+/// Runs the test `f`, evaluating `criteria` in the background while it runs
+/// and once it passed. Panics with the report of the violations, if any.
+pub(crate) fn run_with_success_criteria(
+    env: &TestEnv,
+    criteria: Vec<SuccessCriterion>,
+    f: impl FnOnce(),
+) {
+    if criteria.is_empty() {
+        return f();
+    }
+    let log = env.logger();
+    for criterion in &criteria {
+        if let Err(e) = env.query_prometheus(&criterion.query) {
+            panic!(
+                "The success criterion '{}' cannot be evaluated: {:?}",
+                criterion.description, e
+            );
+        }
+    }
+    info!(
+        log,
+        "Evaluating {} success criteria every {:?} ...",
+        criteria.len(),
+        CRITERIA_EVALUATION_INTERVAL
+    );
+    let (stop, stopped) = channel::<()>();
+    let monitor = thread::spawn({
+        let env = env.clone();
+        let log = log.clone();
+        let criteria = criteria.clone();
+        move || {
+            let start = Instant::now();
+            let mut violations = vec![];
+            while let Err(RecvTimeoutError::Timeout) =
+                stopped.recv_timeout(CRITERIA_EVALUATION_INTERVAL)
+            {
+                evaluate(
+                    &env,
+                    &log,
+                    &criteria,
+                    Some(start.elapsed()),
+                    &mut violations,
+                );
+            }
+            violations
+        }
+    });
+    let result = catch_unwind(AssertUnwindSafe(f));
+    drop(stop);
+    let mut violations = monitor.join().unwrap_or_default();
+    if let Err(panic) = result {
+        if !violations.is_empty() {
+            warn!(log, "{}", violation_report(&violations));
+        }
+        resume_unwind(panic);
+    }
+    evaluate(env, &log, &criteria, None, &mut violations);
+    if !violations.is_empty() {
+        panic!("{}", violation_report(&violations));
+    }
+    info!(log, "All {} success criteria hold.", criteria.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(node: &str, value: f64) -> PrometheusSample {
+        PrometheusSample {
+            labels: [("ic_node".to_string(), node.to_string())].into(),
+            value,
+        }
+    }
+
+    #[test]
+    fn violations_are_reported_per_sample() {
+        let criterion = SuccessCriterion::finalization_rate_at_least(0.9, Duration::from_secs(300));
+        assert_eq!(
+            criterion.query,
+            r#"rate(artifact_pool_consensus_height_stat{job="replica",pool_type="validated",stat="max",type="finalization"}[300s])"#
+        );
+        assert_eq!(criterion.violations(&[sample("a", 1.0)], true), None);
+        assert_eq!(criterion.violations(&[], false), None);
+        let details = criterion
+            .violations(
+                &[sample("a", 0.9), sample("b", 0.5), sample("c", f64::NAN)],
+                true,
+            )
+            .unwrap();
+        assert_eq!(details, r#"{ic_node="b"} = 0.5, {ic_node="c"} = NaN"#);
+
+        let violations = vec![
+            CriterionViolation {
+                criterion,
+                elapsed: Some(Duration::from_secs(90)),
+                details,
+            },
+            CriterionViolation {
+                criterion: SuccessCriterion::no_replica_restarts(),
+                elapsed: None,
+                details: SuccessCriterion::no_replica_restarts()
+                    .violations(&[], true)
+                    .unwrap(),
+            },
+        ];
+        assert_eq!(
+            violation_report(&violations),
+            r#"2 success criteria were violated:
+- 'finalization rate >= 0.9 blocks/s over the last 300s' (rate(artifact_pool_consensus_height_stat{job="replica",pool_type="validated",stat="max",type="finalization"}[300s]) >= 0.9) was violated after 90s: {ic_node="b"} = 0.5, {ic_node="c"} = NaN
+- 'no replica restarts' (changes(process_start_time_seconds{job="replica"}[300s]) <= 0) was violated at the end of the test: the query resulted in no samples"#
+        );
+    }
+}
@@ -61,6 +61,7 @@ pub struct SkipTestTask {
     spawned: AtomicBool,
     task_id: TaskId,
     sub_fact: Arc<dyn BroadcastingEventSubscriberFactory>,
+    reason: Option<String>,
 }
 
 impl SkipTestTask {
@@ -69,8 +70,15 @@ impl SkipTestTask {
             spawned: Default::default(),
             task_id,
             sub_fact,
+            reason: None,
         }
     }
+
+    /// Reports `reason` as the sub-report of the skipped test.
+    pub fn with_reason(mut self, reason: String) -> Self {
+        self.reason = Some(reason);
+        self
+    }
 }
 
 pub struct SkipTestTaskHandle;
@@ -90,6 +98,9 @@ impl Task for SkipTestTask {
         let mut sub = self.sub_fact.create_broadcasting_subscriber();
         (sub)(Event::task_spawned(self.task_id.clone()));
         (sub)(Event::task_skipped(self.task_id.clone()));
+        if let Some(ref reason) = self.reason {
+            (sub)(Event::task_sub_report(self.task_id.clone(), reason.clone()));
+        }
         (sub)(Event::task_stopped(self.task_id.clone()));
 
         Box::new(SkipTestTaskHandle) as Box<dyn TaskHandle>
@@ -0,0 +1,352 @@
+//! Dependencies between the tests of a group, see
+//! [TestFunction::after](crate::driver::dsl::TestFunction::after).
+//!
+//! A dependent test is wrapped into a [DependentTask], which spawns the test
+//! once all its prerequisites passed and skips it as soon as one of them
+//! failed or was skipped. The outcomes of the tests are collected in the
+//! parent process by the event subscriber of [TestOutcomes].
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    time::Duration,
+};
+
+use slog::{info, Logger};
+
+use crate::driver::{
+    event::{BroadcastingEventSubscriberFactory, Event, EventPayload, EventSubscriber, TaskId},
+    plan::EvalOrder,
+    task::{Task, TaskHandle},
+};
+
+/// How often a waiting dependent test checks whether it was cancelled.
+const CANCELLATION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// The outcomes of the finished tests of a group, by test name.
+#[derive(Clone, Default)]
+pub struct TestOutcomes(Arc<(Mutex<HashMap<String, TestOutcome>>, Condvar)>);
+
+impl TestOutcomes {
+    /// Returns the subscriber recording the outcome of each test. As a
+    /// skipped test is stopped afterwards, only the first outcome counts. A
+    /// test stopped by the scheduler, e.g. after a timeout, did not pass.
+    pub fn subscriber(&self) -> Box<dyn EventSubscriber> {
+        let outcomes = self.clone();
+        Box::new(move |event: Event| {
+            let (task_id, outcome) = match event.what {
+                EventPayload::TaskStopped { task_id } => (task_id, TestOutcome::Passed),
+                EventPayload::TaskStopRequested { task_id } => (task_id, TestOutcome::Failed),
+                EventPayload::TaskFailed { task_id, .. } => (task_id, TestOutcome::Failed),
+                EventPayload::TaskSkipped { task_id } => (task_id, TestOutcome::Skipped),
+                _ => return,
+            };
+            if let TaskId::Test(name) = task_id {
+                let (lock, cvar) = &*outcomes.0;
+                lock.lock().unwrap().entry(name).or_insert(outcome);
+                cvar.notify_all();
+            }
+        })
+    }
+
+    /// Waits until the first of `tests` that did not pass finished, or all of
+    /// them passed. Returns `None` if `cancelled` was set in the meantime.
+    fn await_prerequisites(
+        &self,
+        tests: &[String],
+        cancelled: &AtomicBool,
+    ) -> Option<Result<(), (String, TestOutcome)>> {
+        let (lock, cvar) = &*self.0;
+        let mut outcomes = lock.lock().unwrap();
+        loop {
+            if cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
+            let not_passed = tests.iter().find_map(|test| match outcomes.get(test) {
+                Some(TestOutcome::Passed) | None => None,
+                Some(outcome) => Some((test.clone(), *outcome)),
+            });
+            if let Some(not_passed) = not_passed {
+                return Some(Err(not_passed));
+            }
+            if tests.iter().all(|test| outcomes.contains_key(test)) {
+                return Some(Ok(()));
+            }
+            outcomes = cvar
+                .wait_timeout(outcomes, CANCELLATION_POLL_INTERVAL)
+                .unwrap()
+                .0;
+        }
+    }
+}
+
+/// A test that only runs once its prerequisites passed. The time spent waiting
+/// counts towards the timeout of the test, but not towards its runtime.
+pub struct DependentTask {
+    inner: Arc<dyn Task>,
+    prerequisites: Vec<String>,
+    outcomes: TestOutcomes,
+    sub_fact: Arc<dyn BroadcastingEventSubscriberFactory>,
+    logger: Logger,
+}
+
+impl DependentTask {
+    pub fn new(
+        inner: Box<dyn Task>,
+        prerequisites: Vec<String>,
+        outcomes: TestOutcomes,
+        sub_fact: Arc<dyn BroadcastingEventSubscriberFactory>,
+        logger: Logger,
+    ) -> Self {
+        Self {
+            inner: Arc::from(inner),
+            prerequisites,
+            outcomes,
+            sub_fact,
+            logger,
+        }
+    }
+}
+
+enum DependentTaskState {
+    Waiting,
+    Running(Box<dyn TaskHandle>),
+    Finished,
+}
+
+impl Task for DependentTask {
+    fn spawn(&self) -> Box<dyn TaskHandle> {
+        let handle = Arc::new(DependentTaskHandle {
+            task_id: self.task_id(),
+            state: Mutex::new(DependentTaskState::Waiting),
+            cancelled: Default::default(),
+            sub_fact: self.sub_fact.clone(),
+        });
+        std::thread::spawn({
+            let handle = handle.clone();
+            let inner = self.inner.clone();
+            let prerequisites = self.prerequisites.clone();
+            let outcomes = self.outcomes.clone();
+            let logger = self.logger.clone();
+            move || {
+                let task_id = inner.task_id();
+                let result = match outcomes.await_prerequisites(&prerequisites, &handle.cancelled) {
+                    Some(result) => result,
+                    None => return,
+                };
+                let mut state = handle.state.lock().unwrap();
+                if !matches!(*state, DependentTaskState::Waiting) {
+                    return;
+                }
+                match result {
+                    Ok(()) => *state = DependentTaskState::Running(inner.spawn()),
+                    Err((prerequisite, outcome)) => {
+                        info!(
+                            logger,
+                            "Skipping {} as its prerequisite {} did not pass ({:?}).",
+                            task_id,
+                            prerequisite,
+                            outcome
+                        );
+                        let mut sub = handle.sub_fact.create_broadcasting_subscriber();
+                        (sub)(Event::task_spawned(task_id.clone()));
+                        (sub)(Event::task_sub_report(
+                            task_id.clone(),
+                            format!("prerequisite {} {:?}", prerequisite, outcome),
+                        ));
+                        (sub)(Event::task_skipped(task_id.clone()));
+                        (sub)(Event::task_stopped(task_id));
+                        *state = DependentTaskState::Finished;
+                    }
+                }
+            }
+        });
+        Box::new(SharedTaskHandle(handle))
+    }
+
+    fn execute(&self) -> Result<(), String> {
+        self.inner.execute()
+    }
+
+    fn task_id(&self) -> TaskId {
+        self.inner.task_id()
+    }
+}
+
+struct DependentTaskHandle {
+    task_id: TaskId,
+    state: Mutex<DependentTaskState>,
+    cancelled: AtomicBool,
+    sub_fact: Arc<dyn BroadcastingEventSubscriberFactory>,
+}
+
+impl DependentTaskHandle {
+    /// Forwards `f` to the running test. A test still waiting for its
+    /// prerequisites is cancelled and reported as failed.
+    fn finish(&self, f: impl FnOnce(&dyn TaskHandle)) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        let mut state = self.state.lock().unwrap();
+        match std::mem::replace(&mut *state, DependentTaskState::Finished) {
+            DependentTaskState::Running(handle) => f(handle.as_ref()),
+            DependentTaskState::Waiting => {
+                let mut sub = self.sub_fact.create_broadcasting_subscriber();
+                (sub)(Event::task_spawned(self.task_id.clone()));
+                (sub)(Event::task_failed(
+                    self.task_id.clone(),
+                    "Cancelled while waiting for the prerequisites.".to_string(),
+                ));
+            }
+            DependentTaskState::Finished => {}
+        }
+    }
+}
+
+struct SharedTaskHandle(Arc<DependentTaskHandle>);
+
+impl TaskHandle for SharedTaskHandle {
+    fn fail(&self) {
+        self.0.finish(|handle| handle.fail())
+    }
+
+    fn stop(&self) {
+        self.0.finish(|handle| handle.stop())
+    }
+}
+
+/// The position of a test in the plan of a group: the ordering and the index
+/// of the enclosing composition at each level, outermost first.
+pub type TestPosition = Vec<(EvalOrder, usize)>;
+
+/// Checks that the prerequisites of each test exist, do not depend on the test
+/// themselves and do not run after it in a sequential composition, as the
+/// test would wait for them forever.
+pub fn check_dependencies(
+    positions: &BTreeMap<String, TestPosition>,
+    dependencies: &BTreeMap<String, Vec<String>>,
+) -> Result<(), String> {
+    for (test, prerequisites) in dependencies {
+        for prerequisite in prerequisites {
+            let prerequisite_position = positions
+                .get(prerequisite)
+                .ok_or_else(|| format!("{} depends on the unknown test {}", test, prerequisite))?;
+            let diverging = positions[test]
+                .iter()
+                .zip(prerequisite_position)
+                .find(|(a, b)| a != b);
+            if let Some(((EvalOrder::Sequential, test_idx), (_, prerequisite_idx))) = diverging {
+                if test_idx < prerequisite_idx {
+                    return Err(format!(
+                        "{} runs before its prerequisite {}",
+                        test, prerequisite
+                    ));
+                }
+            }
+        }
+    }
+    // A depth-first search for cycles, where `path` is the current chain of
+    // dependencies.
+    fn visit<'a>(
+        test: &'a String,
+        dependencies: &'a BTreeMap<String, Vec<String>>,
+        path: &mut Vec<&'a String>,
+    ) -> Result<(), String> {
+        if path.contains(&test) {
+            path.push(test);
+            let cycle: Vec<&str> = path.iter().map(|t| t.as_str()).collect();
+            return Err(format!("cyclic dependencies {}", cycle.join(" -> ")));
+        }
+        path.push(test);
+        for prerequisite in dependencies.get(test).into_iter().flatten() {
+            visit(prerequisite, dependencies, path)?;
+        }
+        path.pop();
+        Ok(())
+    }
+    for test in dependencies.keys() {
+        visit(test, dependencies, &mut vec![])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(tests: &[(&str, TestPosition)]) -> BTreeMap<String, TestPosition> {
+        tests
+            .iter()
+            .map(|(name, position)| (name.to_string(), position.clone()))
+            .collect()
+    }
+
+    fn dependencies(deps: &[(&str, &str)]) -> BTreeMap<String, Vec<String>> {
+        let mut dependencies: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (test, prerequisite) in deps {
+            dependencies
+                .entry(test.to_string())
+                .or_default()
+                .push(prerequisite.to_string());
+        }
+        dependencies
+    }
+
+    #[test]
+    fn invalid_dependencies_are_rejected() {
+        use EvalOrder::*;
+        // The group runs t1, then t2 and t3 in parallel.
+        let positions = positions(&[
+            ("t1", vec![(Sequential, 1)]),
+            ("t2", vec![(Sequential, 2), (Parallel, 0)]),
+            ("t3", vec![(Sequential, 2), (Parallel, 1)]),
+        ]);
+        assert!(check_dependencies(&positions, &dependencies(&[("t2", "t1")])).is_ok());
+        assert!(check_dependencies(&positions, &dependencies(&[("t3", "t2")])).is_ok());
+        assert_eq!(
+            check_dependencies(&positions, &dependencies(&[("t1", "t2")])),
+            Err("t1 runs before its prerequisite t2".to_string())
+        );
+        assert_eq!(
+            check_dependencies(&positions, &dependencies(&[("t2", "t4")])),
+            Err("t2 depends on the unknown test t4".to_string())
+        );
+        assert_eq!(
+            check_dependencies(&positions, &dependencies(&[("t2", "t3"), ("t3", "t2")])),
+            Err("cyclic dependencies t2 -> t3 -> t2".to_string())
+        );
+    }
+
+    #[test]
+    fn outcomes_are_recorded_once() {
+        let outcomes = TestOutcomes::default();
+        let mut sub = outcomes.subscriber();
+        let task_id = TaskId::Test("t1".to_string());
+        (sub)(Event::task_skipped(task_id.clone()));
+        (sub)(Event::task_stopped(task_id));
+        (sub)(Event::task_stopped(TaskId::Test("t2".to_string())));
+        (sub)(Event::task_stop_requested(TaskId::Test("t3".to_string())));
+        (sub)(Event::task_stopped(TaskId::Test("t3".to_string())));
+        assert_eq!(
+            outcomes.await_prerequisites(&["t3".to_string()], &AtomicBool::new(false)),
+            Some(Err(("t3".to_string(), TestOutcome::Failed)))
+        );
+        let tests = vec!["t2".to_string(), "t1".to_string()];
+        assert_eq!(
+            outcomes.await_prerequisites(&tests, &AtomicBool::new(false)),
+            Some(Err(("t1".to_string(), TestOutcome::Skipped)))
+        );
+        assert_eq!(
+            outcomes.await_prerequisites(&tests[..1], &AtomicBool::new(false)),
+            Some(Ok(()))
+        );
+    }
+}
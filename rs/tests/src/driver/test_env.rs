@@ -3,7 +3,7 @@ use ic_prep_lib::prep_state_directory::IcPrepStateDir;
 use rand::{RngCore, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 use serde::de::DeserializeOwned;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use slog::{info, o, Drain, Logger};
 use slog_async::OverflowStrategy;
 use std::fs::{self, File};
@@ -206,18 +206,37 @@ impl HasTestPath for TestEnv {
     }
 }
 
+/// The seed of the randomness of a system test group. It is taken from the
+/// `--seed` argument of the test driver or chosen randomly and is recorded in
+/// the results, such that a failed run can be reproduced.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TestSeed(pub u64);
+
+impl TestEnvAttribute for TestSeed {
+    fn attribute_name() -> String {
+        "test_seed".to_string()
+    }
+}
+
+/// The seed used in environments without a [TestSeed].
+const DEFAULT_SEED: u64 = 42;
+
 pub trait HasDefaultRng {
+    /// Returns the seed of the random number generator returned by
+    /// `default_rng`.
+    fn default_rng_seed(&self) -> u64;
+
     /// Returns a random number generator the seed of which is either constant
     /// or depends on the state of the underlying object.
-    fn default_rng(&self) -> Box<dyn RngCore>;
+    fn default_rng(&self) -> Box<dyn RngCore> {
+        Box::new(ChaCha8Rng::seed_from_u64(self.default_rng_seed()))
+    }
 }
 
 impl HasDefaultRng for TestEnv {
-    /// Returns a random number generator based on a constant seed. At a later
-    /// point, the seed will be configured through the underlying test
-    /// environment.
-    fn default_rng(&self) -> Box<dyn RngCore> {
-        Box::new(ChaCha8Rng::seed_from_u64(42))
+    /// Returns the [TestSeed] of the environment.
+    fn default_rng_seed(&self) -> u64 {
+        TestSeed::try_read_attribute(self).map_or(DEFAULT_SEED, |seed| seed.0)
     }
 }
 
@@ -947,13 +947,19 @@ impl<T: HasDependencies + HasTestEnv> HasIcDependencies for T {
 }
 
 pub trait HasGroupSetup {
-    fn create_group_setup(&self);
+    /// Creates the Farm group of the test with the TTL `group_timeout`,
+    /// allocating its VMs on preemptible resources if `preemptible_vms`.
+    fn create_group_setup(&self, preemptible_vms: bool, group_timeout: Duration);
 }
 
 impl HasGroupSetup for TestEnv {
-    fn create_group_setup(&self) {
+    fn create_group_setup(&self, preemptible_vms: bool, group_timeout: Duration) {
         let log = self.logger();
-        let group_setup = GroupSetup::from_bazel_env();
+        let group_setup = GroupSetup {
+            preemptible_vms,
+            group_timeout,
+            ..GroupSetup::from_bazel_env()
+        };
         let farm_base_url = FarmBaseUrl::read_attribute(self);
         let farm = Farm::new(farm_base_url.into(), self.logger());
         let group_spec = GroupSpec {
@@ -961,6 +967,7 @@ impl HasGroupSetup for TestEnv {
             required_host_features: vec![],
             preferred_network: None,
             metadata: None,
+            preemptible: preemptible_vms,
         };
         farm.create_group(
             &group_setup.farm_group_name,
@@ -1321,6 +1328,17 @@ pub trait NnsInstallationExt {
 pub struct NnsCustomizations {
     /// Summarizes the custom parameters that a newly installed NNS should have.
     pub ledger_balances: Option<HashMap<AccountIdentifier, Tokens>>,
+    /// The WASMs of the canisters that do not follow the
+    /// [NnsCanisterWasmStrategy] of the installation, e.g., to test a
+    /// governance canister built from sources against the mainnet ledger.
+    pub canister_wasms: HashMap<NnsCanister, NnsCanisterWasm>,
+}
+
+impl NnsCustomizations {
+    pub fn with_canister_wasm(mut self, canister: NnsCanister, wasm: NnsCanisterWasm) -> Self {
+        self.canister_wasms.insert(canister, wasm);
+        self
+    }
 }
 
 pub enum NnsCanisterWasmStrategy {
@@ -1328,6 +1346,45 @@ pub enum NnsCanisterWasmStrategy {
     TakeLatestMainnetDeployments,
 }
 
+/// The canisters installed by [NnsInstallationExt].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NnsCanister {
+    Registry,
+    Governance,
+    Ledger,
+    Root,
+    CyclesMinting,
+    Lifeline,
+    GenesisToken,
+    SnsWasm,
+}
+
+impl NnsCanister {
+    /// The name of the canister in the `nns-canisters` and
+    /// `mainnet-nns-canisters` dependencies.
+    fn dependency_name(&self) -> &'static str {
+        match self {
+            NnsCanister::Registry => "registry-canister",
+            NnsCanister::Governance => "governance-canister_test",
+            NnsCanister::Ledger => "ledger-canister_notify-method",
+            NnsCanister::Root => "root-canister",
+            NnsCanister::CyclesMinting => "cycles-minting-canister",
+            NnsCanister::Lifeline => "lifeline_canister",
+            NnsCanister::GenesisToken => "genesis-token-canister",
+            NnsCanister::SnsWasm => "sns-wasm-canister",
+        }
+    }
+}
+
+/// The WASM installed for a single [NnsCanister].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NnsCanisterWasm {
+    BuiltFromSources,
+    LatestMainnetDeployment,
+    /// A WASM file on the local disk, e.g., built from another commit.
+    Path(PathBuf),
+}
+
 impl<T> NnsInstallationExt for T
 where
     T: HasIcName + HasPublicApiUrl,
@@ -1346,6 +1403,9 @@ where
                 test_env.set_mainnet_nns_canisters_env_vars()?;
             }
         }
+        for (canister, wasm) in &customizations.canister_wasms {
+            test_env.set_nns_canister_env_var(*canister, wasm)?;
+        }
         let log = test_env.logger();
         let ic_name = self.ic_name();
         let url = self.get_public_url();
@@ -1400,6 +1460,9 @@ where
 pub trait NnsCanisterEnvVars {
     fn set_nns_canisters_env_vars(&self) -> Result<()>;
     fn set_mainnet_nns_canisters_env_vars(&self) -> Result<()>;
+    /// Overrides the WASM of `canister` set by one of the functions above.
+    fn set_nns_canister_env_var(&self, canister: NnsCanister, wasm: &NnsCanisterWasm)
+        -> Result<()>;
 }
 
 impl NnsCanisterEnvVars for TestEnv {
@@ -1410,6 +1473,36 @@ impl NnsCanisterEnvVars for TestEnv {
     fn set_mainnet_nns_canisters_env_vars(&self) -> Result<()> {
         self.set_canister_env_vars("rs/tests/mainnet-nns-canisters")
     }
+
+    fn set_nns_canister_env_var(
+        &self,
+        canister: NnsCanister,
+        wasm: &NnsCanisterWasm,
+    ) -> Result<()> {
+        let name = canister.dependency_name();
+        let path = match wasm {
+            NnsCanisterWasm::BuiltFromSources => std::fs::read_link(
+                self.get_dependency_path("rs/tests/nns-canisters")
+                    .join(name),
+            )?,
+            NnsCanisterWasm::LatestMainnetDeployment => std::fs::read_link(
+                self.get_dependency_path("rs/tests/mainnet-nns-canisters")
+                    .join(name),
+            )?,
+            NnsCanisterWasm::Path(path) => {
+                if !path.is_file() {
+                    bail!(
+                        "WASM {:?} of the {:?} canister does not exist.",
+                        path,
+                        canister
+                    );
+                }
+                path.clone()
+            }
+        };
+        std::env::set_var(canister_wasm_env_var(name), path);
+        Ok(())
+    }
 }
 
 pub trait SnsCanisterEnvVars {
@@ -1434,16 +1527,20 @@ impl<T: HasDependencies> CanisterEnvVars for T {
             let canister_name = file_name
                 .to_str()
                 .expect("Couldn't convert file path to canister name!");
-            let env_name = format!("{}_WASM_PATH", canister_name)
-                .replace('-', "_")
-                .to_uppercase();
             let path = std::fs::read_link(dir.join(file_name))?;
-            std::env::set_var(env_name, path);
+            std::env::set_var(canister_wasm_env_var(canister_name), path);
         }
         Ok(())
     }
 }
 
+/// The environment variable holding the path of the WASM of `canister_name`.
+fn canister_wasm_env_var(canister_name: &str) -> String {
+    format!("{}_WASM_PATH", canister_name)
+        .replace('-', "_")
+        .to_uppercase()
+}
+
 pub trait HasRegistryVersion {
     fn get_registry_version(&self) -> RegistryVersion;
 }
@@ -1570,6 +1667,84 @@ where
     }
 }
 
+/* ### Node Control ### */
+
+/// The systemd unit running the orchestrator, which in turn runs the replica.
+const IC_REPLICA_SERVICE: &str = "ic-replica.service";
+
+/// NodeControl enables a user to stop, crash and restart the software of an IC
+/// node, as opposed to [VmControl], which acts on the whole VM. The functions
+/// returning once the node reached the expected state wait for at most
+/// [READY_WAIT_TIMEOUT].
+pub trait NodeControl {
+    /// Stops the orchestrator and the replica gracefully and waits until the
+    /// node is unavailable.
+    fn stop_node(&self) -> Result<()>;
+
+    /// Starts the orchestrator and the replica and waits until the node is
+    /// healthy.
+    fn start_node(&self) -> Result<()>;
+
+    /// Restarts the orchestrator and the replica gracefully and waits until
+    /// the node is healthy.
+    fn restart_node(&self) -> Result<()>;
+
+    /// Kills the orchestrator and the replica with SIGKILL, simulating a crash.
+    /// As systemd restarts them, the node recovers on its own.
+    fn kill_node(&self) -> Result<()>;
+
+    /// Kills the node like [NodeControl::kill_node] and waits until it is
+    /// healthy again.
+    fn kill_node_and_await_recovery(&self) -> Result<()>;
+
+    /// Reboots the operating system of the node gracefully and waits until the
+    /// node is healthy again.
+    fn reboot_node_and_await_recovery(&self) -> Result<()>;
+}
+
+impl NodeControl for IcNodeSnapshot {
+    fn stop_node(&self) -> Result<()> {
+        self.block_on_bash_script(&format!("sudo systemctl stop {IC_REPLICA_SERVICE}"))?;
+        self.await_status_is_unavailable()
+    }
+
+    fn start_node(&self) -> Result<()> {
+        self.block_on_bash_script(&format!("sudo systemctl start {IC_REPLICA_SERVICE}"))?;
+        self.await_status_is_healthy()
+    }
+
+    fn restart_node(&self) -> Result<()> {
+        self.block_on_bash_script(&format!("sudo systemctl restart {IC_REPLICA_SERVICE}"))?;
+        self.await_status_is_healthy()
+    }
+
+    fn kill_node(&self) -> Result<()> {
+        info!(self.env.logger(), "Killing node {} ...", self.node_id);
+        // The unit may only be started 5 times within 60s (`StartLimitBurst`
+        // and `StartLimitIntervalSec`), after which systemd gives up on it.
+        // Resetting the start rate counter allows tests to kill nodes more
+        // often than that.
+        self.block_on_bash_script(&format!(
+            "sudo systemctl kill --signal=SIGKILL {IC_REPLICA_SERVICE} && sudo systemctl reset-failed {IC_REPLICA_SERVICE}"
+        ))?;
+        Ok(())
+    }
+
+    fn kill_node_and_await_recovery(&self) -> Result<()> {
+        self.kill_node()?;
+        self.await_status_is_healthy()
+    }
+
+    fn reboot_node_and_await_recovery(&self) -> Result<()> {
+        info!(self.env.logger(), "Rebooting node {} ...", self.node_id);
+        // Reboot with a delay, such that the SSH command returns before the
+        // connection is torn down.
+        self.block_on_bash_script("sudo systemd-run --on-active=1 systemctl reboot")?;
+        self.await_status_is_unavailable()?;
+        self.await_status_is_healthy()
+    }
+}
+
 pub fn get_ssh_session_from_env(env: &TestEnv, ip: IpAddr) -> Result<Session> {
     let tcp = TcpStream::connect((ip, 22))?;
     let mut sess = Session::new()?;
@@ -1673,6 +1848,11 @@ pub fn secs(sec: u64) -> Duration {
     Duration::from_secs(sec)
 }
 
+/// Quotes `arg` for use as a single word in a bash script.
+pub fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r#"'\''"#))
+}
+
 impl<T> RegistryResultHelper<T> for RegistryClientResult<T> {
     fn unwrap_result(self) -> T {
         self.expect("registry error!")
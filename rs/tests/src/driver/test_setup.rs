@@ -8,10 +8,14 @@ use crate::driver::ic::VmResources;
 #[derive(Clone, Deserialize, Serialize, Default, Debug)]
 pub struct GroupSetup {
     pub farm_group_name: String,
-    /// For now, the group timeout strictly translates to the corresponding group
-    /// TTL.
+    /// The TTL the group was created with, which covers the planned runtime
+    /// of the setup and the tests.
     pub group_timeout: Duration,
     pub default_vm_resources: Option<VmResources>,
+    /// Whether the VMs are allocated on preemptible resources, such that
+    /// preempted VMs need to be re-allocated while the tests run.
+    #[serde(default)]
+    pub preemptible_vms: bool,
 }
 
 impl GroupSetup {
@@ -0,0 +1,328 @@
+//! A declarative description of the testnet of a system test, such that the
+//! common topologies do not need to be assembled by hand in every setup
+//! function:
+//!
+//! ```ignore
+//! Testnet::new()
+//!     .with_system_subnet(4)
+//!     .with_application_subnets(2, 7)
+//!     .with_unassigned_nodes(3)
+//!     .with_boundary_nodes(1)
+//!     .with_boundary_node_real_certs_and_dns()
+//!     .with_prometheus()
+//!     .with_httpbin()
+//!     .with_subnet_latencies(
+//!         LatencyMatrix::new().with_latency(0, 1, Duration::from_millis(40), Duration::ZERO),
+//!     )
+//!     .setup_and_start(&env)
+//!     .expect("failed to set up the testnet");
+//! ```
+//!
+//! The description is validated before any VM is allocated. Topologies that do
+//! not fit this shape can still be built with [InternetComputer] directly.
+
+use anyhow::{bail, Result};
+use ic_registry_subnet_features::SubnetFeatures;
+use ic_registry_subnet_type::SubnetType;
+use slog::info;
+
+use crate::driver::{
+    auxiliary_services::{BitcoindVm, HasAuxiliaryServices, HttpbinVm},
+    boundary_node::{BoundaryNode, BoundaryNodeVm},
+    ic::{InternetComputer, Subnet, VmResources},
+    network_chaos::LatencyMatrix,
+    prometheus_vm::{HasPrometheus, PrometheusVm},
+    test_env::TestEnv,
+    test_env_api::{HasPublicApiUrl, HasTopologySnapshot, IcNodeContainer, NnsInstallationExt},
+};
+
+/// The name of the `i`-th boundary node of a [Testnet].
+pub fn boundary_node_name(i: usize) -> String {
+    format!("boundary-node-{}", i)
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Testnet {
+    system_subnet_size: usize,
+    application_subnets: usize,
+    application_subnet_size: usize,
+    application_subnet_features: Option<SubnetFeatures>,
+    unassigned_nodes: usize,
+    boundary_nodes: usize,
+    boundary_node_real_certs_and_dns: bool,
+    boundary_node_vm_resources: VmResources,
+    install_nns_canisters: bool,
+    prometheus: bool,
+    httpbin: bool,
+    bitcoind: bool,
+    default_vm_resources: VmResources,
+    subnet_latencies: LatencyMatrix,
+}
+
+impl Testnet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a system subnet of `size` nodes, which hosts the NNS.
+    pub fn with_system_subnet(mut self, size: usize) -> Self {
+        self.system_subnet_size = size;
+        self
+    }
+
+    /// Add `count` application subnets of `size` nodes each.
+    pub fn with_application_subnets(mut self, count: usize, size: usize) -> Self {
+        self.application_subnets = count;
+        self.application_subnet_size = size;
+        self
+    }
+
+    /// Set the features of all application subnets.
+    pub fn with_application_subnet_features(mut self, features: SubnetFeatures) -> Self {
+        self.application_subnet_features = Some(features);
+        self
+    }
+
+    pub fn with_unassigned_nodes(mut self, count: usize) -> Self {
+        self.unassigned_nodes = count;
+        self
+    }
+
+    /// Add `count` boundary nodes named [boundary_node_name], routing to all
+    /// subnets of the IC. Boundary nodes need the registry canister, hence the
+    /// NNS canisters are installed. The setup waits until the boundary nodes
+    /// fetched their routes and report healthy.
+    pub fn with_boundary_nodes(mut self, count: usize) -> Self {
+        self.boundary_nodes = count;
+        self
+    }
+
+    /// Serve the boundary nodes under a playnet domain with a real certificate
+    /// instead of a self-signed one, see
+    /// [BoundaryNodeWithVm::use_real_certs_and_dns](crate::driver::boundary_node::BoundaryNodeWithVm::use_real_certs_and_dns).
+    pub fn with_boundary_node_real_certs_and_dns(mut self) -> Self {
+        self.boundary_node_real_certs_and_dns = true;
+        self
+    }
+
+    /// Set the VM resources of the boundary nodes.
+    pub fn with_boundary_node_vm_resources(mut self, vm_resources: VmResources) -> Self {
+        self.boundary_node_vm_resources = vm_resources;
+        self
+    }
+
+    /// Install the NNS canisters on the system subnet.
+    pub fn with_nns_canisters(mut self) -> Self {
+        self.install_nns_canisters = true;
+        self
+    }
+
+    /// Start a Prometheus VM scraping all nodes of the IC, such that tests can
+    /// assert on metrics with [HasPrometheus::await_metric].
+    pub fn with_prometheus(mut self) -> Self {
+        self.prometheus = true;
+        self
+    }
+
+    /// Start a [HttpbinVm] as a target of HTTPS outcalls. Outcalls further need
+    /// the `http_requests` feature, see [Testnet::with_application_subnet_features].
+    pub fn with_httpbin(mut self) -> Self {
+        self.httpbin = true;
+        self
+    }
+
+    /// Start a [BitcoindVm] in regtest mode and connect the Bitcoin adapters of
+    /// all nodes to it.
+    pub fn with_bitcoind(mut self) -> Self {
+        self.bitcoind = true;
+        self
+    }
+
+    /// Delay the traffic between the subnets as given by `latencies`, where the
+    /// system subnet, if any, comes first and is followed by the application
+    /// subnets. The latencies are set up once all nodes are reachable, before
+    /// the NNS canisters are installed.
+    pub fn with_subnet_latencies(mut self, latencies: LatencyMatrix) -> Self {
+        self.subnet_latencies = latencies;
+        self
+    }
+
+    /// Set the VM resources of all nodes of the IC.
+    pub fn with_default_vm_resources(mut self, default_vm_resources: VmResources) -> Self {
+        self.default_vm_resources = default_vm_resources;
+        self
+    }
+
+    /// Checks that the described testnet can be set up.
+    pub fn validate(&self) -> Result<()> {
+        if self.system_subnet_size == 0 && self.application_subnets == 0 {
+            bail!("The testnet has no subnets.");
+        }
+        if self.application_subnets > 0 && self.application_subnet_size == 0 {
+            bail!("Application subnets need at least one node.");
+        }
+        if self.application_subnet_features.is_some() && self.application_subnets == 0 {
+            bail!("Application subnet features are set, but there are no application subnets.");
+        }
+        if self.system_subnet_size == 0 && (self.install_nns_canisters || self.boundary_nodes > 0) {
+            bail!("The NNS canisters and boundary nodes need a system subnet.");
+        }
+        if self.boundary_node_real_certs_and_dns && self.boundary_nodes == 0 {
+            bail!("Real certificates are requested, but there are no boundary nodes.");
+        }
+        let subnets = usize::from(self.system_subnet_size > 0) + self.application_subnets;
+        self.subnet_latencies.validate(subnets)?;
+        Ok(())
+    }
+
+    /// Returns the IC of this testnet, without the boundary nodes.
+    pub fn internet_computer(&self) -> InternetComputer {
+        let mut ic = InternetComputer::new().with_default_vm_resources(self.default_vm_resources);
+        if self.system_subnet_size > 0 {
+            ic = ic.add_subnet(self.subnet(SubnetType::System, self.system_subnet_size));
+        }
+        for _ in 0..self.application_subnets {
+            let mut subnet = self.subnet(SubnetType::Application, self.application_subnet_size);
+            subnet.features = self.application_subnet_features;
+            ic = ic.add_subnet(subnet);
+        }
+        ic.with_unassigned_nodes(self.unassigned_nodes as i32)
+    }
+
+    fn subnet(&self, subnet_type: SubnetType, size: usize) -> Subnet {
+        Subnet::new(subnet_type)
+            .with_default_vm_resources(self.default_vm_resources)
+            .add_nodes(size)
+    }
+
+    /// Validates the testnet, starts the auxiliary services, sets up the IC,
+    /// installs the NNS canisters if needed and starts the boundary nodes and
+    /// the Prometheus VM.
+    pub fn setup_and_start(&self, env: &TestEnv) -> Result<()> {
+        self.validate()?;
+        let log = env.logger();
+        if self.prometheus {
+            PrometheusVm::default().start(env)?;
+        }
+        if self.httpbin {
+            HttpbinVm::default().start(env)?;
+        }
+        let mut ic = self.internet_computer();
+        if self.bitcoind {
+            BitcoindVm::default().start(env)?;
+            ic = ic.with_bitcoind_addr(env.bitcoind_addr()?);
+        }
+        ic.setup_and_start(env)?;
+        if self.prometheus {
+            env.sync_prometheus_config_with_topology();
+        }
+        if !self.subnet_latencies.is_empty() {
+            info!(log, "Setting up the latencies between the subnets ...");
+            let subnets: Vec<Vec<_>> = env
+                .topology_snapshot()
+                .subnets()
+                .map(|subnet| subnet.nodes().collect())
+                .collect();
+            self.subnet_latencies.apply(&subnets)?;
+        }
+        if self.install_nns_canisters || self.boundary_nodes > 0 {
+            info!(log, "Installing the NNS canisters ...");
+            let nns_node = env
+                .topology_snapshot()
+                .root_subnet()
+                .nodes()
+                .next()
+                .expect("the system subnet has no nodes");
+            nns_node.await_status_is_healthy()?;
+            nns_node.install_nns_canisters()?;
+        }
+        for i in 0..self.boundary_nodes {
+            let bn = BoundaryNode::new(boundary_node_name(i))
+                .with_vm_resources(self.boundary_node_vm_resources)
+                .allocate_vm(env)?
+                .for_ic(env, "");
+            if self.boundary_node_real_certs_and_dns {
+                bn.use_real_certs_and_dns().start(env)?;
+            } else {
+                bn.start(env)?;
+            }
+        }
+        for i in 0..self.boundary_nodes {
+            env.get_deployed_boundary_node(&boundary_node_name(i))?
+                .get_snapshot()?
+                .await_ready()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn invalid_testnets_are_rejected() {
+        assert!(Testnet::new().validate().is_err());
+        assert!(Testnet::new()
+            .with_application_subnets(2, 0)
+            .validate()
+            .is_err());
+        assert!(Testnet::new()
+            .with_application_subnets(1, 4)
+            .with_boundary_nodes(1)
+            .validate()
+            .is_err());
+        assert!(Testnet::new()
+            .with_system_subnet(1)
+            .with_application_subnet_features(SubnetFeatures::default())
+            .validate()
+            .is_err());
+        assert!(Testnet::new()
+            .with_system_subnet(1)
+            .with_application_subnets(1, 4)
+            .with_subnet_latencies(LatencyMatrix::new().with_latency(
+                0,
+                2,
+                Duration::from_millis(40),
+                Duration::ZERO
+            ))
+            .validate()
+            .is_err());
+        assert!(Testnet::new()
+            .with_system_subnet(1)
+            .with_boundary_node_real_certs_and_dns()
+            .validate()
+            .is_err());
+        assert!(Testnet::new()
+            .with_system_subnet(4)
+            .with_application_subnets(2, 7)
+            .with_boundary_nodes(1)
+            .with_boundary_node_real_certs_and_dns()
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn internet_computer_has_the_declared_topology() {
+        let ic = Testnet::new()
+            .with_system_subnet(4)
+            .with_application_subnets(2, 7)
+            .with_unassigned_nodes(3)
+            .internet_computer();
+        let subnets: Vec<_> = ic
+            .subnets
+            .iter()
+            .map(|s| (s.subnet_type, s.nodes.len()))
+            .collect();
+        assert_eq!(
+            subnets,
+            vec![
+                (SubnetType::System, 4),
+                (SubnetType::Application, 7),
+                (SubnetType::Application, 7)
+            ]
+        );
+        assert_eq!(ic.unassigned_nodes.len(), 3);
+    }
+}
@@ -0,0 +1,191 @@
+//! A machine-readable description of the testnet provisioned by the setup of a
+//! group, such that external tools, e.g., load generators or debugging
+//! scripts, can find the nodes and endpoints without parsing the logs. It is
+//! written to [TESTNET_DESCRIPTION_FILE] in the setup directory at the end of
+//! the setup.
+
+use std::{
+    fs,
+    net::IpAddr,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use slog::{info, warn};
+use url::Url;
+
+use crate::driver::{
+    boundary_node::{BoundaryNodeVm, BOUNDARY_NODE_VMS_DIR},
+    keepalive::vm_names,
+    test_env::{HasIcPrepDir, TestEnv, TestEnvAttribute},
+    test_env_api::{
+        HasMetricsUrl, HasPublicApiUrl, HasRegistryVersion, HasTopologySnapshot, IcNodeContainer,
+        IcNodeSnapshot,
+    },
+    test_setup::GroupSetup,
+    universal_vm::{UniversalVms, UNIVERSAL_VMS_DIR},
+};
+
+/// Name of the file within the setup directory holding the description.
+pub const TESTNET_DESCRIPTION_FILE: &str = "testnet.json";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TestnetDescription {
+    pub farm_group: Option<String>,
+    /// The registry version of the topology, if the group deployed an IC.
+    pub registry_version: Option<u64>,
+    pub subnets: Vec<SubnetDescription>,
+    pub unassigned_nodes: Vec<NodeDescription>,
+    pub boundary_nodes: Vec<VmDescription>,
+    pub universal_vms: Vec<VmDescription>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SubnetDescription {
+    pub subnet_id: String,
+    pub subnet_type: String,
+    pub nodes: Vec<NodeDescription>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NodeDescription {
+    pub node_id: String,
+    pub ip: IpAddr,
+    pub public_url: Url,
+    pub metrics_url: Option<Url>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VmDescription {
+    pub name: String,
+    pub ip: IpAddr,
+    /// The URL of the API endpoint, for boundary nodes.
+    pub public_url: Option<Url>,
+}
+
+impl NodeDescription {
+    fn of(node: &IcNodeSnapshot) -> Self {
+        Self {
+            node_id: node.node_id.to_string(),
+            ip: node.get_ip_addr(),
+            public_url: node.get_public_url(),
+            metrics_url: node.get_metrics_url(),
+        }
+    }
+}
+
+impl TestnetDescription {
+    /// Describes the testnet deployed in `env`. VMs whose deployment cannot be
+    /// read are left out.
+    pub fn of(env: &TestEnv) -> Self {
+        let mut description = Self {
+            farm_group: GroupSetup::try_read_attribute(env)
+                .ok()
+                .map(|group_setup| group_setup.farm_group_name),
+            ..Default::default()
+        };
+        if env.prep_dir("").is_some() {
+            let topology = env.topology_snapshot();
+            description.registry_version = Some(topology.get_registry_version().get());
+            description.subnets = topology
+                .subnets()
+                .map(|subnet| SubnetDescription {
+                    subnet_id: subnet.subnet_id.to_string(),
+                    subnet_type: format!("{:?}", subnet.subnet_type()),
+                    nodes: subnet
+                        .nodes()
+                        .map(|node| NodeDescription::of(&node))
+                        .collect(),
+                })
+                .collect();
+            description.unassigned_nodes = topology
+                .unassigned_nodes()
+                .map(|node| NodeDescription::of(&node))
+                .collect();
+        }
+        description.boundary_nodes = vm_names(&env.get_path(BOUNDARY_NODE_VMS_DIR))
+            .into_iter()
+            .filter_map(|name| {
+                let bn = env
+                    .get_deployed_boundary_node(&name)
+                    .and_then(|bn| bn.get_snapshot())
+                    .ok()?;
+                Some(VmDescription {
+                    ip: IpAddr::V6(bn.ipv6()),
+                    public_url: Some(bn.get_public_url()),
+                    name,
+                })
+            })
+            .collect();
+        description.universal_vms = vm_names(&env.get_path(UNIVERSAL_VMS_DIR))
+            .into_iter()
+            .filter_map(|name| {
+                let vm = env
+                    .get_deployed_universal_vm(&name)
+                    .and_then(|vm| vm.get_vm())
+                    .ok()?;
+                Some(VmDescription {
+                    ip: IpAddr::V6(vm.ipv6),
+                    public_url: None,
+                    name,
+                })
+            })
+            .collect();
+        description
+    }
+
+    pub fn write(&self, env: &TestEnv) -> Result<()> {
+        let path = env.get_path(TESTNET_DESCRIPTION_FILE);
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        info!(env.logger(), "Wrote the testnet description to {:?}", path);
+        Ok(())
+    }
+}
+
+/// Writes the description of the testnet deployed in `env`. Failures are only
+/// logged, such that they do not fail the setup.
+pub fn write_testnet_description(env: &TestEnv) {
+    // Obtaining the topology panics if the registry local store is unreadable.
+    let result = catch_unwind(AssertUnwindSafe(|| TestnetDescription::of(env)))
+        .map_err(|_| anyhow!("failed to read the topology"))
+        .and_then(|description| description.write(env));
+    if let Err(e) = result {
+        warn!(
+            env.logger(),
+            "Failed to write the testnet description: {:?}", e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn description_round_trips_through_json() {
+        let node = NodeDescription {
+            node_id: "node-1".to_string(),
+            ip: "2a00:fb01::1".parse().unwrap(),
+            public_url: Url::parse("http://[2a00:fb01::1]:8080/").unwrap(),
+            metrics_url: None,
+        };
+        let description = TestnetDescription {
+            farm_group: Some("group".to_string()),
+            registry_version: Some(1),
+            subnets: vec![SubnetDescription {
+                subnet_id: "subnet-1".to_string(),
+                subnet_type: "System".to_string(),
+                nodes: vec![node.clone()],
+            }],
+            unassigned_nodes: vec![node],
+            ..Default::default()
+        };
+        let json = serde_json::to_string_pretty(&description).unwrap();
+        assert!(json.contains("\"public_url\": \"http://[2a00:fb01::1]:8080/\""));
+        assert_eq!(
+            serde_json::from_str::<TestnetDescription>(&json).unwrap(),
+            description
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! Snapshots of all VMs of a testnet, including their disks and memory, such
+//! that a testnet can be reset to the state right after the setup instead of
+//! being set up again. See [SystemTestGroup::with_testnet_snapshot](crate::driver::group::SystemTestGroup::with_testnet_snapshot).
+
+use std::{fs, thread};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use slog::{info, warn};
+
+use crate::driver::{
+    boundary_node::BOUNDARY_NODE_VMS_DIR,
+    farm::{Farm, FarmResult},
+    test_env::{HasIcPrepDir, TestEnv, TestEnvAttribute},
+    test_env_api::{HasIcDependencies, HasTopologySnapshot, HasVmName, IcNodeContainer},
+    test_setup::GroupSetup,
+    universal_vm::UNIVERSAL_VMS_DIR,
+};
+
+/// A snapshot of the VMs of the testnet, such that restoring it covers exactly
+/// the VMs that existed when the snapshot was taken.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TestnetSnapshot {
+    pub name: String,
+    pub vms: Vec<String>,
+}
+
+impl TestEnvAttribute for TestnetSnapshot {
+    fn attribute_name() -> String {
+        "testnet_snapshot".to_string()
+    }
+}
+
+pub trait HasTestnetSnapshot {
+    /// Snapshots all VMs of the testnet under the name `name` and records the
+    /// snapshot in this environment.
+    fn snapshot_testnet(&self, name: &str) -> Result<TestnetSnapshot>;
+
+    /// Restores all VMs of the testnet from the snapshot recorded in this
+    /// environment and waits until the nodes of the IC are healthy again.
+    fn restore_testnet(&self) -> Result<()>;
+}
+
+impl HasTestnetSnapshot for TestEnv {
+    fn snapshot_testnet(&self, name: &str) -> Result<TestnetSnapshot> {
+        let snapshot = TestnetSnapshot {
+            name: name.to_string(),
+            vms: testnet_vms(self),
+        };
+        info!(
+            self.logger(),
+            "Taking snapshot {} of {} VMs ...",
+            name,
+            snapshot.vms.len()
+        );
+        for_each_vm(self, &snapshot, |farm, group_name, vm_name| {
+            farm.snapshot_vm(group_name, vm_name, name)
+        })?;
+        snapshot.write_attribute(self);
+        Ok(snapshot)
+    }
+
+    fn restore_testnet(&self) -> Result<()> {
+        let snapshot = TestnetSnapshot::try_read_attribute(self)?;
+        info!(
+            self.logger(),
+            "Restoring snapshot {} of {} VMs ...",
+            snapshot.name,
+            snapshot.vms.len()
+        );
+        for_each_vm(self, &snapshot, |farm, group_name, vm_name| {
+            farm.restore_vm(group_name, vm_name, &snapshot.name)
+        })?;
+        if self.prep_dir("").is_some() {
+            for subnet in self.topology_snapshot().subnets() {
+                subnet.await_all_nodes_healthy()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the names of the VMs of the IC nodes, universal VMs and boundary
+/// nodes deployed in `env`.
+fn testnet_vms(env: &TestEnv) -> Vec<String> {
+    let mut vms = vec![];
+    if env.prep_dir("").is_some() {
+        let topology = env.topology_snapshot();
+        vms.extend(
+            topology
+                .subnets()
+                .flat_map(|subnet| subnet.nodes())
+                .chain(topology.unassigned_nodes())
+                .map(|node| node.vm_name()),
+        );
+    }
+    for dir in [UNIVERSAL_VMS_DIR, BOUNDARY_NODE_VMS_DIR] {
+        if let Ok(entries) = fs::read_dir(env.get_path(dir)) {
+            vms.extend(
+                entries
+                    .flatten()
+                    .filter(|entry| entry.path().is_dir())
+                    .map(|entry| entry.file_name().to_string_lossy().to_string()),
+            );
+        }
+    }
+    vms
+}
+
+/// Applies `f` to all VMs of `snapshot` in parallel.
+fn for_each_vm<F>(env: &TestEnv, snapshot: &TestnetSnapshot, f: F) -> Result<()>
+where
+    F: Fn(&Farm, &str, &str) -> FarmResult<()> + Sync,
+{
+    let farm = &Farm::new(env.get_farm_url()?, env.logger());
+    let group_name = &GroupSetup::read_attribute(env).farm_group_name;
+    let f = &f;
+    let results: Vec<(&String, _)> = thread::scope(|s| {
+        let handles: Vec<_> = snapshot
+            .vms
+            .iter()
+            .map(|vm_name| (vm_name, s.spawn(move || f(farm, group_name, vm_name))))
+            .collect();
+        handles
+            .into_iter()
+            .map(|(vm_name, handle)| (vm_name, handle.join().expect("thread panicked")))
+            .collect()
+    });
+    let mut result = Ok(());
+    for (vm_name, res) in results {
+        if let Err(e) = res {
+            warn!(
+                env.logger(),
+                "Snapshot {} of VM {} failed: {:?}", snapshot.name, vm_name, e
+            );
+            result = Err(anyhow!(
+                "failed to process snapshot {} of VM {}: {:?}",
+                snapshot.name,
+                vm_name,
+                e
+            ));
+        }
+    }
+    result
+}
@@ -1,3 +1,4 @@
+use crate::driver::console_log::stream_console_log;
 use crate::driver::driver_setup::SSH_AUTHORIZED_PUB_KEYS_DIR;
 use crate::driver::farm::id_of_file;
 use crate::driver::farm::ClaimResult;
@@ -51,7 +52,7 @@ pub enum UniversalVmConfig {
     Img(PathBuf),
 }
 
-const UNIVERSAL_VMS_DIR: &str = "universal_vms";
+pub(crate) const UNIVERSAL_VMS_DIR: &str = "universal_vms";
 const CONF_IMG_FNAME: &str = "config_disk.img.zst";
 const CONF_SSH_IMG_FNAME: &str = "config_ssh_disk.img.zst";
 
@@ -183,6 +184,7 @@ impl UniversalVm {
         )?;
 
         farm.start_vm(&pot_setup.farm_group_name, &self.name)?;
+        stream_console_log(env, &farm, &pot_setup.farm_group_name, &self.name);
         Ok(())
     }
 }
@@ -6,7 +6,7 @@ Goal:: Test the robustness of the replica by sending invalid requests to its HTT
 
 end::catalog[] */
 use crate::driver::{
-    test_env::TestEnv,
+    test_env::{HasDefaultRng, TestEnv},
     test_env_api::{GetFirstHealthyNodeSnapshot, HasPublicApiUrl},
 };
 use ic_base_types::CanisterId;
@@ -18,8 +18,6 @@ use rand_chacha::ChaCha8Rng;
 use reqwest::StatusCode;
 use std::time::{Duration, SystemTime};
 
-const RND_SEED: u64 = 42;
-
 const ENDPOINTS: &[&str; 3] = &["call", "query", "read_state"];
 
 pub fn malicious_input_test(env: TestEnv) {
@@ -67,7 +65,7 @@ fn test_invalid_get_requests(env: TestEnv) {
 
 // Endpoints reject garbage payloads.
 fn test_garbage_payload(env: TestEnv) {
-    let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(RND_SEED);
+    let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(env.default_rng_seed());
     let node_url = env.get_first_healthy_node_snapshot().get_public_url();
     let client = reqwest::blocking::Client::new();
 
@@ -91,7 +89,7 @@ fn test_garbage_payload(env: TestEnv) {
 }
 
 fn test_valid_query_followed_by_garbage(env: TestEnv) {
-    let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(RND_SEED);
+    let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(env.default_rng_seed());
     let node_url = env.get_first_healthy_node_snapshot().get_public_url();
     let client = reqwest::blocking::Client::new();
 
@@ -133,7 +131,7 @@ fn test_valid_query_followed_by_garbage(env: TestEnv) {
 }
 
 fn test_valid_update_followed_by_garbage(env: TestEnv) {
-    let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(RND_SEED);
+    let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(env.default_rng_seed());
     let node_url = env.get_first_healthy_node_snapshot().get_public_url();
     let client = reqwest::blocking::Client::new();
 
@@ -20,7 +20,7 @@ Not Covered:: Ledger archives, Timestamps, Mint & Burn, Subaccounts
 
 end::catalog[] */
 
-use crate::driver::test_env::TestEnv;
+use crate::driver::test_env::{HasDefaultRng, TestEnv};
 use crate::driver::test_env_api::{
     HasPublicApiUrl, HasTopologySnapshot, IcNodeContainer, NnsCanisterEnvVars, NnsInstallationExt,
 };
@@ -48,9 +48,6 @@ use icp_ledger::{
 use quickcheck::{Arbitrary, Gen};
 use rand::Rng;
 
-// Seed for a random generator
-const RND_SEED: u64 = 42;
-
 /* Runbook::
 . Setup NNS with a ledger canister tracking test neurons' account
 . upgrade the minting canister to become a funds holder (Motoko canister)
@@ -95,7 +92,7 @@ pub fn test(env: TestEnv) {
         .unwrap();
     let app_agent = app_node.with_default_agent(|agent| async move { agent });
     let app_runtime = runtime_from_url(app_node.get_public_url(), app_node.effective_canister_id());
-    let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(RND_SEED);
+    let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(env.default_rng_seed());
     let plan = create_plan(&mut rng);
     info!(logger, "plan is {:?}", plan);
     block_on(async move {
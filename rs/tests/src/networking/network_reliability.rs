@@ -18,12 +18,13 @@ Runbook::
 end::catalog[] */
 
 use crate::driver::constants::DEVICE_NAME;
-use crate::driver::ic::{AmountOfMemoryKiB, InternetComputer, NrOfVCPUs, Subnet, VmResources};
-use crate::driver::test_env::TestEnv;
+use crate::driver::ic::VmResources;
+use crate::driver::test_env::{HasDefaultRng, TestEnv};
 use crate::driver::test_env_api::{
     HasPublicApiUrl, HasTopologySnapshot, IcNodeContainer, IcNodeSnapshot, NnsInstallationExt,
     SshSession,
 };
+use crate::driver::testnet::Testnet;
 use crate::util::{
     self, agent_observes_canister_module, assert_canister_counter_with_retries, block_on,
 };
@@ -42,8 +43,6 @@ use std::time::{Duration, Instant};
 
 const COUNTER_CANISTER_WAT: &str = "rs/tests/src/counter.wat";
 const CANISTER_METHOD: &str = "write";
-// Seed for random generator
-const RND_SEED: u64 = 42;
 // Size of the payload sent to the counter canister in update("write") call.
 const PAYLOAD_SIZE_BYTES: usize = 1024;
 // Duration of each request is placed into one of two categories - below or above this threshold.
@@ -79,22 +78,10 @@ pub struct Config {
 }
 
 pub fn setup(env: TestEnv, config: Config) {
-    let vm_resources = VmResources {
-        vcpus: Some(NrOfVCPUs::new(8)),
-        memory_kibibytes: Some(AmountOfMemoryKiB::new(50331648)), // 48GiB
-        boot_image_minimal_size_gibibytes: None,
-    };
-    InternetComputer::new()
-        .add_subnet(
-            Subnet::new(SubnetType::System)
-                .with_default_vm_resources(vm_resources)
-                .add_nodes(config.nodes_system_subnet),
-        )
-        .add_subnet(
-            Subnet::new(SubnetType::Application)
-                .with_default_vm_resources(vm_resources)
-                .add_nodes(config.nodes_app_subnet),
-        )
+    Testnet::new()
+        .with_system_subnet(config.nodes_system_subnet)
+        .with_application_subnets(1, config.nodes_app_subnet)
+        .with_default_vm_resources(VmResources::new().with_vcpus(8).with_memory_gibibytes(48))
         .setup_and_start(&env)
         .expect("Failed to setup IC under test.");
 }
@@ -239,7 +226,7 @@ pub fn test(env: TestEnv, config: Config) {
         "At least one node needs to be stressed on each subnet."
     );
     // We stress (modify node's traffic) using random parameters.
-    let rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(RND_SEED);
+    let rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(env.default_rng_seed());
     // Stress function for each node is executed in a separate thread.
     let stress_nns_handles: Vec<_> = subnet_nns
         .nodes()
@@ -18,10 +18,10 @@ Runbook::
 
 end::catalog[] */
 
+use crate::driver::ingress_workload::IngressWorkload;
 use crate::driver::test_env::TestEnv;
 use crate::driver::test_env_api::{HasPublicApiUrl, HasTopologySnapshot, IcNodeContainer};
-use crate::util::block_on;
-use crate::workload::{CallSpec, Request, RoundRobinPlan, Workload};
+use crate::workload::{CallSpec, Request};
 
 use ic_registry_subnet_type::SubnetType;
 
@@ -38,9 +38,6 @@ const PAYLOAD_SIZE_BYTES: usize = 1024;
 const DURATION_THRESHOLD: Duration = Duration::from_secs(2);
 // Ratio of requests with duration < DURATION_THRESHOLD should exceed this parameter.
 const MIN_REQUESTS_RATIO_BELOW_THRESHOLD: f64 = 0.9;
-// Parameters related to workload creation.
-const RESPONSES_COLLECTION_EXTRA_TIMEOUT: Duration = Duration::from_secs(30); // Responses are collected during the workload execution + this extra time, after all requests had been dispatched.
-const REQUESTS_DISPATCH_EXTRA_TIMEOUT: Duration = Duration::from_secs(2); // This param can be slightly tweaked (1-2 sec), if the workload fails to dispatch requests precisely on time.
 
 pub fn log_max_open_files(log: &Logger) {
     let output = Command::new("sh")
@@ -86,37 +83,25 @@ pub fn test(env: TestEnv, rps: usize, runtime: Duration, min_success_ratio: f64)
     let app_canister = app_node.create_and_install_canister_with_arg(COUNTER_CANISTER_WAT, None);
     info!(&log, "Installation of counter canister has succeeded.");
     info!(&log, "Step 3: Instantiate and start a workload using one node of the Application subnet as target.");
-    // Workload sends messages to canister via node agents.
-    // As we talk to a single node, we create one agent, accordingly.
-    let app_agent = app_node.with_default_agent(|agent| async move { agent });
     // Spawn a workload against counter canister.
     let payload: Vec<u8> = vec![0; PAYLOAD_SIZE_BYTES];
-    let plan = RoundRobinPlan::new(vec![Request::Query(CallSpec::new(
-        app_canister,
-        CANISTER_METHOD,
-        payload,
-    ))]);
-    let dispatch_timeout = REQUESTS_DISPATCH_EXTRA_TIMEOUT + runtime.div_f32(50.0);
-    let workload = Workload::new(vec![app_agent], rps, runtime, plan, log.clone())
-        .with_responses_collection_extra_timeout(RESPONSES_COLLECTION_EXTRA_TIMEOUT)
-        .increase_requests_dispatch_timeout(dispatch_timeout)
-        .with_requests_duration_bucket(DURATION_THRESHOLD);
-    let metrics = block_on(async {
-        workload.execute().await.unwrap_or_else(|err| {
-            panic!("Execution of the workload failed, err={:?}", err);
+    let stats = app_node
+        .generate_ingress(rps, runtime, move |_| {
+            Request::Query(CallSpec::new(
+                app_canister,
+                CANISTER_METHOD,
+                payload.clone(),
+            ))
         })
-    });
+        .unwrap_or_else(|err| {
+            panic!("Execution of the workload failed, err={:?}", err);
+        });
     info!(
         &log,
         "Step 4: Collect metrics from the workload and perform assertions ..."
     );
-    let total_requests_count = rps * runtime.as_secs() as usize;
-    let success_calls: usize = total_requests_count - metrics.errors().values().sum::<usize>();
-    let success_ratio: f64 = (success_calls as f64) / total_requests_count as f64;
-    let duration_bucket = metrics
-        .find_request_duration_bucket(DURATION_THRESHOLD)
-        .unwrap();
-    debug!(&log, "Results of the workload execution {:?}", metrics);
+    let success_ratio = stats.success_ratio();
+    debug!(&log, "Results of the workload execution {:?}", stats);
     info!(
         &log,
         "Minimum expected success ratio is {}, actual success ratio is {}.",
@@ -127,12 +112,12 @@ pub fn test(env: TestEnv, rps: usize, runtime: Duration, min_success_ratio: f64)
         success_ratio > min_success_ratio,
         "Too many requests have failed."
     );
+    let ratio_below_threshold = stats.ratio_below(DURATION_THRESHOLD);
     info!(
         &log,
-        "Requests below {} sec:\nRequests_count = {}\nRequests_ratio = {}",
+        "Requests below {} sec:\nRequests_ratio = {}",
         DURATION_THRESHOLD.as_secs(),
-        duration_bucket.requests_count_below_threshold(),
-        duration_bucket.requests_ratio_below_threshold(),
+        ratio_below_threshold,
     );
-    assert!(duration_bucket.requests_ratio_below_threshold() > MIN_REQUESTS_RATIO_BELOW_THRESHOLD);
+    assert!(ratio_below_threshold > MIN_REQUESTS_RATIO_BELOW_THRESHOLD);
 }
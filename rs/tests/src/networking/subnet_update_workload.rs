@@ -24,12 +24,13 @@ end::catalog[] */
 use crate::{
     driver::{
         boundary_node::{BoundaryNode, BoundaryNodeVm},
-        ic::{ImageSizeGiB, InternetComputer, NrOfVCPUs, Subnet, VmResources},
+        ic::{ImageSizeGiB, InternetComputer, Subnet, VmResources},
         prometheus_vm::{HasPrometheus, PrometheusVm},
+        ssh_exec::{CommandOutput, SshCommand, SshExec},
         test_env::TestEnv,
         test_env_api::{
             retry_async, HasPublicApiUrl, HasTopologySnapshot, HasVmName, IcNodeContainer,
-            NnsInstallationExt, RetrieveIpv4Addr, SshSession, SubnetSnapshot, READY_WAIT_TIMEOUT,
+            NnsInstallationExt, RetrieveIpv4Addr, SubnetSnapshot, READY_WAIT_TIMEOUT,
             RETRY_BACKOFF,
         },
     },
@@ -37,9 +38,9 @@ use crate::{
     workload::{CallSpec, Metrics, Request, RoundRobinPlan, Workload},
 };
 
-use std::{io::Read, thread::JoinHandle, time::Duration};
+use std::{thread::JoinHandle, time::Duration};
 
-use anyhow::{bail, Context, Error};
+use anyhow::{bail, Context};
 use ic_agent::{export::Principal, Agent};
 use ic_interfaces_registry::RegistryValue;
 use ic_prep_lib::subnet_configuration::constants;
@@ -65,18 +66,6 @@ const CANISTER_READ_RETRY_WAIT: Duration = Duration::from_secs(10);
 const RESPONSES_COLLECTION_EXTRA_TIMEOUT: Duration = Duration::from_secs(30); // Responses are collected during the workload execution + this extra time, after all requests had been dispatched.
 const REQUESTS_DISPATCH_EXTRA_TIMEOUT: Duration = Duration::from_secs(2); // This param can be slightly tweaked (1-2 sec), if the workload fails to dispatch requests precisely on time.
 
-fn exec_ssh_command(vm: &dyn SshSession, command: &str) -> Result<(String, i32), Error> {
-    let mut channel = vm.block_on_ssh_session()?.channel_session()?;
-
-    channel.exec(command)?;
-
-    let mut output = String::new();
-    channel.read_to_string(&mut output)?;
-    channel.wait_close()?;
-
-    Ok((output, channel.exit_status()?))
-}
-
 // Create an IC with two subnets, with variable number of nodes and boundary nodes
 // Install NNS canister on system subnet
 pub fn config(
@@ -91,9 +80,8 @@ pub fn config(
         .start(&env)
         .expect("failed to start prometheus VM");
     let vm_resources = VmResources {
-        vcpus: Some(NrOfVCPUs::new(4)),
-        memory_kibibytes: None,
         boot_image_minimal_size_gibibytes,
+        ..VmResources::new().with_vcpus(4)
     };
     InternetComputer::new()
         .add_subnet(
@@ -178,8 +166,13 @@ pub fn config(
         let sleep_command =
             format!("while grep -q '// PLACEHOLDER' {routes_path}; do sleep 5; done");
 
-        let (cmd_output, exit_status) =
-            exec_ssh_command(&boundary_node_vm, &sleep_command).unwrap();
+        let CommandOutput {
+            stdout: cmd_output,
+            exit_status,
+            ..
+        } = boundary_node_vm
+            .ssh_exec(&SshCommand::new(&sleep_command))
+            .unwrap();
 
         info!(
             logger,
@@ -26,7 +26,7 @@ NotCovered:
 
 end::catalog[] */
 
-use crate::driver::test_env::TestEnv;
+use crate::driver::test_env::{HasDefaultRng, TestEnv};
 use crate::driver::test_env_api::{
     HasPublicApiUrl, HasTopologySnapshot, IcNodeContainer, NnsInstallationExt,
 };
@@ -58,9 +58,6 @@ use rand_chacha::ChaCha8Rng;
 use slog::info;
 use std::collections::HashSet;
 
-// Seed for a random generator
-const RND_SEED: u64 = 42;
-
 pub fn config(env: TestEnv) {
     InternetComputer::new()
         .add_fast_single_node_subnet(SubnetType::System)
@@ -83,6 +80,7 @@ pub fn test(env: TestEnv) {
         .install_mainnet_nns_canisters()
         .expect("Could not install NNS canisters");
     info!(logger, "NNS canisters installed successfully.");
+    let seed = env.default_rng_seed();
     block_on(async move {
         let governance = Canister::new(&nns, GOVERNANCE_CANISTER_ID);
         let valid_topic = Topic::ParticipantManagement as i32;
@@ -126,7 +124,7 @@ pub fn test(env: TestEnv) {
         // Note: the chances to hit a real topic is minuscule with a random
         //       number from the below range. At least we don't use magics.
         // expect reject
-        let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(RND_SEED);
+        let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(seed);
         let invalid_topic = rng.gen_range(i32::MAX - 10000..i32::MAX);
         let reject = setup_following(&logger, &governance, n2, n1, invalid_topic)
             .await
@@ -38,7 +38,7 @@ end::catalog[] */
 use rand_chacha::ChaCha8Rng;
 use slog::info;
 
-use crate::driver::test_env::TestEnv;
+use crate::driver::test_env::{HasDefaultRng, TestEnv};
 use crate::driver::test_env_api::{
     HasPublicApiUrl, HasTopologySnapshot, IcNodeContainer, NnsInstallationExt,
 };
@@ -69,9 +69,6 @@ use ic_nns_test_utils::governance::submit_external_update_proposal_allowing_erro
 use ic_registry_subnet_type::SubnetType;
 use rand::Rng;
 
-// Seed for a random generator
-const RND_SEED: u64 = 42;
-
 /// A test runs within a given IC configuration. Later on, we really want to
 /// combine tests that are being run in similar environments. Please, keep this
 /// in mind when writing your tests!
@@ -129,7 +126,7 @@ pub fn test(env: TestEnv) {
 
         // Observe a reject for "forward_vote" when called from
         // a principal not being owner, here the test identity.
-        let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(RND_SEED);
+        let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(env.default_rng_seed());
         let fake_proposal_id = ProposalId(rng.gen());
         let vote_reject = forward_vote(
             &governance,
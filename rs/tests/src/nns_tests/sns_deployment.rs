@@ -292,6 +292,7 @@ fn setup(env: TestEnv, sale_participants: Vec<SaleParticipant>) {
     }
     let nns_customizations = NnsCustomizations {
         ledger_balances: Some(ledger_balances),
+        ..Default::default()
     };
 
     // Install NNS with ledger customizations
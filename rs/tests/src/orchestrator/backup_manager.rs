@@ -35,10 +35,7 @@ use crate::{
             generate_key_strings, get_updatesubnetpayload_with_keys, update_subnet_record,
             wait_until_authentication_is_granted, AuthMean,
         },
-        upgrade::{
-            assert_assigned_replica_version, bless_public_replica_version,
-            get_assigned_replica_version, update_subnet_replica_version, UpdateImageType,
-        },
+        upgrade::{get_assigned_replica_version, public_release_package, upgrade_subnet},
     },
     util::{block_on, get_nns_node},
 };
@@ -215,25 +212,16 @@ pub fn test(env: TestEnv) {
         .expect("Failed to start backup process");
     info!(log, "Started process: {}", child.id());
 
-    info!(log, "Elect the mainnet replica version");
+    info!(log, "Upgrade the subnet to the mainnet replica version");
     info!(log, "TARGET_VERSION: {}", mainnet_version);
-    block_on(bless_public_replica_version(
-        &nns_node,
-        &mainnet_version,
-        UpdateImageType::Image,
-        UpdateImageType::Image,
-        &log,
-    ));
-
-    info!(log, "Proposal to upgrade the subnet replica version");
-    block_on(update_subnet_replica_version(
+    let release_package = block_on(public_release_package(&mainnet_version, &log));
+    upgrade_subnet(
         &nns_node,
+        &env.topology_snapshot().root_subnet(),
         &ReplicaVersion::try_from(mainnet_version.clone()).expect("bad TARGET_VERSION string"),
-        subnet_id,
-    ));
-
-    info!(log, "Wait until the upgrade happens");
-    assert_assigned_replica_version(&nns_node, &mainnet_version, env.logger());
+        Some(&release_package),
+        &log,
+    );
 
     let checkpoint_dir = backup_dir
         .join("data")
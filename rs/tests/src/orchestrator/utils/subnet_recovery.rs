@@ -13,7 +13,11 @@ use crate::tecdsa::tecdsa_signature_test::{
 };
 use crate::util::*;
 use crate::{
-    driver::{test_env::TestEnv, test_env_api::*},
+    driver::{
+        node_logs::{LogQuery, NodeLogs},
+        test_env::TestEnv,
+        test_env_api::*,
+    },
     orchestrator::utils::rw_message::{can_read_msg, cannot_store_msg},
     util::runtime_from_url,
 };
@@ -30,7 +34,6 @@ use ic_types::ReplicaVersion;
 use k256::ecdsa::VerifyingKey;
 use registry_canister::mutations::do_create_subnet::EcdsaKeyRequest;
 use registry_canister::mutations::do_update_subnet::UpdateSubnetPayload;
-use serde::{Deserialize, Serialize};
 use slog::{info, Logger};
 use url::Url;
 
@@ -78,39 +81,15 @@ pub(crate) fn halt_subnet(
     recovery: &Recovery,
     logger: &Logger,
 ) {
-    #[derive(Debug, Deserialize, Serialize)]
-    struct Cursor {
-        #[serde(alias = "__CURSOR")]
-        cursor: String,
-    }
-
     info!(logger, "Breaking the app subnet by halting it",);
-    let s = app_node.block_on_ssh_session().unwrap();
-    let message_str = execute_bash_command(
-        &s,
-        "journalctl -n1 -o json --output-fields='__CURSOR'".to_string(),
-    )
-    .expect("journal message");
-    let message: Cursor = serde_json::from_str(&message_str).expect("JSON journal message");
+    let cursor = app_node.journal_cursor().expect("journal cursor");
     recovery
         .halt_subnet(subnet_id, true, &[])
         .exec()
         .expect("Failed to halt subnet.");
-    retry(logger.clone(), secs(120), secs(10), || {
-        let res = execute_bash_command(
-            &s,
-            format!(
-                "journalctl --after-cursor='{}' | grep -c 'is halted'",
-                message.cursor
-            ),
-        );
-        if res.map_or(false, |r| r.trim().parse::<i32>().unwrap() > 0) {
-            Ok(())
-        } else {
-            bail!("Did not find log entry that consensus is halted.")
-        }
-    })
-    .expect("Failed to detect broken subnet.");
+    app_node
+        .await_log_lines(&LogQuery::new("is halted").after(&cursor), secs(120))
+        .expect("Failed to detect broken subnet.");
 }
 
 /// A subnet is considered to be broken if it still works in read mode,
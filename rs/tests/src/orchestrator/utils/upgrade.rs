@@ -4,7 +4,7 @@ use crate::{
         get_governance_canister, submit_update_elected_replica_versions_proposal,
         submit_update_subnet_replica_version_proposal, vote_execute_proposal_assert_executed,
     },
-    util::runtime_from_url,
+    util::{block_on, runtime_from_url, MetricsFetcher},
 };
 use anyhow::{bail, Result};
 use ic_canister_client::Sender;
@@ -345,3 +345,124 @@ pub(crate) async fn update_subnet_replica_version(
     .await;
     vote_execute_proposal_assert_executed(&governance_canister, proposal_id).await;
 }
+
+/// The release package of a replica version that is not blessed yet.
+#[derive(Clone, Debug)]
+pub(crate) struct ReleasePackage {
+    pub sha256: String,
+    pub urls: Vec<String>,
+}
+
+/// Returns the release package of the publicly released `version`.
+pub(crate) async fn public_release_package(version: &str, logger: &Logger) -> ReleasePackage {
+    ReleasePackage {
+        sha256: fetch_update_file_sha256_with_retry(logger, version, false).await,
+        urls: vec![get_update_image_url(UpdateImageType::Image, version)],
+    }
+}
+
+/// Upgrades (or downgrades) the subnet `subnet` to `target_version` in one
+/// go: blesses the version with `release_package` if it is not blessed yet,
+/// proposes to update the replica version of the subnet, waits until all nodes
+/// of the subnet run the new version and asserts that their replicas report
+/// it in their version metric.
+///
+/// Panics if any step fails.
+pub(crate) fn upgrade_subnet(
+    nns_node: &IcNodeSnapshot,
+    subnet: &SubnetSnapshot,
+    target_version: &ReplicaVersion,
+    release_package: Option<&ReleasePackage>,
+    logger: &Logger,
+) {
+    if let Some(release_package) = release_package {
+        block_on(bless_replica_version_if_needed(
+            nns_node,
+            target_version,
+            release_package,
+            logger,
+        ));
+    }
+    info!(
+        logger,
+        "Upgrading subnet {} to {}", subnet.subnet_id, target_version
+    );
+    block_on(update_subnet_replica_version(
+        nns_node,
+        target_version,
+        subnet.subnet_id,
+    ));
+    for node in subnet.nodes() {
+        assert_assigned_replica_version(&node, target_version.as_ref(), logger.clone());
+    }
+    block_on(assert_version_metric(
+        subnet.nodes(),
+        target_version,
+        logger,
+    ));
+    info!(
+        logger,
+        "Successfully upgraded subnet {} to {}", subnet.subnet_id, target_version
+    );
+}
+
+async fn bless_replica_version_if_needed(
+    nns_node: &IcNodeSnapshot,
+    replica_version: &ReplicaVersion,
+    release_package: &ReleasePackage,
+    logger: &Logger,
+) {
+    let registry_canister = RegistryCanister::new(vec![nns_node.get_public_url()]);
+    let blessed_versions = get_blessed_replica_versions(&registry_canister).await;
+    if blessed_versions
+        .blessed_version_ids
+        .contains(&replica_version.to_string())
+    {
+        info!(
+            logger,
+            "Replica version {} is blessed already", replica_version
+        );
+        return;
+    }
+    bless_replica_version_with_sha(
+        nns_node,
+        replica_version.as_ref(),
+        UpdateImageType::Image,
+        logger,
+        &release_package.sha256,
+        release_package.urls.clone(),
+    )
+    .await;
+}
+
+/// Asserts that the replicas of all `nodes` report `replica_version` as their
+/// active version in the `ic_replica_info` metric.
+pub(crate) async fn assert_version_metric(
+    nodes: impl Iterator<Item = IcNodeSnapshot>,
+    replica_version: &ReplicaVersion,
+    logger: &Logger,
+) {
+    let nodes: Vec<_> = nodes.collect();
+    let label = format!("ic_active_version=\"{}\"", replica_version);
+    let fetcher = MetricsFetcher::new(nodes.iter().cloned(), vec!["ic_replica_info".to_string()]);
+    retry_async(logger, READY_WAIT_TIMEOUT, RETRY_BACKOFF, || async {
+        let metrics = fetcher.fetch().await?;
+        let reporting = metrics
+            .iter()
+            .filter(|(metric, _)| metric.contains(&label))
+            .map(|(_, values)| values.len())
+            .sum::<usize>();
+        if reporting != nodes.len() {
+            bail!(
+                "Only {} of {} nodes report version {}: {:?}",
+                reporting,
+                nodes.len(),
+                replica_version,
+                metrics
+            );
+        }
+        Ok(())
+    })
+    .await
+    .expect("Nodes do not report the expected version in their metrics");
+}
@@ -17,7 +17,7 @@ Success::
 end::catalog[] */
 
 use crate::driver::ic::{InternetComputer, Subnet};
-use crate::driver::test_env::TestEnv;
+use crate::driver::test_env::{HasDefaultRng, TestEnv};
 use crate::driver::test_env_api::{
     HasPublicApiUrl, HasTopologySnapshot, HasVm, IcNodeContainer, NnsInstallationExt,
 };
@@ -42,8 +42,6 @@ use super::tecdsa_signature_test::DKG_INTERVAL;
 
 const NODES_COUNT: usize = 4;
 const REMOVE_NODES_COUNT: usize = (NODES_COUNT / 3) + 1;
-// Seed for a random generator
-const RND_SEED: u64 = 42;
 
 pub fn config(env: TestEnv) {
     InternetComputer::new()
@@ -66,7 +64,7 @@ pub fn test(env: TestEnv) {
     let nns_subnet = env.topology_snapshot().root_subnet();
     let mut nns_nodes: Vec<_> = nns_subnet.nodes().collect();
     let (nns_node, nns_nodes_to_remove) = {
-        let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(RND_SEED);
+        let mut rng: ChaCha8Rng = rand::SeedableRng::seed_from_u64(env.default_rng_seed());
         nns_nodes.shuffle(&mut rng);
         (&nns_nodes[0], &nns_nodes[1..REMOVE_NODES_COUNT + 1])
     };
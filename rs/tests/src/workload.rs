@@ -1,4 +1,4 @@
-use ic_agent::{export::Principal, Agent};
+use ic_agent::{export::Principal, Agent, AgentError};
 use slog::info;
 use std::collections::HashMap;
 use std::fmt::Display;
@@ -450,31 +450,9 @@ impl<T: Plan> Workload<T> {
 /// * `sender` - a producer, which submits the result of request execution to a channel.
 async fn execute_request(request: Request, agent: Agent, sender: Sender<RequestResult>) {
     let start = Instant::now();
-    let call_status = {
-        let request_result = match request {
-            Request::UpdateE2e(spec) => agent
-                .update(&spec.canister_id, spec.method_name)
-                .with_arg(spec.payload)
-                .call_and_wait(delay())
-                .await
-                .map(|_| ()),
-            Request::Update(spec) => agent
-                .update(&spec.canister_id, spec.method_name)
-                .with_arg(spec.payload)
-                .call()
-                .await
-                .map(|_| ()),
-            Request::Query(spec) => agent
-                .query(&spec.canister_id, spec.method_name)
-                .with_arg(spec.payload)
-                .call()
-                .await
-                .map(|_| ()),
-        };
-        match request_result {
-            Ok(_) => CallStatus::Success,
-            Err(err) => CallStatus::Failure(err.to_string()),
-        }
+    let call_status = match call(request, &agent).await {
+        Ok(_) => CallStatus::Success,
+        Err(err) => CallStatus::Failure(err.to_string()),
     };
     let duration = start.elapsed();
     sender
@@ -486,6 +464,31 @@ async fn execute_request(request: Request, agent: Agent, sender: Sender<RequestR
         .expect("Sending request's result to the channel has failed.");
 }
 
+/// Submits the method call defined by `request` via `agent` and awaits its
+/// outcome as far as the kind of the request requires.
+pub(crate) async fn call(request: Request, agent: &Agent) -> Result<(), AgentError> {
+    match request {
+        Request::UpdateE2e(spec) => agent
+            .update(&spec.canister_id, spec.method_name)
+            .with_arg(spec.payload)
+            .call_and_wait(delay())
+            .await
+            .map(|_| ()),
+        Request::Update(spec) => agent
+            .update(&spec.canister_id, spec.method_name)
+            .with_arg(spec.payload)
+            .call()
+            .await
+            .map(|_| ()),
+        Request::Query(spec) => agent
+            .query(&spec.canister_id, spec.method_name)
+            .with_arg(spec.payload)
+            .call()
+            .await
+            .map(|_| ()),
+    }
+}
+
 /// A collector, implementing a very simple post-processing/aggregation of the executed requests.
 async fn collect_results(
     log: slog::Logger,
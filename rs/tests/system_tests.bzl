@@ -63,7 +63,7 @@ run_system_test = rule(
     },
 )
 
-def system_test(name, runtime_deps = [], tags = [], test_timeout = "long", flaky = True, **kwargs):
+def system_test(name, runtime_deps = [], tags = [], test_timeout = "long", flaky = True, colocate = False, shard_count = None, **kwargs):
     """Declares a system-test.
 
     Args:
@@ -72,6 +72,8 @@ def system_test(name, runtime_deps = [], tags = [], test_timeout = "long", flaky
       tags: additional tags for the system_test.
       test_timeout: bazel test timeout (short, moderate, long or eternal).
       flaky: rerun in case of failure (up to 3 times).
+      colocate: make the test runnable with --colocate, i.e., on a universal VM on Farm.
+      shard_count: split the tests of the group across this many shards, each with its own setup.
       **kwargs: additional arguments to pass to the rust_binary rule.
     """
 
@@ -121,14 +123,27 @@ def system_test(name, runtime_deps = [], tags = [], test_timeout = "long", flaky
         tags = ["manual"],  # this target will be built if required as a dependency of another target
     )
 
+    env = {}
+    test_runtime_deps = runtime_deps
+    if colocate:
+        # The test binary passes these on to the universal VM running it colocated.
+        env = {
+            "COLOCATED_TEST_BIN": native.package_name() + "/" + bin_name,
+            "COLOCATED_TEST_IMAGE": "bazel/" + native.package_name() + ":" + container_name,
+            "COLOCATED_UVM_CONFIG_IMAGE": native.package_name() + "/" + uvm_config_image_name + ".zst",
+        }
+        test_runtime_deps = runtime_deps + [":" + uvm_config_image_name]
+
     run_system_test(
         name = name,
         src = bin_name,
-        runtime_deps = runtime_deps,
+        env = env,
+        runtime_deps = test_runtime_deps,
         tags = tags + ["requires-network", "system_test"],
         timeout = test_timeout,
         # TODO: set flaky = False by default when PFOPS-3148 is resolved
         flaky = flaky,
+        shard_count = shard_count,
     )
 
 def _symlink_dir(ctx):
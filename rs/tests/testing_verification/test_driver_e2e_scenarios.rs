@@ -66,6 +66,38 @@ fn get_all_e2e_test_scenarios() -> HashMap<String, SystemTestGroup> {
                 .with_timeout_per_test(Duration::from_secs(10))
                 .without_farm(),
         ),
+        (
+            "test_that_runs_out_of_its_own_time".to_string(),
+            SystemTestGroup::new()
+                .with_setup(setup_to_succeed)
+                .add_parallel(
+                    SystemTestSubGroup::new()
+                        .add_test(systest!(never_ending_task, Duration::from_secs(3)))
+                        .add_test(systest!(test_to_fail_5sec)),
+                )
+                .with_timeout_per_test(Duration::from_secs(60))
+                .without_farm(),
+        ),
+        (
+            "test_with_retries".to_string(),
+            SystemTestGroup::new()
+                .with_setup(setup_to_succeed)
+                .add_parallel(
+                    SystemTestSubGroup::new()
+                        .add_test(systest!(test_to_fail_once))
+                        .add_test(systest!(test_to_fail)),
+                )
+                .with_retries(1)
+                .without_farm(),
+        ),
+        (
+            "test_with_teardown_after_panic".to_string(),
+            SystemTestGroup::new()
+                .with_setup(setup_to_succeed)
+                .add_test(systest!(test_to_fail))
+                .with_teardown(teardown_to_succeed)
+                .without_farm(),
+        ),
         (
             "test_duplicate_tasks".to_string(),
             SystemTestGroup::new()
@@ -178,6 +210,10 @@ fn setup_to_panic(_: TestEnv) {
 
 fn test_to_succeed(_: TestEnv) {}
 
+fn teardown_to_succeed(env: TestEnv) {
+    info!(env.logger(), "Releasing external resources ...");
+}
+
 fn test_to_succeed_7sec(_: TestEnv) {
     std::thread::sleep(Duration::from_secs(7));
 }
@@ -213,6 +249,19 @@ fn test_to_fail_1sec(_: TestEnv) {
     panic!("this test panics after 1 seconds");
 }
 
+fn test_to_fail_once(env: TestEnv) {
+    // The directory containing the test environments outlives retries.
+    let marker = env
+        .base_path()
+        .parent()
+        .unwrap()
+        .join("test_to_fail_once.marker");
+    if !marker.exists() {
+        std::fs::write(&marker, "").unwrap();
+        panic!("this test panics on the first attempt");
+    }
+}
+
 fn test_to_fail(_: TestEnv) {
     panic!("this test panics");
 }
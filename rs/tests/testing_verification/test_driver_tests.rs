@@ -1,5 +1,6 @@
 use ic_tests::driver::{
     report::{SystemTestGroupReportSummary, TestResultSummary},
+    results::{SystemTestGroupResults, TestStatus},
     test_env_api::FarmBaseUrl,
 };
 use std::{
@@ -104,6 +105,85 @@ fn test_scenario_with_test_panic_fails() {
     );
 }
 
+#[test]
+fn test_scenario_with_result_files() {
+    let working_dir = create_unique_working_dir();
+    let json_path = working_dir.join("results.json");
+    let junit_path = working_dir.join("results.xml");
+    let binary_path = env::current_dir().unwrap().join(BINARY_PATH);
+    let mut cmd = Command::new(binary_path);
+    cmd.env("TEST_SCENARIO_NAME", "test_with_panic").args([
+        "--working-dir",
+        working_dir.to_str().unwrap(),
+        "--json-output",
+        json_path.to_str().unwrap(),
+        "--junit-xml-output",
+        junit_path.to_str().unwrap(),
+        "run",
+    ]);
+    let result = cmd.output().expect("failed to execute process");
+    assert!(!result.status.success(), "{:?}", result);
+
+    let results: SystemTestGroupResults =
+        serde_json::from_str(&std::fs::read_to_string(json_path).unwrap()).unwrap();
+    assert_eq!(results.tests.len(), 2);
+    assert_eq!(results.tests[0].name, "setup");
+    assert_eq!(results.tests[0].status, TestStatus::Passed);
+    assert_eq!(results.tests[1].name, "test_to_fail");
+    assert_eq!(results.tests[1].status, TestStatus::Failed);
+    assert_eq!(
+        results.tests[1].message.as_deref(),
+        Some("this test panics")
+    );
+    assert_eq!(
+        results.tests[1].artifacts,
+        vec![working_dir.join("tests").join("test_to_fail")]
+    );
+
+    let junit = std::fs::read_to_string(junit_path).unwrap();
+    assert!(junit.contains(r#"tests="2" failures="1""#), "{}", junit);
+    assert!(junit.contains(r#"<failure message="this test panics" type="panic"/>"#));
+}
+
+#[test]
+fn test_scenario_with_reused_setup() {
+    let export_dir = create_unique_working_dir().join("exported_setup");
+    let binary_path = env::current_dir().unwrap().join(BINARY_PATH);
+    let mut cmd = Command::new(&binary_path);
+    cmd.env("TEST_SCENARIO_NAME", "test_without_errors").args([
+        "--working-dir",
+        create_unique_working_dir().to_str().unwrap(),
+        "--export-setup",
+        export_dir.to_str().unwrap(),
+        "run",
+    ]);
+    let result = cmd.output().expect("failed to execute process");
+    assert!(result.status.success(), "{:?}", result);
+
+    // The panicking setup function of this scenario is not executed.
+    let mut cmd = Command::new(&binary_path);
+    cmd.env("TEST_SCENARIO_NAME", "test_with_setup_panic")
+        .args([
+            "--working-dir",
+            create_unique_working_dir().to_str().unwrap(),
+            "--reuse-setup",
+            export_dir.to_str().unwrap(),
+            "run",
+        ]);
+    let result = cmd.output().expect("failed to execute process");
+    assert!(!result.status.success(), "{:?}", result);
+    let summary = extract_report(result.stderr).expect("Failed to extract report from logs.");
+    assert_test_summary_size(
+        &summary, /* successes */ 1, /* failures */ 1, /* skipped */ 0,
+    );
+    assert_name_and_message_eq(&summary.success[0], "setup", None);
+    assert_name_and_message_eq(
+        &summary.failure[0],
+        "test_to_fail",
+        Some("this test panics"),
+    );
+}
+
 #[test]
 fn test_scenario_with_default_farm_url_succeeds() {
     let working_dir = create_unique_working_dir();
@@ -225,6 +305,34 @@ fn test_scenario_with_two_skipped_panic_tests_succeeds() {
     assert_name_and_message_eq(&summary.skipped[1], "test_to_fail_2", None);
 }
 
+#[test]
+fn test_scenario_with_pattern_filtered_tests_succeeds() {
+    let working_dir = create_unique_working_dir();
+    let scenario_name = "test_with_two_panics";
+    let binary_path = env::current_dir().unwrap().join(BINARY_PATH);
+    let mut cmd = Command::new(binary_path);
+    cmd.env("TEST_SCENARIO_NAME", scenario_name).args([
+        "--working-dir",
+        working_dir.to_str().unwrap(),
+        "--include-pattern",
+        "^test_to_",
+        "--skip-pattern",
+        "fail",
+        "run",
+    ]);
+    let result = cmd.output().expect("failed to execute process");
+    assert!(result.status.success(), "{:?}", result);
+    let mut summary = extract_report(result.stderr).expect("Failed to extract report from logs.");
+    assert_test_summary_size(
+        &summary, /* successes */ 2, /* failures */ 0, /* skipped */ 2,
+    );
+    summary.skipped.sort_by(|t1, t2| t1.name.cmp(&t2.name));
+    assert_name_and_message_eq(&summary.success[0], "setup", None);
+    assert_name_and_message_eq(&summary.success[1], "test_to_succeed", None);
+    assert_name_and_message_eq(&summary.skipped[0], "test_to_fail", None);
+    assert_name_and_message_eq(&summary.skipped[1], "test_to_fail_2", None);
+}
+
 #[test]
 fn test_scenario_with_setup_panic_fails() {
     let result = execute_test_scenario_with_default_cmd("test_with_setup_panic");
@@ -253,6 +361,61 @@ fn test_that_runs_out_of_time() {
     assert_name_and_message_eq(&summary.failure[0], "never_ending_task", None);
 }
 
+#[test]
+fn test_that_runs_out_of_its_own_time() {
+    let result = execute_test_scenario_with_default_cmd("test_that_runs_out_of_its_own_time");
+    assert!(!result.status.success(), "{:?}", result);
+    let mut summary = extract_report(result.stderr).expect("Failed to extract report from logs.");
+    assert_test_summary_size(
+        &summary, /* successes */ 1, /* failures */ 2, /* skipped */ 0,
+    );
+    summary.failure.sort_by(|t1, t2| t1.name.cmp(&t2.name));
+    assert_name_and_message_eq(&summary.success[0], "setup", None);
+    // The timed out task has no message, unlike the one that panics after the timeout fired.
+    assert_name_and_message_eq(&summary.failure[0], "never_ending_task", None);
+    assert_name_and_message_eq(
+        &summary.failure[1],
+        "test_to_fail_5sec",
+        Some("this `test_to_fail` panics after 5 seconds"),
+    );
+}
+
+#[test]
+fn test_with_retries() {
+    let result = execute_test_scenario_with_default_cmd("test_with_retries");
+    assert!(!result.status.success(), "{:?}", result);
+    let summary = extract_report(result.stderr).expect("Failed to extract report from logs.");
+    assert_test_summary_size(
+        &summary, /* successes */ 2, /* failures */ 1, /* skipped */ 0,
+    );
+    assert_name_and_message_eq(&summary.success[0], "setup", None);
+    assert_eq!(summary.success[0].retries, 0);
+    assert_name_and_message_eq(&summary.success[1], "test_to_fail_once", None);
+    assert_eq!(summary.success[1].retries, 1);
+    assert_name_and_message_eq(
+        &summary.failure[0],
+        "test_to_fail",
+        Some("this test panics"),
+    );
+    assert_eq!(summary.failure[0].retries, 1);
+}
+
+#[test]
+fn test_teardown_runs_after_panic() {
+    let result = execute_test_scenario_with_default_cmd("test_with_teardown_after_panic");
+    assert!(!result.status.success(), "{:?}", result);
+    let summary =
+        extract_report(result.stderr.clone()).expect("Failed to extract report from logs.");
+    assert_name_and_message_eq(
+        &summary.failure[0],
+        "test_to_fail",
+        Some("this test panics"),
+    );
+    let log = String::from_utf8(result.stderr).unwrap();
+    assert!(log.contains("Releasing external resources ..."), "{}", log);
+    assert!(log.contains("Teardown finished"), "{}", log);
+}
+
 #[test]
 fn test_duplicate_tasks() {
     let result = execute_test_scenario_with_default_cmd("test_duplicate_tasks");
@@ -440,6 +440,7 @@ impl TransportImpl {
                 stream,
                 allowed_clients,
                 latest_registry_version,
+                vec![],
             ),
         )
         .await
@@ -477,8 +478,12 @@ impl TransportImpl {
         let earliest_registry_version = *self.earliest_registry_version.read().await;
         let tls_stream = match tokio::time::timeout(
             Duration::from_secs(TLS_HANDSHAKE_TIMEOUT_SECONDS),
-            self.crypto
-                .perform_tls_client_handshake(stream, peer_id, latest_registry_version),
+            self.crypto.perform_tls_client_handshake(
+                stream,
+                peer_id,
+                latest_registry_version,
+                vec![],
+            ),
         )
         .await
         {
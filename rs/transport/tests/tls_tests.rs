@@ -45,7 +45,8 @@ fn test_single_transient_failure_of_tls_client_handshake_impl(use_h2: bool) {
                     .returning({
                         move |_tcp_stream: TcpStream,
                               _server: NodeId,
-                              _registry_version: RegistryVersion| {
+                              _registry_version: RegistryVersion,
+                              _alpn_protocols: Vec<Vec<u8>>| {
                             Err(TlsClientHandshakeError::HandshakeError {
                                 internal_error: "transient".to_string(),
                             })
@@ -58,7 +59,8 @@ fn test_single_transient_failure_of_tls_client_handshake_impl(use_h2: bool) {
                     .returning(
                         move |tcp_stream: TcpStream,
                               server: NodeId,
-                              registry_version: RegistryVersion| {
+                              registry_version: RegistryVersion,
+                              alpn_protocols: Vec<Vec<u8>>| {
                             let rt_handle = rt_handle.clone();
                             let crypto = crypto.clone();
                             #[allow(clippy::disallowed_methods)]
@@ -71,6 +73,7 @@ fn test_single_transient_failure_of_tls_client_handshake_impl(use_h2: bool) {
                                             tcp_stream,
                                             server,
                                             registry_version,
+                                            alpn_protocols,
                                         )
                                         .await
                                 })
@@ -182,7 +185,8 @@ fn test_single_transient_failure_of_tls_server_handshake_impl(use_h2: bool) {
                     .returning({
                         move |_tcp_stream: TcpStream,
                               _allowed_clients: AllowedClients,
-                              _registry_version: RegistryVersion| {
+                              _registry_version: RegistryVersion,
+                              _alpn_protocols: Vec<Vec<u8>>| {
                             Err(TlsServerHandshakeError::HandshakeError {
                                 internal_error: "transient".to_string(),
                             })
@@ -195,7 +199,8 @@ fn test_single_transient_failure_of_tls_server_handshake_impl(use_h2: bool) {
                     .returning(
                         move |tcp_stream: TcpStream,
                               allowed_clients: AllowedClients,
-                              registry_version: RegistryVersion| {
+                              registry_version: RegistryVersion,
+                              alpn_protocols: Vec<Vec<u8>>| {
                             let rt_handle = rt_handle.clone();
                             let crypto = crypto.clone();
                             #[allow(clippy::disallowed_methods)]
@@ -208,6 +213,7 @@ fn test_single_transient_failure_of_tls_server_handshake_impl(use_h2: bool) {
                                             tcp_stream,
                                             allowed_clients,
                                             registry_version,
+                                            alpn_protocols,
                                         )
                                         .await
                                 })
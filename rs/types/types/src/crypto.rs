@@ -4,6 +4,8 @@ pub mod canister_threshold_sig;
 mod hash;
 
 pub use hash::crypto_hash;
+pub use hash::crypto_hash_with_algorithm;
+pub use hash::CryptoHashAlgorithm;
 pub use hash::CryptoHashDomain;
 pub use hash::CryptoHashable;
 pub use hash::CryptoHashableTestDummy;
@@ -15,6 +17,7 @@ pub use sign::{Signable, SignableMock};
 
 pub mod error;
 pub mod threshold_sig;
+pub mod vetkd;
 
 use crate::crypto::threshold_sig::ni_dkg::DkgId;
 use crate::registry::RegistryClientError;
@@ -142,6 +145,7 @@ pub enum AlgorithmId {
     RsaSha256 = 14,
     ThresholdEcdsaSecp256k1 = 15,
     MegaSecp256k1 = 16,
+    RsaPssSha256 = 17,
 }
 
 impl AlgorithmId {
@@ -198,6 +202,7 @@ impl From<i32> for AlgorithmId {
             14 => AlgorithmId::RsaSha256,
             15 => AlgorithmId::ThresholdEcdsaSecp256k1,
             16 => AlgorithmId::MegaSecp256k1,
+            17 => AlgorithmId::RsaPssSha256,
             _ => AlgorithmId::Placeholder,
         }
     }
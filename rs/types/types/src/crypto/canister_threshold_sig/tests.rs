@@ -3,6 +3,7 @@ use crate::crypto::canister_threshold_sig::error::{
     PresignatureQuadrupleCreationError, ThresholdEcdsaSigInputsCreationError,
 };
 use crate::crypto::canister_threshold_sig::idkg::IDkgTranscriptId;
+use crate::crypto::CryptoHashAlgorithm;
 use crate::{Height, NodeId, RegistryVersion, SubnetId};
 use assert_matches::assert_matches;
 use ic_crypto_test_utils_canister_threshold_sigs::set_of_nodes;
@@ -267,6 +268,32 @@ fn should_create_ecdsa_inputs_correctly() {
     assert_eq!(ecdsa_inputs.algorithm_id(), key_transcript.algorithm_id);
 }
 
+#[test]
+fn should_create_ecdsa_inputs_for_keccak256_hashed_message() {
+    let common_receivers = set_of_nodes(&[1, 2, 3]);
+    let (kappa_unmasked, lambda_masked, kappa_times_lambda, key_times_lambda, key_transcript) =
+        transcripts_for_ecdsa_inputs(common_receivers);
+    let quadruple = PreSignatureQuadruple::new(
+        kappa_unmasked,
+        lambda_masked,
+        kappa_times_lambda,
+        key_times_lambda,
+    )
+    .unwrap();
+    let hashed_message = CryptoHashAlgorithm::Keccak256.hash(b"ethereum transaction");
+
+    let ecdsa_inputs = ThresholdEcdsaSigInputs::new(
+        &derivation_path(),
+        &hashed_message,
+        nonce(),
+        quadruple,
+        key_transcript,
+    )
+    .unwrap();
+
+    assert_eq!(ecdsa_inputs.hashed_message(), &hashed_message);
+}
+
 #[test]
 fn should_not_create_ecdsa_inputs_with_inconsistent_algorithm() {
     let common_receivers = set_of_nodes(&[1, 2, 3]);
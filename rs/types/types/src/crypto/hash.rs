@@ -24,7 +24,7 @@ use crate::signature::{
     BasicSignature, MultiSignature, MultiSignatureShare, ThresholdSignature,
     ThresholdSignatureShare,
 };
-use ic_crypto_sha::{DomainSeparationContext, Sha256};
+use ic_crypto_sha::{Context, DomainSeparationContext, Keccak256, Sha256, Sha3_256};
 use std::hash::Hash;
 
 #[cfg(test)]
@@ -502,7 +502,59 @@ impl<T> CryptoHashable for T where T: CryptoHashDomain + Hash {}
 /// versions. Use `Sha256` instead if the algorithm used for producing
 /// the hash must not change across registry/protocol versions.
 pub fn crypto_hash<T: CryptoHashable>(data: &T) -> CryptoHashOf<T> {
-    let mut hash = Sha256::new_with_context(&DomainSeparationContext::new(data.domain()));
-    data.hash(&mut hash);
-    CryptoHashOf::new(CryptoHash(hash.finish().to_vec()))
+    crypto_hash_with_algorithm(data, CryptoHashAlgorithm::Sha256)
+}
+
+/// The hash algorithms of [crypto_hash_with_algorithm], all of which produce
+/// 32-byte digests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CryptoHashAlgorithm {
+    /// SHA-256, which [crypto_hash] currently uses.
+    Sha256,
+    /// SHA3-256.
+    Sha3_256,
+    /// Keccak-256, e.g., for hashes that Ethereum-compatible code verifies.
+    Keccak256,
+}
+
+impl CryptoHashAlgorithm {
+    /// Hashes `data` without domain separation, e.g., to obtain the
+    /// `hashed_message` of [ThresholdEcdsaSigInputs](crate::crypto::canister_threshold_sig::ThresholdEcdsaSigInputs)
+    /// of an Ethereum transaction with [Keccak256](CryptoHashAlgorithm::Keccak256).
+    pub fn hash(&self, data: &[u8]) -> [u8; 32] {
+        match self {
+            CryptoHashAlgorithm::Sha256 => Sha256::hash(data),
+            CryptoHashAlgorithm::Sha3_256 => Sha3_256::hash(data),
+            CryptoHashAlgorithm::Keccak256 => Keccak256::hash(data),
+        }
+    }
+}
+
+/// Creates a (typed) domain-separated cryptographic hash like [crypto_hash],
+/// but with the given `algorithm`, which is guaranteed not to change.
+pub fn crypto_hash_with_algorithm<T: CryptoHashable>(
+    data: &T,
+    algorithm: CryptoHashAlgorithm,
+) -> CryptoHashOf<T> {
+    let context = DomainSeparationContext::new(data.domain());
+    let digest = match algorithm {
+        CryptoHashAlgorithm::Sha256 => {
+            let mut hash = Sha256::new_with_context(&context);
+            data.hash(&mut hash);
+            hash.finish()
+        }
+        CryptoHashAlgorithm::Sha3_256 => {
+            let mut hash = Sha3_256::new();
+            hash.write(context.as_bytes());
+            data.hash(&mut hash);
+            hash.finish()
+        }
+        CryptoHashAlgorithm::Keccak256 => {
+            let mut hash = Keccak256::new();
+            hash.write(context.as_bytes());
+            data.hash(&mut hash);
+            hash.finish()
+        }
+    };
+    CryptoHashOf::new(CryptoHash(digest.to_vec()))
 }
@@ -71,4 +71,54 @@ mod crypto_hash_tests {
             }
         }
     }
+
+    #[test]
+    fn crypto_hash_uses_sha256() {
+        let struct_to_hash = CryptoHashableTestDummy(TEST_INPUT.to_vec());
+
+        assert_eq!(
+            crypto_hash_with_algorithm(&struct_to_hash, CryptoHashAlgorithm::Sha256),
+            crypto_hash(&struct_to_hash)
+        );
+    }
+
+    #[test]
+    fn should_produce_domain_separated_sha3_256_and_keccak256_crypto_hashes() {
+        use ic_crypto_sha::{Context, Keccak256, Sha3_256};
+
+        let struct_to_hash = CryptoHashableTestDummy(TEST_INPUT.to_vec());
+        let context = DomainSeparationContext::new(struct_to_hash.domain());
+        let mut sha3_256 = Sha3_256::new();
+        sha3_256.write(context.as_bytes());
+        struct_to_hash.hash(&mut sha3_256);
+        let mut keccak256 = Keccak256::new();
+        keccak256.write(context.as_bytes());
+        struct_to_hash.hash(&mut keccak256);
+
+        let sha3_256_hash =
+            crypto_hash_with_algorithm(&struct_to_hash, CryptoHashAlgorithm::Sha3_256);
+        let keccak256_hash =
+            crypto_hash_with_algorithm(&struct_to_hash, CryptoHashAlgorithm::Keccak256);
+
+        assert_eq!(sha3_256_hash.get_ref().0, sha3_256.finish().to_vec());
+        assert_eq!(keccak256_hash.get_ref().0, keccak256.finish().to_vec());
+        assert_ne!(sha3_256_hash, keccak256_hash);
+        assert_ne!(sha3_256_hash, crypto_hash(&struct_to_hash));
+    }
+
+    #[test]
+    fn should_hash_bytes_with_the_chosen_algorithm() {
+        assert_eq!(
+            hex::encode(CryptoHashAlgorithm::Sha3_256.hash(b"")),
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+        );
+        assert_eq!(
+            hex::encode(CryptoHashAlgorithm::Keccak256.hash(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            CryptoHashAlgorithm::Sha256.hash(TEST_INPUT),
+            Sha256::hash(TEST_INPUT)
+        );
+    }
 }
@@ -4,7 +4,7 @@ use strum::IntoEnumIterator;
 
 #[test]
 fn should_correctly_convert_i32_to_algorithm_id() {
-    ensure_all_algorithm_ids_are_compared(&(0..=16).collect::<Vec<_>>());
+    ensure_all_algorithm_ids_are_compared(&(0..=17).collect::<Vec<_>>());
 
     assert_eq!(AlgorithmId::from(0), AlgorithmId::Placeholder);
     assert_eq!(AlgorithmId::from(1), AlgorithmId::MultiBls12_381);
@@ -23,6 +23,7 @@ fn should_correctly_convert_i32_to_algorithm_id() {
     assert_eq!(AlgorithmId::from(14), AlgorithmId::RsaSha256);
     assert_eq!(AlgorithmId::from(15), AlgorithmId::ThresholdEcdsaSecp256k1);
     assert_eq!(AlgorithmId::from(16), AlgorithmId::MegaSecp256k1);
+    assert_eq!(AlgorithmId::from(17), AlgorithmId::RsaPssSha256);
 
     // Verify that an unknown i32 maps onto Placeholder
     assert_eq!(AlgorithmId::from(42), AlgorithmId::Placeholder);
@@ -30,7 +31,7 @@ fn should_correctly_convert_i32_to_algorithm_id() {
 
 #[test]
 fn should_correctly_convert_algorithm_id_to_i32() {
-    ensure_all_algorithm_ids_are_compared(&(0..=16).collect::<Vec<_>>());
+    ensure_all_algorithm_ids_are_compared(&(0..=17).collect::<Vec<_>>());
 
     assert_eq!(AlgorithmId::Placeholder as i32, 0);
     assert_eq!(AlgorithmId::MultiBls12_381 as i32, 1);
@@ -48,12 +49,13 @@ fn should_correctly_convert_algorithm_id_to_i32() {
     assert_eq!(AlgorithmId::IcCanisterSignature as i32, 13);
     assert_eq!(AlgorithmId::RsaSha256 as i32, 14);
     assert_eq!(AlgorithmId::ThresholdEcdsaSecp256k1 as i32, 15);
-    assert_eq!(AlgorithmId::MegaSecp256k1 as i32, 16)
+    assert_eq!(AlgorithmId::MegaSecp256k1 as i32, 16);
+    assert_eq!(AlgorithmId::RsaPssSha256 as i32, 17)
 }
 
 #[test]
 fn should_correctly_convert_algorithm_id_to_u8() {
-    ensure_all_algorithm_ids_are_compared(&(0..=16).collect::<Vec<_>>());
+    ensure_all_algorithm_ids_are_compared(&(0..=17).collect::<Vec<_>>());
 
     let tests: Vec<(AlgorithmId, u8)> = vec![
         (AlgorithmId::Placeholder, 0),
@@ -73,6 +75,7 @@ fn should_correctly_convert_algorithm_id_to_u8() {
         (AlgorithmId::RsaSha256, 14),
         (AlgorithmId::ThresholdEcdsaSecp256k1, 15),
         (AlgorithmId::MegaSecp256k1, 16),
+        (AlgorithmId::RsaPssSha256, 17),
     ];
 
     for (algorithm_id, expected_discriminant) in tests {
@@ -134,7 +137,7 @@ pub fn set_of(node_ids: &[NodeId]) -> BTreeSet<NodeId> {
 }
 
 fn ensure_all_algorithm_ids_are_compared(tested_algorithm_ids: &[isize]) {
-    let all_algorithm_ids: Vec<isize> = (0..=16).collect();
+    let all_algorithm_ids: Vec<isize> = (0..=17).collect();
     assert_eq!(tested_algorithm_ids, all_algorithm_ids);
 }
 
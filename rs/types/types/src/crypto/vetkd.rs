@@ -0,0 +1,123 @@
+//! Defines types used for vetKD (verifiable encrypted threshold key
+//! derivation).
+use crate::crypto::canister_threshold_sig::error::impl_display_using_debug;
+use crate::crypto::canister_threshold_sig::ExtendedDerivationPath;
+use crate::crypto::threshold_sig::ni_dkg::DkgId;
+use crate::NodeId;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The inputs for deriving a key and encrypting it to the transport public
+/// key of the recipient.
+///
+/// The key is derived from the threshold key established by the DKG with ID
+/// `dkg_id`.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct VetKdArgs {
+    pub dkg_id: DkgId,
+    pub derivation_path: ExtendedDerivationPath,
+    #[serde(with = "serde_bytes")]
+    pub derivation_id: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    pub encryption_public_key: Vec<u8>,
+}
+
+impl fmt::Debug for VetKdArgs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VetKdArgs")
+            .field("dkg_id", &self.dkg_id)
+            .field("derivation_path", &self.derivation_path)
+            .field("derivation_id", &hex::encode(&self.derivation_id))
+            .field(
+                "encryption_public_key",
+                &hex::encode(&self.encryption_public_key),
+            )
+            .finish()
+    }
+}
+
+/// A node's share of a [`VetKdEncryptedKey`]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct VetKdEncryptedKeyShare {
+    #[serde(with = "serde_bytes")]
+    pub encrypted_key_share: Vec<u8>,
+}
+
+impl fmt::Debug for VetKdEncryptedKeyShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "VetKdEncryptedKeyShare(0x{})",
+            hex::encode(&self.encrypted_key_share)
+        )
+    }
+}
+
+/// A derived key encrypted to the transport public key of its recipient
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct VetKdEncryptedKey {
+    #[serde(with = "serde_bytes")]
+    pub encrypted_key: Vec<u8>,
+}
+
+impl fmt::Debug for VetKdEncryptedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "VetKdEncryptedKey(0x{})",
+            hex::encode(&self.encrypted_key)
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VetKdKeyShareCreationError {
+    ThresholdSigDataNotFound { dkg_id: DkgId },
+    SecretKeyNotFound { dkg_id: DkgId, key_id: String },
+    InvalidArgument(String),
+    InternalError { internal_error: String },
+    TransientInternalError { internal_error: String },
+}
+impl_display_using_debug!(VetKdKeyShareCreationError);
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VetKdKeyShareVerificationError {
+    ThresholdSigDataNotFound { dkg_id: DkgId },
+    SignerNotAllowed { node_id: NodeId },
+    InvalidArgument(String),
+    VerificationError,
+    InternalError { internal_error: String },
+}
+impl_display_using_debug!(VetKdKeyShareVerificationError);
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VetKdKeyShareCombinationError {
+    ThresholdSigDataNotFound {
+        dkg_id: DkgId,
+    },
+    SignerNotAllowed {
+        node_id: NodeId,
+    },
+    InvalidArgument(String),
+    UnsatisfiedReconstructionThreshold {
+        threshold: u32,
+        share_count: usize,
+    },
+    InsufficientValidShares {
+        threshold: u32,
+        valid_share_count: usize,
+    },
+    InternalError {
+        internal_error: String,
+    },
+}
+impl_display_using_debug!(VetKdKeyShareCombinationError);
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VetKdKeyVerificationError {
+    ThresholdSigDataNotFound { dkg_id: DkgId },
+    InvalidArgument(String),
+    VerificationError,
+    InternalError { internal_error: String },
+}
+impl_display_using_debug!(VetKdKeyVerificationError);
@@ -124,6 +124,12 @@ pub enum RegistryDataProviderError {
     Timeout,
     /// Error when using registry transfer
     Transfer { source: String },
+    /// The history of the registry between `version` and `base_version` was
+    /// compacted into a snapshot at `base_version`.
+    VersionCompacted {
+        version: RegistryVersion,
+        base_version: RegistryVersion,
+    },
 }
 
 impl std::error::Error for RegistryDataProviderError {}
@@ -136,6 +142,11 @@ impl fmt::Display for RegistryDataProviderError {
                 f,
                 "Registry transport client failed to fetch registry update from registry canister: {}", source
             ),
+            RegistryDataProviderError::VersionCompacted { version, base_version } => write!(
+                f,
+                "The registry history since version {} was compacted into a snapshot at version {}.",
+                version, base_version
+            ),
         }
     }
 }
@@ -146,6 +157,14 @@ pub enum RegistryClientError {
     #[error("the requested version is not available locally: {version}")]
     VersionNotAvailable { version: RegistryVersion },
 
+    #[error(
+        "the requested version {version} was compacted into the snapshot at version {base_version}"
+    )]
+    VersionCompacted {
+        version: RegistryVersion,
+        base_version: RegistryVersion,
+    },
+
     #[error("failed to query data provider: {source}")]
     DataProviderQueryFailed {
         #[from]
@@ -4,7 +4,7 @@ use ic_crypto::{user_public_key_from_bytes, KeyBytesContentType};
 use ic_interfaces::crypto::IngressSigVerifier;
 use ic_types::crypto::{CanisterSig, CanisterSigOf};
 use ic_types::{
-    crypto::{AlgorithmId, BasicSig, BasicSigOf, CryptoError, UserPublicKey},
+    crypto::{BasicSig, BasicSigOf, CryptoError, UserPublicKey},
     malicious_flags::MaliciousFlags,
     messages::{
         Authentication, Delegation, HasCanisterId, HttpRequest, HttpRequestContent, MessageId,
@@ -313,7 +313,8 @@ fn validate_signature(
 
     match pk_type {
         KeyBytesContentType::EcdsaP256PublicKeyDerWrappedCose
-        | KeyBytesContentType::RsaSha256PublicKeyDerWrappedCose => {
+        | KeyBytesContentType::RsaSha256PublicKeyDerWrappedCose
+        | KeyBytesContentType::RsaPssSha256PublicKeyDerWrappedCose => {
             let webauthn_sig = WebAuthnSignature::try_from(signature.signature.as_slice())
                 .map_err(WebAuthnError)
                 .map_err(InvalidSignature)?;
@@ -324,7 +325,8 @@ fn validate_signature(
         }
         KeyBytesContentType::Ed25519PublicKeyDer
         | KeyBytesContentType::EcdsaP256PublicKeyDer
-        | KeyBytesContentType::EcdsaSecp256k1PublicKeyDer => {
+        | KeyBytesContentType::EcdsaSecp256k1PublicKeyDer
+        | KeyBytesContentType::EcdsaSecp256k1PublicKeySec1 => {
             let basic_sig = BasicSigOf::from(BasicSig(signature.signature.clone()));
             validate_signature_plain(validator, message_id, &basic_sig, &pk)
                 .map_err(InvalidSignature)?;
@@ -338,10 +340,11 @@ fn validate_signature(
                 .map_err(InvalidSignature)?;
             Ok(targets)
         }
-        KeyBytesContentType::RsaSha256PublicKeyDer => {
+        KeyBytesContentType::RsaSha256PublicKeyDer
+        | KeyBytesContentType::RsaPssSha256PublicKeyDer => {
             Err(RequestValidationError::InvalidSignature(
                 AuthenticationError::InvalidBasicSignature(CryptoError::AlgorithmNotSupported {
-                    algorithm: AlgorithmId::RsaSha256,
+                    algorithm: pk.algorithm_id,
                     reason: "RSA signatures are not allowed except in webauthn context".to_owned(),
                 }),
             ))
@@ -418,7 +421,8 @@ fn validate_delegation(
 
     match pk_type {
         KeyBytesContentType::EcdsaP256PublicKeyDerWrappedCose
-        | KeyBytesContentType::RsaSha256PublicKeyDerWrappedCose => {
+        | KeyBytesContentType::RsaSha256PublicKeyDerWrappedCose
+        | KeyBytesContentType::RsaPssSha256PublicKeyDerWrappedCose => {
             let webauthn_sig = WebAuthnSignature::try_from(signature).map_err(WebAuthnError)?;
             validate_webauthn_sig(validator, &webauthn_sig, delegation, &pk)
                 .map_err(WebAuthnError)?;
@@ -426,7 +430,9 @@ fn validate_delegation(
         KeyBytesContentType::Ed25519PublicKeyDer
         | KeyBytesContentType::EcdsaP256PublicKeyDer
         | KeyBytesContentType::EcdsaSecp256k1PublicKeyDer
-        | KeyBytesContentType::RsaSha256PublicKeyDer => {
+        | KeyBytesContentType::EcdsaSecp256k1PublicKeySec1
+        | KeyBytesContentType::RsaSha256PublicKeyDer
+        | KeyBytesContentType::RsaPssSha256PublicKeyDer => {
             let basic_sig = BasicSigOf::from(BasicSig(signature.to_vec()));
             validator
                 .verify_basic_sig_by_public_key(&basic_sig, delegation, &pk)
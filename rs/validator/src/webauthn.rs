@@ -55,12 +55,12 @@ fn basic_sig_from_webauthn_sig(
             ecdsa_p256_signature_from_der_bytes(&webauthn_sig.signature().0)
                 .map_err(|e| format!("Failed to parse EcdsaP256 signature: {}", e))
         }
-        AlgorithmId::RsaSha256 => {
+        AlgorithmId::RsaSha256 | AlgorithmId::RsaPssSha256 => {
             // RSA signatures are not DER wrapped, see https://www.w3.org/TR/webauthn-2/#sctn-signature-attestation-types
             Ok(rsa_signature_from_bytes(&webauthn_sig.signature()))
         }
         _ => Err(format!(
-            "Only ECDSA on curve P-256 and RSA PKCS #1 v1.5 are supported for WebAuthn, given: {:?}",
+            "Only ECDSA on curve P-256, RSA PKCS #1 v1.5 and RSA PSS are supported for WebAuthn, given: {:?}",
             algorithm_id
         ))
     }
@@ -269,6 +269,74 @@ mod tests {
         }
     }
 
+    mod rsa_pss {
+        use super::*;
+
+        /// An RSA public key for PSS signatures (COSE algorithm PS256) in
+        /// COSE format, DER wrapped. The key was generated with OpenSSL and
+        /// encoded in the same way as the keys returned by authenticators.
+        const RSA_PSS_PK_COSE_DER_WRAPPED_HEX: &str = "30820122300c060a2b0601040183b84301010382011000a401030338242059010098194bcfd243773a9701dacad80d895225906826e74f34631a6f214374829bd407918e1a075aa14ba67898eb4d2c6feffb77d78b776ed60073a107d938318bf68289d1118cb555355bf7ecd4c94e8af560cd5069d35a947a454bdc312d228a0b6f749b560759295fa428b6596bd882d1f66b95db9217997a1fb1772ca13abda58178a44d1587eae516dbf0d66f29de377bfab06e174d3007262345b7624308091fcbabe0a9d7ae57a8daec5bc4c2a8876b0fd368269c850d0ae78980212c545be5b1803874d2cde67722d997ce6526a6430d5d3009452e00944e70be032c0bb33cad1d016ad6ba26c74e281514192a2bb2268062678aecb5ebc5061ee16ef3592143010001";
+
+        /// An RSA PSS signature with the secret key corresponding to the above
+        /// public key of the bytes b"hello", in the format returned by
+        /// navigator.credentials.get() with the challenge set to b"hello".
+        const RSA_PSS_WEBAUTHN_SIG_HELLO_HEX: &str = "d9d9f7a37261757468656e74696361746f725f646174615825bfabc37432958b063360d3ad6461c9c4735ae7f8edd46592a5e0f01452b2e4b5050000000170636c69656e745f646174615f6a736f6e584c7b2274797065223a22776562617574686e2e676574222c226368616c6c656e6765223a2261475673624738222c226f726967696e223a2268747470733a2f2f6578616d706c652e6f7267227d697369676e6174757265590100823d0a5aba6f166f85ce7a3973a731aef47b735ddc2091a50d34970e0f6c090dd7276c682b2c264052437a2c42b670e16e185a03caa3b3b6f4cf1bf7fef85c0ab3ff6ed8c1869fddb89883064291446645cb23cb68db07bfc42c7697be3166b44b2fb5b4a2c443a29c3dfb3c3e8757c13e2cbc3fa85e2de52817305752d3e39b69c7f9603dd3e42d13085a16cf799e59dff8a346e33c70d1fd9ac54250b74b65dcd297673e53faf4b5e512cd2c7b6b76b960640cb374c4c9807b1845d8ae8ec3474b93b188bc7618ae3bf70a7e14d0d468d3babe747bb4595bdab2dfaeae0d8442122d51275123095a5267a9150aa6223c67b1fb76beb398d9bdbb3e12d81116";
+
+        #[test]
+        fn should_verify_valid_rsa_pss_signature_on_bytes() {
+            let verifier = temp_crypto_component_with_fake_registry(node_test_id(0));
+            let (pk, sig) = load_pk_and_sig(
+                RSA_PSS_PK_COSE_DER_WRAPPED_HEX.as_ref(),
+                RSA_PSS_WEBAUTHN_SIG_HELLO_HEX.as_bytes(),
+            );
+            assert_eq!(pk.algorithm_id, AlgorithmId::RsaPssSha256);
+            let hello_message = SignableMock {
+                domain: vec![],
+                signed_bytes_without_domain: b"hello".to_vec(),
+            };
+
+            assert_eq!(
+                validate_webauthn_sig(&verifier, &sig, &hello_message, &pk),
+                Ok(())
+            );
+        }
+
+        #[test]
+        fn should_return_error_on_rsa_pss_signature_verified_as_pkcs1_signature() {
+            let verifier = temp_crypto_component_with_fake_registry(node_test_id(0));
+            let (mut pk, sig) = load_pk_and_sig(
+                RSA_PSS_PK_COSE_DER_WRAPPED_HEX.as_ref(),
+                RSA_PSS_WEBAUTHN_SIG_HELLO_HEX.as_bytes(),
+            );
+            pk.algorithm_id = AlgorithmId::RsaSha256;
+
+            let result = validate_webauthn_sig(&verifier, &sig, &SignableMock::new(vec![]), &pk);
+
+            assert!(result
+                .err()
+                .unwrap()
+                .contains("Verifying signature failed."));
+        }
+
+        #[test]
+        fn should_return_error_on_malformed_rsa_pss_signature() {
+            let verifier = temp_crypto_component_with_fake_registry(node_test_id(0));
+            let (pk, sig) = load_pk_and_sig(
+                RSA_PSS_PK_COSE_DER_WRAPPED_HEX.as_ref(),
+                RSA_PSS_WEBAUTHN_SIG_HELLO_HEX.as_bytes(),
+            );
+            let sig = WebAuthnSignature::new(
+                sig.authenticator_data(),
+                sig.client_data_json(),
+                Blob(vec![0, 1, 2]), /* malformed signature */
+            );
+
+            let result = validate_webauthn_sig(&verifier, &sig, &SignableMock::new(vec![]), &pk);
+
+            assert!(result.err().unwrap().contains("Verifying signature failed"));
+        }
+    }
+
     #[test]
     fn should_return_error_if_algorithm_id_is_not_supported() {
         let verifier = temp_crypto_component_with_fake_registry(node_test_id(0));
@@ -283,7 +351,7 @@ mod tests {
         let result = validate_webauthn_sig(&verifier, &sig, &delegation, &pk);
 
         assert!(
-            result.err().unwrap().contains("Only ECDSA on curve P-256 and RSA PKCS #1 v1.5 are supported for WebAuthn, given: Ed25519")
+            result.err().unwrap().contains("Only ECDSA on curve P-256, RSA PKCS #1 v1.5 and RSA PSS are supported for WebAuthn, given: Ed25519")
         );
     }
 
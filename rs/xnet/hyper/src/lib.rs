@@ -218,6 +218,7 @@ impl Accept for TlsAccept {
                                 conn.into_inner(),
                                 AllowedClients::new(SomeOrAllNodes::All).unwrap(),
                                 registry_version,
+                                vec![],
                             )
                             .await
                         };
@@ -387,6 +388,7 @@ impl Service<Uri> for TlsConnector {
                             tcp_stream,
                             xnet_auth.node_id,
                             xnet_auth.registry_version,
+                            vec![],
                         )
                         .await
                         .map_err(box_err)?;